@@ -0,0 +1,381 @@
+//! delivery of collected data over Arrow Flight
+//!
+//! tickets are json objects, either of cli options like those of `cryo_cli::parse_json_args`,
+//! whose chunks are collected and streamed without being written to files, or of the form
+//! `{"job": ID, "datatype": DATATYPE}` to stream the output files of a finished job of
+//! `cryo serve`. a ticket streams a single datatype, which cannot be a group of datatypes such as
+//! `state_diffs`, and can only set the options that jobs of `cryo serve` can set, so that tickets
+//! cannot make the server read or run its files, such as transforms, abi files, or configs
+
+use std::{net::SocketAddr, pin::Pin, sync::Arc};
+
+use arrow2::{
+    datatypes::Schema as ArrowSchema,
+    io::{
+        flight::{default_ipc_fields, serialize_batch, serialize_schema, serialize_schema_to_info},
+        ipc::{write::WriteOptions, IpcField},
+    },
+};
+use arrow_format::flight::{
+    data::{
+        flight_descriptor::DescriptorType, Action, ActionType, Criteria, Empty, FlightData,
+        FlightDescriptor, FlightEndpoint, FlightInfo, HandshakeRequest, HandshakeResponse,
+        PutResult, SchemaResult, Ticket,
+    },
+    service::flight_service_server::{FlightService, FlightServiceServer},
+};
+use cryo_freeze::{collect_chunks, read_output_file, CollectError, Datatype};
+use futures::{channel::mpsc, SinkExt, Stream, StreamExt};
+use polars::prelude::*;
+use std::str::FromStr;
+use tonic::{Request, Response, Status, Streaming};
+
+use crate::{
+    args::Args,
+    parse,
+    serve::{check_server_options, Server},
+};
+
+/// address that `cryo flight` listens on if none is given
+pub(crate) const DEFAULT_FLIGHT_ADDRESS: &str = "127.0.0.1:50051";
+
+type FlightStream<T> = Pin<Box<dyn Stream<Item = Result<T, Status>> + Send + 'static>>;
+
+/// Arrow Flight service collecting tickets, and streaming the files of jobs if run by
+/// `cryo serve`
+pub(crate) struct FlightServer {
+    /// options of the server, the defaults of the options of tickets
+    defaults: Args,
+    jobs: Option<Arc<Server>>,
+}
+
+/// serve Arrow Flight at address until the server fails
+pub(crate) async fn serve_flight(
+    address: &str,
+    defaults: Args,
+    jobs: Option<Arc<Server>>,
+) -> Result<(), CollectError> {
+    let address: SocketAddr = address
+        .parse()
+        .map_err(|_| CollectError::CollectError(format!("invalid address: {}", address)))?;
+    println!("serving arrow flight on grpc://{}", address);
+    tonic::transport::Server::builder()
+        .add_service(FlightServiceServer::new(FlightServer { defaults, jobs }))
+        .serve(address)
+        .await
+        .map_err(|e| CollectError::CollectError(format!("flight server error: {}", e)))
+}
+
+/// run standalone Arrow Flight service collecting tickets, `cryo flight [ADDRESS]`
+pub(crate) async fn run_flight(args: &Args) -> Result<(), CollectError> {
+    let address = args.datatype.get(1).map(String::as_str).unwrap_or(DEFAULT_FLIGHT_ADDRESS);
+    serve_flight(address, args.clone(), None).await
+}
+
+/// contents of a ticket
+enum TicketRequest {
+    Collect(Box<Args>, Datatype),
+    Job(String, Datatype),
+}
+
+// errors are grpc statuses, as returned by the methods of the service
+#[allow(clippy::result_large_err)]
+impl FlightServer {
+    fn parse_ticket(&self, ticket: &[u8]) -> Result<TicketRequest, Status> {
+        let ticket = std::str::from_utf8(ticket)
+            .map_err(|_| Status::invalid_argument("ticket must be a json object"))?;
+        let config: serde_json::Map<String, serde_json::Value> = serde_json::from_str(ticket)
+            .map_err(|e| {
+                Status::invalid_argument(format!("ticket must be a json object: {}", e))
+            })?;
+        if let Some(id) = config.get("job") {
+            let id = id.as_str().ok_or_else(|| Status::invalid_argument("job must be a string"))?;
+            let datatype = config
+                .get("datatype")
+                .and_then(|datatype| datatype.as_str())
+                .ok_or_else(|| Status::invalid_argument("ticket of job must specify datatype"))?;
+            let datatype = Datatype::from_str(datatype)
+                .map_err(|e| Status::invalid_argument(e.to_string()))?;
+            return Ok(TicketRequest::Job(id.to_string(), datatype))
+        }
+        check_server_options(&config).map_err(Status::invalid_argument)?;
+        let args = parse::parse_json_args(ticket, &self.defaults)
+            .map_err(|e| Status::invalid_argument(e.to_string()))?;
+        let datatype = match &args.datatype[..] {
+            [datatype] => Datatype::from_str(datatype).map_err(|_| {
+                Status::invalid_argument(format!(
+                    "ticket must specify a single datatype, not {}",
+                    datatype
+                ))
+            })?,
+            _ => return Err(Status::invalid_argument("ticket must specify a single datatype")),
+        };
+        let args = Args { no_verbose: true, tui: false, dry: false, ..args };
+        Ok(TicketRequest::Collect(Box::new(args), datatype))
+    }
+
+    fn job_server(&self) -> Result<&Arc<Server>, Status> {
+        self.jobs.as_ref().ok_or_else(|| Status::unimplemented("jobs are served by cryo serve"))
+    }
+
+    /// flight of the output files of datatype of job
+    fn job_flight_info(&self, id: &str, datatype: &Datatype) -> Result<FlightInfo, Status> {
+        let files = self.job_server()?.job_files(id, datatype).map_err(Status::not_found)?;
+        let schema = match files.first() {
+            Some(path) => {
+                read_output_file(path, Some(0)).map_err(|e| Status::internal(e.to_string()))?
+            }
+            None => return Err(Status::not_found(format!("no {} files", datatype.name()))),
+        };
+        let schema = serialize_schema_to_info(&schema.schema().to_arrow(), None)
+            .map_err(|e| Status::internal(e.to_string()))?;
+        let ticket = serde_json::json!({ "job": id, "datatype": datatype.name() }).to_string();
+        Ok(FlightInfo {
+            schema,
+            flight_descriptor: Some(FlightDescriptor {
+                r#type: DescriptorType::Cmd as i32,
+                cmd: ticket.clone().into_bytes(),
+                path: Vec::new(),
+            }),
+            endpoint: vec![FlightEndpoint {
+                ticket: Some(Ticket { ticket: ticket.into_bytes() }),
+                location: Vec::new(),
+            }],
+            total_records: -1,
+            total_bytes: -1,
+        })
+    }
+}
+
+#[tonic::async_trait]
+impl FlightService for FlightServer {
+    type HandshakeStream = FlightStream<HandshakeResponse>;
+    type ListFlightsStream = FlightStream<FlightInfo>;
+    type DoGetStream = FlightStream<FlightData>;
+    type DoPutStream = FlightStream<PutResult>;
+    type DoExchangeStream = FlightStream<FlightData>;
+    type DoActionStream = FlightStream<arrow_format::flight::data::Result>;
+    type ListActionsStream = FlightStream<ActionType>;
+
+    async fn handshake(
+        &self,
+        _request: Request<Streaming<HandshakeRequest>>,
+    ) -> Result<Response<Self::HandshakeStream>, Status> {
+        Err(Status::unimplemented("handshake is not required"))
+    }
+
+    /// list the datatypes of finished jobs of `cryo serve`
+    async fn list_flights(
+        &self,
+        _request: Request<Criteria>,
+    ) -> Result<Response<Self::ListFlightsStream>, Status> {
+        let mut flights = Vec::new();
+        for (id, datatypes) in self.job_server()?.finished_jobs().into_iter() {
+            for datatype in datatypes.iter() {
+                flights.push(self.job_flight_info(&id, datatype));
+            }
+        }
+        Ok(Response::new(Box::pin(futures::stream::iter(flights))))
+    }
+
+    async fn get_flight_info(
+        &self,
+        request: Request<FlightDescriptor>,
+    ) -> Result<Response<FlightInfo>, Status> {
+        match self.parse_ticket(&request.into_inner().cmd)? {
+            TicketRequest::Job(id, datatype) => {
+                Ok(Response::new(self.job_flight_info(&id, &datatype)?))
+            }
+            TicketRequest::Collect(..) => Err(Status::unimplemented(
+                "schemas of collections are known once collected, use do_get with the ticket",
+            )),
+        }
+    }
+
+    async fn get_schema(
+        &self,
+        _request: Request<FlightDescriptor>,
+    ) -> Result<Response<SchemaResult>, Status> {
+        Err(Status::unimplemented("use get_flight_info"))
+    }
+
+    async fn do_get(
+        &self,
+        request: Request<Ticket>,
+    ) -> Result<Response<Self::DoGetStream>, Status> {
+        let (tx, rx) = mpsc::channel(2);
+        match self.parse_ticket(&request.into_inner().ticket)? {
+            TicketRequest::Job(id, datatype) => {
+                let files =
+                    self.job_server()?.job_files(&id, &datatype).map_err(Status::not_found)?;
+                tokio::spawn(async move {
+                    let mut writer = FlightWriter::new(tx);
+                    for path in files.iter() {
+                        let df = read_output_file(path, None).map_err(|e| e.to_string());
+                        if !writer.write(df).await {
+                            break
+                        }
+                    }
+                });
+            }
+            TicketRequest::Collect(args, datatype) => {
+                tokio::spawn(async move {
+                    let mut writer = FlightWriter::new(tx);
+                    let (query, source, _sink, _env) = match parse::parse_args(&args).await {
+                        Ok(parsed) => parsed,
+                        Err(e) => {
+                            writer.write(Err(e.to_string())).await;
+                            return
+                        }
+                    };
+                    let mut chunks = std::pin::pin!(collect_chunks(&query, Arc::new(source)));
+                    while let Some(dfs) = chunks.next().await {
+                        let df = dfs.map_err(|e| e.to_string()).and_then(|mut dfs| {
+                            dfs.remove(&datatype).ok_or_else(|| {
+                                format!("no {} dataframe collected", datatype.name())
+                            })
+                        });
+                        if !writer.write(df).await {
+                            break
+                        }
+                    }
+                });
+            }
+        }
+        Ok(Response::new(Box::pin(rx)))
+    }
+
+    async fn do_put(
+        &self,
+        _request: Request<Streaming<FlightData>>,
+    ) -> Result<Response<Self::DoPutStream>, Status> {
+        Err(Status::unimplemented("cryo does not accept data"))
+    }
+
+    async fn do_exchange(
+        &self,
+        _request: Request<Streaming<FlightData>>,
+    ) -> Result<Response<Self::DoExchangeStream>, Status> {
+        Err(Status::unimplemented("cryo does not accept data"))
+    }
+
+    async fn do_action(
+        &self,
+        _request: Request<Action>,
+    ) -> Result<Response<Self::DoActionStream>, Status> {
+        Err(Status::unimplemented("cryo has no actions"))
+    }
+
+    async fn list_actions(
+        &self,
+        _request: Request<Empty>,
+    ) -> Result<Response<Self::ListActionsStream>, Status> {
+        Ok(Response::new(Box::pin(futures::stream::empty())))
+    }
+}
+
+/// writer of dataframes into a stream of flight data, starting with the schema of the first
+struct FlightWriter {
+    tx: mpsc::Sender<Result<FlightData, Status>>,
+    schema: Option<(ArrowSchema, Vec<IpcField>)>,
+}
+
+impl FlightWriter {
+    fn new(tx: mpsc::Sender<Result<FlightData, Status>>) -> FlightWriter {
+        FlightWriter { tx, schema: None }
+    }
+
+    /// write dataframe, or error and end stream, returning whether the stream continues
+    async fn write(&mut self, df: Result<DataFrame, String>) -> bool {
+        let messages = df.and_then(|mut df| self.serialize(&mut df));
+        let messages = match messages {
+            Ok(messages) => messages,
+            Err(e) => {
+                let _ = self.tx.send(Err(Status::internal(e))).await;
+                return false
+            }
+        };
+        for message in messages.into_iter() {
+            if self.tx.send(Ok(message)).await.is_err() {
+                return false
+            }
+        }
+        true
+    }
+
+    fn serialize(&mut self, df: &mut DataFrame) -> Result<Vec<FlightData>, String> {
+        let arrow_schema = df.schema().to_arrow();
+        let mut messages = Vec::new();
+        let (schema, ipc_fields) = match &self.schema {
+            Some(schema) => schema,
+            None => {
+                let ipc_fields = default_ipc_fields(&arrow_schema.fields);
+                messages.push(serialize_schema(&arrow_schema, Some(&ipc_fields)));
+                self.schema.insert((arrow_schema.clone(), ipc_fields))
+            }
+        };
+        if schema.fields != arrow_schema.fields {
+            return Err("schema of chunk differs from schema of earlier chunks".to_string())
+        }
+        let options = WriteOptions { compression: None };
+        df.align_chunks();
+        for chunk in df.iter_chunks() {
+            let (dictionaries, batch) =
+                serialize_batch(&chunk, ipc_fields, &options).map_err(|e| e.to_string())?;
+            messages.extend(dictionaries);
+            messages.push(batch);
+        }
+        Ok(messages)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow2::io::flight::{deserialize_batch, deserialize_schemas};
+    use clap_cryo::Parser;
+
+    #[test]
+    fn test_parse_ticket() {
+        let defaults = Args::parse_from(["cryo", "flight"]);
+        let server = FlightServer { defaults, jobs: None };
+        let ticket = server.parse_ticket(br#"{"datatype": ["blocks"]}"#);
+        assert!(matches!(ticket, Ok(TicketRequest::Collect(_, Datatype::Blocks))));
+        let ticket = server.parse_ticket(br#"{"job": "1", "datatype": "blocks"}"#);
+        assert!(matches!(ticket, Ok(TicketRequest::Job(..))));
+
+        // tickets cannot make the server read or run its files
+        for option in ["output_dir", "replay_fixtures", "transform", "config", "js_tracer"] {
+            let ticket = format!(r#"{{"datatype": ["blocks"], "{}": "/tmp/x"}}"#, option);
+            let status = server.parse_ticket(ticket.as_bytes()).err().unwrap();
+            assert_eq!(status.message(), format!("{} is chosen by the server", option));
+        }
+        let ticket = br#"{"datatype": ["blocks"], "function_abi": "Cargo.toml"}"#;
+        let status = server.parse_ticket(ticket).err().unwrap();
+        assert_eq!(status.message(), "function_abi must be a signature");
+        assert!(server.parse_ticket(br#"{"datatype": ["blocks", "logs"]}"#).is_err());
+        let status = server.parse_ticket(br#"{"datatype": ["state_diffs"]}"#).err().unwrap();
+        assert_eq!(status.message(), "ticket must specify a single datatype, not state_diffs");
+    }
+
+    #[tokio::test]
+    async fn test_flight_writer_round_trip() {
+        let (tx, rx) = mpsc::channel(16);
+        let mut writer = FlightWriter::new(tx);
+        let df = df!("block_number" => [1u32, 2], "address" => [vec![1u8], vec![2u8]]).unwrap();
+        assert!(writer.write(Ok(df.clone())).await);
+        assert!(!writer.write(Ok(df!("block_number" => [1u64]).unwrap())).await);
+        drop(writer);
+
+        // schema, record batch, and error of the chunk of a different schema
+        let messages: Vec<_> = rx.collect().await;
+        assert_eq!(messages.len(), 3);
+        let schema = messages[0].as_ref().unwrap();
+        let (schema, ipc_schema) = deserialize_schemas(&schema.data_header).unwrap();
+        let batch = messages[1].as_ref().unwrap();
+        let chunk =
+            deserialize_batch(batch, &schema.fields, &ipc_schema, &Default::default()).unwrap();
+        assert_eq!(DataFrame::try_from((chunk, schema.fields.as_slice())).unwrap(), df);
+        assert!(messages[2].is_err());
+    }
+}
@@ -8,13 +8,18 @@
 ))]
 
 mod args;
+mod flight;
+mod mempool;
 mod parse;
 mod run;
+mod schedule;
+mod serve;
+mod tail;
 
 // used in main.rs but not lib.rs
 use eyre as _;
 use tokio as _;
 
 pub use args::Args;
-pub use parse::{parse_args, parse_str};
+pub use parse::{parse_args, parse_json_args, parse_str};
 pub use run::run;
@@ -3,8 +3,13 @@
 use clap_cryo::Parser;
 
 mod args;
+mod flight;
+mod mempool;
 mod parse;
 mod run;
+mod schedule;
+mod serve;
+mod tail;
 
 pub use args::Args;
 use eyre::Result;
@@ -14,7 +19,13 @@ use eyre::Result;
 #[allow(clippy::needless_return)]
 async fn main() -> Result<()> {
     let args = Args::parse();
+    // finish in-flight chunks on Ctrl-C, leaving the remaining chunks for a rerun
+    cryo_freeze::Shutdown::on_signal();
     match run::run(args).await {
+        // exit like a process killed by SIGINT, so that scripts stop rather than continue
+        Ok(Some(freeze_summary)) if !freeze_summary.interrupted.is_empty() => {
+            std::process::exit(130)
+        }
         Ok(Some(freeze_summary)) if freeze_summary.errored.is_empty() => Ok(()),
         Ok(Some(_freeze_summary)) => std::process::exit(1),
         Ok(None) => Ok(()),
@@ -0,0 +1,100 @@
+//! `cryo mempool`, collecting the transactions of the mempool over time
+//!
+//! the pending and queued transactions of `txpool_content` are snapshotted every
+//! `--poll-interval` seconds, and each transaction is recorded once when it enters a pool, with
+//! the time it was first seen in any pool. rows are written to a file for each `--time-bucket`
+//! seconds when the bucket ends or collection is interrupted, and a rerun appends to the file of
+//! the current bucket
+
+use std::{
+    str::FromStr,
+    sync::Arc,
+    time::{Duration, SystemTime},
+};
+
+use cryo_freeze::{CollectError, Datatype, MempoolTracker, Shutdown, TimeBuckets};
+
+use crate::{args::Args, parse};
+
+/// whether datatypes of args include the mempool, which is collected by `cryo mempool`
+pub(crate) fn is_mempool(args: &Args) -> bool {
+    args.datatype
+        .iter()
+        .any(|datatype| Datatype::from_str(datatype).ok() == Some(Datatype::Mempool))
+}
+
+/// run `cryo mempool` command
+pub(crate) async fn run_mempool(args: &Args) -> Result<(), CollectError> {
+    if args.datatype.len() > 1 {
+        return Err(CollectError::CollectError(
+            "mempool is collected over time and cannot be collected with other datatypes"
+                .to_string(),
+        ))
+    }
+    let interval = Duration::try_from_secs_f64(args.poll_interval)
+        .map_err(|_| CollectError::CollectError("invalid --poll-interval".to_string()))?;
+    let blocks = Some(vec!["latest".to_string()]);
+    let (query, source, sink, _env) = parse::parse_args(&Args { blocks, ..args.clone() }).await?;
+    let source = Arc::new(source);
+    let datatype = Datatype::Mempool;
+    let schema = query
+        .schemas
+        .get(&datatype)
+        .ok_or(CollectError::CollectError("schema not provided".to_string()))?;
+    let sort = schema.sort_columns.clone().unwrap_or_default();
+    let mut buckets = TimeBuckets::new(datatype, args.time_bucket, sink, sort)?;
+
+    // transactions already recorded in the file of the current bucket are not recorded again
+    let mut tracker = MempoolTracker::default();
+    if let Some(existing) = buckets.read_existing(now()?)? {
+        tracker.seed(&existing)?;
+    }
+
+    let shutdown = Shutdown::on_signal_if_handled();
+    if let Some(shutdown) = &shutdown {
+        shutdown.set_collecting(true);
+    }
+    let result =
+        follow(&query.schemas, source, &mut tracker, &mut buckets, interval, shutdown).await;
+    // write rows of the current bucket, even if a snapshot failed
+    let flushed = buckets.flush();
+    result?;
+    if let Some(path) = flushed? {
+        println!("wrote {}", path.display());
+    }
+    Ok(())
+}
+
+/// snapshot the mempool every interval until shutdown is requested
+async fn follow(
+    schemas: &cryo_freeze::Schemas,
+    source: Arc<cryo_freeze::Source>,
+    tracker: &mut MempoolTracker,
+    buckets: &mut TimeBuckets,
+    interval: Duration,
+    shutdown: Option<Arc<Shutdown>>,
+) -> Result<(), CollectError> {
+    loop {
+        let txpool = source.fetcher.txpool_content().await?;
+        let timestamp = now()?;
+        let df = tracker.snapshot(txpool, timestamp as u32, schemas, source.chain_id)?;
+        if let Some(path) = buckets.push(timestamp, df)? {
+            println!("wrote {}", path.display());
+        }
+        match &shutdown {
+            Some(shutdown) => tokio::select! {
+                _ = tokio::time::sleep(interval) => {}
+                _ = shutdown.requested() => return Ok(()),
+            },
+            None => tokio::time::sleep(interval).await,
+        }
+    }
+}
+
+/// seconds since the unix epoch
+fn now() -> Result<u64, CollectError> {
+    SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .map_err(|_| CollectError::CollectError("system time is before the unix epoch".to_string()))
+}
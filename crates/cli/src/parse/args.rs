@@ -5,21 +5,86 @@ use cryo_freeze::{ExecutionEnv, FileOutput, ParseError, Query, Source};
 use crate::args::Args;
 use clap_cryo::Parser;
 
-use super::{execution, file_output, query, source};
+use super::{config, execution, file_output, query, source};
 
 /// parse options for running freeze
 pub async fn parse_args(
     args: &Args,
 ) -> Result<(Query, Source, FileOutput, ExecutionEnv), ParseError> {
+    config::parse_config(args)?;
     let source = source::parse_source(args).await?;
-    let query = query::parse_query(args, Arc::clone(&source.fetcher)).await?;
-    let sink = file_output::parse_file_output(args, &source)?;
+    let query = parse_source_query(args, &source).await?;
+    let args =
+        &Args { chunk_size: Some(source::parse_chunk_size(args, source.chain_id)), ..args.clone() };
+    let source = Source { trace_cache: source::parse_trace_cache(args, &query), ..source };
+    let source =
+        Source { dataset_sources: source::parse_dataset_sources(args, &query, &source)?, ..source };
+    let sink = file_output::parse_file_output(args, &source, &query)?;
     let env = execution::parse_execution_env(args, query.n_tasks() as u64)?;
     Ok((query, source, sink, env))
 }
 
+/// parse query of args for an already parsed source
+pub(crate) async fn parse_source_query(args: &Args, source: &Source) -> Result<Query, ParseError> {
+    let args =
+        &Args { chunk_size: Some(source::parse_chunk_size(args, source.chain_id)), ..args.clone() };
+    let query = query::parse_query(args, Arc::clone(&source.fetcher)).await?;
+    source.capabilities.check_query(&query)?;
+    Ok(query)
+}
+
 /// parse command string
 #[allow(dead_code)]
 pub async fn parse_str(command: &str) -> Result<Args, ParseError> {
     Ok(Args::parse_from(command.split_whitespace()))
 }
+
+/// parse json object of cli options, filling options not given from defaults
+///
+/// options are named like the fields of `Args`, and options taking several values are arrays
+pub fn parse_json_args(config: &str, defaults: &Args) -> Result<Args, ParseError> {
+    let invalid = |message: String| ParseError::ParseError(format!("invalid config: {}", message));
+    let config: serde_json::Value =
+        serde_json::from_str(config).map_err(|e| invalid(e.to_string()))?;
+    let config = match config {
+        serde_json::Value::Object(config) => config,
+        _ => return Err(invalid("config must be a json object".to_string())),
+    };
+    if !config.contains_key("datatype") {
+        return Err(invalid("config must specify datatype".to_string()))
+    }
+    let mut args = match serde_json::to_value(defaults) {
+        Ok(serde_json::Value::Object(args)) => args,
+        _ => return Err(invalid("could not serialize default options".to_string())),
+    };
+    for (key, value) in config.into_iter() {
+        if !args.contains_key(&key) {
+            return Err(invalid(format!("unknown option {}", key)))
+        }
+        args.insert(key, value);
+    }
+    serde_json::from_value(serde_json::Value::Object(args)).map_err(|e| invalid(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_json_args() {
+        let defaults = Args::parse_from(["cryo", "blocks", "--max-retries", "3"]);
+        let config = r#"{"datatype": ["logs"], "blocks": ["0:10"], "hex": true}"#;
+        let args = parse_json_args(config, &defaults).unwrap();
+        assert_eq!(args.datatype, vec!["logs"]);
+        assert_eq!(args.blocks, Some(vec!["0:10".to_string()]));
+        assert!(args.hex);
+        assert_eq!(args.max_retries, 3);
+
+        assert!(parse_json_args(r#"{"blocks": ["0:10"]}"#, &defaults).is_err());
+        assert!(
+            parse_json_args(r#"{"datatype": ["logs"], "not_an_option": 1}"#, &defaults).is_err()
+        );
+        assert!(parse_json_args(r#"{"datatype": "logs"}"#, &defaults).is_err());
+        assert!(parse_json_args("[]", &defaults).is_err());
+    }
+}
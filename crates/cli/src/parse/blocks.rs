@@ -2,7 +2,10 @@ use ethers::prelude::*;
 use polars::prelude::*;
 use std::collections::HashMap;
 
-use cryo_freeze::{BlockChunk, ChunkData, Datatype, Fetcher, ParseError, Subchunk, Table};
+use cryo_freeze::{
+    find_empty_block_ranges, find_file_block_ranges, BeaconSlotChunk, BlockChunk, ChunkData,
+    Datatype, Fetcher, ParseError, Subchunk, Table, DEFAULT_CHUNK_SIZE,
+};
 
 use crate::args::Args;
 
@@ -68,6 +71,93 @@ pub(crate) async fn parse_blocks<P: JsonRpcClient>(
     Ok((labels, Some(block_chunks)))
 }
 
+/// parse beacon chain slots to freeze
+///
+/// uses the same syntax as block numbers, except that `latest` is not resolved
+pub(crate) fn parse_beacon_slots(args: &Args) -> Result<Option<Vec<BeaconSlotChunk>>, ParseError> {
+    let inputs = match &args.beacon_slots {
+        Some(inputs) => inputs,
+        None => return Ok(None),
+    };
+
+    let mut slot_chunks = Vec::new();
+    for input in inputs.iter() {
+        let parts: Vec<&str> = input.split(' ').collect();
+        let as_range = parts.len() == 1;
+        for part in parts {
+            slot_chunks.push(parse_beacon_slot_token(part, as_range)?);
+        }
+    }
+
+    let chunk_size = args.chunk_size.unwrap_or(DEFAULT_CHUNK_SIZE);
+    let slot_chunks = if args.align {
+        slot_chunks.into_iter().filter_map(|x| x.align(chunk_size)).collect()
+    } else {
+        slot_chunks
+    };
+    let slot_chunks = match args.n_chunks {
+        Some(n_chunks) => slot_chunks.subchunk_by_count(&n_chunks),
+        None => slot_chunks.subchunk_by_size(&chunk_size),
+    };
+    Ok(Some(slot_chunks))
+}
+
+/// parse block range that does not depend on the chain, such as 1M:2M or 1000:+500
+pub(crate) fn parse_static_block_range(s: &str) -> Result<(u64, u64), ParseError> {
+    match parse_beacon_slot_token(s, true)? {
+        BeaconSlotChunk::Range(start_block, end_block) => Ok((start_block, end_block)),
+        BeaconSlotChunk::Numbers(numbers) => {
+            let block = numbers.first().copied().unwrap_or_default();
+            Ok((block, block))
+        }
+    }
+}
+
+fn parse_beacon_slot_token(s: &str, as_range: bool) -> Result<BeaconSlotChunk, ParseError> {
+    let s = s.replace('_', "");
+    let parts: Vec<&str> = s.split(':').collect();
+    let (start_slot, end_slot) = match parts.as_slice() {
+        [slot_ref] => return Ok(BeaconSlotChunk::Numbers(vec![parse_number_ref(slot_ref)?])),
+        [_, "latest"] | [_, ""] => {
+            return Err(ParseError::ParseError("beacon slot ranges must specify an end".to_string()))
+        }
+        [first_ref, second_ref] if first_ref.starts_with('-') => {
+            let end_slot = parse_number_ref(second_ref)?;
+            let start_slot = end_slot
+                .checked_sub(parse_number_ref(&first_ref[1..])?)
+                .ok_or_else(|| ParseError::ParseError("start_slot underflow".to_string()))?;
+            (start_slot + 1, end_slot)
+        }
+        [first_ref, second_ref] if second_ref.starts_with('+') => {
+            let start_slot = parse_number_ref(first_ref)?;
+            let end_slot = (start_slot + parse_number_ref(&second_ref[1..])?)
+                .checked_sub(1)
+                .ok_or_else(|| ParseError::ParseError("end_slot underflow".to_string()))?;
+            (start_slot, end_slot)
+        }
+        [first_ref, second_ref] => {
+            let start_slot = if first_ref.is_empty() { 0 } else { parse_number_ref(first_ref)? };
+            let end_slot = parse_number_ref(second_ref)?
+                .checked_sub(1)
+                .ok_or_else(|| ParseError::ParseError("end_slot underflow".to_string()))?;
+            (start_slot, end_slot)
+        }
+        _ => {
+            return Err(ParseError::ParseError(
+                "beacon slots must be in format slot or start_slot:end_slot".to_string(),
+            ))
+        }
+    };
+
+    if end_slot < start_slot {
+        Err(ParseError::ParseError("end_slot should not be less than start_slot".to_string()))
+    } else if as_range {
+        Ok(BeaconSlotChunk::Range(start_slot, end_slot))
+    } else {
+        Ok(BeaconSlotChunk::Numbers((start_slot..=end_slot).collect()))
+    }
+}
+
 fn read_integer_column(path: &str, column: &str) -> Result<Vec<u64>, ParseError> {
     let file = std::fs::File::open(path)
         .map_err(|_e| ParseError::ParseError("could not open file path".to_string()))?;
@@ -112,8 +202,9 @@ async fn postprocess_block_chunks<P: JsonRpcClient>(
     fetcher: Arc<Fetcher<P>>,
 ) -> Result<Vec<BlockChunk>, ParseError> {
     // align
+    let chunk_size = args.chunk_size.unwrap_or(DEFAULT_CHUNK_SIZE);
     let block_chunks = if args.align {
-        block_chunks.into_iter().filter_map(|x| x.align(args.chunk_size)).collect()
+        block_chunks.into_iter().filter_map(|x| x.align(chunk_size)).collect()
     } else {
         block_chunks
     };
@@ -121,7 +212,7 @@ async fn postprocess_block_chunks<P: JsonRpcClient>(
     // split block range into chunks
     let block_chunks = match args.n_chunks {
         Some(n_chunks) => block_chunks.subchunk_by_count(&n_chunks),
-        None => block_chunks.subchunk_by_size(&args.chunk_size),
+        None => block_chunks.subchunk_by_size(&chunk_size),
     };
 
     // apply reorg buffer
@@ -147,6 +238,47 @@ pub(crate) async fn get_default_block_chunks<P: JsonRpcClient>(
     postprocess_block_chunks(block_chunks, args, fetcher).await
 }
 
+/// remove blocks up to the last block collected into the output files of every datatype, for
+/// `--append`
+///
+/// if a datatype has no output files yet, blocks are kept so that it is collected from the start
+pub(crate) fn remove_collected_blocks(
+    block_chunks: Vec<BlockChunk>,
+    args: &Args,
+    schemas: &HashMap<Datatype, Table>,
+) -> Result<Vec<BlockChunk>, ParseError> {
+    let output_dir = &cryo_freeze::OutputLocation::parse(&args.output_dir)?.local_dir();
+    if !output_dir.is_dir() {
+        return Ok(block_chunks)
+    }
+    let mut last_collected = u64::MAX;
+    for datatype in schemas.keys() {
+        let error = |e: cryo_freeze::CollectError| ParseError::ParseError(e.to_string());
+        let files = find_file_block_ranges(output_dir, datatype).map_err(error)?;
+        let empty = find_empty_block_ranges(output_dir, datatype).map_err(error)?;
+        match files.iter().chain(empty.iter()).map(|range| range.end_block).max() {
+            Some(end_block) => last_collected = last_collected.min(end_block),
+            None => return Ok(block_chunks),
+        }
+    }
+    Ok(block_chunks
+        .into_iter()
+        .filter_map(|chunk| remove_blocks_through(chunk, last_collected))
+        .collect())
+}
+
+/// remove blocks of chunk up to and including block, returning None if no blocks remain
+fn remove_blocks_through(chunk: BlockChunk, block: u64) -> Option<BlockChunk> {
+    match chunk {
+        BlockChunk::Range(_, end) if end <= block => None,
+        BlockChunk::Range(start, end) => Some(BlockChunk::Range(start.max(block + 1), end)),
+        BlockChunk::Numbers(numbers) => {
+            let numbers: Vec<u64> = numbers.into_iter().filter(|number| *number > block).collect();
+            (!numbers.is_empty()).then_some(BlockChunk::Numbers(numbers))
+        }
+    }
+}
+
 /// parse block numbers to freeze
 async fn parse_block_inputs<P: JsonRpcClient>(
     inputs: &str,
@@ -264,6 +396,13 @@ async fn parse_block_number<P: JsonRpcClient>(
             })
         }
         ("", RangePosition::None) => Err(ParseError::ParseError("invalid input".to_string())),
+        _ => parse_number_ref(block_ref),
+    }
+}
+
+/// parse number that can contain { K M B } suffixes
+pub(crate) fn parse_number_ref(block_ref: &str) -> Result<u64, ParseError> {
+    match block_ref {
         _ if block_ref.ends_with('B') | block_ref.ends_with('b') => {
             let s = &block_ref[..block_ref.len() - 1];
             s.parse::<f64>()
@@ -303,7 +442,7 @@ async fn apply_reorg_buffer<P: JsonRpcClient>(
                     return Err(ParseError::ParseError("reorg buffer parse error".to_string()))
                 }
             };
-            let max_allowed = latest_block - reorg_filter;
+            let max_allowed = latest_block.saturating_sub(reorg_filter);
             Ok(block_chunks
                 .into_iter()
                 .filter_map(|x| match x.max_value() {
@@ -326,7 +465,18 @@ mod tests {
 
     async fn block_token_test_helper(tests: Vec<(BlockTokenTest<'_>, bool)>) {
         let (provider, mock) = Provider::mocked();
-        let fetcher = Fetcher { provider, semaphore: None, rate_limiter: None };
+        let fetcher = Fetcher {
+            provider,
+            semaphore: None,
+            rate_limiter: None,
+            n_requests: Default::default(),
+            rpc_stats: Default::default(),
+            disk_cache: None,
+            parent: None,
+            autotune: None,
+            coalescer: None,
+            middleware: Vec::new(),
+        };
         for (test, res) in tests {
             match test {
                 BlockTokenTest::WithMock((token, expected, latest)) => {
@@ -372,7 +522,18 @@ mod tests {
 
     async fn block_input_test_helper(tests: Vec<(BlockInputTest<'_>, bool)>) {
         let (provider, mock) = Provider::mocked();
-        let fetcher = Fetcher { provider, semaphore: None, rate_limiter: None };
+        let fetcher = Fetcher {
+            provider,
+            semaphore: None,
+            rate_limiter: None,
+            n_requests: Default::default(),
+            rpc_stats: Default::default(),
+            disk_cache: None,
+            parent: None,
+            autotune: None,
+            coalescer: None,
+            middleware: Vec::new(),
+        };
         for (test, res) in tests {
             match test {
                 BlockInputTest::WithMock((inputs, expected, latest)) => {
@@ -433,7 +594,18 @@ mod tests {
 
     async fn block_number_test_helper(tests: Vec<(BlockNumberTest<'_>, bool)>) {
         let (provider, mock) = Provider::mocked();
-        let fetcher = Fetcher { provider, semaphore: None, rate_limiter: None };
+        let fetcher = Fetcher {
+            provider,
+            semaphore: None,
+            rate_limiter: None,
+            n_requests: Default::default(),
+            rpc_stats: Default::default(),
+            disk_cache: None,
+            parent: None,
+            autotune: None,
+            coalescer: None,
+            middleware: Vec::new(),
+        };
         for (test, res) in tests {
             match test {
                 BlockNumberTest::WithMock((block_ref, range_position, expected, latest)) => {
@@ -543,4 +715,16 @@ mod tests {
         ];
         block_number_test_helper(tests).await;
     }
+
+    #[test]
+    fn beacon_slot_parsing() {
+        let slots = parse_beacon_slot_token("1K:+100", true).unwrap();
+        assert!(matches!(slots, BeaconSlotChunk::Range(1000, 1099)));
+        let slots = parse_beacon_slot_token("-10:100", true).unwrap();
+        assert!(matches!(slots, BeaconSlotChunk::Range(91, 100)));
+        let slots = parse_beacon_slot_token("5:8", false).unwrap();
+        assert!(matches!(slots, BeaconSlotChunk::Numbers(numbers) if numbers == vec![5, 6, 7]));
+        assert!(parse_beacon_slot_token("5:latest", true).is_err());
+        assert!(parse_beacon_slot_token("8:5", true).is_err());
+    }
 }
@@ -0,0 +1,47 @@
+use std::{collections::BTreeMap, path::PathBuf, str::FromStr};
+
+use cryo_freeze::{register_multi_datatype, Datatype, ParseError};
+
+use crate::args::Args;
+
+/// contents of config file
+#[derive(Default, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Config {
+    /// groups of datatypes collected together, by name
+    #[serde(default)]
+    multi_datatypes: BTreeMap<String, Vec<String>>,
+}
+
+/// default path of config file, `~/.cryo/config.json`
+fn default_config_path() -> Option<PathBuf> {
+    std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".cryo/config.json"))
+}
+
+/// register the multi datatypes of the config file given by `--config`, or of the default config
+/// file if it exists
+pub(crate) fn parse_config(args: &Args) -> Result<(), ParseError> {
+    let path = match &args.config {
+        Some(path) => PathBuf::from(path),
+        None => match default_config_path() {
+            Some(path) if path.is_file() => path,
+            _ => return Ok(()),
+        },
+    };
+    let invalid = |message: String| {
+        ParseError::ParseError(format!("invalid config {}: {}", path.display(), message))
+    };
+    let contents = std::fs::read_to_string(&path).map_err(|e| invalid(e.to_string()))?;
+    let config: Config = serde_json::from_str(&contents).map_err(|e| invalid(e.to_string()))?;
+    for (name, datatypes) in config.multi_datatypes.iter() {
+        let datatypes = datatypes
+            .iter()
+            .map(|datatype| {
+                Datatype::from_str(datatype)
+                    .map_err(|_| invalid(format!("unknown datatype {}", datatype)))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        register_multi_datatype(name, datatypes)?;
+    }
+    Ok(())
+}
@@ -1,5 +1,5 @@
 use crate::args::Args;
-use cryo_freeze::{ExecutionEnv, ExecutionEnvBuilder, ParseError};
+use cryo_freeze::{ExecutionEnv, ExecutionEnvBuilder, ParseError, Shutdown};
 
 pub(crate) fn parse_execution_env(args: &Args, n_tasks: u64) -> Result<ExecutionEnv, ParseError> {
     let args_str =
@@ -9,9 +9,13 @@ pub(crate) fn parse_execution_env(args: &Args, n_tasks: u64) -> Result<Execution
         .verbose(!args.no_verbose)
         .report(!args.no_report)
         .report_dir(args.report_dir.clone())
+        .tui(args.tui && !args.no_verbose)
+        .chunk_retries(args.chunk_retries)
+        .chunk_retry_backoff(args.initial_backoff)
+        .shutdown(Shutdown::on_signal_if_handled())
         .args(args_str);
 
-    let builder = if !args.no_verbose {
+    let builder = if !args.no_verbose && !args.tui {
         builder
             .bar(n_tasks)
             .map_err(|_| ParseError::ParseError("could not create progress bar".to_string()))?
@@ -1,13 +1,23 @@
 use crate::args::Args;
-use cryo_freeze::{FileFormat, FileOutput, ParseError, Source};
+use cryo_freeze::{
+    get_chain, ClickhouseOutput, Datatype, ExistingFilePolicy, FileFormat, FileOutput,
+    OutputLocation, ParseError, Query, RemoteOutput, Source, TextCompression, DEFAULT_CHUNK_SIZE,
+};
 use polars::prelude::*;
-use std::fs;
+use std::{collections::HashMap, fs};
 
-pub(crate) fn parse_file_output(args: &Args, source: &Source) -> Result<FileOutput, ParseError> {
-    // process output directory
-    std::fs::create_dir_all(args.output_dir.clone())
+pub(crate) fn parse_file_output(
+    args: &Args,
+    source: &Source,
+    query: &Query,
+) -> Result<FileOutput, ParseError> {
+    // process output directory, which stages the files of object store outputs
+    let location = OutputLocation::parse(&args.output_dir)?;
+    let remote = RemoteOutput::new(&location)?;
+    let output_dir = location.local_dir();
+    std::fs::create_dir_all(&output_dir)
         .map_err(|_| ParseError::ParseError("could not create dir".to_string()))?;
-    let output_dir = std::fs::canonicalize(args.output_dir.clone()).map_err(|_e| {
+    let output_dir = std::fs::canonicalize(output_dir).map_err(|_e| {
         ParseError::ParseError("Failed to canonicalize output directory".to_string())
     })?;
     match fs::create_dir_all(&output_dir) {
@@ -17,56 +27,176 @@ pub(crate) fn parse_file_output(args: &Args, source: &Source) -> Result<FileOutp
 
     let file_suffix = &args.file_suffix;
 
-    let parquet_compression = parse_compression(&args.compression)?;
+    let format = parse_output_format(args)?;
+    let (parquet_compression, text_compression) = match format {
+        FileFormat::Parquet => {
+            (parse_compression(&args.compression)?, TextCompression::Uncompressed)
+        }
+        FileFormat::Csv | FileFormat::Json => {
+            (ParquetCompression::Lz4Raw, parse_text_compression(&args.compression)?)
+        }
+    };
 
     let row_group_size = parse_row_group_size(
         args.row_group_size,
         args.n_row_groups,
-        Some(args.chunk_size as usize),
+        Some(args.chunk_size.unwrap_or(DEFAULT_CHUNK_SIZE) as usize),
     );
 
-    let format = parse_output_format(args)?;
     let file_prefix = parse_network_name(args, source.chain_id);
+    let clickhouse = args.clickhouse.as_deref().map(ClickhouseOutput::parse).transpose()?;
+    let datatypes: Vec<Datatype> = query.schemas.keys().cloned().collect();
+    let dedup_keys = parse_dedup_keys(args, &datatypes)?;
+    for (datatype, keys) in dedup_keys.iter() {
+        let schema = query.schemas.get(datatype);
+        if let Some(key) = keys.iter().find(|key| !schema.is_some_and(|s| s.has_column(key))) {
+            return Err(ParseError::ParseError(format!(
+                "dedup key {} is not a column of {}",
+                key,
+                datatype.name()
+            )))
+        }
+    }
+    let max_concurrent_writes = match args.max_concurrent_writes {
+        Some(0) => None,
+        Some(max) => Some(max),
+        None => Some(4),
+    };
 
     let output = FileOutput {
         output_dir,
         parquet_statistics: !args.no_stats,
-        overwrite: args.overwrite,
+        existing_files: parse_existing_files(args)?,
+        allow_schema_change: args.allow_schema_change,
         prefix: file_prefix,
         format,
         suffix: file_suffix.clone(),
         parquet_compression,
+        text_compression,
+        hex_prefix: !args.no_hex_prefix,
+        clickhouse,
+        manifest: args.manifest || args.pin_block_hash,
+        pin_block_hash: args.pin_block_hash,
+        skip_empty_files: args.skip_empty_files,
         row_group_size,
+        global_sort: args.global_sort,
+        dedup_keys,
+        max_concurrent_writes,
+        remote,
     };
 
     Ok(output)
 }
 
+/// policy for chunks whose files exist, from `--overwrite` and `--no-clobber`
+fn parse_existing_files(args: &Args) -> Result<ExistingFilePolicy, ParseError> {
+    match (args.overwrite, &args.no_clobber) {
+        (true, Some(_)) => {
+            Err(ParseError::ParseError("--overwrite cannot be used with --no-clobber".to_string()))
+        }
+        (true, None) => Ok(ExistingFilePolicy::Overwrite),
+        (false, Some(policy)) => match policy.parse()? {
+            ExistingFilePolicy::Overwrite => Err(ParseError::ParseError(
+                "--no-clobber accepts skip, error, or version".to_string(),
+            )),
+            policy => Ok(policy),
+        },
+        (false, None) => Ok(ExistingFilePolicy::Skip),
+    }
+}
+
+/// key columns of each datatype for `--dedup-keys`, either the given columns or the keys defined
+/// by each datatype
+fn parse_dedup_keys(
+    args: &Args,
+    datatypes: &[Datatype],
+) -> Result<HashMap<Datatype, Vec<String>>, ParseError> {
+    let columns: Vec<String> = match &args.dedup_keys {
+        Some(columns) => columns
+            .iter()
+            .flat_map(|column| column.split(','))
+            .filter(|column| !column.is_empty())
+            .map(|column| column.to_string())
+            .collect(),
+        None => return Ok(HashMap::new()),
+    };
+    let mut dedup_keys = HashMap::new();
+    for datatype in datatypes.iter() {
+        let keys = match (columns.is_empty(), datatype.dedup_keys()) {
+            (false, _) => columns.clone(),
+            (true, Some(keys)) => keys,
+            (true, None) => {
+                return Err(ParseError::ParseError(format!(
+                    "{} has no dedup keys, specify them with --dedup-keys COLS",
+                    datatype.name()
+                )))
+            }
+        };
+        dedup_keys.insert(*datatype, keys);
+    }
+    Ok(dedup_keys)
+}
+
+/// output options of `cryo merge` of datatypes, which rewrites existing files of output_dir
+pub(crate) fn parse_merge_output(
+    args: &Args,
+    datatypes: &[Datatype],
+) -> Result<FileOutput, ParseError> {
+    let output_dir = OutputLocation::parse(&args.output_dir)?.local_dir();
+    let output_dir = std::fs::canonicalize(output_dir).map_err(|_e| {
+        ParseError::ParseError("Failed to canonicalize output directory".to_string())
+    })?;
+    if parse_output_format(args)? != FileFormat::Parquet {
+        return Err(ParseError::ParseError("cryo merge only merges parquet files".to_string()))
+    }
+    Ok(FileOutput {
+        output_dir,
+        parquet_statistics: !args.no_stats,
+        existing_files: ExistingFilePolicy::Overwrite,
+        allow_schema_change: false,
+        prefix: String::new(),
+        format: FileFormat::Parquet,
+        suffix: args.file_suffix.clone(),
+        parquet_compression: parse_compression(&args.compression)?,
+        text_compression: TextCompression::Uncompressed,
+        hex_prefix: !args.no_hex_prefix,
+        clickhouse: None,
+        manifest: args.manifest,
+        pin_block_hash: false,
+        skip_empty_files: false,
+        row_group_size: parse_row_group_size(args.row_group_size, args.n_row_groups, None),
+        global_sort: args.global_sort,
+        dedup_keys: parse_dedup_keys(args, datatypes)?,
+        max_concurrent_writes: None,
+        remote: None,
+    })
+}
+
+/// parse file size such as 512MB or 1.5G into bytes
+pub(crate) fn parse_file_size(size: &str) -> Result<u64, ParseError> {
+    let invalid = || ParseError::ParseError(format!("invalid file size: {}", size));
+    let upper = size.trim().to_uppercase();
+    let number = upper.trim_end_matches('B');
+    let (number, multiplier) = match number.chars().last() {
+        Some('K') => (&number[..number.len() - 1], 1u64 << 10),
+        Some('M') => (&number[..number.len() - 1], 1 << 20),
+        Some('G') => (&number[..number.len() - 1], 1 << 30),
+        Some('T') => (&number[..number.len() - 1], 1 << 40),
+        _ => (number, 1),
+    };
+    let value: f64 = number.trim().parse().map_err(|_| invalid())?;
+    if value <= 0.0 {
+        return Err(invalid())
+    }
+    Ok((value * multiplier as f64) as u64)
+}
+
 pub(crate) fn parse_network_name(args: &Args, chain_id: u64) -> String {
     match &args.network_name {
         Some(name) => name.clone(),
-        None => match chain_id {
-            1 => "ethereum".to_string(),
-            5 => "goerli".to_string(),
-            10 => "optimism".to_string(),
-            56 => "bnb".to_string(),
-            69 => "optimism_kovan".to_string(),
-            100 => "gnosis".to_string(),
-            137 => "polygon".to_string(),
-            420 => "optimism_goerli".to_string(),
-            1101 => "polygon_zkevm".to_string(),
-            1442 => "polygon_zkevm_testnet".to_string(),
-            8453 => "base".to_string(),
-            10200 => "gnosis_chidao".to_string(),
-            17000 => "holesky".to_string(),
-            42161 => "arbitrum".to_string(),
-            42170 => "arbitrum_nova".to_string(),
-            43114 => "avalanche".to_string(),
-            80001 => "polygon_mumbai".to_string(),
-            84531 => "base_goerli".to_string(),
-            7777777 => "zora".to_string(),
-            11155111 => "sepolia".to_string(),
-            chain_id => "network_".to_string() + chain_id.to_string().as_str(),
+        None => match get_chain(chain_id) {
+            Some(chain) => chain.name.to_string(),
+            None => "network_".to_string() + chain_id.to_string().as_str(),
         },
     }
 }
@@ -118,6 +248,29 @@ fn parse_compression(input: &Vec<String>) -> Result<ParquetCompression, ParseErr
     }
 }
 
+fn parse_text_compression(input: &Vec<String>) -> Result<TextCompression, ParseError> {
+    let invalid_level = || ParseError::ParseError("Invalid compression level".to_string());
+    match input.as_slice() {
+        // lz4 is the parquet default and does not apply to csv or json
+        [algorithm] if ["uncompressed", "lz4"].contains(&algorithm.as_str()) => {
+            Ok(TextCompression::Uncompressed)
+        }
+        [algorithm] if algorithm.as_str() == "gzip" => Ok(TextCompression::Gzip(6)),
+        [algorithm] if algorithm.as_str() == "zstd" => Ok(TextCompression::Zstd(3)),
+        [algorithm, level_str] if algorithm.as_str() == "gzip" => match level_str.parse::<u32>() {
+            Ok(level) if level <= 9 => Ok(TextCompression::Gzip(level)),
+            _ => Err(invalid_level()),
+        },
+        [algorithm, level_str] if algorithm.as_str() == "zstd" => match level_str.parse::<i32>() {
+            Ok(level) if (1..=22).contains(&level) => Ok(TextCompression::Zstd(level)),
+            _ => Err(invalid_level()),
+        },
+        _ => Err(ParseError::ParseError(
+            "csv and json outputs only support gzip and zstd compression".to_string(),
+        )),
+    }
+}
+
 fn parse_row_group_size(
     row_group_size: Option<usize>,
     n_row_groups: Option<usize>,
@@ -129,3 +282,17 @@ fn parse_row_group_size(
         _ => None,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_file_size() {
+        assert_eq!(parse_file_size("512MB").unwrap(), 512 << 20);
+        assert_eq!(parse_file_size("1.5g").unwrap(), 3 << 29);
+        assert_eq!(parse_file_size("4096").unwrap(), 4096);
+        assert!(parse_file_size("MB").is_err());
+        assert!(parse_file_size("0").is_err());
+    }
+}
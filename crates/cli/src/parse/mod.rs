@@ -1,5 +1,6 @@
 mod args;
 mod blocks;
+mod config;
 mod execution;
 mod file_output;
 mod parse_utils;
@@ -10,4 +11,7 @@ mod source;
 // mod transactions;
 
 pub use args::*;
+pub(crate) use blocks::parse_static_block_range;
+pub(crate) use file_output::{parse_file_size, parse_merge_output};
 use schemas::*;
+pub(crate) use source::parse_rpc_chains;
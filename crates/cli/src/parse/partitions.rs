@@ -4,8 +4,8 @@ use super::{
 };
 use crate::args::Args;
 use cryo_freeze::{
-    AddressChunk, CallDataChunk, Datatype, Dim, Fetcher, ParseError, Partition, PartitionLabels,
-    SlotChunk, Table, TimeDimension, TopicChunk, TransactionChunk,
+    AddressChunk, CallDataChunk, ChunkData, Datatype, Dim, Fetcher, ParseError, Partition,
+    PartitionLabels, SlotChunk, Table, TimeDimension, TopicChunk, TransactionChunk,
 };
 use ethers::prelude::*;
 use std::{collections::HashMap, str::FromStr, sync::Arc};
@@ -33,12 +33,51 @@ pub(crate) async fn parse_partitions<P: JsonRpcClient>(
     let (topic1_labels, topic1s) = parse_topic(&args.topic1, "topic1")?;
     let (topic2_labels, topic2s) = parse_topic(&args.topic2, "topic2")?;
     let (topic3_labels, topic3s) = parse_topic(&args.topic3, "topic3")?;
+    let beacon_slots = blocks::parse_beacon_slots(args)?;
+    let beacon_slot_labels = None;
+
+    // give each value of the dimensions of --partition its own chunk
+    let split_dims = parse_split_dims(args)?;
+    let split =
+        |dim: Dim, (labels, chunks): (Option<ChunkLabels>, Option<Vec<AddressChunk>>)| match (
+            split_dims.contains(&dim),
+            chunks,
+        ) {
+            (true, Some(chunks)) => Ok(split_values(&chunks)),
+            (true, None) => Err(ParseError::ParseError(format!(
+                "--partition {} requires values of {}",
+                dim,
+                dim.plural_name()
+            ))),
+            (false, chunks) => Ok((labels, chunks)),
+        };
+    let (address_labels, addresses) = split(Dim::Address, (address_labels, addresses))?;
+    let (contract_labels, contracts) = split(Dim::Contract, (contract_labels, contracts))?;
+    let (to_address_labels, to_addresses) =
+        split(Dim::ToAddress, (to_address_labels, to_addresses))?;
+    let (slot_labels, slots) = split(Dim::Slot, (slot_labels, slots))?;
+    let (topic0_labels, topic0s) = split(Dim::Topic0, (topic0_labels, topic0s))?;
+    let (topic1_labels, topic1s) = split(Dim::Topic1, (topic1_labels, topic1s))?;
+    let (topic2_labels, topic2s) = split(Dim::Topic2, (topic2_labels, topic2s))?;
+    let (topic3_labels, topic3s) = split(Dim::Topic3, (topic3_labels, topic3s))?;
 
     // set default blocks
-    let block_numbers = if block_numbers.is_none() && transactions.is_none() {
-        Some(blocks::get_default_block_chunks(args, fetcher, schemas).await?)
-    } else {
-        block_numbers
+    let block_numbers =
+        if block_numbers.is_none() && transactions.is_none() && beacon_slots.is_none() {
+            Some(blocks::get_default_block_chunks(args, fetcher, schemas).await?)
+        } else {
+            block_numbers
+        };
+    let block_numbers = match (args.append, block_numbers, &block_number_labels) {
+        (true, Some(_), Some(_)) => {
+            return Err(ParseError::ParseError(
+                "--append cannot be used with block files".to_string(),
+            ))
+        }
+        (true, Some(block_numbers), None) => {
+            Some(blocks::remove_collected_blocks(block_numbers, args, schemas)?)
+        }
+        (_, block_numbers, _) => block_numbers,
     };
 
     // aggregate chunk data
@@ -55,6 +94,7 @@ pub(crate) async fn parse_partitions<P: JsonRpcClient>(
         topic1s,
         topic2s,
         topic3s,
+        beacon_slots,
     };
     let labels = PartitionLabels {
         block_number_labels,
@@ -68,6 +108,7 @@ pub(crate) async fn parse_partitions<P: JsonRpcClient>(
         topic1_labels,
         topic2_labels,
         topic3_labels,
+        beacon_slot_labels,
     };
     let time_dimension = parse_time_dimension(&chunk);
 
@@ -85,6 +126,8 @@ pub(crate) async fn parse_partitions<P: JsonRpcClient>(
                 .collect();
             if args.txs.is_some() {
                 vec![Dim::TransactionHash]
+            } else if args.beacon_slots.is_some() {
+                vec![Dim::BeaconSlot]
             } else if multichunk_dims.is_empty() {
                 vec![Dim::BlockNumber]
             } else {
@@ -92,12 +135,69 @@ pub(crate) async fn parse_partitions<P: JsonRpcClient>(
             }
         }
     };
+    // files of each value of the dimensions of --partition are chunked like other files
+    let partition_by = match (split_dims.is_empty(), &args.partition_by) {
+        (true, _) => partition_by,
+        (false, Some(_)) => {
+            let other_dims = partition_by.into_iter().filter(|dim| !split_dims.contains(dim));
+            split_dims.iter().cloned().chain(other_dims).collect()
+        }
+        (false, None) => {
+            let time_dim = match time_dimension {
+                TimeDimension::Transactions => Dim::TransactionHash,
+                TimeDimension::Blocks if chunk.beacon_slots.is_some() => Dim::BeaconSlot,
+                TimeDimension::Blocks => Dim::BlockNumber,
+            };
+            [split_dims, vec![time_dim]].concat()
+        }
+    };
     let partitions = chunk
         .partition_with_labels(labels, partition_by.clone())
         .map_err(|e| ParseError::ParseError(format!("could not partition labels ({})", e)));
     Ok((partitions?, partition_by, time_dimension))
 }
 
+/// dimensions whose values each have their own series of files
+fn parse_split_dims(args: &Args) -> Result<Vec<Dim>, ParseError> {
+    let mut dims = Vec::new();
+    for name in args.partition.iter().flatten() {
+        let dim = match Dim::from_str(name) {
+            Ok(
+                dim @ (Dim::Address |
+                Dim::Contract |
+                Dim::ToAddress |
+                Dim::Slot |
+                Dim::Topic0 |
+                Dim::Topic1 |
+                Dim::Topic2 |
+                Dim::Topic3),
+            ) => dim,
+            _ => {
+                return Err(ParseError::ParseError(format!(
+                    "invalid --partition {}, must be address, contract, to_address, slot, or \
+                     topic0 through topic3",
+                    name
+                )))
+            }
+        };
+        if !dims.contains(&dim) {
+            dims.push(dim);
+        }
+    }
+    Ok(dims)
+}
+
+/// split chunks of binary values into one chunk per value, labeled by its value
+fn split_values(chunks: &[AddressChunk]) -> (Option<ChunkLabels>, Option<Vec<AddressChunk>>) {
+    let chunks = AddressChunk::split_values(chunks);
+    let labels = chunks
+        .iter()
+        .flat_map(|chunk| chunk.values())
+        .map(|value| Some(format!("0x{}", hex::encode(value))))
+        .collect();
+    (Some(labels), Some(chunks))
+}
+
 fn parse_time_dimension(partition: &Partition) -> TimeDimension {
     if partition.transactions.is_some() {
         TimeDimension::Transactions
@@ -201,3 +301,28 @@ fn parse_topic(
         Ok((None, None))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap_cryo::Parser;
+
+    #[test]
+    fn test_split_values() {
+        let args =
+            Args::parse_from(["cryo", "logs", "--partition", "contract", "topic0", "contract"]);
+        assert_eq!(parse_split_dims(&args).unwrap(), vec![Dim::Contract, Dim::Topic0]);
+        let args = Args::parse_from(["cryo", "logs", "--partition", "block"]);
+        assert!(parse_split_dims(&args).is_err());
+
+        let chunks = vec![
+            AddressChunk::Values(vec![vec![0xaa; 20], vec![0xbb; 20]]),
+            AddressChunk::Values(vec![vec![0xaa; 20]]),
+        ];
+        let (labels, chunks) = split_values(&chunks);
+        let address = |byte: &str| Some(format!("0x{}", byte.repeat(20)));
+        assert_eq!(labels, Some(vec![address("aa"), address("bb")]));
+        let values: Vec<_> = chunks.unwrap().iter().map(|chunk| chunk.values()).collect();
+        assert_eq!(values, vec![vec![vec![0xaa; 20]], vec![vec![0xbb; 20]]]);
+    }
+}
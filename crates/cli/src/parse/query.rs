@@ -11,6 +11,7 @@ pub(crate) async fn parse_query<P: JsonRpcClient>(
     let schemas = parse_schemas(args)?;
     let (partitions, partitioned_by, time_dimension) =
         partitions::parse_partitions(args, fetcher, &schemas).await?;
-    let datatypes = cryo_freeze::cluster_datatypes(schemas.keys().cloned().collect());
+    let datatypes =
+        cryo_freeze::cluster_partition_datatypes(schemas.keys().cloned().collect(), &partitions);
     Ok(Query { datatypes, schemas, time_dimension, partitions, partitioned_by })
 }
@@ -1,8 +1,10 @@
 use std::collections::{HashMap, HashSet};
 
-use cryo_freeze::{ColumnEncoding, Datatype, FileFormat, ParseError, Table};
+use cryo_freeze::{
+    ColumnEncoding, Datatype, DerivedColumn, ErrorDecoder, FunctionDecoder, FunctionSignatures,
+    JsTracer, LogDecoder, ParseError, RowFilter, Table, TraceFilter, WasmTransform,
+};
 
-use super::file_output;
 use crate::args::Args;
 use cryo_freeze::U256Type;
 use std::str::FromStr;
@@ -12,6 +14,11 @@ fn parse_datatypes(raw_inputs: &Vec<String>) -> Result<Vec<Datatype>, ParseError
 
     for raw_input in raw_inputs {
         match raw_input.as_str() {
+            "account_states" => {
+                datatypes.push(Datatype::Balances);
+                datatypes.push(Datatype::Codes);
+                datatypes.push(Datatype::Nonces);
+            }
             "state_diffs" => {
                 datatypes.push(Datatype::BalanceDiffs);
                 datatypes.push(Datatype::CodeDiffs);
@@ -28,28 +35,132 @@ pub(crate) fn parse_schemas(args: &Args) -> Result<HashMap<Datatype, Table>, Par
     // parse inputs
     let datatypes = parse_datatypes(&args.datatype)?;
     let sort = parse_sort_columns(&args.sort, &datatypes)?;
+    let columns = parse_dataset_columns(&args.columns, &datatypes)?;
     let u256_types = parse_u256_types(args)?;
-    let output_format = file_output::parse_output_format(args)?;
-    let binary_column_format = match args.hex | (output_format != FileFormat::Parquet) {
-        true => ColumnEncoding::Hex,
-        false => ColumnEncoding::Binary,
+    let checksum_columns = args.checksum_addresses.clone().unwrap_or_default();
+    let binary_column_format = if args.checksum_addresses.is_some() && checksum_columns.is_empty() {
+        ColumnEncoding::ChecksumHex
+    } else if args.hex {
+        ColumnEncoding::Hex
+    } else {
+        ColumnEncoding::Binary
+    };
+    let trace_filter = match &args.trace_filter {
+        Some(trace_filter) => {
+            let trace_filter = TraceFilter::from_str(trace_filter)?;
+            let filtered: Vec<_> = datatypes
+                .iter()
+                .filter(|datatype| matches!(datatype, Datatype::Traces | Datatype::VmTraces))
+                .collect();
+            if filtered.is_empty() {
+                return Err(ParseError::ParseError(
+                    "--trace-filter only applies to traces and vm_traces".to_string(),
+                ))
+            }
+            for datatype in filtered.into_iter() {
+                trace_filter.check_supported(datatype)?;
+            }
+            Some(trace_filter)
+        }
+        None => None,
+    };
+    let derived_columns = args
+        .derive
+        .iter()
+        .flatten()
+        .map(|raw| DerivedColumn::from_str(raw))
+        .collect::<Result<Vec<_>, _>>()?;
+    let row_filters = parse_row_filters(args)?;
+    let transforms = parse_transforms(args)?;
+    let sql = parse_sql(args, &datatypes)?;
+    let function_signatures = match &args.signature_file {
+        Some(path) => Some(FunctionSignatures::from_file(std::path::Path::new(path))?),
+        None if args.function_signatures => Some(FunctionSignatures::bundled()),
+        None => None,
+    };
+    let function_decoder = args.function_abi.clone().map(FunctionDecoder::new).transpose()?;
+    let error_decoder = args.error_abi.clone().map(ErrorDecoder::new).transpose()?;
+    let log_decoder = args
+        .event_signature
+        .as_deref()
+        .map(LogDecoder::from_event)
+        .transpose()
+        .map_err(ParseError::ParseError)?;
+    let js_tracer = match &args.js_tracer {
+        Some(path) => {
+            Some(JsTracer::new(path.clone(), &args.js_tracer_column.clone().unwrap_or_default())?)
+        }
+        None if datatypes.contains(&Datatype::JavascriptTraces) => {
+            return Err(ParseError::ParseError(
+                "javascript_traces requires a tracer, specify one with --js-tracer".to_string(),
+            ))
+        }
+        None => None,
     };
 
     // create schemas
     let schemas: Result<HashMap<Datatype, Table>, ParseError> = datatypes
         .iter()
         .map(|datatype| {
+            let include_columns = match args.adjust_decimals {
+                true => add_decimal_columns(&args.include_columns, datatype),
+                false => args.include_columns.clone(),
+            };
+            let include_columns = match function_signatures {
+                Some(_) => add_function_signature_columns(&include_columns, datatype),
+                None => include_columns,
+            };
+            let include_columns = match args.excluded_address {
+                Some(_) => {
+                    add_circulating_supply_columns(&include_columns, datatype, args.adjust_decimals)
+                }
+                None => include_columns,
+            };
+            let include_columns = match datatype {
+                Datatype::VmTraces => add_vm_trace_columns(&include_columns, args),
+                _ => include_columns,
+            };
             datatype
                 .table_schema(
                     &u256_types,
                     &binary_column_format,
-                    &args.include_columns,
+                    &include_columns,
                     &args.exclude_columns,
-                    &args.columns,
+                    &columns[datatype],
                     sort[datatype].clone(),
-                    None,
+                    log_decoder.clone().filter(|_| datatype == &Datatype::Logs),
                 )
-                .map(|schema| (*datatype, schema))
+                .map(|mut schema| {
+                    if matches!(datatype, Datatype::Traces | Datatype::VmTraces) {
+                        schema.trace_filter = trace_filter.clone();
+                    }
+                    // columns included without --function-signatures use the bundled signatures
+                    if schema.has_column("function_signature") | schema.has_column("function_name")
+                    {
+                        schema.function_signatures = function_signatures
+                            .clone()
+                            .or_else(|| Some(FunctionSignatures::bundled()));
+                    }
+                    if matches!(
+                        datatype,
+                        Datatype::Transactions | Datatype::Traces | Datatype::TraceCalls
+                    ) {
+                        schema.function_decoder = function_decoder.clone();
+                    }
+                    if datatype == &Datatype::JavascriptTraces {
+                        schema.js_tracer = js_tracer.clone();
+                    }
+                    if matches!(datatype, Datatype::TraceErrors | Datatype::EthCalls) {
+                        schema.error_decoder = error_decoder.clone();
+                    }
+                    schema.transform =
+                        transforms.get(&Some(*datatype)).or(transforms.get(&None)).cloned();
+                    schema.sql = sql
+                        .as_ref()
+                        .filter(|(sql_datatype, _)| sql_datatype == datatype)
+                        .map(|(_, query)| query.clone());
+                    (*datatype, schema)
+                })
                 .map_err(|e| {
                     ParseError::ParseError(format!(
                         "Failed to get schema for datatype: {:?}, {:?}",
@@ -59,6 +170,21 @@ pub(crate) fn parse_schemas(args: &Args) -> Result<HashMap<Datatype, Table>, Par
         })
         .collect();
 
+    // append derived columns, then filter rows, which may reference derived columns
+    let schemas = schemas.and_then(|mut schemas| {
+        add_checksum_columns(&checksum_columns, &mut schemas)?;
+        add_derived_columns(&derived_columns, &mut schemas)?;
+        for schema in schemas.values_mut() {
+            schema.row_filter = get_row_filter(&row_filters, schema);
+        }
+        Ok(schemas)
+    });
+
+    // make sure each row filter applies to a collected dataset with the filtered columns
+    if let Ok(schemas) = &schemas {
+        ensure_row_filters(&row_filters, schemas)?
+    };
+
     // make sure all included columns ended up in at least one schema
     if let (Ok(schemas), Some(include_columns)) = (&schemas, &args.include_columns) {
         ensure_included_columns(include_columns, schemas)?
@@ -72,6 +198,72 @@ pub(crate) fn parse_schemas(args: &Args) -> Result<HashMap<Datatype, Table>, Par
     schemas
 }
 
+/// add decimal-adjusted value columns of datatype to include columns
+fn add_decimal_columns(
+    include_columns: &Option<Vec<String>>,
+    datatype: &Datatype,
+) -> Option<Vec<String>> {
+    let decimal_column = match datatype {
+        Datatype::Erc20Transfers => "value_float",
+        Datatype::Erc20Balances => "balance_float",
+        Datatype::Erc20Holders => "balance_float",
+        Datatype::Erc20Supplies => "total_supply_float",
+        Datatype::ChainlinkPrices => "answer_float",
+        _ => return include_columns.clone(),
+    };
+    let mut include_columns = include_columns.clone().unwrap_or_default();
+    include_columns.push(decimal_column.to_string());
+    Some(include_columns)
+}
+
+/// add function signature columns of datatype to include columns
+fn add_function_signature_columns(
+    include_columns: &Option<Vec<String>>,
+    datatype: &Datatype,
+) -> Option<Vec<String>> {
+    if !matches!(datatype, Datatype::Transactions | Datatype::Traces | Datatype::TraceCalls) {
+        return include_columns.clone()
+    }
+    let mut include_columns = include_columns.clone().unwrap_or_default();
+    include_columns.push("function_signature".to_string());
+    include_columns.push("function_name".to_string());
+    Some(include_columns)
+}
+
+/// add vm_traces columns of the memory, stack, and storage captures enabled by args
+fn add_vm_trace_columns(include_columns: &Option<Vec<String>>, args: &Args) -> Option<Vec<String>> {
+    let captures = [
+        (args.vm_trace_memory, vec!["mem_off", "mem_data"]),
+        (args.vm_trace_stack, vec!["push"]),
+        (args.vm_trace_storage, vec!["storage_key", "storage_val"]),
+    ];
+    if captures.iter().all(|(enabled, _)| !enabled) {
+        return include_columns.clone()
+    }
+    let mut include_columns = include_columns.clone().unwrap_or_default();
+    for (_, columns) in captures.into_iter().filter(|(enabled, _)| *enabled) {
+        include_columns.extend(columns.into_iter().map(String::from));
+    }
+    Some(include_columns)
+}
+
+/// add circulating supply columns of datatype to include columns
+fn add_circulating_supply_columns(
+    include_columns: &Option<Vec<String>>,
+    datatype: &Datatype,
+    adjust_decimals: bool,
+) -> Option<Vec<String>> {
+    if datatype != &Datatype::Erc20Supplies {
+        return include_columns.clone()
+    }
+    let mut include_columns = include_columns.clone().unwrap_or_default();
+    include_columns.push("circulating_supply".to_string());
+    if adjust_decimals {
+        include_columns.push("circulating_supply_float".to_string());
+    }
+    Some(include_columns)
+}
+
 fn parse_u256_types(args: &Args) -> Result<HashSet<U256Type>, ParseError> {
     if let Some(raw_u256_types) = args.u256_types.clone() {
         let mut u256_types: HashSet<U256Type> = HashSet::new();
@@ -157,6 +349,224 @@ fn ensure_excluded_columns(
     Ok(())
 }
 
+/// parse transforms, keyed by datatype or by None for transforms that apply to all datatypes
+fn parse_transforms(args: &Args) -> Result<HashMap<Option<Datatype>, WasmTransform>, ParseError> {
+    let mut transforms = HashMap::new();
+    for raw_transform in args.transform.clone().unwrap_or_default().iter() {
+        let (datatype, path) = match raw_transform.split_once('=') {
+            Some((datatype, path)) => match Datatype::from_str(datatype) {
+                Ok(datatype) => (Some(datatype), path),
+                Err(_) => (None, raw_transform.as_str()),
+            },
+            None => (None, raw_transform.as_str()),
+        };
+        if transforms.contains_key(&datatype) {
+            return Err(ParseError::ParseError(
+                "only one transform can be specified per dataset".to_string(),
+            ))
+        }
+        transforms.insert(datatype, WasmTransform::from_file(path.into())?);
+    }
+    Ok(transforms)
+}
+
+/// encode given columns as checksummed addresses in each schema that has them
+fn add_checksum_columns(
+    columns: &[String],
+    schemas: &mut HashMap<Datatype, Table>,
+) -> Result<(), ParseError> {
+    for column in columns.iter() {
+        let mut applied = false;
+        for schema in schemas.values_mut().filter(|schema| schema.has_column(column)) {
+            schema.set_column_encoding(column, ColumnEncoding::ChecksumHex).map_err(|_| {
+                ParseError::ParseError(format!(
+                    "cannot checksum non-binary column {} of {}",
+                    column,
+                    schema.datatype.name()
+                ))
+            })?;
+            applied = true;
+        }
+        if !applied {
+            return Err(ParseError::ParseError(format!(
+                "checksummed column {} is not in collected datasets",
+                column
+            )))
+        }
+    }
+    Ok(())
+}
+
+/// append each derived column to the schemas that have the columns it references
+fn add_derived_columns(
+    derived_columns: &[DerivedColumn],
+    schemas: &mut HashMap<Datatype, Table>,
+) -> Result<(), ParseError> {
+    for derived_column in derived_columns.iter() {
+        let mut applied = false;
+        for schema in schemas.values_mut() {
+            if derived_column.applies_to(schema) {
+                schema.add_derived_column(derived_column.clone())?;
+                applied = true;
+            }
+        }
+        if !applied {
+            return Err(ParseError::ParseError(format!(
+                "derived column {} references columns not in collected datasets: {}",
+                derived_column.name,
+                derived_column.expression.columns().join(", ")
+            )))
+        }
+    }
+    Ok(())
+}
+
+/// parse row filters, keyed by datatype or by None for filters that apply to all datatypes
+///
+/// multiple filters of the same key are combined with AND
+fn parse_row_filters(args: &Args) -> Result<HashMap<Option<Datatype>, RowFilter>, ParseError> {
+    let mut row_filters: HashMap<Option<Datatype>, RowFilter> = HashMap::new();
+    for raw_filter in args.filter.clone().unwrap_or_default().iter() {
+        let (datatype, raw_filter) = match raw_filter.split_once('=') {
+            Some((datatype, filter)) => match Datatype::from_str(datatype.trim()) {
+                Ok(datatype) => (Some(datatype), filter),
+                Err(_) => (None, raw_filter.as_str()),
+            },
+            None => (None, raw_filter.as_str()),
+        };
+        let row_filter = RowFilter::from_str(raw_filter)?;
+        let row_filter = match row_filters.remove(&datatype) {
+            Some(previous) => previous.and(row_filter),
+            None => row_filter,
+        };
+        row_filters.insert(datatype, row_filter);
+    }
+    Ok(row_filters)
+}
+
+/// get row filter of schema, where filters of all datatypes apply to schemas with their columns
+fn get_row_filter(
+    row_filters: &HashMap<Option<Datatype>, RowFilter>,
+    schema: &Table,
+) -> Option<RowFilter> {
+    let shared = row_filters.get(&None).filter(|f| has_filter_columns(f, schema)).cloned();
+    match (row_filters.get(&Some(schema.datatype)).cloned(), shared) {
+        (Some(row_filter), Some(shared)) => Some(row_filter.and(shared)),
+        (row_filter, shared) => row_filter.or(shared),
+    }
+}
+
+/// whether schema has each column of filter, where u256 columns may have a type suffix
+fn has_filter_columns(row_filter: &RowFilter, schema: &Table) -> bool {
+    row_filter.columns().iter().all(|column| {
+        schema.has_column(column) ||
+            schema.u256_types.iter().any(|u256_type| {
+                column.strip_suffix(&u256_type.suffix()).is_some_and(|c| schema.has_column(c))
+            })
+    })
+}
+
+fn ensure_row_filters(
+    row_filters: &HashMap<Option<Datatype>, RowFilter>,
+    schemas: &HashMap<Datatype, Table>,
+) -> Result<(), ParseError> {
+    for (datatype, row_filter) in row_filters.iter() {
+        let applies = match datatype {
+            Some(datatype) => {
+                schemas.get(datatype).is_some_and(|s| has_filter_columns(row_filter, s))
+            }
+            None => schemas.values().any(|schema| has_filter_columns(row_filter, schema)),
+        };
+        if !applies {
+            return Err(ParseError::ParseError(format!(
+                "filter references columns not in collected datasets: {}",
+                row_filter.columns().join(", ")
+            )))
+        }
+    }
+    Ok(())
+}
+
+/// get sql query and the collected datatype whose chunks it runs over
+///
+/// queries run over each chunk of a single dataset, so they must reference exactly one of the
+/// collected datasets as a table, after `from` or `join`. names within string literals and
+/// comments are not tables
+fn parse_sql(
+    args: &Args,
+    datatypes: &[Datatype],
+) -> Result<Option<(Datatype, String)>, ParseError> {
+    let query = match &args.sql {
+        Some(query) => query,
+        None => return Ok(None),
+    };
+    let invalid_regex = |e: regex::Error| ParseError::ParseError(e.to_string());
+    let code = regex::Regex::new(r"'(?:[^']|'')*'|--[^\n]*|/\*(?s:.)*?\*/")
+        .map_err(invalid_regex)?
+        .replace_all(query, " ");
+    let tables = regex::Regex::new(r#"(?i)\b(?:from|join)\s+"?(\w+)"?"#).map_err(invalid_regex)?;
+    let mut referenced: Vec<Datatype> = Vec::new();
+    for captures in tables.captures_iter(&code) {
+        let table = captures[1].to_lowercase();
+        if let Some(datatype) = datatypes.iter().find(|datatype| datatype.name() == table) {
+            if !referenced.contains(datatype) {
+                referenced.push(*datatype)
+            }
+        }
+    }
+    match referenced[..] {
+        [datatype] => Ok(Some((datatype, query.clone()))),
+        [] => Err(ParseError::ParseError(
+            "sql query does not reference any of the collected datasets as a table".to_string(),
+        )),
+        _ => {
+            let names: Vec<_> = referenced.iter().map(|datatype| datatype.name()).collect();
+            Err(ParseError::ParseError(format!(
+                "sql query references several collected datasets ({}), but queries run over the \
+                chunks of a single dataset",
+                names.join(", ")
+            )))
+        }
+    }
+}
+
+/// parse columns of each datatype
+///
+/// entries of the form DATASET=COL1,COL2 select the columns of a single dataset, other entries
+/// select the columns of every dataset without its own selection
+fn parse_dataset_columns(
+    raw_columns: &Option<Vec<String>>,
+    datatypes: &[Datatype],
+) -> Result<HashMap<Datatype, Option<Vec<String>>>, ParseError> {
+    let mut shared_columns: Option<Vec<String>> = None;
+    let mut dataset_columns: HashMap<Datatype, Vec<String>> = HashMap::new();
+    for raw in raw_columns.iter().flatten() {
+        let (datatype, raw) = match raw.split_once('=') {
+            Some((datatype, raw)) => (Some(Datatype::from_str(datatype)?), raw),
+            None => (None, raw.as_str()),
+        };
+        let columns = raw.split(',').map(|c| c.trim()).filter(|c| !c.is_empty());
+        let columns = columns.map(|c| c.to_string());
+        match datatype {
+            Some(datatype) if !datatypes.contains(&datatype) => {
+                return Err(ParseError::ParseError(format!(
+                    "columns specified for dataset that is not collected: {}",
+                    datatype.name()
+                )))
+            }
+            Some(datatype) => dataset_columns.entry(datatype).or_default().extend(columns),
+            None => shared_columns.get_or_insert_with(Vec::new).extend(columns),
+        }
+    }
+    Ok(datatypes
+        .iter()
+        .map(|datatype| {
+            let columns = dataset_columns.get(datatype).cloned().or(shared_columns.clone());
+            (*datatype, columns)
+        })
+        .collect())
+}
+
 fn parse_sort_columns(
     raw_sort: &Option<Vec<String>>,
     datatypes: &Vec<Datatype>,
@@ -185,3 +595,49 @@ fn parse_sort_columns(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap_cryo::Parser;
+
+    #[test]
+    fn test_parse_dataset_columns() {
+        let datatypes = vec![Datatype::Blocks, Datatype::Logs, Datatype::Transactions];
+        let raw = ["blocks=number,timestamp", "logs=address,topic0", "logs=data", "hash"];
+        let raw = Some(raw.iter().map(|s| s.to_string()).collect());
+        let columns = parse_dataset_columns(&raw, &datatypes).unwrap();
+        assert_eq!(columns[&Datatype::Blocks], Some(vec!["number".into(), "timestamp".into()]));
+        assert_eq!(
+            columns[&Datatype::Logs],
+            Some(vec!["address".into(), "topic0".into(), "data".into()])
+        );
+        assert_eq!(columns[&Datatype::Transactions], Some(vec!["hash".into()]));
+
+        let raw = Some(vec!["traces=action_from".to_string()]);
+        assert!(parse_dataset_columns(&raw, &datatypes).is_err());
+        assert_eq!(parse_dataset_columns(&None, &datatypes).unwrap()[&Datatype::Logs], None);
+    }
+
+    #[test]
+    fn test_parse_sql() {
+        let datatypes = vec![Datatype::Blocks, Datatype::Logs];
+        let sql = |query: &str| {
+            let args = Args::parse_from(["cryo", "blocks", "logs", "--sql", query]);
+            parse_sql(&args, &datatypes)
+        };
+        let query = "select * from logs where block_number > 100 and address != 'from blocks'";
+        assert_eq!(sql(query).unwrap(), Some((Datatype::Logs, query.to_string())));
+        let query = "with counts as (select block_number, count(*) as n from LOGS group by 1) \
+                     select * from counts";
+        assert_eq!(sql(query).unwrap().map(|(datatype, _)| datatype), Some(Datatype::Logs));
+
+        // joins of collected datasets and queries of no collected dataset are rejected
+        assert!(sql("select * from blocks join logs using (block_number)").is_err());
+        assert!(
+            sql("select * from logs where block_number in (select number from blocks)").is_err()
+        );
+        assert!(sql("select * from traces -- from logs").is_err());
+        assert_eq!(parse_sql(&Args::parse_from(["cryo", "logs"]), &datatypes).unwrap(), None);
+    }
+}
@@ -2,44 +2,107 @@ use std::env;
 
 use ethers::prelude::*;
 use governor::{Quota, RateLimiter};
-use polars::prelude::*;
-use std::num::NonZeroU32;
+use std::{
+    collections::HashMap,
+    num::NonZeroU32,
+    str::FromStr,
+    sync::{Arc, Mutex},
+};
 
-use cryo_freeze::{Fetcher, ParseError, Source};
+use cryo_freeze::{
+    autotune_host, default_autotune_path, get_chain, get_chain_by_name, read_binary_column,
+    read_learned_concurrency, AutoTuner, BlockCache, Capabilities, ChainInfo, Datatype, DiskCache,
+    Fetcher, MulticallBatcher, NftMarketplace, ParseError, PoolEvent, PoolTokens, Query,
+    RpcBatcher, RpcTransport, Source, SourceVerifier, SwapProtocol, VerificationProvider,
+    AUTOTUNE_INITIAL_CONCURRENCY, CHAINS, DEFAULT_CHUNK_SIZE, DISK_CACHE_CONFIRMATIONS,
+    LOG_SPLIT_ERRORS, PROPOSER_DUTIES_CACHE_EPOCHS, TIMESTAMP_CACHE_BLOCKS,
+};
 
+use super::parse_utils::hex_strings_to_binary;
 use crate::args::Args;
 
+const DEFAULT_TRACE_CACHE_BLOCKS: usize = 128;
+
+/// default rate limits of source verification apis, within their free tiers
+const DEFAULT_SOURCIFY_REQUESTS_PER_SECOND: u32 = 10;
+const DEFAULT_ETHERSCAN_REQUESTS_PER_SECOND: u32 = 5;
+
+/// maximum number of token ids of a --token-id range
+const MAX_TOKEN_ID_RANGE: u64 = 1_000_000;
+
 pub(crate) async fn parse_source(args: &Args) -> Result<Source, ParseError> {
     // parse network info
-    let rpc_url = parse_rpc_url(args);
-    let provider =
-        Provider::<RetryClient<Http>>::new_client(&rpc_url, args.max_retries, args.initial_backoff)
-            .map_err(|_e| ParseError::ParseError("could not connect to provider".to_string()))?;
+    let network = parse_network(args)?;
+    // requests replayed from fixtures or read from a reth database are not sent to a node
+    let rpc_url = match args.replay_fixtures.as_ref().or(args.reth_db.as_ref()) {
+        Some(path) => path.clone(),
+        None => parse_rpc_url(args, network),
+    };
+    let http_client = parse_http_client(args, false)?;
+    let rpc_client = parse_http_client(args, true)?;
+    let max_concurrent_requests = args.max_concurrent_requests.unwrap_or(100);
+    let autotune = parse_autotune(args, &rpc_url, max_concurrent_requests);
+    let provider = parse_provider(args, &rpc_url, &rpc_client, autotune.clone())?;
     let chain_id = provider.get_chainid().await.map_err(ParseError::ProviderError)?.as_u64();
+    if let Some(network) = network {
+        if network.chain_id != chain_id {
+            return Err(ParseError::ParseError(format!(
+                "rpc has chain id {} but --network {} has chain id {}",
+                chain_id, network.name, network.chain_id
+            )))
+        }
+    }
 
-    let rate_limiter = match args.requests_per_second {
-        Some(rate_limit) => match NonZeroU32::new(rate_limit) {
-            Some(value) => {
-                let quota = Quota::per_second(value);
-                Some(RateLimiter::direct(quota))
-            }
-            _ => None,
-        },
-        None => None,
-    };
+    let rate_limiter = parse_rate_limiter(args.requests_per_second);
 
     // process concurrency info
-    let max_concurrent_requests = args.max_concurrent_requests.unwrap_or(100);
     let max_concurrent_chunks = match args.max_concurrent_chunks {
         Some(0) => None,
         Some(max) => Some(max),
         None => Some(4),
     };
 
-    let semaphore = tokio::sync::Semaphore::new(max_concurrent_requests as usize);
+    let initial_concurrency = autotune.as_ref().map_or(max_concurrent_requests, |t| t.limit());
+    let semaphore = tokio::sync::Semaphore::new(initial_concurrency as usize);
     let semaphore = Some(semaphore);
 
-    let fetcher = Fetcher { provider, semaphore, rate_limiter };
+    // responses of blocks within the reorg buffer of the chain head are never cached
+    let disk_cache = match &args.cache_dir {
+        Some(cache_dir) => {
+            let latest_block =
+                provider.get_block_number().await.map_err(ParseError::ProviderError)?.as_u64();
+            let confirmations = DISK_CACHE_CONFIRMATIONS.max(args.reorg_buffer);
+            Some(DiskCache {
+                cache_dir: cache_dir.into(),
+                chain_id,
+                max_block: latest_block.saturating_sub(confirmations),
+            })
+        }
+        None => None,
+    };
+
+    let fetcher = Fetcher {
+        provider,
+        semaphore,
+        rate_limiter,
+        n_requests: Default::default(),
+        rpc_stats: Default::default(),
+        disk_cache,
+        parent: None,
+        autotune,
+        coalescer: (!args.no_request_dedup).then(Default::default),
+        middleware: Vec::new(),
+    };
+    // batches are sent around the transport, so they are only sent to nodes over http
+    let batch_endpoint =
+        fetcher.provider.as_ref().is_http().then_some((&rpc_client, rpc_url.as_str()));
+    let capabilities = Capabilities::probe(&fetcher, batch_endpoint).await;
+    let rpc_batcher = match (args.rpc_batch_size, capabilities.batch_requests) {
+        (Some(batch_size), true) => {
+            Some(Arc::new(RpcBatcher::new(batch_size, rpc_client, rpc_url.clone())))
+        }
+        _ => None,
+    };
     let output = Source {
         fetcher: Arc::new(fetcher),
         chain_id,
@@ -48,14 +111,419 @@ pub(crate) async fn parse_source(args: &Args) -> Result<Source, ParseError> {
         max_concurrent_chunks,
         max_requests_per_second: args.requests_per_second.map(|x| x as u64),
         rpc_url,
+        erc20_decimals: Default::default(),
+        http_client,
+        relay_urls: parse_relay_urls(args),
+        beacon_url: args.beacon_url.clone(),
+        multicall: args.multicall.map(|batch_size| Arc::new(MulticallBatcher::new(batch_size))),
+        use_get_proof: args.get_proof,
+        validate_continuity: args.validate_continuity,
+        bloom_prefilter: args.bloom_prefilter,
+        trace_cache: None,
+        timestamp_cache: args
+            .include_timestamps
+            .then(|| Arc::new(BlockCache::new(TIMESTAMP_CACHE_BLOCKS))),
+        proposer_duties: Arc::new(BlockCache::new(PROPOSER_DUTIES_CACHE_EPOCHS)),
+        capabilities,
+        log_split_errors: parse_log_split_errors(args),
+        rpc_batcher,
+        excluded_addresses: parse_excluded_addresses(args)?,
+        token_ids: parse_token_ids(args)?,
+        erc20_transfers_dir: args.transfers_dir.as_ref().map(std::path::PathBuf::from),
+        source_verifier: parse_source_verifier(args)?,
+        pool_events: parse_pool_events(args)?,
+        swap_protocols: parse_swap_protocols(args)?,
+        pool_tokens: Arc::new(Mutex::new(parse_pools_files(args)?)),
+        nft_marketplaces: parse_nft_marketplaces(args)?,
+        chainlink_aggregators: Default::default(),
+        dataset_sources: Default::default(),
     };
 
     Ok(output)
 }
 
-fn parse_rpc_url(args: &Args) -> String {
-    let mut url = match &args.rpc {
-        Some(url) => url.clone(),
+/// provider of rpc, of the fixtures of --replay-fixtures, or of the reth database of --reth-db,
+/// recording responses of rpc to the fixtures of --record-fixtures
+fn parse_provider(
+    args: &Args,
+    rpc_url: &str,
+    rpc_client: &reqwest::Client,
+    autotune: Option<Arc<AutoTuner>>,
+) -> Result<Provider<RpcTransport>, ParseError> {
+    let transport_error = |e: cryo_freeze::CollectError| ParseError::ParseError(e.to_string());
+    if let Some(path) = &args.replay_fixtures {
+        let transport =
+            RpcTransport::replay(std::path::Path::new(path)).map_err(transport_error)?;
+        return Ok(Provider::new(transport))
+    }
+    if let Some(path) = &args.reth_db {
+        let transport =
+            RpcTransport::database("reth", std::path::Path::new(path)).map_err(transport_error)?;
+        return Ok(Provider::new(transport))
+    }
+    let url: reqwest::Url = rpc_url
+        .parse()
+        .map_err(|_e| ParseError::ParseError("could not connect to provider".to_string()))?;
+    let policy: Box<dyn RetryPolicy<HttpClientError>> = match autotune {
+        Some(tuner) => Box::new(AutotuneRetryPolicy(tuner)),
+        None => Box::new(HttpRateLimitRetryPolicy),
+    };
+    let client = RetryClient::new(
+        Http::new_with_client(url, rpc_client.clone()),
+        policy,
+        args.max_retries,
+        args.initial_backoff,
+    );
+    let record_fixtures = args.record_fixtures.as_deref().map(std::path::Path::new);
+    Ok(Provider::new(RpcTransport::http(client, record_fixtures).map_err(transport_error)?))
+}
+
+/// tuner of request concurrency, starting from the concurrency learned for the provider host by
+/// earlier runs if there is one
+fn parse_autotune(
+    args: &Args,
+    rpc_url: &str,
+    max_concurrent_requests: u64,
+) -> Option<Arc<AutoTuner>> {
+    if !args.autotune {
+        return None
+    }
+    let learned = default_autotune_path()
+        .zip(autotune_host(rpc_url))
+        .and_then(|(path, host)| read_learned_concurrency(&path, &host));
+    let initial = learned.unwrap_or(AUTOTUNE_INITIAL_CONCURRENCY);
+    Some(Arc::new(AutoTuner::new(initial, 1, max_concurrent_requests)))
+}
+
+/// retry policy that retries rate limited requests like `HttpRateLimitRetryPolicy`, reporting
+/// each of them to the tuner of request concurrency
+#[derive(Debug)]
+struct AutotuneRetryPolicy(Arc<AutoTuner>);
+
+impl RetryPolicy<HttpClientError> for AutotuneRetryPolicy {
+    fn should_retry(&self, error: &HttpClientError) -> bool {
+        let retry = HttpRateLimitRetryPolicy.should_retry(error);
+        if retry {
+            self.0.record_throttled();
+        }
+        retry
+    }
+
+    fn backoff_hint(&self, error: &HttpClientError) -> Option<std::time::Duration> {
+        HttpRateLimitRetryPolicy.backoff_hint(error)
+    }
+}
+
+fn parse_rate_limiter(requests_per_second: Option<u32>) -> Option<cryo_freeze::RateLimiter> {
+    let quota = Quota::per_second(NonZeroU32::new(requests_per_second?)?);
+    Some(RateLimiter::direct(quota))
+}
+
+/// request limits and sizes of --dataset-option that apply to a single dataset
+#[derive(Default)]
+struct DatasetOptions {
+    max_concurrent_requests: Option<u64>,
+    requests_per_second: Option<u32>,
+    inner_request_size: Option<u64>,
+}
+
+/// sources of datasets given options of their own with --dataset-option DATASET.KEY=VALUE
+///
+/// requests of these datasets are limited by both their own limits and the limits of source
+pub(crate) fn parse_dataset_sources(
+    args: &Args,
+    query: &Query,
+    source: &Source,
+) -> Result<HashMap<Datatype, Arc<Source>>, ParseError> {
+    let mut options: HashMap<Datatype, DatasetOptions> = HashMap::new();
+    for raw in args.dataset_option.iter().flatten() {
+        let invalid = |message: &str| {
+            ParseError::ParseError(format!("invalid --dataset-option {}: {}", raw, message))
+        };
+        let (key, value) =
+            raw.split_once('=').ok_or_else(|| invalid("expected DATASET.KEY=VALUE"))?;
+        let (dataset, key) =
+            key.split_once('.').ok_or_else(|| invalid("expected DATASET.KEY=VALUE"))?;
+        let datatype = Datatype::from_str(dataset)?;
+        if !query.datatypes.iter().any(|meta| meta.datatypes().contains(&datatype)) {
+            return Err(invalid("dataset is not collected"))
+        }
+        let entry = options.entry(datatype).or_default();
+        let number = || value.parse::<u64>().map_err(|_| invalid("value must be an integer"));
+        match key {
+            "max_concurrent_requests" => entry.max_concurrent_requests = Some(number()?),
+            "requests_per_second" => {
+                entry.requests_per_second =
+                    Some(number()?.try_into().map_err(|_| invalid("too large"))?)
+            }
+            "inner_request_size" => entry.inner_request_size = Some(number()?),
+            _ => return Err(invalid(
+                "key must be max_concurrent_requests, requests_per_second, or inner_request_size",
+            )),
+        }
+    }
+
+    let mut sources = HashMap::new();
+    for (datatype, options) in options.into_iter() {
+        let fetcher = match (options.max_concurrent_requests, options.requests_per_second) {
+            (None, None) => source.fetcher.clone(),
+            (max_concurrent_requests, requests_per_second) => Arc::new(Fetcher {
+                provider: parse_provider(
+                    args,
+                    &source.rpc_url,
+                    &parse_http_client(args, true)?,
+                    source.fetcher.autotune.clone(),
+                )?,
+                semaphore: max_concurrent_requests
+                    .map(|max| tokio::sync::Semaphore::new(max as usize)),
+                rate_limiter: parse_rate_limiter(requests_per_second),
+                n_requests: Default::default(),
+                rpc_stats: Default::default(),
+                disk_cache: source.fetcher.disk_cache.clone(),
+                parent: Some(source.fetcher.clone()),
+                autotune: None,
+                coalescer: source.fetcher.coalescer.clone(),
+                middleware: Vec::new(),
+            }),
+        };
+        let dataset_source = Source {
+            fetcher,
+            inner_request_size: options.inner_request_size.unwrap_or(source.inner_request_size),
+            max_concurrent_requests: options
+                .max_concurrent_requests
+                .or(source.max_concurrent_requests),
+            max_requests_per_second: options
+                .requests_per_second
+                .map(|x| x as u64)
+                .or(source.max_requests_per_second),
+            ..source.clone()
+        };
+        sources.insert(datatype, Arc::new(dataset_source));
+    }
+    Ok(sources)
+}
+
+fn parse_excluded_addresses(args: &Args) -> Result<Vec<Vec<u8>>, ParseError> {
+    let addresses = hex_strings_to_binary(args.excluded_address.as_deref().unwrap_or_default())?;
+    match addresses.iter().all(|address| address.len() == 20) {
+        true => Ok(addresses),
+        false => Err(ParseError::ParseError("excluded addresses must be 20 bytes".to_string())),
+    }
+}
+
+fn parse_pool_events(args: &Args) -> Result<Vec<PoolEvent>, ParseError> {
+    let mut pool_events = PoolEvent::defaults();
+    for raw in args.pool_event.iter().flatten() {
+        pool_events.push(PoolEvent::new(raw)?);
+    }
+    Ok(pool_events)
+}
+
+fn parse_swap_protocols(args: &Args) -> Result<Vec<SwapProtocol>, ParseError> {
+    match &args.dex {
+        Some(names) => names.iter().map(|name| SwapProtocol::from_name(name)).collect(),
+        None => Ok(SwapProtocol::all()),
+    }
+}
+
+fn parse_nft_marketplaces(args: &Args) -> Result<Vec<NftMarketplace>, ParseError> {
+    match &args.marketplace {
+        Some(names) => names.iter().map(|name| NftMarketplace::from_name(name)).collect(),
+        None => Ok(NftMarketplace::all()),
+    }
+}
+
+/// load tokens of pools from files written by the pools dataset
+fn parse_pools_files(args: &Args) -> Result<PoolTokens, ParseError> {
+    let mut pool_tokens = PoolTokens::new();
+    for path in args.pools_file.iter().flatten() {
+        let read_column = |column: &str| {
+            read_binary_column(path, column).map_err(|_| {
+                ParseError::ParseError(format!("could not read {} column of {}", column, path))
+            })
+        };
+        let (pools, token0s, token1s) =
+            (read_column("pool")?, read_column("token0")?, read_column("token1")?);
+        for ((pool, token0), token1) in pools.into_iter().zip(token0s).zip(token1s) {
+            pool_tokens.insert((pool.clone(), 0), Some(token0));
+            pool_tokens.insert((pool, 1), Some(token1));
+        }
+    }
+    Ok(pool_tokens)
+}
+
+/// parse token ids, given as ids or as START:END ranges excluding END
+fn parse_token_ids(args: &Args) -> Result<Vec<U256>, ParseError> {
+    let parse_id = |id: &str| match id.strip_prefix("0x") {
+        Some(hex) => U256::from_str_radix(hex, 16).ok(),
+        None => U256::from_dec_str(id).ok(),
+    };
+    let mut token_ids = Vec::new();
+    for input in args.token_id.as_deref().unwrap_or_default() {
+        let invalid = || ParseError::ParseError(format!("invalid token id: {}", input));
+        match input.split_once(':') {
+            Some((start, end)) => {
+                let (start, end) =
+                    (parse_id(start).ok_or_else(invalid)?, parse_id(end).ok_or_else(invalid)?);
+                if end <= start || end - start > U256::from(MAX_TOKEN_ID_RANGE) {
+                    return Err(ParseError::ParseError(format!(
+                        "token id range must contain between 1 and {} ids: {}",
+                        MAX_TOKEN_ID_RANGE, input
+                    )))
+                }
+                let mut token_id = start;
+                while token_id < end {
+                    token_ids.push(token_id);
+                    token_id += U256::one();
+                }
+            }
+            None => token_ids.push(parse_id(input).ok_or_else(invalid)?),
+        }
+    }
+    Ok(token_ids)
+}
+
+fn parse_log_split_errors(args: &Args) -> Vec<String> {
+    let extra = args.log_split_error.clone().unwrap_or_default();
+    LOG_SPLIT_ERRORS
+        .iter()
+        .map(|pattern| pattern.to_string())
+        .chain(extra.iter().map(|pattern| pattern.to_lowercase()))
+        .collect()
+}
+
+/// cache trace_block responses when several datasets are collected, unless disabled
+pub(crate) fn parse_trace_cache(args: &Args, query: &Query) -> Option<Arc<BlockCache<Vec<Trace>>>> {
+    let capacity = match args.trace_cache_blocks {
+        Some(capacity) => capacity,
+        None if query.datatypes.len() > 1 => DEFAULT_TRACE_CACHE_BLOCKS,
+        None => 0,
+    };
+    match capacity {
+        0 => None,
+        capacity => Some(Arc::new(BlockCache::new(capacity))),
+    }
+}
+
+fn parse_source_verifier(args: &Args) -> Result<Option<Arc<SourceVerifier>>, ParseError> {
+    let provider = match &args.verify_sources {
+        Some(provider) => VerificationProvider::from_str(provider)
+            .map_err(|e| ParseError::ParseError(e.to_string()))?,
+        None => return Ok(None),
+    };
+    let api_key = match provider {
+        VerificationProvider::Etherscan => {
+            args.verify_api_key.clone().or_else(|| env::var("ETHERSCAN_API_KEY").ok())
+        }
+        VerificationProvider::Sourcify => args.verify_api_key.clone(),
+    };
+    let requests_per_second = args.verify_requests_per_second.or(match provider {
+        VerificationProvider::Sourcify => Some(DEFAULT_SOURCIFY_REQUESTS_PER_SECOND),
+        VerificationProvider::Etherscan => Some(DEFAULT_ETHERSCAN_REQUESTS_PER_SECOND),
+    });
+    let verifier =
+        SourceVerifier::new(provider, args.verify_url.clone(), api_key, requests_per_second)
+            .map_err(|e| ParseError::ParseError(e.to_string()))?;
+    Ok(Some(Arc::new(verifier)))
+}
+
+fn parse_relay_urls(args: &Args) -> Vec<String> {
+    match &args.relay_url {
+        Some(relay_urls) => relay_urls.clone(),
+        None => vec!["https://boost-relay.flashbots.net".to_string()],
+    }
+}
+
+/// build http client with the proxy, timeout, and tls settings of args, including the rpc
+/// headers of args only if the client is used for rpc requests
+fn parse_http_client(
+    args: &Args,
+    include_rpc_headers: bool,
+) -> Result<reqwest::Client, ParseError> {
+    let mut builder = reqwest::Client::builder();
+    if include_rpc_headers {
+        let mut headers = reqwest::header::HeaderMap::new();
+        for header in args.rpc_header.iter().flatten() {
+            let (name, value) = header.split_once(':').ok_or_else(|| {
+                ParseError::ParseError(format!("rpc header must be 'Name: value', got {}", header))
+            })?;
+            let name = reqwest::header::HeaderName::from_bytes(name.trim().as_bytes())
+                .map_err(|_| ParseError::ParseError(format!("invalid header name: {}", name)))?;
+            let value = reqwest::header::HeaderValue::from_str(value.trim())
+                .map_err(|_| ParseError::ParseError(format!("invalid value of header {}", name)))?;
+            headers.insert(name, value);
+        }
+        builder = builder.default_headers(headers);
+    }
+    if let Some(timeout) = args.rpc_timeout {
+        builder = builder.timeout(std::time::Duration::from_secs(timeout));
+    }
+    if let Some(proxy) = &args.rpc_proxy {
+        let proxy = reqwest::Proxy::all(proxy)
+            .map_err(|_| ParseError::ParseError(format!("invalid proxy url: {}", proxy)))?;
+        builder = builder.proxy(proxy);
+    }
+    if let Some(path) = &args.rpc_ca_cert {
+        let pem = std::fs::read(path)
+            .map_err(|_| ParseError::ParseError(format!("could not read certificate {}", path)))?;
+        let certificate = reqwest::Certificate::from_pem(&pem)
+            .map_err(|_| ParseError::ParseError(format!("invalid certificate {}", path)))?;
+        builder = builder.add_root_certificate(certificate);
+    }
+    if args.rpc_insecure {
+        builder = builder.danger_accept_invalid_certs(true);
+    }
+    builder
+        .build()
+        .map_err(|e| ParseError::ParseError(format!("could not build http client: {}", e)))
+}
+
+/// split rpc args into chains, each with a name if given as name=url
+pub(crate) fn parse_rpc_chains(args: &Args) -> Vec<(Option<String>, String)> {
+    args.rpc.iter().flatten().map(|rpc| split_chain_name(rpc)).collect()
+}
+
+fn split_chain_name(rpc: &str) -> (Option<String>, String) {
+    match rpc.split_once('=') {
+        Some((name, url))
+            if !name.is_empty() &&
+                name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-') =>
+        {
+            (Some(name.to_string()), url.to_string())
+        }
+        _ => (None, rpc.to_string()),
+    }
+}
+
+/// parse number of blocks per file, scaling the default by the block time of known chains
+pub(crate) fn parse_chunk_size(args: &Args, chain_id: u64) -> u64 {
+    match (args.chunk_size, get_chain(chain_id)) {
+        (Some(chunk_size), _) => chunk_size,
+        (None, Some(chain)) => chain.default_chunk_size(),
+        (None, None) => DEFAULT_CHUNK_SIZE,
+    }
+}
+
+/// parse chain of --network
+fn parse_network(args: &Args) -> Result<Option<&'static ChainInfo>, ParseError> {
+    match &args.network {
+        Some(name) => match get_chain_by_name(name) {
+            Some(chain) => Ok(Some(chain)),
+            None => Err(ParseError::ParseError(format!(
+                "unknown network: {}, known networks are {}",
+                name,
+                CHAINS.iter().map(|chain| chain.name).collect::<Vec<_>>().join(", ")
+            ))),
+        },
+        None => Ok(None),
+    }
+}
+
+fn parse_rpc_url(args: &Args, network: Option<&ChainInfo>) -> String {
+    let default_rpc = network.and_then(|network| network.default_rpc);
+    let mut url = match (parse_rpc_chains(args).into_iter().next(), default_rpc) {
+        (Some((_, url)), _) => url,
+        (None, Some(url)) => url.to_string(),
         _ => match env::var("ETH_RPC_URL") {
             Ok(url) => url,
             Err(_e) => {
@@ -69,3 +537,20 @@ fn parse_rpc_url(args: &Args) -> String {
     };
     url
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_chain_name() {
+        assert_eq!(
+            split_chain_name("arb=https://arb.example"),
+            (Some("arb".to_string()), "https://arb.example".to_string())
+        );
+        assert_eq!(
+            split_chain_name("https://example/?key=abc"),
+            (None, "https://example/?key=abc".to_string())
+        );
+    }
+}
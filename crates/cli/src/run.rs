@@ -1,16 +1,569 @@
 use crate::{args, parse};
-use cryo_freeze::{CollectError, ExecutionEnv, FreezeSummary};
-use std::{sync::Arc, time::SystemTime};
+use cryo_freeze::{
+    autotune_host, default_autotune_path, find_block_gaps, find_empty_block_ranges,
+    find_file_block_ranges, format_binary_columns, inspect_file, list_output_files, merge_files,
+    plan_merges, print_chain_summaries, query_output_dir, read_file_metadata, recheck_partitions,
+    write_dataframe, write_learned_concurrency, CollectError, Datatype, ExecutionEnv,
+    ExistingFilePolicy, FileOutput, FreezeSummary, InspectMode, OutputLocation, ParseError, Source,
+    METADATA_PREFIX,
+};
+use opentelemetry::KeyValue;
+use opentelemetry_otlp::WithExportConfig;
+use opentelemetry_sdk::Resource;
+use std::{str::FromStr, sync::Arc, time::SystemTime};
+use tracing_subscriber::{
+    filter::LevelFilter, layer::SubscriberExt, util::SubscriberInitExt, Layer,
+};
 
 /// run cli
 pub async fn run(args: args::Args) -> Result<Option<FreezeSummary>, CollectError> {
     let t_start_parse = Some(SystemTime::now());
+    if args.no_color {
+        colored::control::set_override(false);
+    }
+    if args.datatype.first().map(|s| s.as_str()) == Some("gaps") {
+        return run_gaps(&args).map(|_| None)
+    }
+    if args.datatype.first().map(|s| s.as_str()) == Some("cat") {
+        return run_cat(&args).map(|_| None)
+    }
+    if args.datatype.first().map(|s| s.as_str()) == Some("meta") {
+        return run_meta(&args).map(|_| None)
+    }
+    if args.datatype.first().map(|s| s.as_str()) == Some("query") {
+        return run_query(&args).map(|_| None)
+    }
+    if args.datatype.first().map(|s| s.as_str()) == Some("merge") {
+        return run_merge(&args).map(|_| None)
+    }
+    if args.datatype.first().map(|s| s.as_str()) == Some("bench") {
+        return run_bench(&args).await.map(|_| None)
+    }
+    if args.datatype.first().map(|s| s.as_str()) == Some("flight") {
+        return crate::flight::run_flight(&args).await.map(|_| None)
+    }
+    if args.datatype.first().map(|s| s.as_str()) == Some("schedule") {
+        return crate::schedule::run_schedule(&args).await.map(|_| None)
+    }
+    if args.datatype.first().map(|s| s.as_str()) == Some("serve") {
+        return crate::serve::run_serve(&args).await.map(|_| None)
+    }
+    if args.datatype.first().map(|s| s.as_str()) == Some("tail") {
+        return crate::tail::run_tail(&args).await.map(|_| None)
+    }
+    if crate::mempool::is_mempool(&args) {
+        return crate::mempool::run_mempool(&args).await.map(|_| None)
+    }
+    let exporting_spans = init_tracing(&args)?;
+    let result = run_collect(args, t_start_parse).await;
+    if exporting_spans {
+        // flush spans that are still buffered by the batch exporter
+        let _ = tokio::task::spawn_blocking(opentelemetry::global::shutdown_tracer_provider).await;
+    }
+    result
+}
+
+/// run collection of datatypes, against each chain of args
+pub(crate) async fn run_collect(
+    args: args::Args,
+    t_start_parse: Option<SystemTime>,
+) -> Result<Option<FreezeSummary>, CollectError> {
+    let chains = parse::parse_rpc_chains(&args);
+    match chains.len() > 1 {
+        true => run_chains(args, chains, t_start_parse).await,
+        false => run_chain(args, t_start_parse).await,
+    }
+}
+
+/// report block ranges missing from the output files of each datatype of `cryo gaps`
+fn run_gaps(args: &args::Args) -> Result<(), CollectError> {
+    let datatypes = &args.datatype[1..];
+    if datatypes.is_empty() {
+        return Err(CollectError::CollectError("usage: cryo gaps DATATYPE [-b BLOCKS]".to_string()))
+    }
+    let bounds = match args.blocks.as_deref() {
+        Some([blocks]) => Some(parse::parse_static_block_range(blocks)?),
+        Some(_) => {
+            return Err(CollectError::CollectError(
+                "cryo gaps accepts a single block range".to_string(),
+            ))
+        }
+        None => None,
+    };
+    let output_dir = &OutputLocation::parse(&args.output_dir)?.local_dir();
+    for datatype in datatypes.iter() {
+        let datatype = Datatype::from_str(datatype)?;
+        let files = find_file_block_ranges(output_dir, &datatype)?;
+        let empty = find_empty_block_ranges(output_dir, &datatype)?;
+        let ranges: Vec<(u64, u64)> =
+            files.iter().chain(empty.iter()).map(|f| (f.start_block, f.end_block)).collect();
+        let gaps = find_block_gaps(&ranges, bounds);
+        match empty.len() {
+            0 => {
+                println!("{} files of {} in {}", files.len(), datatype.name(), output_dir.display())
+            }
+            n_empty => println!(
+                "{} files and {} empty chunks of {} in {}",
+                files.len(),
+                n_empty,
+                datatype.name(),
+                output_dir.display()
+            ),
+        }
+        if gaps.is_empty() {
+            println!("no missing blocks");
+            continue
+        }
+        let n_missing: u64 = gaps.iter().map(|(start, end)| end - start + 1).sum();
+        println!("{} missing blocks in {} ranges:", n_missing, gaps.len());
+        for (start, end) in gaps.iter() {
+            println!("- {} to {} ({} blocks)", start, end, end - start + 1);
+        }
+        let blocks: Vec<String> =
+            gaps.iter().map(|(start, end)| format!("{}:{}", start, end + 1)).collect();
+        println!("fill with: --blocks {}", blocks.join(" "));
+    }
+    Ok(())
+}
+
+/// merge small output files of each datatype of `cryo merge` into files of about --merge-size
+fn run_merge(args: &args::Args) -> Result<(), CollectError> {
+    let datatypes = &args.datatype[1..];
+    if datatypes.is_empty() {
+        return Err(CollectError::CollectError(
+            "usage: cryo merge DATATYPE [--merge-size SIZE] [--hive-partition COLUMN]".to_string(),
+        ))
+    }
+    let datatypes = datatypes
+        .iter()
+        .map(|datatype| Datatype::from_str(datatype))
+        .collect::<Result<Vec<_>, _>>()?;
+    let target_size = parse::parse_file_size(&args.merge_size)?;
+    let sink = parse::parse_merge_output(args, &datatypes)?;
+    for datatype in datatypes.into_iter() {
+        let files = find_file_block_ranges(&sink.output_dir, &datatype)?;
+        let dedup_keys = sink.dedup_keys.get(&datatype).map(|keys| keys.as_slice());
+        let groups = plan_merges(&files, &datatype, target_size, dedup_keys.is_some())?;
+        let n_inputs: usize = groups.iter().map(|group| group.inputs.len()).sum();
+        println!(
+            "merging {} of {} files of {} into {} files",
+            n_inputs,
+            files.len(),
+            datatype.name(),
+            groups.len()
+        );
+        let sort = datatype.default_sort();
+        for group in groups.iter() {
+            let hive_column = args.hive_partition.as_deref();
+            let paths = merge_files(group, &sink, Some(&sort), dedup_keys, hive_column)?;
+            for path in paths.iter() {
+                println!("- {}", path.display());
+            }
+        }
+    }
+    Ok(())
+}
+
+/// print head, tail, schema, or row count of output files given to `cryo cat`
+fn run_cat(args: &args::Args) -> Result<(), CollectError> {
+    let usage = || {
+        CollectError::CollectError(
+            "usage: cryo cat [head [N] | tail [N] | schema | count] PATH...".to_string(),
+        )
+    };
+    let tokens = &args.datatype[1..];
+    let (mode, paths) = match tokens.first().map(|s| s.as_str()) {
+        Some("schema") => (InspectMode::Schema, &tokens[1..]),
+        Some("count") => (InspectMode::Count, &tokens[1..]),
+        Some(name @ ("head" | "tail")) => {
+            let (n, paths) = match tokens.get(1).and_then(|n| n.parse().ok()) {
+                Some(n) => (n, &tokens[2..]),
+                None => (10, &tokens[1..]),
+            };
+            match name {
+                "head" => (InspectMode::Head(n), paths),
+                _ => (InspectMode::Tail(n), paths),
+            }
+        }
+        _ => (InspectMode::Head(10), tokens),
+    };
+    if paths.is_empty() {
+        return Err(usage())
+    }
+    if let InspectMode::Head(n) | InspectMode::Tail(n) = mode {
+        std::env::set_var("POLARS_FMT_MAX_ROWS", n.to_string());
+    }
+    if std::env::var("POLARS_FMT_STR_LEN").is_err() {
+        // show full hashes instead of truncating them
+        std::env::set_var("POLARS_FMT_STR_LEN", "66");
+    }
+    let mut files = Vec::new();
+    for path in paths.iter() {
+        files.extend(list_output_files(std::path::Path::new(path))?);
+    }
+    for file in files.iter() {
+        let output = inspect_file(file, mode)?;
+        match (files.len(), mode) {
+            (1, _) => println!("{}", output),
+            (_, InspectMode::Count) => println!("{}: {}", file.display(), output),
+            _ => println!("{}\n{}\n", file.display(), output),
+        }
+    }
+    Ok(())
+}
+
+/// print the provenance metadata of the parquet files of `cryo meta`
+fn run_meta(args: &args::Args) -> Result<(), CollectError> {
+    let paths = &args.datatype[1..];
+    if paths.is_empty() {
+        return Err(CollectError::CollectError("usage: cryo meta PATH...".to_string()))
+    }
+    let mut files = Vec::new();
+    for path in paths.iter() {
+        files.extend(list_output_files(std::path::Path::new(path))?);
+    }
+    files.retain(|file| file.extension().and_then(|e| e.to_str()) == Some("parquet"));
+    if files.is_empty() {
+        return Err(CollectError::CollectError(
+            "no parquet files given, only parquet files record metadata".to_string(),
+        ))
+    }
+    for (i, file) in files.iter().enumerate() {
+        let metadata = read_file_metadata(file)?;
+        if files.len() > 1 {
+            if i > 0 {
+                println!();
+            }
+            println!("{}", file.display());
+        }
+        if metadata.is_empty() {
+            println!("no metadata, file was not written by this version of cryo");
+        }
+        for (key, value) in metadata.iter() {
+            println!("{}: {}", key.strip_prefix(METADATA_PREFIX).unwrap_or(key), value);
+        }
+    }
+    Ok(())
+}
+
+/// run sql of `cryo query` over the output files of output dir
+fn run_query(args: &args::Args) -> Result<(), CollectError> {
+    let query = args.datatype[1..].join(" ");
+    if query.trim().is_empty() {
+        return Err(CollectError::CollectError(
+            "usage: cryo query \"SQL\" [-o DIR] [--query-output FILE]".to_string(),
+        ))
+    }
+    let mut df = query_output_dir(&OutputLocation::parse(&args.output_dir)?.local_dir(), &query)?;
+    match &args.query_output {
+        Some(path) => {
+            write_dataframe(&mut df, std::path::Path::new(path))?;
+            println!("wrote {} rows to {}", df.height(), path);
+        }
+        None => {
+            if std::env::var("POLARS_FMT_STR_LEN").is_err() {
+                std::env::set_var("POLARS_FMT_STR_LEN", "66");
+            }
+            println!("{}", format_binary_columns(df, None)?);
+        }
+    }
+    Ok(())
+}
+
+/// datatypes collected by `cryo bench` when none are given
+const BENCH_DATATYPES: [&str; 3] = ["blocks", "logs", "traces"];
+
+/// blocks collected by `cryo bench` when none are given
+const BENCH_BLOCKS: &str = "-20:latest";
+
+/// concurrency levels at which `cryo bench` collects each datatype
+const BENCH_CONCURRENCY: [u64; 3] = [4, 16, 64];
+
+/// time a short collection of each datatype of `cryo bench` at increasing concurrency, then
+/// recommend the concurrency of each datatype
+///
+/// each block is collected as its own chunk into a temporary directory, with at most as many
+/// concurrent chunks and requests as the concurrency level
+async fn run_bench(args: &args::Args) -> Result<(), CollectError> {
+    let datatypes: Vec<String> = match args.datatype.len() {
+        1 => BENCH_DATATYPES.iter().map(|datatype| datatype.to_string()).collect(),
+        _ => args.datatype[1..].to_vec(),
+    };
+    let blocks = args.blocks.clone().unwrap_or_else(|| vec![BENCH_BLOCKS.to_string()]);
+    let mut levels: Vec<u64> = BENCH_CONCURRENCY
+        .into_iter()
+        .filter(|level| *level <= args.max_concurrent_requests.unwrap_or(u64::MAX))
+        .collect();
+    if levels.is_empty() {
+        levels.extend(args.max_concurrent_requests);
+    }
+    let output_dir = std::env::temp_dir().join(format!("cryo_bench_{}", std::process::id()));
+
+    let mut recommended = Vec::new();
+    for datatype in datatypes.iter() {
+        let name = Datatype::from_str(datatype)?.name();
+        println!("{} over blocks {}", name, blocks.join(" "));
+        println!(
+            "{:>11}  {:>8}  {:>9}  {:>10}  {:>10}  {:>14}",
+            "concurrency", "seconds", "blocks/s", "rows/s", "requests/s", "errored chunks"
+        );
+        let mut results = Vec::new();
+        for level in levels.iter() {
+            let bench_args = args::Args {
+                datatype: vec![datatype.clone()],
+                blocks: Some(blocks.clone()),
+                chunk_size: Some(1),
+                max_concurrent_requests: Some(*level),
+                max_concurrent_chunks: Some(*level),
+                dataset_option: None,
+                output_dir: output_dir.to_string_lossy().to_string(),
+                overwrite: true,
+                no_clobber: None,
+                no_report: true,
+                no_verbose: true,
+                tui: false,
+                ..args.clone()
+            };
+            let t_start = std::time::Instant::now();
+            let summary = match run_chain(bench_args, None).await {
+                Ok(summary) => summary.unwrap_or_default(),
+                Err(e) => {
+                    println!("{:>11}  error: {}", level, e);
+                    break
+                }
+            };
+            let seconds = t_start.elapsed().as_secs_f64().max(1e-9);
+            let n_rows: u64 = summary.chunks.iter().filter_map(|chunk| chunk.n_rows).sum();
+            let n_requests: u64 = summary.rpc_methods.iter().map(|m| m.n_requests).sum();
+            let blocks_per_second = summary.completed.len() as f64 / seconds;
+            println!(
+                "{:>11}  {:>8.2}  {:>9.1}  {:>10.1}  {:>10.1}  {:>14}",
+                level,
+                seconds,
+                blocks_per_second,
+                n_rows as f64 / seconds,
+                n_requests as f64 / seconds,
+                summary.errored.len()
+            );
+            results.push((*level, blocks_per_second, summary));
+        }
+        let throughputs: Vec<(u64, f64, bool)> = results
+            .iter()
+            .map(|(level, throughput, summary)| (*level, *throughput, summary.errored.is_empty()))
+            .collect();
+        let level = match recommend_concurrency(&throughputs) {
+            Some(level) => level,
+            None => {
+                println!("no concurrency level collected {} without errors\n", name);
+                continue
+            }
+        };
+        if let Some((_, _, summary)) = results.iter().find(|(l, _, _)| *l == level) {
+            println!("latency at concurrency {}:", level);
+            for method in summary.rpc_methods.iter().filter(|method| method.n_requests > 0) {
+                println!(
+                    "- {}: {} requests, {} errors, p50 {:.1}ms, p90 {:.1}ms, p99 {:.1}ms",
+                    method.method,
+                    method.n_requests,
+                    method.n_errors,
+                    method.p50_ms,
+                    method.p90_ms,
+                    method.p99_ms
+                );
+            }
+        }
+        println!("recommended concurrency of {}: {}\n", name, level);
+        recommended.push((name, level));
+    }
+    let _ = std::fs::remove_dir_all(&output_dir);
+
+    if let Some(max) = recommended.iter().map(|(_, level)| *level).max() {
+        let options: Vec<String> = recommended
+            .iter()
+            .map(|(name, level)| format!("{}.max_concurrent_requests={}", name, level))
+            .collect();
+        println!("recommended settings:");
+        println!(
+            "--max-concurrent-requests {} --max-concurrent-chunks {} --dataset-option {}",
+            max,
+            max,
+            options.join(" ")
+        );
+    }
+    Ok(())
+}
+
+/// lowest concurrency level whose throughput is within 10% of the best throughput, among the
+/// levels whose collection did not error
+fn recommend_concurrency(results: &[(u64, f64, bool)]) -> Option<u64> {
+    let succeeded: Vec<(u64, f64)> =
+        results.iter().filter(|(_, _, ok)| *ok).map(|(level, t, _)| (*level, *t)).collect();
+    let best = succeeded.iter().map(|(_, throughput)| *throughput).fold(0.0, f64::max);
+    succeeded
+        .iter()
+        .filter(|(_, throughput)| *throughput >= 0.9 * best)
+        .map(|(level, _)| *level)
+        .min()
+}
+
+/// run query against each chain in turn, writing the outputs of each chain to a subdirectory
+/// named after it
+async fn run_chains(
+    args: args::Args,
+    chains: Vec<(Option<String>, String)>,
+    t_start_parse: Option<SystemTime>,
+) -> Result<Option<FreezeSummary>, CollectError> {
+    let mut results = Vec::new();
+    for (i, (name, url)) in chains.into_iter().enumerate() {
+        let name = name.unwrap_or_else(|| format!("chain_{}", i));
+        let output_dir = std::path::Path::new(&args.output_dir).join(&name);
+        let chain_args = args::Args {
+            rpc: Some(vec![url]),
+            network_name: Some(name.clone()),
+            output_dir: output_dir.to_string_lossy().to_string(),
+            ..args.clone()
+        };
+        if !args.no_verbose {
+            println!("collecting chain {}", name);
+        }
+        let result = run_chain(chain_args, t_start_parse).await;
+        results.push((name, result.map(|summary| summary.unwrap_or_default())));
+    }
+    if !args.no_verbose {
+        print_chain_summaries(&results);
+    }
+
+    let mut summary = FreezeSummary::default();
+    for (name, result) in results.into_iter() {
+        let chain_summary = result.map_err(|e| {
+            CollectError::CollectError(format!("collection of chain {} failed: {}", name, e))
+        })?;
+        summary.completed.extend(chain_summary.completed);
+        summary.skipped.extend(chain_summary.skipped);
+        summary.errored.extend(chain_summary.errored);
+        summary.empty.extend(chain_summary.empty);
+        summary.interrupted.extend(chain_summary.interrupted);
+        summary.rpc_methods.extend(chain_summary.rpc_methods);
+        summary.chunks.extend(chain_summary.chunks);
+    }
+    Ok(Some(summary))
+}
+
+/// run query against a single chain
+async fn run_chain(
+    args: args::Args,
+    t_start_parse: Option<SystemTime>,
+) -> Result<Option<FreezeSummary>, CollectError> {
+    let (args, recheck) = match args.datatype.first().map(|s| s.as_str()) {
+        Some("recheck") => (args::Args { datatype: args.datatype[1..].to_vec(), ..args }, true),
+        _ => (args, false),
+    };
     let (query, source, sink, env) = match parse::parse_args(&args).await {
         Ok(opts) => opts,
         Err(e) => return Err(e.into()),
     };
+    let (query, sink) = match recheck {
+        true => {
+            let query = recheck_partitions(query, &source, &sink, args.recheck_blocks).await?;
+            if query.partitions.is_empty() {
+                if !args.no_verbose {
+                    println!("no reorged chunks in last {} blocks", args.recheck_blocks);
+                }
+                return Ok(Some(FreezeSummary::default()))
+            }
+            (query, FileOutput { existing_files: ExistingFilePolicy::Overwrite, ..sink })
+        }
+        false => (query, sink),
+    };
     let source = Arc::new(source);
     let env = ExecutionEnv { t_start_parse, ..env };
     let env = env.set_start_time();
-    cryo_freeze::freeze(&query, &source, &sink, &env).await
+    let result = cryo_freeze::freeze(&query, &source, &sink, &env).await;
+
+    if !env.dry {
+        save_learned_concurrency(&source, !args.no_verbose)?;
+    }
+    result
+}
+
+/// save the concurrency learned by the tuner of source as the starting point of later runs
+/// against its provider host
+pub(crate) fn save_learned_concurrency(source: &Source, verbose: bool) -> Result<(), CollectError> {
+    let tuner = match &source.fetcher.autotune {
+        Some(tuner) => tuner,
+        None => return Ok(()),
+    };
+    if let (Some(path), Some(host)) = (default_autotune_path(), autotune_host(&source.rpc_url)) {
+        write_learned_concurrency(&path, &host, tuner.limit())?;
+        if verbose {
+            println!("learned concurrency of {}: {}", host, tuner.limit());
+        }
+    }
+    Ok(())
+}
+
+/// set up logging to file and exporting of spans to an otlp collector
+fn init_tracing(args: &args::Args) -> Result<bool, ParseError> {
+    let log_layer = match &args.log_file {
+        Some(log_file) => {
+            let file =
+                std::fs::OpenOptions::new().create(true).append(true).open(log_file).map_err(
+                    |e| ParseError::ParseError(format!("could not open log file: {}", e)),
+                )?;
+            let layer = tracing_subscriber::fmt::layer()
+                .json()
+                .with_ansi(false)
+                .with_writer(std::sync::Mutex::new(file))
+                .with_filter(LevelFilter::INFO);
+            Some(layer)
+        }
+        None => None,
+    };
+    let otlp_layer = match &args.otlp_endpoint {
+        Some(endpoint) => {
+            let tracer = otlp_tracer(endpoint)?;
+            Some(tracing_opentelemetry::layer().with_tracer(tracer).with_filter(LevelFilter::INFO))
+        }
+        None => None,
+    };
+    if log_layer.is_none() && otlp_layer.is_none() {
+        return Ok(false)
+    }
+
+    // tracing may already be initialized when run is called repeatedly, e.g. from python
+    let _ = tracing_subscriber::registry().with(log_layer).with(otlp_layer).try_init();
+    Ok(args.otlp_endpoint.is_some())
+}
+
+/// tracer that exports spans to the otlp http endpoint of a collector, e.g. jaeger or tempo
+fn otlp_tracer(endpoint: &str) -> Result<opentelemetry_sdk::trace::Tracer, ParseError> {
+    let endpoint = endpoint.trim_end_matches('/');
+    let endpoint = match endpoint.ends_with("/v1/traces") {
+        true => endpoint.to_string(),
+        false => format!("{}/v1/traces", endpoint),
+    };
+    let resource = Resource::new(vec![
+        KeyValue::new("service.name", "cryo"),
+        KeyValue::new("service.version", env!("CARGO_PKG_VERSION")),
+    ]);
+    opentelemetry_otlp::new_pipeline()
+        .tracing()
+        .with_exporter(opentelemetry_otlp::new_exporter().http().with_endpoint(endpoint))
+        .with_trace_config(opentelemetry_sdk::trace::config().with_resource(resource))
+        .install_batch(opentelemetry_sdk::runtime::Tokio)
+        .map_err(|e| ParseError::ParseError(format!("could not create otlp exporter: {}", e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_recommend_concurrency() {
+        let results = [(4, 10.0, true), (16, 37.0, true), (64, 40.0, true)];
+        assert_eq!(recommend_concurrency(&results), Some(16));
+        let results = [(4, 10.0, true), (16, 37.0, true), (64, 80.0, false)];
+        assert_eq!(recommend_concurrency(&results), Some(16));
+        assert_eq!(recommend_concurrency(&[(4, 10.0, false)]), None);
+    }
 }
@@ -0,0 +1,433 @@
+//! `cryo schedule`, collecting jobs on cron schedules
+//!
+//! - `cryo schedule add SCHEDULE DATATYPE... [OPTIONS]` adds a job collecting datatypes with
+//!   options on a cron schedule such as `"0 * * * *"`, usually with `--append` so that each run
+//!   collects only new blocks
+//! - `cryo schedule list` lists jobs, and `cryo schedule remove ID` removes a job
+//! - `cryo schedule history [ID]` lists recent runs of jobs
+//! - `cryo schedule run` runs jobs as they come due until interrupted
+//!
+//! jobs and the history of their runs are stored in `~/.cryo/schedule.sqlite`. schedules are in
+//! local time, and runs of a job that come due while jobs are running are run once afterwards
+
+use std::{path::PathBuf, str::FromStr, time::Duration};
+
+use chrono::{DateTime, Local};
+use cryo_freeze::{CollectError, FreezeSummary};
+use rusqlite::{params, Connection, OptionalExtension};
+
+use crate::{args::Args, serve::SUBCOMMANDS};
+
+/// longest time between checks for jobs added while `cryo schedule run` is waiting
+const MAX_WAIT: Duration = Duration::from_secs(60);
+
+/// number of runs listed by `cryo schedule history`
+const HISTORY_LENGTH: usize = 20;
+
+/// job stored in schedule database
+struct Job {
+    id: i64,
+    schedule: String,
+    args: Args,
+}
+
+/// run `cryo schedule` command
+pub(crate) async fn run_schedule(args: &Args) -> Result<(), CollectError> {
+    let path = default_schedule_path()
+        .ok_or_else(|| CollectError::CollectError("could not find home directory".to_string()))?;
+    let command: Vec<&str> = args.datatype[1..].iter().map(String::as_str).collect();
+    match command.as_slice() {
+        ["add", schedule, datatypes @ ..] if !datatypes.is_empty() => {
+            let id = add_job(&path, schedule, datatypes, args)?;
+            let next = next_run(&parse_schedule(schedule)?, &Local::now());
+            println!("added job {}, next run at {}", id, format_time(next));
+            Ok(())
+        }
+        ["list"] => list_jobs(&path),
+        ["remove", id] => {
+            let id = parse_id(id)?;
+            match open(&path)?.execute("DELETE FROM jobs WHERE id = ?1", [id]).map_err(db_error)? {
+                0 => Err(CollectError::CollectError(format!("no job {}", id))),
+                _ => {
+                    println!("removed job {}", id);
+                    Ok(())
+                }
+            }
+        }
+        ["history"] => list_runs(&path, None),
+        ["history", id] => list_runs(&path, Some(parse_id(id)?)),
+        ["run"] => run_jobs(&path).await,
+        _ => Err(CollectError::CollectError(
+            "usage: cryo schedule [add SCHEDULE DATATYPE... | list | remove ID | history [ID] | run]"
+                .to_string(),
+        )),
+    }
+}
+
+/// default path of the schedule database, `~/.cryo/schedule.sqlite`
+fn default_schedule_path() -> Option<PathBuf> {
+    std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".cryo/schedule.sqlite"))
+}
+
+fn open(path: &std::path::Path) -> Result<Connection, CollectError> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| {
+            CollectError::CollectError(format!("could not create {}: {}", parent.display(), e))
+        })?;
+    }
+    let connection = Connection::open(path).map_err(db_error)?;
+    connection
+        .execute_batch(
+            "CREATE TABLE IF NOT EXISTS jobs (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                schedule TEXT NOT NULL,
+                args TEXT NOT NULL,
+                created_at TEXT NOT NULL
+            );
+            CREATE TABLE IF NOT EXISTS runs (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                job_id INTEGER NOT NULL,
+                started_at TEXT NOT NULL,
+                finished_at TEXT,
+                status TEXT NOT NULL,
+                chunks_completed INTEGER,
+                chunks_skipped INTEGER,
+                chunks_errored INTEGER,
+                error TEXT
+            );",
+        )
+        .map_err(db_error)?;
+    Ok(connection)
+}
+
+/// cron schedule, with or without a leading field of seconds
+///
+/// days of the week of five-field schedules are numbered from sunday as 0 or 7 like those of
+/// crontab, while schedules with seconds number them from sunday as 1 like the cron crate
+fn parse_schedule(schedule: &str) -> Result<cron::Schedule, CollectError> {
+    let invalid =
+        |e: String| CollectError::CollectError(format!("invalid schedule {}: {}", schedule, e));
+    let expression = match schedule.split_whitespace().collect::<Vec<_>>()[..] {
+        [minute, hour, day, month, weekdays] => {
+            let weekdays = crontab_weekdays(weekdays).map_err(invalid)?;
+            format!("0 {} {} {} {} {}", minute, hour, day, month, weekdays)
+        }
+        _ => schedule.to_string(),
+    };
+    cron::Schedule::from_str(&expression).map_err(|e| invalid(e.to_string()))
+}
+
+/// days of the week of a crontab schedule, numbered 0-7 from sunday, in the numbering 1-7 from
+/// sunday of the cron crate
+///
+/// numeric ranges and steps are expanded into lists of days, since the sunday of 7 would wrap
+/// ranges ending with it, while names of days are kept
+fn crontab_weekdays(field: &str) -> Result<String, String> {
+    let mut items = Vec::new();
+    for item in field.split(',') {
+        let (range, step) = match item.split_once('/') {
+            Some((range, step)) => match step.parse::<usize>() {
+                Ok(step) if step > 0 => (range, Some(step)),
+                _ => return Err(format!("invalid step of days of week: {}", item)),
+            },
+            None => (item, None),
+        };
+        let bounds = match (range, step) {
+            ("*", None) => None,
+            ("*", Some(_)) => Some((0, 6)),
+            (range, _) => match range.split_once('-') {
+                Some((start, end)) => start.parse::<u8>().ok().zip(end.parse::<u8>().ok()),
+                None => range.parse::<u8>().ok().map(|day| (day, step.map_or(day, |_| 6))),
+            },
+        };
+        let Some((start, end)) = bounds else {
+            items.push(item.to_string());
+            continue
+        };
+        if end > 7 || start > end {
+            return Err(format!("days of week must be within 0-7: {}", item))
+        }
+        let mut days: Vec<u8> = (start..=end)
+            .step_by(step.unwrap_or(1))
+            .map(|day| match day {
+                0 | 7 => 1,
+                day => day + 1,
+            })
+            .collect();
+        days.sort();
+        days.dedup();
+        items.extend(days.iter().map(|day| day.to_string()));
+    }
+    Ok(items.join(","))
+}
+
+fn next_run(schedule: &cron::Schedule, after: &DateTime<Local>) -> Option<DateTime<Local>> {
+    schedule.after(after).next()
+}
+
+fn add_job(
+    path: &std::path::Path,
+    schedule: &str,
+    datatypes: &[&str],
+    args: &Args,
+) -> Result<i64, CollectError> {
+    parse_schedule(schedule)?;
+    if let Some(subcommand) = SUBCOMMANDS.iter().find(|subcommand| **subcommand == datatypes[0]) {
+        return Err(CollectError::CollectError(format!("{} cannot be scheduled", subcommand)))
+    }
+    // jobs are run from the directory of the daemon, so output paths are made absolute
+    let output_dir = std::path::absolute(&args.output_dir).map_err(|e| {
+        CollectError::CollectError(format!("invalid output dir {}: {}", args.output_dir, e))
+    })?;
+    let args = Args {
+        datatype: datatypes.iter().map(|datatype| datatype.to_string()).collect(),
+        output_dir: output_dir.to_string_lossy().to_string(),
+        ..args.clone()
+    };
+    let args = serde_json::to_string(&args)
+        .map_err(|e| CollectError::CollectError(format!("could not serialize job: {}", e)))?;
+    let connection = open(path)?;
+    connection
+        .execute(
+            "INSERT INTO jobs (schedule, args, created_at) VALUES (?1, ?2, ?3)",
+            params![schedule, args, Local::now().to_rfc3339()],
+        )
+        .map_err(db_error)?;
+    Ok(connection.last_insert_rowid())
+}
+
+fn read_jobs(path: &std::path::Path) -> Result<Vec<Job>, CollectError> {
+    let connection = open(path)?;
+    let mut statement =
+        connection.prepare("SELECT id, schedule, args FROM jobs ORDER BY id").map_err(db_error)?;
+    let rows = statement
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get::<_, String>(2)?)))
+        .map_err(db_error)?;
+    let mut jobs = Vec::new();
+    for row in rows {
+        let (id, schedule, args) = row.map_err(db_error)?;
+        let args = serde_json::from_str(&args)
+            .map_err(|e| CollectError::CollectError(format!("could not read job {}: {}", id, e)))?;
+        jobs.push(Job { id, schedule, args });
+    }
+    Ok(jobs)
+}
+
+fn list_jobs(path: &std::path::Path) -> Result<(), CollectError> {
+    let jobs = read_jobs(path)?;
+    if jobs.is_empty() {
+        println!("no scheduled jobs");
+        return Ok(())
+    }
+    let connection = open(path)?;
+    let now = Local::now();
+    for job in jobs.iter() {
+        let next = parse_schedule(&job.schedule).ok().and_then(|s| next_run(&s, &now));
+        let last: Option<(String, String)> = connection
+            .query_row(
+                "SELECT started_at, status FROM runs WHERE job_id = ?1 ORDER BY id DESC LIMIT 1",
+                [job.id],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .optional()
+            .map_err(db_error)?;
+        let last = match last {
+            Some((started_at, status)) => format!("{} at {}", status, started_at),
+            None => "never run".to_string(),
+        };
+        println!(
+            "{}  \"{}\"  {}  -> {}  (next run {}, last run {})",
+            job.id,
+            job.schedule,
+            job.args.datatype.join(" "),
+            job.args.output_dir,
+            format_time(next),
+            last,
+        );
+    }
+    Ok(())
+}
+
+fn list_runs(path: &std::path::Path, job_id: Option<i64>) -> Result<(), CollectError> {
+    let connection = open(path)?;
+    let mut statement = connection
+        .prepare(
+            "SELECT job_id, started_at, finished_at, status, chunks_completed, chunks_skipped,
+                chunks_errored, error
+            FROM runs WHERE ?1 IS NULL OR job_id = ?1 ORDER BY id DESC LIMIT ?2",
+        )
+        .map_err(db_error)?;
+    let rows = statement
+        .query_map(params![job_id, HISTORY_LENGTH], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, Option<String>>(2)?,
+                row.get::<_, String>(3)?,
+                row.get::<_, Option<i64>>(4)?,
+                row.get::<_, Option<i64>>(5)?,
+                row.get::<_, Option<i64>>(6)?,
+                row.get::<_, Option<String>>(7)?,
+            ))
+        })
+        .map_err(db_error)?;
+    let mut n_runs = 0;
+    for row in rows {
+        let (job_id, started_at, finished_at, status, completed, skipped, errored, error) =
+            row.map_err(db_error)?;
+        n_runs += 1;
+        let mut line = format!(
+            "job {}  {}  started {}  finished {}",
+            job_id,
+            status,
+            started_at,
+            finished_at.unwrap_or("-".to_string())
+        );
+        if let (Some(completed), Some(skipped), Some(errored)) = (completed, skipped, errored) {
+            line.push_str(&format!(
+                "  chunks: {} completed, {} skipped, {} errored",
+                completed, skipped, errored
+            ));
+        }
+        if let Some(error) = error {
+            line.push_str(&format!("  error: {}", error));
+        }
+        println!("{}", line);
+    }
+    if n_runs == 0 {
+        println!("no runs");
+    }
+    Ok(())
+}
+
+/// run jobs as they come due, until interrupted
+async fn run_jobs(path: &std::path::Path) -> Result<(), CollectError> {
+    println!("running scheduled jobs of {}", path.display());
+    let mut last_check = Local::now();
+    loop {
+        let now = Local::now();
+        let jobs = read_jobs(path)?;
+        let mut next_check = now + chrono::Duration::seconds(MAX_WAIT.as_secs() as i64);
+        let mut due = Vec::new();
+        for job in jobs.into_iter() {
+            let schedule = match parse_schedule(&job.schedule) {
+                Ok(schedule) => schedule,
+                Err(_) => continue,
+            };
+            match next_run(&schedule, &last_check) {
+                Some(next) if next <= now => due.push(job),
+                Some(next) => next_check = next_check.min(next),
+                None => {}
+            }
+        }
+        last_check = now;
+        for job in due.into_iter() {
+            run_job(path, job).await?;
+        }
+        let wait = (next_check - Local::now()).to_std().unwrap_or_default();
+        if !wait.is_zero() {
+            tokio::time::sleep(wait).await;
+        }
+    }
+}
+
+async fn run_job(path: &std::path::Path, job: Job) -> Result<(), CollectError> {
+    println!(
+        "[{}] running job {}: {}",
+        format_time(Some(Local::now())),
+        job.id,
+        job.args.datatype.join(" ")
+    );
+    let run_id = {
+        let connection = open(path)?;
+        connection
+            .execute(
+                "INSERT INTO runs (job_id, started_at, status) VALUES (?1, ?2, 'running')",
+                params![job.id, Local::now().to_rfc3339()],
+            )
+            .map_err(db_error)?;
+        connection.last_insert_rowid()
+    };
+    let result = crate::run::run_collect(job.args, Some(std::time::SystemTime::now())).await;
+    let (status, summary, error) = match result {
+        Ok(summary) => {
+            let summary = summary.unwrap_or_default();
+            let status = match summary.errored.is_empty() {
+                true => "completed",
+                false => "errored",
+            };
+            (status, Some(summary), None)
+        }
+        Err(e) => ("failed", None, Some(e.to_string())),
+    };
+    if let Some(error) = &error {
+        println!("job {} failed: {}", job.id, error);
+    }
+    let count = |f: fn(&FreezeSummary) -> usize| summary.as_ref().map(|s| f(s) as i64);
+    open(path)?
+        .execute(
+            "UPDATE runs SET finished_at = ?1, status = ?2, chunks_completed = ?3,
+                chunks_skipped = ?4, chunks_errored = ?5, error = ?6
+            WHERE id = ?7",
+            params![
+                Local::now().to_rfc3339(),
+                status,
+                count(|s| s.completed.len()),
+                count(|s| s.skipped.len()),
+                count(|s| s.errored.len()),
+                error,
+                run_id
+            ],
+        )
+        .map_err(db_error)?;
+    Ok(())
+}
+
+fn parse_id(id: &str) -> Result<i64, CollectError> {
+    id.parse().map_err(|_| CollectError::CollectError(format!("invalid job id: {}", id)))
+}
+
+fn format_time(time: Option<DateTime<Local>>) -> String {
+    match time {
+        Some(time) => time.format("%Y-%m-%d %H:%M:%S").to_string(),
+        None => "never".to_string(),
+    }
+}
+
+fn db_error(e: rusqlite::Error) -> CollectError {
+    CollectError::CollectError(format!("schedule database error: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_parse_schedule() {
+        let schedule = parse_schedule("0 * * * *").unwrap();
+        let after = Local.with_ymd_and_hms(2024, 1, 1, 10, 30, 0).unwrap();
+        let next = Local.with_ymd_and_hms(2024, 1, 1, 11, 0, 0).unwrap();
+        assert_eq!(next_run(&schedule, &after), Some(next));
+        assert!(parse_schedule("0 * * *").is_err());
+    }
+
+    #[test]
+    fn test_parse_weekday_schedule() {
+        // 2024-01-01 is a monday
+        let at = |day, hour| Local.with_ymd_and_hms(2024, 1, day, hour, 0, 0).unwrap();
+        let next = |schedule: &str, after| next_run(&parse_schedule(schedule).unwrap(), &after);
+        assert_eq!(next("0 9 * * 1-5", at(6, 0)), Some(at(8, 9)));
+        assert_eq!(next("0 9 * * 1-5", at(1, 10)), Some(at(2, 9)));
+        assert_eq!(next("0 9 * * 0", at(1, 10)), Some(at(7, 9)));
+        assert_eq!(next("0 9 * * 7", at(1, 10)), Some(at(7, 9)));
+        assert_eq!(next("0 9 * * 6-7", at(1, 10)), Some(at(6, 9)));
+        assert_eq!(next("0 9 * * 6-7", at(6, 10)), Some(at(7, 9)));
+        assert_eq!(next("0 9 * * 0,3", at(1, 10)), Some(at(3, 9)));
+        assert_eq!(next("0 9 * * */3", at(1, 10)), Some(at(3, 9)));
+        assert_eq!(next("0 9 * * Sun", at(1, 10)), Some(at(7, 9)));
+        assert_eq!(next("0 0 9 * * 1", at(1, 10)), Some(at(7, 9)));
+        assert!(parse_schedule("0 9 * * 8").is_err());
+        assert!(parse_schedule("0 9 * * 5-1").is_err());
+    }
+}
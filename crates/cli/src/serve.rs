@@ -0,0 +1,514 @@
+//! `cryo serve`, collecting jobs submitted over http
+//!
+//! - `POST /jobs` submits a job, whose body is a json object of cli options like those of
+//!   `cryo_cli::parse_json_args`, returning the id of the job
+//! - `GET /jobs` lists jobs, and `GET /jobs/{id}` reports the status and progress of a job
+//! - `DELETE /jobs/{id}` cancels a job, finishing its in-flight chunks, or dropping them if the job
+//!   was already cancelled
+//! - `GET /jobs/{id}/files/{path}` downloads an output file of a job
+//!
+//! options not given by a job are taken from the options of `cryo serve`, such as the rpc url.
+//! jobs can only set options that choose the data they collect and the format of their files,
+//! not options that choose files of the server, such as transforms, abi files, or configs.
+//! each job is written to its own directory within the output directory of `cryo serve`, and
+//! jobs are collected one at a time in the order submitted
+
+use std::{
+    collections::BTreeMap,
+    net::SocketAddr,
+    path::{Component, Path, PathBuf},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    time::SystemTime,
+};
+
+use axum::{
+    extract::{Path as UrlPath, State},
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+    routing::get,
+    Json, Router,
+};
+use cryo_freeze::{
+    file_datatype, list_output_files, CollectError, Datatype, ExecutionEnv, FreezeSummary, Shutdown,
+};
+use indicatif::ProgressBar;
+use serde_json::json;
+use tokio::sync::Semaphore;
+
+use crate::{args::Args, parse};
+
+/// address that `cryo serve` listens on if none is given
+const DEFAULT_SERVE_ADDRESS: &str = "127.0.0.1:8080";
+
+/// options that jobs can set, which choose the data that a job collects and how its files are
+/// formatted. other options are chosen by the server, since they choose files that the server
+/// reads, writes, or runs, or services other than the rpc that it connects to
+const JOB_OPTIONS: [&str; 95] = [
+    // content
+    "datatype",
+    "blocks",
+    "txs",
+    "beacon_slots",
+    "align",
+    "reorg_buffer",
+    "time_bucket",
+    "include_columns",
+    "exclude_columns",
+    "columns",
+    "u256_types",
+    "hex",
+    "no_hex_prefix",
+    "checksum_addresses",
+    "sort",
+    "derive",
+    "filter",
+    "sql",
+    // source
+    "rpc",
+    "network",
+    "rpc_header",
+    "rpc_timeout",
+    "rpc_proxy",
+    "rpc_insecure",
+    "network_name",
+    "beacon_url",
+    // acquisition
+    "requests_per_second",
+    "max_retries",
+    "initial_backoff",
+    "chunk_retries",
+    "max_concurrent_requests",
+    "autotune",
+    "max_concurrent_chunks",
+    "dataset_option",
+    "max_concurrent_writes",
+    "multicall",
+    "rpc_batch_size",
+    "get_proof",
+    "validate_continuity",
+    "bloom_prefilter",
+    "include_timestamps",
+    "trace_cache_blocks",
+    "no_request_dedup",
+    "dry",
+    "no_verbose",
+    "no_color",
+    "tui",
+    // output
+    "chunk_size",
+    "n_chunks",
+    "partition_by",
+    "partition",
+    "file_suffix",
+    "overwrite",
+    "no_clobber",
+    "allow_schema_change",
+    "manifest",
+    "pin_block_hash",
+    "skip_empty_files",
+    "csv",
+    "json",
+    "row_group_size",
+    "n_row_groups",
+    "global_sort",
+    "dedup_keys",
+    "no_stats",
+    "compression",
+    "no_report",
+    // dataset-specific
+    "address",
+    "to_address",
+    "from_address",
+    "call_data",
+    "function",
+    "inputs",
+    "slot",
+    "excluded_address",
+    "token_id",
+    "pool_event",
+    "dex",
+    "marketplace",
+    "contract",
+    "topic0",
+    "topic1",
+    "topic2",
+    "topic3",
+    "inner_request_size",
+    "log_split_error",
+    "event_signature",
+    "adjust_decimals",
+    "trace_filter",
+    "vm_trace_memory",
+    "vm_trace_stack",
+    "vm_trace_storage",
+    "function_signatures",
+    "js_tracer_column",
+    "relay_url",
+];
+
+/// options that jobs can set to signatures, but not to abi files of the server
+const SIGNATURE_OPTIONS: [&str; 2] = ["function_abi", "error_abi"];
+
+/// first datatypes of commands that are not collections
+pub(crate) const SUBCOMMANDS: [&str; 11] = [
+    "bench", "cat", "flight", "gaps", "merge", "meta", "query", "recheck", "schedule", "serve",
+    "tail",
+];
+
+pub(crate) struct Server {
+    /// options of `cryo serve`, the defaults of the options of jobs
+    defaults: Args,
+    output_dir: PathBuf,
+    jobs: Mutex<BTreeMap<String, Arc<Job>>>,
+    n_jobs: AtomicU64,
+    /// permit held by the job being collected
+    queue: Semaphore,
+}
+
+struct Job {
+    id: String,
+    args: Args,
+    output_dir: PathBuf,
+    submitted_at: SystemTime,
+    state: Mutex<JobState>,
+    /// progress of chunks, hidden since it is only reported through the api
+    bar: Arc<ProgressBar>,
+    shutdown: Arc<Shutdown>,
+}
+
+enum JobState {
+    Queued,
+    Running,
+    Completed(Box<FreezeSummary>),
+    Failed(String),
+    Cancelled,
+}
+
+impl Server {
+    /// jobs that are no longer collecting, with the datatypes of their output files
+    pub(crate) fn finished_jobs(&self) -> Vec<(String, Vec<Datatype>)> {
+        let jobs: Vec<Arc<Job>> = lock(&self.jobs).values().cloned().collect();
+        let mut finished = Vec::new();
+        for job in jobs.into_iter().filter(|job| job.is_finished()) {
+            let mut datatypes: Vec<Datatype> =
+                job_files(&job).iter().filter_map(|path| file_datatype(path)).collect();
+            datatypes.sort_by_key(|datatype| datatype.name());
+            datatypes.dedup();
+            finished.push((job.id.clone(), datatypes));
+        }
+        finished
+    }
+
+    /// output files of datatype of job, if the job is no longer collecting
+    pub(crate) fn job_files(&self, id: &str, datatype: &Datatype) -> Result<Vec<PathBuf>, String> {
+        let job = lock(&self.jobs).get(id).cloned().ok_or_else(|| format!("no job {}", id))?;
+        if !job.is_finished() {
+            return Err(format!("job {} is still collecting", id))
+        }
+        let files = job_files(&job)
+            .into_iter()
+            .filter(|path| file_datatype(path).as_ref() == Some(datatype))
+            .collect();
+        Ok(files)
+    }
+}
+
+impl Job {
+    fn is_finished(&self) -> bool {
+        !matches!(*lock(&self.state), JobState::Queued | JobState::Running)
+    }
+}
+
+/// run cryo as an http service collecting submitted jobs, `cryo serve [ADDRESS]`
+pub(crate) async fn run_serve(args: &Args) -> Result<(), CollectError> {
+    let address = args.datatype.get(1).map(String::as_str).unwrap_or(DEFAULT_SERVE_ADDRESS);
+    let address: SocketAddr = address
+        .parse()
+        .map_err(|_| CollectError::CollectError(format!("invalid address: {}", address)))?;
+    let output_dir = PathBuf::from(&args.output_dir);
+    let server = Arc::new(Server {
+        defaults: args.clone(),
+        output_dir: output_dir.clone(),
+        jobs: Mutex::new(BTreeMap::new()),
+        n_jobs: AtomicU64::new(0),
+        queue: Semaphore::new(1),
+    });
+    if let Some(flight_address) = args.flight.clone() {
+        let (defaults, jobs) = (args.clone(), server.clone());
+        tokio::spawn(async move {
+            let flight = crate::flight::serve_flight(&flight_address, defaults, Some(jobs));
+            if let Err(e) = flight.await {
+                eprintln!("{}", e);
+            }
+        });
+    }
+    let app = Router::new()
+        .route("/jobs", get(list_jobs).post(submit_job))
+        .route("/jobs/:id", get(get_job).delete(cancel_job))
+        .route("/jobs/:id/files/*path", get(get_file))
+        .with_state(server);
+    println!("serving on http://{}, writing jobs to {}", address, output_dir.display());
+    axum::Server::try_bind(&address)
+        .map_err(|e| CollectError::CollectError(format!("could not bind {}: {}", address, e)))?
+        .serve(app.into_make_service())
+        .await
+        .map_err(|e| CollectError::CollectError(format!("server error: {}", e)))
+}
+
+async fn submit_job(State(server): State<Arc<Server>>, body: String) -> Response {
+    let args = match parse_job_args(&server, &body) {
+        Ok(args) => args,
+        Err(e) => return error_response(StatusCode::BAD_REQUEST, e),
+    };
+    let n_job = server.n_jobs.fetch_add(1, Ordering::SeqCst) + 1;
+    let submitted_at = SystemTime::now();
+    let timestamp =
+        submitted_at.duration_since(SystemTime::UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+    let id = format!("{}-{}", timestamp, n_job);
+    let output_dir = server.output_dir.join(&id);
+    let args = Args { output_dir: output_dir.to_string_lossy().to_string(), ..args };
+    let job = Arc::new(Job {
+        id: id.clone(),
+        args,
+        output_dir,
+        submitted_at,
+        state: Mutex::new(JobState::Queued),
+        bar: Arc::new(ProgressBar::hidden()),
+        shutdown: Arc::new(Shutdown::default()),
+    });
+    lock(&server.jobs).insert(id.clone(), job.clone());
+    tokio::spawn(run_job(server, job));
+    (StatusCode::ACCEPTED, Json(json!({ "id": id }))).into_response()
+}
+
+/// options of job, with the options it does not give taken from the server
+fn parse_job_args(server: &Server, body: &str) -> Result<Args, String> {
+    let config: serde_json::Map<String, serde_json::Value> =
+        serde_json::from_str(body).map_err(|e| format!("body must be a json object: {}", e))?;
+    check_server_options(&config)?;
+    let args = parse::parse_json_args(body, &server.defaults).map_err(|e| e.to_string())?;
+    match args.datatype.first() {
+        Some(datatype) if SUBCOMMANDS.contains(&datatype.as_str()) => {
+            Err(format!("{} cannot be run as a job", datatype))
+        }
+        _ if args.rpc.as_ref().is_some_and(|rpc| rpc.len() > 1) => {
+            Err("jobs collect from a single rpc".to_string())
+        }
+        _ => Ok(Args { no_verbose: true, tui: false, dry: false, ..args }),
+    }
+}
+
+/// error if the options of a job or flight ticket set an option chosen by the server
+pub(crate) fn check_server_options(
+    config: &serde_json::Map<String, serde_json::Value>,
+) -> Result<(), String> {
+    for (key, value) in config.iter() {
+        if SIGNATURE_OPTIONS.contains(&key.as_str()) {
+            match value {
+                serde_json::Value::Null => {}
+                serde_json::Value::String(value) if !Path::new(value).exists() => {}
+                _ => return Err(format!("{} must be a signature", key)),
+            }
+        } else if !JOB_OPTIONS.contains(&key.as_str()) {
+            return Err(format!("{} is chosen by the server", key))
+        }
+    }
+    Ok(())
+}
+
+async fn run_job(server: Arc<Server>, job: Arc<Job>) {
+    let _permit = server.queue.acquire().await;
+    {
+        let mut state = lock(&job.state);
+        if !matches!(*state, JobState::Queued) {
+            return
+        }
+        *state = JobState::Running;
+    }
+    let result = collect_job(&job).await;
+    let mut state = lock(&job.state);
+    *state = match result {
+        Ok(summary) if job.shutdown.is_requested() && !summary.interrupted.is_empty() => {
+            JobState::Cancelled
+        }
+        Ok(summary) => JobState::Completed(Box::new(summary)),
+        Err(e) => JobState::Failed(e.to_string()),
+    };
+}
+
+async fn collect_job(job: &Job) -> Result<FreezeSummary, CollectError> {
+    let (query, source, sink, env) = parse::parse_args(&job.args).await?;
+    let source = Arc::new(source);
+    let env = ExecutionEnv {
+        verbose: false,
+        bar: Some(job.bar.clone()),
+        shutdown: Some(job.shutdown.clone()),
+        ..env
+    };
+    let summary = cryo_freeze::freeze(&query, &source, &sink, &env.set_start_time()).await?;
+    crate::run::save_learned_concurrency(&source, false)?;
+    Ok(summary.unwrap_or_default())
+}
+
+async fn list_jobs(State(server): State<Arc<Server>>) -> Response {
+    let jobs: Vec<_> = lock(&server.jobs).values().map(|job| job_status(job)).collect();
+    Json(json!({ "jobs": jobs })).into_response()
+}
+
+async fn get_job(State(server): State<Arc<Server>>, UrlPath(id): UrlPath<String>) -> Response {
+    let job = match find_job(&server, &id) {
+        Some(job) => job,
+        None => return error_response(StatusCode::NOT_FOUND, format!("no job {}", id)),
+    };
+    let mut status = job_status(&job);
+    status["files"] = json!(list_files(&job));
+    Json(status).into_response()
+}
+
+async fn cancel_job(State(server): State<Arc<Server>>, UrlPath(id): UrlPath<String>) -> Response {
+    let job = match find_job(&server, &id) {
+        Some(job) => job,
+        None => return error_response(StatusCode::NOT_FOUND, format!("no job {}", id)),
+    };
+    let mut state = lock(&job.state);
+    match *state {
+        JobState::Queued => *state = JobState::Cancelled,
+        JobState::Running => job.shutdown.request(),
+        _ => {}
+    }
+    drop(state);
+    Json(job_status(&job)).into_response()
+}
+
+async fn get_file(
+    State(server): State<Arc<Server>>,
+    UrlPath((id, path)): UrlPath<(String, String)>,
+) -> Response {
+    let job = match find_job(&server, &id) {
+        Some(job) => job,
+        None => return error_response(StatusCode::NOT_FOUND, format!("no job {}", id)),
+    };
+    let path = Path::new(&path);
+    if !path.components().all(|component| matches!(component, Component::Normal(_))) {
+        return error_response(StatusCode::BAD_REQUEST, "invalid path".to_string())
+    }
+    match std::fs::read(job.output_dir.join(path)) {
+        Ok(contents) => {
+            ([(header::CONTENT_TYPE, "application/octet-stream")], contents).into_response()
+        }
+        Err(_) => error_response(StatusCode::NOT_FOUND, format!("no file {}", path.display())),
+    }
+}
+
+fn find_job(server: &Server, id: &str) -> Option<Arc<Job>> {
+    lock(&server.jobs).get(id).cloned()
+}
+
+fn job_status(job: &Job) -> serde_json::Value {
+    let submitted_at: chrono::DateTime<chrono::Utc> = job.submitted_at.into();
+    let mut status = json!({
+        "id": job.id,
+        "datatype": job.args.datatype,
+        "submitted_at": submitted_at.to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+        "chunks_done": job.bar.position(),
+        "chunks_total": job.bar.length(),
+    });
+    let (name, details) = match &*lock(&job.state) {
+        JobState::Queued => ("queued", json!({})),
+        JobState::Running => ("running", json!({})),
+        JobState::Completed(summary) => (
+            "completed",
+            json!({
+                "chunks_completed": summary.completed.len(),
+                "chunks_skipped": summary.skipped.len(),
+                "chunks_empty": summary.empty.len(),
+                "chunks_errored": summary.errored.len(),
+                "data_quality_anomalies": summary.quality.anomalies(),
+                "errors": summary.errored.iter().map(|(_, e)| e.to_string()).collect::<Vec<_>>(),
+            }),
+        ),
+        JobState::Failed(error) => ("failed", json!({ "error": error })),
+        JobState::Cancelled => ("cancelled", json!({})),
+    };
+    status["status"] = json!(name);
+    if let (Some(status), serde_json::Value::Object(details)) = (status.as_object_mut(), details) {
+        status.extend(details);
+    }
+    status
+}
+
+/// paths of output files of job, relative to its output directory
+fn list_files(job: &Job) -> Vec<String> {
+    job_files(job)
+        .iter()
+        .filter_map(|path| path.strip_prefix(&job.output_dir).ok())
+        .map(|path| path.to_string_lossy().to_string())
+        .collect()
+}
+
+fn job_files(job: &Job) -> Vec<PathBuf> {
+    list_output_files(&job.output_dir).unwrap_or_default()
+}
+
+fn error_response(status: StatusCode, error: String) -> Response {
+    (status, Json(json!({ "error": error }))).into_response()
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> std::sync::MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap_cryo::Parser;
+
+    #[test]
+    fn test_parse_job_args() {
+        let server = Server {
+            defaults: Args::parse_from(["cryo", "serve", "--output-dir", "jobs"]),
+            output_dir: PathBuf::from("jobs"),
+            jobs: Mutex::new(BTreeMap::new()),
+            n_jobs: AtomicU64::new(0),
+            queue: Semaphore::new(1),
+        };
+        let args = parse_job_args(&server, r#"{"datatype": ["blocks"], "tui": true}"#).unwrap();
+        assert_eq!(args.datatype, vec!["blocks"]);
+        assert!(args.no_verbose && !args.tui);
+
+        // every option that is not a job option is rejected, with the value of the server
+        let defaults = match serde_json::to_value(&server.defaults).unwrap() {
+            serde_json::Value::Object(defaults) => defaults,
+            _ => panic!("options are not a json object"),
+        };
+        assert!(JOB_OPTIONS.iter().all(|option| defaults.contains_key(*option)));
+        let reserved: Vec<_> = defaults
+            .iter()
+            .filter(|(option, _)| !JOB_OPTIONS.contains(&option.as_str()))
+            .filter(|(option, _)| !SIGNATURE_OPTIONS.contains(&option.as_str()))
+            .collect();
+        let files = ["transform", "transfers_dir", "pools_file", "signature_file", "js_tracer"];
+        for option in files.into_iter().chain(["config", "rpc_ca_cert", "clickhouse"]) {
+            assert!(reserved.iter().any(|(reserved, _)| reserved.as_str() == option));
+        }
+        for (option, value) in reserved.into_iter() {
+            let body = json!({ "datatype": ["blocks"], option: value }).to_string();
+            assert_eq!(
+                parse_job_args(&server, &body).unwrap_err(),
+                format!("{} is chosen by the server", option)
+            );
+        }
+
+        // abis are given as signatures rather than files of the server
+        let signature = "function transfer(address to, uint256 amount)";
+        let body = json!({ "datatype": ["blocks"], "function_abi": signature }).to_string();
+        assert!(parse_job_args(&server, &body).is_ok());
+        let body = json!({ "datatype": ["blocks"], "error_abi": "Cargo.toml" }).to_string();
+        assert_eq!(parse_job_args(&server, &body).unwrap_err(), "error_abi must be a signature");
+
+        assert!(parse_job_args(&server, r#"{"datatype": ["merge"]}"#).is_err());
+    }
+}
@@ -0,0 +1,138 @@
+//! `cryo tail`, printing the rows of new blocks as they arrive
+//!
+//! `cryo tail DATATYPE... [OPTIONS]` collects datatypes from each new block and prints their rows
+//! to stdout as json lines instead of writing files, e.g.
+//! `cryo tail logs --contract 0x... --event Transfer`. blocks given with `--blocks` are printed
+//! before following the chain from the block after them, otherwise following starts with the
+//! latest block. blocks are collected once they are `--reorg-buffer` blocks old
+//!
+//! `--event` takes event names such as `Transfer` and event signatures as well as topics, and
+//! logs of a single event are decoded into `event__` columns
+
+use std::{io::Write, sync::Arc, time::Duration};
+
+use cryo_freeze::{
+    collect_chunks, format_binary_columns, ChunkData, CollectError, Datatype, LogDecoder,
+    ParseError, Query, Source,
+};
+use futures::StreamExt;
+use polars::prelude::*;
+
+use crate::{args::Args, parse};
+
+/// run `cryo tail` command
+pub(crate) async fn run_tail(args: &Args) -> Result<(), CollectError> {
+    let datatype = args.datatype[1..].to_vec();
+    if datatype.is_empty() {
+        return Err(CollectError::CollectError("usage: cryo tail DATATYPE... [OPTIONS]".to_string()))
+    }
+    let mut args = Args { datatype, ..args.clone() };
+    resolve_events(&mut args)?;
+    let interval = Duration::try_from_secs_f64(args.poll_interval)
+        .map_err(|_| CollectError::CollectError("invalid --poll-interval".to_string()))?;
+
+    // print the given blocks, or the latest block
+    let blocks = args.blocks.clone().or_else(|| Some(vec!["latest".to_string()]));
+    let (query, source, _sink, _env) = parse::parse_args(&Args { blocks, ..args.clone() }).await?;
+    let source = Arc::new(source);
+    let reorg_buffer = std::mem::take(&mut args.reorg_buffer);
+    let head = get_head(&source, reorg_buffer).await?;
+    let next = max_block(&query).map(|block| block + 1).unwrap_or(head + 1);
+
+    follow(&query, &args, source, next, reorg_buffer, interval).await
+}
+
+/// print rows of query, then of blocks from next as they arrive, until interrupted
+async fn follow(
+    query: &Query,
+    args: &Args,
+    source: Arc<Source>,
+    mut next: u64,
+    reorg_buffer: u64,
+    interval: Duration,
+) -> Result<(), CollectError> {
+    print_rows(query, source.clone()).await?;
+    loop {
+        tokio::time::sleep(interval).await;
+        let head = get_head(&source, reorg_buffer).await?;
+        if head >= next {
+            let query = parse_range(args, &source, next, head).await?;
+            print_rows(&query, source.clone()).await?;
+            next = head + 1;
+        }
+    }
+}
+
+/// replace events given by name or signature with their topic0, decoding their logs if there is
+/// a single event
+fn resolve_events(args: &mut Args) -> Result<(), ParseError> {
+    let topic0s = match &args.topic0 {
+        Some(topic0s) => topic0s.clone(),
+        None => return Ok(()),
+    };
+    let mut resolved = Vec::new();
+    for topic0 in topic0s.iter() {
+        if topic0.starts_with("0x") || std::path::Path::new(topic0).exists() {
+            resolved.push(topic0.clone());
+            continue
+        }
+        let decoder = LogDecoder::from_event(topic0).map_err(ParseError::ParseError)?;
+        resolved.push(format!("{:?}", decoder.topic0()));
+        if topic0s.len() == 1 && args.event_signature.is_none() {
+            args.event_signature = Some(decoder.raw);
+        }
+    }
+    args.topic0 = Some(resolved);
+    Ok(())
+}
+
+/// latest block that is at least reorg_buffer blocks old
+async fn get_head(source: &Source, reorg_buffer: u64) -> Result<u64, CollectError> {
+    let latest = source.fetcher.get_block_number().await?.as_u64();
+    Ok(latest.saturating_sub(reorg_buffer))
+}
+
+/// query of args over blocks start to end, inclusive
+async fn parse_range(
+    args: &Args,
+    source: &Source,
+    start: u64,
+    end: u64,
+) -> Result<Query, CollectError> {
+    let blocks = Some(vec![format!("{}:{}", start, end + 1)]);
+    Ok(parse::parse_source_query(&Args { blocks, ..args.clone() }, source).await?)
+}
+
+fn max_block(query: &Query) -> Option<u64> {
+    query
+        .partitions
+        .iter()
+        .flat_map(|partition| partition.block_numbers.iter().flatten())
+        .filter_map(|chunk| chunk.max_value())
+        .max()
+}
+
+/// collect query and print its rows as json lines, with the dataset of each row if there are
+/// several datatypes
+async fn print_rows(query: &Query, source: Arc<Source>) -> Result<(), CollectError> {
+    let several = query.schemas.len() > 1;
+    let mut chunks = std::pin::pin!(collect_chunks(query, source));
+    while let Some(chunk) = chunks.next().await {
+        let mut dfs: Vec<(Datatype, DataFrame)> = chunk?.into_iter().collect();
+        dfs.sort_by_key(|(datatype, _)| datatype.name());
+        for (datatype, df) in dfs.into_iter() {
+            if df.height() == 0 {
+                continue
+            }
+            let mut df = format_binary_columns(df, Some(&datatype))?;
+            if several {
+                let dataset = Series::new("dataset", vec![datatype.name(); df.height()]);
+                df.insert_at_idx(0, dataset)?;
+            }
+            let mut stdout = std::io::stdout().lock();
+            JsonWriter::new(&mut stdout).with_json_format(JsonFormat::JsonLines).finish(&mut df)?;
+            stdout.flush().map_err(|e| CollectError::CollectError(e.to_string()))?;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,100 @@
+//! golden tests of datasets, collected from recorded rpc fixtures instead of a node
+//!
+//! each directory of `tests/golden` is a case of
+//! - `args.txt`: args of the collection, e.g. `blocks transactions -b 0:2 --json`
+//! - `fixtures.jsonl`: responses of the rpc, recorded by running the args against a node with
+//!   `--record-fixtures fixtures.jsonl`
+//! - `expected/`: output files of the collection
+//!
+//! run with `CRYO_UPDATE_GOLDEN=1` to write the output files of each case to its `expected/`
+
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+
+use clap_cryo::Parser;
+use cryo_cli::Args;
+
+#[tokio::test]
+async fn test_golden_datasets() {
+    let golden_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests").join("golden");
+    let update = std::env::var("CRYO_UPDATE_GOLDEN").is_ok();
+    let mut cases: Vec<PathBuf> = std::fs::read_dir(&golden_dir)
+        .unwrap()
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.is_dir())
+        .collect();
+    cases.sort();
+    assert!(!cases.is_empty(), "no golden cases in {}", golden_dir.display());
+    for case in cases.iter() {
+        let output_dir = std::env::temp_dir()
+            .join(format!("cryo_golden_{}", std::process::id()))
+            .join(case.file_name().unwrap());
+        let _ = std::fs::remove_dir_all(&output_dir);
+        collect_case(case, &output_dir).await;
+
+        let expected_dir = case.join("expected");
+        if update {
+            let _ = std::fs::remove_dir_all(&expected_dir);
+            std::fs::create_dir_all(&expected_dir).unwrap();
+            for (name, contents) in read_files(&output_dir).into_iter() {
+                std::fs::write(expected_dir.join(name), contents).unwrap();
+            }
+        } else {
+            let expected = read_files(&expected_dir);
+            let actual = read_files(&output_dir);
+            assert_eq!(
+                expected.keys().collect::<Vec<_>>(),
+                actual.keys().collect::<Vec<_>>(),
+                "output files of {} differ",
+                case.display()
+            );
+            for (name, contents) in expected.iter() {
+                assert!(
+                    actual.get(name) == Some(contents),
+                    "{} of {} differs from expected, rerun with CRYO_UPDATE_GOLDEN=1 if the \
+                     change is intended",
+                    name,
+                    case.display()
+                );
+            }
+        }
+        let _ = std::fs::remove_dir_all(&output_dir);
+    }
+}
+
+/// collect args of case from its fixtures into output_dir
+async fn collect_case(case: &Path, output_dir: &Path) {
+    let args_path = case.join("args.txt");
+    let raw_args = std::fs::read_to_string(&args_path).unwrap();
+    let fixtures = case.join("fixtures.jsonl");
+    let mut tokens = vec!["cryo".to_string()];
+    tokens.extend(raw_args.split_whitespace().map(|token| token.to_string()));
+    tokens.extend([
+        "--replay-fixtures".to_string(),
+        fixtures.to_string_lossy().to_string(),
+        "--output-dir".to_string(),
+        output_dir.to_string_lossy().to_string(),
+        "--no-report".to_string(),
+        "--no-verbose".to_string(),
+    ]);
+    let args = Args::parse_from(tokens);
+    let summary = cryo_cli::run(args).await.unwrap();
+    if let Some(summary) = summary {
+        assert!(summary.errored.is_empty(), "collection of {} errored", case.display());
+    }
+}
+
+/// contents of the files of directory, by name, excluding subdirectories such as `.cryo`
+fn read_files(dir: &Path) -> BTreeMap<String, Vec<u8>> {
+    let mut files = BTreeMap::new();
+    for entry in std::fs::read_dir(dir).unwrap().flatten() {
+        if entry.path().is_file() {
+            let name = entry.file_name().to_string_lossy().to_string();
+            files.insert(name, std::fs::read(entry.path()).unwrap());
+        }
+    }
+    files
+}
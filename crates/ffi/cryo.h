@@ -0,0 +1,51 @@
+/* C interface to cryo, built as libcryo_ffi by `cargo build --release -p cryo_ffi` */
+
+#ifndef CRYO_H
+#define CRYO_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* collection succeeded */
+#define CRYO_OK 0
+/* collection failed, see cryo_last_error() */
+#define CRYO_ERROR 1
+/* collection was stopped by the callback */
+#define CRYO_STOPPED 2
+
+/*
+ * receives the name of the datatype of a dataframe, the dataframe as an arrow ipc stream, and
+ * the user data given to cryo_collect_json(). the buffers are only valid for the duration of
+ * the call. returning nonzero stops the collection
+ */
+typedef int (*cryo_callback)(const char *datatype, const uint8_t *data, size_t len,
+                             void *user_data);
+
+/*
+ * collect data described by a json object of cli options, ex:
+ * {"datatype": ["logs"], "blocks": ["18000000:18000100"], "rpc": ["http://localhost:8545"]}
+ *
+ * options are named like the options of `cryo --help` with dashes replaced by underscores,
+ * options taking several values are json arrays, and options not given take their cli defaults. dataframes are passed to callback one per chunk
+ * and datatype, in the order of the chunks, on the calling thread
+ */
+int cryo_collect_json(const char *config_json, cryo_callback callback, void *user_data);
+
+/*
+ * error of the last failed collection of the calling thread, or NULL if there is none. the
+ * string is owned by cryo and is valid until the next collection of the calling thread
+ */
+const char *cryo_last_error(void);
+
+/* version of cryo */
+const char *cryo_version(void);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
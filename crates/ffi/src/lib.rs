@@ -0,0 +1,153 @@
+//! C interface to cryo, for embedding cryo in languages other than rust and python
+//!
+//! the interface is declared in `cryo.h`. collected dataframes are streamed to a callback as
+//! arrow ipc stream buffers, one per chunk and datatype, in the order of the chunks
+
+#![allow(clippy::missing_safety_doc)]
+
+use std::{
+    cell::RefCell,
+    ffi::{c_char, c_int, c_void, CStr, CString},
+    sync::Arc,
+};
+
+use clap_cryo::Parser;
+use cryo_cli::{parse_args, parse_json_args, Args};
+use cryo_freeze::{collect_chunks, Datatype};
+use futures::StreamExt;
+use polars::prelude::*;
+
+/// callback receiving the dataframes of a collection
+///
+/// receives the name of the datatype of the dataframe, the bytes of the dataframe as an arrow ipc
+/// stream, and the user data given to `cryo_collect_json`. the buffers are only valid for the
+/// duration of the call. returning nonzero stops the collection
+pub type CryoCallback = extern "C" fn(
+    datatype: *const c_char,
+    data: *const u8,
+    len: usize,
+    user_data: *mut c_void,
+) -> c_int;
+
+/// collection succeeded
+pub const CRYO_OK: c_int = 0;
+
+/// collection failed, see `cryo_last_error`
+pub const CRYO_ERROR: c_int = 1;
+
+/// collection was stopped by the callback
+pub const CRYO_STOPPED: c_int = 2;
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+static VERSION: &str = concat!(env!("CARGO_PKG_VERSION"), "\0");
+
+/// version of cryo
+#[no_mangle]
+pub extern "C" fn cryo_version() -> *const c_char {
+    VERSION.as_ptr() as *const c_char
+}
+
+/// error of the last failed collection of the calling thread, or null if there is none
+///
+/// the string is owned by cryo and is valid until the next collection of the calling thread
+#[no_mangle]
+pub extern "C" fn cryo_last_error() -> *const c_char {
+    LAST_ERROR.with(|error| match error.borrow().as_ref() {
+        Some(error) => error.as_ptr(),
+        None => std::ptr::null(),
+    })
+}
+
+/// collect data described by a json object of cli options, ex:
+/// `{"datatype": ["logs"], "blocks": ["18000000:18000100"], "rpc": ["http://localhost:8545"]}`
+///
+/// options are named like the options of `cryo --help` with dashes replaced by underscores,
+/// options taking several values are json arrays, and options not given take their cli defaults.
+/// returns `CRYO_OK`, `CRYO_ERROR`, or `CRYO_STOPPED`
+#[no_mangle]
+pub unsafe extern "C" fn cryo_collect_json(
+    config_json: *const c_char,
+    callback: CryoCallback,
+    user_data: *mut c_void,
+) -> c_int {
+    LAST_ERROR.with(|error| error.replace(None));
+    if config_json.is_null() {
+        set_last_error("config_json is null".to_string());
+        return CRYO_ERROR
+    }
+    let config = CStr::from_ptr(config_json).to_string_lossy().into_owned();
+    let user_data = UserData(user_data);
+    let result = std::panic::catch_unwind(move || collect_json(&config, callback, user_data));
+    match result {
+        Ok(Ok(true)) => CRYO_OK,
+        Ok(Ok(false)) => CRYO_STOPPED,
+        Ok(Err(e)) => {
+            set_last_error(e);
+            CRYO_ERROR
+        }
+        Err(_) => {
+            set_last_error("collection panicked".to_string());
+            CRYO_ERROR
+        }
+    }
+}
+
+/// user data of callback, only passed back to the callback on the calling thread
+struct UserData(*mut c_void);
+
+impl std::panic::UnwindSafe for UserData {}
+
+fn set_last_error(message: String) {
+    let message = CString::new(message.replace('\0', " ")).unwrap_or_default();
+    LAST_ERROR.with(|error| error.replace(Some(message)));
+}
+
+/// collect chunks and pass their dataframes to callback, returning whether all were passed
+fn collect_json(config: &str, callback: CryoCallback, user_data: UserData) -> Result<bool, String> {
+    let defaults = Args::try_parse_from(["cryo", "blocks"]).map_err(|e| e.to_string())?;
+    let args = parse_json_args(config, &defaults).map_err(|e| e.to_string())?;
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()
+        .map_err(|e| format!("could not start runtime: {}", e))?;
+    runtime.block_on(async move {
+        let (query, source, _sink, _env) = parse_args(&args).await.map_err(|e| e.to_string())?;
+        // chunks are collected concurrently but passed to callback in order, on this thread
+        let mut results = std::pin::pin!(collect_chunks(&query, Arc::new(source)));
+        while let Some(dfs) = results.next().await {
+            let mut dfs: Vec<(Datatype, DataFrame)> =
+                dfs.map_err(|e| e.to_string())?.into_iter().collect();
+            dfs.sort_by_key(|(datatype, _)| datatype.name());
+            for (datatype, mut df) in dfs.into_iter() {
+                let buffer = to_ipc_stream(&mut df)?;
+                let name = CString::new(datatype.name()).map_err(|e| e.to_string())?;
+                if callback(name.as_ptr(), buffer.as_ptr(), buffer.len(), user_data.0) != 0 {
+                    return Ok(false)
+                }
+            }
+        }
+        Ok(true)
+    })
+}
+
+fn to_ipc_stream(df: &mut DataFrame) -> Result<Vec<u8>, String> {
+    let mut buffer = Vec::new();
+    IpcStreamWriter::new(&mut buffer).finish(df).map_err(|e| e.to_string())?;
+    Ok(buffer)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_ipc_stream() {
+        let mut df = df!("block_number" => [1u32, 2, 3]).unwrap();
+        let buffer = to_ipc_stream(&mut df).unwrap();
+        let read = IpcStreamReader::new(std::io::Cursor::new(buffer)).finish().unwrap();
+        assert_eq!(read, df);
+    }
+}
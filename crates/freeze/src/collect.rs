@@ -1,5 +1,7 @@
-use crate::{collect_partition, CollectError, Query, Source};
+use crate::{collect_partition, CollectError, Datatype, Query, Source};
+use futures::StreamExt;
 use polars::prelude::*;
+use std::collections::HashMap;
 
 /// collect single dataframe
 pub async fn collect(query: Query, source: Arc<Source>) -> Result<DataFrame, CollectError> {
@@ -29,3 +31,25 @@ pub async fn collect(query: Query, source: Arc<Source>) -> Result<DataFrame, Col
         }
     }
 }
+
+/// collect the partitions of query as a stream of dataframes of each datatype, in the order of
+/// the partitions, collecting up to the max concurrent chunks of source at once
+pub fn collect_chunks(
+    query: &Query,
+    source: Arc<Source>,
+) -> impl futures::Stream<Item = Result<HashMap<Datatype, DataFrame>, CollectError>> {
+    let max_concurrent_chunks = source.max_concurrent_chunks.unwrap_or(4).max(1) as usize;
+    let mut tasks = Vec::new();
+    for partition in query.partitions.iter() {
+        for datatype in query.datatypes.iter() {
+            tasks.push(collect_partition(
+                query.time_dimension.clone(),
+                datatype.clone(),
+                partition.clone(),
+                source.clone(),
+                query.schemas.clone(),
+            ));
+        }
+    }
+    futures::stream::iter(tasks).buffered(max_concurrent_chunks)
+}
@@ -0,0 +1,104 @@
+use crate::*;
+use ethers::prelude::*;
+use polars::prelude::*;
+use std::collections::HashMap;
+
+/// columns for access lists
+#[cryo_to_df::to_df(Datatype::AccessLists)]
+#[derive(Default)]
+pub struct AccessLists {
+    n_rows: u64,
+    block_number: Vec<Option<u32>>,
+    transaction_index: Vec<Option<u32>>,
+    transaction_hash: Vec<Vec<u8>>,
+    address: Vec<Vec<u8>>,
+    slot: Vec<Option<Vec<u8>>>,
+    chain_id: Vec<u64>,
+}
+
+#[async_trait::async_trait]
+impl Dataset for AccessLists {
+    fn name() -> &'static str {
+        "access_lists"
+    }
+
+    fn default_sort() -> Vec<String> {
+        vec!["block_number".to_string(), "transaction_index".to_string()]
+    }
+}
+
+type Result<T> = ::core::result::Result<T, CollectError>;
+
+#[async_trait::async_trait]
+impl CollectByBlock for AccessLists {
+    type Response = Block<Transaction>;
+
+    async fn extract(
+        request: Params,
+        source: Arc<Source>,
+        _schemas: Schemas,
+    ) -> Result<Self::Response> {
+        let block = source
+            .fetcher
+            .get_block_with_txs(request.block_number()?)
+            .await?
+            .ok_or(CollectError::CollectError("block not found".to_string()))?;
+        Ok(block)
+    }
+
+    fn transform(response: Self::Response, columns: &mut Self, schemas: &Schemas) -> Result<()> {
+        let schema = schemas.get(&Datatype::AccessLists).ok_or(err("schema not provided"))?;
+        for tx in response.transactions.iter() {
+            process_access_list(tx, columns, schema);
+        }
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl CollectByTransaction for AccessLists {
+    type Response = Transaction;
+
+    async fn extract(
+        request: Params,
+        source: Arc<Source>,
+        _schemas: Schemas,
+    ) -> Result<Self::Response> {
+        let transaction = source
+            .fetcher
+            .get_transaction(request.ethers_transaction_hash()?)
+            .await?
+            .ok_or(CollectError::CollectError("transaction not found".to_string()))?;
+        Ok(transaction)
+    }
+
+    fn transform(response: Self::Response, columns: &mut Self, schemas: &Schemas) -> Result<()> {
+        let schema = schemas.get(&Datatype::AccessLists).ok_or(err("schema not provided"))?;
+        process_access_list(&response, columns, schema);
+        Ok(())
+    }
+}
+
+/// process access list of transaction into rows, one per accessed storage slot
+///
+/// addresses that are accessed without any storage slots produce a single row with a null slot
+fn process_access_list(tx: &Transaction, columns: &mut AccessLists, schema: &Table) {
+    let access_list = match &tx.access_list {
+        Some(access_list) => access_list,
+        None => return,
+    };
+    for item in access_list.0.iter() {
+        let slots: Vec<Option<Vec<u8>>> = match item.storage_keys.is_empty() {
+            true => vec![None],
+            false => item.storage_keys.iter().map(|key| Some(key.as_bytes().to_vec())).collect(),
+        };
+        for slot in slots.into_iter() {
+            columns.n_rows += 1;
+            store!(schema, columns, block_number, tx.block_number.map(|x| x.as_u32()));
+            store!(schema, columns, transaction_index, tx.transaction_index.map(|x| x.as_u32()));
+            store!(schema, columns, transaction_hash, tx.hash.as_bytes().to_vec());
+            store!(schema, columns, address, item.address.as_bytes().to_vec());
+            store!(schema, columns, slot, slot);
+        }
+    }
+}
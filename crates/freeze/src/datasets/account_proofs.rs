@@ -0,0 +1,102 @@
+use crate::*;
+use ethers::prelude::*;
+use polars::prelude::*;
+use std::collections::HashMap;
+
+/// columns for account proofs
+#[cryo_to_df::to_df(Datatype::AccountProofs)]
+#[derive(Default)]
+pub struct AccountProofs {
+    n_rows: usize,
+    block_number: Vec<u32>,
+    address: Vec<Vec<u8>>,
+    balance: Vec<U256>,
+    nonce: Vec<u64>,
+    code_hash: Vec<Vec<u8>>,
+    storage_hash: Vec<Vec<u8>>,
+    account_proof: Vec<String>,
+    slot: Vec<Option<Vec<u8>>>,
+    value: Vec<Option<Vec<u8>>>,
+    storage_proof: Vec<Option<String>>,
+    chain_id: Vec<u64>,
+}
+
+#[async_trait::async_trait]
+impl Dataset for AccountProofs {
+    fn name() -> &'static str {
+        "account_proofs"
+    }
+
+    fn aliases() -> Vec<&'static str> {
+        vec!["proofs"]
+    }
+
+    fn default_sort() -> Vec<String> {
+        vec!["block_number".to_string(), "address".to_string(), "slot".to_string()]
+    }
+
+    fn required_parameters() -> Vec<Dim> {
+        vec![Dim::Address]
+    }
+
+    fn optional_parameters() -> Vec<Dim> {
+        vec![Dim::Slot]
+    }
+}
+
+type Result<T> = ::core::result::Result<T, CollectError>;
+
+#[async_trait::async_trait]
+impl CollectByBlock for AccountProofs {
+    type Response = (u32, Option<Vec<u8>>, EIP1186ProofResponse);
+
+    async fn extract(
+        request: Params,
+        source: Arc<Source>,
+        _schemas: Schemas,
+    ) -> Result<Self::Response> {
+        let address = H160::from_slice(&request.address()?);
+        let block_number = request.block_number()? as u32;
+        let slot = request.slot().ok();
+        let locations = slot.iter().map(|slot| H256::from_slice(slot)).collect();
+        let proof = source.fetcher.get_proof(address, locations, block_number.into()).await?;
+        Ok((block_number, slot, proof))
+    }
+
+    fn transform(response: Self::Response, columns: &mut Self, schemas: &Schemas) -> Result<()> {
+        let schema = schemas.get(&Datatype::AccountProofs).ok_or(err("schema not provided"))?;
+        let (block_number, slot, proof) = response;
+        let storage_proof = proof.storage_proof.first();
+        columns.n_rows += 1;
+        store!(schema, columns, block_number, block_number);
+        store!(schema, columns, address, proof.address.as_bytes().to_vec());
+        store!(schema, columns, balance, proof.balance);
+        store!(schema, columns, nonce, proof.nonce.as_u64());
+        store!(schema, columns, code_hash, proof.code_hash.as_bytes().to_vec());
+        store!(schema, columns, storage_hash, proof.storage_hash.as_bytes().to_vec());
+        store!(schema, columns, account_proof, encode_proof(&proof.account_proof));
+        store!(schema, columns, slot, slot);
+        store!(
+            schema,
+            columns,
+            value,
+            storage_proof.map(|storage| {
+                let mut value = [0u8; 32];
+                storage.value.to_big_endian(&mut value);
+                value.to_vec()
+            })
+        );
+        store!(schema, columns, storage_proof, storage_proof.map(|s| encode_proof(&s.proof)));
+        Ok(())
+    }
+}
+
+impl CollectByTransaction for AccountProofs {
+    type Response = ();
+}
+
+/// encode proof nodes as a json list of hex strings, ordered from the root node
+fn encode_proof(nodes: &[Bytes]) -> String {
+    let nodes: Vec<String> = nodes.iter().map(|node| prefix_hex::encode(node.to_vec())).collect();
+    serde_json::to_string(&nodes).unwrap_or_default()
+}
@@ -29,6 +29,18 @@ impl Dataset for BalanceDiffs {
     fn default_sort() -> Vec<String> {
         vec!["block_number".to_string(), "transaction_index".to_string()]
     }
+
+    fn dedup_keys() -> Option<Vec<String>> {
+        Some(vec![
+            "block_number".to_string(),
+            "transaction_index".to_string(),
+            "address".to_string(),
+        ])
+    }
+
+    fn relative_cost() -> u64 {
+        8
+    }
 }
 
 #[async_trait::async_trait]
@@ -24,13 +24,17 @@ impl Dataset for Balances {
         vec!["block_number".to_string(), "address".to_string()]
     }
 
+    fn dedup_keys() -> Option<Vec<String>> {
+        Some(vec!["block_number".to_string(), "address".to_string()])
+    }
+
     fn required_parameters() -> Vec<Dim> {
         vec![Dim::Address]
     }
 }
 
 type Result<T> = ::core::result::Result<T, CollectError>;
-type BlockTxAddressOutput = (u32, Option<Vec<u8>>, Vec<u8>, U256);
+pub(crate) type BlockTxAddressOutput = (u32, Option<Vec<u8>>, Vec<u8>, U256);
 
 #[async_trait::async_trait]
 impl CollectByBlock for Balances {
@@ -43,8 +47,7 @@ impl CollectByBlock for Balances {
     ) -> Result<Self::Response> {
         let address = request.address()?;
         let block_number = request.block_number()? as u32;
-        let balance =
-            source.fetcher.get_balance(H160::from_slice(&address), block_number.into()).await?;
+        let balance = source.get_balance(H160::from_slice(&address), block_number.into()).await?;
         Ok((block_number, None, address, balance))
     }
 
@@ -59,7 +62,7 @@ impl CollectByTransaction for Balances {
     type Response = ();
 }
 
-fn process_balance(
+pub(crate) fn process_balance(
     columns: &mut Balances,
     data: BlockTxAddressOutput,
     schema: &Table,
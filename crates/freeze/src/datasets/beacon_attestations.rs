@@ -0,0 +1,140 @@
+use crate::*;
+use polars::prelude::*;
+use std::collections::HashMap;
+
+/// columns for beacon attestations
+#[cryo_to_df::to_df(Datatype::BeaconAttestations)]
+#[derive(Default)]
+pub struct BeaconAttestations {
+    n_rows: u64,
+    slot: Vec<u64>,
+    attestation_index: Vec<u32>,
+    attested_slot: Vec<u64>,
+    committee_index: Vec<u64>,
+    aggregation_bits: Vec<Vec<u8>>,
+    beacon_block_root: Vec<Vec<u8>>,
+    source_epoch: Vec<u64>,
+    source_root: Vec<Vec<u8>>,
+    target_epoch: Vec<u64>,
+    target_root: Vec<Vec<u8>>,
+    signature: Vec<Vec<u8>>,
+    chain_id: Vec<u64>,
+}
+
+#[async_trait::async_trait]
+impl Dataset for BeaconAttestations {
+    fn name() -> &'static str {
+        "beacon_attestations"
+    }
+
+    fn aliases() -> Vec<&'static str> {
+        vec!["attestations"]
+    }
+
+    fn default_columns() -> Option<Vec<&'static str>> {
+        Some(vec![
+            "slot",
+            "attestation_index",
+            "attested_slot",
+            "committee_index",
+            "aggregation_bits",
+            "beacon_block_root",
+            "source_epoch",
+            "target_epoch",
+            "chain_id",
+        ])
+    }
+
+    fn default_sort() -> Vec<String> {
+        vec!["slot".to_string(), "attestation_index".to_string()]
+    }
+
+    fn required_parameters() -> Vec<Dim> {
+        vec![Dim::BeaconSlot]
+    }
+}
+
+type Result<T> = ::core::result::Result<T, CollectError>;
+
+/// checkpoint of an attestation
+#[derive(serde::Deserialize, Debug)]
+pub struct BeaconCheckpoint {
+    /// epoch of checkpoint
+    pub epoch: String,
+    /// root of checkpoint
+    pub root: String,
+}
+
+/// data of an attestation
+#[derive(serde::Deserialize, Debug)]
+pub struct BeaconAttestationData {
+    /// slot being attested to
+    pub slot: String,
+    /// committee index
+    pub index: String,
+    /// block root being attested to
+    pub beacon_block_root: String,
+    /// source checkpoint
+    pub source: BeaconCheckpoint,
+    /// target checkpoint
+    pub target: BeaconCheckpoint,
+}
+
+/// attestation included in a beacon block, as reported by the beacon node api
+#[derive(serde::Deserialize, Debug)]
+pub struct BeaconAttestation {
+    /// bitlist of aggregated validators
+    pub aggregation_bits: String,
+    /// attestation data
+    pub data: BeaconAttestationData,
+    /// aggregate signature
+    pub signature: String,
+}
+
+#[async_trait::async_trait]
+impl CollectByBlock for BeaconAttestations {
+    type Response = (u64, Vec<BeaconAttestation>);
+
+    async fn extract(
+        request: Params,
+        source: Arc<Source>,
+        _schemas: Schemas,
+    ) -> Result<Self::Response> {
+        // missed slots have no block and therefore no attestations
+        let slot = request.beacon_slot()?;
+        let path = format!("/eth/v1/beacon/blocks/{}/attestations", slot);
+        let attestations = source.get_beacon_data(&path).await?.unwrap_or_default();
+        Ok((slot, attestations))
+    }
+
+    fn transform(response: Self::Response, columns: &mut Self, schemas: &Schemas) -> Result<()> {
+        let schema =
+            schemas.get(&Datatype::BeaconAttestations).ok_or(err("schema not provided"))?;
+        let (slot, attestations) = response;
+        for (index, attestation) in attestations.iter().enumerate() {
+            let data = &attestation.data;
+            columns.n_rows += 1;
+            store!(schema, columns, slot, slot);
+            store!(schema, columns, attestation_index, index as u32);
+            store!(schema, columns, attested_slot, parse_u64_string(&data.slot)?);
+            store!(schema, columns, committee_index, parse_u64_string(&data.index)?);
+            store!(
+                schema,
+                columns,
+                aggregation_bits,
+                parse_hex_string(&attestation.aggregation_bits)?
+            );
+            store!(schema, columns, beacon_block_root, parse_hex_string(&data.beacon_block_root)?);
+            store!(schema, columns, source_epoch, parse_u64_string(&data.source.epoch)?);
+            store!(schema, columns, source_root, parse_hex_string(&data.source.root)?);
+            store!(schema, columns, target_epoch, parse_u64_string(&data.target.epoch)?);
+            store!(schema, columns, target_root, parse_hex_string(&data.target.root)?);
+            store!(schema, columns, signature, parse_hex_string(&attestation.signature)?);
+        }
+        Ok(())
+    }
+}
+
+impl CollectByTransaction for BeaconAttestations {
+    type Response = ();
+}
@@ -0,0 +1,200 @@
+use crate::*;
+use ethers::prelude::*;
+use ethers_core::abi::{self, ParamType};
+use polars::prelude::*;
+use std::collections::HashMap;
+
+/// columns for beacon deposits
+#[cryo_to_df::to_df(Datatype::BeaconDeposits)]
+#[derive(Default)]
+pub struct BeaconDeposits {
+    n_rows: u64,
+    block_number: Vec<u32>,
+    transaction_index: Vec<u32>,
+    log_index: Vec<u32>,
+    transaction_hash: Vec<Vec<u8>>,
+    pubkey: Vec<Vec<u8>>,
+    withdrawal_credentials: Vec<Vec<u8>>,
+    amount: Vec<u64>,
+    signature: Vec<Vec<u8>>,
+    index: Vec<u64>,
+    chain_id: Vec<u64>,
+}
+
+#[async_trait::async_trait]
+impl Dataset for BeaconDeposits {
+    fn name() -> &'static str {
+        "beacon_deposits"
+    }
+
+    fn aliases() -> Vec<&'static str> {
+        vec!["deposits"]
+    }
+
+    fn default_columns() -> Option<Vec<&'static str>> {
+        Some(vec![
+            "block_number",
+            "transaction_hash",
+            "pubkey",
+            "withdrawal_credentials",
+            "amount",
+            "index",
+            "chain_id",
+        ])
+    }
+
+    fn default_sort() -> Vec<String> {
+        vec!["block_number".to_string(), "log_index".to_string()]
+    }
+
+    fn optional_parameters() -> Vec<Dim> {
+        vec![Dim::Contract]
+    }
+
+    fn use_block_ranges() -> bool {
+        true
+    }
+}
+
+/// address of deposit contract of chain, if known
+fn deposit_contract(chain_id: u64) -> Option<H160> {
+    let address = match chain_id {
+        1 => "0x00000000219ab540356cbb839cbe05303d7705fa",
+        100 => "0x0b98057ea310f4d31f2a452b414647007d1645d9",
+        17000 => "0x4242424242424242424242424242424242424242",
+        11155111 => "0x7f02c3e3c98b133055b8b348b2ac625669ed295d",
+        _ => return None,
+    };
+    prefix_hex::decode::<Vec<u8>>(address).ok().map(|address| H160::from_slice(&address))
+}
+
+/// deposit decoded from DepositEvent, all of whose fields are encoded as bytes
+#[derive(Debug, PartialEq)]
+struct Deposit {
+    pubkey: Vec<u8>,
+    withdrawal_credentials: Vec<u8>,
+    amount: u64,
+    signature: Vec<u8>,
+    index: u64,
+}
+
+/// decode DepositEvent, whose amount (in gwei) and index are little-endian integers
+fn decode_deposit(log: &Log) -> Option<Deposit> {
+    if log.topics.first() != Some(&*EVENT_BEACON_DEPOSIT) {
+        return None
+    }
+    let tokens = abi::decode(&vec![ParamType::Bytes; 5], &log.data).ok()?;
+    let fields: Vec<Vec<u8>> =
+        tokens.into_iter().map(|token| token.into_bytes()).collect::<Option<_>>()?;
+    let little_endian = |bytes: &Vec<u8>| -> Option<u64> {
+        Some(u64::from_le_bytes(bytes.as_slice().try_into().ok()?))
+    };
+    Some(Deposit {
+        pubkey: fields[0].clone(),
+        withdrawal_credentials: fields[1].clone(),
+        amount: little_endian(&fields[2])?,
+        signature: fields[3].clone(),
+        index: little_endian(&fields[4])?,
+    })
+}
+
+type Result<T> = ::core::result::Result<T, CollectError>;
+
+#[async_trait::async_trait]
+impl CollectByBlock for BeaconDeposits {
+    type Response = Vec<Log>;
+
+    async fn extract(request: Params, source: Arc<Source>, _: Schemas) -> Result<Self::Response> {
+        let contract = match &request.contract {
+            Some(contract) => H160::from_slice(contract),
+            None => deposit_contract(source.chain_id)
+                .ok_or(err("deposit contract of chain is unknown, specify it with --contract"))?,
+        };
+        let topics = [Some(ValueOrArray::Value(Some(*EVENT_BEACON_DEPOSIT))), None, None, None];
+        let address = Some(ValueOrArray::Value(contract));
+        let filter = Filter { topics, address, ..request.ethers_log_filter()? };
+        source.get_logs(&filter).await
+    }
+
+    fn transform(response: Self::Response, columns: &mut Self, schemas: &Schemas) -> Result<()> {
+        let schema = schemas.get(&Datatype::BeaconDeposits).ok_or(err("schema not provided"))?;
+        process_beacon_deposits(response, columns, schema)
+    }
+}
+
+#[async_trait::async_trait]
+impl CollectByTransaction for BeaconDeposits {
+    type Response = Vec<Log>;
+
+    async fn extract(request: Params, source: Arc<Source>, _: Schemas) -> Result<Self::Response> {
+        let logs = source.fetcher.get_transaction_logs(request.transaction_hash()?).await?;
+        let contract = request
+            .contract
+            .as_ref()
+            .map(|contract| H160::from_slice(contract))
+            .or(deposit_contract(source.chain_id));
+        Ok(logs.into_iter().filter(|log| contract.is_none_or(|c| log.address == c)).collect())
+    }
+
+    fn transform(response: Self::Response, columns: &mut Self, schemas: &Schemas) -> Result<()> {
+        let schema = schemas.get(&Datatype::BeaconDeposits).ok_or(err("schema not provided"))?;
+        process_beacon_deposits(response, columns, schema)
+    }
+}
+
+/// process deposit logs into columns
+fn process_beacon_deposits(
+    logs: Vec<Log>,
+    columns: &mut BeaconDeposits,
+    schema: &Table,
+) -> Result<()> {
+    for log in logs.iter() {
+        if let (Some(bn), Some(tx), Some(ti), Some(li), Some(deposit)) = (
+            log.block_number,
+            log.transaction_hash,
+            log.transaction_index,
+            log.log_index,
+            decode_deposit(log),
+        ) {
+            columns.n_rows += 1;
+            store!(schema, columns, block_number, bn.as_u32());
+            store!(schema, columns, transaction_index, ti.as_u32());
+            store!(schema, columns, log_index, li.as_u32());
+            store!(schema, columns, transaction_hash, tx.as_bytes().to_vec());
+            store!(schema, columns, pubkey, deposit.pubkey);
+            store!(schema, columns, withdrawal_credentials, deposit.withdrawal_credentials);
+            store!(schema, columns, amount, deposit.amount);
+            store!(schema, columns, signature, deposit.signature);
+            store!(schema, columns, index, deposit.index);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers_core::abi::Token;
+
+    #[test]
+    fn test_decode_deposit() {
+        let data = abi::encode(&[
+            Token::Bytes(vec![1; 48]),
+            Token::Bytes(vec![2; 32]),
+            Token::Bytes(32_000_000_000u64.to_le_bytes().to_vec()),
+            Token::Bytes(vec![3; 96]),
+            Token::Bytes(7u64.to_le_bytes().to_vec()),
+        ]);
+        let log =
+            Log { topics: vec![*EVENT_BEACON_DEPOSIT], data: data.into(), ..Default::default() };
+        let deposit = decode_deposit(&log).unwrap();
+        assert_eq!(deposit.pubkey, vec![1; 48]);
+        assert_eq!(deposit.withdrawal_credentials, vec![2; 32]);
+        assert_eq!(deposit.amount, 32_000_000_000);
+        assert_eq!(deposit.signature, vec![3; 96]);
+        assert_eq!(deposit.index, 7);
+
+        let log = Log { topics: vec![H256::zero()], ..log };
+        assert!(decode_deposit(&log).is_none());
+    }
+}
@@ -0,0 +1,81 @@
+use crate::*;
+use polars::prelude::*;
+use std::collections::HashMap;
+
+/// columns for beacon proposer duties
+#[cryo_to_df::to_df(Datatype::BeaconProposerDuties)]
+#[derive(Default)]
+pub struct BeaconProposerDuties {
+    n_rows: u64,
+    slot: Vec<u64>,
+    epoch: Vec<u64>,
+    validator_index: Vec<u64>,
+    pubkey: Vec<Vec<u8>>,
+    chain_id: Vec<u64>,
+}
+
+#[async_trait::async_trait]
+impl Dataset for BeaconProposerDuties {
+    fn name() -> &'static str {
+        "beacon_proposer_duties"
+    }
+
+    fn aliases() -> Vec<&'static str> {
+        vec!["proposer_duties"]
+    }
+
+    fn default_sort() -> Vec<String> {
+        vec!["slot".to_string()]
+    }
+
+    fn required_parameters() -> Vec<Dim> {
+        vec![Dim::BeaconSlot]
+    }
+}
+
+type Result<T> = ::core::result::Result<T, CollectError>;
+
+/// proposer assignment of a slot, as reported by the beacon node api
+#[derive(serde::Deserialize, Clone, Debug)]
+pub struct BeaconProposerDuty {
+    /// pubkey of proposer
+    pub pubkey: String,
+    /// index of proposer
+    pub validator_index: String,
+    /// slot assigned to proposer
+    pub slot: String,
+}
+
+#[async_trait::async_trait]
+impl CollectByBlock for BeaconProposerDuties {
+    type Response = Vec<BeaconProposerDuty>;
+
+    async fn extract(
+        request: Params,
+        source: Arc<Source>,
+        _schemas: Schemas,
+    ) -> Result<Self::Response> {
+        // duties are served per epoch, keep only the duty of the requested slot
+        let slot = request.beacon_slot()?;
+        let duties = source.get_proposer_duties(slot / SLOTS_PER_EPOCH).await?;
+        Ok(duties.iter().filter(|duty| duty.slot == slot.to_string()).cloned().collect())
+    }
+
+    fn transform(response: Self::Response, columns: &mut Self, schemas: &Schemas) -> Result<()> {
+        let schema =
+            schemas.get(&Datatype::BeaconProposerDuties).ok_or(err("schema not provided"))?;
+        for duty in response.iter() {
+            let slot = parse_u64_string(&duty.slot)?;
+            columns.n_rows += 1;
+            store!(schema, columns, slot, slot);
+            store!(schema, columns, epoch, slot / SLOTS_PER_EPOCH);
+            store!(schema, columns, validator_index, parse_u64_string(&duty.validator_index)?);
+            store!(schema, columns, pubkey, parse_hex_string(&duty.pubkey)?);
+        }
+        Ok(())
+    }
+}
+
+impl CollectByTransaction for BeaconProposerDuties {
+    type Response = ();
+}
@@ -0,0 +1,79 @@
+use crate::*;
+use polars::prelude::*;
+use std::collections::HashMap;
+
+/// columns for beacon validator balances
+#[cryo_to_df::to_df(Datatype::BeaconValidatorBalances)]
+#[derive(Default)]
+pub struct BeaconValidatorBalances {
+    n_rows: u64,
+    slot: Vec<u64>,
+    epoch: Vec<u64>,
+    validator_index: Vec<u64>,
+    balance: Vec<u64>,
+    chain_id: Vec<u64>,
+}
+
+#[async_trait::async_trait]
+impl Dataset for BeaconValidatorBalances {
+    fn name() -> &'static str {
+        "beacon_validator_balances"
+    }
+
+    fn aliases() -> Vec<&'static str> {
+        vec!["validator_balances"]
+    }
+
+    fn default_sort() -> Vec<String> {
+        vec!["slot".to_string(), "validator_index".to_string()]
+    }
+
+    fn required_parameters() -> Vec<Dim> {
+        vec![Dim::BeaconSlot]
+    }
+}
+
+type Result<T> = ::core::result::Result<T, CollectError>;
+
+/// balance of a validator, as reported by the beacon node api
+#[derive(serde::Deserialize, Debug)]
+pub struct BeaconValidatorBalance {
+    /// index of validator
+    pub index: String,
+    /// balance of validator, in gwei
+    pub balance: String,
+}
+
+#[async_trait::async_trait]
+impl CollectByBlock for BeaconValidatorBalances {
+    type Response = (u64, Vec<BeaconValidatorBalance>);
+
+    async fn extract(
+        request: Params,
+        source: Arc<Source>,
+        _schemas: Schemas,
+    ) -> Result<Self::Response> {
+        let slot = request.beacon_slot()?;
+        let path = format!("/eth/v1/beacon/states/{}/validator_balances", slot);
+        let balances = source.get_beacon_data(&path).await?.unwrap_or_default();
+        Ok((slot, balances))
+    }
+
+    fn transform(response: Self::Response, columns: &mut Self, schemas: &Schemas) -> Result<()> {
+        let schema =
+            schemas.get(&Datatype::BeaconValidatorBalances).ok_or(err("schema not provided"))?;
+        let (slot, balances) = response;
+        for balance in balances.iter() {
+            columns.n_rows += 1;
+            store!(schema, columns, slot, slot);
+            store!(schema, columns, epoch, slot / SLOTS_PER_EPOCH);
+            store!(schema, columns, validator_index, parse_u64_string(&balance.index)?);
+            store!(schema, columns, balance, parse_u64_string(&balance.balance)?);
+        }
+        Ok(())
+    }
+}
+
+impl CollectByTransaction for BeaconValidatorBalances {
+    type Response = ();
+}
@@ -0,0 +1,138 @@
+use crate::*;
+use ethers::prelude::*;
+use polars::prelude::*;
+use std::collections::HashMap;
+
+/// columns for block fees, joining each block with its receipts
+///
+/// fee columns are denominated in wei, and miner revenue excludes block rewards and MEV payments
+#[cryo_to_df::to_df(Datatype::BlockFees)]
+#[derive(Default)]
+pub struct BlockFees {
+    n_rows: u64,
+    block_number: Vec<u32>,
+    timestamp: Vec<u32>,
+    miner: Vec<Option<Vec<u8>>>,
+    n_transactions: Vec<u32>,
+    base_fee: Vec<Option<u64>>,
+    total_gas_used: Vec<u64>,
+    eth_burned: Vec<U256>,
+    total_priority_fees: Vec<U256>,
+    miner_revenue: Vec<U256>,
+    chain_id: Vec<u64>,
+}
+
+#[async_trait::async_trait]
+impl Dataset for BlockFees {
+    fn name() -> &'static str {
+        "block_fees"
+    }
+
+    fn default_sort() -> Vec<String> {
+        vec!["block_number".to_string()]
+    }
+
+    fn dedup_keys() -> Option<Vec<String>> {
+        Some(vec!["block_number".to_string()])
+    }
+
+    fn relative_cost() -> u64 {
+        2
+    }
+}
+
+type Result<T> = ::core::result::Result<T, CollectError>;
+
+/// fee totals of a block
+#[derive(Clone)]
+pub struct BlockFeeTotals {
+    block_number: u32,
+    timestamp: u32,
+    miner: Option<H160>,
+    n_transactions: u32,
+    base_fee: Option<U256>,
+    total_gas_used: U256,
+    total_fees: U256,
+}
+
+#[async_trait::async_trait]
+impl CollectByBlock for BlockFees {
+    type Response = BlockFeeTotals;
+
+    async fn extract(
+        request: Params,
+        source: Arc<Source>,
+        _schemas: Schemas,
+    ) -> Result<Self::Response> {
+        let block_number = request.block_number()?;
+
+        // otterscan nodes report the total fees of a block without fetching its receipts
+        if source.capabilities.otterscan {
+            let details = source.fetcher.ots_get_block_details(block_number).await?;
+            let block = details.block;
+            return Ok(BlockFeeTotals {
+                block_number: block.number.as_u32(),
+                timestamp: block.timestamp.as_u32(),
+                miner: block.miner,
+                n_transactions: block.transaction_count as u32,
+                base_fee: block.base_fee_per_gas,
+                total_gas_used: block.gas_used,
+                total_fees: details.total_fees,
+            })
+        }
+
+        let (block, receipts) = tokio::try_join!(
+            source.fetcher.get_block_with_txs(block_number),
+            source.get_block_receipts(block_number),
+        )?;
+        let block = block.ok_or(CollectError::CollectError("block not found".to_string()))?;
+        if block.transactions.len() != receipts.len() {
+            return Err(err("number of receipts does not match number of transactions"))
+        }
+        let mut total_gas_used = U256::zero();
+        let mut total_fees = U256::zero();
+        for (tx, receipt) in block.transactions.iter().zip(receipts.iter()) {
+            let gas_used = receipt.gas_used.unwrap_or_default();
+            let gas_price = receipt
+                .effective_gas_price
+                .or(tx.gas_price)
+                .ok_or(err("transaction gas price not available"))?;
+            total_gas_used += gas_used;
+            total_fees += gas_used * gas_price;
+        }
+        Ok(BlockFeeTotals {
+            block_number: block.number.map(|x| x.as_u32()).unwrap_or_default(),
+            timestamp: block.timestamp.as_u32(),
+            miner: block.author,
+            n_transactions: block.transactions.len() as u32,
+            base_fee: block.base_fee_per_gas,
+            total_gas_used,
+            total_fees,
+        })
+    }
+
+    fn transform(response: Self::Response, columns: &mut Self, schemas: &Schemas) -> Result<()> {
+        let schema = schemas.get(&Datatype::BlockFees).ok_or(err("schema not provided"))?;
+        let totals = response;
+
+        // blocks before eip-1559 burn nothing and pay the full gas price to the miner
+        let eth_burned = totals.total_gas_used * totals.base_fee.unwrap_or_default();
+        let total_priority_fees = totals.total_fees.saturating_sub(eth_burned);
+
+        columns.n_rows += 1;
+        store!(schema, columns, block_number, totals.block_number);
+        store!(schema, columns, timestamp, totals.timestamp);
+        store!(schema, columns, miner, totals.miner.map(|x| x.as_bytes().to_vec()));
+        store!(schema, columns, n_transactions, totals.n_transactions);
+        store!(schema, columns, base_fee, totals.base_fee.map(|x| x.as_u64()));
+        store!(schema, columns, total_gas_used, totals.total_gas_used.as_u64());
+        store!(schema, columns, eth_burned, eth_burned);
+        store!(schema, columns, total_priority_fees, total_priority_fees);
+        store!(schema, columns, miner_revenue, total_priority_fees);
+        Ok(())
+    }
+}
+
+impl CollectByTransaction for BlockFees {
+    type Response = ();
+}
@@ -22,6 +22,12 @@ pub struct Blocks {
     total_difficulty: Vec<Option<U256>>,
     size: Vec<Option<u32>>,
     base_fee_per_gas: Vec<Option<u64>>,
+    nonce: Vec<Option<Vec<u8>>>,
+    mix_hash: Vec<Option<Vec<u8>>>,
+    withdrawals_root: Vec<Option<Vec<u8>>>,
+    blob_gas_used: Vec<Option<u64>>,
+    excess_blob_gas: Vec<Option<u64>>,
+    parent_beacon_block_root: Vec<Option<Vec<u8>>>,
     chain_id: Vec<u64>,
 }
 
@@ -47,6 +53,10 @@ impl Dataset for Blocks {
     fn default_sort() -> Vec<String> {
         vec!["block_number".to_string()]
     }
+
+    fn dedup_keys() -> Option<Vec<String>> {
+        Some(vec!["block_number".to_string()])
+    }
 }
 
 type Result<T> = ::core::result::Result<T, CollectError>;
@@ -124,5 +134,25 @@ pub(crate) fn process_block<TX>(
     store!(schema, columns, total_difficulty, block.total_difficulty);
     store!(schema, columns, base_fee_per_gas, block.base_fee_per_gas.map(|x| x.as_u64()));
     store!(schema, columns, size, block.size.map(|x| x.as_u32()));
+    store!(schema, columns, nonce, block.nonce.map(|x| x.0.to_vec()));
+    store!(schema, columns, mix_hash, block.mix_hash.map(|x| x.0.to_vec()));
+    store!(schema, columns, withdrawals_root, block.withdrawals_root.map(|x| x.0.to_vec()));
+
+    // post-dencun header fields are not yet typed fields of ethers blocks
+    let blob_gas_used = get_other_field::<U64, TX>(&block, "blobGasUsed");
+    store!(schema, columns, blob_gas_used, blob_gas_used.map(|x| x.as_u64()));
+    let excess_blob_gas = get_other_field::<U64, TX>(&block, "excessBlobGas");
+    store!(schema, columns, excess_blob_gas, excess_blob_gas.map(|x| x.as_u64()));
+    let parent_beacon_block_root = get_other_field::<H256, TX>(&block, "parentBeaconBlockRoot");
+    store!(
+        schema,
+        columns,
+        parent_beacon_block_root,
+        parent_beacon_block_root.map(|x| x.0.to_vec())
+    );
     Ok(())
 }
+
+fn get_other_field<T: serde::de::DeserializeOwned, TX>(block: &Block<TX>, key: &str) -> Option<T> {
+    block.other.get_deserialized(key).and_then(|value| value.ok())
+}
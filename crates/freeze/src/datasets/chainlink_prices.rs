@@ -0,0 +1,210 @@
+use crate::*;
+use ethers::prelude::*;
+use polars::prelude::*;
+use std::collections::{hash_map::Entry, HashMap};
+
+/// columns for chainlink prices
+#[cryo_to_df::to_df(Datatype::ChainlinkPrices)]
+#[derive(Default)]
+pub struct ChainlinkPrices {
+    n_rows: u64,
+    block_number: Vec<u32>,
+    transaction_index: Vec<u32>,
+    log_index: Vec<u32>,
+    transaction_hash: Vec<Vec<u8>>,
+    feed: Vec<Vec<u8>>,
+    round_id: Vec<U256>,
+    answer: Vec<Option<U256>>,
+    answer_float: Vec<Option<f64>>,
+    updated_at: Vec<u64>,
+    chain_id: Vec<u64>,
+}
+
+#[async_trait::async_trait]
+impl Dataset for ChainlinkPrices {
+    fn name() -> &'static str {
+        "chainlink_prices"
+    }
+
+    fn default_columns() -> Option<Vec<&'static str>> {
+        Some(vec![
+            "block_number",
+            "transaction_hash",
+            "feed",
+            "round_id",
+            "answer",
+            "updated_at",
+            "chain_id",
+        ])
+    }
+
+    fn default_sort() -> Vec<String> {
+        vec!["block_number".to_string(), "log_index".to_string()]
+    }
+
+    fn optional_parameters() -> Vec<Dim> {
+        vec![Dim::Contract]
+    }
+
+    fn use_block_ranges() -> bool {
+        true
+    }
+}
+
+/// block before deployment of the chainlink feed registry
+const FEED_REGISTRY_START_BLOCK: u64 = 12_800_000;
+
+type Result<T> = ::core::result::Result<T, CollectError>;
+
+type LogsAndDecimals = (Vec<Log>, HashMap<Vec<u8>, Option<u32>>);
+
+#[async_trait::async_trait]
+impl CollectByBlock for ChainlinkPrices {
+    type Response = LogsAndDecimals;
+
+    async fn extract(
+        request: Params,
+        source: Arc<Source>,
+        schemas: Schemas,
+    ) -> Result<Self::Response> {
+        let (_, end_block) = request.block_range()?;
+        let feeds = match &request.contract {
+            Some(contract) => Some(get_aggregators(contract, end_block, &source).await),
+            None => get_registry_aggregators(&source).await?,
+        };
+        let topics =
+            [Some(ValueOrArray::Value(Some(*EVENT_CHAINLINK_ANSWER_UPDATED))), None, None, None];
+        let address = feeds.map(ValueOrArray::Array);
+        let filter = Filter { topics, address, ..request.ethers_log_filter()? };
+        let logs = source.get_logs(&filter).await?;
+        let logs: Vec<Log> = logs.into_iter().filter(is_answer_updated).collect();
+        let decimals = get_decimals(&logs, &source, &schemas).await?;
+        Ok((logs, decimals))
+    }
+
+    fn transform(response: Self::Response, columns: &mut Self, schemas: &Schemas) -> Result<()> {
+        let schema = schemas.get(&Datatype::ChainlinkPrices).ok_or(err("schema not provided"))?;
+        process_chainlink_prices(response, columns, schema)
+    }
+}
+
+#[async_trait::async_trait]
+impl CollectByTransaction for ChainlinkPrices {
+    type Response = LogsAndDecimals;
+
+    async fn extract(
+        request: Params,
+        source: Arc<Source>,
+        schemas: Schemas,
+    ) -> Result<Self::Response> {
+        let logs = source.fetcher.get_transaction_logs(request.transaction_hash()?).await?;
+        let logs: Vec<Log> = logs.into_iter().filter(is_answer_updated).collect();
+        let decimals = get_decimals(&logs, &source, &schemas).await?;
+        Ok((logs, decimals))
+    }
+
+    fn transform(response: Self::Response, columns: &mut Self, schemas: &Schemas) -> Result<()> {
+        let schema = schemas.get(&Datatype::ChainlinkPrices).ok_or(err("schema not provided"))?;
+        process_chainlink_prices(response, columns, schema)
+    }
+}
+
+/// addresses emitting answers of feed, which are the aggregator behind the feed if it is a proxy
+async fn get_aggregators(contract: &[u8], block_number: u64, source: &Source) -> Vec<H160> {
+    let contract = H160::from_slice(contract);
+    let call_data = FUNCTION_CHAINLINK_AGGREGATOR.clone();
+    let mut addresses = vec![contract];
+    if let Ok(output) = source.call_batched(contract, call_data, block_number).await {
+        if output.len() == 32 {
+            addresses.push(H160::from_slice(&output[12..]));
+        }
+    }
+    addresses
+}
+
+/// aggregators confirmed by the feed registry, None on chains without a registry
+///
+/// aggregators are discovered once per run, from the registry deployment to the latest block
+async fn get_registry_aggregators(source: &Source) -> Result<Option<Vec<H160>>> {
+    if source.chain_id != 1 {
+        return Ok(None)
+    }
+    let aggregators = source
+        .chainlink_aggregators
+        .get_or_try_init(|| async {
+            let filter = Filter::new()
+                .address(*CHAINLINK_FEED_REGISTRY)
+                .topic0(*EVENT_CHAINLINK_FEED_CONFIRMED)
+                .from_block(FEED_REGISTRY_START_BLOCK)
+                .to_block(BlockNumber::Latest);
+            let logs = source.get_logs(&filter).await?;
+            let mut aggregators: Vec<H160> = logs
+                .iter()
+                .filter_map(|log| log.topics.get(3))
+                .map(|topic| H160::from_slice(&topic.as_bytes()[12..]))
+                .collect();
+            aggregators.sort();
+            aggregators.dedup();
+            Ok::<_, CollectError>(aggregators)
+        })
+        .await?;
+    Ok(Some(aggregators.clone()))
+}
+
+/// fetch decimals of each feed in logs, if decimal-adjusted answers are requested
+async fn get_decimals(
+    logs: &[Log],
+    source: &Source,
+    schemas: &Schemas,
+) -> Result<HashMap<Vec<u8>, Option<u32>>> {
+    let schema = schemas.get(&Datatype::ChainlinkPrices).ok_or(err("schema not provided"))?;
+    let mut decimals = HashMap::new();
+    if schema.has_column("answer_float") {
+        for log in logs.iter() {
+            let feed = log.address.as_bytes().to_vec();
+            if let Entry::Vacant(entry) = decimals.entry(feed) {
+                // aggregators share the decimals() selector of erc20 tokens
+                let value = source.get_erc20_decimals(entry.key().clone()).await?;
+                entry.insert(value);
+            }
+        }
+    }
+    Ok(decimals)
+}
+
+fn is_answer_updated(log: &Log) -> bool {
+    log.topics.len() == 3 &&
+        log.data.len() == 32 &&
+        log.topics[0] == *EVENT_CHAINLINK_ANSWER_UPDATED
+}
+
+/// process answer logs into columns
+fn process_chainlink_prices(
+    response: LogsAndDecimals,
+    columns: &mut ChainlinkPrices,
+    schema: &Table,
+) -> Result<()> {
+    let (logs, decimals) = response;
+    for log in logs.iter() {
+        if let (Some(bn), Some(tx), Some(ti), Some(li)) =
+            (log.block_number, log.transaction_hash, log.transaction_index, log.log_index)
+        {
+            let answer = I256::from_raw(U256::from_big_endian(log.topics[1].as_bytes()));
+            let feed_decimals = decimals.get(log.address.as_bytes()).cloned().flatten();
+            let answer_float = feed_decimals
+                .and_then(|d| u256_to_f64_decimals(answer.unsigned_abs(), d))
+                .map(|value| if answer.is_negative() { -value } else { value });
+            columns.n_rows += 1;
+            store!(schema, columns, block_number, bn.as_u32());
+            store!(schema, columns, transaction_index, ti.as_u32());
+            store!(schema, columns, log_index, li.as_u32());
+            store!(schema, columns, transaction_hash, tx.as_bytes().to_vec());
+            store!(schema, columns, feed, log.address.as_bytes().to_vec());
+            store!(schema, columns, round_id, U256::from_big_endian(log.topics[2].as_bytes()));
+            store!(schema, columns, answer, (!answer.is_negative()).then(|| answer.into_raw()));
+            store!(schema, columns, answer_float, answer_float);
+            store!(schema, columns, updated_at, U256::from_big_endian(&log.data).low_u64());
+        }
+    }
+    Ok(())
+}
@@ -11,6 +11,11 @@ pub struct Codes {
     block_number: Vec<u32>,
     address: Vec<Vec<u8>>,
     code: Vec<Vec<u8>>,
+    code_size: Vec<u32>,
+    is_erc20: Vec<Option<bool>>,
+    is_erc721: Vec<Option<bool>>,
+    metadata_hash: Vec<Option<Vec<u8>>>,
+    compiler: Vec<Option<String>>,
     chain_id: Vec<u64>,
 }
 
@@ -20,17 +25,25 @@ impl Dataset for Codes {
         "codes"
     }
 
+    fn default_columns() -> Option<Vec<&'static str>> {
+        Some(vec!["block_number", "address", "code", "chain_id"])
+    }
+
     fn default_sort() -> Vec<String> {
         vec!["block_number".to_string(), "address".to_string()]
     }
 
+    fn dedup_keys() -> Option<Vec<String>> {
+        Some(vec!["block_number".to_string(), "address".to_string()])
+    }
+
     fn required_parameters() -> Vec<Dim> {
         vec![Dim::Address]
     }
 }
 
 type Result<T> = ::core::result::Result<T, CollectError>;
-type BlockTxAddressOutput = (u32, Option<Vec<u8>>, Vec<u8>, Vec<u8>);
+pub(crate) type BlockTxAddressOutput = (u32, Option<Vec<u8>>, Vec<u8>, Vec<u8>);
 
 #[async_trait::async_trait]
 impl CollectByBlock for Codes {
@@ -43,14 +56,13 @@ impl CollectByBlock for Codes {
     ) -> Result<Self::Response> {
         let address = request.address()?;
         let block_number = request.block_number()? as u32;
-        let output =
-            source.fetcher.get_code(H160::from_slice(&address), block_number.into()).await?;
+        let output = source.get_code(H160::from_slice(&address), block_number.into()).await?;
         Ok((block_number, None, address, output.to_vec()))
     }
 
     fn transform(response: Self::Response, columns: &mut Self, schemas: &Schemas) -> Result<()> {
         let schema = schemas.get(&Datatype::Codes).ok_or(err("schema not provided"))?;
-        process_nonce(columns, response, schema)
+        process_code(columns, response, schema)
     }
 }
 
@@ -59,11 +71,27 @@ impl CollectByTransaction for Codes {
     type Response = ();
 }
 
-fn process_nonce(columns: &mut Codes, data: BlockTxAddressOutput, schema: &Table) -> Result<()> {
+pub(crate) fn process_code(
+    columns: &mut Codes,
+    data: BlockTxAddressOutput,
+    schema: &Table,
+) -> Result<()> {
     let (block, _tx, address, output) = data;
     columns.n_rows += 1;
     store!(schema, columns, block_number, block);
     store!(schema, columns, address, address);
+    store!(schema, columns, code_size, output.len() as u32);
+    let analysis = analyze_code(schema, &output);
+    store!(schema, columns, is_erc20, analysis.as_ref().map(|a| a.is_erc20()));
+    store!(schema, columns, is_erc721, analysis.as_ref().map(|a| a.is_erc721()));
+    store!(schema, columns, metadata_hash, analysis.as_ref().and_then(|a| a.metadata_hash.clone()));
+    store!(schema, columns, compiler, analysis.and_then(|a| a.compiler));
     store!(schema, columns, code, output);
     Ok(())
 }
+
+/// analyze code if any bytecode analysis column is collected, None for accounts without code
+pub(crate) fn analyze_code(schema: &Table, code: &[u8]) -> Option<BytecodeAnalysis> {
+    let analyzed = BYTECODE_ANALYSIS_COLUMNS.iter().any(|column| schema.has_column(column));
+    (analyzed && !code.is_empty()).then(|| BytecodeAnalysis::new(code))
+}
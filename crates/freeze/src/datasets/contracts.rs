@@ -1,4 +1,4 @@
-use super::traces;
+use super::{codes, traces};
 use crate::*;
 use ethers::prelude::*;
 use ethers_core::utils::keccak256;
@@ -20,6 +20,11 @@ pub struct Contracts {
     code: Vec<Vec<u8>>,
     init_code_hash: Vec<Vec<u8>>,
     code_hash: Vec<Vec<u8>>,
+    code_size: Vec<u32>,
+    is_erc20: Vec<Option<bool>>,
+    is_erc721: Vec<Option<bool>>,
+    metadata_hash: Vec<Option<Vec<u8>>>,
+    compiler: Vec<Option<String>>,
     chain_id: Vec<u64>,
 }
 
@@ -29,9 +34,33 @@ impl Dataset for Contracts {
         "contracts"
     }
 
+    fn default_columns() -> Option<Vec<&'static str>> {
+        Some(vec![
+            "block_number",
+            "create_index",
+            "transaction_hash",
+            "contract_address",
+            "deployer",
+            "factory",
+            "init_code",
+            "code",
+            "init_code_hash",
+            "code_hash",
+            "chain_id",
+        ])
+    }
+
     fn default_sort() -> Vec<String> {
         vec!["block_number".to_string(), "create_index".to_string()]
     }
+
+    fn dedup_keys() -> Option<Vec<String>> {
+        Some(vec!["block_number".to_string(), "create_index".to_string()])
+    }
+
+    fn relative_cost() -> u64 {
+        8
+    }
 }
 
 type Result<T> = ::core::result::Result<T, CollectError>;
@@ -45,7 +74,7 @@ impl CollectByBlock for Contracts {
         source: Arc<Source>,
         _schemas: Schemas,
     ) -> Result<Self::Response> {
-        source.fetcher.trace_block(request.ethers_block_number()?).await
+        source.trace_block(request.block_number()?).await
     }
 
     fn transform(response: Self::Response, columns: &mut Self, schemas: &Schemas) -> Result<()> {
@@ -106,6 +135,13 @@ pub(crate) fn process_contracts(
             store!(schema, columns, code, result.code.to_vec());
             store!(schema, columns, code_hash, keccak256(create.init.clone()).into());
             store!(schema, columns, init_code_hash, keccak256(result.code.clone()).into());
+            store!(schema, columns, code_size, result.code.len() as u32);
+            let analysis = codes::analyze_code(schema, &result.code);
+            store!(schema, columns, is_erc20, analysis.as_ref().map(|a| a.is_erc20()));
+            store!(schema, columns, is_erc721, analysis.as_ref().map(|a| a.is_erc721()));
+            let metadata_hash = analysis.as_ref().and_then(|a| a.metadata_hash.clone());
+            store!(schema, columns, metadata_hash, metadata_hash);
+            store!(schema, columns, compiler, analysis.and_then(|a| a.compiler));
         }
     }
     Ok(())
@@ -0,0 +1,344 @@
+use crate::*;
+use ethers::prelude::*;
+use polars::prelude::*;
+use std::collections::{hash_map::Entry, HashMap};
+
+/// columns for dex swaps
+#[cryo_to_df::to_df(Datatype::DexSwaps)]
+#[derive(Default)]
+pub struct DexSwaps {
+    n_rows: u64,
+    block_number: Vec<u32>,
+    transaction_index: Vec<u32>,
+    log_index: Vec<u32>,
+    transaction_hash: Vec<Vec<u8>>,
+    protocol: Vec<String>,
+    pool: Vec<Vec<u8>>,
+    sender: Vec<Option<Vec<u8>>>,
+    recipient: Vec<Option<Vec<u8>>>,
+    token_in: Vec<Option<Vec<u8>>>,
+    token_out: Vec<Option<Vec<u8>>>,
+    amount_in: Vec<U256>,
+    amount_out: Vec<U256>,
+    chain_id: Vec<u64>,
+}
+
+#[async_trait::async_trait]
+impl Dataset for DexSwaps {
+    fn name() -> &'static str {
+        "dex_swaps"
+    }
+
+    fn default_sort() -> Vec<String> {
+        vec!["block_number".to_string(), "log_index".to_string()]
+    }
+
+    fn dedup_keys() -> Option<Vec<String>> {
+        Some(vec!["block_number".to_string(), "log_index".to_string()])
+    }
+
+    fn optional_parameters() -> Vec<Dim> {
+        vec![Dim::Contract]
+    }
+
+    fn use_block_ranges() -> bool {
+        true
+    }
+}
+
+/// dex protocol whose swap events are decoded
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SwapProtocol {
+    /// uniswap v2 pairs and their forks
+    UniswapV2,
+    /// uniswap v3 pools and their forks
+    UniswapV3,
+    /// curve stableswap and cryptoswap pools
+    Curve,
+    /// balancer v2 vault
+    Balancer,
+}
+
+impl SwapProtocol {
+    /// all supported protocols
+    pub fn all() -> Vec<SwapProtocol> {
+        vec![
+            SwapProtocol::UniswapV2,
+            SwapProtocol::UniswapV3,
+            SwapProtocol::Curve,
+            SwapProtocol::Balancer,
+        ]
+    }
+
+    /// parse protocol from name
+    pub fn from_name(name: &str) -> ::core::result::Result<SwapProtocol, ParseError> {
+        match name.to_lowercase().as_str() {
+            "uniswap_v2" | "univ2" => Ok(SwapProtocol::UniswapV2),
+            "uniswap_v3" | "univ3" => Ok(SwapProtocol::UniswapV3),
+            "curve" => Ok(SwapProtocol::Curve),
+            "balancer" => Ok(SwapProtocol::Balancer),
+            _ => Err(ParseError::ParseError(format!(
+                "unknown dex protocol: {}, use uniswap_v2, uniswap_v3, curve, or balancer",
+                name
+            ))),
+        }
+    }
+
+    /// name of protocol
+    pub fn name(&self) -> &'static str {
+        match self {
+            SwapProtocol::UniswapV2 => "uniswap_v2",
+            SwapProtocol::UniswapV3 => "uniswap_v3",
+            SwapProtocol::Curve => "curve",
+            SwapProtocol::Balancer => "balancer",
+        }
+    }
+
+    /// event hashes of swap events of protocol
+    pub fn topics(&self) -> Vec<H256> {
+        match self {
+            SwapProtocol::UniswapV2 => vec![*EVENT_UNISWAP_V2_SWAP],
+            SwapProtocol::UniswapV3 => vec![*EVENT_UNISWAP_V3_SWAP],
+            SwapProtocol::Curve => {
+                vec![*EVENT_CURVE_TOKEN_EXCHANGE, *EVENT_CURVE_CRYPTO_TOKEN_EXCHANGE]
+            }
+            SwapProtocol::Balancer => vec![*EVENT_BALANCER_SWAP],
+        }
+    }
+}
+
+/// token of swap, either known from the event or given by index of token in pool
+#[derive(Debug, PartialEq)]
+enum SwapToken {
+    Address(Vec<u8>),
+    Index(u64),
+}
+
+#[derive(Debug, PartialEq)]
+struct DecodedSwap {
+    protocol: SwapProtocol,
+    pool: Vec<u8>,
+    sender: Option<Vec<u8>>,
+    recipient: Option<Vec<u8>>,
+    token_in: SwapToken,
+    token_out: SwapToken,
+    amount_in: U256,
+    amount_out: U256,
+}
+
+/// decode swap event of one of protocols, None if log is not a swap
+fn decode_swap(log: &Log, protocols: &[SwapProtocol]) -> Option<DecodedSwap> {
+    let topic0 = log.topics.first()?;
+    let protocol = *protocols.iter().find(|protocol| protocol.topics().contains(topic0))?;
+    let words: Vec<U256> = log.data.chunks(32).map(U256::from_big_endian).collect();
+    let topic_address = |i: usize| log.topics.get(i).map(|topic| topic.as_bytes()[12..].to_vec());
+    let pool = log.address.as_bytes().to_vec();
+    match protocol {
+        SwapProtocol::UniswapV2 if log.topics.len() == 3 && words.len() == 4 => {
+            let (amount0_in, amount1_in, amount0_out, amount1_out) =
+                (words[0], words[1], words[2], words[3]);
+            let (token_in, token_out, amount_in, amount_out) = match amount0_in.is_zero() {
+                false => (0, 1, amount0_in, amount1_out),
+                true => (1, 0, amount1_in, amount0_out),
+            };
+            Some(DecodedSwap {
+                protocol,
+                pool,
+                sender: topic_address(1),
+                recipient: topic_address(2),
+                token_in: SwapToken::Index(token_in),
+                token_out: SwapToken::Index(token_out),
+                amount_in,
+                amount_out,
+            })
+        }
+        SwapProtocol::UniswapV3 if log.topics.len() == 3 && words.len() == 5 => {
+            // amounts are deltas of pool balances, positive for the token sent to the pool
+            let (amount0, amount1) = (I256::from_raw(words[0]), I256::from_raw(words[1]));
+            let (token_in, token_out, amount_in, amount_out) = match amount0.is_positive() {
+                true => (0, 1, amount0, amount1),
+                false => (1, 0, amount1, amount0),
+            };
+            Some(DecodedSwap {
+                protocol,
+                pool,
+                sender: topic_address(1),
+                recipient: topic_address(2),
+                token_in: SwapToken::Index(token_in),
+                token_out: SwapToken::Index(token_out),
+                amount_in: amount_in.unsigned_abs(),
+                amount_out: amount_out.unsigned_abs(),
+            })
+        }
+        SwapProtocol::Curve if log.topics.len() == 2 && words.len() == 4 => Some(DecodedSwap {
+            protocol,
+            pool,
+            sender: topic_address(1),
+            recipient: topic_address(1),
+            token_in: SwapToken::Index(words[0].low_u64()),
+            token_out: SwapToken::Index(words[2].low_u64()),
+            amount_in: words[1],
+            amount_out: words[3],
+        }),
+        SwapProtocol::Balancer if log.topics.len() == 4 && words.len() == 2 => {
+            // pool ids start with the address of the pool
+            Some(DecodedSwap {
+                protocol,
+                pool: log.topics[1].as_bytes()[..20].to_vec(),
+                sender: None,
+                recipient: None,
+                token_in: SwapToken::Address(topic_address(2)?),
+                token_out: SwapToken::Address(topic_address(3)?),
+                amount_in: words[0],
+                amount_out: words[1],
+            })
+        }
+        _ => None,
+    }
+}
+
+type Result<T> = ::core::result::Result<T, CollectError>;
+
+/// tokens of pools, keyed by pool address and index of token in pool
+pub type PoolTokens = HashMap<(Vec<u8>, u64), Option<Vec<u8>>>;
+
+type LogsAndPoolTokens = (Vec<Log>, Vec<SwapProtocol>, PoolTokens);
+
+#[async_trait::async_trait]
+impl CollectByBlock for DexSwaps {
+    type Response = LogsAndPoolTokens;
+
+    async fn extract(request: Params, source: Arc<Source>, _: Schemas) -> Result<Self::Response> {
+        let topic0s =
+            source.swap_protocols.iter().flat_map(|protocol| protocol.topics()).map(Some).collect();
+        let topics = [Some(ValueOrArray::Array(topic0s)), None, None, None];
+        let address = request.contract.as_ref().map(|x| ValueOrArray::Value(H160::from_slice(x)));
+        let filter = Filter { topics, address, ..request.ethers_log_filter()? };
+        let logs = source.get_logs(&filter).await?;
+        let pool_tokens = get_pool_tokens(&logs, &source).await?;
+        Ok((logs, source.swap_protocols.clone(), pool_tokens))
+    }
+
+    fn transform(response: Self::Response, columns: &mut Self, schemas: &Schemas) -> Result<()> {
+        let schema = schemas.get(&Datatype::DexSwaps).ok_or(err("schema not provided"))?;
+        process_dex_swaps(response, columns, schema)
+    }
+}
+
+#[async_trait::async_trait]
+impl CollectByTransaction for DexSwaps {
+    type Response = LogsAndPoolTokens;
+
+    async fn extract(request: Params, source: Arc<Source>, _: Schemas) -> Result<Self::Response> {
+        let logs = source.fetcher.get_transaction_logs(request.transaction_hash()?).await?;
+        let pool_tokens = get_pool_tokens(&logs, &source).await?;
+        Ok((logs, source.swap_protocols.clone(), pool_tokens))
+    }
+
+    fn transform(response: Self::Response, columns: &mut Self, schemas: &Schemas) -> Result<()> {
+        let schema = schemas.get(&Datatype::DexSwaps).ok_or(err("schema not provided"))?;
+        process_dex_swaps(response, columns, schema)
+    }
+}
+
+/// resolve tokens of swaps that identify their tokens by index in pool
+async fn get_pool_tokens(logs: &[Log], source: &Source) -> Result<PoolTokens> {
+    let mut pool_tokens = HashMap::new();
+    for swap in logs.iter().filter_map(|log| decode_swap(log, &source.swap_protocols)) {
+        for token in [swap.token_in, swap.token_out] {
+            if let SwapToken::Index(index) = token {
+                if let Entry::Vacant(entry) = pool_tokens.entry((swap.pool.clone(), index)) {
+                    entry.insert(source.get_pool_token(swap.pool.clone(), index).await?);
+                }
+            }
+        }
+    }
+    Ok(pool_tokens)
+}
+
+/// process swap logs into columns
+fn process_dex_swaps(
+    response: LogsAndPoolTokens,
+    columns: &mut DexSwaps,
+    schema: &Table,
+) -> Result<()> {
+    let (logs, protocols, pool_tokens) = response;
+    let resolve = |pool: &Vec<u8>, token: SwapToken| match token {
+        SwapToken::Address(address) => Some(address),
+        SwapToken::Index(index) => pool_tokens.get(&(pool.clone(), index)).cloned().flatten(),
+    };
+    for log in logs.iter() {
+        if let (Some(bn), Some(tx), Some(ti), Some(li), Some(swap)) = (
+            log.block_number,
+            log.transaction_hash,
+            log.transaction_index,
+            log.log_index,
+            decode_swap(log, &protocols),
+        ) {
+            columns.n_rows += 1;
+            store!(schema, columns, block_number, bn.as_u32());
+            store!(schema, columns, transaction_index, ti.as_u32());
+            store!(schema, columns, log_index, li.as_u32());
+            store!(schema, columns, transaction_hash, tx.as_bytes().to_vec());
+            store!(schema, columns, protocol, swap.protocol.name().to_string());
+            store!(schema, columns, token_in, resolve(&swap.pool, swap.token_in));
+            store!(schema, columns, token_out, resolve(&swap.pool, swap.token_out));
+            store!(schema, columns, pool, swap.pool);
+            store!(schema, columns, sender, swap.sender);
+            store!(schema, columns, recipient, swap.recipient);
+            store!(schema, columns, amount_in, swap.amount_in);
+            store!(schema, columns, amount_out, swap.amount_out);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn word(value: U256) -> Vec<u8> {
+        let mut word = [0u8; 32];
+        value.to_big_endian(&mut word);
+        word.to_vec()
+    }
+
+    #[test]
+    fn test_decode_swap() {
+        let address = |byte: u8| H256::from(H160::repeat_byte(byte));
+        let protocols = SwapProtocol::all();
+
+        // uniswap v3 swap of 5 token1 for 3 token0
+        let log = Log {
+            address: H160::repeat_byte(9),
+            topics: vec![*EVENT_UNISWAP_V3_SWAP, address(1), address(2)],
+            data: [
+                word(I256::from(-3).into_raw()),
+                word(5.into()),
+                word(0.into()),
+                word(0.into()),
+                word(0.into()),
+            ]
+            .concat()
+            .into(),
+            ..Default::default()
+        };
+        let swap = decode_swap(&log, &protocols).unwrap();
+        assert_eq!(swap.pool, vec![9; 20]);
+        assert_eq!(swap.recipient, Some(vec![2; 20]));
+        assert_eq!((swap.token_in, swap.token_out), (SwapToken::Index(1), SwapToken::Index(0)));
+        assert_eq!((swap.amount_in, swap.amount_out), (5.into(), 3.into()));
+        assert!(decode_swap(&log, &[SwapProtocol::UniswapV2]).is_none());
+
+        // balancer swap names its tokens
+        let log = Log {
+            topics: vec![*EVENT_BALANCER_SWAP, H256::repeat_byte(7), address(3), address(4)],
+            data: [word(10.into()), word(20.into())].concat().into(),
+            ..Default::default()
+        };
+        let swap = decode_swap(&log, &protocols).unwrap();
+        assert_eq!(swap.pool, vec![7; 20]);
+        assert_eq!(swap.token_in, SwapToken::Address(vec![3; 20]));
+        assert_eq!(swap.token_out, SwapToken::Address(vec![4; 20]));
+    }
+}
@@ -0,0 +1,81 @@
+use crate::*;
+use ethers::prelude::*;
+use polars::prelude::*;
+use std::collections::HashMap;
+
+/// columns for erc20 allowances, using `--address` for owners and `--to-address` for spenders
+#[cryo_to_df::to_df(Datatype::Erc20Allowances)]
+#[derive(Default)]
+pub struct Erc20Allowances {
+    n_rows: u64,
+    block_number: Vec<u32>,
+    erc20: Vec<Vec<u8>>,
+    owner: Vec<Vec<u8>>,
+    spender: Vec<Vec<u8>>,
+    allowance: Vec<Option<U256>>,
+    chain_id: Vec<u64>,
+}
+
+#[async_trait::async_trait]
+impl Dataset for Erc20Allowances {
+    fn name() -> &'static str {
+        "erc20_allowances"
+    }
+
+    fn default_sort() -> Vec<String> {
+        vec![
+            "block_number".to_string(),
+            "erc20".to_string(),
+            "owner".to_string(),
+            "spender".to_string(),
+        ]
+    }
+
+    fn required_parameters() -> Vec<Dim> {
+        vec![Dim::Contract, Dim::Address, Dim::ToAddress]
+    }
+}
+
+type Result<T> = ::core::result::Result<T, CollectError>;
+
+type BlockErc20OwnerSpenderAllowance = (u32, Vec<u8>, Vec<u8>, Vec<u8>, Option<U256>);
+
+#[async_trait::async_trait]
+impl CollectByBlock for Erc20Allowances {
+    type Response = BlockErc20OwnerSpenderAllowance;
+
+    async fn extract(
+        request: Params,
+        source: Arc<Source>,
+        _schemas: Schemas,
+    ) -> Result<Self::Response> {
+        let owner = request.address()?;
+        let spender = request.to_address()?;
+        let mut call_data = FUNCTION_ERC20_ALLOWANCE.clone();
+        call_data.extend(vec![0; 12]);
+        call_data.extend(owner.clone());
+        call_data.extend(vec![0; 12]);
+        call_data.extend(spender.clone());
+        let block_number = request.block_number()?;
+        let contract = request.ethers_contract()?;
+        let allowance = source.call_batched(contract, call_data, block_number).await.ok();
+        let allowance = allowance.map(|x| x.to_vec().as_slice().into());
+        Ok((block_number as u32, request.contract()?, owner, spender, allowance))
+    }
+
+    fn transform(response: Self::Response, columns: &mut Self, schemas: &Schemas) -> Result<()> {
+        let schema = schemas.get(&Datatype::Erc20Allowances).ok_or(err("schema not provided"))?;
+        let (block, erc20, owner, spender, allowance) = response;
+        columns.n_rows += 1;
+        store!(schema, columns, block_number, block);
+        store!(schema, columns, erc20, erc20);
+        store!(schema, columns, owner, owner);
+        store!(schema, columns, spender, spender);
+        store!(schema, columns, allowance, allowance);
+        Ok(())
+    }
+}
+
+impl CollectByTransaction for Erc20Allowances {
+    type Response = ();
+}
@@ -12,6 +12,7 @@ pub struct Erc20Balances {
     erc20: Vec<Vec<u8>>,
     address: Vec<Vec<u8>>,
     balance: Vec<Option<U256>>,
+    balance_float: Vec<Option<f64>>,
     chain_id: Vec<u64>,
 }
 
@@ -21,6 +22,10 @@ impl Dataset for Erc20Balances {
         "erc20_balances"
     }
 
+    fn default_columns() -> Option<Vec<&'static str>> {
+        Some(vec!["block_number", "erc20", "address", "balance", "chain_id"])
+    }
+
     fn default_sort() -> Vec<String> {
         vec!["block_number".to_string()]
     }
@@ -32,7 +37,7 @@ impl Dataset for Erc20Balances {
 
 type Result<T> = ::core::result::Result<T, CollectError>;
 
-type BlockErc20AddressBalance = (u32, Vec<u8>, Vec<u8>, Option<U256>);
+type BlockErc20AddressBalance = (u32, Vec<u8>, Vec<u8>, Option<U256>, Option<u32>);
 
 #[async_trait::async_trait]
 impl CollectByBlock for Erc20Balances {
@@ -41,27 +46,44 @@ impl CollectByBlock for Erc20Balances {
     async fn extract(
         request: Params,
         source: Arc<Source>,
-        _schemas: Schemas,
+        schemas: Schemas,
     ) -> Result<Self::Response> {
         let signature = FUNCTION_ERC20_BALANCE_OF.clone();
         let mut call_data = signature.clone();
         call_data.extend(vec![0; 12]);
         call_data.extend(request.address()?);
-        let block_number = request.ethers_block_number()?;
+        let block_number = request.block_number()?;
         let contract = request.ethers_contract()?;
-        let balance = source.fetcher.call2(contract, call_data, block_number).await.ok();
+        let balance = source.call_batched(contract, call_data, block_number).await.ok();
         let balance = balance.map(|x| x.to_vec().as_slice().into());
-        Ok((request.block_number()? as u32, request.contract()?, request.address()?, balance))
+        let schema = schemas.get(&Datatype::Erc20Balances).ok_or(err("schema not provided"))?;
+        let decimals = match schema.has_column("balance_float") {
+            true => source.get_erc20_decimals(request.contract()?).await?,
+            false => None,
+        };
+        Ok((
+            request.block_number()? as u32,
+            request.contract()?,
+            request.address()?,
+            balance,
+            decimals,
+        ))
     }
 
     fn transform(response: Self::Response, columns: &mut Self, schemas: &Schemas) -> Result<()> {
         let schema = schemas.get(&Datatype::Erc20Balances).ok_or(err("schema not provided"))?;
-        let (block, erc20, address, balance) = response;
+        let (block, erc20, address, balance, decimals) = response;
         columns.n_rows += 1;
         store!(schema, columns, block_number, block);
         store!(schema, columns, erc20, erc20);
         store!(schema, columns, address, address);
         store!(schema, columns, balance, balance);
+        store!(
+            schema,
+            columns,
+            balance_float,
+            balance.zip(decimals).and_then(|(b, d)| u256_to_f64_decimals(b, d))
+        );
         Ok(())
     }
 }
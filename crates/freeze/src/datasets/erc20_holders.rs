@@ -0,0 +1,319 @@
+use crate::*;
+use ethers::prelude::*;
+use polars::prelude::*;
+use std::{
+    collections::{BTreeMap, HashMap},
+    path::Path,
+};
+
+/// columns for transactions
+#[cryo_to_df::to_df(Datatype::Erc20Holders)]
+#[derive(Default)]
+pub struct Erc20Holders {
+    n_rows: u64,
+    block_number: Vec<u32>,
+    erc20: Vec<Vec<u8>>,
+    holder: Vec<Vec<u8>>,
+    balance: Vec<U256>,
+    balance_float: Vec<Option<f64>>,
+    chain_id: Vec<u64>,
+}
+
+impl Dataset for Erc20Holders {
+    fn name() -> &'static str {
+        "erc20_holders"
+    }
+
+    fn default_columns() -> Option<Vec<&'static str>> {
+        Some(vec!["block_number", "erc20", "holder", "balance", "chain_id"])
+    }
+
+    fn default_sort() -> Vec<String> {
+        vec!["block_number".to_string(), "erc20".to_string(), "holder".to_string()]
+    }
+
+    fn required_parameters() -> Vec<Dim> {
+        vec![Dim::Contract]
+    }
+}
+
+type Result<T> = ::core::result::Result<T, CollectError>;
+
+/// transfer of value from an address to an address at a block
+type Transfer = (u64, Vec<u8>, Vec<u8>, U256);
+
+type BlockErc20Holders = (u32, Vec<u8>, Vec<(Vec<u8>, U256)>, Option<u32>);
+
+#[async_trait::async_trait]
+impl CollectByBlock for Erc20Holders {
+    type Response = BlockErc20Holders;
+
+    /// transfers of each erc20 are fetched once through the last block of the chunk, and the
+    /// balances of each block are aggregated from them in block order, rather than fetching the
+    /// transfers of every block from genesis again
+    async fn collect_by_block(
+        partition: Partition,
+        source: Arc<Source>,
+        schemas: &HashMap<Datatype, Table>,
+        _inner_request_size: Option<u64>,
+    ) -> Result<HashMap<Datatype, DataFrame>> {
+        let mut blocks_of_erc20s: BTreeMap<Vec<u8>, (Params, Vec<u64>)> = BTreeMap::new();
+        for request in partition.param_sets(None)?.into_iter() {
+            let block_number = request.block_number()?;
+            let (_, blocks) =
+                blocks_of_erc20s.entry(request.contract()?).or_insert((request, vec![]));
+            blocks.push(block_number);
+        }
+        let schema = schemas.get(&Datatype::Erc20Holders).ok_or(err("schema not provided"))?;
+        let mut columns = Self::default();
+        for (contract, (request, mut blocks)) in blocks_of_erc20s.into_iter() {
+            blocks.sort();
+            blocks.dedup();
+            let last_block = match blocks.last() {
+                Some(last_block) => *last_block,
+                None => continue,
+            };
+            let transfers = match &source.erc20_transfers_dir {
+                Some(dir) => read_transfers(dir, &contract, last_block)?,
+                None => fetch_transfers(&source, request.ethers_contract()?, last_block).await?,
+            };
+            let decimals = match schema.has_column("balance_float") {
+                true => source.get_erc20_decimals(contract.clone()).await?,
+                false => None,
+            };
+            for (block, holders) in aggregate_balances(transfers, &blocks)?.into_iter() {
+                let response = (block as u32, contract.clone(), holders, decimals);
+                <Self as CollectByBlock>::transform(response, &mut columns, schemas)?;
+            }
+        }
+        columns.create_dfs(schemas, source.chain_id)
+    }
+
+    fn transform(response: Self::Response, columns: &mut Self, schemas: &Schemas) -> Result<()> {
+        let schema = schemas.get(&Datatype::Erc20Holders).ok_or(err("schema not provided"))?;
+        let (block, erc20, holders, decimals) = response;
+        for (holder, balance) in holders.into_iter() {
+            columns.n_rows += 1;
+            store!(schema, columns, block_number, block);
+            store!(schema, columns, erc20, erc20.clone());
+            store!(schema, columns, holder, holder);
+            store!(schema, columns, balance, balance);
+            store!(
+                schema,
+                columns,
+                balance_float,
+                decimals.and_then(|d| u256_to_f64_decimals(balance, d))
+            );
+        }
+        Ok(())
+    }
+}
+
+impl CollectByTransaction for Erc20Holders {
+    type Response = ();
+}
+
+/// fetch transfers of erc20 from genesis through block, in block order
+async fn fetch_transfers(source: &Source, erc20: H160, block: u64) -> Result<Vec<Transfer>> {
+    let filter = Filter::new()
+        .address(erc20)
+        .topic0(*EVENT_ERC20_TRANSFER)
+        .from_block(0)
+        .to_block(BlockNumber::Number(block.into()));
+    let logs = source.get_logs(&filter).await?;
+    Ok(logs
+        .into_iter()
+        .filter(|log| log.topics.len() == 3 && log.data.len() == 32)
+        .filter_map(|log| {
+            Some((
+                log.block_number?.as_u64(),
+                log.topics[1].as_bytes()[12..].to_vec(),
+                log.topics[2].as_bytes()[12..].to_vec(),
+                log.data.to_vec().as_slice().into(),
+            ))
+        })
+        .collect())
+}
+
+/// read transfers of erc20 through block from the erc20_transfers output files of directory, in
+/// block order
+///
+/// the files must cover every block from genesis through block, since balances are aggregated
+/// from every transfer of the erc20
+fn read_transfers(dir: &Path, erc20: &[u8], block: u64) -> Result<Vec<Transfer>> {
+    let datatype = Datatype::Erc20Transfers;
+    let files = find_file_block_ranges(dir, &datatype)?;
+    let empty = find_empty_block_ranges(dir, &datatype)?;
+    let ranges: Vec<(u64, u64)> = files
+        .iter()
+        .chain(empty.iter())
+        .map(|range| (range.start_block, range.end_block))
+        .collect();
+    if let Some((start, end)) = find_block_gaps(&ranges, Some((0, block))).first() {
+        return Err(CollectError::CollectError(format!(
+            "erc20_transfers files in {} are missing blocks {} to {}",
+            dir.display(),
+            start,
+            end
+        )))
+    }
+
+    let mut transfers = Vec::new();
+    for file in files.iter().filter(|file| file.start_block <= block) {
+        let df = read_output_file(&file.path, None)?;
+        let blocks = df.column("block_number")?.cast(&DataType::UInt64)?;
+        let erc20s = binary_values(&df, "erc20")?;
+        let from_addresses = binary_values(&df, "from_address")?;
+        let to_addresses = binary_values(&df, "to_address")?;
+        let values = u256_values(&df, "value")?;
+        let rows = blocks.u64()?.into_iter().zip(erc20s).zip(from_addresses).zip(to_addresses);
+        for ((((row_block, row_erc20), from), to), value) in rows.zip(values) {
+            if let (Some(row_block), Some(row_erc20), Some(from), Some(to), Some(value)) =
+                (row_block, row_erc20, from, to, value)
+            {
+                if row_block <= block && row_erc20 == erc20 {
+                    transfers.push((row_block, from, to, value));
+                }
+            }
+        }
+    }
+    transfers.sort_by_key(|transfer| transfer.0);
+    Ok(transfers)
+}
+
+/// values of binary column, stored as binary or as hex strings
+fn binary_values(df: &DataFrame, column: &str) -> Result<Vec<Option<Vec<u8>>>> {
+    let series = df.column(column)?;
+    match series.dtype() {
+        DataType::Binary => {
+            Ok(series.binary()?.into_iter().map(|value| value.map(|v| v.to_vec())).collect())
+        }
+        DataType::Utf8 => Ok(series
+            .utf8()?
+            .into_iter()
+            .map(|value| value.and_then(|v| prefix_hex::decode(v).ok()))
+            .collect()),
+        _ => Err(err(&format!("column {} of erc20_transfers is not binary", column))),
+    }
+}
+
+/// values of u256 column, stored as 32 byte binary or as decimal strings
+fn u256_values(df: &DataFrame, column: &str) -> Result<Vec<Option<U256>>> {
+    if let Ok(series) = df.column(&format!("{}_binary", column)) {
+        if let Ok(values) = binary_values(df, series.name()) {
+            return Ok(values
+                .into_iter()
+                .map(|value| value.filter(|v| v.len() == 32).map(|v| U256::from_big_endian(&v)))
+                .collect())
+        }
+    }
+    if let Ok(series) = df.column(&format!("{}_string", column)) {
+        return Ok(series
+            .utf8()?
+            .into_iter()
+            .map(|value| value.and_then(|v| U256::from_dec_str(v).ok()))
+            .collect())
+    }
+    Err(err("erc20_transfers files need a value_binary or value_string column"))
+}
+
+/// aggregate transfers in block order into the balance of each holder at each of blocks, sorted
+/// by holder
+///
+/// mints and burns are transfers from and to the zero address, which is not a holder.
+/// addresses whose balances are zero are omitted. transfers must start at the first transfer of
+/// the erc20, so a holder that sends more than it received is an error
+fn aggregate_balances(
+    transfers: Vec<Transfer>,
+    blocks: &[u64],
+) -> Result<Vec<(u64, Vec<(Vec<u8>, U256)>)>> {
+    let zero_address = vec![0u8; 20];
+    let mut balances: HashMap<Vec<u8>, U256> = HashMap::new();
+    let mut transfers = transfers.into_iter().peekable();
+    let mut output = Vec::new();
+    for block in blocks.iter() {
+        // flows are summed over the transfers of each step before they are applied, so that the
+        // order of transfers within a block does not matter
+        let mut flows: HashMap<Vec<u8>, (U256, U256)> = HashMap::new();
+        while let Some((_, from, to, value)) = transfers.next_if(|transfer| transfer.0 <= *block) {
+            if from != zero_address {
+                let (_, sent) = flows.entry(from).or_default();
+                *sent = sent.saturating_add(value);
+            }
+            if to != zero_address {
+                let (received, _) = flows.entry(to).or_default();
+                *received = received.saturating_add(value);
+            }
+        }
+        for (holder, (received, sent)) in flows.into_iter() {
+            let balance = balances.entry(holder.clone()).or_default();
+            *balance = balance.saturating_add(received).checked_sub(sent).ok_or_else(|| {
+                CollectError::CollectError(format!(
+                    "{} sends more than it received by block {}, so transfers do not start at \
+                    the first transfer of the erc20",
+                    prefix_hex::encode(holder),
+                    block
+                ))
+            })?;
+        }
+        let mut holders: Vec<(Vec<u8>, U256)> = balances
+            .iter()
+            .filter(|(_, balance)| !balance.is_zero())
+            .map(|(holder, balance)| (holder.clone(), *balance))
+            .collect();
+        holders.sort();
+        output.push((*block, holders));
+    }
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_aggregate_balances() {
+        let zero = vec![0u8; 20];
+        let (a, b) = (vec![1u8; 20], vec![2u8; 20]);
+        let transfers = vec![
+            (1, zero.clone(), a.clone(), U256::from(100)),
+            (2, b.clone(), zero.clone(), U256::from(30)),
+            (2, a.clone(), b.clone(), U256::from(30)),
+            (3, a.clone(), a.clone(), U256::from(5)),
+            (5, a.clone(), b.clone(), U256::from(70)),
+        ];
+        assert_eq!(
+            aggregate_balances(transfers.clone(), &[0, 2, 4, 5]).unwrap(),
+            vec![
+                (0, vec![]),
+                (2, vec![(a.clone(), U256::from(70))]),
+                (4, vec![(a.clone(), U256::from(70))]),
+                (5, vec![(b.clone(), U256::from(70))]),
+            ]
+        );
+
+        // transfers that do not start at the first transfer of the erc20 send more than received
+        assert!(aggregate_balances(transfers[1..].to_vec(), &[2]).is_err());
+    }
+
+    #[test]
+    fn test_read_transfers_coverage() {
+        let dir = std::env::temp_dir().join("cryo_test_erc20_holders");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let erc20 = vec![1u8; 20];
+        let path = dir.join("ethereum__erc20_transfers__00001000_to_00001999.parquet");
+        std::fs::write(path, "").unwrap();
+
+        // files that start after genesis miss the transfers that minted earlier balances
+        let e = read_transfers(&dir, &erc20, 1500).unwrap_err();
+        assert!(e.to_string().contains("missing blocks 0 to 999"));
+
+        let path = dir.join("ethereum__erc20_transfers__00000000_to_00000999.parquet");
+        std::fs::write(path, "").unwrap();
+        let e = read_transfers(&dir, &erc20, 2500).unwrap_err();
+        assert!(e.to_string().contains("missing blocks 2000 to 2500"));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -11,6 +11,9 @@ pub struct Erc20Supplies {
     block_number: Vec<u32>,
     erc20: Vec<Vec<u8>>,
     total_supply: Vec<Option<U256>>,
+    total_supply_float: Vec<Option<f64>>,
+    circulating_supply: Vec<Option<U256>>,
+    circulating_supply_float: Vec<Option<f64>>,
     chain_id: Vec<u64>,
 }
 
@@ -20,6 +23,10 @@ impl Dataset for Erc20Supplies {
         "erc20_supplies"
     }
 
+    fn default_columns() -> Option<Vec<&'static str>> {
+        Some(vec!["block_number", "erc20", "total_supply", "chain_id"])
+    }
+
     fn default_sort() -> Vec<String> {
         vec!["erc20".to_string(), "block_number".to_string()]
     }
@@ -31,7 +38,7 @@ impl Dataset for Erc20Supplies {
 
 type Result<T> = ::core::result::Result<T, CollectError>;
 
-type BlockErc20Supply = (u32, Vec<u8>, Option<U256>);
+type BlockErc20Supply = (u32, Vec<u8>, Option<U256>, Option<U256>, Option<u32>);
 
 #[async_trait::async_trait]
 impl CollectByBlock for Erc20Supplies {
@@ -40,25 +47,77 @@ impl CollectByBlock for Erc20Supplies {
     async fn extract(
         request: Params,
         source: Arc<Source>,
-        _schemas: Schemas,
+        schemas: Schemas,
     ) -> Result<Self::Response> {
         let signature: Vec<u8> = FUNCTION_ERC20_TOTAL_SUPPLY.clone();
         let mut call_data = signature.clone();
         call_data.extend(request.contract()?);
-        let block_number = request.ethers_block_number()?;
+        let block_number = request.block_number()?;
         let contract = request.ethers_contract()?;
-        let output = source.fetcher.call2(contract, call_data, block_number).await.ok();
-        let output = output.map(|x| x.to_vec().as_slice().into());
-        Ok((request.block_number()? as u32, request.contract()?, output))
+        let schema = schemas.get(&Datatype::Erc20Supplies).ok_or(err("schema not provided"))?;
+        let excluded_addresses = match schema.has_column("circulating_supply") ||
+            schema.has_column("circulating_supply_float")
+        {
+            true => source.excluded_addresses.clone(),
+            false => vec![],
+        };
+
+        // balances of excluded addresses are requested together with the supply so that all
+        // calls of a block share multicall batches
+        let supply = source.call_batched(contract, call_data, block_number);
+        let excluded_balances = excluded_addresses.iter().map(|address| {
+            let mut call_data = FUNCTION_ERC20_BALANCE_OF.clone();
+            call_data.extend(vec![0; 12]);
+            call_data.extend(address);
+            source.call_batched(contract, call_data, block_number)
+        });
+        let (output, excluded_balances) =
+            tokio::join!(supply, futures::future::join_all(excluded_balances));
+        let output: Option<U256> = output.ok().map(|x| x.to_vec().as_slice().into());
+        let circulating_supply = match excluded_balances.into_iter().collect::<Result<Vec<_>>>() {
+            Ok(balances) => output.map(|supply| {
+                balances.iter().fold(supply, |supply, balance| {
+                    supply.saturating_sub(balance.to_vec().as_slice().into())
+                })
+            }),
+            Err(_) => None,
+        };
+
+        let decimals = match schema.has_column("total_supply_float") ||
+            schema.has_column("circulating_supply_float")
+        {
+            true => source.get_erc20_decimals(request.contract()?).await?,
+            false => None,
+        };
+        Ok((
+            request.block_number()? as u32,
+            request.contract()?,
+            output,
+            circulating_supply,
+            decimals,
+        ))
     }
 
     fn transform(response: Self::Response, columns: &mut Self, schemas: &Schemas) -> Result<()> {
         let schema = schemas.get(&Datatype::Erc20Supplies).ok_or(err("schema not provided"))?;
-        let (block, erc20, total_supply) = response;
+        let (block, erc20, total_supply, circulating_supply, decimals) = response;
         columns.n_rows += 1;
         store!(schema, columns, block_number, block);
         store!(schema, columns, erc20, erc20);
         store!(schema, columns, total_supply, total_supply);
+        store!(
+            schema,
+            columns,
+            total_supply_float,
+            total_supply.zip(decimals).and_then(|(s, d)| u256_to_f64_decimals(s, d))
+        );
+        store!(schema, columns, circulating_supply, circulating_supply);
+        store!(
+            schema,
+            columns,
+            circulating_supply_float,
+            circulating_supply.zip(decimals).and_then(|(s, d)| u256_to_f64_decimals(s, d))
+        );
         Ok(())
     }
 }
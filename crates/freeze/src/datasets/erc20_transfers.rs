@@ -1,7 +1,7 @@
 use crate::*;
 use ethers::prelude::*;
 use polars::prelude::*;
-use std::collections::HashMap;
+use std::collections::{hash_map::Entry, HashMap};
 
 /// columns for transactions
 #[cryo_to_df::to_df(Datatype::Erc20Transfers)]
@@ -16,6 +16,7 @@ pub struct Erc20Transfers {
     from_address: Vec<Vec<u8>>,
     to_address: Vec<Vec<u8>>,
     value: Vec<U256>,
+    value_float: Vec<Option<f64>>,
     chain_id: Vec<u64>,
 }
 
@@ -25,10 +26,28 @@ impl Dataset for Erc20Transfers {
         "erc20_transfers"
     }
 
+    fn default_columns() -> Option<Vec<&'static str>> {
+        Some(vec![
+            "block_number",
+            "transaction_index",
+            "log_index",
+            "transaction_hash",
+            "erc20",
+            "from_address",
+            "to_address",
+            "value",
+            "chain_id",
+        ])
+    }
+
     fn default_sort() -> Vec<String> {
         vec!["block_number".to_string(), "log_index".to_string()]
     }
 
+    fn dedup_keys() -> Option<Vec<String>> {
+        Some(vec!["block_number".to_string(), "log_index".to_string()])
+    }
+
     fn optional_parameters() -> Vec<Dim> {
         vec![Dim::Contract, Dim::Topic0, Dim::Topic1, Dim::Topic2]
     }
@@ -40,19 +59,24 @@ impl Dataset for Erc20Transfers {
 
 type Result<T> = ::core::result::Result<T, CollectError>;
 
+type LogsAndDecimals = (Vec<Log>, HashMap<Vec<u8>, Option<u32>>);
+
 #[async_trait::async_trait]
 impl CollectByBlock for Erc20Transfers {
-    type Response = Vec<Log>;
+    type Response = LogsAndDecimals;
 
     async fn extract(
         request: Params,
         source: Arc<Source>,
-        _schemas: Schemas,
+        schemas: Schemas,
     ) -> Result<Self::Response> {
         let topics = [Some(ValueOrArray::Value(Some(*EVENT_ERC20_TRANSFER))), None, None, None];
         let filter = Filter { topics, ..request.ethers_log_filter()? };
-        let logs = source.fetcher.get_logs(&filter).await?;
-        Ok(logs.into_iter().filter(|x| x.topics.len() == 3 && x.data.len() == 32).collect())
+        let logs = source.get_logs(&filter).await?;
+        let logs: Vec<Log> =
+            logs.into_iter().filter(|x| x.topics.len() == 3 && x.data.len() == 32).collect();
+        let decimals = get_decimals(&logs, &source, &schemas).await?;
+        Ok((logs, decimals))
     }
 
     fn transform(response: Self::Response, columns: &mut Self, schemas: &Schemas) -> Result<()> {
@@ -63,15 +87,17 @@ impl CollectByBlock for Erc20Transfers {
 
 #[async_trait::async_trait]
 impl CollectByTransaction for Erc20Transfers {
-    type Response = Vec<Log>;
+    type Response = LogsAndDecimals;
 
     async fn extract(
         request: Params,
         source: Arc<Source>,
-        _schemas: Schemas,
+        schemas: Schemas,
     ) -> Result<Self::Response> {
         let logs = source.fetcher.get_transaction_logs(request.transaction_hash()?).await?;
-        Ok(logs.into_iter().filter(is_erc20_transfer).collect())
+        let logs: Vec<Log> = logs.into_iter().filter(is_erc20_transfer).collect();
+        let decimals = get_decimals(&logs, &source, &schemas).await?;
+        Ok((logs, decimals))
     }
 
     fn transform(response: Self::Response, columns: &mut Self, schemas: &Schemas) -> Result<()> {
@@ -80,16 +106,37 @@ impl CollectByTransaction for Erc20Transfers {
     }
 }
 
+/// fetch decimals of each erc20 in logs, if decimal-adjusted values are requested
+async fn get_decimals(
+    logs: &[Log],
+    source: &Source,
+    schemas: &Schemas,
+) -> Result<HashMap<Vec<u8>, Option<u32>>> {
+    let schema = schemas.get(&Datatype::Erc20Transfers).ok_or(err("schema not provided"))?;
+    let mut decimals = HashMap::new();
+    if schema.has_column("value_float") {
+        for log in logs.iter() {
+            let erc20 = log.address.as_bytes().to_vec();
+            if let Entry::Vacant(entry) = decimals.entry(erc20) {
+                let value = source.get_erc20_decimals(entry.key().clone()).await?;
+                entry.insert(value);
+            }
+        }
+    }
+    Ok(decimals)
+}
+
 fn is_erc20_transfer(log: &Log) -> bool {
     log.topics.len() == 3 && log.data.len() == 32 && log.topics[0] == *EVENT_ERC20_TRANSFER
 }
 
 /// process block into columns
 fn process_erc20_transfers(
-    logs: Vec<Log>,
+    response: LogsAndDecimals,
     columns: &mut Erc20Transfers,
     schema: &Table,
 ) -> Result<()> {
+    let (logs, decimals) = response;
     for log in logs.iter() {
         if let (Some(bn), Some(tx), Some(ti), Some(li)) =
             (log.block_number, log.transaction_hash, log.transaction_index, log.log_index)
@@ -102,7 +149,15 @@ fn process_erc20_transfers(
             store!(schema, columns, erc20, log.address.as_bytes().to_vec());
             store!(schema, columns, from_address, log.topics[1].as_bytes()[12..].to_vec());
             store!(schema, columns, to_address, log.topics[2].as_bytes()[12..].to_vec());
-            store!(schema, columns, value, log.data.to_vec().as_slice().into());
+            let value: U256 = log.data.to_vec().as_slice().into();
+            let erc20_decimals = decimals.get(log.address.as_bytes()).cloned().flatten();
+            store!(schema, columns, value, value);
+            store!(
+                schema,
+                columns,
+                value_float,
+                erc20_decimals.and_then(|d| u256_to_f64_decimals(value, d))
+            );
         }
     }
     Ok(())
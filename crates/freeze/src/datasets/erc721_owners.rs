@@ -0,0 +1,112 @@
+use crate::*;
+use ethers::prelude::*;
+use polars::prelude::*;
+use std::collections::HashMap;
+
+/// number of ownerOf calls per Multicall3 call when --multicall is not used
+const OWNER_OF_BATCH_SIZE: usize = 100;
+
+/// columns for transactions
+#[cryo_to_df::to_df(Datatype::Erc721Owners)]
+#[derive(Default)]
+pub struct Erc721Owners {
+    n_rows: u64,
+    block_number: Vec<u32>,
+    erc721: Vec<Vec<u8>>,
+    token_id: Vec<U256>,
+    owner: Vec<Option<Vec<u8>>>,
+    chain_id: Vec<u64>,
+}
+
+impl Dataset for Erc721Owners {
+    fn name() -> &'static str {
+        "erc721_owners"
+    }
+
+    fn default_sort() -> Vec<String> {
+        vec!["erc721".to_string(), "block_number".to_string()]
+    }
+
+    fn required_parameters() -> Vec<Dim> {
+        vec![Dim::Contract]
+    }
+}
+
+type Result<T> = ::core::result::Result<T, CollectError>;
+
+type BlockErc721Owners = (u32, Vec<u8>, Vec<(U256, Option<Vec<u8>>)>);
+
+#[async_trait::async_trait]
+impl CollectByBlock for Erc721Owners {
+    type Response = BlockErc721Owners;
+
+    async fn extract(
+        request: Params,
+        source: Arc<Source>,
+        _schemas: Schemas,
+    ) -> Result<Self::Response> {
+        if source.token_ids.is_empty() {
+            return Err(err("erc721_owners requires token ids, given by --token-id"))
+        }
+        let block_number = request.block_number()?;
+        let contract = request.ethers_contract()?;
+        let calls: Vec<(H160, Vec<u8>)> = source
+            .token_ids
+            .iter()
+            .map(|token_id| {
+                let mut call_data = FUNCTION_ERC721_OWNER_OF.clone();
+                call_data.extend(ethers::abi::encode(&[ethers::abi::Token::Uint(*token_id)]));
+                (contract, call_data)
+            })
+            .collect();
+
+        // calls of burned or unminted tokens revert, giving null owners. at blocks before
+        // Multicall3 was deployed, calls are sent directly to the contract
+        let outputs: Vec<Option<Bytes>> = match &source.multicall {
+            Some(_) => {
+                let calls = calls.into_iter().map(|(address, call_data)| {
+                    source.call_through_multicall(address, call_data, block_number)
+                });
+                futures::future::try_join_all(calls).await?
+            }
+            None => {
+                let batches = calls.chunks(OWNER_OF_BATCH_SIZE).map(|batch| {
+                    source.fetcher.multicall(batch, BlockNumber::Number(block_number.into()))
+                });
+                futures::future::try_join_all(batches).await?.into_iter().flatten().collect()
+            }
+        };
+        let owners = source
+            .token_ids
+            .iter()
+            .zip(outputs)
+            .map(|(token_id, output)| (*token_id, output.and_then(|output| decode_owner(&output))))
+            .collect();
+        Ok((block_number as u32, request.contract()?, owners))
+    }
+
+    fn transform(response: Self::Response, columns: &mut Self, schemas: &Schemas) -> Result<()> {
+        let schema = schemas.get(&Datatype::Erc721Owners).ok_or(err("schema not provided"))?;
+        let (block, erc721, owners) = response;
+        for (token_id, owner) in owners.into_iter() {
+            columns.n_rows += 1;
+            store!(schema, columns, block_number, block);
+            store!(schema, columns, erc721, erc721.clone());
+            store!(schema, columns, token_id, token_id);
+            store!(schema, columns, owner, owner);
+        }
+        Ok(())
+    }
+}
+
+impl CollectByTransaction for Erc721Owners {
+    type Response = ();
+}
+
+/// decode address returned by ownerOf, None if output is not an abi encoded address
+fn decode_owner(output: &[u8]) -> Option<Vec<u8>> {
+    match output.len() == 32 && output[..12].iter().all(|byte| *byte == 0) {
+        true => Some(output[12..].to_vec()),
+        false => None,
+    }
+}
@@ -29,6 +29,10 @@ impl Dataset for Erc721Transfers {
         vec!["block_number".to_string(), "log_index".to_string()]
     }
 
+    fn dedup_keys() -> Option<Vec<String>> {
+        Some(vec!["block_number".to_string(), "log_index".to_string()])
+    }
+
     fn optional_parameters() -> Vec<Dim> {
         vec![Dim::Contract]
     }
@@ -51,7 +55,7 @@ impl CollectByBlock for Erc721Transfers {
     ) -> Result<Self::Response> {
         let topics = [Some(ValueOrArray::Value(Some(*EVENT_ERC721_TRANSFER))), None, None, None];
         let filter = Filter { topics, ..request.ethers_log_filter()? };
-        let logs = source.fetcher.get_logs(&filter).await?;
+        let logs = source.get_logs(&filter).await?;
         Ok(logs.into_iter().filter(|x| x.topics.len() == 4 && x.data.len() == 0).collect())
     }
 
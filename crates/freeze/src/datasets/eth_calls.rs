@@ -14,6 +14,9 @@ pub struct EthCalls {
     call_data_hash: Vec<Vec<u8>>,
     output_data: Vec<Vec<u8>>,
     output_data_hash: Vec<Vec<u8>>,
+    reverted: Vec<bool>,
+    error_signature: Vec<Option<String>>,
+    revert_reason: Vec<Option<String>>,
     chain_id: Vec<u64>,
 }
 
@@ -24,7 +27,15 @@ impl Dataset for EthCalls {
     }
 
     fn default_columns() -> Option<Vec<&'static str>> {
-        Some(vec!["block_number", "contract_address", "call_data", "output_data", "chain_id"])
+        Some(vec![
+            "block_number",
+            "contract_address",
+            "call_data",
+            "output_data",
+            "reverted",
+            "revert_reason",
+            "chain_id",
+        ])
     }
 
     fn default_sort() -> Vec<String> {
@@ -50,7 +61,9 @@ impl Dataset for EthCalls {
 
 type Result<T> = ::core::result::Result<T, CollectError>;
 
-type EthCallsResponse = (u32, Vec<u8>, Vec<u8>, Vec<u8>);
+/// (block number, contract address, call data, output data, reverted), where the output data of
+/// a reverted call is its revert data
+type EthCallsResponse = (u32, Vec<u8>, Vec<u8>, Vec<u8>, bool);
 
 #[async_trait::async_trait]
 impl CollectByBlock for EthCalls {
@@ -67,8 +80,11 @@ impl CollectByBlock for EthCalls {
             ..Default::default()
         };
         let number = request.block_number()?;
-        let output = source.fetcher.call(transaction, number.into()).await?;
-        Ok((number as u32, request.contract()?, request.call_data()?, output.to_vec()))
+        let (output, reverted) = match source.fetcher.call(transaction, number.into()).await {
+            Ok(output) => (output.to_vec(), false),
+            Err(e) => (sources::revert_data(&e).ok_or(e)?, true),
+        };
+        Ok((number as u32, request.contract()?, request.call_data()?, output, reverted))
     }
 
     fn transform(response: Self::Response, columns: &mut Self, schemas: &Schemas) -> Result<()> {
@@ -83,7 +99,11 @@ impl CollectByTransaction for EthCalls {
 }
 
 fn process_eth_call(response: EthCallsResponse, columns: &mut EthCalls, schema: &Table) {
-    let (block_number, contract_address, call_data, output_data) = response;
+    let (block_number, contract_address, call_data, output_data, reverted) = response;
+    let (error_signature, revert_reason) = match reverted {
+        true => decode_revert(&output_data, schema.error_decoder.as_ref()),
+        false => (None, None),
+    };
     columns.n_rows += 1;
     store!(schema, columns, block_number, block_number);
     store!(schema, columns, contract_address, contract_address);
@@ -91,4 +111,7 @@ fn process_eth_call(response: EthCallsResponse, columns: &mut EthCalls, schema:
     store!(schema, columns, call_data_hash, ethers_core::utils::keccak256(call_data).into());
     store!(schema, columns, output_data, output_data.to_vec());
     store!(schema, columns, output_data_hash, ethers_core::utils::keccak256(output_data).into());
+    store!(schema, columns, reverted, reverted);
+    store!(schema, columns, error_signature, error_signature);
+    store!(schema, columns, revert_reason, revert_reason);
 }
@@ -0,0 +1,126 @@
+use crate::*;
+use ethers::prelude::*;
+use polars::prelude::*;
+use std::collections::HashMap;
+
+/// columns for gas usage of transactions, from their receipts
+///
+/// fees are denominated in wei, and block_gas_share is the fraction of the gas used by the
+/// transactions of block that was used by the transaction
+#[cryo_to_df::to_df(Datatype::GasUsage)]
+#[derive(Default)]
+pub struct GasUsage {
+    n_rows: u64,
+    block_number: Vec<Option<u32>>,
+    transaction_index: Vec<u32>,
+    transaction_hash: Vec<Vec<u8>>,
+    from_address: Vec<Vec<u8>>,
+    to_address: Vec<Option<Vec<u8>>>,
+    gas_used: Vec<Option<u64>>,
+    cumulative_gas_used: Vec<u64>,
+    effective_gas_price: Vec<Option<u64>>,
+    fee: Vec<Option<U256>>,
+    block_gas_share: Vec<Option<f64>>,
+    chain_id: Vec<u64>,
+}
+
+#[async_trait::async_trait]
+impl Dataset for GasUsage {
+    fn name() -> &'static str {
+        "gas_usage"
+    }
+
+    fn default_sort() -> Vec<String> {
+        vec!["block_number".to_string(), "transaction_index".to_string()]
+    }
+
+    fn dedup_keys() -> Option<Vec<String>> {
+        Some(vec!["block_number".to_string(), "transaction_index".to_string()])
+    }
+}
+
+type Result<T> = ::core::result::Result<T, CollectError>;
+
+#[async_trait::async_trait]
+impl CollectByBlock for GasUsage {
+    type Response = Vec<TransactionReceipt>;
+
+    async fn extract(
+        request: Params,
+        source: Arc<Source>,
+        _schemas: Schemas,
+    ) -> Result<Self::Response> {
+        source.get_block_receipts(request.block_number()?).await
+    }
+
+    fn transform(response: Self::Response, columns: &mut Self, schemas: &Schemas) -> Result<()> {
+        let schema = schemas.get(&Datatype::GasUsage).ok_or(err("schema not provided"))?;
+        process_gas_usage(&response, columns, schema);
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl CollectByTransaction for GasUsage {
+    type Response = TransactionReceipt;
+
+    async fn extract(
+        request: Params,
+        source: Arc<Source>,
+        _schemas: Schemas,
+    ) -> Result<Self::Response> {
+        source
+            .get_transaction_receipt(request.ethers_transaction_hash()?)
+            .await?
+            .ok_or(CollectError::CollectError("receipt not found".to_string()))
+    }
+
+    // the gas used by the other transactions of block is not known from a single receipt
+    fn transform(response: Self::Response, columns: &mut Self, schemas: &Schemas) -> Result<()> {
+        let schema = schemas.get(&Datatype::GasUsage).ok_or(err("schema not provided"))?;
+        process_receipt_gas(&response, None, columns, schema);
+        Ok(())
+    }
+}
+
+/// process receipts of a block into columns
+pub(crate) fn process_gas_usage(
+    receipts: &[TransactionReceipt],
+    columns: &mut GasUsage,
+    schema: &Table,
+) {
+    let block_gas_used = receipts.iter().map(|receipt| receipt.cumulative_gas_used).max();
+    for receipt in receipts.iter() {
+        process_receipt_gas(receipt, block_gas_used, columns, schema);
+    }
+}
+
+/// process receipt into columns, with the total gas used by its block if known
+pub(crate) fn process_receipt_gas(
+    receipt: &TransactionReceipt,
+    block_gas_used: Option<U256>,
+    columns: &mut GasUsage,
+    schema: &Table,
+) {
+    let fee = match (receipt.gas_used, receipt.effective_gas_price) {
+        (Some(gas_used), Some(gas_price)) => Some(gas_used * gas_price),
+        _ => None,
+    };
+    let block_gas_share = match (receipt.gas_used, block_gas_used) {
+        (Some(gas_used), Some(total)) if !total.is_zero() => {
+            Some(gas_used.as_u64() as f64 / total.as_u64() as f64)
+        }
+        _ => None,
+    };
+    columns.n_rows += 1;
+    store!(schema, columns, block_number, receipt.block_number.map(|x| x.as_u32()));
+    store!(schema, columns, transaction_index, receipt.transaction_index.as_u32());
+    store!(schema, columns, transaction_hash, receipt.transaction_hash.as_bytes().to_vec());
+    store!(schema, columns, from_address, receipt.from.as_bytes().to_vec());
+    store!(schema, columns, to_address, receipt.to.map(|x| x.as_bytes().to_vec()));
+    store!(schema, columns, gas_used, receipt.gas_used.map(|x| x.as_u64()));
+    store!(schema, columns, cumulative_gas_used, receipt.cumulative_gas_used.as_u64());
+    store!(schema, columns, effective_gas_price, receipt.effective_gas_price.map(|x| x.as_u64()));
+    store!(schema, columns, fee, fee);
+    store!(schema, columns, block_gas_share, block_gas_share);
+}
@@ -0,0 +1,140 @@
+use crate::*;
+use ethers::prelude::*;
+use polars::prelude::*;
+use std::collections::HashMap;
+
+/// columns for transactions
+#[cryo_to_df::to_df(Datatype::InternalEthFlows)]
+#[derive(Default)]
+pub struct InternalEthFlows {
+    n_rows: u64,
+    block_number: Vec<u32>,
+    transaction_index: Vec<Option<u32>>,
+    transaction_hash: Vec<Option<Vec<u8>>>,
+    from_address: Vec<Vec<u8>>,
+    to_address: Vec<Vec<u8>>,
+    value: Vec<U256>,
+    n_transfers: Vec<u32>,
+    chain_id: Vec<u64>,
+}
+
+#[async_trait::async_trait]
+impl Dataset for InternalEthFlows {
+    fn name() -> &'static str {
+        "internal_eth_flows"
+    }
+
+    fn default_sort() -> Vec<String> {
+        vec![
+            "block_number".to_string(),
+            "transaction_index".to_string(),
+            "from_address".to_string(),
+            "to_address".to_string(),
+        ]
+    }
+
+    fn relative_cost() -> u64 {
+        8
+    }
+}
+
+type Result<T> = ::core::result::Result<T, CollectError>;
+
+#[async_trait::async_trait]
+impl CollectByBlock for InternalEthFlows {
+    type Response = Vec<Trace>;
+
+    async fn extract(
+        request: Params,
+        source: Arc<Source>,
+        _schemas: Schemas,
+    ) -> Result<Self::Response> {
+        source.trace_block(request.block_number()?).await
+    }
+
+    fn transform(response: Self::Response, columns: &mut Self, schemas: &Schemas) -> Result<()> {
+        let traces = traces::filter_failed_traces(response);
+        process_internal_eth_flows(&traces, columns, schemas)
+    }
+}
+
+#[async_trait::async_trait]
+impl CollectByTransaction for InternalEthFlows {
+    type Response = Vec<Trace>;
+
+    async fn extract(
+        request: Params,
+        source: Arc<Source>,
+        _schemas: Schemas,
+    ) -> Result<Self::Response> {
+        source.fetcher.trace_transaction(request.ethers_transaction_hash()?).await
+    }
+
+    fn transform(response: Self::Response, columns: &mut Self, schemas: &Schemas) -> Result<()> {
+        let traces = traces::filter_failed_traces(response);
+        process_internal_eth_flows(&traces, columns, schemas)
+    }
+}
+
+/// eth moved by trace, as (from, to, value)
+///
+/// delegatecalls and callcodes run code in the context of the caller, moving no eth
+fn eth_transfer(trace: &Trace) -> Option<(H160, H160, U256)> {
+    let (from, to, value) = match &trace.action {
+        Action::Call(action) => match action.call_type {
+            CallType::DelegateCall | CallType::CallCode => return None,
+            _ => (action.from, action.to, action.value),
+        },
+        Action::Create(action) => match trace.result.as_ref() {
+            Some(Res::Create(result)) => (action.from, result.address, action.value),
+            _ => return None,
+        },
+        Action::Suicide(action) => (action.address, action.refund_address, action.balance),
+        Action::Reward(action) => (H160::zero(), action.author, action.value),
+    };
+    (!value.is_zero()).then_some((from, to, value))
+}
+
+/// process traces into the sum of eth moved between each pair of addresses of each transaction
+pub(crate) fn process_internal_eth_flows(
+    traces: &[Trace],
+    columns: &mut InternalEthFlows,
+    schemas: &Schemas,
+) -> Result<()> {
+    let schema = schemas.get(&Datatype::InternalEthFlows).ok_or(err("schema not provided"))?;
+    type FlowKey = (u64, Option<H256>, H160, H160);
+    let mut flows: Vec<(FlowKey, Option<usize>, U256, u32)> = Vec::new();
+    let mut indices: HashMap<FlowKey, usize> = HashMap::new();
+    for trace in traces.iter() {
+        let (from, to, value) = match eth_transfer(trace) {
+            Some(transfer) => transfer,
+            None => continue,
+        };
+        let key = (trace.block_number, trace.transaction_hash, from, to);
+        match indices.get(&key) {
+            Some(index) => {
+                let (_, _, total, n_transfers) = &mut flows[*index];
+                *total = total.saturating_add(value);
+                *n_transfers += 1;
+            }
+            None => {
+                indices.insert(key, flows.len());
+                flows.push((key, trace.transaction_position, value, 1));
+            }
+        }
+    }
+
+    for ((block_number, transaction_hash, from, to), transaction_index, value, n_transfers) in
+        flows.into_iter()
+    {
+        columns.n_rows += 1;
+        store!(schema, columns, block_number, block_number as u32);
+        store!(schema, columns, transaction_index, transaction_index.map(|x| x as u32));
+        store!(schema, columns, transaction_hash, transaction_hash.map(|x| x.as_bytes().to_vec()));
+        store!(schema, columns, from_address, from.as_bytes().to_vec());
+        store!(schema, columns, to_address, to.as_bytes().to_vec());
+        store!(schema, columns, value, value);
+        store!(schema, columns, n_transfers, n_transfers);
+    }
+    Ok(())
+}
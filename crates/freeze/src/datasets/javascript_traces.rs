@@ -0,0 +1,127 @@
+use crate::*;
+use polars::prelude::*;
+use std::collections::HashMap;
+
+/// columns for javascript traces
+#[cryo_to_df::to_df(Datatype::JavascriptTraces)]
+#[derive(Default)]
+pub struct JavascriptTraces {
+    n_rows: u64,
+    block_number: Vec<Option<u32>>,
+    transaction_index: Vec<Option<u32>>,
+    transaction_hash: Vec<Option<Vec<u8>>>,
+    result: Vec<Option<String>>,
+    error: Vec<Option<String>>,
+    js_cols: TracerResults,
+    chain_id: Vec<u64>,
+}
+
+#[async_trait::async_trait]
+impl Dataset for JavascriptTraces {
+    fn name() -> &'static str {
+        "javascript_traces"
+    }
+
+    fn aliases() -> Vec<&'static str> {
+        vec!["js_traces"]
+    }
+
+    fn default_sort() -> Vec<String> {
+        vec!["block_number".to_string(), "transaction_index".to_string()]
+    }
+
+    fn relative_cost() -> u64 {
+        8
+    }
+}
+
+type Result<T> = ::core::result::Result<T, CollectError>;
+
+/// block number, transaction hash, and traces of a block or a transaction
+type TracerResponse = (Option<u32>, Option<Vec<u8>>, Vec<serde_json::Value>);
+
+#[async_trait::async_trait]
+impl CollectByBlock for JavascriptTraces {
+    type Response = TracerResponse;
+
+    async fn extract(
+        request: Params,
+        source: Arc<Source>,
+        schemas: Schemas,
+    ) -> Result<Self::Response> {
+        let block_number = request.block_number()?;
+        let tracer = get_tracer(&schemas)?;
+        let traces =
+            source.fetcher.debug_trace_block_javascript(block_number, &tracer.code).await?;
+        Ok((Some(block_number as u32), None, traces))
+    }
+
+    fn transform(response: Self::Response, columns: &mut Self, schemas: &Schemas) -> Result<()> {
+        process_javascript_traces(response, columns, schemas)
+    }
+}
+
+#[async_trait::async_trait]
+impl CollectByTransaction for JavascriptTraces {
+    type Response = TracerResponse;
+
+    async fn extract(
+        request: Params,
+        source: Arc<Source>,
+        schemas: Schemas,
+    ) -> Result<Self::Response> {
+        let tracer = get_tracer(&schemas)?;
+        let result = source
+            .fetcher
+            .debug_trace_transaction_javascript(request.ethers_transaction_hash()?, &tracer.code)
+            .await?;
+        let trace = serde_json::json!({ "result": result });
+        Ok((None, Some(request.transaction_hash()?), vec![trace]))
+    }
+
+    fn transform(response: Self::Response, columns: &mut Self, schemas: &Schemas) -> Result<()> {
+        process_javascript_traces(response, columns, schemas)
+    }
+}
+
+fn get_tracer(schemas: &Schemas) -> Result<&JsTracer> {
+    schemas
+        .get(&Datatype::JavascriptTraces)
+        .and_then(|schema| schema.js_tracer.as_ref())
+        .ok_or(err("javascript_traces requires a tracer, specify one with --js-tracer"))
+}
+
+/// process traces, each of the form {"txHash": .., "result": ..} or {"txHash": .., "error": ..}
+fn process_javascript_traces(
+    response: TracerResponse,
+    columns: &mut JavascriptTraces,
+    schemas: &Schemas,
+) -> Result<()> {
+    let schema = schemas.get(&Datatype::JavascriptTraces).ok_or(err("schema not provided"))?;
+    let (block_number, tx_hash, traces) = response;
+    let extract_columns = schema.js_tracer.as_ref().is_some_and(|t| !t.columns.is_empty());
+    for (index, mut trace) in traces.into_iter().enumerate() {
+        let result = trace.get_mut("result").map(serde_json::Value::take).filter(|r| !r.is_null());
+        let error = trace.get("error").map(|e| match e {
+            serde_json::Value::String(e) => e.clone(),
+            e => e.to_string(),
+        });
+        let hash = match &tx_hash {
+            Some(tx_hash) => Some(tx_hash.clone()),
+            None => trace
+                .get("txHash")
+                .and_then(|hash| hash.as_str())
+                .and_then(|hash| prefix_hex::decode::<Vec<u8>>(hash).ok()),
+        };
+        columns.n_rows += 1;
+        store!(schema, columns, block_number, block_number);
+        store!(schema, columns, transaction_index, block_number.map(|_| index as u32));
+        store!(schema, columns, transaction_hash, hash);
+        store!(schema, columns, result, result.as_ref().map(|result| result.to_string()));
+        store!(schema, columns, error, error);
+        if extract_columns {
+            columns.js_cols.0.push(result);
+        }
+    }
+    Ok(())
+}
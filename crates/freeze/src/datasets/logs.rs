@@ -37,6 +37,10 @@ impl Dataset for Logs {
         vec!["block_number".to_string(), "log_index".to_string()]
     }
 
+    fn dedup_keys() -> Option<Vec<String>> {
+        Some(vec!["block_number".to_string(), "log_index".to_string()])
+    }
+
     fn optional_parameters() -> Vec<Dim> {
         vec![Dim::Contract, Dim::Topic0, Dim::Topic1, Dim::Topic2, Dim::Topic3]
     }
@@ -57,7 +61,11 @@ impl CollectByBlock for Logs {
         source: Arc<Source>,
         _schemas: Schemas,
     ) -> Result<Self::Response> {
-        source.fetcher.get_logs(&request.ethers_log_filter()?).await
+        let filter = request.ethers_log_filter()?;
+        match source.bloom_prefilter {
+            true => get_logs_bloom_prefiltered(&filter, &source).await,
+            false => source.get_logs(&filter).await,
+        }
     }
 
     fn transform(response: Self::Response, columns: &mut Self, schemas: &Schemas) -> Result<()> {
@@ -84,8 +92,84 @@ impl CollectByTransaction for Logs {
     }
 }
 
+/// get logs of filter, requesting them only for the blocks whose logsBloom may contain a match
+///
+/// headers of every block of the range are fetched first, so this saves requests when few blocks
+/// of a large range can contain matches
+async fn get_logs_bloom_prefiltered(filter: &Filter, source: &Source) -> Result<Vec<Log>> {
+    let (start_block, end_block) = match filter.block_option {
+        FilterBlockOption::Range {
+            from_block: Some(BlockNumber::Number(start_block)),
+            to_block: Some(BlockNumber::Number(end_block)),
+        } => (start_block.as_u64(), end_block.as_u64()),
+        _ => return source.get_logs(filter).await,
+    };
+    let blocks = futures::future::try_join_all(
+        (start_block..=end_block).map(|block_number| source.fetcher.get_block(block_number)),
+    )
+    .await?;
+
+    // merge consecutive blocks that may contain matches into ranges
+    let mut ranges: Vec<(u64, u64)> = Vec::new();
+    for (block_number, block) in (start_block..=end_block).zip(blocks) {
+        let may_match = match block.and_then(|block| block.logs_bloom) {
+            Some(bloom) => bloom_may_match(&bloom, filter),
+            None => true,
+        };
+        match ranges.last_mut() {
+            Some((_, end)) if may_match && *end + 1 == block_number => *end = block_number,
+            _ if may_match => ranges.push((block_number, block_number)),
+            _ => {}
+        }
+    }
+    tracing::debug!(start_block, end_block, n_ranges = ranges.len(), "bloom prefiltered logs");
+
+    let mut logs = Vec::new();
+    for (start, end) in ranges.into_iter() {
+        logs.extend(source.get_logs(&filter.clone().from_block(start).to_block(end)).await?);
+    }
+    Ok(logs)
+}
+
+/// whether bloom may contain a log matching the address and topics of filter
+fn bloom_may_match(bloom: &Bloom, filter: &Filter) -> bool {
+    if bloom.is_zero() {
+        return false
+    }
+    let addresses = match &filter.address {
+        Some(ValueOrArray::Value(address)) => vec![*address],
+        Some(ValueOrArray::Array(addresses)) => addresses.clone(),
+        None => vec![],
+    };
+    if !addresses.is_empty() &&
+        !addresses.iter().any(|address| bloom_contains(bloom, address.as_bytes()))
+    {
+        return false
+    }
+    filter.topics.iter().flatten().all(|topic| {
+        let topics = match topic {
+            ValueOrArray::Value(topic) => vec![*topic],
+            ValueOrArray::Array(topics) => topics.clone(),
+        };
+        // a null topic matches any value
+        topics.iter().any(|topic| match topic {
+            Some(topic) => bloom_contains(bloom, topic.as_bytes()),
+            None => true,
+        })
+    })
+}
+
+/// whether the 3 bits that input sets in a 2048 bit logs bloom are set
+fn bloom_contains(bloom: &Bloom, input: &[u8]) -> bool {
+    let hash = ethers_core::utils::keccak256(input);
+    (0..3).all(|i| {
+        let bit = (((hash[2 * i] as usize) << 8) | hash[2 * i + 1] as usize) & 2047;
+        bloom.0[255 - bit / 8] & (1 << (bit % 8)) != 0
+    })
+}
+
 /// process block into columns
-fn process_logs(logs: Vec<Log>, columns: &mut Logs, schema: &Table) -> Result<()> {
+pub(crate) fn process_logs(logs: Vec<Log>, columns: &mut Logs, schema: &Table) -> Result<()> {
     for log in logs.iter() {
         if let (Some(bn), Some(tx), Some(ti), Some(li)) =
             (log.block_number, log.transaction_hash, log.transaction_index, log.log_index)
@@ -126,3 +210,28 @@ fn process_logs(logs: Vec<Log>, columns: &mut Logs, schema: &Table) -> Result<()
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bloom_may_match() {
+        let address: H160 = "0xc02aaa39b223fe8d0a0e5c4f27ead9083c756cc2".parse().unwrap();
+        let topic = H256::from(ethers_core::utils::keccak256("Transfer(address,address,uint256)"));
+        let mut bloom = Bloom::zero();
+        for input in [address.as_bytes(), topic.as_bytes()] {
+            let hash = ethers_core::utils::keccak256(input);
+            for i in 0..3 {
+                let bit = (((hash[2 * i] as usize) << 8) | hash[2 * i + 1] as usize) & 2047;
+                bloom.0[255 - bit / 8] |= 1 << (bit % 8);
+            }
+        }
+        let filter = Filter::new().address(address).topic0(topic);
+        assert!(bloom_may_match(&bloom, &filter));
+        assert!(!bloom_may_match(&bloom, &Filter::new().address(H160::zero())));
+        assert!(!bloom_may_match(&bloom, &Filter::new().topic1(H256::zero())));
+        assert!(bloom_may_match(&bloom, &Filter::new().address(vec![H160::zero(), address])));
+        assert!(!bloom_may_match(&Bloom::zero(), &Filter::new()));
+    }
+}
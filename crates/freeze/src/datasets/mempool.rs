@@ -0,0 +1,159 @@
+use crate::*;
+use ethers::prelude::*;
+use polars::prelude::*;
+use std::collections::HashMap;
+
+/// columns for transactions of the mempool, one row for each transaction entering a pool
+#[cryo_to_df::to_df(Datatype::Mempool)]
+#[derive(Default)]
+pub struct Mempool {
+    n_rows: u64,
+    timestamp: Vec<u32>,
+    first_seen_timestamp: Vec<u32>,
+    pool: Vec<String>,
+    transaction_hash: Vec<Vec<u8>>,
+    nonce: Vec<u64>,
+    from_address: Vec<Vec<u8>>,
+    to_address: Vec<Option<Vec<u8>>>,
+    value: Vec<U256>,
+    input: Vec<Vec<u8>>,
+    gas_limit: Vec<u32>,
+    gas_price: Vec<Option<u64>>,
+    transaction_type: Vec<Option<u32>>,
+    max_priority_fee_per_gas: Vec<Option<u64>>,
+    max_fee_per_gas: Vec<Option<u64>>,
+    chain_id: Vec<u64>,
+}
+
+#[async_trait::async_trait]
+impl Dataset for Mempool {
+    fn name() -> &'static str {
+        "mempool"
+    }
+
+    fn aliases() -> Vec<&'static str> {
+        vec!["txpool"]
+    }
+
+    fn default_sort() -> Vec<String> {
+        vec![
+            "timestamp".to_string(),
+            "pool".to_string(),
+            "from_address".to_string(),
+            "nonce".to_string(),
+        ]
+    }
+}
+
+type Result<T> = ::core::result::Result<T, CollectError>;
+
+// the mempool is collected over time by `cryo mempool` rather than by block or transaction
+
+impl CollectByBlock for Mempool {
+    type Response = ();
+}
+
+impl CollectByTransaction for Mempool {
+    type Response = ();
+}
+
+/// transactions of earlier snapshots of the mempool, so that each transaction is recorded once
+/// when it enters a pool rather than in every snapshot it is in
+#[derive(Default)]
+pub struct MempoolTracker {
+    first_seen: HashMap<H256, u32>,
+    pools: HashMap<H256, String>,
+}
+
+impl MempoolTracker {
+    /// record the transactions of rows of an earlier collection as seen, such as the rows of a
+    /// file that is being appended to
+    pub fn seed(&mut self, df: &DataFrame) -> Result<()> {
+        let (Ok(hashes), Ok(pools)) = (df.column("transaction_hash"), df.column("pool")) else {
+            return Ok(())
+        };
+        let first_seen = match df.column("first_seen_timestamp") {
+            Ok(column) => column.u32()?.into_iter().collect(),
+            Err(_) => match df.column("timestamp") {
+                Ok(column) => column.u32()?.into_iter().collect(),
+                Err(_) => vec![None; df.height()],
+            },
+        };
+        let rows = hashes.binary()?.into_iter().zip(pools.utf8()?).zip(first_seen);
+        for ((hash, pool), first_seen) in rows {
+            let (Some(hash), Some(pool)) = (hash, pool) else { continue };
+            if hash.len() != 32 {
+                continue
+            }
+            let hash = H256::from_slice(hash);
+            if let Some(timestamp) = first_seen {
+                let entry = self.first_seen.entry(hash).or_insert(timestamp);
+                *entry = (*entry).min(timestamp);
+            }
+            self.pools.insert(hash, pool.to_string());
+        }
+        Ok(())
+    }
+
+    /// rows of the transactions of txpool that were not in the same pool in the previous snapshot
+    pub fn snapshot(
+        &mut self,
+        txpool: TxpoolContent,
+        timestamp: u32,
+        schemas: &Schemas,
+        chain_id: u64,
+    ) -> Result<DataFrame> {
+        let schema = schemas.get(&Datatype::Mempool).ok_or(err("schema not provided"))?;
+        let mut columns = Mempool::default();
+        let mut first_seen = HashMap::new();
+        let mut pools = HashMap::new();
+        let txpool = [("pending", txpool.pending), ("queued", txpool.queued)];
+        for (pool, transactions) in txpool.into_iter() {
+            for tx in transactions.into_values().flat_map(|nonces| nonces.into_values()) {
+                let seen = *self.first_seen.get(&tx.hash).unwrap_or(&timestamp);
+                if self.pools.get(&tx.hash).map(|p| p.as_str()) != Some(pool) {
+                    process_mempool_transaction(&tx, pool, timestamp, seen, &mut columns, schema);
+                }
+                first_seen.insert(tx.hash, seen);
+                pools.insert(tx.hash, pool.to_string());
+            }
+        }
+        // forget transactions that left the mempool
+        self.first_seen = first_seen;
+        self.pools = pools;
+        let mut dfs = columns.create_dfs(schemas, chain_id)?;
+        dfs.remove(&Datatype::Mempool).ok_or(err("could not create mempool dataframe"))
+    }
+}
+
+/// process transaction of pool into columns
+fn process_mempool_transaction(
+    tx: &Transaction,
+    pool: &str,
+    timestamp: u32,
+    first_seen: u32,
+    columns: &mut Mempool,
+    schema: &Table,
+) {
+    columns.n_rows += 1;
+    store!(schema, columns, timestamp, timestamp);
+    store!(schema, columns, first_seen_timestamp, first_seen);
+    store!(schema, columns, pool, pool.to_string());
+    store!(schema, columns, transaction_hash, tx.hash.as_bytes().to_vec());
+    store!(schema, columns, nonce, tx.nonce.as_u64());
+    store!(schema, columns, from_address, tx.from.as_bytes().to_vec());
+    store!(schema, columns, to_address, tx.to.map(|x| x.as_bytes().to_vec()));
+    store!(schema, columns, value, tx.value);
+    store!(schema, columns, input, tx.input.to_vec());
+    store!(schema, columns, gas_limit, tx.gas.as_u32());
+    store!(schema, columns, gas_price, tx.gas_price.map(|gas_price| gas_price.as_u64()));
+    let transaction_type = tx.transaction_type.map(|value| value.as_u32()).unwrap_or(0);
+    store!(schema, columns, transaction_type, Some(transaction_type));
+    store!(schema, columns, max_fee_per_gas, tx.max_fee_per_gas.map(|value| value.as_u64()));
+    store!(
+        schema,
+        columns,
+        max_priority_fee_per_gas,
+        tx.max_priority_fee_per_gas.map(|value| value.as_u64())
+    );
+}
@@ -0,0 +1,140 @@
+use crate::*;
+use ethers::prelude::*;
+use polars::prelude::*;
+use std::collections::HashMap;
+
+/// columns for miner payments, the direct transfers to a block's fee recipient within its block
+///
+/// these coinbase transfers are how searchers pay builders, so they measure MEV payments that
+/// are not captured by priority fees. block rewards are not included
+#[cryo_to_df::to_df(Datatype::MinerPayments)]
+#[derive(Default)]
+pub struct MinerPayments {
+    n_rows: u64,
+    block_number: Vec<u32>,
+    transaction_index: Vec<Option<u32>>,
+    transaction_hash: Vec<Option<Vec<u8>>>,
+    trace_address: Vec<String>,
+    from_address: Vec<Vec<u8>>,
+    value: Vec<U256>,
+    builder: Vec<Vec<u8>>,
+    chain_id: Vec<u64>,
+}
+
+#[async_trait::async_trait]
+impl Dataset for MinerPayments {
+    fn name() -> &'static str {
+        "miner_payments"
+    }
+
+    fn aliases() -> Vec<&'static str> {
+        vec!["coinbase_transfers"]
+    }
+
+    fn default_sort() -> Vec<String> {
+        vec!["block_number".to_string(), "transaction_index".to_string()]
+    }
+
+    fn relative_cost() -> u64 {
+        8
+    }
+}
+
+type Result<T> = ::core::result::Result<T, CollectError>;
+
+#[async_trait::async_trait]
+impl CollectByBlock for MinerPayments {
+    type Response = (H160, Vec<Trace>);
+
+    async fn extract(
+        request: Params,
+        source: Arc<Source>,
+        _schemas: Schemas,
+    ) -> Result<Self::Response> {
+        let block_number = request.block_number()?;
+        let (block, traces) = tokio::try_join!(
+            source.fetcher.get_block(block_number),
+            source.trace_block(block_number),
+        )?;
+        Ok((get_fee_recipient(block)?, traces))
+    }
+
+    fn transform(response: Self::Response, columns: &mut Self, schemas: &Schemas) -> Result<()> {
+        let (fee_recipient, traces) = response;
+        let traces = traces::filter_failed_traces(traces);
+        process_miner_payments(fee_recipient, &traces, columns, schemas)
+    }
+}
+
+#[async_trait::async_trait]
+impl CollectByTransaction for MinerPayments {
+    type Response = (H160, Vec<Trace>);
+
+    async fn extract(
+        request: Params,
+        source: Arc<Source>,
+        _schemas: Schemas,
+    ) -> Result<Self::Response> {
+        let traces = source.fetcher.trace_transaction(request.ethers_transaction_hash()?).await?;
+        let block_number = match traces.first() {
+            Some(trace) => trace.block_number,
+            None => return Err(err("transaction has no traces")),
+        };
+        let block = source.fetcher.get_block(block_number).await?;
+        Ok((get_fee_recipient(block)?, traces))
+    }
+
+    fn transform(response: Self::Response, columns: &mut Self, schemas: &Schemas) -> Result<()> {
+        let (fee_recipient, traces) = response;
+        let traces = traces::filter_failed_traces(traces);
+        process_miner_payments(fee_recipient, &traces, columns, schemas)
+    }
+}
+
+fn get_fee_recipient(block: Option<Block<TxHash>>) -> Result<H160> {
+    block
+        .ok_or(CollectError::CollectError("block not found".to_string()))?
+        .author
+        .ok_or(CollectError::CollectError("block has no fee recipient".to_string()))
+}
+
+/// process traces into rows, one per transfer of value to the fee recipient
+fn process_miner_payments(
+    fee_recipient: H160,
+    traces: &[Trace],
+    columns: &mut MinerPayments,
+    schemas: &Schemas,
+) -> Result<()> {
+    let schema = schemas.get(&Datatype::MinerPayments).ok_or(err("schema not provided"))?;
+    for trace in traces.iter() {
+        let (from_address, value) = match &trace.action {
+            Action::Call(action) if action.to == fee_recipient => (action.from, action.value),
+            Action::Suicide(action) if action.refund_address == fee_recipient => {
+                (action.address, action.balance)
+            }
+            _ => continue,
+        };
+        if value.is_zero() {
+            continue
+        }
+        columns.n_rows += 1;
+        store!(schema, columns, block_number, trace.block_number as u32);
+        store!(schema, columns, transaction_index, trace.transaction_position.map(|x| x as u32));
+        store!(
+            schema,
+            columns,
+            transaction_hash,
+            trace.transaction_hash.map(|x| x.as_bytes().to_vec())
+        );
+        store!(
+            schema,
+            columns,
+            trace_address,
+            trace.trace_address.iter().map(|n| n.to_string()).collect::<Vec<String>>().join("_")
+        );
+        store!(schema, columns, from_address, from_address.as_bytes().to_vec());
+        store!(schema, columns, value, value);
+        store!(schema, columns, builder, fee_recipient.as_bytes().to_vec());
+    }
+    Ok(())
+}
@@ -1,17 +1,39 @@
+/// access lists
+pub mod access_lists;
+/// account proofs
+pub mod account_proofs;
 /// balance diffs
 pub mod balance_diffs;
 /// balances
 pub mod balances;
+/// beacon attestations
+pub mod beacon_attestations;
+/// beacon deposits
+pub mod beacon_deposits;
+/// beacon proposer duties
+pub mod beacon_proposer_duties;
+/// beacon validator balances
+pub mod beacon_validator_balances;
+/// block fees
+pub mod block_fees;
 /// blocks
 pub mod blocks;
+/// chainlink prices
+pub mod chainlink_prices;
 /// code diffs
 pub mod code_diffs;
 /// codes
 pub mod codes;
 /// contracts
 pub mod contracts;
+/// dex swaps
+pub mod dex_swaps;
+/// erc20 allowances
+pub mod erc20_allowances;
 /// erc20 balances
 pub mod erc20_balances;
+/// erc20 holders
+pub mod erc20_holders;
 /// erc20 metadata
 pub mod erc20_metadata;
 /// erc20 supplies
@@ -20,54 +42,107 @@ pub mod erc20_supplies;
 pub mod erc20_transfers;
 /// erc721 metadata
 pub mod erc721_metadata;
+/// erc721 owners
+pub mod erc721_owners;
 /// erc721 transfers
 pub mod erc721_transfers;
 /// eth calls
 pub mod eth_calls;
+/// gas usage
+pub mod gas_usage;
+/// internal eth flows
+pub mod internal_eth_flows;
+/// javascript traces
+pub mod javascript_traces;
 /// logs
 pub mod logs;
+/// mempool
+pub mod mempool;
+/// miner payments
+pub mod miner_payments;
+/// multicall
+pub mod multicall;
 /// native transfers
 pub mod native_transfers;
+/// nft sales
+pub mod nft_sales;
 /// nonce diffs
 pub mod nonce_diffs;
 /// nonces
 pub mod nonces;
+/// pools
+pub mod pools;
+/// proxy implementations
+pub mod proxy_implementations;
+/// receipts
+pub mod receipts;
+/// relay payloads
+pub mod relay_payloads;
 /// storage diffs
 pub mod storage_diffs;
 /// storages
 pub mod storages;
 /// trace calls
 pub mod trace_calls;
+/// trace errors
+pub mod trace_errors;
 /// traces
 pub mod traces;
 /// transaction addresses
 pub mod transaction_addresses;
+/// transaction status
+pub mod transaction_status;
 /// transactions
 pub mod transactions;
 /// vm traces
 pub mod vm_traces;
 
+pub use access_lists::*;
+pub use account_proofs::*;
 pub use balance_diffs::*;
 pub use balances::*;
+pub use beacon_attestations::*;
+pub use beacon_deposits::*;
+pub use beacon_proposer_duties::*;
+pub use beacon_validator_balances::*;
+pub use block_fees::*;
 pub use blocks::*;
+pub use chainlink_prices::*;
 pub use code_diffs::*;
 pub use codes::*;
 pub use contracts::*;
+pub use dex_swaps::*;
+pub use erc20_allowances::*;
 pub use erc20_balances::*;
+pub use erc20_holders::*;
 pub use erc20_metadata::*;
 pub use erc20_supplies::*;
 pub use erc20_transfers::*;
 pub use erc721_metadata::*;
+pub use erc721_owners::*;
 pub use erc721_transfers::*;
 pub use eth_calls::*;
+pub use gas_usage::*;
+pub use internal_eth_flows::*;
+pub use javascript_traces::*;
 pub use logs::*;
+pub use mempool::*;
+pub use miner_payments::*;
+pub use multicall::*;
 pub use native_transfers::*;
+pub use nft_sales::*;
 pub use nonce_diffs::*;
 pub use nonces::*;
+pub use pools::*;
+pub use proxy_implementations::*;
+pub use receipts::*;
+pub use relay_payloads::*;
 pub use storage_diffs::*;
 pub use storages::*;
 pub use trace_calls::*;
+pub use trace_errors::*;
 pub use traces::*;
 pub use transaction_addresses::*;
+pub use transaction_status::*;
 pub use transactions::*;
 pub use vm_traces::*;
@@ -0,0 +1,105 @@
+use crate::*;
+use polars::prelude::*;
+use std::collections::HashMap;
+
+/// columns for multicall
+#[cryo_to_df::to_df(Datatype::Multicall)]
+#[derive(Default)]
+pub struct Multicall {
+    n_rows: u64,
+    block_number: Vec<u32>,
+    contract_address: Vec<Vec<u8>>,
+    call_data: Vec<Vec<u8>>,
+    call_data_hash: Vec<Vec<u8>>,
+    success: Vec<bool>,
+    output_data: Vec<Option<Vec<u8>>>,
+    output_data_hash: Vec<Option<Vec<u8>>>,
+    chain_id: Vec<u64>,
+}
+
+#[async_trait::async_trait]
+impl Dataset for Multicall {
+    fn name() -> &'static str {
+        "multicall"
+    }
+
+    fn default_columns() -> Option<Vec<&'static str>> {
+        Some(vec![
+            "block_number",
+            "contract_address",
+            "call_data",
+            "success",
+            "output_data",
+            "chain_id",
+        ])
+    }
+
+    fn default_sort() -> Vec<String> {
+        vec!["block_number".to_string(), "contract_address".to_string()]
+    }
+
+    fn default_blocks() -> Option<String> {
+        Some("latest".to_string())
+    }
+
+    fn arg_aliases() -> Option<HashMap<String, String>> {
+        let aliases = [("address", "contract"), ("to_address", "contract")]
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        Some(aliases)
+    }
+
+    fn required_parameters() -> Vec<Dim> {
+        vec![Dim::Contract, Dim::CallData]
+    }
+}
+
+type Result<T> = ::core::result::Result<T, CollectError>;
+
+type MulticallResponse = (u32, Vec<u8>, Vec<u8>, Option<Vec<u8>>);
+
+#[async_trait::async_trait]
+impl CollectByBlock for Multicall {
+    type Response = MulticallResponse;
+
+    async fn extract(
+        request: Params,
+        source: Arc<Source>,
+        _schemas: Schemas,
+    ) -> Result<Self::Response> {
+        let number = request.block_number()?;
+        let output = source
+            .call_through_multicall(request.ethers_contract()?, request.call_data()?, number)
+            .await?;
+        Ok((
+            number as u32,
+            request.contract()?,
+            request.call_data()?,
+            output.map(|output| output.to_vec()),
+        ))
+    }
+
+    fn transform(response: Self::Response, columns: &mut Self, schemas: &Schemas) -> Result<()> {
+        let schema = schemas.get(&Datatype::Multicall).ok_or(err("schema not provided"))?;
+        let (block_number, contract_address, call_data, output_data) = response;
+        columns.n_rows += 1;
+        store!(schema, columns, block_number, block_number);
+        store!(schema, columns, contract_address, contract_address);
+        store!(schema, columns, call_data, call_data.clone());
+        store!(schema, columns, call_data_hash, ethers_core::utils::keccak256(call_data).into());
+        store!(schema, columns, success, output_data.is_some());
+        store!(
+            schema,
+            columns,
+            output_data_hash,
+            output_data.as_ref().map(|output| ethers_core::utils::keccak256(output).into())
+        );
+        store!(schema, columns, output_data, output_data);
+        Ok(())
+    }
+}
+
+impl CollectByTransaction for Multicall {
+    type Response = ();
+}
@@ -27,6 +27,14 @@ impl Dataset for NativeTransfers {
     fn default_sort() -> Vec<String> {
         vec!["block_number".to_string(), "transfer_index".to_string()]
     }
+
+    fn dedup_keys() -> Option<Vec<String>> {
+        Some(vec!["block_number".to_string(), "transfer_index".to_string()])
+    }
+
+    fn relative_cost() -> u64 {
+        8
+    }
 }
 
 type Result<T> = ::core::result::Result<T, CollectError>;
@@ -40,7 +48,7 @@ impl CollectByBlock for NativeTransfers {
         source: Arc<Source>,
         _schemas: Schemas,
     ) -> Result<Self::Response> {
-        source.fetcher.trace_block(request.block_number()?.into()).await
+        source.trace_block(request.block_number()?).await
     }
 
     fn transform(response: Self::Response, columns: &mut Self, schemas: &Schemas) -> Result<()> {
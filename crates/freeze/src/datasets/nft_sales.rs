@@ -0,0 +1,442 @@
+use crate::*;
+use ethers::prelude::*;
+use ethers_core::abi::{self, ParamType, Token};
+use polars::prelude::*;
+use std::collections::HashMap;
+
+/// columns for nft sales
+#[cryo_to_df::to_df(Datatype::NftSales)]
+#[derive(Default)]
+pub struct NftSales {
+    n_rows: u64,
+    block_number: Vec<u32>,
+    transaction_index: Vec<u32>,
+    log_index: Vec<u32>,
+    transaction_hash: Vec<Vec<u8>>,
+    marketplace: Vec<String>,
+    collection: Vec<Vec<u8>>,
+    token_id: Vec<U256>,
+    quantity: Vec<U256>,
+    buyer: Vec<Vec<u8>>,
+    seller: Vec<Vec<u8>>,
+    price: Vec<U256>,
+    currency: Vec<Vec<u8>>,
+    chain_id: Vec<u64>,
+}
+
+#[async_trait::async_trait]
+impl Dataset for NftSales {
+    fn name() -> &'static str {
+        "nft_sales"
+    }
+
+    fn default_sort() -> Vec<String> {
+        vec!["block_number".to_string(), "log_index".to_string()]
+    }
+
+    fn optional_parameters() -> Vec<Dim> {
+        vec![Dim::Contract]
+    }
+
+    fn use_block_ranges() -> bool {
+        true
+    }
+}
+
+/// marketplace whose fill events are decoded
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NftMarketplace {
+    /// opensea seaport
+    Seaport,
+    /// blur exchange v1
+    Blur,
+    /// x2y2
+    X2y2,
+}
+
+impl NftMarketplace {
+    /// all supported marketplaces
+    pub fn all() -> Vec<NftMarketplace> {
+        vec![NftMarketplace::Seaport, NftMarketplace::Blur, NftMarketplace::X2y2]
+    }
+
+    /// parse marketplace from name
+    pub fn from_name(name: &str) -> ::core::result::Result<NftMarketplace, ParseError> {
+        match name.to_lowercase().as_str() {
+            "seaport" | "opensea" => Ok(NftMarketplace::Seaport),
+            "blur" => Ok(NftMarketplace::Blur),
+            "x2y2" => Ok(NftMarketplace::X2y2),
+            _ => Err(ParseError::ParseError(format!(
+                "unknown nft marketplace: {}, use seaport, blur, or x2y2",
+                name
+            ))),
+        }
+    }
+
+    /// name of marketplace
+    pub fn name(&self) -> &'static str {
+        match self {
+            NftMarketplace::Seaport => "seaport",
+            NftMarketplace::Blur => "blur",
+            NftMarketplace::X2y2 => "x2y2",
+        }
+    }
+
+    /// event hash of fill event of marketplace
+    pub fn topic(&self) -> H256 {
+        match self {
+            NftMarketplace::Seaport => *EVENT_SEAPORT_ORDER_FULFILLED,
+            NftMarketplace::Blur => *EVENT_BLUR_ORDERS_MATCHED,
+            NftMarketplace::X2y2 => *EVENT_X2Y2_INVENTORY,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+struct Sale {
+    marketplace: NftMarketplace,
+    collection: Vec<u8>,
+    token_id: U256,
+    quantity: U256,
+    buyer: Vec<u8>,
+    seller: Vec<u8>,
+    price: U256,
+    currency: Vec<u8>,
+}
+
+/// decode fill event of one of marketplaces into one sale per nft
+///
+/// the price of orders selling several nfts is split evenly between them
+fn decode_sales(log: &Log, marketplaces: &[NftMarketplace]) -> Vec<Sale> {
+    let marketplace = log
+        .topics
+        .first()
+        .and_then(|topic0| marketplaces.iter().find(|marketplace| marketplace.topic() == *topic0));
+    let sales = match marketplace {
+        Some(NftMarketplace::Seaport) => decode_seaport(log),
+        Some(NftMarketplace::Blur) => decode_blur(log),
+        Some(NftMarketplace::X2y2) => decode_x2y2(log),
+        None => None,
+    };
+    sales.unwrap_or_default()
+}
+
+fn topic_address(log: &Log, i: usize) -> Option<Vec<u8>> {
+    log.topics.get(i).map(|topic| topic.as_bytes()[12..].to_vec())
+}
+
+fn address(token: &Token) -> Option<Vec<u8>> {
+    token.clone().into_address().map(|address| address.as_bytes().to_vec())
+}
+
+fn uint(token: &Token) -> Option<U256> {
+    token.clone().into_uint()
+}
+
+fn tuple(token: &Token) -> Option<Vec<Token>> {
+    token.clone().into_tuple()
+}
+
+/// collection, token id, and quantity of nft
+type Nft = (Vec<u8>, U256, U256);
+
+/// item type, token, identifier, and amount of seaport offer or consideration item
+type SeaportItem = (u64, Vec<u8>, U256, U256);
+
+/// split price of order between its nfts
+fn split_sales(
+    marketplace: NftMarketplace,
+    nfts: Vec<Nft>,
+    buyer: Vec<u8>,
+    seller: Vec<u8>,
+    price: U256,
+    currency: Vec<u8>,
+) -> Vec<Sale> {
+    let n_nfts = U256::from(nfts.len().max(1));
+    nfts.into_iter()
+        .map(|(collection, token_id, quantity)| Sale {
+            marketplace,
+            collection,
+            token_id,
+            quantity,
+            buyer: buyer.clone(),
+            seller: seller.clone(),
+            price: price / n_nfts,
+            currency: currency.clone(),
+        })
+        .collect()
+}
+
+/// decode seaport OrderFulfilled
+///
+/// an order offering nfts is a listing bought by the recipient, and an order offering currency
+/// is a bid accepted by the recipient
+fn decode_seaport(log: &Log) -> Option<Vec<Sale>> {
+    let spent_item = ParamType::Tuple(vec![
+        ParamType::Uint(8),
+        ParamType::Address,
+        ParamType::Uint(256),
+        ParamType::Uint(256),
+    ]);
+    let received_item = ParamType::Tuple(vec![
+        ParamType::Uint(8),
+        ParamType::Address,
+        ParamType::Uint(256),
+        ParamType::Uint(256),
+        ParamType::Address,
+    ]);
+    let params = [
+        ParamType::FixedBytes(32),
+        ParamType::Address,
+        ParamType::Array(Box::new(spent_item)),
+        ParamType::Array(Box::new(received_item)),
+    ];
+    let tokens = abi::decode(&params, &log.data).ok()?;
+    let offerer = topic_address(log, 1)?;
+    let recipient = address(&tokens[1])?;
+    let items = |token: &Token| -> Option<Vec<SeaportItem>> {
+        token
+            .clone()
+            .into_array()?
+            .iter()
+            .map(|item| {
+                let item = tuple(item)?;
+                Some((
+                    uint(&item[0])?.low_u64(),
+                    address(&item[1])?,
+                    uint(&item[2])?,
+                    uint(&item[3])?,
+                ))
+            })
+            .collect()
+    };
+    let (offer, consideration) = (items(&tokens[2])?, items(&tokens[3])?);
+
+    // item types 0 and 1 are native and erc20 currency, while 2 through 5 are nfts
+    let nfts = |items: &[SeaportItem]| -> Vec<Nft> {
+        items
+            .iter()
+            .filter(|item| item.0 >= 2)
+            .map(|(_, token, id, amount)| (token.clone(), *id, *amount))
+            .collect()
+    };
+    let payment = |items: &[SeaportItem]| -> (U256, Vec<u8>) {
+        let payments: Vec<_> = items.iter().filter(|item| item.0 < 2).collect();
+        let price = payments.iter().fold(U256::zero(), |total, item| total.saturating_add(item.3));
+        let currency = payments.first().map(|item| item.1.clone()).unwrap_or(vec![0; 20]);
+        (price, currency)
+    };
+    let (nfts, buyer, seller, (price, currency)) = match nfts(&offer) {
+        nfts if !nfts.is_empty() => (nfts, recipient, offerer, payment(&consideration)),
+        _ => (nfts(&consideration), offerer, recipient, payment(&offer)),
+    };
+    Some(split_sales(NftMarketplace::Seaport, nfts, buyer, seller, price, currency))
+}
+
+/// decode blur OrdersMatched, in which the sell order names the nft and price
+fn decode_blur(log: &Log) -> Option<Vec<Sale>> {
+    let order = ParamType::Tuple(vec![
+        ParamType::Address,
+        ParamType::Uint(8),
+        ParamType::Address,
+        ParamType::Address,
+        ParamType::Uint(256),
+        ParamType::Uint(256),
+        ParamType::Address,
+        ParamType::Uint(256),
+        ParamType::Uint(256),
+        ParamType::Uint(256),
+        ParamType::Array(Box::new(ParamType::Tuple(vec![ParamType::Uint(16), ParamType::Address]))),
+        ParamType::Uint(256),
+        ParamType::Bytes,
+    ]);
+    let params = [order.clone(), ParamType::FixedBytes(32), order, ParamType::FixedBytes(32)];
+    let tokens = abi::decode(&params, &log.data).ok()?;
+    let (sell, buy) = (tuple(&tokens[0])?, tuple(&tokens[2])?);
+    let nft = (address(&sell[3])?, uint(&sell[4])?, uint(&sell[5])?);
+    let (buyer, seller) = (address(&buy[0])?, address(&sell[0])?);
+    let (price, currency) = (uint(&sell[7])?, address(&sell[6])?);
+    Some(split_sales(NftMarketplace::Blur, vec![nft], buyer, seller, price, currency))
+}
+
+/// decode x2y2 EvInventory, whose item data lists the nfts of the order
+fn decode_x2y2(log: &Log) -> Option<Vec<Sale>> {
+    let fee = ParamType::Tuple(vec![ParamType::Uint(256), ParamType::Address]);
+    let detail = ParamType::Tuple(vec![
+        ParamType::Uint(8),
+        ParamType::Uint(256),
+        ParamType::Uint(256),
+        ParamType::Uint(256),
+        ParamType::FixedBytes(32),
+        ParamType::Address,
+        ParamType::Bytes,
+        ParamType::Uint(256),
+        ParamType::Uint(256),
+        ParamType::Uint(256),
+        ParamType::Array(Box::new(fee)),
+    ]);
+    let params = [
+        ParamType::Address,
+        ParamType::Address,
+        ParamType::Uint(256),
+        ParamType::Uint(256),
+        ParamType::Uint(256),
+        ParamType::Uint(256),
+        ParamType::Uint(256),
+        ParamType::Address,
+        ParamType::Bytes,
+        ParamType::Tuple(vec![ParamType::Uint(256), ParamType::Bytes]),
+        detail,
+    ];
+    let tokens = abi::decode(&params, &log.data).ok()?;
+    let (maker, taker) = (address(&tokens[0])?, address(&tokens[1])?);
+    let (intent, delegate_type) = (uint(&tokens[4])?, uint(&tokens[5])?);
+    let currency = address(&tokens[7])?;
+    let item_data = tuple(&tokens[9])?.get(1)?.clone().into_bytes()?;
+    let price = uint(tuple(&tokens[10])?.get(3)?)?;
+
+    // erc721 items are (token, id) pairs and erc1155 items are (token, id, amount) triples
+    let nft = match delegate_type.low_u64() {
+        1 => ParamType::Tuple(vec![ParamType::Address, ParamType::Uint(256)]),
+        2 => ParamType::Tuple(vec![ParamType::Address, ParamType::Uint(256), ParamType::Uint(256)]),
+        _ => return None,
+    };
+    let nfts = abi::decode(&[ParamType::Array(Box::new(nft))], &item_data).ok()?;
+    let nfts = nfts
+        .first()?
+        .clone()
+        .into_array()?
+        .iter()
+        .map(|nft| {
+            let nft = tuple(nft)?;
+            let quantity = nft.get(2).and_then(uint).unwrap_or(U256::one());
+            Some((address(&nft[0])?, uint(&nft[1])?, quantity))
+        })
+        .collect::<Option<Vec<_>>>()?;
+
+    // intent 1 is a listing sold by its maker, intent 3 is a bid bought by its maker
+    let (buyer, seller) = match intent.low_u64() {
+        1 => (taker, maker),
+        3 => (maker, taker),
+        _ => return None,
+    };
+    Some(split_sales(NftMarketplace::X2y2, nfts, buyer, seller, price, currency))
+}
+
+type Result<T> = ::core::result::Result<T, CollectError>;
+
+type LogsAndMarketplaces = (Vec<Log>, Vec<NftMarketplace>);
+
+#[async_trait::async_trait]
+impl CollectByBlock for NftSales {
+    type Response = LogsAndMarketplaces;
+
+    async fn extract(request: Params, source: Arc<Source>, _: Schemas) -> Result<Self::Response> {
+        let topic0s = source.nft_marketplaces.iter().map(|m| Some(m.topic())).collect();
+        let topics = [Some(ValueOrArray::Array(topic0s)), None, None, None];
+        let address = request.contract.as_ref().map(|x| ValueOrArray::Value(H160::from_slice(x)));
+        let filter = Filter { topics, address, ..request.ethers_log_filter()? };
+        let logs = source.get_logs(&filter).await?;
+        Ok((logs, source.nft_marketplaces.clone()))
+    }
+
+    fn transform(response: Self::Response, columns: &mut Self, schemas: &Schemas) -> Result<()> {
+        let schema = schemas.get(&Datatype::NftSales).ok_or(err("schema not provided"))?;
+        process_nft_sales(response, columns, schema)
+    }
+}
+
+#[async_trait::async_trait]
+impl CollectByTransaction for NftSales {
+    type Response = LogsAndMarketplaces;
+
+    async fn extract(request: Params, source: Arc<Source>, _: Schemas) -> Result<Self::Response> {
+        let logs = source.fetcher.get_transaction_logs(request.transaction_hash()?).await?;
+        Ok((logs, source.nft_marketplaces.clone()))
+    }
+
+    fn transform(response: Self::Response, columns: &mut Self, schemas: &Schemas) -> Result<()> {
+        let schema = schemas.get(&Datatype::NftSales).ok_or(err("schema not provided"))?;
+        process_nft_sales(response, columns, schema)
+    }
+}
+
+/// process fill logs into columns
+fn process_nft_sales(
+    response: LogsAndMarketplaces,
+    columns: &mut NftSales,
+    schema: &Table,
+) -> Result<()> {
+    let (logs, marketplaces) = response;
+    for log in logs.iter() {
+        if let (Some(bn), Some(tx), Some(ti), Some(li)) =
+            (log.block_number, log.transaction_hash, log.transaction_index, log.log_index)
+        {
+            for sale in decode_sales(log, &marketplaces) {
+                columns.n_rows += 1;
+                store!(schema, columns, block_number, bn.as_u32());
+                store!(schema, columns, transaction_index, ti.as_u32());
+                store!(schema, columns, log_index, li.as_u32());
+                store!(schema, columns, transaction_hash, tx.as_bytes().to_vec());
+                store!(schema, columns, marketplace, sale.marketplace.name().to_string());
+                store!(schema, columns, collection, sale.collection);
+                store!(schema, columns, token_id, sale.token_id);
+                store!(schema, columns, quantity, sale.quantity);
+                store!(schema, columns, buyer, sale.buyer);
+                store!(schema, columns, seller, sale.seller);
+                store!(schema, columns, price, sale.price);
+                store!(schema, columns, currency, sale.currency);
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_seaport_sales() {
+        let account = |byte: u8| H160::repeat_byte(byte);
+        let item = |item_type: u8, token: H160, id: u64, amount: u64| {
+            vec![
+                Token::Uint(item_type.into()),
+                Token::Address(token),
+                Token::Uint(id.into()),
+                Token::Uint(amount.into()),
+            ]
+        };
+        let received = |item_type: u8, token: H160, id: u64, amount: u64, recipient: H160| {
+            let mut item = item(item_type, token, id, amount);
+            item.push(Token::Address(recipient));
+            Token::Tuple(item)
+        };
+
+        // listing of two erc721s of collection 5 by offerer 1, bought by recipient 2 for 100 wei
+        let data = abi::encode(&[
+            Token::FixedBytes(vec![0; 32]),
+            Token::Address(account(2)),
+            Token::Array(vec![
+                Token::Tuple(item(2, account(5), 7, 1)),
+                Token::Tuple(item(2, account(5), 8, 1)),
+            ]),
+            Token::Array(vec![
+                received(0, H160::zero(), 0, 90, account(1)),
+                received(0, H160::zero(), 0, 10, account(3)),
+            ]),
+        ]);
+        let log = Log {
+            topics: vec![*EVENT_SEAPORT_ORDER_FULFILLED, account(1).into(), H256::zero()],
+            data: data.into(),
+            ..Default::default()
+        };
+        let sales = decode_sales(&log, &NftMarketplace::all());
+        assert_eq!(sales.len(), 2);
+        assert_eq!((sales[0].buyer.clone(), sales[0].seller.clone()), (vec![2; 20], vec![1; 20]));
+        assert_eq!((sales[0].token_id, sales[1].token_id), (7.into(), 8.into()));
+        assert_eq!(sales[0].price, 50.into());
+        assert_eq!(sales[0].currency, vec![0; 20]);
+        assert!(decode_sales(&log, &[NftMarketplace::Blur]).is_empty());
+    }
+}
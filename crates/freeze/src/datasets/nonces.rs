@@ -24,13 +24,17 @@ impl Dataset for Nonces {
         vec!["block_number".to_string(), "address".to_string()]
     }
 
+    fn dedup_keys() -> Option<Vec<String>> {
+        Some(vec!["block_number".to_string(), "address".to_string()])
+    }
+
     fn required_parameters() -> Vec<Dim> {
         vec![Dim::Address]
     }
 }
 
 type Result<T> = ::core::result::Result<T, CollectError>;
-type BlockTxAddressOutput = (u32, Option<Vec<u8>>, Vec<u8>, u64);
+pub(crate) type BlockTxAddressOutput = (u32, Option<Vec<u8>>, Vec<u8>, u64);
 
 #[async_trait::async_trait]
 impl CollectByBlock for Nonces {
@@ -43,10 +47,8 @@ impl CollectByBlock for Nonces {
     ) -> Result<Self::Response> {
         let address = request.address()?;
         let block_number = request.block_number()? as u32;
-        let output = source
-            .fetcher
-            .get_transaction_count(H160::from_slice(&address), block_number.into())
-            .await?;
+        let output =
+            source.get_transaction_count(H160::from_slice(&address), block_number.into()).await?;
         Ok((block_number, None, address, output.as_u64()))
     }
 
@@ -61,7 +63,11 @@ impl CollectByTransaction for Nonces {
     type Response = ();
 }
 
-fn process_nonce(columns: &mut Nonces, data: BlockTxAddressOutput, schema: &Table) -> Result<()> {
+pub(crate) fn process_nonce(
+    columns: &mut Nonces,
+    data: BlockTxAddressOutput,
+    schema: &Table,
+) -> Result<()> {
     let (block, _tx, address, output) = data;
     columns.n_rows += 1;
     store!(schema, columns, block_number, block);
@@ -0,0 +1,262 @@
+use crate::*;
+use ethers::prelude::*;
+use ethers_core::abi::{self, HumanReadableParser, RawLog, Token};
+use polars::prelude::*;
+use std::collections::HashMap;
+
+/// columns for pools
+#[cryo_to_df::to_df(Datatype::Pools)]
+#[derive(Default)]
+pub struct Pools {
+    n_rows: u64,
+    block_number: Vec<u32>,
+    transaction_index: Vec<u32>,
+    log_index: Vec<u32>,
+    transaction_hash: Vec<Vec<u8>>,
+    factory: Vec<Vec<u8>>,
+    protocol: Vec<String>,
+    pool: Vec<Vec<u8>>,
+    token0: Vec<Vec<u8>>,
+    token1: Vec<Vec<u8>>,
+    fee: Vec<Option<u32>>,
+    tick_spacing: Vec<Option<i32>>,
+    chain_id: Vec<u64>,
+}
+
+#[async_trait::async_trait]
+impl Dataset for Pools {
+    fn name() -> &'static str {
+        "pools"
+    }
+
+    fn default_columns() -> Option<Vec<&'static str>> {
+        Some(vec![
+            "block_number",
+            "transaction_hash",
+            "factory",
+            "protocol",
+            "pool",
+            "token0",
+            "token1",
+            "fee",
+            "tick_spacing",
+            "chain_id",
+        ])
+    }
+
+    fn default_sort() -> Vec<String> {
+        vec!["block_number".to_string(), "log_index".to_string()]
+    }
+
+    fn optional_parameters() -> Vec<Dim> {
+        vec![Dim::Contract]
+    }
+
+    fn use_block_ranges() -> bool {
+        true
+    }
+}
+
+/// pool creation events decoded by default, as protocol and event signature
+pub const DEFAULT_POOL_EVENTS: [(&str, &str); 2] = [
+    (
+        "uniswap_v2",
+        "event PairCreated(address indexed token0, address indexed token1, address pair, uint256)",
+    ),
+    (
+        "uniswap_v3",
+        "event PoolCreated(address indexed token0, address indexed token1, uint24 indexed fee, int24 tickSpacing, address pool)",
+    ),
+];
+
+/// factory event that creates a pool
+///
+/// the event must have `token0`, `token1`, and `pool` (or `pair`) address parameters, and may
+/// have `fee` and `tickSpacing` parameters
+#[derive(Clone, Debug)]
+pub struct PoolEvent {
+    /// protocol label of pools created by event
+    pub protocol: String,
+    /// abi of event
+    pub event: abi::Event,
+}
+
+impl PoolEvent {
+    /// parse pool event from event signature, optionally prefixed by a protocol label
+    /// ex: PoolEvent::new("aerodrome=event PoolCreated(address indexed token0, address indexed
+    /// token1, bool indexed stable, address pool, uint256)")
+    pub fn new(raw: &str) -> ::core::result::Result<PoolEvent, ParseError> {
+        let (protocol, signature) = match raw.split_once('=') {
+            Some((protocol, signature)) => (protocol.trim(), signature.trim()),
+            None => ("custom", raw.trim()),
+        };
+        let event = HumanReadableParser::parse_event(signature).map_err(|e| {
+            ParseError::ParseError(format!("could not parse pool event {}: {}", signature, e))
+        })?;
+        let has_param = |names: &[&str]| {
+            event.inputs.iter().any(|input| {
+                names.contains(&input.name.as_str()) && input.kind == abi::ParamType::Address
+            })
+        };
+        if !has_param(&["token0"]) || !has_param(&["token1"]) || !has_param(&["pool", "pair"]) {
+            return Err(ParseError::ParseError(format!(
+                "pool event {} must have token0, token1, and pool address parameters",
+                signature
+            )))
+        }
+        Ok(PoolEvent { protocol: protocol.to_string(), event })
+    }
+
+    /// pool events of uniswap v2 and v3 factories and their forks
+    pub fn defaults() -> Vec<PoolEvent> {
+        DEFAULT_POOL_EVENTS
+            .iter()
+            .filter_map(|(protocol, signature)| {
+                PoolEvent::new(&format!("{}={}", protocol, signature)).ok()
+            })
+            .collect()
+    }
+
+    /// decode log into pool, None if log was not emitted by event
+    fn decode(&self, log: &Log) -> Option<DecodedPool> {
+        let log = self.event.parse_log(RawLog::from(log.clone())).ok()?;
+        let params: HashMap<String, Token> =
+            log.params.into_iter().map(|param| (param.name, param.value)).collect();
+        let address = |name: &str| match params.get(name) {
+            Some(Token::Address(address)) => Some(address.as_bytes().to_vec()),
+            _ => None,
+        };
+        let fee = match params.get("fee") {
+            Some(Token::Uint(fee)) => Some(fee.low_u32()),
+            _ => None,
+        };
+        let tick_spacing = match params.get("tickSpacing").or(params.get("tick_spacing")) {
+            Some(Token::Int(value)) => Some(I256::from_raw(*value).low_i32()),
+            Some(Token::Uint(value)) => Some(value.low_u32() as i32),
+            _ => None,
+        };
+        Some(DecodedPool {
+            pool: address("pool").or(address("pair"))?,
+            token0: address("token0")?,
+            token1: address("token1")?,
+            fee,
+            tick_spacing,
+        })
+    }
+}
+
+struct DecodedPool {
+    pool: Vec<u8>,
+    token0: Vec<u8>,
+    token1: Vec<u8>,
+    fee: Option<u32>,
+    tick_spacing: Option<i32>,
+}
+
+type Result<T> = ::core::result::Result<T, CollectError>;
+
+#[async_trait::async_trait]
+impl CollectByBlock for Pools {
+    type Response = (Vec<Log>, Vec<PoolEvent>);
+
+    async fn extract(request: Params, source: Arc<Source>, _: Schemas) -> Result<Self::Response> {
+        let topic0s = source.pool_events.iter().map(|e| Some(e.event.signature())).collect();
+        let topics = [Some(ValueOrArray::Array(topic0s)), None, None, None];
+        let address = request.contract.as_ref().map(|x| ValueOrArray::Value(H160::from_slice(x)));
+        let filter = Filter { topics, address, ..request.ethers_log_filter()? };
+        let logs = source.get_logs(&filter).await?;
+        Ok((logs, source.pool_events.clone()))
+    }
+
+    fn transform(response: Self::Response, columns: &mut Self, schemas: &Schemas) -> Result<()> {
+        let schema = schemas.get(&Datatype::Pools).ok_or(err("schema not provided"))?;
+        process_pools(response, columns, schema)
+    }
+}
+
+#[async_trait::async_trait]
+impl CollectByTransaction for Pools {
+    type Response = (Vec<Log>, Vec<PoolEvent>);
+
+    async fn extract(request: Params, source: Arc<Source>, _: Schemas) -> Result<Self::Response> {
+        let logs = source.fetcher.get_transaction_logs(request.transaction_hash()?).await?;
+        Ok((logs, source.pool_events.clone()))
+    }
+
+    fn transform(response: Self::Response, columns: &mut Self, schemas: &Schemas) -> Result<()> {
+        let schema = schemas.get(&Datatype::Pools).ok_or(err("schema not provided"))?;
+        process_pools(response, columns, schema)
+    }
+}
+
+/// process pool creation logs into columns
+fn process_pools(
+    response: (Vec<Log>, Vec<PoolEvent>),
+    columns: &mut Pools,
+    schema: &Table,
+) -> Result<()> {
+    let (logs, pool_events) = response;
+    let pool_events: HashMap<H256, &PoolEvent> =
+        pool_events.iter().map(|e| (e.event.signature(), e)).collect();
+    for log in logs.iter() {
+        let pool_event = match log.topics.first().and_then(|topic0| pool_events.get(topic0)) {
+            Some(pool_event) => pool_event,
+            None => continue,
+        };
+        if let (Some(bn), Some(tx), Some(ti), Some(li), Some(pool)) = (
+            log.block_number,
+            log.transaction_hash,
+            log.transaction_index,
+            log.log_index,
+            pool_event.decode(log),
+        ) {
+            columns.n_rows += 1;
+            store!(schema, columns, block_number, bn.as_u32());
+            store!(schema, columns, transaction_index, ti.as_u32());
+            store!(schema, columns, log_index, li.as_u32());
+            store!(schema, columns, transaction_hash, tx.as_bytes().to_vec());
+            store!(schema, columns, factory, log.address.as_bytes().to_vec());
+            store!(schema, columns, protocol, pool_event.protocol.clone());
+            store!(schema, columns, pool, pool.pool);
+            store!(schema, columns, token0, pool.token0);
+            store!(schema, columns, token1, pool.token1);
+            store!(schema, columns, fee, pool.fee);
+            store!(schema, columns, tick_spacing, pool.tick_spacing);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_pool_events() {
+        let token = |byte: u8| H256::from(H160::repeat_byte(byte));
+        let word = |value: u64| H256::from_low_u64_be(value).as_bytes().to_vec();
+        let [v2, v3]: [PoolEvent; 2] = PoolEvent::defaults().try_into().unwrap();
+
+        let log = Log {
+            topics: vec![v2.event.signature(), token(1), token(2)],
+            data: [H256::from(H160::repeat_byte(3)).as_bytes().to_vec(), word(7)].concat().into(),
+            ..Default::default()
+        };
+        let pool = v2.decode(&log).unwrap();
+        assert_eq!(pool.pool, vec![3; 20]);
+        assert_eq!((pool.token0, pool.token1), (vec![1; 20], vec![2; 20]));
+        assert_eq!((pool.fee, pool.tick_spacing), (None, None));
+        assert!(v3.decode(&log).is_none());
+
+        let log = Log {
+            topics: vec![v3.event.signature(), token(1), token(2), H256::from_low_u64_be(500)],
+            data: [word(10), H256::from(H160::repeat_byte(4)).as_bytes().to_vec()].concat().into(),
+            ..Default::default()
+        };
+        let pool = v3.decode(&log).unwrap();
+        assert_eq!(pool.pool, vec![4; 20]);
+        assert_eq!((pool.fee, pool.tick_spacing), (Some(500), Some(10)));
+
+        assert!(PoolEvent::new("event Swap(address indexed sender, uint256 amount)").is_err());
+    }
+}
@@ -0,0 +1,146 @@
+use crate::*;
+use ethers::prelude::*;
+use polars::prelude::*;
+use std::collections::HashMap;
+
+/// columns for proxy implementations
+#[cryo_to_df::to_df(Datatype::ProxyImplementations)]
+#[derive(Default)]
+pub struct ProxyImplementations {
+    n_rows: u64,
+    block_number: Vec<u32>,
+    address: Vec<Vec<u8>>,
+    proxy_type: Vec<Option<String>>,
+    implementation: Vec<Option<Vec<u8>>>,
+    admin: Vec<Option<Vec<u8>>>,
+    beacon: Vec<Option<Vec<u8>>>,
+    chain_id: Vec<u64>,
+}
+
+#[async_trait::async_trait]
+impl Dataset for ProxyImplementations {
+    fn name() -> &'static str {
+        "proxy_implementations"
+    }
+
+    fn aliases() -> Vec<&'static str> {
+        vec!["proxies"]
+    }
+
+    fn default_sort() -> Vec<String> {
+        vec!["block_number".to_string(), "address".to_string()]
+    }
+
+    fn arg_aliases() -> Option<HashMap<String, String>> {
+        let aliases =
+            [("contract", "address")].iter().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+        Some(aliases)
+    }
+
+    fn required_parameters() -> Vec<Dim> {
+        vec![Dim::Address]
+    }
+}
+
+type Result<T> = ::core::result::Result<T, CollectError>;
+
+/// (block_number, address, proxy_type, implementation, admin, beacon)
+type ProxyResponse = (u32, Vec<u8>, Option<String>, Option<H160>, Option<H160>, Option<H160>);
+
+#[async_trait::async_trait]
+impl CollectByBlock for ProxyImplementations {
+    type Response = ProxyResponse;
+
+    async fn extract(
+        request: Params,
+        source: Arc<Source>,
+        _schemas: Schemas,
+    ) -> Result<Self::Response> {
+        let address = request.ethers_address()?;
+        let block_number = request.block_number()?;
+        let ethers_block_number: BlockNumber = block_number.into();
+
+        let (implementation, admin, beacon, code) = tokio::try_join!(
+            source.fetcher.get_storage_at(
+                address,
+                *SLOT_EIP1967_IMPLEMENTATION,
+                ethers_block_number
+            ),
+            source.fetcher.get_storage_at(address, *SLOT_EIP1967_ADMIN, ethers_block_number),
+            source.fetcher.get_storage_at(address, *SLOT_EIP1967_BEACON, ethers_block_number),
+            source.fetcher.get_code(address, ethers_block_number),
+        )?;
+        let (implementation, admin, beacon) =
+            (slot_to_address(implementation), slot_to_address(admin), slot_to_address(beacon));
+
+        let (proxy_type, implementation) = match (implementation, beacon) {
+            (Some(implementation), _) => (Some("eip1967"), Some(implementation)),
+            (None, Some(beacon)) => {
+                let call_data = FUNCTION_BEACON_IMPLEMENTATION.clone();
+                let output = source.call_batched(beacon, call_data, block_number).await.ok();
+                (Some("eip1967_beacon"), output.and_then(|output| bytes_to_address(&output)))
+            }
+            (None, None) => match minimal_proxy_implementation(&code) {
+                Some(implementation) => (Some("eip1167"), Some(implementation)),
+                None => (None, None),
+            },
+        };
+
+        Ok((
+            block_number as u32,
+            request.address()?,
+            proxy_type.map(|x| x.to_string()),
+            implementation,
+            admin,
+            beacon,
+        ))
+    }
+
+    fn transform(response: Self::Response, columns: &mut Self, schemas: &Schemas) -> Result<()> {
+        let schema =
+            schemas.get(&Datatype::ProxyImplementations).ok_or(err("schema not provided"))?;
+        let (block_number, address, proxy_type, implementation, admin, beacon) = response;
+        columns.n_rows += 1;
+        store!(schema, columns, block_number, block_number);
+        store!(schema, columns, address, address);
+        store!(schema, columns, proxy_type, proxy_type);
+        store!(schema, columns, implementation, implementation.map(|x| x.as_bytes().to_vec()));
+        store!(schema, columns, admin, admin.map(|x| x.as_bytes().to_vec()));
+        store!(schema, columns, beacon, beacon.map(|x| x.as_bytes().to_vec()));
+        Ok(())
+    }
+}
+
+impl CollectByTransaction for ProxyImplementations {
+    type Response = ();
+}
+
+/// interpret a storage slot as an address, treating an empty slot as no address
+fn slot_to_address(value: H256) -> Option<H160> {
+    match value.is_zero() {
+        true => None,
+        false => Some(H160::from_slice(&value.as_bytes()[12..])),
+    }
+}
+
+/// interpret abi-encoded call output as an address
+fn bytes_to_address(output: &[u8]) -> Option<H160> {
+    match output.len() {
+        32 => slot_to_address(H256::from_slice(output)),
+        _ => None,
+    }
+}
+
+/// extract implementation address from EIP-1167 minimal proxy bytecode
+fn minimal_proxy_implementation(code: &[u8]) -> Option<H160> {
+    const PREFIX: [u8; 10] = [0x36, 0x3d, 0x3d, 0x37, 0x3d, 0x3d, 0x3d, 0x36, 0x3d, 0x73];
+    const SUFFIX: [u8; 15] =
+        [0x5a, 0xf4, 0x3d, 0x82, 0x80, 0x3e, 0x90, 0x3d, 0x91, 0x60, 0x2b, 0x57, 0xfd, 0x5b, 0xf3];
+    match code.len() == PREFIX.len() + 20 + SUFFIX.len() &&
+        code.starts_with(&PREFIX) &&
+        code.ends_with(&SUFFIX)
+    {
+        true => Some(H160::from_slice(&code[PREFIX.len()..PREFIX.len() + 20])),
+        false => None,
+    }
+}
@@ -0,0 +1,134 @@
+use crate::*;
+use ethers::prelude::*;
+use polars::prelude::*;
+use std::collections::HashMap;
+
+/// columns for receipts
+#[cryo_to_df::to_df(Datatype::Receipts)]
+#[derive(Default)]
+pub struct Receipts {
+    n_rows: u64,
+    block_number: Vec<Option<u32>>,
+    block_hash: Vec<Option<Vec<u8>>>,
+    transaction_index: Vec<u32>,
+    transaction_hash: Vec<Vec<u8>>,
+    from_address: Vec<Vec<u8>>,
+    to_address: Vec<Option<Vec<u8>>>,
+    contract_address: Vec<Option<Vec<u8>>>,
+    success: Vec<Option<bool>>,
+    gas_used: Vec<Option<u64>>,
+    cumulative_gas_used: Vec<u64>,
+    effective_gas_price: Vec<Option<u64>>,
+    transaction_type: Vec<Option<u32>>,
+    n_logs: Vec<u32>,
+    logs_bloom: Vec<Vec<u8>>,
+    state_root: Vec<Option<Vec<u8>>>,
+    chain_id: Vec<u64>,
+}
+
+#[async_trait::async_trait]
+impl Dataset for Receipts {
+    fn name() -> &'static str {
+        "receipts"
+    }
+
+    fn default_columns() -> Option<Vec<&'static str>> {
+        Some(vec![
+            "block_number",
+            "transaction_index",
+            "transaction_hash",
+            "from_address",
+            "to_address",
+            "contract_address",
+            "success",
+            "gas_used",
+            "cumulative_gas_used",
+            "effective_gas_price",
+            "transaction_type",
+            "n_logs",
+            "chain_id",
+        ])
+    }
+
+    fn default_sort() -> Vec<String> {
+        vec!["block_number".to_string(), "transaction_index".to_string()]
+    }
+
+    fn dedup_keys() -> Option<Vec<String>> {
+        Some(vec!["block_number".to_string(), "transaction_index".to_string()])
+    }
+}
+
+type Result<T> = ::core::result::Result<T, CollectError>;
+
+#[async_trait::async_trait]
+impl CollectByBlock for Receipts {
+    type Response = Vec<TransactionReceipt>;
+
+    async fn extract(
+        request: Params,
+        source: Arc<Source>,
+        _schemas: Schemas,
+    ) -> Result<Self::Response> {
+        source.get_block_receipts(request.block_number()?).await
+    }
+
+    fn transform(response: Self::Response, columns: &mut Self, schemas: &Schemas) -> Result<()> {
+        let schema = schemas.get(&Datatype::Receipts).ok_or(err("schema not provided"))?;
+        for receipt in response.iter() {
+            process_receipt(receipt, columns, schema);
+        }
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl CollectByTransaction for Receipts {
+    type Response = TransactionReceipt;
+
+    async fn extract(
+        request: Params,
+        source: Arc<Source>,
+        _schemas: Schemas,
+    ) -> Result<Self::Response> {
+        source
+            .get_transaction_receipt(request.ethers_transaction_hash()?)
+            .await?
+            .ok_or(CollectError::CollectError("receipt not found".to_string()))
+    }
+
+    fn transform(response: Self::Response, columns: &mut Self, schemas: &Schemas) -> Result<()> {
+        let schema = schemas.get(&Datatype::Receipts).ok_or(err("schema not provided"))?;
+        process_receipt(&response, columns, schema);
+        Ok(())
+    }
+}
+
+/// process receipt into columns
+pub(crate) fn process_receipt(
+    receipt: &TransactionReceipt,
+    columns: &mut Receipts,
+    schema: &Table,
+) {
+    columns.n_rows += 1;
+    store!(schema, columns, block_number, receipt.block_number.map(|x| x.as_u32()));
+    store!(schema, columns, block_hash, receipt.block_hash.map(|x| x.as_bytes().to_vec()));
+    store!(schema, columns, transaction_index, receipt.transaction_index.as_u32());
+    store!(schema, columns, transaction_hash, receipt.transaction_hash.as_bytes().to_vec());
+    store!(schema, columns, from_address, receipt.from.as_bytes().to_vec());
+    store!(schema, columns, to_address, receipt.to.map(|x| x.as_bytes().to_vec()));
+    store!(
+        schema,
+        columns,
+        contract_address,
+        receipt.contract_address.map(|x| x.as_bytes().to_vec())
+    );
+    store!(schema, columns, success, receipt.status.map(|x| x.as_u64() == 1));
+    store!(schema, columns, gas_used, receipt.gas_used.map(|x| x.as_u64()));
+    store!(schema, columns, cumulative_gas_used, receipt.cumulative_gas_used.as_u64());
+    store!(schema, columns, effective_gas_price, receipt.effective_gas_price.map(|x| x.as_u64()));
+    store!(schema, columns, transaction_type, receipt.transaction_type.map(|x| x.as_u32()));
+    store!(schema, columns, n_logs, receipt.logs.len() as u32);
+    store!(schema, columns, logs_bloom, receipt.logs_bloom.as_bytes().to_vec());
+    store!(schema, columns, state_root, receipt.root.map(|x| x.as_bytes().to_vec()));
+}
@@ -0,0 +1,143 @@
+use crate::*;
+use ethers::prelude::*;
+use polars::prelude::*;
+use std::collections::HashMap;
+
+/// columns for relay payloads
+#[cryo_to_df::to_df(Datatype::RelayPayloads)]
+#[derive(Default)]
+pub struct RelayPayloads {
+    n_rows: u64,
+    block_number: Vec<u32>,
+    slot: Vec<u64>,
+    relay: Vec<String>,
+    block_hash: Vec<Vec<u8>>,
+    parent_hash: Vec<Vec<u8>>,
+    builder_pubkey: Vec<Vec<u8>>,
+    proposer_pubkey: Vec<Vec<u8>>,
+    proposer_fee_recipient: Vec<Vec<u8>>,
+    gas_limit: Vec<u64>,
+    gas_used: Vec<u64>,
+    value: Vec<U256>,
+    n_transactions: Vec<Option<u64>>,
+    chain_id: Vec<u64>,
+}
+
+#[async_trait::async_trait]
+impl Dataset for RelayPayloads {
+    fn name() -> &'static str {
+        "relay_payloads"
+    }
+
+    fn aliases() -> Vec<&'static str> {
+        vec!["flashbots_bundles", "mev_payloads"]
+    }
+
+    fn default_sort() -> Vec<String> {
+        vec!["block_number".to_string(), "relay".to_string()]
+    }
+}
+
+type Result<T> = ::core::result::Result<T, CollectError>;
+
+/// payload delivered to a proposer, as reported by the relay data api
+#[derive(serde::Deserialize, Debug)]
+pub struct RelayPayloadDelivered {
+    /// slot of payload
+    pub slot: String,
+    /// parent hash of block
+    pub parent_hash: String,
+    /// hash of block
+    pub block_hash: String,
+    /// pubkey of block builder
+    pub builder_pubkey: String,
+    /// pubkey of block proposer
+    pub proposer_pubkey: String,
+    /// fee recipient of block proposer
+    pub proposer_fee_recipient: String,
+    /// gas limit of block
+    pub gas_limit: String,
+    /// gas used by block
+    pub gas_used: String,
+    /// value paid to proposer, in wei
+    pub value: String,
+    /// number of block
+    pub block_number: String,
+    /// number of transactions in block
+    pub num_tx: Option<String>,
+}
+
+#[async_trait::async_trait]
+impl CollectByBlock for RelayPayloads {
+    type Response = Vec<(String, RelayPayloadDelivered)>;
+
+    async fn extract(
+        request: Params,
+        source: Arc<Source>,
+        _schemas: Schemas,
+    ) -> Result<Self::Response> {
+        if source.relay_urls.is_empty() {
+            return Err(err("must specify at least one relay url"))
+        }
+        let block_number = request.block_number()?;
+        let mut payloads = Vec::new();
+        for relay_url in source.relay_urls.iter() {
+            let url = format!(
+                "{}/relay/v1/data/bidtraces/proposer_payload_delivered?block_number={}",
+                relay_url.trim_end_matches('/'),
+                block_number
+            );
+            let response = source
+                .http_client
+                .get(url)
+                .send()
+                .await
+                .and_then(|response| response.error_for_status())
+                .map_err(|e| CollectError::CollectError(format!("relay request failed: {}", e)))?;
+            let delivered: Vec<RelayPayloadDelivered> = response.json().await.map_err(|e| {
+                CollectError::CollectError(format!("could not parse relay response: {}", e))
+            })?;
+            payloads.extend(delivered.into_iter().map(|payload| (relay_url.clone(), payload)));
+        }
+        Ok(payloads)
+    }
+
+    fn transform(response: Self::Response, columns: &mut Self, schemas: &Schemas) -> Result<()> {
+        let schema = schemas.get(&Datatype::RelayPayloads).ok_or(err("schema not provided"))?;
+        for (relay, payload) in response.into_iter() {
+            columns.n_rows += 1;
+            store!(schema, columns, block_number, parse_u64_string(&payload.block_number)? as u32);
+            store!(schema, columns, slot, parse_u64_string(&payload.slot)?);
+            store!(schema, columns, relay, relay);
+            store!(schema, columns, block_hash, parse_hex_string(&payload.block_hash)?);
+            store!(schema, columns, parent_hash, parse_hex_string(&payload.parent_hash)?);
+            store!(schema, columns, builder_pubkey, parse_hex_string(&payload.builder_pubkey)?);
+            store!(schema, columns, proposer_pubkey, parse_hex_string(&payload.proposer_pubkey)?);
+            store!(
+                schema,
+                columns,
+                proposer_fee_recipient,
+                parse_hex_string(&payload.proposer_fee_recipient)?
+            );
+            store!(schema, columns, gas_limit, parse_u64_string(&payload.gas_limit)?);
+            store!(schema, columns, gas_used, parse_u64_string(&payload.gas_used)?);
+            store!(
+                schema,
+                columns,
+                value,
+                U256::from_dec_str(&payload.value).map_err(|_| err("could not parse value"))?
+            );
+            store!(
+                schema,
+                columns,
+                n_transactions,
+                payload.num_tx.as_deref().map(parse_u64_string).transpose()?
+            );
+        }
+        Ok(())
+    }
+}
+
+impl CollectByTransaction for RelayPayloads {
+    type Response = ();
+}
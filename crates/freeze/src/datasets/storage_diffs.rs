@@ -27,6 +27,19 @@ impl Dataset for StorageDiffs {
     fn default_sort() -> Vec<String> {
         vec!["block_number".to_string(), "transaction_index".to_string()]
     }
+
+    fn dedup_keys() -> Option<Vec<String>> {
+        Some(vec![
+            "block_number".to_string(),
+            "transaction_index".to_string(),
+            "address".to_string(),
+            "slot".to_string(),
+        ])
+    }
+
+    fn relative_cost() -> u64 {
+        8
+    }
 }
 
 type BlockTxsTraces = (Option<u32>, Vec<Option<Vec<u8>>>, Vec<ethers::types::BlockTrace>);
@@ -25,6 +25,10 @@ impl Dataset for Storages {
         vec!["block_number".to_string(), "address".to_string(), "slot".to_string()]
     }
 
+    fn dedup_keys() -> Option<Vec<String>> {
+        Some(vec!["block_number".to_string(), "address".to_string(), "slot".to_string()])
+    }
+
     fn required_parameters() -> Vec<Dim> {
         vec![Dim::Address, Dim::Slot]
     }
@@ -46,7 +50,6 @@ impl CollectByBlock for Storages {
         let block_number = request.block_number()? as u32;
         let slot = request.slot()?;
         let output = source
-            .fetcher
             .get_storage_at(
                 H160::from_slice(&address),
                 H256::from_slice(&slot),
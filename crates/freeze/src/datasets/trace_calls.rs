@@ -16,6 +16,9 @@ pub struct TraceCalls {
     action_value: Vec<String>,
     action_gas: Vec<Option<u32>>,
     action_input: Vec<Option<Vec<u8>>>,
+    function_signature: Vec<Option<String>>,
+    function_name: Vec<Option<String>>,
+    function_cols: DecodedCalls,
     action_call_type: Vec<Option<String>>,
     action_init: Vec<Option<Vec<u8>>>,
     action_reward_type: Vec<Option<String>>,
@@ -37,6 +40,32 @@ impl Dataset for TraceCalls {
         "trace_calls"
     }
 
+    fn default_columns() -> Option<Vec<&'static str>> {
+        Some(vec![
+            "block_number",
+            "transaction_position",
+            "action_from",
+            "action_to",
+            "action_value",
+            "action_gas",
+            "action_input",
+            "action_call_type",
+            "action_init",
+            "action_reward_type",
+            "action_type",
+            "result_gas_used",
+            "result_output",
+            "result_code",
+            "result_address",
+            "trace_address",
+            "subtraces",
+            "error",
+            "tx_to_address",
+            "tx_call_data",
+            "chain_id",
+        ])
+    }
+
     fn default_sort() -> Vec<String> {
         vec!["block_number".to_string(), "transaction_position".to_string()]
     }
@@ -44,6 +73,10 @@ impl Dataset for TraceCalls {
     fn required_parameters() -> Vec<Dim> {
         vec![Dim::Contract, Dim::CallData]
     }
+
+    fn relative_cost() -> u64 {
+        8
+    }
 }
 
 type Result<T> = ::core::result::Result<T, CollectError>;
@@ -111,6 +144,21 @@ fn process_transaction_traces(
 }
 
 fn process_action(action: &Action, columns: &mut TraceCalls, schema: &Table) {
+    if schema.has_column("function_signature") | schema.has_column("function_name") {
+        let (signature, name) = match (&schema.function_signatures, action) {
+            (Some(signatures), Action::Call(action)) => signatures.function(&action.input),
+            _ => (None, None),
+        };
+        store!(schema, columns, function_signature, signature);
+        store!(schema, columns, function_name, name);
+    }
+    if let Some(decoder) = &schema.function_decoder {
+        let decoded = match action {
+            Action::Call(action) => decoder.decode(&action.input),
+            _ => None,
+        };
+        columns.function_cols.0.push(decoded);
+    }
     match action {
         Action::Call(action) => {
             store!(schema, columns, action_from, Some(action.from.as_bytes().to_vec()));
@@ -0,0 +1,142 @@
+use crate::*;
+use ethers::prelude::*;
+use polars::prelude::*;
+use std::collections::HashMap;
+
+/// columns for trace errors
+#[cryo_to_df::to_df(Datatype::TraceErrors)]
+#[derive(Default)]
+pub struct TraceErrors {
+    n_rows: u64,
+    block_number: Vec<u32>,
+    transaction_index: Vec<Option<u32>>,
+    transaction_hash: Vec<Option<Vec<u8>>>,
+    trace_address: Vec<String>,
+    from_address: Vec<Option<Vec<u8>>>,
+    to_address: Vec<Option<Vec<u8>>>,
+    error: Vec<String>,
+    error_selector: Vec<Option<Vec<u8>>>,
+    error_signature: Vec<Option<String>>,
+    revert_reason: Vec<Option<String>>,
+    revert_data: Vec<Option<Vec<u8>>>,
+    chain_id: Vec<u64>,
+}
+
+#[async_trait::async_trait]
+impl Dataset for TraceErrors {
+    fn name() -> &'static str {
+        "trace_errors"
+    }
+
+    fn default_sort() -> Vec<String> {
+        vec!["block_number".to_string(), "transaction_index".to_string()]
+    }
+
+    fn relative_cost() -> u64 {
+        8
+    }
+}
+
+type Result<T> = ::core::result::Result<T, CollectError>;
+
+#[async_trait::async_trait]
+impl CollectByBlock for TraceErrors {
+    type Response = Vec<Trace>;
+
+    async fn extract(
+        request: Params,
+        source: Arc<Source>,
+        _schemas: Schemas,
+    ) -> Result<Self::Response> {
+        source.trace_block(request.block_number()?).await
+    }
+
+    fn transform(response: Self::Response, columns: &mut Self, schemas: &Schemas) -> Result<()> {
+        process_trace_errors(&response, columns, schemas)
+    }
+}
+
+#[async_trait::async_trait]
+impl CollectByTransaction for TraceErrors {
+    type Response = Vec<Trace>;
+
+    async fn extract(
+        request: Params,
+        source: Arc<Source>,
+        _schemas: Schemas,
+    ) -> Result<Self::Response> {
+        source.fetcher.trace_transaction(request.ethers_transaction_hash()?).await
+    }
+
+    fn transform(response: Self::Response, columns: &mut Self, schemas: &Schemas) -> Result<()> {
+        process_trace_errors(&response, columns, schemas)
+    }
+}
+
+/// (from, to) of the call, create, or selfdestruct of trace
+fn trace_addresses(trace: &Trace) -> (Option<H160>, Option<H160>) {
+    match &trace.action {
+        Action::Call(action) => (Some(action.from), Some(action.to)),
+        Action::Create(action) => (Some(action.from), None),
+        Action::Suicide(action) => (Some(action.address), Some(action.refund_address)),
+        Action::Reward(action) => (None, Some(action.author)),
+    }
+}
+
+/// process traces into one row per failed call, with the decoded reason of its revert
+///
+/// only calls that failed themselves are included, not the calls below them whose effects were
+/// reverted with them. revert data is taken from the call output when the node returns it
+pub(crate) fn process_trace_errors(
+    traces: &[Trace],
+    columns: &mut TraceErrors,
+    schemas: &Schemas,
+) -> Result<()> {
+    let schema = schemas.get(&Datatype::TraceErrors).ok_or(err("schema not provided"))?;
+    for trace in traces.iter() {
+        let error = match &trace.error {
+            Some(error) => error,
+            None => continue,
+        };
+        let revert_data = match &trace.result {
+            Some(Res::Call(result)) => Some(result.output.to_vec()),
+            Some(Res::Create(result)) => Some(result.code.to_vec()),
+            _ => None,
+        }
+        .filter(|data| !data.is_empty());
+        let (signature, reason) = match &revert_data {
+            Some(data) => decode_revert(data, schema.error_decoder.as_ref()),
+            None => (None, None),
+        };
+        let (from, to) = trace_addresses(trace);
+
+        columns.n_rows += 1;
+        store!(schema, columns, block_number, trace.block_number as u32);
+        store!(schema, columns, transaction_index, trace.transaction_position.map(|x| x as u32));
+        store!(
+            schema,
+            columns,
+            transaction_hash,
+            trace.transaction_hash.map(|x| x.as_bytes().to_vec())
+        );
+        store!(
+            schema,
+            columns,
+            trace_address,
+            trace.trace_address.iter().map(|n| n.to_string()).collect::<Vec<String>>().join("_")
+        );
+        store!(schema, columns, from_address, from.map(|x| x.as_bytes().to_vec()));
+        store!(schema, columns, to_address, to.map(|x| x.as_bytes().to_vec()));
+        store!(schema, columns, error, error.clone());
+        store!(
+            schema,
+            columns,
+            error_selector,
+            revert_data.as_ref().and_then(|data| data.get(..4)).map(|selector| selector.to_vec())
+        );
+        store!(schema, columns, error_signature, signature);
+        store!(schema, columns, revert_reason, reason);
+        store!(schema, columns, revert_data, revert_data);
+    }
+    Ok(())
+}
@@ -13,6 +13,9 @@ pub struct Traces {
     action_value: Vec<String>,
     action_gas: Vec<Option<u32>>,
     action_input: Vec<Option<Vec<u8>>>,
+    function_signature: Vec<Option<String>>,
+    function_name: Vec<Option<String>>,
+    function_cols: DecodedCalls,
     action_call_type: Vec<Option<String>>,
     action_init: Vec<Option<Vec<u8>>>,
     action_reward_type: Vec<Option<String>>,
@@ -37,9 +40,39 @@ impl Dataset for Traces {
         "traces"
     }
 
+    fn default_columns() -> Option<Vec<&'static str>> {
+        Some(vec![
+            "action_from",
+            "action_to",
+            "action_value",
+            "action_gas",
+            "action_input",
+            "action_call_type",
+            "action_init",
+            "action_reward_type",
+            "action_type",
+            "result_gas_used",
+            "result_output",
+            "result_code",
+            "result_address",
+            "trace_address",
+            "subtraces",
+            "transaction_position",
+            "transaction_hash",
+            "block_number",
+            "block_hash",
+            "error",
+            "chain_id",
+        ])
+    }
+
     fn default_sort() -> Vec<String> {
         vec!["block_number".to_string(), "transaction_position".to_string()]
     }
+
+    fn relative_cost() -> u64 {
+        8
+    }
 }
 
 type Result<T> = ::core::result::Result<T, CollectError>;
@@ -53,7 +86,7 @@ impl CollectByBlock for Traces {
         source: Arc<Source>,
         _schemas: Schemas,
     ) -> Result<Self::Response> {
-        source.fetcher.trace_block(request.block_number()?.into()).await
+        source.trace_block(request.block_number()?).await
     }
 
     fn transform(response: Self::Response, columns: &mut Self, schemas: &Schemas) -> Result<()> {
@@ -87,6 +120,11 @@ pub(crate) fn process_traces(
 ) -> Result<()> {
     let schema = schemas.get(&Datatype::Traces).ok_or(err("schema not provided"))?;
     for trace in traces.iter() {
+        if let Some(trace_filter) = &schema.trace_filter {
+            if !trace_filter.matches(trace) {
+                continue
+            }
+        }
         columns.n_rows += 1;
         process_action(&trace.action, columns, schema);
         process_result(&trace.result, columns, schema);
@@ -113,6 +151,21 @@ pub(crate) fn process_traces(
 }
 
 fn process_action(action: &Action, columns: &mut Traces, schema: &Table) {
+    if schema.has_column("function_signature") | schema.has_column("function_name") {
+        let (signature, name) = match (&schema.function_signatures, action) {
+            (Some(signatures), Action::Call(action)) => signatures.function(&action.input),
+            _ => (None, None),
+        };
+        store!(schema, columns, function_signature, signature);
+        store!(schema, columns, function_name, name);
+    }
+    if let Some(decoder) = &schema.function_decoder {
+        let decoded = match action {
+            Action::Call(action) => decoder.decode(&action.input),
+            _ => None,
+        };
+        columns.function_cols.0.push(decoded);
+    }
     match action {
         Action::Call(action) => {
             store!(schema, columns, action_from, Some(action.from.as_bytes().to_vec()));
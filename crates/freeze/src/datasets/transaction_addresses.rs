@@ -3,12 +3,17 @@ use ethers::prelude::*;
 use polars::prelude::*;
 use std::collections::HashMap;
 
-/// columns for transactions
+/// columns for transaction addresses, an index of every appearance of an address in a block
+///
+/// relationships are tx_from, tx_to, miner_fee, call_from, call_to, factory, create, suicide,
+/// suicide_refund, author, log_emitter, erc20_transfer_from, erc20_transfer_to,
+/// erc721_transfer_from, erc721_transfer_to, and state_diff
 #[cryo_to_df::to_df(Datatype::TransactionAddresses)]
 #[derive(Default)]
 pub struct TransactionAddresses {
     n_rows: usize,
     block_number: Vec<u32>,
+    transaction_index: Vec<u32>,
     transaction_hash: Vec<Vec<u8>>,
     address: Vec<Vec<u8>>,
     relationship: Vec<String>,
@@ -29,11 +34,16 @@ impl Dataset for TransactionAddresses {
             "relationship".to_string(),
         ]
     }
+
+    fn relative_cost() -> u64 {
+        8
+    }
 }
 
 type Result<T> = ::core::result::Result<T, CollectError>;
 
-type BlockLogsTraces = (Block<TxHash>, Vec<Log>, Vec<Trace>);
+/// block, logs, traces, and state diffs of a block or a transaction
+type BlockLogsTraces = (Block<TxHash>, Vec<Log>, Vec<Trace>, Vec<BlockTrace>);
 
 #[async_trait::async_trait]
 impl CollectByBlock for TransactionAddresses {
@@ -54,9 +64,13 @@ impl CollectByBlock for TransactionAddresses {
             },
             ..Default::default()
         };
-        let logs = source.fetcher.get_logs(&filter).await?;
-        let traces = source.fetcher.trace_block(request.block_number()?.into()).await?;
-        Ok((block, logs, traces))
+        let logs = source.get_logs(&filter).await?;
+        let traces = source.trace_block(request.block_number()?).await?;
+        let state_diffs = source
+            .fetcher
+            .trace_replay_block_transactions(block_number, vec![TraceType::StateDiff])
+            .await?;
+        Ok((block, logs, traces, state_diffs))
     }
 
     fn transform(response: Self::Response, columns: &mut Self, schemas: &Schemas) -> Result<()> {
@@ -77,7 +91,7 @@ impl CollectByTransaction for TransactionAddresses {
     ) -> Result<Self::Response> {
         let tx_hash = request.ethers_transaction_hash()?;
 
-        let tx_data = source.fetcher.get_transaction(tx_hash).await?.ok_or_else(|| {
+        let tx_data = source.get_transaction(tx_hash).await?.ok_or_else(|| {
             CollectError::CollectError("could not find transaction data".to_string())
         })?;
 
@@ -100,9 +114,14 @@ impl CollectByTransaction for TransactionAddresses {
             .logs;
 
         // traces
-        let traces = source.fetcher.trace_transaction(request.ethers_transaction_hash()?).await?;
+        let traces = source.fetcher.trace_transaction(tx_hash).await?;
 
-        Ok((block, logs, traces))
+        // state diffs
+        let mut state_diff =
+            source.fetcher.trace_replay_transaction(tx_hash, vec![TraceType::StateDiff]).await?;
+        state_diff.transaction_hash = Some(tx_hash);
+
+        Ok((block, logs, traces, vec![state_diff]))
     }
 
     fn transform(response: Self::Response, columns: &mut Self, schemas: &Schemas) -> Result<()> {
@@ -126,7 +145,6 @@ fn name(log: &Log) -> Option<&'static str> {
         None
     }
 }
-
 impl TransactionAddresses {
     fn process_first_transaction(
         &mut self,
@@ -137,7 +155,9 @@ impl TransactionAddresses {
         logs_by_tx: &HashMap<H256, Vec<Log>>,
     ) {
         let block_number = trace.block_number as u32;
-        self.process_address(block_author, "miner_fee", block_number, tx_hash, schema);
+        let tx_index = trace.transaction_position.unwrap_or_default() as u32;
+        let tx = (tx_index, tx_hash);
+        self.process_address(block_author, "miner_fee", block_number, tx, schema);
 
         if let Some(logs) = logs_by_tx.get(&tx_hash) {
             for log in logs.iter() {
@@ -145,77 +165,97 @@ impl TransactionAddresses {
                     if let Some(name) = name(log) {
                         let mut from: [u8; 20] = [0; 20];
                         from.copy_from_slice(&log.topics[1].to_fixed_bytes()[12..32]);
-
-                        let name = &(name.to_string() + "_from");
-                        self.process_address(H160(from), name, block_number, tx_hash, schema);
+                        let from_name = &(name.to_string() + "_from");
+                        self.process_address(H160(from), from_name, block_number, tx, schema);
 
                         let mut to: [u8; 20] = [0; 20];
-                        to.copy_from_slice(&log.topics[1].to_fixed_bytes()[12..32]);
-                        let name = &(name.to_string() + "_to");
-                        self.process_address(H160(to), name, block_number, tx_hash, schema);
+                        to.copy_from_slice(&log.topics[2].to_fixed_bytes()[12..32]);
+                        let to_name = &(name.to_string() + "_to");
+                        self.process_address(H160(to), to_name, block_number, tx, schema);
                     }
                 }
+                self.process_address(log.address, "log_emitter", block_number, tx, schema);
             }
         }
 
         match &trace.action {
             Action::Call(action) => {
-                self.process_address(action.from, "tx_from", block_number, tx_hash, schema);
-                self.process_address(action.to, "tx_to", block_number, tx_hash, schema);
+                self.process_address(action.from, "tx_from", block_number, tx, schema);
+                self.process_address(action.to, "tx_to", block_number, tx, schema);
             }
             Action::Create(action) => {
-                self.process_address(action.from, "tx_from", block_number, tx_hash, schema);
+                self.process_address(action.from, "tx_from", block_number, tx, schema);
             }
             _ => {}
         }
 
         if let Some(Res::Create(result)) = &trace.result {
-            self.process_address(result.address, "tx_to", block_number, tx_hash, schema);
+            self.process_address(result.address, "tx_to", block_number, tx, schema);
         }
     }
 
     fn process_trace(&mut self, trace: &Trace, schema: &Table, tx_hash: H256) {
         let block_number = trace.block_number as u32;
+        let tx = (trace.transaction_position.unwrap_or_default() as u32, tx_hash);
         match &trace.action {
             Action::Call(action) => {
-                self.process_address(action.from, "call_from", block_number, tx_hash, schema);
-                self.process_address(action.to, "call_to", block_number, tx_hash, schema);
+                self.process_address(action.from, "call_from", block_number, tx, schema);
+                self.process_address(action.to, "call_to", block_number, tx, schema);
             }
             Action::Create(action) => {
-                self.process_address(action.from, "factory", block_number, tx_hash, schema);
+                self.process_address(action.from, "factory", block_number, tx, schema);
             }
             Action::Suicide(action) => {
-                self.process_address(action.address, "suicide", block_number, tx_hash, schema);
+                self.process_address(action.address, "suicide", block_number, tx, schema);
                 self.process_address(
                     action.refund_address,
                     "suicide_refund",
                     block_number,
-                    tx_hash,
+                    tx,
                     schema,
                 );
             }
             Action::Reward(action) => {
-                self.process_address(action.author, "author", block_number, tx_hash, schema);
+                self.process_address(action.author, "author", block_number, tx, schema);
             }
         }
 
         if let Some(Res::Create(result)) = &trace.result {
-            self.process_address(result.address, "create", block_number, tx_hash, schema);
+            self.process_address(result.address, "create", block_number, tx, schema);
         };
     }
 
+    fn process_state_diff(
+        &mut self,
+        state_diff: &BlockTrace,
+        block_number: u32,
+        tx: (u32, H256),
+        schema: &Table,
+    ) {
+        if let Some(StateDiff(diffs)) = &state_diff.state_diff {
+            let mut addresses: Vec<&H160> = diffs.keys().collect();
+            addresses.sort();
+            for address in addresses.into_iter() {
+                self.process_address(*address, "state_diff", block_number, tx, schema);
+            }
+        }
+    }
+
+    /// record appearance of address in transaction, given as (index, hash)
     fn process_address(
         &mut self,
         address: H160,
         relationship: &str,
         block_number: u32,
-        transaction_hash: H256,
+        transaction: (u32, H256),
         schema: &Table,
     ) {
+        let (transaction_index, transaction_hash) = transaction;
         self.n_rows += 1;
         store!(schema, self, address, address.as_bytes().to_vec());
         store!(schema, self, relationship, relationship.to_string());
         store!(schema, self, block_number, block_number);
+        store!(schema, self, transaction_index, transaction_index);
         store!(schema, self, transaction_hash, transaction_hash.as_bytes().to_vec());
     }
 }
@@ -225,7 +265,7 @@ fn process_appearances(
     columns: &mut TransactionAddresses,
     schema: &Table,
 ) -> Result<()> {
-    let (block, logs, traces) = traces;
+    let (block, logs, traces, state_diffs) = traces;
     let mut logs_by_tx: HashMap<H256, Vec<Log>> = HashMap::new();
     for log in logs.into_iter() {
         if let Some(tx_hash) = log.transaction_hash {
@@ -233,20 +273,31 @@ fn process_appearances(
         }
     }
 
-    let (_block_number, block_author) = match (block.number, block.author) {
-        (Some(number), Some(author)) => (number.as_u64(), author),
+    let (block_number, block_author) = match (block.number, block.author) {
+        (Some(number), Some(author)) => (number.as_u64() as u32, author),
         _ => return Ok(()),
     };
 
     let mut current_tx_hash = H256([0; 32]);
+    let mut tx_indices: HashMap<H256, u32> = HashMap::new();
     for trace in traces.iter() {
-        if let (Some(tx_hash), Some(_tx_pos)) = (trace.transaction_hash, trace.transaction_position)
+        if let (Some(tx_hash), Some(tx_pos)) = (trace.transaction_hash, trace.transaction_position)
         {
             if tx_hash != current_tx_hash {
                 columns.process_first_transaction(block_author, trace, schema, tx_hash, &logs_by_tx)
             }
             columns.process_trace(trace, schema, tx_hash);
             current_tx_hash = tx_hash;
+            tx_indices.insert(tx_hash, tx_pos as u32);
+        }
+    }
+
+    // state diffs of a block are in transaction order, which gives the index of transactions
+    // without traces
+    for (index, state_diff) in state_diffs.iter().enumerate() {
+        if let Some(tx_hash) = state_diff.transaction_hash {
+            let tx_index = tx_indices.get(&tx_hash).copied().unwrap_or(index as u32);
+            columns.process_state_diff(state_diff, block_number, (tx_index, tx_hash), schema);
         }
     }
 
@@ -0,0 +1,95 @@
+use crate::*;
+use ethers::prelude::*;
+use polars::prelude::*;
+use std::collections::HashMap;
+
+/// columns for the success of transactions, from their receipts
+#[cryo_to_df::to_df(Datatype::TransactionStatus)]
+#[derive(Default)]
+pub struct TransactionStatus {
+    n_rows: u64,
+    block_number: Vec<Option<u32>>,
+    transaction_index: Vec<u32>,
+    transaction_hash: Vec<Vec<u8>>,
+    success: Vec<Option<bool>>,
+    gas_used: Vec<Option<u64>>,
+    chain_id: Vec<u64>,
+}
+
+#[async_trait::async_trait]
+impl Dataset for TransactionStatus {
+    fn name() -> &'static str {
+        "transaction_status"
+    }
+
+    fn aliases() -> Vec<&'static str> {
+        vec!["tx_status"]
+    }
+
+    fn default_sort() -> Vec<String> {
+        vec!["block_number".to_string(), "transaction_index".to_string()]
+    }
+
+    fn dedup_keys() -> Option<Vec<String>> {
+        Some(vec!["block_number".to_string(), "transaction_index".to_string()])
+    }
+}
+
+type Result<T> = ::core::result::Result<T, CollectError>;
+
+#[async_trait::async_trait]
+impl CollectByBlock for TransactionStatus {
+    type Response = Vec<TransactionReceipt>;
+
+    async fn extract(
+        request: Params,
+        source: Arc<Source>,
+        _schemas: Schemas,
+    ) -> Result<Self::Response> {
+        source.get_block_receipts(request.block_number()?).await
+    }
+
+    fn transform(response: Self::Response, columns: &mut Self, schemas: &Schemas) -> Result<()> {
+        let schema = schemas.get(&Datatype::TransactionStatus).ok_or(err("schema not provided"))?;
+        for receipt in response.iter() {
+            process_transaction_status(receipt, columns, schema);
+        }
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl CollectByTransaction for TransactionStatus {
+    type Response = TransactionReceipt;
+
+    async fn extract(
+        request: Params,
+        source: Arc<Source>,
+        _schemas: Schemas,
+    ) -> Result<Self::Response> {
+        source
+            .get_transaction_receipt(request.ethers_transaction_hash()?)
+            .await?
+            .ok_or(CollectError::CollectError("receipt not found".to_string()))
+    }
+
+    fn transform(response: Self::Response, columns: &mut Self, schemas: &Schemas) -> Result<()> {
+        let schema = schemas.get(&Datatype::TransactionStatus).ok_or(err("schema not provided"))?;
+        process_transaction_status(&response, columns, schema);
+        Ok(())
+    }
+}
+
+/// process receipt into columns
+pub(crate) fn process_transaction_status(
+    receipt: &TransactionReceipt,
+    columns: &mut TransactionStatus,
+    schema: &Table,
+) {
+    columns.n_rows += 1;
+    store!(schema, columns, block_number, receipt.block_number.map(|x| x.as_u32()));
+    store!(schema, columns, transaction_index, receipt.transaction_index.as_u32());
+    store!(schema, columns, transaction_hash, receipt.transaction_hash.as_bytes().to_vec());
+    store!(schema, columns, success, receipt.status.map(|x| x.as_u64() == 1));
+    store!(schema, columns, gas_used, receipt.gas_used.map(|x| x.as_u64()));
+}
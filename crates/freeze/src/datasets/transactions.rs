@@ -3,6 +3,10 @@ use ethers::prelude::*;
 use polars::prelude::*;
 use std::collections::HashMap;
 
+use crate::types::gas;
+
+use crate::types::collection::collect_generic::{fetch_partition, join_partition_handles};
+
 /// columns for transactions
 #[cryo_to_df::to_df(Datatype::Transactions)]
 #[derive(Default)]
@@ -16,12 +20,24 @@ pub struct Transactions {
     to_address: Vec<Option<Vec<u8>>>,
     value: Vec<U256>,
     input: Vec<Vec<u8>>,
+    function_signature: Vec<Option<String>>,
+    function_name: Vec<Option<String>>,
+    function_cols: DecodedCalls,
     gas_limit: Vec<u32>,
     gas_used: Vec<Option<u32>>,
     gas_price: Vec<Option<u64>>,
     transaction_type: Vec<Option<u32>>,
     max_priority_fee_per_gas: Vec<Option<u64>>,
     max_fee_per_gas: Vec<Option<u64>>,
+    max_fee_per_blob_gas: Vec<Option<U256>>,
+    access_list: Vec<Option<String>>,
+    blob_versioned_hashes: Vec<Option<String>>,
+    y_parity: Vec<Option<u64>>,
+    intrinsic_gas: Vec<u64>,
+    calldata_gas: Vec<u64>,
+    calldata_floor_gas: Vec<Option<u64>>,
+    execution_gas: Vec<Option<u64>>,
+    gas_refund: Vec<Option<u64>>,
     chain_id: Vec<u64>,
 }
 
@@ -35,58 +51,277 @@ impl Dataset for Transactions {
         vec!["txs"]
     }
 
+    fn default_columns() -> Option<Vec<&'static str>> {
+        Some(vec![
+            "block_number",
+            "transaction_index",
+            "transaction_hash",
+            "nonce",
+            "from_address",
+            "to_address",
+            "value",
+            "input",
+            "gas_limit",
+            "gas_used",
+            "gas_price",
+            "transaction_type",
+            "max_priority_fee_per_gas",
+            "max_fee_per_gas",
+            "chain_id",
+        ])
+    }
+
     fn default_sort() -> Vec<String> {
         vec!["block_number".to_string(), "transaction_index".to_string()]
     }
+
+    fn dedup_keys() -> Option<Vec<String>> {
+        Some(vec!["block_number".to_string(), "transaction_index".to_string()])
+    }
+
+    fn optional_parameters() -> Vec<Dim> {
+        vec![Dim::Address]
+    }
+
+    fn relative_cost() -> u64 {
+        2
+    }
 }
 
+/// number of transactions per page of otterscan address searches
+const OTS_PAGE_SIZE: u64 = 25;
+
 type Result<T> = ::core::result::Result<T, CollectError>;
 
+/// gas used by a transaction from its receipt, and its gas accounting, each if collected
+pub(crate) type TransactionGas = (Option<u32>, Option<GasAccounting>);
+
 #[async_trait::async_trait]
 impl CollectByBlock for Transactions {
-    type Response = (Block<Transaction>, Option<Vec<u32>>);
+    type Response = Vec<(Transaction, TransactionGas)>;
 
     async fn extract(
         request: Params,
         source: Arc<Source>,
         schemas: Schemas,
     ) -> Result<Self::Response> {
-        let block = source
-            .fetcher
-            .get_block_with_txs(request.block_number()?)
-            .await?
-            .ok_or(CollectError::CollectError("block not found".to_string()))?;
         let schema = schemas.get(&Datatype::Transactions).ok_or(err("schema not provided"))?;
-        let gas_used = if schema.has_column("gas_used") {
-            Some(source.get_txs_gas_used(&block).await?)
-        } else {
-            None
-        };
-        Ok((block, gas_used))
+        if let Some(block_range) = request.block_range {
+            let address = request.ethers_address()?;
+            return match source.capabilities.otterscan {
+                true => search_address_transactions(address, block_range, &source, schema).await,
+                false => trace_address_transactions(address, block_range, &source, schema).await,
+            }
+        }
+        let (block, gas) =
+            fetch_block_transactions(request.block_number()?, &source, schema).await?;
+        Ok(block.transactions.into_iter().zip(gas).collect())
     }
 
     fn transform(response: Self::Response, columns: &mut Self, schemas: &Schemas) -> Result<()> {
         let schema = schemas.get(&Datatype::Transactions).ok_or(err("schema not provided"))?;
-        let (block, gas_used) = response;
-        match gas_used {
-            Some(gas_used) => {
-                for (tx, gas_used) in block.transactions.into_iter().zip(gas_used.iter()) {
-                    process_transaction(tx, Some(*gas_used), columns, schema);
-                }
+        for (tx, gas) in response.into_iter() {
+            process_transaction(tx, gas, columns, schema);
+        }
+        Ok(())
+    }
+
+    /// transactions touching addresses are searched across the blocks of each chunk at once
+    /// rather than block by block
+    async fn collect_by_block(
+        partition: Partition,
+        source: Arc<Source>,
+        schemas: &HashMap<Datatype, Table>,
+        inner_request_size: Option<u64>,
+    ) -> Result<HashMap<Datatype, DataFrame>> {
+        let inner_request_size = match (&partition.addresses, &partition.block_numbers) {
+            (Some(_), Some(chunks)) => {
+                Some(chunks.iter().map(|chunk| chunk.size()).sum::<u64>().max(1))
             }
-            None => {
-                for tx in block.transactions.into_iter() {
-                    process_transaction(tx, None, columns, schema);
-                }
+            _ => inner_request_size,
+        };
+        let (sender, receiver) = tokio::sync::mpsc::channel(1);
+        let chain_id = source.chain_id;
+        let handles = fetch_partition(
+            <Self as CollectByBlock>::extract,
+            partition,
+            source,
+            inner_request_size,
+            schemas.clone(),
+            sender,
+        )
+        .await?;
+        let columns = <Self as CollectByBlock>::transform_channel(receiver, schemas).await?;
+        join_partition_handles(handles).await?;
+        columns.create_dfs(schemas, chain_id)
+    }
+}
+
+/// whether the gas used by each transaction is needed by the collected columns
+fn needs_gas_used(schema: &Table) -> bool {
+    schema.has_column("gas_used") || schema.has_column("gas_refund")
+}
+
+fn needs_gas_accounting(schema: &Table) -> bool {
+    GAS_ACCOUNTING_COLUMNS.iter().any(|column| schema.has_column(column))
+}
+
+fn needs_traces(schema: &Table) -> bool {
+    EXECUTION_GAS_COLUMNS.iter().any(|column| schema.has_column(column))
+}
+
+/// gas accounting of transaction under the gas rules of its block
+fn gas_accounting(
+    tx: &Transaction,
+    gas_used: Option<u32>,
+    trace_gas_used: Option<Option<u64>>,
+    chain_id: u64,
+) -> GasAccounting {
+    let rules = GasRules::at_block(chain_id, tx.block_number.map(|x| x.as_u64()));
+    GasAccounting::new(tx, rules).with_execution(gas_used.map(u64::from), trace_gas_used)
+}
+
+/// fetch block with its transactions, and the gas of each as needed by the collected columns
+pub(crate) async fn fetch_block_transactions(
+    block_number: u64,
+    source: &Source,
+    schema: &Table,
+) -> Result<(Block<Transaction>, Vec<TransactionGas>)> {
+    let block = source
+        .fetcher
+        .get_block_with_txs(block_number)
+        .await?
+        .ok_or(CollectError::CollectError("block not found".to_string()))?;
+    let n_transactions = block.transactions.len();
+    let gas_used: Vec<Option<u32>> = if needs_gas_used(schema) {
+        source.get_txs_gas_used(&block).await?.into_iter().map(Some).collect()
+    } else {
+        vec![None; n_transactions]
+    };
+    let accounting = if needs_gas_accounting(schema) {
+        let trace_gas_used = match needs_traces(schema) {
+            true => {
+                gas::top_level_gas_used(&source.trace_block(block_number).await?, n_transactions)
             }
+            false => vec![None; n_transactions],
+        };
+        block
+            .transactions
+            .iter()
+            .zip(gas_used.iter())
+            .zip(trace_gas_used)
+            .map(|((tx, gas_used), trace_gas_used)| {
+                Some(gas_accounting(tx, *gas_used, trace_gas_used, source.chain_id))
+            })
+            .collect()
+    } else {
+        vec![None; n_transactions]
+    };
+    Ok((block, gas_used.into_iter().zip(accounting).collect()))
+}
+
+/// gas accounting of a transaction if it is collected, tracing the transaction if needed
+async fn fetch_gas_accounting(
+    tx: &Transaction,
+    gas_used: Option<u32>,
+    source: &Source,
+    schema: &Table,
+) -> Result<Option<GasAccounting>> {
+    if !needs_gas_accounting(schema) {
+        return Ok(None)
+    }
+    let trace_gas_used = match needs_traces(schema) {
+        true => {
+            gas::transaction_top_level_gas_used(&source.fetcher.trace_transaction(tx.hash).await?)
+        }
+        false => None,
+    };
+    Ok(Some(gas_accounting(tx, gas_used, trace_gas_used, source.chain_id)))
+}
+
+/// find transactions touching address within block range through otterscan's address index
+async fn search_address_transactions(
+    address: H160,
+    block_range: (u64, u64),
+    source: &Source,
+    schema: &Table,
+) -> Result<Vec<(Transaction, TransactionGas)>> {
+    let (start_block, end_block) = block_range;
+    let in_range = |tx: &Transaction| {
+        tx.block_number.map(|n| (start_block..=end_block).contains(&n.as_u64())).unwrap_or(false)
+    };
+    let mut output = Vec::new();
+    let mut cursor = start_block.saturating_sub(1);
+    loop {
+        let page =
+            source.fetcher.ots_search_transactions_after(address, cursor, OTS_PAGE_SIZE).await?;
+        if page.txs.len() != page.receipts.len() {
+            return Err(err("number of receipts does not match number of transactions"))
+        }
+        let last_block = page.txs.iter().filter_map(|tx| tx.block_number).max();
+        let first_page = page.first_page;
+        for (tx, receipt) in page.txs.into_iter().zip(page.receipts) {
+            if in_range(&tx) {
+                let gas_used = receipt.gas_used.map(|x| x.as_u32());
+                let accounting = fetch_gas_accounting(&tx, gas_used, source, schema).await?;
+                output.push((tx, (gas_used, accounting)));
+            }
+        }
+        match last_block {
+            Some(last_block) if !first_page && last_block.as_u64() < end_block => {
+                cursor = last_block.as_u64()
+            }
+            _ => break,
         }
-        Ok(())
     }
+    Ok(output)
+}
+
+/// find transactions touching address within block range through traces sent from or to it
+async fn trace_address_transactions(
+    address: H160,
+    block_range: (u64, u64),
+    source: &Source,
+    schema: &Table,
+) -> Result<Vec<(Transaction, TransactionGas)>> {
+    let (start_block, end_block) = block_range;
+    let filter = ethers::types::TraceFilter::default().from_block(start_block).to_block(end_block);
+    let (from_traces, to_traces) = tokio::try_join!(
+        source.fetcher.trace_filter(filter.clone().from_address(vec![address])),
+        source.fetcher.trace_filter(filter.to_address(vec![address])),
+    )?;
+    let mut tx_hashes: Vec<H256> = from_traces
+        .iter()
+        .chain(to_traces.iter())
+        .filter_map(|trace| trace.transaction_hash)
+        .collect();
+    tx_hashes.sort();
+    tx_hashes.dedup();
+
+    let fetch_gas_used = needs_gas_used(schema);
+    let requests = tx_hashes.into_iter().map(|tx_hash| async move {
+        let tx = source
+            .get_transaction(tx_hash)
+            .await?
+            .ok_or(CollectError::CollectError("transaction not found".to_string()))?;
+        let gas_used = match fetch_gas_used {
+            true => source
+                .get_transaction_receipt(tx_hash)
+                .await?
+                .ok_or(CollectError::CollectError("transaction not found".to_string()))?
+                .gas_used
+                .map(|x| x.as_u32()),
+            false => None,
+        };
+        let accounting = fetch_gas_accounting(&tx, gas_used, source, schema).await?;
+        Ok::<_, CollectError>((tx, (gas_used, accounting)))
+    });
+    futures::future::try_join_all(requests).await
 }
 
 #[async_trait::async_trait]
 impl CollectByTransaction for Transactions {
-    type Response = (Transaction, Option<u32>);
+    type Response = (Transaction, TransactionGas);
 
     async fn extract(
         request: Params,
@@ -96,13 +331,11 @@ impl CollectByTransaction for Transactions {
         let tx_hash = request.ethers_transaction_hash()?;
         let schema = schemas.get(&Datatype::Transactions).ok_or(err("schema not provided"))?;
         let transaction = source
-            .fetcher
             .get_transaction(tx_hash)
             .await?
             .ok_or(CollectError::CollectError("transaction not found".to_string()))?;
-        let gas_used = if schema.has_column("gas_used") {
+        let gas_used = if needs_gas_used(schema) {
             source
-                .fetcher
                 .get_transaction_receipt(tx_hash)
                 .await?
                 .ok_or(CollectError::CollectError("transaction not found".to_string()))?
@@ -111,23 +344,25 @@ impl CollectByTransaction for Transactions {
         } else {
             None
         };
-        Ok((transaction, gas_used))
+        let accounting = fetch_gas_accounting(&transaction, gas_used, &source, schema).await?;
+        Ok((transaction, (gas_used, accounting)))
     }
 
     fn transform(response: Self::Response, columns: &mut Self, schemas: &Schemas) -> Result<()> {
-        let (transaction, gas_used) = response;
+        let (transaction, gas) = response;
         let schema = schemas.get(&Datatype::Transactions).ok_or(err("schema not provided"))?;
-        process_transaction(transaction, gas_used, columns, schema);
+        process_transaction(transaction, gas, columns, schema);
         Ok(())
     }
 }
 
 pub(crate) fn process_transaction(
     tx: Transaction,
-    gas_used: Option<u32>,
+    gas: TransactionGas,
     columns: &mut Transactions,
     schema: &Table,
 ) {
+    let (gas_used, accounting) = gas;
     columns.n_rows += 1;
     store!(schema, columns, block_number, tx.block_number.map(|x| x.as_u64()));
     store!(schema, columns, transaction_index, tx.transaction_index.map(|x| x.as_u64()));
@@ -137,10 +372,26 @@ pub(crate) fn process_transaction(
     store!(schema, columns, nonce, tx.nonce.as_u64());
     store!(schema, columns, value, tx.value);
     store!(schema, columns, input, tx.input.to_vec());
+    if schema.has_column("function_signature") | schema.has_column("function_name") {
+        // contract creations carry init code rather than call data
+        let (signature, name) = match (&schema.function_signatures, tx.to) {
+            (Some(signatures), Some(_)) => signatures.function(&tx.input),
+            _ => (None, None),
+        };
+        store!(schema, columns, function_signature, signature);
+        store!(schema, columns, function_name, name);
+    }
+    if let Some(decoder) = &schema.function_decoder {
+        let decoded = tx.to.and_then(|_| decoder.decode(&tx.input));
+        columns.function_cols.0.push(decoded);
+    }
     store!(schema, columns, gas_limit, tx.gas.as_u32());
     store!(schema, columns, gas_used, gas_used);
     store!(schema, columns, gas_price, tx.gas_price.map(|gas_price| gas_price.as_u64()));
-    store!(schema, columns, transaction_type, tx.transaction_type.map(|value| value.as_u32()));
+
+    // nodes that predate typed transactions omit the type of legacy transactions
+    let transaction_type = tx.transaction_type.map(|value| value.as_u32()).unwrap_or(0);
+    store!(schema, columns, transaction_type, Some(transaction_type));
     store!(schema, columns, max_fee_per_gas, tx.max_fee_per_gas.map(|value| value.as_u64()));
     store!(
         schema,
@@ -148,4 +399,85 @@ pub(crate) fn process_transaction(
         max_priority_fee_per_gas,
         tx.max_priority_fee_per_gas.map(|value| value.as_u64())
     );
+
+    // access lists were introduced by eip-2930 and are carried by every later envelope
+    let access_list = match transaction_type {
+        0 => None,
+        _ => serde_json::to_string(&tx.access_list.clone().unwrap_or_default()).ok(),
+    };
+    store!(schema, columns, access_list, access_list);
+
+    // typed transactions use v as the signature y parity
+    let y_parity = match transaction_type {
+        0 => None,
+        _ => match tx.other.get_deserialized::<U64>("yParity").and_then(|x| x.ok()) {
+            Some(y_parity) => Some(y_parity.as_u64()),
+            None => Some(tx.v.as_u64()),
+        },
+    };
+    store!(schema, columns, y_parity, y_parity);
+
+    // eip-4844 fields are not yet typed fields of ethers transactions
+    let (max_fee_per_blob_gas, blob_versioned_hashes) = match transaction_type {
+        3 => {
+            let max_fee_per_blob_gas =
+                tx.other.get_deserialized::<U256>("maxFeePerBlobGas").and_then(|x| x.ok());
+            let blob_versioned_hashes = tx
+                .other
+                .get_deserialized::<Vec<H256>>("blobVersionedHashes")
+                .and_then(|x| x.ok())
+                .and_then(|hashes| {
+                    let hashes: Vec<String> =
+                        hashes.iter().map(|hash| format!("{:?}", hash)).collect();
+                    serde_json::to_string(&hashes).ok()
+                });
+            (max_fee_per_blob_gas, blob_versioned_hashes)
+        }
+        _ => (None, None),
+    };
+    store!(schema, columns, max_fee_per_blob_gas, max_fee_per_blob_gas);
+    store!(schema, columns, blob_versioned_hashes, blob_versioned_hashes);
+
+    if let Some(accounting) = accounting {
+        store!(schema, columns, intrinsic_gas, accounting.intrinsic_gas);
+        store!(schema, columns, calldata_gas, accounting.calldata_gas);
+        store!(schema, columns, calldata_floor_gas, accounting.calldata_floor_gas);
+        store!(schema, columns, execution_gas, accounting.execution_gas);
+        store!(schema, columns, gas_refund, accounting.gas_refund);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_process_blob_transaction() {
+        let u256_types = [U256Type::Binary].into_iter().collect();
+        let columns = Some(vec![
+            "transaction_type".to_string(),
+            "max_fee_per_blob_gas".to_string(),
+            "blob_versioned_hashes".to_string(),
+            "access_list".to_string(),
+        ]);
+        let schema = Datatype::Transactions
+            .table_schema(&u256_types, &ColumnEncoding::Binary, &None, &None, &columns, None, None)
+            .unwrap();
+
+        // blob fees above u64 are stored as u256 instead of overflowing
+        let mut tx = Transaction { transaction_type: Some(3.into()), ..Default::default() };
+        let max_fee_per_blob_gas = U256::from(u64::MAX) + 1;
+        tx.other.insert("maxFeePerBlobGas".to_string(), serde_json::json!(max_fee_per_blob_gas));
+        tx.other.insert("blobVersionedHashes".to_string(), serde_json::json!([H256::zero()]));
+        let legacy = Transaction { transaction_type: Some(0.into()), ..Default::default() };
+        let mut columns = Transactions::default();
+        process_transaction(tx, (None, None), &mut columns, &schema);
+        process_transaction(legacy, (None, None), &mut columns, &schema);
+
+        assert_eq!(columns.n_rows, 2);
+        assert_eq!(columns.max_fee_per_blob_gas, vec![Some(max_fee_per_blob_gas), None]);
+        assert_eq!(columns.blob_versioned_hashes[0], Some(format!("[\"{:?}\"]", H256::zero())));
+        assert_eq!(columns.blob_versioned_hashes[1], None);
+        assert_eq!(columns.access_list, vec![Some("[]".to_string()), None]);
+    }
 }
@@ -40,6 +40,10 @@ impl Dataset for VmTraces {
     fn default_sort() -> Vec<String> {
         vec!["block_number".to_string(), "transaction_position".to_string(), "used".to_string()]
     }
+
+    fn relative_cost() -> u64 {
+        20
+    }
 }
 
 type Result<T> = ::core::result::Result<T, CollectError>;
@@ -87,7 +91,7 @@ fn process_vm_traces(
     let schema = schemas.get(&Datatype::VmTraces).ok_or(err("schema not provided"))?;
     for (tx_pos, block_trace) in block_traces.into_iter().enumerate() {
         if let Some(vm_trace) = block_trace.vm_trace {
-            add_ops(vm_trace, schema, columns, block_number, tx.clone(), tx_pos);
+            add_ops(vm_trace, schema, columns, block_number, tx.clone(), tx_pos, 0);
         }
     }
     Ok(())
@@ -100,8 +104,20 @@ fn add_ops(
     number: Option<u32>,
     tx_hash: Option<Vec<u8>>,
     tx_pos: usize,
+    depth: u64,
 ) {
+    let depth_matches = match &schema.trace_filter {
+        Some(trace_filter) => trace_filter.depth_matches(depth),
+        None => true,
+    };
     for opcode in vm_trace.ops {
+        if !depth_matches {
+            if let Some(sub) = opcode.sub {
+                add_ops(sub, schema, columns, number, tx_hash.clone(), tx_pos, depth + 1)
+            }
+            continue
+        }
+
         columns.n_rows += 1;
 
         store!(schema, columns, block_number, number);
@@ -143,7 +159,7 @@ fn add_ops(
         };
 
         if let Some(sub) = opcode.sub {
-            add_ops(sub, schema, columns, number, tx_hash.clone(), tx_pos)
+            add_ops(sub, schema, columns, number, tx_hash.clone(), tx_pos, depth + 1)
         }
     }
 }
@@ -1,14 +1,18 @@
 use crate::{
-    collect_partition, dataframes, err, reports, summaries, CollectError, Datatype, ExecutionEnv,
-    FileOutput, FreezeSummary, MetaDatatype, Partition, Query, Source, Table, TimeDimension,
+    collect_partition, continuity, dataframes, dedup, err, interleave_by_cost, manifests,
+    provenance, quality, reports, summaries, ChunkRecord, CollectError, DataQuality, Datatype,
+    ExecutionEnv, ExistingFilePolicy, FileOutput, FreezeSummary, MetaDatatype, Partition, Query,
+    SchemaGuard, Source, Table, TimeDimension,
 };
 use futures::{stream::FuturesUnordered, StreamExt};
+use polars::prelude::DataFrame;
 use std::{
     collections::{HashMap, HashSet},
     path::PathBuf,
     sync::Arc,
 };
-use tokio::sync::Semaphore;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use tracing::Instrument;
 
 type PartitionPayload = (
     TimeDimension,
@@ -20,6 +24,8 @@ type PartitionPayload = (
     HashMap<Datatype, Table>,
     ExecutionEnv,
     Option<std::sync::Arc<Semaphore>>,
+    Option<std::sync::Arc<Semaphore>>,
+    Option<Arc<SchemaGuard>>,
 );
 
 /// collect data and output as files
@@ -32,11 +38,20 @@ pub async fn freeze(
     // check validity of query
     query.is_valid()?;
 
+    // files already uploaded to an object store are skipped, even if they are not staged locally
+    let sink = &match &sink.remote {
+        Some(remote) => {
+            let remote = remote.clone().list_existing().await?;
+            FileOutput { remote: Some(remote), ..sink.clone() }
+        }
+        None => sink.clone(),
+    };
+
     // get partitions
-    let (payloads, skipping) = get_payloads(query, source, sink, env)?;
+    let (mut payloads, existing) = get_payloads(query, source, sink, env)?;
 
-    // print summary
-    if env.verbose {
+    // print summary, unless it is shown by the interactive view
+    if env.verbose && env.tui.is_none() {
         summaries::print_cryo_intro(query, source, sink, env, payloads.len() as u64);
     }
 
@@ -45,9 +60,33 @@ pub async fn freeze(
         return Ok(None)
     };
 
+    // remove partial files of earlier runs that were killed while writing
+    let removed = sink.remove_tmp_files(query)?;
+    if !removed.is_empty() {
+        tracing::info!(n_files = removed.len(), "removed orphaned tmp files");
+    }
+
+    // check chunks against the schemas of existing files that they do not replace
+    let rewritten: HashSet<PathBuf> =
+        payloads.iter().flat_map(|payload| payload.3.values().cloned()).collect();
+    let guard = Arc::new(SchemaGuard::new(query, sink, &rewritten)?);
+    for payload in payloads.iter_mut() {
+        payload.10 = Some(guard.clone());
+    }
+
+    // check that collected and skipped chunks are on the chain that earlier runs were pinned to
+    if sink.pin_block_hash {
+        let n_verified = manifests::verify_pinned_hashes(&query.partitions, source, sink).await?;
+        tracing::info!(n_blocks = n_verified, "verified pinned block hashes");
+    }
+
     // check if empty
     if payloads.is_empty() {
-        let results = FreezeSummary { skipped: skipping, ..Default::default() };
+        let results = FreezeSummary {
+            skipped: existing.skipped,
+            rpc_methods: source.fetcher.rpc_stats.summarize(),
+            ..Default::default()
+        };
         if env.verbose {
             summaries::print_cryo_conclusion(&results, query, env)
         }
@@ -60,7 +99,31 @@ pub async fn freeze(
     };
 
     // perform collection
-    let results = freeze_partitions(env, payloads, skipping).await;
+    tracing::info!(
+        n_chunks = payloads.len(),
+        n_skipped = existing.skipped.len(),
+        n_overwritten = existing.overwritten.len(),
+        n_versioned = existing.versioned.len(),
+        "starting freeze"
+    );
+    let span = tracing::info_span!("freeze", n_chunks = payloads.len());
+    let results = freeze_partitions(env, payloads, existing.skipped).instrument(span).await;
+    tracing::info!(
+        n_completed = results.completed.len(),
+        n_empty = results.empty.len(),
+        n_errored = results.errored.len(),
+        n_skipped = results.skipped.len(),
+        "finished freeze"
+    );
+    let results = FreezeSummary {
+        rpc_methods: source.fetcher.rpc_stats.summarize(),
+        overwritten: existing.overwritten,
+        versioned: existing.versioned,
+        ..results
+    };
+    for anomaly in results.quality.anomalies() {
+        tracing::warn!(anomaly, "data quality");
+    }
 
     // create summary
     if env.verbose {
@@ -76,25 +139,55 @@ pub async fn freeze(
     Ok(Some(results))
 }
 
+/// partitions whose output files already exist, as handled by the existing file policy of sink
+#[derive(Default)]
+struct ExistingFiles {
+    skipped: Vec<Partition>,
+    overwritten: Vec<PathBuf>,
+    versioned: Vec<(PathBuf, PathBuf)>,
+}
+
 fn get_payloads(
     query: &Query,
     source: &Source,
     sink: &FileOutput,
     env: &ExecutionEnv,
-) -> Result<(Vec<PartitionPayload>, Vec<Partition>), CollectError> {
+) -> Result<(Vec<PartitionPayload>, ExistingFiles), CollectError> {
     let semaphore = source
         .max_concurrent_chunks
         .map(|x| std::sync::Arc::new(tokio::sync::Semaphore::new(x as usize)));
+    let write_semaphore =
+        sink.max_concurrent_writes.map(|x| std::sync::Arc::new(Semaphore::new(x as usize)));
     let source = Arc::new(source.clone());
     let mut payloads = Vec::new();
-    let mut skipping = Vec::new();
+    let mut existing = ExistingFiles::default();
     let mut all_paths = HashSet::new();
-    for datatype in query.datatypes.clone().into_iter() {
-        for partition in query.partitions.clone().into_iter() {
-            let paths = sink.get_paths(query, &partition, Some(vec![datatype.clone()]))?;
-            if !sink.overwrite && paths.values().all(|path| path.exists()) {
-                skipping.push(partition);
-                continue
+    // datatypes of the same partition are adjacent, so that they are collected concurrently and
+    // can share cached responses, until payloads are interleaved by cost below
+    for partition in query.partitions.clone().into_iter() {
+        for datatype in query.datatypes.clone().into_iter() {
+            let mut paths = sink.get_paths(query, &partition, Some(vec![datatype.clone()]))?;
+            let collected: Vec<PathBuf> =
+                paths.values().filter(|path| sink.is_collected(path)).cloned().collect();
+            match sink.existing_files {
+                ExistingFilePolicy::Skip if collected.len() == paths.len() => {
+                    existing.skipped.push(partition.clone());
+                    continue
+                }
+                ExistingFilePolicy::Error if !collected.is_empty() => {
+                    return Err(CollectError::CollectError(format!(
+                        "output file already exists: {}",
+                        collected[0].display()
+                    )))
+                }
+                ExistingFilePolicy::Version => {
+                    for path in paths.values_mut().filter(|path| collected.contains(path)) {
+                        let versioned = sink.versioned_path(path);
+                        existing.versioned.push((path.clone(), versioned.clone()));
+                        *path = versioned;
+                    }
+                }
+                _ => existing.overwritten.extend(collected),
             }
 
             // check for path collisions
@@ -107,21 +200,38 @@ fn get_payloads(
                 return Err(err(&message))
             };
 
+            // datatypes collected together use the source of the first with a source of its own
+            let datatype_source = datatype
+                .datatypes()
+                .iter()
+                .find_map(|datatype| source.dataset_sources.get(datatype))
+                .unwrap_or(&source)
+                .clone();
             let payload = (
                 query.time_dimension.clone(),
                 partition.clone(),
                 datatype.clone(),
                 paths,
-                source.clone(),
+                datatype_source,
                 sink.clone(),
                 query.schemas.clone(),
                 env.clone(),
                 semaphore.clone(),
+                write_semaphore.clone(),
+                None,
             );
             payloads.push(payload);
         }
     }
-    Ok((payloads, skipping))
+
+    // order chunks so that datatypes start chunks in inverse proportion to their relative cost,
+    // so that chunks of cheap datatypes are not queued behind chunks of expensive datatypes such
+    // as traces. costs are static estimates of datasets rather than measured request latencies
+    let payloads = interleave_by_cost(payloads, |payload| {
+        let cost = payload.2.datatypes().iter().map(|d| d.relative_cost()).max().unwrap_or(1);
+        (datatype_label(payload), cost)
+    });
+    Ok((payloads, existing))
 }
 
 async fn freeze_partitions(
@@ -134,51 +244,372 @@ async fn freeze_partitions(
         bar.inc(0);
     }
 
+    // start interactive view
+    let tui = match (&env.tui, payloads.first()) {
+        (Some(tui), Some(payload)) => {
+            let chunks: Vec<_> = payloads
+                .iter()
+                .map(|payload| (chunk_label(payload), datatype_label(payload)))
+                .collect();
+            tui.set_chunks(format!("{} chunks", chunks.len()), &chunks);
+            let fetcher = payload.4.fetcher.parent.clone().unwrap_or(payload.4.fetcher.clone());
+            let n_requests = move || fetcher.n_requests.load(std::sync::atomic::Ordering::Relaxed);
+            Some((tui.clone(), tokio::spawn(tui.clone().run(n_requests))))
+        }
+        _ => None,
+    };
+
+    if let Some(shutdown) = &env.shutdown {
+        shutdown.set_collecting(true);
+    }
+
+    // collect partitions, then retry errored partitions with a fresh backoff
+    let (mut summary, mut errored) = freeze_partitions_once(payloads, 0).await;
+    let mut n_retries = 0;
+    for retry in 0..env.chunk_retries {
+        if errored.is_empty() || env.shutdown.as_ref().is_some_and(|s| s.is_requested()) {
+            break
+        }
+        let backoff = env.chunk_retry_backoff.saturating_mul(1 << retry.min(16));
+        tracing::info!(
+            n_chunks = errored.len(),
+            retry = retry + 1,
+            backoff_ms = backoff,
+            "retrying errored chunks"
+        );
+        if let (true, Some(bar)) = (env.verbose, &env.bar) {
+            bar.println(format!(
+                "retrying {} errored chunks in {}ms (retry {} of {})",
+                errored.len(),
+                backoff,
+                retry + 1,
+                env.chunk_retries
+            ));
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(backoff)).await;
+        let payloads = errored.into_iter().map(|(payload, _e, _duration_ms)| payload).collect();
+        n_retries = retry + 1;
+        let (retried, still_errored) = freeze_partitions_once(payloads, n_retries).await;
+        summary.completed.extend(retried.completed);
+        summary.empty.extend(retried.empty);
+        summary.interrupted.extend(retried.interrupted);
+        summary.chunks.extend(retried.chunks);
+        summary.quality.extend(&retried.quality);
+        errored = still_errored;
+    }
+    for (payload, e, duration_ms) in errored.into_iter() {
+        summary.chunks.extend(chunk_records(&payload, Err(&e), duration_ms, n_retries));
+        summary.errored.push((Some(payload.1), e));
+    }
+    if let Some(shutdown) = &env.shutdown {
+        shutdown.set_collecting(false);
+    }
+
+    if let Some(bar) = &env.bar {
+        bar.finish_and_clear();
+    }
+    if let Some((tui, handle)) = tui {
+        tui.finish();
+        if let Ok(Err(e)) = handle.await {
+            eprintln!("{}", e);
+        }
+    }
+
+    FreezeSummary { skipped, ..summary }
+}
+
+/// outcome of a partition that did not error
+#[derive(Debug, PartialEq)]
+enum ChunkOutcome {
+    /// files were written, with the number of rows of each datatype and column statistics
+    Written(HashMap<Datatype, u64>, DataQuality),
+    /// no rows were collected and empty files are skipped, so no files were written
+    Empty,
+    /// left uncollected because of a shutdown request
+    Interrupted,
+}
+
+/// collect each partition once, returning a summary of the partitions that did not error, and
+/// the payloads that errored with the duration of their attempt
+async fn freeze_partitions_once(
+    payloads: Vec<PartitionPayload>,
+    retries: u64,
+) -> (FreezeSummary, Vec<(PartitionPayload, CollectError, u64)>) {
     // spawn task for each partition
     let mut futures = FuturesUnordered::new();
     for payload in payloads.into_iter() {
-        futures.push(tokio::spawn(
-            async move { (payload.1.clone(), freeze_partition(payload).await) },
-        ));
+        let task = tokio::spawn(freeze_partition(payload.clone()).in_current_span());
+        futures.push(async move { (payload, task.await) });
     }
 
     // aggregate results
-    let mut completed = Vec::new();
+    let mut summary = FreezeSummary::default();
     let mut errored = Vec::new();
-    while let Some(result) = futures.next().await {
-        match result {
-            Ok((partition, Ok(()))) => completed.push(partition),
-            Ok((partition, Err(e))) => errored.push((Some(partition), e)),
-            Err(_e) => errored.push((None, err("error joining chunks"))),
+    while let Some((payload, result)) = futures.next().await {
+        let (outcome, duration_ms) = match result {
+            Ok((Ok(outcome), duration_ms)) => (outcome, duration_ms),
+            Ok((Err(e), duration_ms)) => {
+                errored.push((payload, e, duration_ms));
+                continue
+            }
+            Err(_e) => {
+                errored.push((payload, err("error joining chunks"), 0));
+                continue
+            }
+        };
+        summary.chunks.extend(chunk_records(&payload, Ok(&outcome), duration_ms, retries));
+        match outcome {
+            ChunkOutcome::Written(_, quality) => {
+                summary.quality.extend(&quality);
+                summary.completed.push(payload.1)
+            }
+            ChunkOutcome::Empty => summary.empty.push(payload.1),
+            ChunkOutcome::Interrupted => summary.interrupted.push(payload.1),
         }
     }
+    (summary, errored)
+}
 
-    if let Some(bar) = &env.bar {
-        bar.finish_and_clear();
-    }
+/// records of the output files of partition, given the outcome of its last attempt
+fn chunk_records(
+    payload: &PartitionPayload,
+    outcome: Result<&ChunkOutcome, &CollectError>,
+    duration_ms: u64,
+    retries: u64,
+) -> Vec<ChunkRecord> {
+    let partition = &payload.1;
+    let blocks = partition.stats().block_numbers;
+    payload
+        .3
+        .iter()
+        .map(|(datatype, path)| {
+            let (status, n_rows, error) = match outcome {
+                Ok(ChunkOutcome::Written(n_rows, _)) => match path.exists() {
+                    true => ("written", n_rows.get(datatype).copied(), None),
+                    false => ("empty", Some(0), None),
+                },
+                Ok(ChunkOutcome::Empty) => ("empty", Some(0), None),
+                Ok(ChunkOutcome::Interrupted) => ("interrupted", None, None),
+                Err(e) => ("errored", None, Some(e.to_string())),
+            };
+            let n_bytes = match status {
+                "written" => std::fs::metadata(path).ok().map(|metadata| metadata.len()),
+                _ => None,
+            };
+            ChunkRecord {
+                datatype: datatype.name(),
+                partition: partition.label(&partition.dims()).unwrap_or_default(),
+                start_block: blocks.as_ref().and_then(|blocks| blocks.min_value),
+                end_block: blocks.as_ref().and_then(|blocks| blocks.max_value),
+                path: path.clone(),
+                status: status.to_string(),
+                n_rows,
+                n_bytes,
+                duration_ms,
+                retries,
+                error,
+            }
+        })
+        .collect()
+}
 
-    FreezeSummary { completed, errored, skipped }
+/// label of partition shown in interactive view, the name of its first output file
+fn chunk_label(payload: &PartitionPayload) -> String {
+    payload
+        .3
+        .values()
+        .next()
+        .and_then(|path| path.file_name())
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default()
 }
 
-async fn freeze_partition(payload: PartitionPayload) -> Result<(), CollectError> {
-    let (time_dim, partition, datatype, paths, source, sink, schemas, env, semaphore) = payload;
+fn datatype_label(payload: &PartitionPayload) -> String {
+    payload.2.datatypes().iter().map(|datatype| datatype.name()).collect::<Vec<_>>().join(", ")
+}
 
-    // acquire chunk semaphore
-    let _permit = match &semaphore {
-        Some(semaphore) => Some(semaphore.acquire().await),
+/// collect and write partition, returning its outcome and the duration of the attempt
+async fn freeze_partition(payload: PartitionPayload) -> (Result<ChunkOutcome, CollectError>, u64) {
+    // acquire chunk semaphore, which is released once the chunk is collected
+    let permit = match payload.8.clone() {
+        Some(semaphore) => semaphore.acquire_owned().await.ok(),
         None => None,
     };
+    if payload.7.shutdown.as_ref().is_some_and(|shutdown| shutdown.is_requested()) {
+        return (Ok(ChunkOutcome::Interrupted), 0)
+    }
+    if let Some(guard) = &payload.10 {
+        for datatype in payload.2.datatypes().iter() {
+            if let Err(e) = guard.check_allowed(datatype) {
+                return (Err(e), 0)
+            }
+        }
+    }
+
+    let tui = payload.7.tui.clone();
+    let (label, datatype) = (chunk_label(&payload), datatype_label(&payload));
+    tracing::info!(chunk = label, datatype, "chunk started");
+    if let Some(tui) = &tui {
+        tui.start_chunk(&label);
+    }
+    let t_start = std::time::Instant::now();
+    let span = tracing::info_span!(
+        "chunk",
+        otel.name = %format!("chunk {}", datatype),
+        chunk = label,
+        datatype
+    );
+    let result = collect_and_write_partition(payload, permit).instrument(span).await;
+    let duration_ms = t_start.elapsed().as_millis() as u64;
+    match &result {
+        Ok(ChunkOutcome::Written(..)) => {
+            tracing::info!(chunk = label, datatype, duration_ms, "chunk finished")
+        }
+        Ok(ChunkOutcome::Empty) => {
+            tracing::info!(chunk = label, datatype, duration_ms, "chunk empty, not written")
+        }
+        Ok(ChunkOutcome::Interrupted) => {
+            tracing::info!(chunk = label, datatype, duration_ms, "chunk interrupted")
+        }
+        Err(e) => {
+            tracing::warn!(chunk = label, datatype, duration_ms, error = %e, "chunk errored")
+        }
+    }
+    if let Some(tui) = &tui {
+        match &result {
+            Ok(ChunkOutcome::Written(..) | ChunkOutcome::Empty) => {
+                tui.finish_chunk(&label, &datatype, None)
+            }
+            Ok(ChunkOutcome::Interrupted) => tui.interrupt_chunk(&label),
+            Err(e) => tui.finish_chunk(&label, &datatype, Some(e)),
+        }
+    }
+    (result, duration_ms)
+}
+
+async fn collect_and_write_partition(
+    payload: PartitionPayload,
+    chunk_permit: Option<OwnedSemaphorePermit>,
+) -> Result<ChunkOutcome, CollectError> {
+    let (
+        time_dim,
+        partition,
+        datatype,
+        paths,
+        source,
+        sink,
+        schemas,
+        env,
+        _,
+        write_semaphore,
+        guard,
+    ) = payload;
+
+    // record block hash before collecting, so that a reorg during collection is caught by recheck
+    let manifest = match sink.manifest {
+        true => manifests::get_chunk_manifest(&partition, &source).await?,
+        false => None,
+    };
 
-    // collect data
-    let dfs = collect_partition(time_dim, datatype, partition, source, schemas).await?;
+    // record provenance of each output file
+    let metadata: HashMap<Datatype, Vec<(String, String)>> = paths
+        .keys()
+        .map(|datatype| {
+            let table = schemas.get(datatype);
+            (*datatype, provenance::chunk_metadata(datatype, &partition, &source, table))
+        })
+        .collect();
 
-    // write dataframes to disk
-    for (datatype, mut df) in dfs {
-        let path = paths.get(&datatype).ok_or_else(|| {
-            CollectError::CollectError("could not get path for datatype".to_string())
-        })?;
-        let result = dataframes::df_to_file(&mut df, path, &sink);
-        result.map_err(|_| CollectError::CollectError("error writing file".to_string()))?
+    // collect data, then hand the collection slot to the next chunk once a write slot is free,
+    // so that writes overlap with collection while the number of collected chunks held in memory
+    // stays bounded
+    let collect = async {
+        let validated_partition = source.validate_continuity.then(|| partition.clone());
+        let dfs = collect_partition(time_dim, datatype, partition, source.clone(), schemas.clone())
+            .instrument(tracing::info_span!("collect"))
+            .await?;
+        let dfs = match validated_partition {
+            Some(partition) => {
+                continuity::validate_partition(&partition, &source, dfs, &schemas).await?
+            }
+            None => dfs,
+        };
+        let dfs = match sink.global_sort {
+            true => sort_globally(dfs, &schemas)?,
+            false => dfs,
+        };
+        let write_permit = match write_semaphore {
+            Some(semaphore) => semaphore.acquire_owned().await.ok(),
+            None => None,
+        };
+        Ok::<_, CollectError>((dfs, write_permit))
+    };
+
+    // a repeated shutdown request drops the chunk before anything of it is written, whereas a
+    // chunk that has started writing is always finished
+    let (dfs, _write_permit) = match &env.shutdown {
+        Some(shutdown) => tokio::select! {
+            collected = collect => collected?,
+            _ = shutdown.aborted() => return Ok(ChunkOutcome::Interrupted),
+        },
+        None => collect.await?,
+    };
+    drop(chunk_permit);
+
+    // write dataframes to disk on blocking threads, since serialization and compression are cpu
+    // bound and would otherwise stall the rpc requests of other chunks
+    let span = tracing::Span::current();
+    let write_sink = sink.clone();
+    let upload_paths: Vec<PathBuf> = match sink.remote {
+        Some(_) => paths.values().cloned().collect(),
+        None => vec![],
+    };
+    let prepare_span = span.clone();
+    let prepare_sink = sink.clone();
+    let prepare_paths = paths.clone();
+    let dfs = tokio::task::spawn_blocking(move || {
+        let _entered = prepare_span.enter();
+        prepare_dataframes(dfs, &prepare_paths, &prepare_sink, guard.as_deref())
+    })
+    .await
+    .map_err(|_| err("error joining write task"))??;
+
+    // rows are inserted before files are written, so that a chunk whose insert fails has no file
+    // and is collected again by the next run instead of being skipped as already collected. rows
+    // of the chunk that were already inserted are replaced through the dedup keys of its table
+    if let Some(clickhouse) = &sink.clickhouse {
+        for (datatype, df) in dfs.iter() {
+            clickhouse
+                .insert(datatype, df, schemas.get(datatype))
+                .instrument(tracing::info_span!("clickhouse_insert"))
+                .await?
+        }
+    }
+
+    let (dfs, n_written, chunk_quality) = tokio::task::spawn_blocking(move || {
+        let _entered = span.enter();
+        let (dfs, n_written) =
+            write_dataframes(dfs, &paths, &metadata, &write_sink, manifest.as_ref())?;
+        let mut chunk_quality = DataQuality::default();
+        for (datatype, df) in dfs.iter() {
+            chunk_quality.add(&datatype.name(), &quality::column_stats(df));
+        }
+        Ok::<_, CollectError>((dfs, n_written, chunk_quality))
+    })
+    .await
+    .map_err(|_| err("error joining write task"))??;
+    let n_rows: HashMap<Datatype, u64> =
+        dfs.iter().map(|(datatype, df)| (*datatype, df.height() as u64)).collect();
+
+    // files of chunks without rows are not written when empty files are skipped. uploaded files
+    // are removed from the staging dir along with their manifests, since later runs find them
+    // in the listed files of the object store
+    if let Some(remote) = &sink.remote {
+        for path in upload_paths.iter().filter(|path| path.exists()) {
+            remote.upload(path, &sink.output_dir).instrument(tracing::info_span!("upload")).await?;
+            std::fs::remove_file(path).map_err(|_| err("could not remove staged file"))?;
+            manifests::remove_manifest(&sink, path)?;
+        }
     }
 
     // update progress bar
@@ -186,5 +617,102 @@ async fn freeze_partition(payload: PartitionPayload) -> Result<(), CollectError>
         bar.inc(1);
     }
 
-    Ok(())
+    match n_written {
+        0 => Ok(ChunkOutcome::Empty),
+        _ => Ok(ChunkOutcome::Written(n_rows, chunk_quality)),
+    }
+}
+
+/// sort dataframes by the sort columns of their schemas again, since row filters, transforms,
+/// and sql queries run after the sort of collection and may reorder rows
+fn sort_globally(
+    dfs: HashMap<Datatype, DataFrame>,
+    schemas: &HashMap<Datatype, Table>,
+) -> Result<HashMap<Datatype, DataFrame>, CollectError> {
+    dfs.into_iter()
+        .map(|(datatype, df)| {
+            let sort_columns =
+                schemas.get(&datatype).and_then(|schema| schema.sort_columns.clone());
+            match sort_columns {
+                Some(sort_columns) => {
+                    Ok((datatype, dataframes::sort_by_columns(df, &sort_columns)?))
+                }
+                None => Ok((datatype, df)),
+            }
+        })
+        .collect()
+}
+
+/// check dataframes of chunk against the schemas of existing files, and drop rows already
+/// collected by files of overlapping blocks
+fn prepare_dataframes(
+    dfs: HashMap<Datatype, DataFrame>,
+    paths: &HashMap<Datatype, PathBuf>,
+    sink: &FileOutput,
+    guard: Option<&SchemaGuard>,
+) -> Result<Vec<(Datatype, DataFrame)>, CollectError> {
+    if let Some(guard) = guard {
+        for (datatype, df) in dfs.iter() {
+            guard.check(datatype, df)?;
+        }
+    }
+    dfs.into_iter()
+        .map(|(datatype, df)| {
+            let path = paths.get(&datatype).ok_or_else(|| {
+                CollectError::CollectError("could not get path for datatype".to_string())
+            })?;
+            let df = match sink.dedup_keys.get(&datatype) {
+                Some(keys) => dedup::drop_collected_rows(df, &datatype, path, keys)?,
+                None => df,
+            };
+            Ok((datatype, df))
+        })
+        .collect()
+}
+
+/// write dataframes of chunk to their paths, returning the dataframes and the number of files
+/// written
+///
+/// if empty files are skipped, dataframes without rows are recorded by an empty marker instead
+/// of a file, which also replaces any file of an earlier collection of the chunk
+fn write_dataframes(
+    dfs: Vec<(Datatype, DataFrame)>,
+    paths: &HashMap<Datatype, PathBuf>,
+    metadata: &HashMap<Datatype, Vec<(String, String)>>,
+    sink: &FileOutput,
+    manifest: Option<&manifests::ChunkManifest>,
+) -> Result<(Vec<(Datatype, DataFrame)>, usize), CollectError> {
+    let mut written = Vec::new();
+    let mut n_written = 0;
+    for (datatype, mut df) in dfs {
+        let path = paths.get(&datatype).ok_or_else(|| {
+            CollectError::CollectError("could not get path for datatype".to_string())
+        })?;
+        if sink.skip_empty_files && df.height() == 0 {
+            if path.exists() {
+                std::fs::remove_file(path).map_err(|_| err("could not remove empty chunk file"))?;
+            }
+            manifests::write_empty_marker(sink, path)?;
+            if let Some(manifest) = manifest {
+                manifests::write_manifest(sink, path, manifest)?;
+            }
+            written.push((datatype, df));
+            continue
+        }
+        let write_span =
+            tracing::info_span!("write", datatype = datatype.name(), n_rows = df.height());
+        let metadata = metadata.get(&datatype).map(|metadata| metadata.as_slice()).unwrap_or(&[]);
+        let result = write_span
+            .in_scope(|| dataframes::df_to_file_with_metadata(&mut df, path, sink, metadata));
+        result.map_err(|_| CollectError::CollectError("error writing file".to_string()))?;
+        if sink.skip_empty_files {
+            manifests::remove_empty_marker(sink, path)?;
+        }
+        if let Some(manifest) = manifest {
+            manifests::write_manifest(sink, path, manifest)?;
+        }
+        n_written += 1;
+        written.push((datatype, df));
+    }
+    Ok((written, n_written))
 }
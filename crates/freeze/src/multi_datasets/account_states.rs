@@ -0,0 +1,105 @@
+use crate::*;
+use ethers::prelude::*;
+use futures::Future;
+use polars::prelude::*;
+use std::collections::HashMap;
+
+/// AccountStates
+#[derive(Default)]
+pub struct AccountStates(balances::Balances, codes::Codes, nonces::Nonces);
+
+/// code hash of accounts without code
+const EMPTY_CODE_HASH: [u8; 32] = [
+    0xc5, 0xd2, 0x46, 0x01, 0x86, 0xf7, 0x23, 0x3c, 0x92, 0x7e, 0x7d, 0xb2, 0xdc, 0xc7, 0x03, 0xc0,
+    0xe5, 0x00, 0xb6, 0x53, 0xca, 0x82, 0x27, 0x3b, 0x7b, 0xfa, 0xd8, 0x04, 0x5d, 0x85, 0xa4, 0x70,
+];
+
+type Result<T> = ::core::result::Result<T, CollectError>;
+type AccountState = (u32, Vec<u8>, Option<U256>, Option<Vec<u8>>, Option<u64>);
+
+impl ToDataFrames for AccountStates {
+    fn create_dfs(
+        self,
+        schemas: &HashMap<Datatype, Table>,
+        chain_id: u64,
+    ) -> Result<HashMap<Datatype, DataFrame>> {
+        let AccountStates(balances, codes, nonces) = self;
+        let mut output = HashMap::new();
+        output.extend(balances.create_dfs(schemas, chain_id)?);
+        output.extend(codes.create_dfs(schemas, chain_id)?);
+        output.extend(nonces.create_dfs(schemas, chain_id)?);
+        Ok(output)
+    }
+}
+
+#[async_trait::async_trait]
+impl CollectByBlock for AccountStates {
+    type Response = AccountState;
+
+    async fn extract(
+        request: Params,
+        source: Arc<Source>,
+        schemas: Schemas,
+    ) -> Result<Self::Response> {
+        let address = request.address()?;
+        let block_number = request.block_number()? as u32;
+        let account = H160::from_slice(&address);
+        let block: BlockNumber = block_number.into();
+
+        if source.use_get_proof {
+            // balance, nonce, and code hash are all included in the account proof
+            let proof = source.fetcher.get_proof(account, Vec::new(), block).await?;
+            let code = match schemas.contains_key(&Datatype::Codes) {
+                false => None,
+                true if has_no_code(proof.code_hash) => Some(Vec::new()),
+                true => Some(source.get_code(account, block).await?.to_vec()),
+            };
+            let nonce = Some(proof.nonce.as_u64());
+            return Ok((block_number, address, Some(proof.balance), code, nonce))
+        }
+
+        let (balance, code, nonce) = futures::try_join!(
+            optional(schemas.contains_key(&Datatype::Balances), source.get_balance(account, block)),
+            optional(schemas.contains_key(&Datatype::Codes), source.get_code(account, block)),
+            optional(
+                schemas.contains_key(&Datatype::Nonces),
+                source.get_transaction_count(account, block)
+            ),
+        )?;
+        let code = code.map(|code| code.to_vec());
+        Ok((block_number, address, balance, code, nonce.map(|nonce| nonce.as_u64())))
+    }
+
+    fn transform(response: Self::Response, columns: &mut Self, schemas: &Schemas) -> Result<()> {
+        let AccountStates(balances, codes, nonces) = columns;
+        let (block_number, address, balance, code, nonce) = response;
+        if let (Some(schema), Some(balance)) = (schemas.get(&Datatype::Balances), balance) {
+            let output = (block_number, None, address.clone(), balance);
+            balances::process_balance(balances, output, schema)?;
+        }
+        if let (Some(schema), Some(code)) = (schemas.get(&Datatype::Codes), code) {
+            codes::process_code(codes, (block_number, None, address.clone(), code), schema)?;
+        }
+        if let (Some(schema), Some(nonce)) = (schemas.get(&Datatype::Nonces), nonce) {
+            nonces::process_nonce(nonces, (block_number, None, address, nonce), schema)?;
+        }
+        Ok(())
+    }
+}
+
+impl CollectByTransaction for AccountStates {
+    type Response = ();
+}
+
+/// only send request if its datatype is collected
+async fn optional<T>(enabled: bool, request: impl Future<Output = Result<T>>) -> Result<Option<T>> {
+    match enabled {
+        true => request.await.map(Some),
+        false => Ok(None),
+    }
+}
+
+/// nodes report either the hash of empty code or zero for accounts without code
+fn has_no_code(code_hash: H256) -> bool {
+    code_hash.0 == EMPTY_CODE_HASH || code_hash.is_zero()
+}
@@ -1,4 +1,5 @@
 use crate::{datasets::transactions, types::collection::*, Datatype, *};
+use ethers::types::{Block, Transaction};
 use polars::prelude::*;
 use std::collections::HashMap;
 
@@ -24,22 +25,26 @@ impl ToDataFrames for BlocksAndTransactions {
 
 #[async_trait::async_trait]
 impl CollectByBlock for BlocksAndTransactions {
-    type Response = <Transactions as CollectByBlock>::Response;
+    type Response = (Block<Transaction>, Vec<transactions::TransactionGas>);
 
     async fn extract(
         request: Params,
         source: Arc<Source>,
         schemas: Schemas,
     ) -> Result<Self::Response> {
-        <Transactions as CollectByBlock>::extract(request, source, schemas).await
+        let schema = schemas.get(&Datatype::Transactions).ok_or(err("schema not provided"))?;
+        transactions::fetch_block_transactions(request.block_number()?, &source, schema).await
     }
 
     fn transform(response: Self::Response, columns: &mut Self, schemas: &Schemas) -> Result<()> {
         let BlocksAndTransactions(blocks, transactions) = columns;
-        let (block, _) = response.clone();
+        let (block, gas) = response;
         let schema = schemas.get(&Datatype::Blocks).ok_or(err("schema not provided"))?;
-        blocks::process_block(block, blocks, schema)?;
-        <Transactions as CollectByBlock>::transform(response, transactions, schemas)?;
+        blocks::process_block(block.clone(), blocks, schema)?;
+        let schema = schemas.get(&Datatype::Transactions).ok_or(err("schema not provided"))?;
+        for (tx, gas) in block.transactions.into_iter().zip(gas) {
+            transactions::process_transaction(tx, gas, transactions, schema);
+        }
         Ok(())
     }
 }
@@ -56,7 +61,7 @@ impl CollectByTransaction for BlocksAndTransactions {
         source: Arc<Source>,
         schemas: Schemas,
     ) -> Result<Self::Response> {
-        let (tx, gas_used) =
+        let (tx, gas) =
             <Transactions as CollectByTransaction>::extract(request, source.clone(), schemas)
                 .await?;
         let block_number = tx.block_number.ok_or(err("no block number for tx"))?.as_u64();
@@ -65,16 +70,16 @@ impl CollectByTransaction for BlocksAndTransactions {
             .get_block(block_number)
             .await?
             .ok_or(CollectError::CollectError("block not found".to_string()))?;
-        Ok((block, (tx, gas_used)))
+        Ok((block, (tx, gas)))
     }
 
     fn transform(response: Self::Response, columns: &mut Self, schemas: &Schemas) -> Result<()> {
         let BlocksAndTransactions(blocks, transactions) = columns;
-        let (block, (tx, gas_used)) = response;
+        let (block, (tx, gas)) = response;
         let schema = schemas.get(&Datatype::Blocks).ok_or(err("schema not provided"))?;
         blocks::process_block(block, blocks, schema)?;
         let schema = schemas.get(&Datatype::Transactions).ok_or(err("schema not provided"))?;
-        transactions::process_transaction(tx, gas_used, transactions, schema);
+        transactions::process_transaction(tx, gas, transactions, schema);
         Ok(())
     }
 }
@@ -43,7 +43,7 @@ impl CollectByBlock for CallTraceDerivatives {
         source: Arc<Source>,
         _schemas: Schemas,
     ) -> Result<Self::Response> {
-        source.fetcher.trace_block(request.block_number()?.into()).await
+        source.trace_block(request.block_number()?).await
     }
 
     fn transform(response: Self::Response, columns: &mut Self, schemas: &Schemas) -> Result<()> {
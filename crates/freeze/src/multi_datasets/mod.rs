@@ -1,7 +1,11 @@
+mod account_states;
 mod blocks_and_transactions;
 mod call_trace_derivatives;
+mod receipts_and_logs;
 mod state_diffs;
 
+pub use account_states::*;
 pub use blocks_and_transactions::*;
 pub use call_trace_derivatives::*;
+pub use receipts_and_logs::*;
 pub use state_diffs::*;
@@ -0,0 +1,106 @@
+use crate::{types::collection::*, Datatype, *};
+use ethers::prelude::*;
+use polars::prelude::*;
+use std::collections::HashMap;
+
+type Result<T> = ::core::result::Result<T, CollectError>;
+
+/// ReceiptsAndLogs, collected from one request of the receipts of each block
+#[derive(Default)]
+pub struct ReceiptsAndLogs(Receipts, Logs, GasUsage, TransactionStatus);
+
+impl ToDataFrames for ReceiptsAndLogs {
+    fn create_dfs(
+        self,
+        schemas: &HashMap<Datatype, Table>,
+        chain_id: u64,
+    ) -> Result<HashMap<Datatype, DataFrame>> {
+        let ReceiptsAndLogs(receipts, logs, gas_usage, transaction_status) = self;
+        let mut output = HashMap::new();
+        if schemas.contains_key(&Datatype::Receipts) {
+            output.extend(receipts.create_dfs(schemas, chain_id)?);
+        }
+        if schemas.contains_key(&Datatype::Logs) {
+            output.extend(logs.create_dfs(schemas, chain_id)?);
+        }
+        if schemas.contains_key(&Datatype::GasUsage) {
+            output.extend(gas_usage.create_dfs(schemas, chain_id)?);
+        }
+        if schemas.contains_key(&Datatype::TransactionStatus) {
+            output.extend(transaction_status.create_dfs(schemas, chain_id)?);
+        }
+        Ok(output)
+    }
+}
+
+#[async_trait::async_trait]
+impl CollectByBlock for ReceiptsAndLogs {
+    type Response = Vec<TransactionReceipt>;
+
+    async fn extract(
+        request: Params,
+        source: Arc<Source>,
+        _schemas: Schemas,
+    ) -> Result<Self::Response> {
+        source.get_block_receipts(request.block_number()?).await
+    }
+
+    fn transform(response: Self::Response, columns: &mut Self, schemas: &Schemas) -> Result<()> {
+        process_receipts_and_logs(response, true, columns, schemas)
+    }
+}
+
+#[async_trait::async_trait]
+impl CollectByTransaction for ReceiptsAndLogs {
+    type Response = TransactionReceipt;
+
+    async fn extract(
+        request: Params,
+        source: Arc<Source>,
+        _schemas: Schemas,
+    ) -> Result<Self::Response> {
+        source
+            .get_transaction_receipt(request.ethers_transaction_hash()?)
+            .await?
+            .ok_or(CollectError::CollectError("receipt not found".to_string()))
+    }
+
+    fn transform(response: Self::Response, columns: &mut Self, schemas: &Schemas) -> Result<()> {
+        process_receipts_and_logs(vec![response], false, columns, schemas)
+    }
+}
+
+/// process receipts into the columns of each dataset with a schema, which are all of the receipts
+/// of a block if whole_block
+fn process_receipts_and_logs(
+    receipts: Vec<TransactionReceipt>,
+    whole_block: bool,
+    columns: &mut ReceiptsAndLogs,
+    schemas: &Schemas,
+) -> Result<()> {
+    let ReceiptsAndLogs(receipt_columns, logs, gas_usage, transaction_status) = columns;
+    if let Some(schema) = schemas.get(&Datatype::Receipts) {
+        for receipt in receipts.iter() {
+            receipts::process_receipt(receipt, receipt_columns, schema);
+        }
+    }
+    match schemas.get(&Datatype::GasUsage) {
+        Some(schema) if whole_block => gas_usage::process_gas_usage(&receipts, gas_usage, schema),
+        Some(schema) => {
+            for receipt in receipts.iter() {
+                gas_usage::process_receipt_gas(receipt, None, gas_usage, schema);
+            }
+        }
+        None => {}
+    }
+    if let Some(schema) = schemas.get(&Datatype::TransactionStatus) {
+        for receipt in receipts.iter() {
+            transaction_status::process_transaction_status(receipt, transaction_status, schema);
+        }
+    }
+    if let Some(schema) = schemas.get(&Datatype::Logs) {
+        let block_logs = receipts.into_iter().flat_map(|receipt| receipt.logs).collect();
+        logs::process_logs(block_logs, logs, schema)?;
+    }
+    Ok(())
+}
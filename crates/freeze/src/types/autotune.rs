@@ -0,0 +1,228 @@
+/// tuning of request concurrency from the feedback of providers
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+use crate::CollectError;
+
+/// concurrency at which tuning starts when nothing was learned about a provider
+pub const AUTOTUNE_INITIAL_CONCURRENCY: u64 = 4;
+
+/// minimum time between decreases, so that a burst of throttled requests sent at the old
+/// concurrency halves it only once
+const DECREASE_COOLDOWN: Duration = Duration::from_secs(2);
+
+/// latency relative to the lowest latency seen for a method above which requests of the method
+/// are considered queued by the provider
+const LATENCY_FACTOR: f64 = 4.0;
+
+/// number of requests of a method observed before their latency is used as a signal
+const MIN_LATENCY_SAMPLES: u64 = 20;
+
+/// adjusts the number of concurrent requests additively up and multiplicatively down (AIMD)
+///
+/// concurrency increases by one after as many successful requests as the current concurrency,
+/// and halves when a request is throttled, times out, or takes much longer than the fastest
+/// requests of its method seen so far
+#[derive(Debug)]
+pub struct AutoTuner {
+    min: u64,
+    max: u64,
+    /// number of rate limited responses, recorded by the retry policy of the provider
+    throttled: AtomicU64,
+    state: Mutex<TunerState>,
+}
+
+#[derive(Debug)]
+struct TunerState {
+    limit: u64,
+    /// permits to remove from the semaphore as they are acquired, after a decrease
+    excess: u64,
+    n_successes: u64,
+    seen_throttled: u64,
+    last_decrease: Option<Instant>,
+    latencies: BTreeMap<&'static str, MethodLatency>,
+}
+
+#[derive(Debug, Default)]
+struct MethodLatency {
+    n_samples: u64,
+    min_ms: f64,
+    mean_ms: f64,
+}
+
+impl AutoTuner {
+    /// create tuner starting at initial concurrency, kept within min and max
+    pub fn new(initial: u64, min: u64, max: u64) -> AutoTuner {
+        let min = min.max(1);
+        let max = max.max(min);
+        let state = TunerState {
+            limit: initial.clamp(min, max),
+            excess: 0,
+            n_successes: 0,
+            seen_throttled: 0,
+            last_decrease: None,
+            latencies: BTreeMap::new(),
+        };
+        AutoTuner { min, max, throttled: AtomicU64::new(0), state: Mutex::new(state) }
+    }
+
+    /// current concurrency
+    pub fn limit(&self) -> u64 {
+        self.state().limit
+    }
+
+    /// record a rate limited response, e.g. http 429
+    pub fn record_throttled(&self) {
+        self.throttled.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// observe the outcome of a request, returning whether a permit should be added to the
+    /// semaphore of the fetcher
+    pub(crate) fn observe(&self, method: &'static str, latency: Duration, timed_out: bool) -> bool {
+        let throttled = self.throttled.load(Ordering::Relaxed);
+        let latency_ms = latency.as_secs_f64() * 1000.0;
+        let mut state = self.state();
+        let method_latency = state.latencies.entry(method).or_default();
+        method_latency.n_samples += 1;
+        match method_latency.n_samples {
+            1 => (method_latency.min_ms, method_latency.mean_ms) = (latency_ms, latency_ms),
+            _ => {
+                method_latency.min_ms = method_latency.min_ms.min(latency_ms);
+                method_latency.mean_ms = 0.9 * method_latency.mean_ms + 0.1 * latency_ms;
+            }
+        }
+        let slow = method_latency.n_samples >= MIN_LATENCY_SAMPLES &&
+            method_latency.mean_ms > LATENCY_FACTOR * method_latency.min_ms.max(1.0);
+
+        if timed_out || slow || throttled > state.seen_throttled {
+            state.seen_throttled = throttled;
+            state.n_successes = 0;
+            if state.last_decrease.is_some_and(|t| t.elapsed() < DECREASE_COOLDOWN) {
+                return false
+            }
+            let limit = (state.limit / 2).max(self.min);
+            if limit < state.limit {
+                tracing::info!(
+                    from = state.limit,
+                    to = limit,
+                    timed_out,
+                    slow,
+                    "autotune decreased concurrency"
+                );
+                state.excess += state.limit - limit;
+                state.limit = limit;
+                // latency is compared against requests sent at the new concurrency
+                for method_latency in state.latencies.values_mut() {
+                    method_latency.mean_ms = method_latency.min_ms;
+                }
+            }
+            state.last_decrease = Some(Instant::now());
+            return false
+        }
+
+        state.n_successes += 1;
+        if state.n_successes < state.limit || state.limit >= self.max {
+            return false
+        }
+        state.n_successes = 0;
+        state.limit += 1;
+        tracing::debug!(to = state.limit, "autotune increased concurrency");
+        match state.excess {
+            0 => true,
+            _ => {
+                state.excess -= 1;
+                false
+            }
+        }
+    }
+
+    /// whether an acquired permit should be forgotten to lower the concurrency of the semaphore
+    pub(crate) fn take_excess(&self) -> bool {
+        let mut state = self.state();
+        match state.excess {
+            0 => false,
+            _ => {
+                state.excess -= 1;
+                true
+            }
+        }
+    }
+
+    fn state(&self) -> std::sync::MutexGuard<'_, TunerState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// default path of the concurrency learned for each provider host, `~/.cryo/autotune.json`
+pub fn default_autotune_path() -> Option<PathBuf> {
+    std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".cryo/autotune.json"))
+}
+
+/// host of rpc url under which its learned concurrency is recorded, including any port
+pub fn autotune_host(rpc_url: &str) -> Option<String> {
+    let url: reqwest::Url = rpc_url.parse().ok()?;
+    let host = url.host_str()?;
+    match url.port() {
+        Some(port) => Some(format!("{}:{}", host, port)),
+        None => Some(host.to_string()),
+    }
+}
+
+/// concurrency learned for provider host by earlier runs
+pub fn read_learned_concurrency(path: &Path, host: &str) -> Option<u64> {
+    read_learned(path).get(host).copied()
+}
+
+/// record concurrency learned for provider host, keeping the hosts of other providers
+pub fn write_learned_concurrency(path: &Path, host: &str, limit: u64) -> Result<(), CollectError> {
+    let mut learned = read_learned(path);
+    learned.insert(host.to_string(), limit);
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| {
+            CollectError::CollectError(format!("could not create {}: {}", parent.display(), e))
+        })?;
+    }
+    let serialized = serde_json::to_string_pretty(&learned)
+        .map_err(|_| CollectError::CollectError("could not serialize autotune file".to_string()))?;
+    std::fs::write(path, serialized).map_err(|e| {
+        CollectError::CollectError(format!("could not write {}: {}", path.display(), e))
+    })
+}
+
+fn read_learned(path: &Path) -> BTreeMap<String, u64> {
+    std::fs::read_to_string(path)
+        .ok()
+        .and_then(|contents| serde_json::from_str(&contents).ok())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_autotuner_aimd() {
+        let tuner = AutoTuner::new(4, 1, 6);
+        let fast = Duration::from_millis(10);
+        let added: Vec<bool> = (0..4).map(|_| tuner.observe("eth_getLogs", fast, false)).collect();
+        assert_eq!(added, vec![false, false, false, true]);
+        assert_eq!(tuner.limit(), 5);
+
+        tuner.record_throttled();
+        assert!(!tuner.observe("eth_getLogs", fast, false));
+        assert_eq!(tuner.limit(), 2);
+        let forgotten: Vec<bool> = (0..4).map(|_| tuner.take_excess()).collect();
+        assert_eq!(forgotten, vec![true, true, true, false]);
+
+        // a timeout within the cooldown of the last decrease does not decrease again
+        assert!(!tuner.observe("eth_getLogs", fast, true));
+        assert_eq!(tuner.limit(), 2);
+    }
+}
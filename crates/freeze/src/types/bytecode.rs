@@ -0,0 +1,230 @@
+/// analysis of deployed evm bytecode
+use std::collections::HashSet;
+
+/// selectors of functions that erc20 tokens implement
+const ERC20_SELECTORS: [[u8; 4]; 6] = [
+    [0x18, 0x16, 0x0d, 0xdd], // totalSupply()
+    [0x70, 0xa0, 0x82, 0x31], // balanceOf(address)
+    [0xa9, 0x05, 0x9c, 0xbb], // transfer(address,uint256)
+    [0x23, 0xb8, 0x72, 0xdd], // transferFrom(address,address,uint256)
+    [0x09, 0x5e, 0xa7, 0xb3], // approve(address,uint256)
+    [0xdd, 0x62, 0xed, 0x3e], // allowance(address,address)
+];
+
+/// selectors of functions that erc721 tokens implement
+const ERC721_SELECTORS: [[u8; 4]; 7] = [
+    [0x70, 0xa0, 0x82, 0x31], // balanceOf(address)
+    [0x63, 0x52, 0x21, 0x1e], // ownerOf(uint256)
+    [0x42, 0x84, 0x2e, 0x0e], // safeTransferFrom(address,address,uint256)
+    [0x23, 0xb8, 0x72, 0xdd], // transferFrom(address,address,uint256)
+    [0x09, 0x5e, 0xa7, 0xb3], // approve(address,uint256)
+    [0xa2, 0x2c, 0xb4, 0x65], // setApprovalForAll(address,bool)
+    [0x08, 0x18, 0x12, 0xfc], // getApproved(uint256)
+];
+
+/// columns of codes and contracts computed by analyzing code
+pub const BYTECODE_ANALYSIS_COLUMNS: [&str; 4] =
+    ["is_erc20", "is_erc721", "metadata_hash", "compiler"];
+
+/// functions and compiler metadata found in bytecode
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct BytecodeAnalysis {
+    /// 4 byte values pushed by PUSH4, which include the selectors of the function dispatcher
+    pub selectors: HashSet<[u8; 4]>,
+    /// hash of the source metadata appended by the compiler, as an ipfs multihash or swarm hash
+    pub metadata_hash: Option<Vec<u8>>,
+    /// compiler and version appended by the compiler, e.g. `solc 0.8.19`
+    pub compiler: Option<String>,
+}
+
+impl BytecodeAnalysis {
+    /// analyze bytecode
+    ///
+    /// the cbor encoded metadata that solc and vyper append to bytecode is decoded and excluded
+    /// from the scan for selectors
+    pub fn new(code: &[u8]) -> BytecodeAnalysis {
+        let (metadata, code) = match split_metadata(code) {
+            Some((code, metadata)) => (parse_metadata(metadata), code),
+            None => (None, code),
+        };
+        let (metadata_hash, compiler) = metadata.unwrap_or_default();
+        BytecodeAnalysis { selectors: scan_push4(code), metadata_hash, compiler }
+    }
+
+    /// whether code has dispatcher entries of every erc20 function
+    pub fn is_erc20(&self) -> bool {
+        ERC20_SELECTORS.iter().all(|selector| self.selectors.contains(selector))
+    }
+
+    /// whether code has dispatcher entries of every erc721 function
+    pub fn is_erc721(&self) -> bool {
+        ERC721_SELECTORS.iter().all(|selector| self.selectors.contains(selector))
+    }
+}
+
+/// values pushed by PUSH4 instructions, skipping the immediate data of other pushes
+fn scan_push4(code: &[u8]) -> HashSet<[u8; 4]> {
+    let mut selectors = HashSet::new();
+    let mut i = 0;
+    while i < code.len() {
+        let opcode = code[i];
+        if (0x60..=0x7f).contains(&opcode) {
+            let n_bytes = (opcode - 0x5f) as usize;
+            if opcode == 0x63 {
+                if let Some(value) = code.get(i + 1..i + 5) {
+                    selectors.insert([value[0], value[1], value[2], value[3]]);
+                }
+            }
+            i += n_bytes;
+        }
+        i += 1;
+    }
+    selectors
+}
+
+/// split bytecode into code and the cbor metadata whose length is given by its last 2 bytes
+fn split_metadata(code: &[u8]) -> Option<(&[u8], &[u8])> {
+    let length = u16::from_be_bytes([*code.get(code.len().checked_sub(2)?)?, code[code.len() - 1]]);
+    let start = code.len().checked_sub(2 + length as usize)?;
+    match code.get(start) {
+        // a map (solc and vyper < 0.3.10) or an array (vyper >= 0.3.10)
+        Some(head) if length > 0 && (head >> 5 == 5 || head >> 5 == 4) => {
+            Some((&code[..start], &code[start..code.len() - 2]))
+        }
+        _ => None,
+    }
+}
+
+/// metadata hash and compiler of cbor metadata
+fn parse_metadata(metadata: &[u8]) -> Option<(Option<Vec<u8>>, Option<String>)> {
+    let mut decoder = CborDecoder { data: metadata, position: 0 };
+    let entries = match decoder.value()? {
+        CborValue::Map(entries) => entries,
+        // vyper >= 0.3.10 appends [runtime size, data sizes, immutables size, {"vyper": version}]
+        CborValue::Array(values) => match values.into_iter().last()? {
+            CborValue::Map(entries) => entries,
+            _ => return None,
+        },
+        _ => return None,
+    };
+    if decoder.position != metadata.len() {
+        return None
+    }
+    let mut hash = None;
+    let mut compiler = None;
+    for (key, value) in entries.into_iter() {
+        match (key.as_str(), value) {
+            ("ipfs" | "bzzr0" | "bzzr1", CborValue::Bytes(bytes)) => {
+                compiler = compiler.or(Some("solc".to_string()));
+                hash = Some(bytes);
+            }
+            ("solc", CborValue::Bytes(version)) if version.len() == 3 => {
+                compiler = Some(format!("solc {}.{}.{}", version[0], version[1], version[2]));
+            }
+            ("solc", CborValue::Text(version)) => compiler = Some(format!("solc {}", version)),
+            ("vyper", CborValue::Array(version)) => {
+                let version: Option<Vec<String>> = version
+                    .into_iter()
+                    .map(|part| match part {
+                        CborValue::Uint(part) => Some(part.to_string()),
+                        _ => None,
+                    })
+                    .collect();
+                compiler = version.map(|version| format!("vyper {}", version.join(".")));
+            }
+            _ => {}
+        }
+    }
+    Some((hash, compiler))
+}
+
+enum CborValue {
+    Uint(u64),
+    Bytes(Vec<u8>),
+    Text(String),
+    Array(Vec<CborValue>),
+    Map(Vec<(String, CborValue)>),
+    Simple,
+}
+
+/// decoder of the subset of cbor used by compiler metadata
+struct CborDecoder<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl CborDecoder<'_> {
+    fn take(&mut self, n: usize) -> Option<&[u8]> {
+        let bytes = self.data.get(self.position..self.position.checked_add(n)?)?;
+        self.position += n;
+        Some(bytes)
+    }
+
+    /// major type and argument of the next item
+    fn head(&mut self) -> Option<(u8, u64)> {
+        let initial = *self.take(1)?.first()?;
+        let argument = match initial & 0x1f {
+            info @ 0..=23 => info as u64,
+            24 => *self.take(1)?.first()? as u64,
+            25 => u16::from_be_bytes(self.take(2)?.try_into().ok()?) as u64,
+            26 => u32::from_be_bytes(self.take(4)?.try_into().ok()?) as u64,
+            27 => u64::from_be_bytes(self.take(8)?.try_into().ok()?),
+            _ => return None,
+        };
+        Some((initial >> 5, argument))
+    }
+
+    fn value(&mut self) -> Option<CborValue> {
+        let (major, argument) = self.head()?;
+        let length = usize::try_from(argument).ok();
+        match major {
+            0 => Some(CborValue::Uint(argument)),
+            2 => Some(CborValue::Bytes(self.take(length?)?.to_vec())),
+            3 => Some(CborValue::Text(String::from_utf8(self.take(length?)?.to_vec()).ok()?)),
+            4 => (0..length?).map(|_| self.value()).collect::<Option<_>>().map(CborValue::Array),
+            5 => (0..length?)
+                .map(|_| match self.value()? {
+                    CborValue::Text(key) => Some((key, self.value()?)),
+                    _ => None,
+                })
+                .collect::<Option<_>>()
+                .map(CborValue::Map),
+            7 => Some(CborValue::Simple),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bytecode_analysis() {
+        // dispatcher of erc20 selectors, with a PUSH32 whose data must not be scanned
+        let mut code = Vec::new();
+        for selector in ERC20_SELECTORS.iter() {
+            code.extend([0x80, 0x63]);
+            code.extend(selector);
+            code.extend([0x14, 0x61, 0x00, 0x10, 0x57]);
+        }
+        code.push(0x7f);
+        code.extend([0x63; 32]);
+        code.push(0xfe);
+        // {"ipfs": 34 bytes, "solc": 0.8.19}
+        code.extend([0xa2, 0x64, b'i', b'p', b'f', b's', 0x58, 0x22, 0x12, 0x20]);
+        code.extend([0xab; 32]);
+        code.extend([0x64, b's', b'o', b'l', b'c', 0x43, 0x00, 0x08, 0x13, 0x00, 0x33]);
+
+        let analysis = BytecodeAnalysis::new(&code);
+        assert!(analysis.is_erc20());
+        assert!(!analysis.is_erc721());
+        assert_eq!(analysis.selectors.len(), ERC20_SELECTORS.len());
+        assert_eq!(analysis.metadata_hash.map(|hash| hash.len()), Some(34));
+        assert_eq!(analysis.compiler, Some("solc 0.8.19".to_string()));
+
+        let analysis = BytecodeAnalysis::new(&[0x60, 0x80, 0x60, 0x40, 0x52]);
+        assert_eq!(analysis.metadata_hash, None);
+        assert_eq!(analysis.compiler, None);
+    }
+}
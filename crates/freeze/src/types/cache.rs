@@ -0,0 +1,165 @@
+/// caching of rpc responses shared by datasets collected together
+use std::{
+    any::Any,
+    collections::{HashMap, VecDeque},
+    future::Future,
+    sync::{Arc, Mutex},
+};
+
+use tokio::sync::OnceCell;
+
+use crate::CollectError;
+
+/// bounded cache of responses keyed by block number, evicting the least recently used block
+///
+/// concurrent requests for the same block wait for a single response instead of each sending a
+/// request, and failed requests are not cached
+pub struct BlockCache<T> {
+    capacity: usize,
+    entries: Mutex<CacheEntries<T>>,
+}
+
+struct CacheEntries<T> {
+    cells: HashMap<u64, Arc<OnceCell<T>>>,
+    order: VecDeque<u64>,
+}
+
+impl<T: Clone> BlockCache<T> {
+    /// create cache holding responses of up to `capacity` blocks
+    pub fn new(capacity: usize) -> Self {
+        let entries = CacheEntries { cells: HashMap::new(), order: VecDeque::new() };
+        Self { capacity, entries: Mutex::new(entries) }
+    }
+
+    /// get cached response of block, fetching it if it is not cached
+    pub async fn get_or_fetch<F, Fut>(&self, block_number: u64, fetch: F) -> Result<T, CollectError>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<T, CollectError>>,
+    {
+        let cell = {
+            let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+            let CacheEntries { cells, order } = &mut *entries;
+            if let Some(position) = order.iter().position(|n| *n == block_number) {
+                order.remove(position);
+            }
+            order.push_back(block_number);
+            let cell = cells.entry(block_number).or_default().clone();
+            while order.len() > self.capacity {
+                if let Some(evicted) = order.pop_front() {
+                    cells.remove(&evicted);
+                }
+            }
+            cell
+        };
+        cell.get_or_try_init(fetch).await.cloned()
+    }
+}
+
+/// response shared by identical requests, of the type of the response of their method
+type SharedResponse = Arc<dyn Any + Send + Sync>;
+
+/// sharing of responses between identical requests that are in flight at the same time, such as
+/// the requests of blocks by datasets collected together
+///
+/// requests are keyed by method and params. a request sent while an identical request is in
+/// flight waits for its response instead of being sent again, and if the in-flight request fails
+/// one of the waiting requests is sent instead. responses are not kept once the requests waiting
+/// for them have them
+#[derive(Default)]
+pub struct RequestCoalescer {
+    in_flight: Mutex<HashMap<String, Arc<OnceCell<SharedResponse>>>>,
+}
+
+impl RequestCoalescer {
+    /// get response of request, waiting for an identical request instead if one is in flight
+    pub async fn get_or_fetch<T, F, Fut>(&self, key: String, fetch: F) -> Result<T, CollectError>
+    where
+        T: Clone + Send + Sync + 'static,
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<T, CollectError>>,
+    {
+        let cell = {
+            let mut in_flight = self.in_flight.lock().unwrap_or_else(|e| e.into_inner());
+            in_flight.entry(key.clone()).or_default().clone()
+        };
+        let fetch = || async { fetch().await.map(|response| Arc::new(response) as SharedResponse) };
+        let result = cell.get_or_try_init(fetch).await.cloned();
+        {
+            let mut in_flight = self.in_flight.lock().unwrap_or_else(|e| e.into_inner());
+            if in_flight.get(&key).is_some_and(|entry| Arc::ptr_eq(entry, &cell)) {
+                in_flight.remove(&key);
+            }
+        }
+        result?.downcast_ref::<T>().cloned().ok_or_else(|| {
+            CollectError::CollectError(format!("response of {} shared with a different type", key))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU64, Ordering};
+
+    #[tokio::test]
+    async fn test_block_cache() {
+        let cache = BlockCache::new(2);
+        let n_fetches = AtomicU64::new(0);
+        let fetch = |block_number: u64| {
+            n_fetches.fetch_add(1, Ordering::Relaxed);
+            async move { Ok(block_number * 10) }
+        };
+
+        assert_eq!(cache.get_or_fetch(1, || fetch(1)).await.unwrap(), 10);
+        assert_eq!(cache.get_or_fetch(1, || fetch(1)).await.unwrap(), 10);
+        assert_eq!(n_fetches.load(Ordering::Relaxed), 1);
+
+        // block 2 is evicted as least recently used
+        cache.get_or_fetch(2, || fetch(2)).await.unwrap();
+        cache.get_or_fetch(1, || fetch(1)).await.unwrap();
+        cache.get_or_fetch(3, || fetch(3)).await.unwrap();
+        cache.get_or_fetch(1, || fetch(1)).await.unwrap();
+        assert_eq!(n_fetches.load(Ordering::Relaxed), 3);
+        cache.get_or_fetch(2, || fetch(2)).await.unwrap();
+        assert_eq!(n_fetches.load(Ordering::Relaxed), 4);
+    }
+
+    #[tokio::test]
+    async fn test_request_coalescer() {
+        let coalescer = RequestCoalescer::default();
+        let n_fetches = AtomicU64::new(0);
+        let fetch = |value: u64| {
+            n_fetches.fetch_add(1, Ordering::Relaxed);
+            async move {
+                tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+                Ok(value)
+            }
+        };
+
+        // identical requests in flight together are sent once
+        let (a, b, c) = tokio::join!(
+            coalescer.get_or_fetch("eth_getBlockByNumber(1)".into(), || fetch(1)),
+            coalescer.get_or_fetch("eth_getBlockByNumber(1)".into(), || fetch(1)),
+            coalescer.get_or_fetch("eth_getBlockByNumber(2)".into(), || fetch(2)),
+        );
+        assert_eq!((a.unwrap(), b.unwrap(), c.unwrap()), (1, 1, 2));
+        assert_eq!(n_fetches.load(Ordering::Relaxed), 2);
+
+        // responses are not kept after requests complete
+        coalescer.get_or_fetch("eth_getBlockByNumber(1)".into(), || fetch(1)).await.unwrap();
+        assert_eq!(n_fetches.load(Ordering::Relaxed), 3);
+
+        // requests waiting on a failed request are sent themselves
+        let fail = || async {
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+            Err::<u64, _>(CollectError::CollectError("failed".into()))
+        };
+        let (a, b) = tokio::join!(
+            coalescer.get_or_fetch("eth_call".into(), fail),
+            coalescer.get_or_fetch("eth_call".into(), || fetch(3)),
+        );
+        assert!(a.is_err());
+        assert_eq!(b.unwrap(), 3);
+    }
+}
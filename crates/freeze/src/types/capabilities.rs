@@ -0,0 +1,164 @@
+/// detection of optional rpc apis supported by a node
+use ethers::prelude::*;
+
+use crate::{CollectError, Datatype, Fetcher, ParseError, Query};
+
+/// substrings of errors returned by nodes for methods they do not support
+const UNSUPPORTED_METHOD_ERRORS: [&str; 6] = [
+    "-32601",
+    "method not found",
+    "does not exist",
+    "not available",
+    "not supported",
+    "unsupported",
+];
+
+/// optional rpc apis that a node supports, detected when the source is created
+#[derive(Clone, Debug, Default)]
+pub struct Capabilities {
+    /// whether node supports the `trace_` namespace
+    pub trace: bool,
+    /// whether node supports the `debug_` namespace
+    pub debug: bool,
+    /// whether node supports `eth_getBlockReceipts`
+    pub eth_block_receipts: bool,
+    /// whether node supports `erigon_getBlockReceipts`
+    pub erigon_block_receipts: bool,
+    /// whether node supports the otterscan `ots_` namespace
+    pub otterscan: bool,
+    /// whether node accepts json-rpc batch requests
+    pub batch_requests: bool,
+}
+
+impl Capabilities {
+    /// probe node for optional apis
+    ///
+    /// a namespace is considered supported unless the node rejects its probe as an unknown
+    /// method, so that probes failing for other reasons do not disable it
+    pub async fn probe<P: JsonRpcClient>(
+        fetcher: &Fetcher<P>,
+        batch_endpoint: Option<(&reqwest::Client, &str)>,
+    ) -> Capabilities {
+        let genesis = BlockNumber::Number(0.into());
+        let (trace, debug, eth_block_receipts, erigon_block_receipts, otterscan, batch_requests) = tokio::join!(
+            fetcher.trace_block(genesis),
+            fetcher.debug_trace_block_by_number(genesis),
+            fetcher.get_block_receipts(0),
+            fetcher.erigon_get_block_receipts(0),
+            fetcher.ots_get_api_level(),
+            probe_batch_requests(batch_endpoint),
+        );
+        Capabilities {
+            trace: is_supported(&trace),
+            debug: is_supported(&debug),
+            eth_block_receipts: is_supported(&eth_block_receipts),
+            erigon_block_receipts: erigon_block_receipts.is_ok(),
+            otterscan: otterscan.is_ok(),
+            batch_requests,
+        }
+    }
+
+    /// check that node supports the apis needed to collect query, listing alternatives if not
+    pub fn check_query(&self, query: &Query) -> Result<(), ParseError> {
+        let datatypes: Vec<Datatype> =
+            query.datatypes.iter().flat_map(|datatype| datatype.datatypes()).collect();
+        let needs_trace: Vec<String> = datatypes
+            .iter()
+            .filter(|datatype| requires_trace(datatype))
+            .map(|datatype| datatype.name())
+            .collect();
+        if !self.trace && !needs_trace.is_empty() {
+            return Err(ParseError::ParseError(format!(
+                "rpc node does not support the trace_ namespace, which is required to collect {}. \
+                 use a node that supports trace_ methods (such as erigon, reth, or nethermind), \
+                 or collect datasets that only need eth_ methods (such as blocks, transactions, \
+                 or logs)",
+                needs_trace.join(", ")
+            )))
+        }
+
+        if !self.debug && datatypes.contains(&Datatype::JavascriptTraces) {
+            return Err(ParseError::ParseError(
+                "rpc node does not support the debug_ namespace, which is required to collect \
+                 javascript_traces. use a node that supports debug_ tracers (such as geth)"
+                    .to_string(),
+            ))
+        }
+
+        let by_address = query.partitions.iter().any(|partition| partition.addresses.is_some());
+        if datatypes.contains(&Datatype::Transactions) &&
+            by_address &&
+            !self.trace &&
+            !self.otterscan
+        {
+            return Err(ParseError::ParseError(
+                "collecting transactions by address requires a node that supports the ots_ or \
+                 trace_ namespace. collect transactions by block or by transaction hash instead"
+                    .to_string(),
+            ))
+        }
+        Ok(())
+    }
+}
+
+/// whether collecting datatype requires the `trace_` namespace
+fn requires_trace(datatype: &Datatype) -> bool {
+    matches!(
+        datatype,
+        Datatype::BalanceDiffs |
+            Datatype::CodeDiffs |
+            Datatype::Contracts |
+            Datatype::MinerPayments |
+            Datatype::NativeTransfers |
+            Datatype::NonceDiffs |
+            Datatype::StorageDiffs |
+            Datatype::TraceCalls |
+            Datatype::Traces |
+            Datatype::TransactionAddresses |
+            Datatype::VmTraces
+    )
+}
+
+fn is_supported<T>(result: &Result<T, CollectError>) -> bool {
+    match result {
+        Ok(_) => true,
+        Err(e) => {
+            let message = e.to_string().to_lowercase();
+            !UNSUPPORTED_METHOD_ERRORS.iter().any(|pattern| message.contains(pattern))
+        }
+    }
+}
+
+/// whether node answers a batch of two requests with a batch of two responses, if requests may be
+/// sent to its endpoint in batches
+async fn probe_batch_requests(batch_endpoint: Option<(&reqwest::Client, &str)>) -> bool {
+    let Some((http_client, rpc_url)) = batch_endpoint else { return false };
+    let batch = serde_json::json!([
+        {"jsonrpc": "2.0", "id": 1, "method": "eth_chainId", "params": []},
+        {"jsonrpc": "2.0", "id": 2, "method": "eth_chainId", "params": []},
+    ]);
+    let response = match http_client.post(rpc_url).json(&batch).send().await {
+        Ok(response) => response,
+        Err(_) => return false,
+    };
+    match response.json::<Vec<serde_json::Value>>().await {
+        Ok(responses) => responses.len() == 2,
+        Err(_) => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_supported() {
+        let error = |message: &str| Err::<(), _>(CollectError::CollectError(message.to_string()));
+        assert!(is_supported(&Ok(())));
+        assert!(is_supported(&error("(code: -32000, message: genesis is not traceable)")));
+        assert!(!is_supported(&error(
+            "(code: -32601, message: the method trace_block does not exist/is not available)"
+        )));
+        assert!(!is_supported(&error("Method not found")));
+    }
+}
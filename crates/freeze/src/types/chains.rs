@@ -0,0 +1,230 @@
+/// number of blocks per file on chains without a known block time
+pub const DEFAULT_CHUNK_SIZE: u64 = 1000;
+
+/// block time that `DEFAULT_CHUNK_SIZE` is calibrated for
+const DEFAULT_BLOCK_TIME_MS: u64 = 12_000;
+
+/// metadata and defaults of a known chain
+#[derive(Clone, Debug)]
+pub struct ChainInfo {
+    /// chain id
+    pub chain_id: u64,
+    /// name of chain, used as prefix of output files
+    pub name: &'static str,
+    /// other names accepted by `--network`
+    pub aliases: &'static [&'static str],
+    /// url of block explorer
+    pub explorer: &'static str,
+    /// symbol of native token
+    pub native_symbol: &'static str,
+    /// public rpc endpoint, if chain has one
+    pub default_rpc: Option<&'static str>,
+    /// typical time between blocks, in milliseconds
+    pub block_time_ms: u64,
+}
+
+impl ChainInfo {
+    /// number of blocks per file spanning about as much time as `DEFAULT_CHUNK_SIZE` blocks of
+    /// ethereum, rounded to a multiple of 1000 blocks
+    pub fn default_chunk_size(&self) -> u64 {
+        let chunk_size = DEFAULT_CHUNK_SIZE * DEFAULT_BLOCK_TIME_MS / self.block_time_ms.max(1);
+        ((chunk_size + 500) / 1000 * 1000).max(DEFAULT_CHUNK_SIZE)
+    }
+}
+
+macro_rules! chain {
+    ($chain_id:expr, $name:expr, $aliases:expr, $explorer:expr, $symbol:expr, $rpc:expr, $block_time_ms:expr) => {
+        ChainInfo {
+            chain_id: $chain_id,
+            name: $name,
+            aliases: $aliases,
+            explorer: $explorer,
+            native_symbol: $symbol,
+            default_rpc: $rpc,
+            block_time_ms: $block_time_ms,
+        }
+    };
+}
+
+/// known chains
+pub const CHAINS: &[ChainInfo] = &[
+    chain!(
+        1,
+        "ethereum",
+        &["eth", "mainnet"],
+        "https://etherscan.io",
+        "ETH",
+        Some("https://ethereum-rpc.publicnode.com"),
+        12_000
+    ),
+    chain!(5, "goerli", &[], "https://goerli.etherscan.io", "ETH", None, 12_000),
+    chain!(
+        10,
+        "optimism",
+        &["op"],
+        "https://optimistic.etherscan.io",
+        "ETH",
+        Some("https://mainnet.optimism.io"),
+        2_000
+    ),
+    chain!(
+        56,
+        "bnb",
+        &["bsc"],
+        "https://bscscan.com",
+        "BNB",
+        Some("https://bsc-dataseed.bnbchain.org"),
+        3_000
+    ),
+    chain!(69, "optimism_kovan", &[], "https://kovan-optimistic.etherscan.io", "ETH", None, 2_000),
+    chain!(
+        100,
+        "gnosis",
+        &["xdai"],
+        "https://gnosisscan.io",
+        "xDAI",
+        Some("https://rpc.gnosischain.com"),
+        5_000
+    ),
+    chain!(
+        137,
+        "polygon",
+        &["matic"],
+        "https://polygonscan.com",
+        "POL",
+        Some("https://polygon-rpc.com"),
+        2_000
+    ),
+    chain!(420, "optimism_goerli", &[], "https://goerli-optimism.etherscan.io", "ETH", None, 2_000),
+    chain!(
+        1101,
+        "polygon_zkevm",
+        &[],
+        "https://zkevm.polygonscan.com",
+        "ETH",
+        Some("https://zkevm-rpc.com"),
+        3_000
+    ),
+    chain!(
+        1442,
+        "polygon_zkevm_testnet",
+        &[],
+        "https://testnet-zkevm.polygonscan.com",
+        "ETH",
+        None,
+        3_000
+    ),
+    chain!(
+        8453,
+        "base",
+        &[],
+        "https://basescan.org",
+        "ETH",
+        Some("https://mainnet.base.org"),
+        2_000
+    ),
+    chain!(
+        10200,
+        "gnosis_chidao",
+        &["chiado"],
+        "https://gnosis-chiado.blockscout.com",
+        "xDAI",
+        Some("https://rpc.chiadochain.net"),
+        5_000
+    ),
+    chain!(
+        17000,
+        "holesky",
+        &[],
+        "https://holesky.etherscan.io",
+        "ETH",
+        Some("https://ethereum-holesky-rpc.publicnode.com"),
+        12_000
+    ),
+    chain!(
+        42161,
+        "arbitrum",
+        &["arb", "arbitrum_one"],
+        "https://arbiscan.io",
+        "ETH",
+        Some("https://arb1.arbitrum.io/rpc"),
+        250
+    ),
+    chain!(
+        42170,
+        "arbitrum_nova",
+        &["nova"],
+        "https://nova.arbiscan.io",
+        "ETH",
+        Some("https://nova.arbitrum.io/rpc"),
+        250
+    ),
+    chain!(
+        43114,
+        "avalanche",
+        &["avax"],
+        "https://snowtrace.io",
+        "AVAX",
+        Some("https://api.avax.network/ext/bc/C/rpc"),
+        2_000
+    ),
+    chain!(
+        80001,
+        "polygon_mumbai",
+        &["mumbai"],
+        "https://mumbai.polygonscan.com",
+        "MATIC",
+        None,
+        2_000
+    ),
+    chain!(84531, "base_goerli", &[], "https://goerli.basescan.org", "ETH", None, 2_000),
+    chain!(
+        7777777,
+        "zora",
+        &[],
+        "https://explorer.zora.energy",
+        "ETH",
+        Some("https://rpc.zora.energy"),
+        2_000
+    ),
+    chain!(
+        11155111,
+        "sepolia",
+        &[],
+        "https://sepolia.etherscan.io",
+        "ETH",
+        Some("https://ethereum-sepolia-rpc.publicnode.com"),
+        12_000
+    ),
+];
+
+/// get known chain by chain id
+pub fn get_chain(chain_id: u64) -> Option<&'static ChainInfo> {
+    CHAINS.iter().find(|chain| chain.chain_id == chain_id)
+}
+
+/// get known chain by name, alias, or chain id
+pub fn get_chain_by_name(name: &str) -> Option<&'static ChainInfo> {
+    let name = name.to_lowercase();
+    match name.parse::<u64>() {
+        Ok(chain_id) => get_chain(chain_id),
+        Err(_) => {
+            CHAINS.iter().find(|chain| chain.name == name || chain.aliases.contains(&name.as_str()))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chain_registry() {
+        assert_eq!(get_chain_by_name("arb").map(|chain| chain.chain_id), Some(42161));
+        assert_eq!(get_chain_by_name("8453").map(|chain| chain.name), Some("base"));
+        assert!(get_chain_by_name("unknown").is_none());
+        assert_eq!(get_chain(1).map(|chain| chain.default_chunk_size()), Some(1000));
+        assert_eq!(get_chain(42161).map(|chain| chain.default_chunk_size()), Some(48000));
+        assert_eq!(get_chain(100).map(|chain| chain.default_chunk_size()), Some(2000));
+    }
+}
@@ -10,6 +10,19 @@ pub enum BinaryChunk {
     // Range(Vec<u8>, Vec<u8>),
 }
 
+impl BinaryChunk {
+    /// split chunks into one chunk per distinct value, in order of first appearance
+    pub fn split_values(chunks: &[BinaryChunk]) -> Vec<BinaryChunk> {
+        let mut values: Vec<Vec<u8>> = Vec::new();
+        for value in chunks.iter().flat_map(|chunk| chunk.values()) {
+            if !values.contains(&value) {
+                values.push(value);
+            }
+        }
+        values.into_iter().map(|value| BinaryChunk::Values(vec![value])).collect()
+    }
+}
+
 impl ChunkData for BinaryChunk {
     type Inner = Vec<u8>;
 
@@ -6,6 +6,9 @@ use super::{binary_chunk::BinaryChunk, chunk_ops::ChunkData, number_chunk::Numbe
 /// block chunk
 pub type BlockChunk = NumberChunk;
 
+/// beacon slot chunk
+pub type BeaconSlotChunk = NumberChunk;
+
 /// transaction chunk
 pub type TransactionChunk = BinaryChunk;
 
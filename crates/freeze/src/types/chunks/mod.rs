@@ -5,7 +5,8 @@ pub(crate) mod number_chunk;
 pub(crate) mod subchunks;
 
 pub use chunk::{
-    AddressChunk, BlockChunk, CallDataChunk, Chunk, SlotChunk, TopicChunk, TransactionChunk,
+    AddressChunk, BeaconSlotChunk, BlockChunk, CallDataChunk, Chunk, SlotChunk, TopicChunk,
+    TransactionChunk,
 };
 pub use chunk_ops::{ChunkData, ChunkStats};
 pub use subchunks::Subchunk;
@@ -3,6 +3,7 @@ use futures::Future;
 use polars::prelude::*;
 use std::collections::HashMap;
 use tokio::{sync::mpsc, task};
+use tracing::Instrument;
 
 /// collect single partition
 pub async fn collect_partition(
@@ -12,12 +13,34 @@ pub async fn collect_partition(
     source: Arc<Source>,
     schemas: HashMap<Datatype, Table>,
 ) -> Result<HashMap<Datatype, DataFrame>, CollectError> {
-    match time_dimension {
-        TimeDimension::Blocks => collect_by_block(datatype, partition, source, schemas).await,
+    let dfs = match time_dimension {
+        TimeDimension::Blocks => {
+            collect_by_block(datatype, partition, source.clone(), schemas.clone()).await?
+        }
         TimeDimension::Transactions => {
-            collect_by_transaction(datatype, partition, source, schemas).await
+            collect_by_transaction(datatype, partition, source.clone(), schemas.clone()).await?
+        }
+    };
+    let dfs = timestamps::attach_timestamps(&source, dfs).await?;
+    let dfs = verification::attach_verified_sources(&source, dfs).await?;
+
+    // apply user-defined filters and transforms
+    let mut output = HashMap::new();
+    for (datatype, mut df) in dfs.into_iter() {
+        if let Some(schema) = schemas.get(&datatype) {
+            if let Some(row_filter) = &schema.row_filter {
+                df = row_filter.apply(df)?;
+            }
+            if let Some(transform) = &schema.transform {
+                df = transform.apply(df)?;
+            }
+            if let Some(sql) = &schema.sql {
+                df = apply_sql(df, &datatype.name(), sql)?;
+            }
         }
+        output.insert(datatype, df);
     }
+    Ok(output)
 }
 
 /// fetch data for a given partition
@@ -43,13 +66,18 @@ where
         let sender = sender.clone();
         let source = source.clone();
         let schemas = schemas.clone();
-        let handle = task::spawn(async move {
-            let result = f_request(rpc_params, source.clone(), schemas).await;
-            match sender.send(result).await {
-                Ok(_) => Ok(()),
-                Err(_) => Err(CollectError::CollectError("tokio mpsc send failure".to_string())),
+        let handle = task::spawn(
+            async move {
+                let result = f_request(rpc_params, source.clone(), schemas).await;
+                match sender.send(result).await {
+                    Ok(_) => Ok(()),
+                    Err(_) => {
+                        Err(CollectError::CollectError("tokio mpsc send failure".to_string()))
+                    }
+                }
             }
-        });
+            .in_current_span(),
+        );
         handles.push(handle);
     }
 
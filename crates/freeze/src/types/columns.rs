@@ -62,6 +62,12 @@ pub trait Dataset: Sync + Send {
     /// default sort order for dataset
     fn default_sort() -> Vec<String>;
 
+    /// columns that identify a row of dataset, by which rows of overlapping chunks are
+    /// deduplicated with `--dedup-keys`
+    fn dedup_keys() -> Option<Vec<String>> {
+        None
+    }
+
     /// default columns extracted for Dataset
     fn default_columns() -> Option<Vec<&'static str>> {
         None
@@ -87,6 +93,13 @@ pub trait Dataset: Sync + Send {
         false
     }
 
+    /// cost of collecting a chunk relative to datasets that make one cheap request per block,
+    /// used to order chunks of different datasets so that cheap datasets are not queued behind
+    /// expensive ones
+    fn relative_cost() -> u64 {
+        1
+    }
+
     /// input arg aliases
     fn arg_aliases() -> Option<HashMap<String, String>> {
         None
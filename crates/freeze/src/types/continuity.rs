@@ -0,0 +1,141 @@
+/// validation that the data of a partition comes from a single chain of blocks
+use std::collections::HashMap;
+
+use ethers::prelude::*;
+use polars::prelude::*;
+
+use crate::{
+    err, ChunkData, CollectError, ColumnEncoding, Datatype, Partition, Source, Table, ToVecHex,
+};
+
+/// hash and parent hash of each block of a partition, keyed by block number
+type Headers = HashMap<u64, (H256, H256)>;
+
+/// check that the blocks of partition form a continuous chain and that collected rows belong to
+/// it, adding block_hash and parent_hash columns to dataframes that are indexed by block
+pub(crate) async fn validate_partition(
+    partition: &Partition,
+    source: &Source,
+    dfs: HashMap<Datatype, DataFrame>,
+    schemas: &HashMap<Datatype, Table>,
+) -> Result<HashMap<Datatype, DataFrame>, CollectError> {
+    let block_numbers: Vec<u64> = match &partition.block_numbers {
+        Some(chunks) => chunks.iter().flat_map(|chunk| chunk.values()).collect(),
+        None => return Ok(dfs),
+    };
+    let headers = fetch_headers(block_numbers, source).await?;
+    validate_headers(&headers)?;
+
+    let mut output = HashMap::new();
+    for (datatype, df) in dfs.into_iter() {
+        let schema = schemas.get(&datatype).ok_or(err("schema not provided"))?;
+        output.insert(datatype, validate_df(df, &headers, schema.column_encoding("block_hash"))?);
+    }
+    Ok(output)
+}
+
+async fn fetch_headers(block_numbers: Vec<u64>, source: &Source) -> Result<Headers, CollectError> {
+    let requests = block_numbers.into_iter().map(|block_number| async move {
+        let block = source.fetcher.get_block(block_number).await?.ok_or(err("block not found"))?;
+        let hash = block.hash.ok_or(err("block hash not found"))?;
+        Ok::<_, CollectError>((block_number, (hash, block.parent_hash)))
+    });
+    Ok(futures::future::try_join_all(requests).await?.into_iter().collect())
+}
+
+/// check that the parent hash of every block matches the hash of the previous block
+fn validate_headers(headers: &Headers) -> Result<(), CollectError> {
+    for (block_number, (_hash, parent_hash)) in headers.iter() {
+        if let Some((previous_hash, _)) = block_number.checked_sub(1).and_then(|n| headers.get(&n))
+        {
+            if previous_hash != parent_hash {
+                return Err(CollectError::ReorgDetected(format!(
+                    "parent hash of block {} does not match hash of block {}",
+                    block_number,
+                    block_number - 1
+                )))
+            }
+        }
+    }
+    Ok(())
+}
+
+/// check block hashes of rows, or add block hash columns if the dataframe does not have them
+fn validate_df(
+    mut df: DataFrame,
+    headers: &Headers,
+    binary_type: &ColumnEncoding,
+) -> Result<DataFrame, CollectError> {
+    let block_numbers: Vec<Option<u64>> = match df.column("block_number") {
+        Ok(column) => column.cast(&DataType::UInt64)?.u64()?.into_iter().collect(),
+        Err(_) => return Ok(df),
+    };
+    if let Ok(column) = df.column("block_hash") {
+        let row_hashes: Vec<Option<Vec<u8>>> = match binary_type {
+            ColumnEncoding::Binary => {
+                column.binary()?.into_iter().map(|hash| hash.map(|h| h.to_vec())).collect()
+            }
+            ColumnEncoding::Hex | ColumnEncoding::ChecksumHex => column
+                .utf8()?
+                .into_iter()
+                .map(|hash| hash.and_then(|h| prefix_hex::decode::<Vec<u8>>(h).ok()))
+                .collect(),
+        };
+        for (block_number, row_hash) in block_numbers.iter().zip(row_hashes) {
+            let expected = block_number.and_then(|n| headers.get(&n));
+            if let (Some(block_number), Some((hash, _)), Some(row_hash)) =
+                (block_number, expected, row_hash)
+            {
+                if hash.as_bytes() != row_hash.as_slice() {
+                    return Err(CollectError::ReorgDetected(format!(
+                        "row of block {} was collected from a different fork",
+                        block_number
+                    )))
+                }
+            }
+        }
+        return Ok(df)
+    }
+
+    let header = |n: &Option<u64>| n.and_then(|n| headers.get(&n));
+    let block_hashes: Vec<Option<Vec<u8>>> =
+        block_numbers.iter().map(|n| header(n).map(|(hash, _)| hash.0.to_vec())).collect();
+    let parent_hashes: Vec<Option<Vec<u8>>> =
+        block_numbers.iter().map(|n| header(n).map(|(_, parent)| parent.0.to_vec())).collect();
+    for (name, values) in [("block_hash", block_hashes), ("parent_hash", parent_hashes)] {
+        if df.column(name).is_err() {
+            let series = match binary_type {
+                ColumnEncoding::Binary => Series::new(name, values),
+                ColumnEncoding::Hex | ColumnEncoding::ChecksumHex => {
+                    Series::new(name, values.to_vec_hex())
+                }
+            };
+            df.with_column(series)?;
+        }
+    }
+    Ok(df)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_continuity() {
+        let hash = |n: u8| H256::from([n; 32]);
+        let mut headers: Headers =
+            [(10, (hash(10), hash(9))), (11, (hash(11), hash(10)))].into_iter().collect();
+        assert!(validate_headers(&headers).is_ok());
+
+        let df = df!("block_number" => [10u32, 11, 11]).unwrap();
+        let df = validate_df(df, &headers, &ColumnEncoding::Binary).unwrap();
+        let block_hashes = df.column("block_hash").unwrap().binary().unwrap();
+        assert_eq!(block_hashes.get(2), Some(hash(11).as_bytes()));
+        assert!(validate_df(df.clone(), &headers, &ColumnEncoding::Binary).is_ok());
+
+        headers.insert(11, (hash(12), hash(10)));
+        assert!(validate_df(df, &headers, &ColumnEncoding::Binary).is_err());
+        headers.insert(11, (hash(11), hash(0)));
+        assert!(validate_headers(&headers).is_err());
+    }
+}
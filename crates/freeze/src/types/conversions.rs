@@ -13,6 +13,25 @@ pub fn bytes_to_u32(value: Bytes) -> Result<u32, CollectError> {
     }
 }
 
+/// parse decimal string into u64, as used by http apis that serialize integers as strings
+pub(crate) fn parse_u64_string(value: &str) -> Result<u64, CollectError> {
+    value
+        .parse::<u64>()
+        .map_err(|_| CollectError::CollectError(format!("invalid integer: {}", value)))
+}
+
+/// parse 0x-prefixed hex string into bytes
+pub(crate) fn parse_hex_string(value: &str) -> Result<Vec<u8>, CollectError> {
+    prefix_hex::decode(value)
+        .map_err(|_| CollectError::CollectError(format!("invalid hex: {}", value)))
+}
+
+/// convert U256 to f64, scaled down by 10^decimals
+pub fn u256_to_f64_decimals(value: U256, decimals: u32) -> Option<f64> {
+    let value = value.to_string().parse::<f64>().ok()?;
+    Some(value / 10f64.powi(decimals as i32))
+}
+
 /// Converts data to Vec<u8>
 pub trait ToVecU8 {
     /// Convert to Vec<u8>
@@ -75,3 +94,39 @@ impl ToVecHex for Vec<Option<Vec<u8>>> {
         self.iter().map(|opt| opt.as_ref().map(|v| prefix_hex::encode(v.clone()))).collect()
     }
 }
+
+/// Encodes data as Vec of EIP-55 checksummed address String
+///
+/// data are encoded as plain hex unless every value is a 20 byte address
+pub trait ToVecChecksumHex {
+    /// Output type
+    type Output;
+
+    /// Convert to Vec of checksummed hex String
+    fn to_vec_checksum_hex(&self) -> Self::Output;
+}
+
+fn checksum_hex(value: &[u8], is_address: bool) -> String {
+    match is_address {
+        true => ethers::utils::to_checksum(&H160::from_slice(value), None),
+        false => prefix_hex::encode(value.to_vec()),
+    }
+}
+
+impl ToVecChecksumHex for Vec<Vec<u8>> {
+    type Output = Vec<String>;
+
+    fn to_vec_checksum_hex(&self) -> Self::Output {
+        let is_address = self.iter().all(|v| v.len() == 20);
+        self.iter().map(|v| checksum_hex(v, is_address)).collect()
+    }
+}
+
+impl ToVecChecksumHex for Vec<Option<Vec<u8>>> {
+    type Output = Vec<Option<String>>;
+
+    fn to_vec_checksum_hex(&self) -> Self::Output {
+        let is_address = self.iter().flatten().all(|v| v.len() == 20);
+        self.iter().map(|opt| opt.as_ref().map(|v| checksum_hex(v, is_address))).collect()
+    }
+}
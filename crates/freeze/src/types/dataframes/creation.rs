@@ -14,7 +14,11 @@ macro_rules! with_series_binary {
     ($all_series:expr, $name:expr, $value:expr, $schema:expr) => {
         if $schema.has_column($name) {
             if let Some(ColumnType::Hex) = $schema.column_type($name) {
-                $all_series.push(Series::new($name, $value.to_vec_hex()));
+                if $schema.column_encoding($name) == &ColumnEncoding::ChecksumHex {
+                    $all_series.push(Series::new($name, $value.to_vec_checksum_hex()));
+                } else {
+                    $all_series.push(Series::new($name, $value.to_vec_hex()));
+                }
             } else {
                 $all_series.push(Series::new($name, $value));
             }
@@ -33,7 +37,7 @@ macro_rules! with_series_u256 {
                 let name = name.as_str();
 
                 let converted: Vec<Vec<u8>> = $value.iter().map(|v| v.to_vec_u8()).collect();
-                if ColumnEncoding::Hex == $schema.binary_type {
+                if $schema.binary_type.is_hex() {
                     $all_series.push(Series::new(name, converted.to_vec_hex()));
                 } else {
                     $all_series.push(Series::new(name, converted));
@@ -107,7 +111,7 @@ macro_rules! with_series_option_u256 {
 
                 let converted: Vec<Option<Vec<u8>>> =
                     $value.iter().map(|v| v.map(|x| x.to_vec_u8())).collect();
-                if ColumnEncoding::Hex == $schema.binary_type {
+                if $schema.binary_type.is_hex() {
                     $all_series.push(Series::new(name, converted.to_vec_hex()));
                 } else {
                     $all_series.push(Series::new(name, converted));
@@ -1,26 +1,78 @@
-use std::path::Path;
+use std::{
+    io::Write,
+    path::{Path, PathBuf},
+};
 
-use polars::prelude::*;
+use polars::{
+    export::arrow::{
+        datatypes::{PhysicalType, Schema as ArrowSchema},
+        io::parquet::write::{
+            transverse, CompressionOptions, Encoding, FileWriter, KeyValue, RowGroupIterator,
+            Version, WriteOptions,
+        },
+    },
+    prelude::*,
+};
 
-use crate::types::{FileError, FileOutput};
+use crate::types::{FileError, FileFormat, FileOutput, TextCompression};
+
+/// suffix of files that are still being written
+pub(crate) const TMP_SUFFIX: &str = ".tmp";
+
+/// whether file name is of a tmp file, including tmp files of earlier versions of cryo, which
+/// end in `._tmp`
+pub(crate) fn is_tmp_file(name: &str) -> bool {
+    name.ends_with(TMP_SUFFIX) || name.ends_with("._tmp")
+}
+
+/// path that file is written to before it is renamed to path
+pub(crate) fn tmp_path(path: &Path) -> PathBuf {
+    let mut tmp_path = path.as_os_str().to_owned();
+    tmp_path.push(TMP_SUFFIX);
+    PathBuf::from(tmp_path)
+}
 
 /// write polars dataframe to file
+///
+/// the file is written to a tmp file that is synced to disk and then renamed, so that a file at
+/// filename is always complete, even if the process is killed while writing
 pub(crate) fn df_to_file(
     df: &mut DataFrame,
     filename: &Path,
     file_output: &FileOutput,
 ) -> Result<(), FileError> {
-    let tmp_filename = filename.with_extension("_tmp");
-    let result = match filename.extension().and_then(|ex| ex.to_str()) {
-        Some("parquet") => df_to_parquet(df, &tmp_filename, file_output),
-        Some("csv") => df_to_csv(df, &tmp_filename),
-        Some("json") => df_to_json(df, &tmp_filename),
-        _ => return Err(FileError::FileWriteError),
+    df_to_file_with_metadata(df, filename, file_output, &[])
+}
+
+/// write polars dataframe to file, recording metadata as the key-value metadata of parquet files
+pub(crate) fn df_to_file_with_metadata(
+    df: &mut DataFrame,
+    filename: &Path,
+    file_output: &FileOutput,
+    metadata: &[(String, String)],
+) -> Result<(), FileError> {
+    let filename = &crate::sinks::long_path(filename);
+    let tmp_filename = tmp_path(filename);
+    let result = match file_output.format {
+        FileFormat::Parquet => df_to_parquet(df, &tmp_filename, file_output, metadata),
+        FileFormat::Csv => hex_encode_binary_columns(df, file_output.hex_prefix)
+            .and_then(|mut df| df_to_csv(&mut df, &tmp_filename, &file_output.text_compression)),
+        FileFormat::Json => hex_encode_binary_columns(df, file_output.hex_prefix)
+            .and_then(|mut df| df_to_json(&mut df, &tmp_filename, &file_output.text_compression)),
     };
-    match result {
-        Ok(()) => std::fs::rename(tmp_filename, filename).map_err(|_e| FileError::FileWriteError),
-        Err(_e) => Err(FileError::FileWriteError),
+    let result = result.and_then(|()| commit_tmp_file(&tmp_filename, filename));
+    if result.is_err() {
+        let _ = std::fs::remove_file(&tmp_filename);
     }
+    result
+}
+
+/// sync tmp file to disk and rename it to path
+pub(crate) fn commit_tmp_file(tmp_path: &Path, path: &Path) -> Result<(), FileError> {
+    std::fs::File::open(tmp_path)
+        .and_then(|file| file.sync_all())
+        .and_then(|()| std::fs::rename(tmp_path, path))
+        .map_err(|_e| FileError::FileWriteError)
 }
 
 /// write polars dataframe to parquet file
@@ -28,35 +80,216 @@ fn df_to_parquet(
     df: &mut DataFrame,
     filename: &Path,
     file_output: &FileOutput,
+    metadata: &[(String, String)],
 ) -> Result<(), FileError> {
-    let file = std::fs::File::create(filename).map_err(|_e| FileError::FileWriteError)?;
-    let result = ParquetWriter::new(file)
-        .with_statistics(file_output.parquet_statistics)
-        .with_compression(file_output.parquet_compression)
-        .with_row_group_size(file_output.row_group_size)
-        .finish(df);
-    match result {
-        Err(_e) => Err(FileError::FileWriteError),
-        _ => Ok(()),
+    let mut writer = ParquetStreamWriter::try_new(filename, &df.schema(), file_output)?;
+    writer.write(df)?;
+    writer.finish(metadata)
+}
+
+/// parquet writer that is given the rows of a file a dataframe at a time, writing a row group
+/// whenever enough rows are buffered
+///
+/// written with the arrow writer rather than polars' ParquetWriter, which cannot write key-value
+/// metadata
+pub(crate) struct ParquetStreamWriter {
+    writer: FileWriter<std::fs::File>,
+    schema: ArrowSchema,
+    options: WriteOptions,
+    encodings: Vec<Vec<Encoding>>,
+    row_group_size: usize,
+    buffer: Option<DataFrame>,
+}
+
+impl ParquetStreamWriter {
+    /// create parquet file at filename for rows of schema
+    pub(crate) fn try_new(
+        filename: &Path,
+        schema: &Schema,
+        file_output: &FileOutput,
+    ) -> Result<Self, FileError> {
+        let file = std::fs::File::create(filename).map_err(|_e| FileError::FileWriteError)?;
+        let schema = ArrowSchema::from(schema.to_arrow().fields);
+        let options = WriteOptions {
+            write_statistics: file_output.parquet_statistics,
+            compression: CompressionOptions::from(file_output.parquet_compression),
+            version: Version::V2,
+            data_pagesize_limit: None,
+        };
+        let encodings: Vec<Vec<Encoding>> = schema
+            .fields
+            .iter()
+            .map(|field| {
+                transverse(&field.data_type, |data_type| match data_type.to_physical_type() {
+                    PhysicalType::Dictionary(_) => Encoding::RleDictionary,
+                    _ => Encoding::Plain,
+                })
+            })
+            .collect();
+        let writer = FileWriter::try_new(file, schema.clone(), options)
+            .map_err(|_e| FileError::FileWriteError)?;
+
+        // same default row group size as polars
+        let row_group_size = file_output.row_group_size.unwrap_or(512 * 512).max(1);
+        Ok(Self { writer, schema, options, encodings, row_group_size, buffer: None })
+    }
+
+    /// append rows of df, writing every full row group
+    pub(crate) fn write(&mut self, df: &DataFrame) -> Result<(), FileError> {
+        let mut buffer = match self.buffer.take() {
+            Some(mut buffer) => {
+                buffer.vstack_mut(df).map_err(|_e| FileError::FileWriteError)?;
+                buffer
+            }
+            None => df.clone(),
+        };
+        while buffer.height() >= self.row_group_size {
+            let row_group = buffer.slice(0, self.row_group_size);
+            self.write_row_group(row_group)?;
+            buffer = buffer.slice(self.row_group_size as i64, buffer.height());
+        }
+        self.buffer = Some(buffer);
+        Ok(())
+    }
+
+    /// write remaining rows and the footer of file with metadata as its key-value metadata
+    pub(crate) fn finish(mut self, metadata: &[(String, String)]) -> Result<(), FileError> {
+        if let Some(buffer) = self.buffer.take() {
+            self.write_row_group(buffer)?;
+        }
+        let key_value_metadata = metadata
+            .iter()
+            .map(|(key, value)| KeyValue { key: key.clone(), value: Some(value.clone()) })
+            .collect::<Vec<_>>();
+        let key_value_metadata = (!key_value_metadata.is_empty()).then_some(key_value_metadata);
+        self.writer.end(key_value_metadata).map_err(|_e| FileError::FileWriteError)?;
+        Ok(())
+    }
+
+    fn write_row_group(&mut self, mut row_group: DataFrame) -> Result<(), FileError> {
+        if row_group.height() == 0 {
+            return Ok(())
+        }
+        row_group.as_single_chunk_par();
+        let chunks = row_group.iter_chunks().map(Ok);
+        let row_groups =
+            RowGroupIterator::try_new(chunks, &self.schema, self.options, self.encodings.clone())
+                .map_err(|_e| FileError::FileWriteError)?;
+        for row_group in row_groups {
+            let row_group = row_group.map_err(|_e| FileError::FileWriteError)?;
+            self.writer.write(row_group).map_err(|_e| FileError::FileWriteError)?;
+        }
+        Ok(())
     }
 }
 
+/// copy of dataframe with its binary columns encoded as hex strings, which text formats can hold
+///
+/// columns that are already hex strings, such as those of `--hex`, are left unchanged
+pub(crate) fn hex_encode_binary_columns(
+    df: &DataFrame,
+    prefix: bool,
+) -> Result<DataFrame, FileError> {
+    let columns = df
+        .get_columns()
+        .iter()
+        .map(|series| match series.dtype() {
+            DataType::Binary => {
+                let binary = series.binary().map_err(|_e| FileError::FileWriteError)?;
+                let mut hex: Utf8Chunked = binary
+                    .into_iter()
+                    .map(|value| {
+                        value.map(|value| match prefix {
+                            true => prefix_hex::encode(value),
+                            false => ethers::utils::hex::encode(value),
+                        })
+                    })
+                    .collect();
+                hex.rename(series.name());
+                Ok(hex.into_series())
+            }
+            _ => Ok(series.clone()),
+        })
+        .collect::<Result<Vec<Series>, FileError>>()?;
+    DataFrame::new(columns).map_err(|_e| FileError::FileWriteError)
+}
+
 /// write polars dataframe to csv file
-fn df_to_csv(df: &mut DataFrame, filename: &Path) -> Result<(), FileError> {
-    let file = std::fs::File::create(filename).map_err(|_e| FileError::FileWriteError)?;
-    let result = CsvWriter::new(file).finish(df);
-    match result {
-        Err(_e) => Err(FileError::FileWriteError),
-        _ => Ok(()),
-    }
+fn df_to_csv(
+    df: &mut DataFrame,
+    filename: &Path,
+    compression: &TextCompression,
+) -> Result<(), FileError> {
+    write_text_file(filename, compression, |writer| CsvWriter::new(writer).finish(df))
 }
 
 /// write polars dataframe to json file
-fn df_to_json(df: &mut DataFrame, filename: &Path) -> Result<(), FileError> {
+fn df_to_json(
+    df: &mut DataFrame,
+    filename: &Path,
+    compression: &TextCompression,
+) -> Result<(), FileError> {
+    write_text_file(filename, compression, |writer| {
+        JsonWriter::new(writer).with_json_format(JsonFormat::Json).finish(df)
+    })
+}
+
+/// write text file, wrapping the file in an encoder if compression is enabled
+fn write_text_file<F>(
+    filename: &Path,
+    compression: &TextCompression,
+    write: F,
+) -> Result<(), FileError>
+where
+    F: FnOnce(&mut dyn Write) -> PolarsResult<()>,
+{
     let file = std::fs::File::create(filename).map_err(|_e| FileError::FileWriteError)?;
-    let result = JsonWriter::new(file).with_json_format(JsonFormat::Json).finish(df);
-    match result {
-        Err(_e) => Err(FileError::FileWriteError),
-        _ => Ok(()),
+    match compression {
+        TextCompression::Uncompressed => {
+            let mut file = file;
+            write(&mut file).map_err(|_e| FileError::FileWriteError)
+        }
+        TextCompression::Gzip(level) => {
+            let mut encoder = flate2::write::GzEncoder::new(file, flate2::Compression::new(*level));
+            write(&mut encoder).map_err(|_e| FileError::FileWriteError)?;
+            encoder.finish().map(|_| ()).map_err(|_e| FileError::FileWriteError)
+        }
+        TextCompression::Zstd(level) => {
+            let mut encoder =
+                zstd::Encoder::new(file, *level).map_err(|_e| FileError::FileWriteError)?;
+            write(&mut encoder).map_err(|_e| FileError::FileWriteError)?;
+            encoder.finish().map(|_| ()).map_err(|_e| FileError::FileWriteError)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hex_encode_binary_columns() {
+        let df = df!(
+            "address" => [Some(vec![0u8, 171]), None],
+            "hex" => ["0x01", "0x02"],
+        )
+        .unwrap();
+        let prefixed = hex_encode_binary_columns(&df, true).unwrap();
+        let address: Vec<Option<&str>> =
+            prefixed.column("address").unwrap().utf8().unwrap().into_iter().collect();
+        assert_eq!(address, vec![Some("0x00ab"), None]);
+        assert_eq!(prefixed.column("hex").unwrap(), df.column("hex").unwrap());
+        let bare = hex_encode_binary_columns(&df, false).unwrap();
+        assert_eq!(bare.column("address").unwrap().utf8().unwrap().get(0), Some("00ab"));
+    }
+
+    #[test]
+    fn test_tmp_path() {
+        let path = Path::new("data/ethereum__blocks__00000000_to_00000999.parquet");
+        let tmp = tmp_path(path);
+        assert_eq!(tmp, PathBuf::from("data/ethereum__blocks__00000000_to_00000999.parquet.tmp"));
+        assert!(is_tmp_file(tmp.file_name().unwrap().to_str().unwrap()));
+        assert!(is_tmp_file("ethereum__blocks__00000000_to_00000999.parquet._tmp"));
+        assert!(!is_tmp_file("ethereum__blocks__00000000_to_00000999.parquet"));
     }
 }
@@ -7,4 +7,4 @@ mod creation;
 
 pub(crate) use export::*;
 pub use read::*;
-pub(crate) use sort::SortableDataFrame;
+pub(crate) use sort::{sort_by_columns, SortableDataFrame};
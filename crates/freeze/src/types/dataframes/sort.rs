@@ -16,3 +16,17 @@ impl SortableDataFrame for Result<DataFrame, CollectError> {
         }
     }
 }
+
+/// sort rows of df by sort_columns, keeping the order of rows with equal keys
+///
+/// df is left as it is if it lacks any of the sort columns, such as after a sql query that does
+/// not select them
+pub(crate) fn sort_by_columns(
+    df: DataFrame,
+    sort_columns: &[String],
+) -> Result<DataFrame, CollectError> {
+    if sort_columns.is_empty() || !sort_columns.iter().all(|column| df.column(column).is_ok()) {
+        return Ok(df)
+    }
+    Ok(df.sort(sort_columns, false, true)?)
+}
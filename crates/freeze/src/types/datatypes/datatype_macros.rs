@@ -9,20 +9,25 @@ macro_rules! define_datatypes {
                 /// $datatype
                 $datatype,
             )*
+            /// dataset registered at runtime via `register_dataset()`
+            Plugin(&'static str),
         }
 
         impl Datatype {
             /// return Vec of all datatypes
             pub fn all() -> Vec<Self> {
-                vec![
+                let mut datatypes = vec![
                     $(Datatype::$datatype,)*
-                ]
+                ];
+                datatypes.extend(plugin_dataset_names().into_iter().map(Datatype::Plugin));
+                datatypes
             }
 
             /// name of datatype
             pub fn name(&self) -> String {
                 let name = match *self {
                     $(Datatype::$datatype => stringify!($datatype),)*
+                    Datatype::Plugin(name) => return name.to_string(),
                 };
                 format!("{}", heck::AsSnakeCase(name))
             }
@@ -31,6 +36,7 @@ macro_rules! define_datatypes {
             pub fn aliases(&self) -> Vec<&'static str> {
                 match *self {
                     $(Datatype::$datatype => $datatype::aliases(),)*
+                    Datatype::Plugin(name) => get_plugin_dataset(name).map(|plugin| plugin.aliases).unwrap_or_default(),
                 }
             }
 
@@ -38,6 +44,15 @@ macro_rules! define_datatypes {
             pub fn default_sort(&self) -> Vec<String> {
                 match *self {
                     $(Datatype::$datatype => $datatype::default_sort(),)*
+                    Datatype::Plugin(name) => get_plugin_dataset(name).map(|plugin| plugin.default_sort).unwrap_or_default(),
+                }
+            }
+
+            /// columns that identify a row of datatype, if defined
+            pub fn dedup_keys(&self) -> Option<Vec<String>> {
+                match *self {
+                    $(Datatype::$datatype => $datatype::dedup_keys(),)*
+                    Datatype::Plugin(name) => get_plugin_dataset(name).and_then(|plugin| plugin.dedup_keys),
                 }
             }
 
@@ -45,6 +60,7 @@ macro_rules! define_datatypes {
             pub fn default_columns(&self) -> Vec<&'static str> {
                 match *self {
                     $(Datatype::$datatype => $datatype::base_default_columns(),)*
+                    Datatype::Plugin(name) => get_plugin_dataset(name).map(|plugin| plugin.default_columns).unwrap_or_default(),
                 }
             }
 
@@ -52,6 +68,7 @@ macro_rules! define_datatypes {
             pub fn default_blocks(&self) -> Option<String> {
                 match *self {
                     $(Datatype::$datatype => $datatype::base_default_blocks(),)*
+                    Datatype::Plugin(name) => get_plugin_dataset(name).map(|plugin| plugin.default_blocks).unwrap_or_default(),
                 }
             }
 
@@ -59,6 +76,7 @@ macro_rules! define_datatypes {
             pub fn column_types(&self) -> HashMap<&'static str, ColumnType> {
                 match *self {
                     $(Datatype::$datatype => $datatype::column_types(),)*
+                    Datatype::Plugin(name) => get_plugin_dataset(name).map(|plugin| plugin.column_types).unwrap_or_default(),
                 }
             }
 
@@ -66,6 +84,15 @@ macro_rules! define_datatypes {
             pub fn use_block_ranges(&self) -> bool {
                 match *self {
                     $(Datatype::$datatype => $datatype::use_block_ranges(),)*
+                    Datatype::Plugin(name) => get_plugin_dataset(name).map(|plugin| plugin.use_block_ranges).unwrap_or(false),
+                }
+            }
+
+            /// cost of collecting a chunk of datatype, relative to cheap datatypes
+            pub fn relative_cost(&self) -> u64 {
+                match *self {
+                    $(Datatype::$datatype => $datatype::relative_cost(),)*
+                    Datatype::Plugin(name) => get_plugin_dataset(name).map(|plugin| plugin.relative_cost).unwrap_or(1),
                 }
             }
 
@@ -73,6 +100,7 @@ macro_rules! define_datatypes {
             pub fn arg_aliases(&self) -> HashMap<String, String> {
                 match *self {
                     $(Datatype::$datatype => $datatype::base_arg_aliases(),)*
+                    Datatype::Plugin(name) => get_plugin_dataset(name).map(|plugin| plugin.arg_aliases).unwrap_or_default(),
                 }
             }
 
@@ -80,6 +108,7 @@ macro_rules! define_datatypes {
             pub fn required_parameters(&self) -> Vec<Dim> {
                 match *self {
                     $(Datatype::$datatype => $datatype::required_parameters(),)*
+                    Datatype::Plugin(name) => get_plugin_dataset(name).map(|plugin| plugin.required_parameters).unwrap_or_default(),
                 }
             }
 
@@ -87,10 +116,60 @@ macro_rules! define_datatypes {
             pub fn optional_parameters(&self) -> Vec<Dim> {
                 match *self {
                     $(Datatype::$datatype => $datatype::optional_parameters(),)*
+                    Datatype::Plugin(name) => get_plugin_dataset(name).map(|plugin| plugin.optional_parameters).unwrap_or_default(),
                 }
             }
         }
 
+        type CollectTask<'a> =
+            futures::future::BoxFuture<'a, Result<HashMap<Datatype, DataFrame>, CollectError>>;
+
+        /// collect scalar datatype by block
+        fn collect_scalar_by_block<'a>(
+            datatype: Datatype,
+            partition: Partition,
+            source: Arc<Source>,
+            schemas: &'a HashMap<Datatype, Table>,
+        ) -> CollectTask<'a> {
+            let inner_request_size = if datatype.use_block_ranges() {
+                Some(source.inner_request_size)
+            } else {
+                None
+            };
+            match datatype {
+            $(
+                Datatype::$datatype => $datatype::collect_by_block(partition, source, schemas, inner_request_size),
+            )*
+                Datatype::Plugin(name) => match get_plugin_dataset(name) {
+                    Some(plugin) => (plugin.collect_by_block)(partition, source, schemas.clone(), inner_request_size),
+                    None => Box::pin(async move { Err(CollectError::CollectError(format!("dataset not registered: {}", name))) }),
+                },
+            }
+        }
+
+        /// collect scalar datatype by transaction
+        fn collect_scalar_by_transaction<'a>(
+            datatype: Datatype,
+            partition: Partition,
+            source: Arc<Source>,
+            schemas: &'a HashMap<Datatype, Table>,
+        ) -> CollectTask<'a> {
+            let inner_request_size = if datatype.use_block_ranges() {
+                Some(source.inner_request_size)
+            } else {
+                None
+            };
+            match datatype {
+            $(
+                Datatype::$datatype => $datatype::collect_by_transaction(partition, source, schemas, inner_request_size),
+            )*
+                Datatype::Plugin(name) => match get_plugin_dataset(name) {
+                    Some(plugin) => (plugin.collect_by_transaction)(partition, source, schemas.clone(), inner_request_size),
+                    None => Box::pin(async move { Err(CollectError::CollectError(format!("dataset not registered: {}", name))) }),
+                },
+            }
+        }
+
         /// collect by block
         pub async fn collect_by_block(
             datatype: MetaDatatype,
@@ -98,29 +177,35 @@ macro_rules! define_datatypes {
             source: Arc<Source>,
             schemas: HashMap<Datatype, Table>,
         ) -> Result<HashMap<Datatype, DataFrame>, CollectError> {
-            let task = match datatype {
+            let task: CollectTask<'_> = match datatype {
                 MetaDatatype::Scalar(datatype) => {
-                    let inner_request_size = if datatype.use_block_ranges() {
-                        Some(source.inner_request_size)
-                    } else {
-                        None
-                    };
-                    match datatype {
-                    $(
-                        Datatype::$datatype => $datatype::collect_by_block(partition, source, &schemas, inner_request_size),
-                    )*
-                    }
+                    collect_scalar_by_block(datatype, partition, source, &schemas)
                 },
                 MetaDatatype::Multi(datatype) => match datatype {
+                    MultiDatatype::AccountStates => {
+                        AccountStates::collect_by_block(partition, source, &schemas, None)
+                    }
                     MultiDatatype::BlocksAndTransactions => {
                         BlocksAndTransactions::collect_by_block(partition, source, &schemas, None)
                     }
                     MultiDatatype::CallTraceDerivatives => {
                         CallTraceDerivatives::collect_by_block(partition, source, &schemas, None)
                     }
+                    MultiDatatype::ReceiptsAndLogs => {
+                        ReceiptsAndLogs::collect_by_block(partition, source, &schemas, None)
+                    }
                     MultiDatatype::StateDiffs => {
                         StateDiffs::collect_by_block(partition, source, &schemas, None)
                     },
+                    MultiDatatype::Custom(_) => {
+                        let tasks = datatype
+                            .datatypes()
+                            .into_iter()
+                            .filter(|datatype| schemas.contains_key(datatype))
+                            .map(|datatype| collect_scalar_by_block(datatype, partition.clone(), source.clone(), &schemas))
+                            .collect();
+                        Box::pin(collect_together(tasks))
+                    }
                 },
             };
             task.await
@@ -133,31 +218,37 @@ macro_rules! define_datatypes {
             source: Arc<Source>,
             schemas: HashMap<Datatype, Table>,
         ) -> Result<HashMap<Datatype, DataFrame>, CollectError> {
-            let task = match datatype {
+            let task: CollectTask<'_> = match datatype {
                 MetaDatatype::Scalar(datatype) => {
-                    let inner_request_size = if datatype.use_block_ranges() {
-                        Some(source.inner_request_size)
-                    } else {
-                        None
-                    };
-                    match datatype {
-                    $(
-                        Datatype::$datatype => $datatype::collect_by_transaction(partition, source, &schemas, inner_request_size),
-                    )*
-                    }
+                    collect_scalar_by_transaction(datatype, partition, source, &schemas)
                 },
                 MetaDatatype::Multi(datatype) => {
                     let inner_request_size = None;
                     match datatype {
+                        MultiDatatype::AccountStates => {
+                            AccountStates::collect_by_transaction(partition, source, &schemas, inner_request_size)
+                        }
                         MultiDatatype::BlocksAndTransactions => {
                             BlocksAndTransactions::collect_by_transaction(partition, source, &schemas, inner_request_size)
                         }
                         MultiDatatype::CallTraceDerivatives => {
                             CallTraceDerivatives::collect_by_transaction(partition, source, &schemas, None)
                         }
+                        MultiDatatype::ReceiptsAndLogs => {
+                            ReceiptsAndLogs::collect_by_transaction(partition, source, &schemas, inner_request_size)
+                        }
                         MultiDatatype::StateDiffs => {
                             StateDiffs::collect_by_transaction(partition, source, &schemas, inner_request_size)
                         }
+                        MultiDatatype::Custom(_) => {
+                            let tasks = datatype
+                                .datatypes()
+                                .into_iter()
+                                .filter(|datatype| schemas.contains_key(datatype))
+                                .map(|datatype| collect_scalar_by_transaction(datatype, partition.clone(), source.clone(), &schemas))
+                                .collect();
+                            Box::pin(collect_together(tasks))
+                        }
                     }
                 },
             };
@@ -1,4 +1,5 @@
 use super::{multi::MultiDatatype, scalar::Datatype};
+use crate::Partition;
 
 /// datatype representing either a Datatype or MultiDatatype
 #[derive(Clone, Debug, serde::Serialize)]
@@ -21,14 +22,18 @@ impl MetaDatatype {
 
 /// cluster datatypes into MultiDatatype / ScalarDatatype groups
 pub fn cluster_datatypes(dts: Vec<Datatype>) -> Vec<MetaDatatype> {
-    // use MultiDatatypes that have at least 2 ScalarDatatypes in datatype list
-    let mdts: Vec<MultiDatatype> = MultiDatatype::variants()
-        .iter()
-        .filter(|mdt| mdt.datatypes().iter().filter(|x| dts.contains(x)).count() >= 2)
-        .cloned()
-        .collect();
-    let mdt_dts: Vec<Datatype> =
-        mdts.iter().flat_map(|mdt| mdt.datatypes()).filter(|dt| dts.contains(dt)).collect();
+    // use MultiDatatypes that have at least 2 ScalarDatatypes in datatype list, and none in an
+    // earlier MultiDatatype
+    let mut mdts: Vec<MultiDatatype> = Vec::new();
+    let mut mdt_dts: Vec<Datatype> = Vec::new();
+    for mdt in MultiDatatype::variants() {
+        let members: Vec<Datatype> =
+            mdt.datatypes().into_iter().filter(|dt| dts.contains(dt)).collect();
+        if members.len() >= 2 && !members.iter().any(|dt| mdt_dts.contains(dt)) {
+            mdts.push(mdt);
+            mdt_dts.extend(members);
+        }
+    }
     let other_dts: Vec<Datatype> = dts.iter().filter(|dt| !mdt_dts.contains(dt)).copied().collect();
 
     [
@@ -37,3 +42,67 @@ pub fn cluster_datatypes(dts: Vec<Datatype>) -> Vec<MetaDatatype> {
     ]
     .concat()
 }
+
+/// cluster datatypes of partitions into MultiDatatype / ScalarDatatype groups
+///
+/// logs filtered by contract or topic are requested with eth_getLogs rather than taken from the
+/// receipts of every block, so they are not clustered with receipts
+pub fn cluster_partition_datatypes(
+    dts: Vec<Datatype>,
+    partitions: &[Partition],
+) -> Vec<MetaDatatype> {
+    let filters_logs = partitions.iter().any(|partition| {
+        partition.contracts.is_some() ||
+            partition.topic0s.is_some() ||
+            partition.topic1s.is_some() ||
+            partition.topic2s.is_some() ||
+            partition.topic3s.is_some()
+    });
+    let clustered = cluster_datatypes(dts.clone());
+    if !filters_logs {
+        return clustered
+    }
+    clustered
+        .into_iter()
+        .flat_map(|mdt| match mdt {
+            MetaDatatype::Multi(MultiDatatype::ReceiptsAndLogs) => MultiDatatype::ReceiptsAndLogs
+                .datatypes()
+                .into_iter()
+                .filter(|dt| dts.contains(dt))
+                .map(MetaDatatype::Scalar)
+                .collect(),
+            mdt => vec![mdt],
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::register_multi_datatype;
+
+    #[test]
+    fn test_cluster_registered_datatypes() {
+        let group = register_multi_datatype(
+            "token_metadata",
+            vec![Datatype::Erc20Metadata, Datatype::Blocks],
+        )
+        .unwrap();
+        assert!(register_multi_datatype("other", vec![Datatype::Blocks, Datatype::Logs]).is_err());
+        assert!(register_multi_datatype("other", vec![Datatype::Logs]).is_err());
+
+        // registered groups take precedence over multi datatypes sharing their datatypes
+        let dts = vec![Datatype::Blocks, Datatype::Transactions, Datatype::Erc20Metadata];
+        let clustered: Vec<Vec<Datatype>> =
+            cluster_datatypes(dts).iter().map(|mdt| mdt.datatypes()).collect();
+        assert_eq!(clustered, vec![group.datatypes(), vec![Datatype::Transactions]],);
+
+        // groups with fewer than two of their datatypes collected are not used
+        let dts = vec![Datatype::Blocks, Datatype::Transactions];
+        let clustered = cluster_datatypes(dts);
+        assert!(matches!(
+            clustered[..],
+            [MetaDatatype::Multi(MultiDatatype::BlocksAndTransactions)]
+        ));
+    }
+}
@@ -2,8 +2,11 @@ mod datatype_macros;
 /// meta datatypes
 pub mod meta;
 mod multi;
+/// datasets registered at runtime
+pub mod plugins;
 mod scalar;
 
 pub use meta::*;
 pub use multi::*;
+pub use plugins::*;
 pub use scalar::*;
@@ -1,23 +1,39 @@
-use crate::types::Datatype;
+use crate::{types::Datatype, CollectError, ParseError};
+use futures::future::BoxFuture;
+use indexmap::IndexMap;
+use polars::prelude::*;
+use std::{collections::HashMap, sync::RwLock};
 
 /// enum of possible sets of datatypes that cryo can collect
 /// used when multiple datatypes are collected together
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Serialize)]
 pub enum MultiDatatype {
+    /// balances, codes, and nonces
+    AccountStates,
+
     /// blocks and transactions
     BlocksAndTransactions,
 
     /// call trace derivatives
     CallTraceDerivatives,
 
+    /// receipts, logs, gas usage, and transaction status
+    ReceiptsAndLogs,
+
     /// balance diffs, code diffs, nonce diffs, and storage diffs
     StateDiffs,
+
+    /// group of datatypes registered at runtime, such as the groups of the cli config file
+    Custom(&'static str),
 }
 
 impl MultiDatatype {
     /// individual datatypes
     pub fn datatypes(&self) -> Vec<Datatype> {
         match &self {
+            MultiDatatype::AccountStates => {
+                vec![Datatype::Balances, Datatype::Codes, Datatype::Nonces]
+            }
             MultiDatatype::BlocksAndTransactions => vec![Datatype::Blocks, Datatype::Transactions],
             MultiDatatype::StateDiffs => vec![
                 Datatype::BalanceDiffs,
@@ -28,15 +44,93 @@ impl MultiDatatype {
             MultiDatatype::CallTraceDerivatives => {
                 vec![Datatype::Contracts, Datatype::NativeTransfers, Datatype::Traces]
             }
+            MultiDatatype::ReceiptsAndLogs => {
+                vec![
+                    Datatype::Receipts,
+                    Datatype::Logs,
+                    Datatype::GasUsage,
+                    Datatype::TransactionStatus,
+                ]
+            }
+            MultiDatatype::Custom(name) => get_multi_datatype(name).unwrap_or_default(),
         }
     }
 
-    /// return all variants of multi datatype
+    /// return all variants of multi datatype, starting with registered groups
     pub fn variants() -> Vec<MultiDatatype> {
-        vec![
+        let mut variants: Vec<MultiDatatype> =
+            multi_datatype_names().into_iter().map(MultiDatatype::Custom).collect();
+        variants.extend([
+            MultiDatatype::AccountStates,
             MultiDatatype::BlocksAndTransactions,
             MultiDatatype::CallTraceDerivatives,
+            MultiDatatype::ReceiptsAndLogs,
             MultiDatatype::StateDiffs,
-        ]
+        ]);
+        variants
+    }
+}
+
+lazy_static::lazy_static! {
+    static ref CUSTOM_MULTI_DATATYPES: RwLock<IndexMap<&'static str, Vec<Datatype>>> =
+        RwLock::new(IndexMap::new());
+}
+
+/// register a group of datatypes that are collected together when at least two of them are
+/// collected, sharing the responses of their requests
+///
+/// unlike the built-in multi datatypes, each datatype of a group is collected by its own dataset,
+/// with the datasets of a chunk collected at the same time so that identical requests share a
+/// response. groups take precedence over built-in multi datatypes that have datatypes in common,
+/// and registering a group again replaces its datatypes
+pub fn register_multi_datatype(
+    name: &str,
+    datatypes: Vec<Datatype>,
+) -> Result<MultiDatatype, ParseError> {
+    let invalid = |message: String| {
+        ParseError::ParseError(format!("invalid multi datatype {}: {}", name, message))
+    };
+    if datatypes.len() < 2 {
+        return Err(invalid("must have at least two datatypes".to_string()))
+    }
+    for (i, datatype) in datatypes.iter().enumerate() {
+        if datatypes[..i].contains(datatype) {
+            return Err(invalid(format!("{} is listed more than once", datatype.name())))
+        }
+    }
+    let mut groups = CUSTOM_MULTI_DATATYPES.write().map_err(|_| {
+        ParseError::ParseError("could not lock multi datatype registry".to_string())
+    })?;
+    for (other, other_datatypes) in groups.iter().filter(|(other, _)| **other != name) {
+        if let Some(datatype) = datatypes.iter().find(|dt| other_datatypes.contains(dt)) {
+            return Err(invalid(format!("{} is already in {}", datatype.name(), other)))
+        }
+    }
+    let name = match groups.get_key_value(name) {
+        Some((name, _)) => *name,
+        None => Box::leak(name.to_string().into_boxed_str()),
+    };
+    groups.insert(name, datatypes);
+    Ok(MultiDatatype::Custom(name))
+}
+
+/// collect the datatypes of a registered group at the same time, merging their dataframes
+pub(crate) async fn collect_together(
+    tasks: Vec<BoxFuture<'_, Result<HashMap<Datatype, DataFrame>, CollectError>>>,
+) -> Result<HashMap<Datatype, DataFrame>, CollectError> {
+    let dfs = futures::future::try_join_all(tasks).await?;
+    Ok(dfs.into_iter().flatten().collect())
+}
+
+/// get datatypes of registered group by name
+pub fn get_multi_datatype(name: &str) -> Option<Vec<Datatype>> {
+    CUSTOM_MULTI_DATATYPES.read().ok().and_then(|groups| groups.get(name).cloned())
+}
+
+/// get names of all registered groups
+pub fn multi_datatype_names() -> Vec<&'static str> {
+    match CUSTOM_MULTI_DATATYPES.read() {
+        Ok(groups) => groups.keys().copied().collect(),
+        Err(_) => Vec::new(),
     }
 }
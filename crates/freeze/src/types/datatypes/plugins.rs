@@ -0,0 +1,240 @@
+use crate::{
+    types::columns::ColumnData, CollectByBlock, CollectByTransaction, CollectError, ColumnType,
+    Datatype, Dim, ParseError, Partition, Source, Table,
+};
+use futures::future::BoxFuture;
+use indexmap::IndexMap;
+use polars::prelude::*;
+use std::{
+    collections::HashMap,
+    sync::{Arc, RwLock},
+};
+
+type CollectResult = Result<HashMap<Datatype, DataFrame>, CollectError>;
+
+/// function that collects a plugin dataset for a partition
+pub type PluginCollectFn = fn(
+    Partition,
+    Arc<Source>,
+    HashMap<Datatype, Table>,
+    Option<u64>,
+) -> BoxFuture<'static, CollectResult>;
+
+/// dataset registered at runtime by a downstream crate
+#[derive(Clone)]
+pub struct PluginDataset {
+    /// name of dataset
+    pub name: &'static str,
+    /// aliases of dataset
+    pub aliases: Vec<&'static str>,
+    /// default sort order of dataset
+    pub default_sort: Vec<String>,
+    /// columns that identify a row of dataset
+    pub dedup_keys: Option<Vec<String>>,
+    /// default columns of dataset
+    pub default_columns: Vec<&'static str>,
+    /// default blocks of dataset
+    pub default_blocks: Option<String>,
+    /// column types of dataset
+    pub column_types: HashMap<&'static str, ColumnType>,
+    /// whether to use block ranges instead of individual blocks
+    pub use_block_ranges: bool,
+    /// cost of collecting a chunk of dataset relative to cheap datasets
+    pub relative_cost: u64,
+    /// input arg aliases of dataset
+    pub arg_aliases: HashMap<String, String>,
+    /// required parameters of dataset
+    pub required_parameters: Vec<Dim>,
+    /// optional parameters of dataset
+    pub optional_parameters: Vec<Dim>,
+    /// collect dataset by block
+    pub collect_by_block: PluginCollectFn,
+    /// collect dataset by transaction
+    pub collect_by_transaction: PluginCollectFn,
+}
+
+lazy_static::lazy_static! {
+    static ref PLUGIN_DATASETS: RwLock<IndexMap<&'static str, PluginDataset>> =
+        RwLock::new(IndexMap::new());
+}
+
+/// register an out-of-tree dataset so that it can be collected like a built-in dataset
+///
+/// the dataset's columns struct should be annotated with
+/// `#[cryo_to_df::to_df(Datatype::Plugin("<name>"))]`, where `<name>` matches `Dataset::name()`.
+/// after registration, `Datatype::from_str("<name>")` resolves to the returned datatype, so a
+/// custom binary can register its datasets and then call `cryo_cli::run()`
+pub fn register_dataset<T>() -> Result<Datatype, ParseError>
+where
+    T: ColumnData + CollectByBlock + CollectByTransaction,
+{
+    let name = T::name();
+    let mut names_in_use: Vec<String> = Datatype::all()
+        .iter()
+        .filter(|datatype| !matches!(datatype, Datatype::Plugin(_)))
+        .flat_map(|datatype| {
+            let mut names = vec![datatype.name()];
+            names.extend(datatype.aliases().iter().map(|alias| alias.to_string()));
+            names
+        })
+        .collect();
+
+    let plugin = PluginDataset {
+        name,
+        aliases: T::aliases(),
+        default_sort: T::default_sort(),
+        dedup_keys: T::dedup_keys(),
+        default_columns: T::base_default_columns(),
+        default_blocks: T::base_default_blocks(),
+        column_types: T::column_types(),
+        use_block_ranges: T::use_block_ranges(),
+        relative_cost: T::relative_cost(),
+        arg_aliases: T::base_arg_aliases(),
+        required_parameters: T::required_parameters(),
+        optional_parameters: T::optional_parameters(),
+        collect_by_block: |partition, source, schemas, inner_request_size| {
+            Box::pin(async move {
+                <T as CollectByBlock>::collect_by_block(
+                    partition,
+                    source,
+                    &schemas,
+                    inner_request_size,
+                )
+                .await
+            })
+        },
+        collect_by_transaction: |partition, source, schemas, inner_request_size| {
+            Box::pin(async move {
+                <T as CollectByTransaction>::collect_by_transaction(
+                    partition,
+                    source,
+                    &schemas,
+                    inner_request_size,
+                )
+                .await
+            })
+        },
+    };
+
+    // names of registered plugins are checked while holding the lock that the plugin is
+    // registered under, so that concurrent registrations cannot share a name
+    let mut plugins = PLUGIN_DATASETS
+        .write()
+        .map_err(|_| ParseError::ParseError("could not lock dataset registry".to_string()))?;
+    names_in_use.extend(plugins.values().flat_map(|plugin| {
+        std::iter::once(plugin.name).chain(plugin.aliases.iter().copied()).map(String::from)
+    }));
+    for label in std::iter::once(name).chain(T::aliases()) {
+        if names_in_use.contains(&label.to_string()) {
+            return Err(ParseError::ParseError(format!("dataset name already in use: {}", label)))
+        }
+    }
+    plugins.insert(name, plugin);
+    Ok(Datatype::Plugin(name))
+}
+
+/// get registered plugin dataset by name
+pub fn get_plugin_dataset(name: &str) -> Option<PluginDataset> {
+    PLUGIN_DATASETS.read().ok().and_then(|plugins| plugins.get(name).cloned())
+}
+
+/// get names of all registered plugin datasets
+pub fn plugin_dataset_names() -> Vec<&'static str> {
+    match PLUGIN_DATASETS.read() {
+        Ok(plugins) => plugins.keys().copied().collect(),
+        Err(_) => Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::*;
+    use std::str::FromStr;
+
+    type Result<T> = ::core::result::Result<T, CollectError>;
+
+    #[cryo_to_df::to_df(Datatype::Plugin("test_plugin"))]
+    #[derive(Default)]
+    struct TestPlugin {
+        n_rows: usize,
+        block_number: Vec<u32>,
+        chain_id: Vec<u64>,
+    }
+
+    impl Dataset for TestPlugin {
+        fn name() -> &'static str {
+            "test_plugin"
+        }
+
+        fn aliases() -> Vec<&'static str> {
+            vec!["test_plugin_alias"]
+        }
+
+        fn default_sort() -> Vec<String> {
+            vec!["block_number".to_string()]
+        }
+
+        fn relative_cost() -> u64 {
+            4
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl CollectByBlock for TestPlugin {
+        type Response = ();
+    }
+
+    #[async_trait::async_trait]
+    impl CollectByTransaction for TestPlugin {
+        type Response = ();
+    }
+
+    #[cryo_to_df::to_df(Datatype::Plugin("test_plugin_other"))]
+    #[derive(Default)]
+    struct TestPluginOther {
+        n_rows: usize,
+        block_number: Vec<u32>,
+        chain_id: Vec<u64>,
+    }
+
+    impl Dataset for TestPluginOther {
+        fn name() -> &'static str {
+            "test_plugin_other"
+        }
+
+        fn aliases() -> Vec<&'static str> {
+            vec!["test_plugin_alias"]
+        }
+
+        fn default_sort() -> Vec<String> {
+            vec!["block_number".to_string()]
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl CollectByBlock for TestPluginOther {
+        type Response = ();
+    }
+
+    #[async_trait::async_trait]
+    impl CollectByTransaction for TestPluginOther {
+        type Response = ();
+    }
+
+    #[test]
+    fn test_register_dataset() {
+        let datatype = register_dataset::<TestPlugin>().unwrap();
+        assert_eq!(datatype, Datatype::Plugin("test_plugin"));
+        assert_eq!(Datatype::from_str("test_plugin").unwrap(), datatype);
+        assert_eq!(Datatype::from_str("test_plugin_alias").unwrap(), datatype);
+        assert_eq!(datatype.default_sort(), vec!["block_number".to_string()]);
+        assert_eq!(datatype.relative_cost(), 4);
+
+        // names and aliases of registered plugins cannot be registered again
+        assert!(register_dataset::<TestPlugin>().is_err());
+        assert!(register_dataset::<TestPluginOther>().is_err());
+        assert!(Datatype::from_str("test_plugin_other").is_err());
+        assert!(!plugin_dataset_names().contains(&"test_plugin_other"));
+    }
+}
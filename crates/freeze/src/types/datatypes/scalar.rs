@@ -3,28 +3,53 @@ use polars::prelude::*;
 use std::collections::HashMap;
 
 define_datatypes!(
+    AccessLists,
+    AccountProofs,
     BalanceDiffs,
     Balances,
+    BeaconAttestations,
+    BeaconDeposits,
+    BeaconProposerDuties,
+    BeaconValidatorBalances,
+    BlockFees,
     Blocks,
+    ChainlinkPrices,
     CodeDiffs,
     Codes,
     Contracts,
+    DexSwaps,
+    Erc20Allowances,
     Erc20Balances,
+    Erc20Holders,
     Erc20Metadata,
     Erc20Supplies,
     Erc20Transfers,
     Erc721Metadata,
+    Erc721Owners,
     Erc721Transfers,
     EthCalls,
+    GasUsage,
+    InternalEthFlows,
+    JavascriptTraces,
     Logs,
+    Mempool,
+    MinerPayments,
+    Multicall,
+    NftSales,
     NonceDiffs,
     Nonces,
+    Pools,
+    ProxyImplementations,
+    Receipts,
+    RelayPayloads,
     StorageDiffs,
     Storages,
     Traces,
     TraceCalls,
+    TraceErrors,
     Transactions,
     TransactionAddresses,
+    TransactionStatus,
     VmTraces,
     NativeTransfers,
 );
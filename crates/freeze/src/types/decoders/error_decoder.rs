@@ -0,0 +1,178 @@
+use crate::ParseError;
+use ethers::prelude::I256;
+use ethers_core::abi::{self, ethabi::AbiError, Abi, HumanReadableParser, ParamType, Token};
+
+/// selector of `Error(string)`, the revert reason of `require` and `revert` with a message
+pub const ERROR_STRING_SELECTOR: [u8; 4] = [0x08, 0xc3, 0x79, 0xa0];
+
+/// selector of `Panic(uint256)`, raised by failed asserts and checked arithmetic
+pub const PANIC_SELECTOR: [u8; 4] = [0x4e, 0x48, 0x7b, 0x71];
+
+/// container for decoding the custom errors of contracts
+#[derive(Clone, Debug, PartialEq)]
+pub struct ErrorDecoder {
+    /// path of abi file or raw error signature, ex: error InsufficientBalance(uint256 available,
+    /// uint256 required)
+    pub raw: String,
+    /// custom errors whose revert data is decoded
+    pub errors: Vec<AbiError>,
+}
+
+impl ErrorDecoder {
+    /// create a new ErrorDecoder from a json abi file or an error signature
+    ///
+    /// abi files can either be a list of abi entries or an artifact with an `abi` field
+    pub fn new(raw: String) -> Result<Self, ParseError> {
+        let errors = match std::path::Path::new(&raw).is_file() {
+            true => parse_abi_file(&raw)?,
+            false => {
+                let error = HumanReadableParser::parse_error(raw.as_str()).map_err(|e| {
+                    ParseError::ParseError(format!(
+                        "incorrectly formatted error {} (expect something like error \
+                         InsufficientBalance(uint256 available, uint256 required)) err: {}",
+                        raw, e
+                    ))
+                })?;
+                vec![error]
+            }
+        };
+        if errors.is_empty() {
+            return Err(ParseError::ParseError(format!("no errors found in {}", raw)))
+        }
+        Ok(Self { raw, errors })
+    }
+
+    /// decode revert data whose selector matches one of the custom errors, as (signature, reason)
+    pub fn decode(&self, data: &[u8]) -> Option<(String, String)> {
+        let selector = data.get(..4)?;
+        self.errors.iter().find_map(|error| {
+            if error.signature()[..4] != *selector {
+                return None
+            }
+            let tokens = error.decode(&data[4..]).ok()?;
+            let args: Vec<String> = tokens.iter().map(format_token).collect();
+            Some((signature(error), format!("{}({})", error.name, args.join(", "))))
+        })
+    }
+}
+
+/// decode the revert data of a failed call into (signature, reason)
+///
+/// `Error(string)` gives its message and `Panic(uint256)` a description of its code, while
+/// other errors are decoded by decoder if it has an error of their selector
+pub fn decode_revert(
+    data: &[u8],
+    decoder: Option<&ErrorDecoder>,
+) -> (Option<String>, Option<String>) {
+    let selector = match data.get(..4) {
+        Some(selector) => selector,
+        None => return (None, None),
+    };
+    if selector == ERROR_STRING_SELECTOR {
+        let reason = match abi::decode(&[ParamType::String], &data[4..]).ok().as_deref() {
+            Some([Token::String(reason)]) => Some(reason.clone()),
+            _ => None,
+        };
+        (Some("Error(string)".to_string()), reason)
+    } else if selector == PANIC_SELECTOR {
+        let reason = match abi::decode(&[ParamType::Uint(256)], &data[4..]).ok().as_deref() {
+            Some([Token::Uint(code)]) => Some(panic_reason(code.low_u64())),
+            _ => None,
+        };
+        (Some("Panic(uint256)".to_string()), reason)
+    } else {
+        match decoder.and_then(|decoder| decoder.decode(data)) {
+            Some((signature, reason)) => (Some(signature), Some(reason)),
+            None => (None, None),
+        }
+    }
+}
+
+/// description of a solidity panic code
+fn panic_reason(code: u64) -> String {
+    let description = match code {
+        0x00 => "generic panic",
+        0x01 => "assertion failed",
+        0x11 => "arithmetic overflow or underflow",
+        0x12 => "division or modulo by zero",
+        0x21 => "invalid enum value",
+        0x22 => "invalid storage byte array encoding",
+        0x31 => "pop of empty array",
+        0x32 => "array index out of bounds",
+        0x41 => "out of memory",
+        0x51 => "call of zero-initialized function",
+        _ => "unknown panic",
+    };
+    format!("panic 0x{:02x}: {}", code, description)
+}
+
+/// text signature of error, ex: InsufficientBalance(uint256,uint256)
+fn signature(error: &AbiError) -> String {
+    let types: Vec<String> = error.inputs.iter().map(|input| input.kind.to_string()).collect();
+    format!("{}({})", error.name, types.join(","))
+}
+
+/// format decoded argument of an error as it could be written in solidity
+fn format_token(token: &Token) -> String {
+    match token {
+        Token::Address(address) => format!("{:?}", address),
+        Token::Bytes(bytes) | Token::FixedBytes(bytes) => prefix_hex::encode(bytes.clone()),
+        Token::Int(value) => I256::from_raw(*value).to_string(),
+        Token::Uint(value) => value.to_string(),
+        Token::Bool(value) => value.to_string(),
+        Token::String(value) => format!("{:?}", value),
+        Token::Array(tokens) | Token::FixedArray(tokens) => {
+            format!("[{}]", tokens.iter().map(format_token).collect::<Vec<_>>().join(", "))
+        }
+        Token::Tuple(tokens) => {
+            format!("({})", tokens.iter().map(format_token).collect::<Vec<_>>().join(", "))
+        }
+    }
+}
+
+fn parse_abi_file(path: &str) -> Result<Vec<AbiError>, ParseError> {
+    let contents = std::fs::read_to_string(path)
+        .map_err(|e| ParseError::ParseError(format!("could not read abi {}: {}", path, e)))?;
+    let invalid = |e: serde_json::Error| {
+        ParseError::ParseError(format!("could not parse abi {}: {}", path, e))
+    };
+    let value: serde_json::Value = serde_json::from_str(&contents).map_err(invalid)?;
+    let value = match value.get("abi") {
+        Some(abi) => abi.clone(),
+        None => value,
+    };
+    let abi: Abi = serde_json::from_value(value).map_err(invalid)?;
+    Ok(abi.errors().cloned().collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_revert() {
+        let mut data = ERROR_STRING_SELECTOR.to_vec();
+        data.extend(abi::encode(&[Token::String("insufficient funds".to_string())]));
+        let decoded = decode_revert(&data, None);
+        assert_eq!(decoded, (Some("Error(string)".into()), Some("insufficient funds".into())));
+
+        let mut data = PANIC_SELECTOR.to_vec();
+        data.extend(abi::encode(&[Token::Uint(0x11.into())]));
+        let reason = "panic 0x11: arithmetic overflow or underflow";
+        assert_eq!(decode_revert(&data, None).1, Some(reason.to_string()));
+
+        let decoder =
+            ErrorDecoder::new("error InsufficientBalance(uint256 available, int256 delta)".into())
+                .unwrap();
+        let mut data = decoder.errors[0].signature()[..4].to_vec();
+        data.extend(abi::encode(&[Token::Uint(10.into()), Token::Int(I256::from(-3).into_raw())]));
+        assert_eq!(decode_revert(&data, None), (None, None));
+        assert_eq!(
+            decode_revert(&data, Some(&decoder)),
+            (
+                Some("InsufficientBalance(uint256,int256)".into()),
+                Some("InsufficientBalance(10, -3)".into())
+            )
+        );
+    }
+}
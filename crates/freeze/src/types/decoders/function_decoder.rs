@@ -0,0 +1,233 @@
+use crate::{CollectError, ColumnEncoding, ParseError, ToVecChecksumHex, ToVecHex};
+use ethers_core::abi::{self, Abi, HumanReadableParser, ParamType, Token};
+use polars::prelude::*;
+
+/// container for calldata decoding context
+#[derive(Clone, Debug, PartialEq)]
+pub struct FunctionDecoder {
+    /// path of abi file or raw function signature, ex: function transfer(address to, uint256
+    /// amount)
+    pub raw: String,
+    /// functions whose calldata is decoded
+    pub functions: Vec<abi::Function>,
+}
+
+/// calldata decoded during collection, one entry per row
+///
+/// each entry holds the index of the matched function and its decoded arguments
+#[derive(Default)]
+pub struct DecodedCalls(pub Vec<Option<(usize, Vec<Token>)>>);
+
+impl FunctionDecoder {
+    /// create a new FunctionDecoder from a json abi file or a function signature
+    ///
+    /// abi files can either be a list of abi entries or an artifact with an `abi` field
+    pub fn new(raw: String) -> Result<Self, ParseError> {
+        let functions = match std::path::Path::new(&raw).is_file() {
+            true => parse_abi_file(&raw)?,
+            false => {
+                let function = HumanReadableParser::parse_function(raw.as_str()).map_err(|e| {
+                    ParseError::ParseError(format!(
+                        "incorrectly formatted function {} (expect something like function \
+                         transfer(address to, uint256 amount)) err: {}",
+                        raw, e
+                    ))
+                })?;
+                vec![function]
+            }
+        };
+        if functions.is_empty() {
+            return Err(ParseError::ParseError(format!("no functions found in {}", raw)))
+        }
+        Ok(Self { raw, functions })
+    }
+
+    /// names of decoded columns, ex: input_amount
+    ///
+    /// names are also prefixed by function name when decoding multiple functions, ex:
+    /// input_transfer_amount
+    pub fn column_names(&self) -> Vec<String> {
+        let mut names = Vec::new();
+        for function in self.functions.iter() {
+            for (i, input) in function.inputs.iter().enumerate() {
+                let name = match input.name.as_str() {
+                    "" => format!("arg{}", i),
+                    name => name.to_string(),
+                };
+                match self.functions.len() {
+                    1 => names.push(format!("input_{}", name)),
+                    _ => names.push(format!("input_{}_{}", function.name, name)),
+                }
+            }
+        }
+        names
+    }
+
+    /// decode the arguments of call data whose selector matches one of the functions
+    pub fn decode(&self, call_data: &[u8]) -> Option<(usize, Vec<Token>)> {
+        let selector = call_data.get(..4)?;
+        self.functions.iter().enumerate().find_map(|(index, function)| {
+            match function.short_signature() == selector {
+                true => function.decode_input(&call_data[4..]).ok().map(|tokens| (index, tokens)),
+                false => None,
+            }
+        })
+    }
+
+    /// convert decoded calls into one series per function argument
+    pub fn make_series(
+        &self,
+        decoded: DecodedCalls,
+        binary_type: &ColumnEncoding,
+    ) -> Result<Vec<Series>, CollectError> {
+        let names = self.column_names();
+        let mut series = Vec::with_capacity(names.len());
+        let mut name_index = 0;
+        for (function_index, function) in self.functions.iter().enumerate() {
+            for (input_index, input) in function.inputs.iter().enumerate() {
+                let tokens: Vec<Option<&Token>> = decoded
+                    .0
+                    .iter()
+                    .map(|row| match row {
+                        Some((index, tokens)) if *index == function_index => {
+                            tokens.get(input_index)
+                        }
+                        _ => None,
+                    })
+                    .collect();
+                series.push(token_series(&names[name_index], &input.kind, tokens, binary_type));
+                name_index += 1;
+            }
+        }
+        Ok(series)
+    }
+}
+
+fn parse_abi_file(path: &str) -> Result<Vec<abi::Function>, ParseError> {
+    let contents = std::fs::read_to_string(path)
+        .map_err(|e| ParseError::ParseError(format!("could not read abi {}: {}", path, e)))?;
+    let invalid = |e: serde_json::Error| {
+        ParseError::ParseError(format!("could not parse abi {}: {}", path, e))
+    };
+    let value: serde_json::Value = serde_json::from_str(&contents).map_err(invalid)?;
+    let value = match value.get("abi") {
+        Some(abi) => abi.clone(),
+        None => value,
+    };
+    let abi: Abi = serde_json::from_value(value).map_err(invalid)?;
+    Ok(abi.functions().cloned().collect())
+}
+
+/// convert tokens of a function argument into a series, typed by the argument's abi type
+fn token_series(
+    name: &str,
+    kind: &ParamType,
+    tokens: Vec<Option<&Token>>,
+    binary_type: &ColumnEncoding,
+) -> Series {
+    match kind {
+        ParamType::Address | ParamType::Bytes | ParamType::FixedBytes(_) => {
+            let values: Vec<Option<Vec<u8>>> = tokens
+                .into_iter()
+                .map(|token| match token {
+                    Some(Token::Address(address)) => Some(address.as_bytes().to_vec()),
+                    Some(Token::Bytes(bytes)) | Some(Token::FixedBytes(bytes)) => {
+                        Some(bytes.clone())
+                    }
+                    _ => None,
+                })
+                .collect();
+            match binary_type {
+                ColumnEncoding::ChecksumHex if kind == &ParamType::Address => {
+                    Series::new(name, values.to_vec_checksum_hex())
+                }
+                ColumnEncoding::Hex | ColumnEncoding::ChecksumHex => {
+                    Series::new(name, values.to_vec_hex())
+                }
+                ColumnEncoding::Binary => Series::new(name, values),
+            }
+        }
+        ParamType::Uint(size) if *size <= 64 => {
+            let values: Vec<Option<u64>> = tokens
+                .into_iter()
+                .map(|token| match token {
+                    Some(Token::Uint(value)) => Some(value.as_u64()),
+                    _ => None,
+                })
+                .collect();
+            Series::new(name, values)
+        }
+        ParamType::Int(size) if *size <= 64 => {
+            let values: Vec<Option<i64>> = tokens
+                .into_iter()
+                .map(|token| match token {
+                    Some(Token::Int(value)) => Some(value.low_u64() as i64),
+                    _ => None,
+                })
+                .collect();
+            Series::new(name, values)
+        }
+        ParamType::Int(_) => {
+            let values: Vec<Option<String>> = tokens
+                .into_iter()
+                .map(|token| match token {
+                    Some(Token::Int(value)) => {
+                        Some(ethers_core::types::I256::from_raw(*value).to_string())
+                    }
+                    _ => None,
+                })
+                .collect();
+            Series::new(name, values)
+        }
+        ParamType::Bool => {
+            let values: Vec<Option<bool>> = tokens
+                .into_iter()
+                .map(|token| match token {
+                    Some(Token::Bool(value)) => Some(*value),
+                    _ => None,
+                })
+                .collect();
+            Series::new(name, values)
+        }
+        // large integers are stored as decimal strings, and arrays and tuples in abi notation
+        _ => {
+            let values: Vec<Option<String>> = tokens
+                .into_iter()
+                .map(|token| match token {
+                    Some(Token::Uint(value)) => Some(value.to_string()),
+                    Some(Token::String(value)) => Some(value.clone()),
+                    Some(token) => Some(token.to_string()),
+                    None => None,
+                })
+                .collect();
+            Series::new(name, values)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_function_decoder() {
+        let raw = "function transfer(address to, uint256 amount)".to_string();
+        let decoder = FunctionDecoder::new(raw).unwrap();
+        assert_eq!(decoder.column_names(), vec!["input_to", "input_amount"]);
+
+        let call_data = prefix_hex::decode::<Vec<u8>>(
+            "0xa9059cbb000000000000000000000000d8da6bf26964af9d7eed9e03e53415d37aa96045\
+             00000000000000000000000000000000000000000000000000000000000f4240",
+        )
+        .unwrap();
+        let decoded = DecodedCalls(vec![decoder.decode(&call_data), decoder.decode(&[0x12])]);
+        let series = decoder.make_series(decoded, &ColumnEncoding::Hex).unwrap();
+        let df = DataFrame::new(series).unwrap();
+        assert_eq!(df.column("input_amount").unwrap().utf8().unwrap().get(0), Some("1000000"));
+        assert_eq!(df.column("input_amount").unwrap().utf8().unwrap().get(1), None);
+        assert_eq!(
+            df.column("input_to").unwrap().utf8().unwrap().get(0),
+            Some("0xd8da6bf26964af9d7eed9e03e53415d37aa96045")
+        );
+    }
+}
@@ -0,0 +1,122 @@
+use crate::ParseError;
+use std::{collections::HashMap, path::Path, sync::Arc};
+
+/// text signatures bundled with cryo
+const BUNDLED_SIGNATURES: &str = include_str!("function_signatures.txt");
+
+/// database mapping 4-byte function selectors to text signatures
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct FunctionSignatures {
+    signatures: Arc<HashMap<[u8; 4], String>>,
+}
+
+impl FunctionSignatures {
+    /// create database of the signatures bundled with cryo
+    pub fn bundled() -> Self {
+        Self::parse(BUNDLED_SIGNATURES).expect("bundled signatures are valid")
+    }
+
+    /// create database of the bundled signatures, extended by the signatures of a file
+    ///
+    /// the file has one text signature per line, optionally preceded by its hex selector, e.g.
+    /// `0xa9059cbb transfer(address,uint256)`. signatures from the file take precedence
+    pub fn from_file(path: &Path) -> Result<Self, ParseError> {
+        let contents = std::fs::read_to_string(path).map_err(|e| {
+            ParseError::ParseError(format!("could not read signature file {:?}: {}", path, e))
+        })?;
+        let mut signatures = Self::bundled().signatures.as_ref().clone();
+        signatures.extend(Self::parse(&contents)?.signatures.as_ref().clone());
+        Ok(Self { signatures: Arc::new(signatures) })
+    }
+
+    /// parse signatures from text, one per line, ignoring blank and `#` comment lines
+    ///
+    /// when a selector occurs more than once, the first signature is kept
+    pub fn parse(text: &str) -> Result<Self, ParseError> {
+        let mut signatures = HashMap::new();
+        for line in text.lines().map(|line| line.trim()) {
+            if line.is_empty() || line.starts_with('#') {
+                continue
+            }
+            let (selector, signature) = parse_line(line)?;
+            signatures.entry(selector).or_insert(signature);
+        }
+        Ok(Self { signatures: Arc::new(signatures) })
+    }
+
+    /// number of signatures in database
+    pub fn len(&self) -> usize {
+        self.signatures.len()
+    }
+
+    /// whether database contains no signatures
+    pub fn is_empty(&self) -> bool {
+        self.signatures.is_empty()
+    }
+
+    /// look up the text signature of the selector at the start of call data
+    pub fn lookup(&self, call_data: &[u8]) -> Option<&str> {
+        let selector: [u8; 4] = call_data.get(..4)?.try_into().ok()?;
+        self.signatures.get(&selector).map(|signature| signature.as_str())
+    }
+
+    /// look up the (signature, name) of the function called by call data
+    pub fn function(&self, call_data: &[u8]) -> (Option<String>, Option<String>) {
+        match self.lookup(call_data) {
+            Some(signature) => (Some(signature.to_string()), Some(function_name(signature))),
+            None => (None, None),
+        }
+    }
+}
+
+/// get name of function from its text signature
+pub fn function_name(signature: &str) -> String {
+    signature.split('(').next().unwrap_or(signature).to_string()
+}
+
+fn parse_line(line: &str) -> Result<([u8; 4], String), ParseError> {
+    let invalid = || ParseError::ParseError(format!("invalid function signature: {}", line));
+    let (raw_selector, signature) = match line.split_once(|c: char| c == ',' || c.is_whitespace()) {
+        Some((selector, signature)) if selector.starts_with("0x") && !selector.contains('(') => {
+            (Some(selector), signature.trim())
+        }
+        _ => (None, line),
+    };
+    if !signature.contains('(') || !signature.ends_with(')') || signature.contains(' ') {
+        return Err(invalid())
+    }
+    let hash = ethers_core::utils::keccak256(signature.as_bytes());
+    let selector = [hash[0], hash[1], hash[2], hash[3]];
+    if let Some(raw_selector) = raw_selector {
+        let raw_selector: Vec<u8> = prefix_hex::decode(raw_selector).map_err(|_| invalid())?;
+        if raw_selector != selector {
+            return Err(ParseError::ParseError(format!(
+                "selector does not match function signature: {}",
+                line
+            )))
+        }
+    }
+    Ok((selector, signature.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_function_signatures() {
+        let signatures = FunctionSignatures::bundled();
+        let call_data = prefix_hex::decode::<Vec<u8>>("0xa9059cbb0000").unwrap();
+        assert_eq!(signatures.lookup(&call_data), Some("transfer(address,uint256)"));
+        assert_eq!(
+            signatures.function(&call_data),
+            (Some("transfer(address,uint256)".to_string()), Some("transfer".to_string()))
+        );
+        assert_eq!(signatures.lookup(&[0xa9, 0x05]), None);
+
+        let parsed = FunctionSignatures::parse("0x095ea7b3 approve(address,uint256)\n").unwrap();
+        assert_eq!(parsed.len(), 1);
+        assert!(FunctionSignatures::parse("0x00000000 approve(address,uint256)").is_err());
+        assert!(FunctionSignatures::parse("approve").is_err());
+    }
+}
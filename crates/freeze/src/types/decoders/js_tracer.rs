@@ -0,0 +1,102 @@
+use crate::{CollectError, Datatype, ParseError};
+use polars::prelude::*;
+
+/// javascript tracer run by `debug_traceBlockByNumber`, with the columns extracted from its
+/// results
+#[derive(Clone, Debug, PartialEq)]
+pub struct JsTracer {
+    /// path of tracer file
+    pub path: String,
+    /// javascript code of tracer
+    pub code: String,
+    /// columns extracted from each result, as (column name, json pointer into result)
+    pub columns: Vec<(String, String)>,
+}
+
+/// tracer results collected during collection, one entry per row
+#[derive(Default)]
+pub struct TracerResults(pub Vec<Option<serde_json::Value>>);
+
+impl JsTracer {
+    /// read tracer from file, extracting columns given as NAME=PATH
+    ///
+    /// PATH is either a json pointer, ex: /calls/0/to, or the same path separated by dots, ex:
+    /// calls.0.to
+    pub fn new(path: String, columns: &[String]) -> Result<Self, ParseError> {
+        let code = std::fs::read_to_string(&path).map_err(|e| {
+            ParseError::ParseError(format!("could not read tracer {}: {}", path, e))
+        })?;
+        let reserved = Datatype::JavascriptTraces.column_types();
+        let mut parsed: Vec<(String, String)> = Vec::new();
+        for column in columns.iter() {
+            let (name, pointer) = match column.split_once('=') {
+                Some((name, pointer)) if !name.is_empty() => (name.to_string(), pointer),
+                _ => {
+                    return Err(ParseError::ParseError(format!(
+                        "tracer columns should be given as NAME=PATH, got {}",
+                        column
+                    )))
+                }
+            };
+            if reserved.contains_key(name.as_str()) || parsed.iter().any(|(n, _)| n == &name) {
+                return Err(ParseError::ParseError(format!(
+                    "tracer column name already used: {}",
+                    name
+                )))
+            }
+            let pointer = match pointer.starts_with('/') || pointer.is_empty() {
+                true => pointer.to_string(),
+                false => format!("/{}", pointer.replace('.', "/")),
+            };
+            parsed.push((name, pointer));
+        }
+        Ok(Self { path, code, columns: parsed })
+    }
+
+    /// convert results into one string series per column
+    ///
+    /// json strings are stored without quotes, other json values are stored serialized, and
+    /// missing values are null
+    pub fn make_series(&self, results: TracerResults) -> Result<Vec<Series>, CollectError> {
+        let series = self
+            .columns
+            .iter()
+            .map(|(name, pointer)| {
+                let values: Vec<Option<String>> = results
+                    .0
+                    .iter()
+                    .map(|result| match result.as_ref().and_then(|r| r.pointer(pointer)) {
+                        None | Some(serde_json::Value::Null) => None,
+                        Some(serde_json::Value::String(value)) => Some(value.clone()),
+                        Some(value) => Some(value.to_string()),
+                    })
+                    .collect();
+                Series::new(name, values)
+            })
+            .collect();
+        Ok(series)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_js_tracer_columns() {
+        let path = std::env::temp_dir().join("cryo_test_js_tracer.js");
+        std::fs::write(&path, "{result: function() { return {}; }, fault: function() {}}").unwrap();
+        let columns = vec!["gas=gas".to_string(), "first_to=/calls/0/to".to_string()];
+        let tracer = JsTracer::new(path.to_string_lossy().to_string(), &columns).unwrap();
+        let results = TracerResults(vec![
+            Some(serde_json::json!({"gas": 21000, "calls": [{"to": "0xab"}]})),
+            None,
+        ]);
+        let series = tracer.make_series(results).unwrap();
+        assert_eq!(series[0].utf8().unwrap().get(0), Some("21000"));
+        assert_eq!(series[1].utf8().unwrap().get(0), Some("0xab"));
+        assert_eq!(series[1].utf8().unwrap().get(1), None);
+        assert!(JsTracer::new(path.to_string_lossy().to_string(), &["block_number=x".to_string()])
+            .is_err());
+    }
+}
@@ -3,6 +3,18 @@ use ethers_core::abi::{AbiEncode, EventParam, HumanReadableParser, ParamType, Ra
 use polars::prelude::*;
 use std::collections::{HashMap, HashSet};
 
+/// standard signatures of common events, by name
+pub const KNOWN_EVENTS: [(&str, &str); 5] = [
+    ("Transfer", "event Transfer(address indexed from, address indexed to, uint256 value)"),
+    ("Approval", "event Approval(address indexed owner, address indexed spender, uint256 value)"),
+    (
+        "ApprovalForAll",
+        "event ApprovalForAll(address indexed owner, address indexed operator, bool approved)",
+    ),
+    ("Deposit", "event Deposit(address indexed dst, uint256 wad)"),
+    ("Withdrawal", "event Withdrawal(address indexed src, uint256 wad)"),
+];
+
 /// container for log decoding context
 #[derive(Clone, Debug, PartialEq)]
 pub struct LogDecoder {
@@ -28,6 +40,31 @@ impl LogDecoder {
         }
     }
 
+    /// create a new LogDecoder from an event name or signature
+    /// ex: LogDecoder::from_event("Transfer"), LogDecoder::from_event("Transfer(address indexed
+    /// from, address indexed to, uint256 amount)")
+    ///
+    /// names of events in KNOWN_EVENTS are resolved to their standard signatures
+    pub fn from_event(event: &str) -> Result<Self, String> {
+        let event = event.trim();
+        match KNOWN_EVENTS.iter().find(|(name, _)| *name == event) {
+            Some((_, signature)) => Self::new(signature.to_string()),
+            None if event.starts_with("event ") => Self::new(event.to_string()),
+            None if event.contains('(') => Self::new(format!("event {}", event)),
+            None => Err(format!(
+                "unknown event {}, use a full signature such as Transfer(address indexed from, \
+                 address indexed to, uint256 amount) or one of: {}",
+                event,
+                KNOWN_EVENTS.iter().map(|(name, _)| *name).collect::<Vec<_>>().join(", ")
+            )),
+        }
+    }
+
+    /// topic0 of logs of event
+    pub fn topic0(&self) -> H256 {
+        self.event.signature()
+    }
+
     // fn field_names(&self) -> Vec<String> {
     //     self.event.inputs.iter().map(|i| i.name.clone()).collect()
     // }
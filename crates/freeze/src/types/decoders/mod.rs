@@ -1,3 +1,15 @@
+/// custom error decoder
+pub mod error_decoder;
+/// calldata decoder
+pub mod function_decoder;
+/// function signature decoder
+pub mod function_signatures;
+/// javascript tracer
+pub mod js_tracer;
 /// log decoder
 pub mod log_decoder;
+pub use error_decoder::*;
+pub use function_decoder::*;
+pub use function_signatures::*;
+pub use js_tracer::*;
 pub use log_decoder::*;
@@ -0,0 +1,97 @@
+/// deduplication of rows by key columns, for output files of overlapping block ranges
+use std::path::Path;
+
+use polars::prelude::*;
+
+use crate::{find_file_block_ranges, CollectError, Datatype};
+
+/// column of the positions of rows in the dataframe being deduplicated
+const ROW_COLUMN: &str = "__dedup_row";
+
+/// drop rows of df whose keys occur in seen or in an earlier row of df
+pub(crate) fn drop_duplicate_keys(
+    df: DataFrame,
+    keys: &[String],
+    seen: Option<&DataFrame>,
+) -> Result<DataFrame, CollectError> {
+    let mut rows = select_keys(&df, keys)?;
+    rows.with_column(Series::new(ROW_COLUMN, (0..df.height() as IdxSize).collect::<Vec<_>>()))?;
+    if let Some(seen) = seen {
+        let mut seen = select_keys(seen, keys)?;
+        seen.with_column(Series::full_null(ROW_COLUMN, seen.height(), &IDX_DTYPE))?;
+        rows = seen.vstack(&rows)?;
+    }
+    let unique = rows.unique_stable(Some(keys), UniqueKeepStrategy::First, None)?;
+    let kept: Vec<IdxSize> = unique.column(ROW_COLUMN)?.idx()?.into_iter().flatten().collect();
+    match kept.len() == df.height() {
+        true => Ok(df),
+        false => Ok(df.take(&IdxCa::from_vec("", kept))?),
+    }
+}
+
+/// drop rows of df that duplicate rows of the other parquet files of datatype in the directory of
+/// path whose block ranges overlap the blocks of df, such as files of an earlier collection with
+/// different chunking, and rows that duplicate earlier rows of df
+pub(crate) fn drop_collected_rows(
+    df: DataFrame,
+    datatype: &Datatype,
+    path: &Path,
+    keys: &[String],
+) -> Result<DataFrame, CollectError> {
+    let directory = path.parent().unwrap_or(Path::new("."));
+    let blocks =
+        df.column("block_number").ok().map(|column| (column.min::<u64>(), column.max::<u64>()));
+    let (Some((Some(start_block), Some(end_block))), true) = (blocks, directory.is_dir()) else {
+        return drop_duplicate_keys(df, keys, None)
+    };
+    let mut seen: Option<DataFrame> = None;
+    for file in find_file_block_ranges(directory, datatype)?.into_iter() {
+        let overlaps = file.start_block <= end_block && file.end_block >= start_block;
+        let parquet = file.path.extension().and_then(|e| e.to_str()) == Some("parquet");
+        if !overlaps || !parquet || file.path == path {
+            continue
+        }
+        let reader = std::fs::File::open(&file.path).map_err(|e| {
+            CollectError::CollectError(format!("could not open {}: {}", file.path.display(), e))
+        })?;
+        let collected = ParquetReader::new(reader).with_columns(Some(keys.to_vec())).finish()?;
+        let collected = select_keys(&collected, keys)?;
+        seen = match seen {
+            None => Some(collected),
+            Some(mut seen) => {
+                seen.vstack_mut(&collected)?;
+                Some(seen)
+            }
+        };
+    }
+    drop_duplicate_keys(df, keys, seen.as_ref())
+}
+
+fn select_keys(df: &DataFrame, keys: &[String]) -> Result<DataFrame, CollectError> {
+    if let Some(key) = keys.iter().find(|key| df.column(key).is_err()) {
+        return Err(CollectError::CollectError(format!("dedup key {} is not a column", key)))
+    }
+    Ok(df.select(keys)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_drop_duplicate_keys() {
+        let df = df!(
+            "block_number" => [1u32, 1, 2, 2, 3],
+            "log_index" => [0u32, 1, 0, 0, 0],
+            "data" => ["a", "b", "c", "d", "e"],
+        )
+        .unwrap();
+        let keys = vec!["block_number".to_string(), "log_index".to_string()];
+        let seen = df!("block_number" => [3u32], "log_index" => [0u32]).unwrap();
+        let deduped = drop_duplicate_keys(df, &keys, Some(&seen)).unwrap();
+        let data: Vec<_> = deduped.column("data").unwrap().utf8().unwrap().into_iter().collect();
+        assert_eq!(data, vec![Some("a"), Some("b"), Some("c")]);
+        let missing = vec!["transaction_index".to_string()];
+        assert!(drop_duplicate_keys(deduped, &missing, None).is_err());
+    }
+}
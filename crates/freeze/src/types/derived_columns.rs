@@ -0,0 +1,386 @@
+/// types and functions related to columns computed from other columns of a dataset
+use std::str::FromStr;
+
+use polars::prelude::{col, lit, DataType, Expr};
+
+use crate::{ColumnType, ParseError, Table, U256Type};
+
+/// arithmetic operator of a derived column expression
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ArithmeticOp {
+    /// +
+    Add,
+    /// -
+    Subtract,
+    /// *
+    Multiply,
+    /// /
+    Divide,
+}
+
+/// arithmetic expression over the numeric columns of a dataset
+#[derive(Clone, Debug, PartialEq)]
+pub enum DeriveExpr {
+    /// column of dataset, where u256 columns are read from their f64 representation
+    Column(String),
+    /// integer literal
+    Integer(u64),
+    /// float literal
+    Float(f64),
+    /// negation of expression
+    Negate(Box<DeriveExpr>),
+    /// binary operation on two expressions
+    Binary(ArithmeticOp, Box<DeriveExpr>, Box<DeriveExpr>),
+}
+
+/// column computed from other columns, e.g. `gas_cost = gas_used * gas_price`
+///
+/// sums, differences, and products of unsigned integers are uint64, of signed integers or of
+/// differences are int64, and of floats are float64. quotients are always float64
+#[derive(Clone, Debug, PartialEq)]
+pub struct DerivedColumn {
+    /// name of column
+    pub name: String,
+    /// expression computing column
+    pub expression: DeriveExpr,
+}
+
+impl DeriveExpr {
+    /// names of columns referenced by expression
+    pub fn columns(&self) -> Vec<String> {
+        match self {
+            DeriveExpr::Column(name) => vec![name.clone()],
+            DeriveExpr::Integer(_) | DeriveExpr::Float(_) => vec![],
+            DeriveExpr::Negate(expr) => expr.columns(),
+            DeriveExpr::Binary(_, lhs, rhs) => [lhs.columns(), rhs.columns()].concat(),
+        }
+    }
+
+    /// infer column type of expression's output
+    fn column_type(&self, schema: &Table) -> Result<ColumnType, ParseError> {
+        let column_type = match self {
+            DeriveExpr::Column(name) => {
+                let (_, column_type) = resolve_column(name, schema)?.ok_or_else(|| {
+                    ParseError::ParseError(format!("derived column references missing {}", name))
+                })?;
+                match column_type {
+                    ColumnType::UInt32 | ColumnType::UInt64 => ColumnType::UInt64,
+                    ColumnType::Int32 | ColumnType::Int64 => ColumnType::Int64,
+                    _ => ColumnType::Float64,
+                }
+            }
+            DeriveExpr::Integer(_) => ColumnType::UInt64,
+            DeriveExpr::Float(_) => ColumnType::Float64,
+            DeriveExpr::Negate(expr) => match expr.column_type(schema)? {
+                ColumnType::Float64 => ColumnType::Float64,
+                _ => ColumnType::Int64,
+            },
+            DeriveExpr::Binary(op, lhs, rhs) => {
+                match (op, lhs.column_type(schema)?, rhs.column_type(schema)?) {
+                    (ArithmeticOp::Divide, _, _) => ColumnType::Float64,
+                    (_, ColumnType::Float64, _) | (_, _, ColumnType::Float64) => {
+                        ColumnType::Float64
+                    }
+                    (ArithmeticOp::Subtract, _, _) => ColumnType::Int64,
+                    (_, ColumnType::Int64, _) | (_, _, ColumnType::Int64) => ColumnType::Int64,
+                    _ => ColumnType::UInt64,
+                }
+            }
+        };
+        Ok(column_type)
+    }
+
+    /// convert expression to polars expression, casting operands to the output type
+    fn to_expr(&self, schema: &Table) -> Result<Expr, ParseError> {
+        let dtype = polars_dtype(self.column_type(schema)?);
+        let expr = match self {
+            DeriveExpr::Column(name) => match resolve_column(name, schema)? {
+                Some((name, _)) => col(&name),
+                None => {
+                    return Err(ParseError::ParseError(format!(
+                        "derived column references missing {}",
+                        name
+                    )))
+                }
+            },
+            DeriveExpr::Integer(value) => lit(*value),
+            DeriveExpr::Float(value) => lit(*value),
+            DeriveExpr::Negate(expr) => {
+                lit(0).cast(dtype.clone()) - expr.to_expr(schema)?.cast(dtype.clone())
+            }
+            DeriveExpr::Binary(op, lhs, rhs) => {
+                let lhs = lhs.to_expr(schema)?.cast(dtype.clone());
+                let rhs = rhs.to_expr(schema)?.cast(dtype.clone());
+                match op {
+                    ArithmeticOp::Add => lhs + rhs,
+                    ArithmeticOp::Subtract => lhs - rhs,
+                    ArithmeticOp::Multiply => lhs * rhs,
+                    ArithmeticOp::Divide => lhs / rhs,
+                }
+            }
+        };
+        Ok(expr.cast(dtype))
+    }
+}
+
+impl DerivedColumn {
+    /// whether schema has every column referenced by derived column
+    pub fn applies_to(&self, schema: &Table) -> bool {
+        self.expression.columns().iter().all(|c| matches!(resolve_column(c, schema), Ok(Some(_))))
+    }
+
+    /// infer column type of derived column, validating its referenced columns
+    pub fn column_type(&self, schema: &Table) -> Result<ColumnType, ParseError> {
+        self.expression.column_type(schema)
+    }
+
+    /// polars expression computing derived column from the other columns of schema
+    pub fn to_expr(&self, schema: &Table) -> Result<Expr, ParseError> {
+        Ok(self.expression.to_expr(schema)?.alias(&self.name))
+    }
+}
+
+fn polars_dtype(column_type: ColumnType) -> DataType {
+    match column_type {
+        ColumnType::UInt64 => DataType::UInt64,
+        ColumnType::Int64 => DataType::Int64,
+        _ => DataType::Float64,
+    }
+}
+
+/// resolve column to the name and type of a numeric column of the dataframe, None if missing
+///
+/// u256 columns are read from their f64 representation, or can name a representation directly
+fn resolve_column(
+    column: &str,
+    schema: &Table,
+) -> Result<Option<(String, ColumnType)>, ParseError> {
+    let non_numeric = |column_type: ColumnType| {
+        ParseError::ParseError(format!(
+            "derived column cannot use {} column {}",
+            column_type.as_str(),
+            column
+        ))
+    };
+    match schema.column_type(column) {
+        Some(ColumnType::UInt256) => match schema.u256_types.contains(&U256Type::F64) {
+            true => Ok(Some((
+                column.to_string() + U256Type::F64.suffix().as_str(),
+                ColumnType::Float64,
+            ))),
+            false => Err(ParseError::ParseError(format!(
+                "derived column using u256 column {} requires --u256-types f64",
+                column
+            ))),
+        },
+        Some(column_type) if is_numeric(column_type) => Ok(Some((column.to_string(), column_type))),
+        Some(column_type) => Err(non_numeric(column_type)),
+        None => {
+            for u256_type in schema.u256_types.iter() {
+                let base = match column.strip_suffix(u256_type.suffix().as_str()) {
+                    Some(base) => base,
+                    None => continue,
+                };
+                if schema.column_type(base) == Some(ColumnType::UInt256) {
+                    return match u256_type.to_columntype() {
+                        column_type if is_numeric(column_type) => {
+                            Ok(Some((column.to_string(), column_type)))
+                        }
+                        column_type => Err(non_numeric(column_type)),
+                    }
+                }
+            }
+            Ok(None)
+        }
+    }
+}
+
+fn is_numeric(column_type: ColumnType) -> bool {
+    matches!(
+        column_type,
+        ColumnType::UInt32 |
+            ColumnType::UInt64 |
+            ColumnType::Int32 |
+            ColumnType::Int64 |
+            ColumnType::Float32 |
+            ColumnType::Float64
+    )
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum DeriveToken {
+    Column(String),
+    Integer(u64),
+    Float(f64),
+    Op(ArithmeticOp),
+    Open,
+    Close,
+}
+
+fn tokenize_expression(s: &str) -> Result<Vec<DeriveToken>, String> {
+    let chars: Vec<char> = s.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        let (token, width) = match c {
+            c if c.is_whitespace() => {
+                i += 1;
+                continue
+            }
+            '+' => (DeriveToken::Op(ArithmeticOp::Add), 1),
+            '-' => (DeriveToken::Op(ArithmeticOp::Subtract), 1),
+            '*' => (DeriveToken::Op(ArithmeticOp::Multiply), 1),
+            '/' => (DeriveToken::Op(ArithmeticOp::Divide), 1),
+            '(' => (DeriveToken::Open, 1),
+            ')' => (DeriveToken::Close, 1),
+            c if c.is_ascii_digit() || c == '.' => {
+                let width = chars[i..]
+                    .iter()
+                    .take_while(|x| x.is_ascii_alphanumeric() || **x == '.' || **x == '_')
+                    .count();
+                let raw: String = chars[i..i + width].iter().filter(|x| **x != '_').collect();
+                let token = match (raw.parse::<u64>(), raw.parse::<f64>()) {
+                    (Ok(value), _) => DeriveToken::Integer(value),
+                    (_, Ok(value)) if value.is_finite() => DeriveToken::Float(value),
+                    _ => return Err(format!("invalid number {}", raw)),
+                };
+                (token, width)
+            }
+            c if c.is_alphabetic() || c == '_' => {
+                let width =
+                    chars[i..].iter().take_while(|x| x.is_alphanumeric() || **x == '_').count();
+                (DeriveToken::Column(chars[i..i + width].iter().collect()), width)
+            }
+            c => return Err(format!("unexpected character {}", c)),
+        };
+        tokens.push(token);
+        i += width;
+    }
+    Ok(tokens)
+}
+
+struct ExpressionParser {
+    tokens: Vec<DeriveToken>,
+    position: usize,
+}
+
+impl ExpressionParser {
+    fn next_op(&mut self, ops: &[ArithmeticOp]) -> Option<ArithmeticOp> {
+        match self.tokens.get(self.position) {
+            Some(DeriveToken::Op(op)) if ops.contains(op) => {
+                self.position += 1;
+                Some(*op)
+            }
+            _ => None,
+        }
+    }
+
+    fn parse_sum(&mut self) -> Result<DeriveExpr, String> {
+        let mut expr = self.parse_product()?;
+        while let Some(op) = self.next_op(&[ArithmeticOp::Add, ArithmeticOp::Subtract]) {
+            expr = DeriveExpr::Binary(op, Box::new(expr), Box::new(self.parse_product()?));
+        }
+        Ok(expr)
+    }
+
+    fn parse_product(&mut self) -> Result<DeriveExpr, String> {
+        let mut expr = self.parse_unary()?;
+        while let Some(op) = self.next_op(&[ArithmeticOp::Multiply, ArithmeticOp::Divide]) {
+            expr = DeriveExpr::Binary(op, Box::new(expr), Box::new(self.parse_unary()?));
+        }
+        Ok(expr)
+    }
+
+    fn parse_unary(&mut self) -> Result<DeriveExpr, String> {
+        if self.next_op(&[ArithmeticOp::Subtract]).is_some() {
+            return Ok(DeriveExpr::Negate(Box::new(self.parse_unary()?)))
+        }
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        match token {
+            Some(DeriveToken::Column(name)) => Ok(DeriveExpr::Column(name)),
+            Some(DeriveToken::Integer(value)) => Ok(DeriveExpr::Integer(value)),
+            Some(DeriveToken::Float(value)) => Ok(DeriveExpr::Float(value)),
+            Some(DeriveToken::Open) => {
+                let expr = self.parse_sum()?;
+                match self.tokens.get(self.position) {
+                    Some(DeriveToken::Close) => {
+                        self.position += 1;
+                        Ok(expr)
+                    }
+                    _ => Err("missing closing parenthesis".to_string()),
+                }
+            }
+            _ => Err("expected column, number, or parenthesized expression".to_string()),
+        }
+    }
+}
+
+impl FromStr for DerivedColumn {
+    type Err = ParseError;
+
+    /// parse derived column of the form `gas_cost = gas_used * gas_price`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid =
+            |e: &str| ParseError::ParseError(format!("invalid derived column {}: {}", s, e));
+        let (name, expression) =
+            s.split_once('=').ok_or_else(|| invalid("expected NAME = EXPR"))?;
+        let name = name.trim();
+        if name.is_empty() || !name.chars().all(|c| c.is_alphanumeric() || c == '_') {
+            return Err(invalid("invalid column name"))
+        }
+        let mut parser = ExpressionParser {
+            tokens: tokenize_expression(expression).map_err(|e| invalid(&e))?,
+            position: 0,
+        };
+        let expression = parser.parse_sum().map_err(|e| invalid(&e))?;
+        if parser.position != parser.tokens.len() {
+            return Err(invalid("unexpected trailing tokens"))
+        }
+        Ok(DerivedColumn { name: name.to_string(), expression })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ColumnEncoding, Datatype};
+    use polars::prelude::{df, IntoLazy, NamedFrom};
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_derived_columns() {
+        let u256_types = HashSet::from_iter([U256Type::Binary, U256Type::F64]);
+        let columns = Some(vec!["gas_used".to_string(), "gas_price".to_string(), "value".into()]);
+        let schema = Datatype::Transactions
+            .table_schema(&u256_types, &ColumnEncoding::Binary, &None, &None, &columns, None, None)
+            .unwrap();
+
+        let gas_cost = DerivedColumn::from_str("gas_cost = gas_used * gas_price").unwrap();
+        assert_eq!(gas_cost.column_type(&schema).unwrap(), ColumnType::UInt64);
+        let eth = DerivedColumn::from_str("eth = value / 1e18 - -(gas_used)").unwrap();
+        assert_eq!(eth.column_type(&schema).unwrap(), ColumnType::Float64);
+        let refund = DerivedColumn::from_str("refund = gas_price - (gas_used * 2)").unwrap();
+        assert_eq!(refund.column_type(&schema).unwrap(), ColumnType::Int64);
+
+        let df = df!(
+            "gas_used" => [21000u64, 50000],
+            "gas_price" => [Some(10u64), None],
+            "value_f64" => [1e18, 0.0],
+        )
+        .unwrap();
+        let exprs = [gas_cost.to_expr(&schema).unwrap(), eth.to_expr(&schema).unwrap()];
+        let df = df.lazy().with_columns(exprs).collect().unwrap();
+        let gas_cost: Vec<_> = df.column("gas_cost").unwrap().u64().unwrap().into_iter().collect();
+        assert_eq!(gas_cost, vec![Some(210000), None]);
+        assert_eq!(
+            df.column("eth").unwrap().f64().unwrap().into_iter().next(),
+            Some(Some(21001.0))
+        );
+
+        assert!(!DerivedColumn::from_str("x = foo + 1").unwrap().applies_to(&schema));
+        assert!(DerivedColumn::from_str("x = gas_used +").is_err());
+        assert!(DerivedColumn::from_str("gas_used * 2").is_err());
+    }
+}
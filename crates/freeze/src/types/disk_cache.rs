@@ -0,0 +1,92 @@
+/// on-disk cache of rpc responses
+use std::{
+    path::PathBuf,
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+use serde::{de::DeserializeOwned, Serialize};
+
+/// number of blocks behind head below which responses are never cached, so that responses of
+/// blocks that might still be reorged are always refetched
+pub const DISK_CACHE_CONFIRMATIONS: u64 = 64;
+
+static N_WRITES: AtomicU64 = AtomicU64::new(0);
+
+/// content-addressed cache of rpc responses, keyed by the hash of method and params
+///
+/// only responses pinned to a block at or before `max_block` are cached
+#[derive(Clone, Debug)]
+pub struct DiskCache {
+    /// directory of cache, with one subdirectory per chain and method
+    pub cache_dir: PathBuf,
+    /// chain id of responses
+    pub chain_id: u64,
+    /// most recent block whose responses are cached
+    pub max_block: u64,
+}
+
+impl DiskCache {
+    /// whether responses of requests pinned to block are cached
+    pub fn is_cacheable(&self, block: Option<u64>) -> bool {
+        matches!(block, Some(block) if block <= self.max_block)
+    }
+
+    fn path<P: Serialize>(&self, method: &str, params: &P) -> Option<PathBuf> {
+        let params = serde_json::to_vec(params).ok()?;
+        let key = ethers_core::utils::keccak256([method.as_bytes(), &params].concat());
+        let key: String = key.iter().map(|byte| format!("{:02x}", byte)).collect();
+        Some(
+            self.cache_dir
+                .join(self.chain_id.to_string())
+                .join(method)
+                .join(&key[..2])
+                .join(format!("{}.json", key)),
+        )
+    }
+
+    /// get cached response of request
+    pub fn get<P: Serialize, T: DeserializeOwned>(&self, method: &str, params: &P) -> Option<T> {
+        let contents = std::fs::read(self.path(method, params)?).ok()?;
+        serde_json::from_slice(&contents).ok()
+    }
+
+    /// cache response of request, writing through a temporary file so that concurrent readers
+    /// never see partial responses
+    pub fn put<P: Serialize, T: Serialize>(&self, method: &str, params: &P, response: &T) {
+        let result = (|| {
+            let path = self.path(method, params).ok_or("could not hash params")?;
+            let parent = path.parent().ok_or("invalid cache path")?;
+            std::fs::create_dir_all(parent).map_err(|_| "could not create cache dir")?;
+            let contents = serde_json::to_vec(response).map_err(|_| "could not serialize")?;
+            let n_write = N_WRITES.fetch_add(1, Ordering::Relaxed);
+            let tmp_path = path.with_extension(format!("{}_{}.tmp", std::process::id(), n_write));
+            std::fs::write(&tmp_path, contents).map_err(|_| "could not write response")?;
+            std::fs::rename(&tmp_path, &path).map_err(|_| "could not move response")
+        })();
+        if let Err(e) = result {
+            tracing::warn!(method, error = e, "could not cache rpc response");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_disk_cache() {
+        let cache_dir =
+            std::env::temp_dir().join(format!("cryo_disk_cache_{}", std::process::id()));
+        let cache = DiskCache { cache_dir: cache_dir.clone(), chain_id: 1, max_block: 100 };
+        assert!(cache.is_cacheable(Some(100)));
+        assert!(!cache.is_cacheable(Some(101)));
+        assert!(!cache.is_cacheable(None));
+
+        assert_eq!(cache.get::<_, u64>("eth_getBalance", &(1u64, 100u64)), None);
+        cache.put("eth_getBalance", &(1u64, 100u64), &7u64);
+        assert_eq!(cache.get::<_, u64>("eth_getBalance", &(1u64, 100u64)), Some(7));
+        assert_eq!(cache.get::<_, u64>("eth_getBalance", &(2u64, 100u64)), None);
+        assert_eq!(cache.get::<_, u64>("eth_getCode", &(1u64, 100u64)), None);
+        let _ = std::fs::remove_dir_all(cache_dir);
+    }
+}
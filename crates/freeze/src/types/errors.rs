@@ -82,6 +82,10 @@ pub enum CollectError {
     /// Generic RPC Error
     #[error("RPC call error")]
     RPCError(String),
+
+    /// Blocks of a partition do not form a single chain
+    #[error("Reorg detected: {0}")]
+    ReorgDetected(String),
 }
 
 /// Error related to parsing
@@ -1,4 +1,4 @@
-use crate::CollectError;
+use crate::{CollectError, Shutdown, TuiProgress};
 use indicatif::ProgressBar;
 use std::{path::PathBuf, sync::Arc, time::SystemTime};
 
@@ -13,6 +13,8 @@ pub struct ExecutionEnv {
     pub report: bool,
     /// progress bar
     pub bar: Option<Arc<ProgressBar>>,
+    /// interactive progress view, used instead of progress bar and intro
+    pub tui: Option<Arc<TuiProgress>>,
     /// cli command
     pub cli_command: Option<Vec<String>>,
     /// input args
@@ -25,6 +27,12 @@ pub struct ExecutionEnv {
     pub t_end: Option<SystemTime>,
     /// report directory
     pub report_dir: Option<PathBuf>,
+    /// number of times to retry errored partitions after the main pass
+    pub chunk_retries: u64,
+    /// initial delay before retrying errored partitions (ms), doubled on each retry
+    pub chunk_retry_backoff: u64,
+    /// shutdown requests that stop collection early
+    pub shutdown: Option<Arc<Shutdown>>,
 }
 
 impl ExecutionEnv {
@@ -61,12 +69,16 @@ pub struct ExecutionEnvBuilder {
     verbose: bool,
     report: bool,
     bar: Option<Arc<ProgressBar>>,
+    tui: Option<Arc<TuiProgress>>,
     cli_command: Option<Vec<String>>,
     args: Option<String>,
     t_start_parse: Option<SystemTime>,
     t_start: SystemTime,
     t_end: Option<SystemTime>,
     report_dir: Option<PathBuf>,
+    chunk_retries: u64,
+    chunk_retry_backoff: u64,
+    shutdown: Option<Arc<Shutdown>>,
 }
 
 impl Default for ExecutionEnvBuilder {
@@ -76,12 +88,16 @@ impl Default for ExecutionEnvBuilder {
             verbose: true,
             report: true,
             bar: None,
+            tui: None,
             cli_command: Some(std::env::args().collect()),
             args: None,
             t_start_parse: None,
             t_start: SystemTime::now(),
             t_end: None,
             report_dir: None,
+            chunk_retries: 0,
+            chunk_retry_backoff: 500,
+            shutdown: None,
         }
     }
 }
@@ -116,12 +132,39 @@ impl ExecutionEnvBuilder {
         self
     }
 
+    /// number of times to retry errored partitions
+    pub fn chunk_retries(mut self, chunk_retries: u64) -> Self {
+        self.chunk_retries = chunk_retries;
+        self
+    }
+
+    /// initial delay before retrying errored partitions (ms)
+    pub fn chunk_retry_backoff(mut self, chunk_retry_backoff: u64) -> Self {
+        self.chunk_retry_backoff = chunk_retry_backoff;
+        self
+    }
+
+    /// shutdown requests that stop collection early
+    pub fn shutdown(mut self, shutdown: Option<Arc<Shutdown>>) -> Self {
+        self.shutdown = shutdown;
+        self
+    }
+
     /// progress bar size
     pub fn bar(mut self, n: u64) -> Result<Self, CollectError> {
         self.bar = Some(new_bar(n)?);
         Ok(self)
     }
 
+    /// interactive progress view
+    pub fn tui(mut self, tui: bool) -> Self {
+        self.tui = match tui {
+            true => Some(Arc::new(TuiProgress::new())),
+            false => None,
+        };
+        self
+    }
+
     /// cli command
     pub fn cli_command(mut self, cli_command: Vec<String>) -> Self {
         self.cli_command = Some(cli_command);
@@ -141,12 +184,16 @@ impl ExecutionEnvBuilder {
             verbose: self.verbose,
             report: self.report,
             bar: self.bar,
+            tui: self.tui,
             cli_command: self.cli_command,
             args: self.args,
             t_start_parse: self.t_start_parse,
             t_start: self.t_start,
             t_end: self.t_end,
             report_dir: self.report_dir,
+            chunk_retries: self.chunk_retries,
+            chunk_retry_backoff: self.chunk_retry_backoff,
+            shutdown: self.shutdown,
         }
     }
 }
@@ -1,4 +1,7 @@
-use crate::{CollectError, Datatype, MetaDatatype, Partition, Query};
+use crate::{
+    sinks::{fit_file_name, sanitize_label},
+    CollectError, Datatype, MetaDatatype, Partition, Query,
+};
 use std::{collections::HashMap, path::PathBuf};
 
 /// Options for file output
@@ -10,16 +13,40 @@ pub struct FileOutput {
     pub prefix: String,
     /// Suffix to use at the end of file names
     pub suffix: Option<String>,
-    /// Whether to overwrite existing files or skip them
-    pub overwrite: bool,
+    /// What to do with chunks whose output files already exist
+    pub existing_files: ExistingFilePolicy,
+    /// Whether to rewrite existing files to a changed schema or refuse to write new chunks
+    pub allow_schema_change: bool,
     /// File format to used for output files
     pub format: FileFormat,
     /// Number of rows per parquet row group
     pub row_group_size: Option<usize>,
+    /// Whether each file is sorted by its sort columns as a whole, sorting rows again after
+    /// user-defined transforms and merging sorted files with an external merge
+    pub global_sort: bool,
+    /// Key columns of datatypes whose rows are not written again if files of overlapping block
+    /// ranges already have rows of the same keys
+    pub dedup_keys: HashMap<Datatype, Vec<String>>,
     /// Parquet statistics recording flag
     pub parquet_statistics: bool,
     /// Parquet compression options
     pub parquet_compression: polars::prelude::ParquetCompression,
+    /// Compression of csv and json files
+    pub text_compression: TextCompression,
+    /// Whether binary columns of csv and json files are written as hex with a 0x prefix
+    pub hex_prefix: bool,
+    /// ClickHouse database to also insert chunks into
+    pub clickhouse: Option<crate::ClickhouseOutput>,
+    /// Whether to record the last block hash of each output file
+    pub manifest: bool,
+    /// Whether to skip writing files of chunks without rows, recording them as empty instead
+    pub skip_empty_files: bool,
+    /// Whether to fail if the chain no longer has the block hashes recorded in manifests
+    pub pin_block_hash: bool,
+    /// Maximum number of chunks written concurrently, unbounded if None
+    pub max_concurrent_writes: Option<u64>,
+    /// Object store that files are uploaded to after they are written to output_dir
+    pub remote: Option<crate::RemoteOutput>,
 }
 
 impl FileOutput {
@@ -51,15 +78,141 @@ impl FileOutput {
         partition: &Partition,
         datatype: Datatype,
     ) -> Result<PathBuf, CollectError> {
-        let filename = format!(
-            "{}__{}__{}.{}",
-            self.prefix.clone(),
+        // labels can hold user supplied values, such as the labels of address files
+        let stem = format!(
+            "{}__{}__{}",
+            sanitize_label(&self.prefix),
             datatype.name(),
-            partition.label(&query.partitioned_by)?,
-            self.format.as_str(),
+            sanitize_label(&partition.label(&query.partitioned_by)?),
         );
+        let filename = fit_file_name(&stem, &self.file_extension());
         Ok(std::path::Path::new(&self.output_dir).join(filename))
     }
+
+    /// output file path of rows of datatype collected over seconds from timestamp start, for
+    /// datasets partitioned by time rather than by block, ex: `<prefix>__mempool__<start>_to_<end>`
+    pub fn get_time_bucket_path(&self, datatype: Datatype, start: u64, seconds: u64) -> PathBuf {
+        let end = start + seconds.max(1) - 1;
+        let stem = format!(
+            "{}__{}__{:010}_to_{:010}",
+            sanitize_label(&self.prefix),
+            datatype.name(),
+            start,
+            end
+        );
+        let filename = fit_file_name(&stem, &self.file_extension());
+        std::path::Path::new(&self.output_dir).join(filename)
+    }
+
+    /// path beside existing file at path for the next version of it, ex: `<name>__v2.parquet`
+    pub fn versioned_path(&self, path: &std::path::Path) -> PathBuf {
+        let extension = self.file_extension();
+        let name =
+            path.file_name().map(|name| name.to_string_lossy().to_string()).unwrap_or_default();
+        let stem = name.strip_suffix(&extension).unwrap_or(&name);
+        let mut version = 2;
+        loop {
+            let versioned = path.with_file_name(format!("{}__v{}{}", stem, version, extension));
+            if !self.is_collected(&versioned) {
+                return versioned
+            }
+            version += 1;
+        }
+    }
+
+    /// whether output file exists in output dir or in the listed files of the object store, or
+    /// its chunk was recorded as empty while skipping empty files
+    pub fn is_collected(&self, path: &std::path::Path) -> bool {
+        let is_remote = |remote: &crate::RemoteOutput| {
+            path.strip_prefix(&self.output_dir).is_ok_and(|path| remote.has_file(path))
+        };
+        path.exists() ||
+            self.remote.as_ref().is_some_and(is_remote) ||
+            (self.skip_empty_files && crate::manifests::is_marked_empty(self, path))
+    }
+
+    /// extension of output files, including compression, ex: `.csv.gz`
+    pub fn file_extension(&self) -> String {
+        let compression_extension = match self.format {
+            FileFormat::Parquet => None,
+            FileFormat::Csv | FileFormat::Json => self.text_compression.extension(),
+        };
+        match compression_extension {
+            Some(extension) => format!(".{}.{}", self.format.as_str(), extension),
+            None => format!(".{}", self.format.as_str()),
+        }
+    }
+
+    /// remove tmp files of the datatypes of query left in output dir by interrupted writes,
+    /// returning the removed paths
+    pub fn remove_tmp_files(&self, query: &Query) -> Result<Vec<PathBuf>, CollectError> {
+        let entries = match std::fs::read_dir(&self.output_dir) {
+            Ok(entries) => entries,
+            Err(_) => return Ok(vec![]),
+        };
+        let patterns: Vec<String> = query
+            .datatypes
+            .iter()
+            .flat_map(|datatype| datatype.datatypes())
+            .map(|datatype| format!("{}__{}__", self.prefix, datatype.name()))
+            .collect();
+        let mut removed = Vec::new();
+        for entry in entries.flatten() {
+            let name = entry.file_name().to_string_lossy().to_string();
+            let is_tmp = crate::dataframes::is_tmp_file(&name);
+            if is_tmp && patterns.iter().any(|pattern| name.starts_with(pattern)) {
+                std::fs::remove_file(entry.path()).map_err(|_| {
+                    CollectError::CollectError(format!("could not remove tmp file {}", name))
+                })?;
+                removed.push(entry.path());
+            }
+        }
+        removed.sort();
+        Ok(removed)
+    }
+}
+
+/// What to do with chunks whose output files already exist
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ExistingFilePolicy {
+    /// Skip chunks whose files all exist
+    #[default]
+    Skip,
+    /// Collect chunks again and replace their files
+    Overwrite,
+    /// Refuse to collect if any file of a chunk exists
+    Error,
+    /// Collect chunks again and write new versions beside their files, ex: `<name>__v2.parquet`
+    Version,
+}
+
+impl ExistingFilePolicy {
+    /// convert ExistingFilePolicy to str
+    pub fn as_str(&self) -> &'static str {
+        match *self {
+            ExistingFilePolicy::Skip => "skip",
+            ExistingFilePolicy::Overwrite => "overwrite",
+            ExistingFilePolicy::Error => "error",
+            ExistingFilePolicy::Version => "version",
+        }
+    }
+}
+
+impl std::str::FromStr for ExistingFilePolicy {
+    type Err = crate::ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "skip" => Ok(ExistingFilePolicy::Skip),
+            "overwrite" => Ok(ExistingFilePolicy::Overwrite),
+            "error" => Ok(ExistingFilePolicy::Error),
+            "version" => Ok(ExistingFilePolicy::Version),
+            _ => Err(crate::ParseError::ParseError(format!(
+                "invalid existing file policy {}, use skip, overwrite, error, or version",
+                s
+            ))),
+        }
+    }
 }
 
 /// File format
@@ -84,6 +237,28 @@ impl FileFormat {
     }
 }
 
+/// Compression of csv and json files
+#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+pub enum TextCompression {
+    /// No compression
+    Uncompressed,
+    /// Gzip compression with level
+    Gzip(u32),
+    /// Zstd compression with level
+    Zstd(i32),
+}
+
+impl TextCompression {
+    /// file extension appended to compressed files
+    pub fn extension(&self) -> Option<&'static str> {
+        match *self {
+            TextCompression::Uncompressed => None,
+            TextCompression::Gzip(_) => Some("gz"),
+            TextCompression::Zstd(_) => Some("zst"),
+        }
+    }
+}
+
 /// Encoding for binary data in a column
 #[derive(Clone, Eq, PartialEq, Debug)]
 pub enum ColumnEncoding {
@@ -91,6 +266,8 @@ pub enum ColumnEncoding {
     Binary,
     /// Hex binary encoding
     Hex,
+    /// Hex binary encoding, with EIP-55 checksums for columns of 20 byte addresses
+    ChecksumHex,
 }
 
 impl ColumnEncoding {
@@ -99,6 +276,75 @@ impl ColumnEncoding {
         match *self {
             ColumnEncoding::Binary => "binary",
             ColumnEncoding::Hex => "hex",
+            ColumnEncoding::ChecksumHex => "checksum_hex",
+        }
+    }
+
+    /// whether binary data is encoded as hex strings
+    pub fn is_hex(&self) -> bool {
+        matches!(self, ColumnEncoding::Hex | ColumnEncoding::ChecksumHex)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TimeDimension;
+
+    #[test]
+    fn test_remove_tmp_files() {
+        let dir = std::env::temp_dir().join("cryo_test_remove_tmp_files");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let names = [
+            "ethereum__blocks__00000000_to_00000999.parquet",
+            "ethereum__blocks__00001000_to_00001999.parquet.tmp",
+            "ethereum__blocks__00002000_to_00002999.parquet._tmp",
+            "ethereum__logs__00000000_to_00000999.parquet.tmp",
+            "polygon__blocks__00000000_to_00000999.parquet.tmp",
+        ];
+        for name in names.iter() {
+            std::fs::write(dir.join(name), "").unwrap();
         }
+        let sink = FileOutput {
+            output_dir: dir.clone(),
+            prefix: "ethereum".to_string(),
+            suffix: None,
+            existing_files: ExistingFilePolicy::Skip,
+            allow_schema_change: false,
+            format: FileFormat::Parquet,
+            row_group_size: None,
+            global_sort: false,
+            dedup_keys: HashMap::new(),
+            parquet_statistics: true,
+            parquet_compression: polars::prelude::ParquetCompression::Lz4Raw,
+            text_compression: TextCompression::Uncompressed,
+            hex_prefix: true,
+            clickhouse: None,
+            manifest: false,
+            pin_block_hash: false,
+            skip_empty_files: false,
+            max_concurrent_writes: None,
+            remote: None,
+        };
+        let query = Query {
+            datatypes: vec![MetaDatatype::Scalar(Datatype::Blocks)],
+            schemas: HashMap::new(),
+            time_dimension: TimeDimension::Blocks,
+            partitions: vec![],
+            partitioned_by: vec![],
+        };
+
+        // only tmp files of the datatypes and prefix of query are removed
+        let removed = sink.remove_tmp_files(&query).unwrap();
+        assert_eq!(removed, vec![dir.join(names[1]), dir.join(names[2])]);
+        assert!(dir.join(names[0]).exists());
+        assert!(!dir.join(names[1]).exists());
+        assert!(!dir.join(names[2]).exists());
+        assert!(dir.join(names[3]).exists());
+        assert!(dir.join(names[4]).exists());
+        assert!(sink.remove_tmp_files(&query).unwrap().is_empty());
+
+        std::fs::remove_dir_all(&dir).unwrap();
     }
 }
@@ -0,0 +1,598 @@
+/// types and functions related to filtering rows during collection
+use std::str::FromStr;
+
+use ethers::prelude::*;
+use polars::prelude::{col, lit, DataFrame, DataType, Expr, IntoLazy, Schema};
+
+use crate::{
+    traces::{action_call_type_to_string, action_type_to_string},
+    CollectError, Datatype, ParseError, U256Type,
+};
+
+/// comparison operator for numeric filters
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Comparison {
+    /// ==
+    Equal,
+    /// !=
+    NotEqual,
+    /// <
+    LessThan,
+    /// <=
+    LessThanOrEqual,
+    /// >
+    GreaterThan,
+    /// >=
+    GreaterThanOrEqual,
+}
+
+impl Comparison {
+    /// check whether lhs and rhs satisfy comparison
+    pub fn compare(&self, lhs: u64, rhs: u64) -> bool {
+        match self {
+            Comparison::Equal => lhs == rhs,
+            Comparison::NotEqual => lhs != rhs,
+            Comparison::LessThan => lhs < rhs,
+            Comparison::LessThanOrEqual => lhs <= rhs,
+            Comparison::GreaterThan => lhs > rhs,
+            Comparison::GreaterThanOrEqual => lhs >= rhs,
+        }
+    }
+
+    fn to_expr(self, lhs: Expr, rhs: Expr) -> Expr {
+        match self {
+            Comparison::Equal => lhs.eq(rhs),
+            Comparison::NotEqual => lhs.neq(rhs),
+            Comparison::LessThan => lhs.lt(rhs),
+            Comparison::LessThanOrEqual => lhs.lt_eq(rhs),
+            Comparison::GreaterThan => lhs.gt(rhs),
+            Comparison::GreaterThanOrEqual => lhs.gt_eq(rhs),
+        }
+    }
+}
+
+/// filter applied to trace rows during transform
+///
+/// conditions on different keys are combined with AND, repeated values of the same key are
+/// combined with OR
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TraceFilter {
+    /// allowed call types, e.g. call, delegate_call, static_call
+    pub call_types: Vec<String>,
+    /// allowed action types, e.g. call, create, suicide, reward
+    pub action_types: Vec<String>,
+    /// allowed from addresses
+    pub from_addresses: Vec<Vec<u8>>,
+    /// allowed to addresses
+    pub to_addresses: Vec<Vec<u8>>,
+    /// depth constraints, where top level call has depth 0
+    pub depths: Vec<(Comparison, u64)>,
+}
+
+fn normalize_name(name: &str) -> String {
+    name.to_lowercase().replace(['_', '-'], "")
+}
+
+impl TraceFilter {
+    /// keys that filter has conditions on
+    pub fn keys(&self) -> Vec<&'static str> {
+        let mut keys = Vec::new();
+        if !self.call_types.is_empty() {
+            keys.push("calltype")
+        }
+        if !self.action_types.is_empty() {
+            keys.push("type")
+        }
+        if !self.from_addresses.is_empty() {
+            keys.push("from")
+        }
+        if !self.to_addresses.is_empty() {
+            keys.push("to")
+        }
+        if !self.depths.is_empty() {
+            keys.push("depth")
+        }
+        keys
+    }
+
+    /// check that datatype supports each key of filter, since vm traces have no call types or
+    /// addresses per opcode and can only be filtered by depth
+    pub fn check_supported(&self, datatype: &Datatype) -> Result<(), ParseError> {
+        let supported: &[&str] = match datatype {
+            Datatype::Traces => &["calltype", "type", "from", "to", "depth"],
+            Datatype::VmTraces => &["depth"],
+            _ => &[],
+        };
+        match self.keys().into_iter().find(|key| !supported.contains(key)) {
+            Some(key) => Err(ParseError::ParseError(format!(
+                "trace filter key {} is not supported by {}",
+                key,
+                datatype.name()
+            ))),
+            None => Ok(()),
+        }
+    }
+
+    /// check whether trace at given depth passes depth constraints
+    pub fn depth_matches(&self, depth: u64) -> bool {
+        self.depths.iter().all(|(comparison, value)| comparison.compare(depth, *value))
+    }
+
+    /// check whether trace passes filter
+    pub fn matches(&self, trace: &Trace) -> bool {
+        if !self.depth_matches(trace.trace_address.len() as u64) {
+            return false
+        }
+
+        let (from, to, call_type) = match &trace.action {
+            Action::Call(action) => (Some(action.from), Some(action.to), Some(&action.call_type)),
+            Action::Create(action) => (Some(action.from), None, None),
+            Action::Suicide(action) => (Some(action.address), Some(action.refund_address), None),
+            Action::Reward(action) => (Some(action.author), None, None),
+        };
+
+        if !self.call_types.is_empty() {
+            match call_type {
+                Some(call_type) => {
+                    let call_type = normalize_name(&action_call_type_to_string(call_type));
+                    if !self.call_types.contains(&call_type) {
+                        return false
+                    }
+                }
+                None => return false,
+            }
+        }
+
+        if !self.action_types.is_empty() {
+            let action_type = normalize_name(&action_type_to_string(&trace.action_type));
+            if !self.action_types.contains(&action_type) {
+                return false
+            }
+        }
+
+        if !self.from_addresses.is_empty() {
+            match from {
+                Some(from) if self.from_addresses.contains(&from.as_bytes().to_vec()) => {}
+                _ => return false,
+            }
+        }
+
+        if !self.to_addresses.is_empty() {
+            match to {
+                Some(to) if self.to_addresses.contains(&to.as_bytes().to_vec()) => {}
+                _ => return false,
+            }
+        }
+
+        true
+    }
+}
+
+impl FromStr for TraceFilter {
+    type Err = ParseError;
+
+    /// parse filter of the form `calltype=delegatecall,depth<=2,to=0x...`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut filter = TraceFilter::default();
+        for condition in s.split(',').map(|c| c.trim()).filter(|c| !c.is_empty()) {
+            let invalid = || ParseError::ParseError(format!("invalid trace filter: {}", condition));
+            // the operator starts at the first operator character and is matched longest first,
+            // so that `>=` is not read as `>` followed by a value of `=...`
+            let position = condition.find(['<', '>', '!', '=']).ok_or_else(invalid)?;
+            let (key, rest) = condition.split_at(position);
+            let (comparison, width) = match rest.get(..2) {
+                Some("<=") => (Comparison::LessThanOrEqual, 2),
+                Some(">=") => (Comparison::GreaterThanOrEqual, 2),
+                Some("!=") => (Comparison::NotEqual, 2),
+                Some("==") => (Comparison::Equal, 2),
+                _ => match rest.chars().next() {
+                    Some('<') => (Comparison::LessThan, 1),
+                    Some('>') => (Comparison::GreaterThan, 1),
+                    Some('=') => (Comparison::Equal, 1),
+                    _ => return Err(invalid()),
+                },
+            };
+            let key = normalize_name(key.trim());
+            let value = rest[width..].trim();
+            if key.is_empty() || value.is_empty() {
+                return Err(invalid())
+            }
+
+            if (comparison != Comparison::Equal) && (key != "depth") {
+                return Err(ParseError::ParseError(format!(
+                    "only depth supports comparisons other than =, got: {}",
+                    condition
+                )))
+            }
+
+            match key.as_str() {
+                "calltype" => filter.call_types.push(normalize_name(value)),
+                "type" | "actiontype" => filter.action_types.push(normalize_name(value)),
+                "from" => filter.from_addresses.push(parse_address(value)?),
+                "to" => filter.to_addresses.push(parse_address(value)?),
+                "depth" => {
+                    let value = value.parse::<u64>().map_err(|_| {
+                        ParseError::ParseError(format!("invalid trace depth: {}", value))
+                    })?;
+                    filter.depths.push((comparison, value))
+                }
+                _ => {
+                    return Err(ParseError::ParseError(format!("unknown trace filter key: {}", key)))
+                }
+            }
+        }
+        Ok(filter)
+    }
+}
+
+/// literal that a column is compared against in a row filter
+#[derive(Clone, Debug, PartialEq)]
+pub enum FilterValue {
+    /// non-negative integer, compared exactly against u256 columns
+    Integer(U256),
+    /// negative or fractional number
+    Float(f64),
+    /// 0x-prefixed bytes, compared against binary or hex columns
+    Bytes(Vec<u8>),
+    /// quoted string
+    String(String),
+    /// true or false
+    Bool(bool),
+}
+
+/// boolean expression over the columns of a dataset, evaluated on each chunk before writing
+///
+/// e.g. `value > 1000000000000000000 && (topic0 == 0x... || !(log_index < 10))`
+#[derive(Clone, Debug, PartialEq)]
+pub enum RowFilter {
+    /// comparison of a column with a literal
+    Compare {
+        /// column name, where u256 columns can be referenced without their type suffix
+        column: String,
+        /// comparison operator
+        comparison: Comparison,
+        /// literal compared against
+        value: FilterValue,
+    },
+    /// negation of a filter
+    Not(Box<RowFilter>),
+    /// conjunction of two filters
+    And(Box<RowFilter>, Box<RowFilter>),
+    /// disjunction of two filters
+    Or(Box<RowFilter>, Box<RowFilter>),
+}
+
+/// columns that a u256 column can be compared through, in order of preference
+const U256_FILTER_TYPES: [U256Type; 5] =
+    [U256Type::Binary, U256Type::U64, U256Type::U32, U256Type::F64, U256Type::F32];
+
+impl RowFilter {
+    /// AND filter with another filter
+    pub fn and(self, other: RowFilter) -> RowFilter {
+        RowFilter::And(Box::new(self), Box::new(other))
+    }
+
+    /// names of columns referenced by filter
+    pub fn columns(&self) -> Vec<String> {
+        match self {
+            RowFilter::Compare { column, .. } => vec![column.clone()],
+            RowFilter::Not(filter) => filter.columns(),
+            RowFilter::And(lhs, rhs) | RowFilter::Or(lhs, rhs) => {
+                [lhs.columns(), rhs.columns()].concat()
+            }
+        }
+    }
+
+    /// names of columns that a referenced column can be read from, including u256 variants
+    pub fn column_candidates(column: &str) -> Vec<String> {
+        let mut candidates = vec![column.to_string()];
+        candidates.extend(U256_FILTER_TYPES.iter().map(|t| format!("{}{}", column, t.suffix())));
+        candidates
+    }
+
+    /// keep only rows of dataframe that pass filter
+    pub fn apply(&self, df: DataFrame) -> Result<DataFrame, CollectError> {
+        let expr = self.to_expr(&df.schema())?;
+        df.lazy()
+            .filter(expr)
+            .collect()
+            .map_err(|e| CollectError::CollectError(format!("row filter failed: {}", e)))
+    }
+
+    /// convert filter to polars expression, resolving literals according to column types
+    pub fn to_expr(&self, schema: &Schema) -> Result<Expr, CollectError> {
+        match self {
+            RowFilter::Compare { column, comparison, value } => {
+                let (name, dtype) = Self::column_candidates(column)
+                    .into_iter()
+                    .find_map(|name| schema.get(&name).map(|dtype| (name, dtype.clone())))
+                    .ok_or_else(|| {
+                        CollectError::CollectError(format!(
+                            "row filter references missing column: {}",
+                            column
+                        ))
+                    })?;
+                let value = filter_literal(&dtype, value).ok_or_else(|| {
+                    CollectError::CollectError(format!(
+                        "row filter cannot compare column {} of type {} with {:?}",
+                        name, dtype, value
+                    ))
+                })?;
+                Ok(comparison.to_expr(col(&name), value))
+            }
+            RowFilter::Not(filter) => Ok(filter.to_expr(schema)?.not()),
+            RowFilter::And(lhs, rhs) => Ok(lhs.to_expr(schema)?.and(rhs.to_expr(schema)?)),
+            RowFilter::Or(lhs, rhs) => Ok(lhs.to_expr(schema)?.or(rhs.to_expr(schema)?)),
+        }
+    }
+}
+
+/// convert value to literal comparable with column of dtype
+///
+/// integers are compared against binary and hex columns as 32 byte big endian words, whose
+/// lexicographic order matches their numeric order
+fn filter_literal(dtype: &DataType, value: &FilterValue) -> Option<Expr> {
+    let word = |value: &U256| {
+        let mut bytes = [0u8; 32];
+        value.to_big_endian(&mut bytes);
+        bytes.to_vec()
+    };
+    match (dtype, value) {
+        (DataType::Binary, FilterValue::Bytes(bytes)) => Some(lit(bytes.clone())),
+        (DataType::Binary, FilterValue::Integer(value)) => Some(lit(word(value))),
+        (DataType::Utf8, FilterValue::String(string)) => Some(lit(string.clone())),
+        (DataType::Utf8, FilterValue::Bytes(bytes)) => Some(lit(prefix_hex::encode(bytes.clone()))),
+        (DataType::Utf8, FilterValue::Integer(value)) => Some(lit(prefix_hex::encode(word(value)))),
+        (DataType::Boolean, FilterValue::Bool(value)) => Some(lit(*value)),
+        (dtype, FilterValue::Integer(value)) if dtype.is_numeric() => match value.bits() <= 64 {
+            true => Some(lit(value.as_u64())),
+            false => value.to_string().parse::<f64>().ok().map(lit),
+        },
+        (dtype, FilterValue::Float(value)) if dtype.is_numeric() => Some(lit(*value)),
+        _ => None,
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum FilterToken {
+    Column(String),
+    Value(FilterValue),
+    Comparison(Comparison),
+    And,
+    Or,
+    Not,
+    Open,
+    Close,
+}
+
+fn tokenize_filter(s: &str) -> Result<Vec<FilterToken>, ParseError> {
+    let invalid =
+        |message: &str| ParseError::ParseError(format!("invalid filter {}: {}", s, message));
+    let chars: Vec<char> = s.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let (c, next) = (chars[i], chars.get(i + 1).copied());
+        let (token, width) = match (c, next) {
+            (c, _) if c.is_whitespace() => {
+                i += 1;
+                continue
+            }
+            ('&', Some('&')) => (FilterToken::And, 2),
+            ('|', Some('|')) => (FilterToken::Or, 2),
+            ('=', Some('=')) => (FilterToken::Comparison(Comparison::Equal), 2),
+            ('!', Some('=')) => (FilterToken::Comparison(Comparison::NotEqual), 2),
+            ('<', Some('=')) => (FilterToken::Comparison(Comparison::LessThanOrEqual), 2),
+            ('>', Some('=')) => (FilterToken::Comparison(Comparison::GreaterThanOrEqual), 2),
+            ('=', _) => (FilterToken::Comparison(Comparison::Equal), 1),
+            ('<', _) => (FilterToken::Comparison(Comparison::LessThan), 1),
+            ('>', _) => (FilterToken::Comparison(Comparison::GreaterThan), 1),
+            ('!', _) => (FilterToken::Not, 1),
+            ('(', _) => (FilterToken::Open, 1),
+            (')', _) => (FilterToken::Close, 1),
+            ('"', _) | ('\'', _) => {
+                let end = chars[i + 1..]
+                    .iter()
+                    .position(|x| *x == c)
+                    .ok_or_else(|| invalid("unterminated string"))?;
+                let string: String = chars[i + 1..i + 1 + end].iter().collect();
+                (FilterToken::Value(FilterValue::String(string)), end + 2)
+            }
+            (c, _) if c.is_ascii_digit() || c == '-' || c == '.' => {
+                let width = 1 + chars[i + 1..]
+                    .iter()
+                    .take_while(|x| x.is_ascii_alphanumeric() || **x == '.' || **x == '_')
+                    .count();
+                let raw: String = chars[i..i + width].iter().collect();
+                let value = parse_filter_number(&raw).ok_or_else(|| invalid(&raw))?;
+                (FilterToken::Value(value), width)
+            }
+            (c, _) if c.is_alphabetic() || c == '_' => {
+                let width =
+                    chars[i..].iter().take_while(|x| x.is_alphanumeric() || **x == '_').count();
+                let word: String = chars[i..i + width].iter().collect();
+                let token = match word.as_str() {
+                    "true" => FilterToken::Value(FilterValue::Bool(true)),
+                    "false" => FilterToken::Value(FilterValue::Bool(false)),
+                    _ => FilterToken::Column(word),
+                };
+                (token, width)
+            }
+            (c, _) => return Err(invalid(&format!("unexpected character {}", c))),
+        };
+        tokens.push(token);
+        i += width;
+    }
+    Ok(tokens)
+}
+
+/// parse number, which can be hex bytes, an integer with optional exponent, or a float
+fn parse_filter_number(raw: &str) -> Option<FilterValue> {
+    let raw = raw.replace('_', "");
+    if let Some(hex) = raw.strip_prefix("0x") {
+        let hex = if hex.len() % 2 == 1 { format!("0{}", hex) } else { hex.to_string() };
+        return prefix_hex::decode(format!("0x{}", hex.to_lowercase())).ok().map(FilterValue::Bytes)
+    }
+    if raw.chars().all(|c| c.is_ascii_digit()) {
+        return U256::from_dec_str(&raw).ok().map(FilterValue::Integer)
+    }
+    if let Some((mantissa, exponent)) = raw.split_once(['e', 'E']) {
+        if let (Ok(mantissa), Ok(exponent)) =
+            (U256::from_dec_str(mantissa), exponent.parse::<u32>())
+        {
+            let value = U256::from(10).checked_pow(U256::from(exponent))?;
+            return mantissa.checked_mul(value).map(FilterValue::Integer)
+        }
+    }
+    raw.parse::<f64>().ok().filter(|value| value.is_finite()).map(FilterValue::Float)
+}
+
+struct FilterParser {
+    tokens: Vec<FilterToken>,
+    position: usize,
+}
+
+impl FilterParser {
+    fn next_if(&mut self, token: &FilterToken) -> bool {
+        let matches = self.tokens.get(self.position) == Some(token);
+        if matches {
+            self.position += 1;
+        }
+        matches
+    }
+
+    fn parse_or(&mut self) -> Result<RowFilter, String> {
+        let mut filter = self.parse_and()?;
+        while self.next_if(&FilterToken::Or) {
+            filter = RowFilter::Or(Box::new(filter), Box::new(self.parse_and()?));
+        }
+        Ok(filter)
+    }
+
+    fn parse_and(&mut self) -> Result<RowFilter, String> {
+        let mut filter = self.parse_unary()?;
+        while self.next_if(&FilterToken::And) {
+            filter = filter.and(self.parse_unary()?);
+        }
+        Ok(filter)
+    }
+
+    fn parse_unary(&mut self) -> Result<RowFilter, String> {
+        if self.next_if(&FilterToken::Not) {
+            return Ok(RowFilter::Not(Box::new(self.parse_unary()?)))
+        }
+        if self.next_if(&FilterToken::Open) {
+            let filter = self.parse_or()?;
+            if !self.next_if(&FilterToken::Close) {
+                return Err("missing closing parenthesis".to_string())
+            }
+            return Ok(filter)
+        }
+        let tokens = self.tokens.get(self.position..self.position + 3);
+        match tokens {
+            Some(
+                [FilterToken::Column(column), FilterToken::Comparison(comparison), FilterToken::Value(value)],
+            ) => {
+                let filter = RowFilter::Compare {
+                    column: column.clone(),
+                    comparison: *comparison,
+                    value: value.clone(),
+                };
+                self.position += 3;
+                Ok(filter)
+            }
+            _ => Err("expected comparison of the form `column <operator> value`".to_string()),
+        }
+    }
+}
+
+impl FromStr for RowFilter {
+    type Err = ParseError;
+
+    /// parse filter of the form `value > 1e18 && (topic0 == 0x... || !(gas_used < 21000))`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parser = FilterParser { tokens: tokenize_filter(s)?, position: 0 };
+        let filter = parser.parse_or();
+        match (filter, parser.position == parser.tokens.len()) {
+            (Ok(filter), true) => Ok(filter),
+            (Ok(_), false) => Err("unexpected trailing tokens".to_string()),
+            (Err(e), _) => Err(e),
+        }
+        .map_err(|e| ParseError::ParseError(format!("invalid filter {}: {}", s, e)))
+    }
+}
+
+fn parse_address(value: &str) -> Result<Vec<u8>, ParseError> {
+    let address: Vec<u8> = prefix_hex::decode(value.to_lowercase())
+        .map_err(|_| ParseError::ParseError(format!("invalid address: {}", value)))?;
+    if address.len() != 20 {
+        return Err(ParseError::ParseError(format!("invalid address: {}", value)))
+    }
+    Ok(address)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use polars::prelude::NamedFrom;
+
+    #[test]
+    fn test_parse_trace_filter() {
+        let filter = TraceFilter::from_str(
+            "calltype=delegatecall,depth<=2,to=0x6b175474e89094c44da98b954eedeac495271d0f",
+        )
+        .unwrap();
+        assert_eq!(filter.call_types, vec!["delegatecall"]);
+        assert_eq!(filter.depths, vec![(Comparison::LessThanOrEqual, 2)]);
+        assert_eq!(filter.to_addresses.len(), 1);
+        assert!(filter.depth_matches(2));
+        assert!(!filter.depth_matches(3));
+
+        // keys are normalized before checking comparisons, and operators match longest first
+        let filter = TraceFilter::from_str("Depth>=1, call_type = static_call").unwrap();
+        assert_eq!(filter.depths, vec![(Comparison::GreaterThanOrEqual, 1)]);
+        assert_eq!(filter.call_types, vec!["staticcall"]);
+        assert!(filter.depth_matches(1));
+        assert!(filter.check_supported(&Datatype::Traces).is_ok());
+        assert!(filter.check_supported(&Datatype::VmTraces).is_err());
+        assert!(TraceFilter::from_str("depth<2")
+            .unwrap()
+            .check_supported(&Datatype::VmTraces)
+            .is_ok());
+
+        assert!(TraceFilter::from_str("calltype<=call").is_err());
+        assert!(TraceFilter::from_str("depth>").is_err());
+        assert!(TraceFilter::from_str("depth!2").is_err());
+        assert!(TraceFilter::from_str("foo=bar").is_err());
+        assert!(TraceFilter::from_str("to=0x1234").is_err());
+    }
+
+    #[test]
+    fn test_row_filter() {
+        let filter = RowFilter::from_str(
+            "value > 1e18 && (topic0 == 0xddf2 || !(log_index < 2)) && name != 'x'",
+        )
+        .unwrap();
+        assert_eq!(filter.columns(), vec!["value", "topic0", "log_index", "name"]);
+
+        let word = |value: U256| {
+            let mut bytes = [0u8; 32];
+            value.to_big_endian(&mut bytes);
+            bytes.to_vec()
+        };
+        let values = [U256::one(), U256::exp10(19), U256::exp10(18) * 2];
+        let df = polars::df!(
+            "value_binary" => values.map(word),
+            "topic0" => [vec![0xdd, 0xf2], vec![0xdd, 0xf2], vec![0xdd, 0xf2]],
+            "log_index" => [0u32, 1, 3],
+            "name" => ["y", "y", "x"],
+        )
+        .unwrap();
+        let filtered = filter.apply(df).unwrap();
+        assert_eq!(filtered.height(), 1);
+
+        assert!(RowFilter::from_str("value >").is_err());
+        assert!(RowFilter::from_str("(value > 1").is_err());
+        assert!(RowFilter::from_str("value > 1 value").is_err());
+    }
+}
@@ -0,0 +1,158 @@
+/// recording and replaying of the json-rpc responses of a node, for tests without a node
+use std::{
+    collections::HashMap,
+    io::{BufRead, Write},
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+use ethers::prelude::*;
+use serde_json::{json, Value};
+
+use crate::{CollectError, TransportError};
+
+/// json-rpc responses of a node, keyed by method and params, stored in a json lines file with
+/// one request per line, ex: `{"method": "eth_blockNumber", "params": null, "result": "0x10"}`
+///
+/// error responses are stored with an `"error"` instead of a `"result"`. fixtures that are
+/// recorded to an existing file are appended to it
+#[derive(Debug)]
+pub struct Fixtures {
+    path: PathBuf,
+    responses: Mutex<HashMap<String, Value>>,
+}
+
+impl Fixtures {
+    /// load fixtures of file at path, which is created when recording if it does not exist
+    pub fn load(path: &Path) -> Result<Fixtures, CollectError> {
+        let mut responses = HashMap::new();
+        if path.exists() {
+            let file = std::fs::File::open(path).map_err(|e| {
+                CollectError::CollectError(format!("could not open {}: {}", path.display(), e))
+            })?;
+            for (i, line) in std::io::BufReader::new(file).lines().enumerate() {
+                let line = line.map_err(|e| CollectError::CollectError(e.to_string()))?;
+                if line.trim().is_empty() {
+                    continue
+                }
+                let fixture: Value = serde_json::from_str(&line).map_err(|_| {
+                    CollectError::CollectError(format!(
+                        "invalid fixture on line {} of {}",
+                        i + 1,
+                        path.display()
+                    ))
+                })?;
+                let method = fixture.get("method").and_then(|method| method.as_str());
+                let params = fixture.get("params").cloned().unwrap_or(Value::Null);
+                match method {
+                    Some(method) => responses.insert(fixture_key(method, &params), fixture),
+                    None => {
+                        return Err(CollectError::CollectError(format!(
+                            "fixture on line {} of {} has no method",
+                            i + 1,
+                            path.display()
+                        )))
+                    }
+                };
+            }
+        }
+        Ok(Fixtures { path: path.to_path_buf(), responses: Mutex::new(responses) })
+    }
+
+    /// number of recorded requests
+    pub fn len(&self) -> usize {
+        self.responses.lock().map(|responses| responses.len()).unwrap_or(0)
+    }
+
+    /// whether no requests are recorded
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// recorded response of request, which is an error if the node responded with an error
+    pub(crate) fn get(
+        &self,
+        method: &str,
+        params: &Value,
+    ) -> Result<Result<Value, JsonRpcError>, TransportError> {
+        let key = fixture_key(method, params);
+        let responses = self.responses.lock().map_err(|_| TransportError::Missing(key.clone()))?;
+        let fixture = responses.get(&key).ok_or_else(|| TransportError::Missing(key.clone()))?;
+        match (fixture.get("result"), fixture.get("error")) {
+            (_, Some(error)) => Ok(Err(JsonRpcError {
+                code: error.get("code").and_then(|code| code.as_i64()).unwrap_or_default(),
+                message: error
+                    .get("message")
+                    .and_then(|message| message.as_str())
+                    .unwrap_or_default()
+                    .to_string(),
+                data: error.get("data").cloned(),
+            })),
+            (result, None) => Ok(Ok(result.cloned().unwrap_or(Value::Null))),
+        }
+    }
+
+    /// record response of request, appending it to the fixtures file if it is new
+    pub(crate) fn record(
+        &self,
+        method: &str,
+        params: &Value,
+        response: Result<&Value, &JsonRpcError>,
+    ) {
+        let mut fixture = json!({"method": method, "params": params});
+        match response {
+            Ok(result) => fixture["result"] = result.clone(),
+            Err(error) => {
+                fixture["error"] =
+                    json!({"code": error.code, "message": error.message, "data": error.data})
+            }
+        }
+        let key = fixture_key(method, params);
+        let Ok(mut responses) = self.responses.lock() else { return };
+        if responses.contains_key(&key) {
+            return
+        }
+        let result = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .and_then(|mut file| writeln!(file, "{}", fixture));
+        match result {
+            Ok(()) => {
+                responses.insert(key, fixture);
+            }
+            Err(e) => tracing::warn!(method, error = %e, "could not record fixture"),
+        }
+    }
+}
+
+fn fixture_key(method: &str, params: &Value) -> String {
+    format!("{}({})", method, params)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::RpcTransport;
+
+    #[tokio::test]
+    async fn test_replay_fixtures() {
+        let path = std::env::temp_dir().join(format!("cryo_fixtures_{}.jsonl", std::process::id()));
+        let lines = [
+            r#"{"method": "eth_blockNumber", "params": null, "result": "0x10"}"#,
+            r#"{"method": "eth_getCode", "params": ["0x0000000000000000000000000000000000000001", "0x1"], "error": {"code": -32000, "message": "missing trie node"}}"#,
+        ];
+        std::fs::write(&path, lines.join("\n")).unwrap();
+        let fixtures = Fixtures::load(&path).unwrap();
+        assert_eq!(fixtures.len(), 2);
+        let provider = Provider::new(RpcTransport::Replay(fixtures));
+
+        assert_eq!(provider.get_block_number().await.unwrap(), U64::from(16));
+        let address = H160::from_low_u64_be(1);
+        let error = provider.get_code(address, Some(1u64.into())).await.unwrap_err();
+        assert_eq!(RpcError::as_error_response(&error).unwrap().message, "missing trie node");
+        let error = provider.get_chainid().await.unwrap_err();
+        assert!(error.to_string().contains("no fixture of request eth_chainId(null)"));
+        let _ = std::fs::remove_file(path);
+    }
+}
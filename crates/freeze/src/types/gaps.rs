@@ -0,0 +1,185 @@
+/// detection of block ranges missing from the output files of a dataset
+use std::path::{Path, PathBuf};
+
+use polars::prelude::*;
+
+use crate::{CollectError, Datatype};
+
+/// block range covered by an output file of a dataset
+#[derive(Clone, Debug, PartialEq)]
+pub struct FileBlockRange {
+    /// path of file
+    pub path: PathBuf,
+    /// first block of file
+    pub start_block: u64,
+    /// last block of file
+    pub end_block: u64,
+}
+
+/// find block ranges covered by the output files of datatype in directory and its subdirectories
+///
+/// ranges are taken from file names when they contain a block chunk label such as
+/// `00001000_to_00001999`, and otherwise from the block_number statistics of parquet files, so
+/// that chunks without any rows are not reported as gaps
+pub fn find_file_block_ranges(
+    dir: &Path,
+    datatype: &Datatype,
+) -> Result<Vec<FileBlockRange>, CollectError> {
+    let pattern = format!("__{}__", datatype.name());
+    let mut ranges = Vec::new();
+    for path in list_files(dir)?.into_iter() {
+        let name = match path.file_name().and_then(|name| name.to_str()) {
+            Some(name) if name.contains(&pattern) && !crate::dataframes::is_tmp_file(name) => name,
+            _ => continue,
+        };
+        let range = match label_block_range(name, &pattern) {
+            Some(range) => Some(range),
+            None if name.ends_with(".parquet") => parquet_block_range(&path)?,
+            None => None,
+        };
+        if let Some((start_block, end_block)) = range {
+            ranges.push(FileBlockRange { path, start_block, end_block });
+        }
+    }
+    ranges.sort_by_key(|range| (range.start_block, range.end_block));
+    Ok(ranges)
+}
+
+/// find block ranges of the chunks of datatype that were collected without rows, and whose files
+/// were not written because of --skip-empty-files
+///
+/// the returned paths are those of the markers in `.cryo/empty` of directory
+pub fn find_empty_block_ranges(
+    dir: &Path,
+    datatype: &Datatype,
+) -> Result<Vec<FileBlockRange>, CollectError> {
+    let pattern = format!("__{}__", datatype.name());
+    let marker_dir = dir.join(".cryo/empty");
+    if !marker_dir.is_dir() {
+        return Ok(Vec::new())
+    }
+    let mut ranges = Vec::new();
+    for path in list_files(&marker_dir)?.into_iter() {
+        let range = path.file_name().and_then(|name| name.to_str()).and_then(|name| {
+            name.contains(&pattern).then(|| label_block_range(name, &pattern)).flatten()
+        });
+        if let Some((start_block, end_block)) = range {
+            ranges.push(FileBlockRange { path, start_block, end_block });
+        }
+    }
+    ranges.sort_by_key(|range| (range.start_block, range.end_block));
+    Ok(ranges)
+}
+
+/// block range of chunk label following pattern in file name, ex: `00001000_to_00001999`
+fn label_block_range(name: &str, pattern: &str) -> Option<(u64, u64)> {
+    let label = regex::Regex::new(r"^(\d+)_to_(\d+)").expect("valid regex");
+    let suffix = &name[name.find(pattern)? + pattern.len()..];
+    let captures = label.captures(suffix)?;
+    match (captures[1].parse(), captures[2].parse()) {
+        (Ok(start), Ok(end)) => Some((start, end)),
+        _ => None,
+    }
+}
+
+/// find block ranges not covered by any of the given ranges
+///
+/// gaps are searched for between the first and last covered block, or within bounds if given
+pub fn find_block_gaps(ranges: &[(u64, u64)], bounds: Option<(u64, u64)>) -> Vec<(u64, u64)> {
+    let mut ranges = ranges.to_vec();
+    ranges.sort();
+    let (start, end) = match (bounds, ranges.first(), ranges.iter().map(|r| r.1).max()) {
+        (Some(bounds), _, _) => bounds,
+        (None, Some((start, _)), Some(end)) => (*start, end),
+        _ => return Vec::new(),
+    };
+    let mut gaps = Vec::new();
+    let mut cursor = start;
+    for (range_start, range_end) in ranges.into_iter() {
+        if cursor > end {
+            break
+        }
+        if range_start > cursor {
+            gaps.push((cursor, (range_start - 1).min(end)));
+        }
+        cursor = cursor.max(range_end.saturating_add(1));
+    }
+    if cursor <= end {
+        gaps.push((cursor, end));
+    }
+    gaps
+}
+
+fn list_files(dir: &Path) -> Result<Vec<PathBuf>, CollectError> {
+    let entries = std::fs::read_dir(dir).map_err(|e| {
+        CollectError::CollectError(format!("could not read directory {}: {}", dir.display(), e))
+    })?;
+    let mut files = Vec::new();
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let hidden = entry.file_name().to_str().is_some_and(|name| name.starts_with('.'));
+        if path.is_dir() {
+            // skip directories such as .cryo, which holds manifests named after output files
+            if !hidden {
+                files.extend(list_files(&path)?);
+            }
+        } else {
+            files.push(path);
+        }
+    }
+    Ok(files)
+}
+
+/// min and max block_number of parquet file, read from its statistics if it has them
+fn parquet_block_range(path: &Path) -> Result<Option<(u64, u64)>, CollectError> {
+    use polars::export::arrow::io::parquet::read;
+
+    let file_error =
+        |e: String| CollectError::CollectError(format!("could not read {}: {}", path.display(), e));
+    let mut file = std::fs::File::open(path).map_err(|e| file_error(e.to_string()))?;
+    let metadata = read::read_metadata(&mut file).map_err(|e| file_error(e.to_string()))?;
+    let schema = read::infer_schema(&metadata).map_err(|e| file_error(e.to_string()))?;
+    let field = match schema.fields.iter().find(|field| field.name == "block_number") {
+        Some(field) => field,
+        None => return Ok(None),
+    };
+    if let Ok(statistics) = read::statistics::deserialize(field, &metadata.row_groups) {
+        let min = Series::try_from(("min", statistics.min_value))
+            .and_then(|s| s.cast(&DataType::UInt64))
+            .map(|s| s.u64().ok().and_then(|s| s.min()));
+        let max = Series::try_from(("max", statistics.max_value))
+            .and_then(|s| s.cast(&DataType::UInt64))
+            .map(|s| s.u64().ok().and_then(|s| s.max()));
+        if let (Ok(Some(min)), Ok(Some(max))) = (min, max) {
+            return Ok(Some((min, max)))
+        }
+    }
+
+    // files written without statistics are scanned
+    let file = std::fs::File::open(path).map_err(|e| file_error(e.to_string()))?;
+    let df = ParquetReader::new(file)
+        .with_columns(Some(vec!["block_number".to_string()]))
+        .finish()
+        .map_err(|e| file_error(e.to_string()))?;
+    let column = df.column("block_number")?.cast(&DataType::UInt64)?;
+    let column = column.u64()?;
+    Ok(column.min().zip(column.max()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_block_gaps() {
+        let ranges = [(2000, 2999), (0, 999), (5000, 5999), (5500, 6499)];
+        assert_eq!(find_block_gaps(&ranges, None), vec![(1000, 1999), (3000, 4999)]);
+        assert_eq!(
+            find_block_gaps(&ranges, Some((500, 7999))),
+            vec![(1000, 1999), (3000, 4999), (6500, 7999)]
+        );
+        assert_eq!(find_block_gaps(&ranges, Some((1500, 2500))), vec![(1500, 1999)]);
+        assert_eq!(find_block_gaps(&[], Some((0, 9))), vec![(0, 9)]);
+        assert!(find_block_gaps(&[], None).is_empty());
+    }
+}
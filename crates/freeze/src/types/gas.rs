@@ -0,0 +1,229 @@
+/// gas accounting of transactions: intrinsic gas, calldata cost, and refunds
+use ethers::prelude::*;
+
+/// columns of transactions computed from the gas accounting of each transaction
+pub const GAS_ACCOUNTING_COLUMNS: [&str; 5] =
+    ["intrinsic_gas", "calldata_gas", "calldata_floor_gas", "execution_gas", "gas_refund"];
+
+/// columns of gas accounting that need the top-level trace of each transaction
+pub const EXECUTION_GAS_COLUMNS: [&str; 2] = ["execution_gas", "gas_refund"];
+
+/// gas charged for every transaction
+const TX_BASE_GAS: u64 = 21_000;
+/// gas charged for a contract creation since homestead
+const TX_CREATE_GAS: u64 = 32_000;
+/// gas charged per 32 byte word of init code since shanghai (eip-3860)
+const INITCODE_WORD_GAS: u64 = 2;
+/// gas charged per zero byte of calldata
+const CALLDATA_ZERO_GAS: u64 = 4;
+/// gas charged per nonzero byte of calldata since istanbul (eip-2028)
+const CALLDATA_NONZERO_GAS: u64 = 16;
+/// gas charged per nonzero byte of calldata before istanbul
+const CALLDATA_NONZERO_GAS_FRONTIER: u64 = 68;
+/// gas charged per address of an access list (eip-2930)
+const ACCESS_LIST_ADDRESS_GAS: u64 = 2_400;
+/// gas charged per storage key of an access list (eip-2930)
+const ACCESS_LIST_STORAGE_KEY_GAS: u64 = 1_900;
+/// gas charged per authorization of a set code transaction (eip-7702)
+const AUTHORIZATION_GAS: u64 = 25_000;
+/// gas charged per calldata token by the calldata floor of prague (eip-7623)
+const FLOOR_TOKEN_GAS: u64 = 10;
+
+/// gas rules in force at a block, as far as they concern intrinsic gas
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct GasRules {
+    /// contract creations pay TX_CREATE_GAS
+    pub homestead: bool,
+    /// nonzero calldata bytes cost 16 gas rather than 68
+    pub istanbul: bool,
+    /// init code pays per word
+    pub shanghai: bool,
+    /// calldata is subject to the eip-7623 floor
+    pub prague: bool,
+}
+
+impl GasRules {
+    /// rules of the latest fork
+    pub fn latest() -> GasRules {
+        GasRules { homestead: true, istanbul: true, shanghai: true, prague: true }
+    }
+
+    /// rules at block of chain, using fork blocks of mainnet and the latest rules elsewhere
+    pub fn at_block(chain_id: u64, block_number: Option<u64>) -> GasRules {
+        match (chain_id, block_number) {
+            (1, Some(block_number)) => GasRules {
+                homestead: block_number >= 1_150_000,
+                istanbul: block_number >= 9_069_000,
+                shanghai: block_number >= 17_034_870,
+                prague: block_number >= 22_431_084,
+            },
+            _ => GasRules::latest(),
+        }
+    }
+}
+
+/// gas accounting of a transaction
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct GasAccounting {
+    /// gas charged before execution
+    pub intrinsic_gas: u64,
+    /// part of intrinsic gas charged for calldata
+    pub calldata_gas: u64,
+    /// minimum gas used by the transaction under eip-7623, None before prague
+    pub calldata_floor_gas: Option<u64>,
+    /// gas used by execution before refunds, None if no trace was fetched
+    pub execution_gas: Option<u64>,
+    /// gas refunded at the end of the transaction, None if it cannot be determined
+    pub gas_refund: Option<u64>,
+}
+
+/// number of zero and nonzero bytes of calldata
+fn count_bytes(input: &[u8]) -> (u64, u64) {
+    let zeros = input.iter().filter(|byte| **byte == 0).count() as u64;
+    (zeros, input.len() as u64 - zeros)
+}
+
+impl GasAccounting {
+    /// intrinsic gas and calldata costs of transaction under rules
+    pub fn new(tx: &Transaction, rules: GasRules) -> GasAccounting {
+        let (zeros, nonzeros) = count_bytes(&tx.input);
+        let nonzero_gas = match rules.istanbul {
+            true => CALLDATA_NONZERO_GAS,
+            false => CALLDATA_NONZERO_GAS_FRONTIER,
+        };
+        let calldata_gas = zeros * CALLDATA_ZERO_GAS + nonzeros * nonzero_gas;
+
+        let mut intrinsic_gas = TX_BASE_GAS + calldata_gas;
+        if tx.to.is_none() {
+            if rules.homestead {
+                intrinsic_gas += TX_CREATE_GAS;
+            }
+            if rules.shanghai {
+                intrinsic_gas += tx.input.len().div_ceil(32) as u64 * INITCODE_WORD_GAS;
+            }
+        }
+        if let Some(access_list) = &tx.access_list {
+            for item in access_list.0.iter() {
+                intrinsic_gas += ACCESS_LIST_ADDRESS_GAS;
+                intrinsic_gas += item.storage_keys.len() as u64 * ACCESS_LIST_STORAGE_KEY_GAS;
+            }
+        }
+        // eip-7702 authorizations are not yet typed fields of ethers transactions
+        let n_authorizations = tx
+            .other
+            .get("authorizationList")
+            .and_then(|list| list.as_array())
+            .map(|list| list.len() as u64)
+            .unwrap_or(0);
+        intrinsic_gas += n_authorizations * AUTHORIZATION_GAS;
+
+        let calldata_floor_gas = match rules.prague {
+            true => Some(TX_BASE_GAS + FLOOR_TOKEN_GAS * (zeros + 4 * nonzeros)),
+            false => None,
+        };
+        GasAccounting {
+            intrinsic_gas,
+            calldata_gas,
+            calldata_floor_gas,
+            execution_gas: None,
+            gas_refund: None,
+        }
+    }
+
+    /// split gas used into execution gas and refund using the top-level trace of transaction
+    ///
+    /// trace_gas_used is the gas used by the top-level call or create, or Some(None) if it
+    /// failed, in which case no gas is refunded. The refund is unknown when gas used is the
+    /// calldata floor, because the floor hides how much of execution was refunded
+    pub fn with_execution(
+        mut self,
+        gas_used: Option<u64>,
+        trace_gas_used: Option<Option<u64>>,
+    ) -> Self {
+        let floored = |gas_used: u64| self.calldata_floor_gas == Some(gas_used);
+        match (gas_used, trace_gas_used) {
+            (Some(gas_used), Some(Some(execution_gas))) => {
+                self.execution_gas = Some(execution_gas);
+                self.gas_refund = match floored(gas_used) {
+                    true => None,
+                    false => Some((self.intrinsic_gas + execution_gas).saturating_sub(gas_used)),
+                };
+            }
+            (None, Some(Some(execution_gas))) => self.execution_gas = Some(execution_gas),
+            (Some(gas_used), Some(None)) if !floored(gas_used) => {
+                self.execution_gas = Some(gas_used.saturating_sub(self.intrinsic_gas));
+                self.gas_refund = Some(0);
+            }
+            _ => {}
+        }
+        self
+    }
+}
+
+/// gas used by a top-level call or create trace, Some(None) if it failed and None for other traces
+fn trace_gas_used(trace: &Trace) -> Option<Option<u64>> {
+    if !trace.trace_address.is_empty() {
+        return None
+    }
+    match (&trace.result, &trace.error) {
+        (_, Some(_)) => Some(None),
+        (Some(Res::Call(result)), None) => Some(Some(result.gas_used.as_u64())),
+        (Some(Res::Create(result)), None) => Some(Some(result.gas_used.as_u64())),
+        _ => None,
+    }
+}
+
+/// gas used by the top-level call or create of each transaction in traces of a block, by
+/// transaction index, Some(None) for failed transactions and None for transactions without a trace
+pub(crate) fn top_level_gas_used(
+    traces: &[Trace],
+    n_transactions: usize,
+) -> Vec<Option<Option<u64>>> {
+    let mut output = vec![None; n_transactions];
+    for trace in traces.iter() {
+        let slot = trace.transaction_position.and_then(|i| output.get_mut(i));
+        if let (Some(slot), Some(gas_used)) = (slot, trace_gas_used(trace)) {
+            *slot = Some(gas_used);
+        }
+    }
+    output
+}
+
+/// gas used by the top-level call or create in traces of a transaction
+pub(crate) fn transaction_top_level_gas_used(traces: &[Trace]) -> Option<Option<u64>> {
+    traces.iter().find_map(trace_gas_used)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gas_accounting() {
+        let mut tx = Transaction {
+            to: Some(H160::zero()),
+            input: vec![0, 0, 1, 2].into(),
+            ..Default::default()
+        };
+        let latest = GasAccounting::new(&tx, GasRules::latest());
+        assert_eq!(latest.calldata_gas, 2 * 4 + 2 * 16);
+        assert_eq!(latest.intrinsic_gas, 21_000 + 40);
+        assert_eq!(latest.calldata_floor_gas, Some(21_000 + 10 * (2 + 8)));
+        let frontier = GasAccounting::new(&tx, GasRules::at_block(1, Some(1)));
+        assert_eq!(frontier.calldata_gas, 2 * 4 + 2 * 68);
+        assert_eq!(frontier.calldata_floor_gas, None);
+
+        let executed = latest.clone().with_execution(Some(30_000), Some(Some(10_000)));
+        assert_eq!(executed.execution_gas, Some(10_000));
+        assert_eq!(executed.gas_refund, Some(1_040));
+        let reverted = latest.clone().with_execution(Some(30_000), Some(None));
+        assert_eq!((reverted.execution_gas, reverted.gas_refund), (Some(8_960), Some(0)));
+        let floored = latest.with_execution(Some(21_100), Some(Some(0)));
+        assert_eq!(floored.gas_refund, None);
+
+        tx.to = None;
+        tx.input = vec![1; 33].into();
+        let create = GasAccounting::new(&tx, GasRules::latest());
+        assert_eq!(create.intrinsic_gas, 21_000 + 33 * 16 + 32_000 + 2 * 2);
+    }
+}
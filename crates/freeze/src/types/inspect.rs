@@ -0,0 +1,219 @@
+/// inspection of collected output files
+use std::{
+    io::Read,
+    path::{Path, PathBuf},
+};
+
+use ethers::prelude::U256;
+use polars::prelude::*;
+
+use crate::{CollectError, ColumnType, Datatype, ToVecHex};
+
+/// what to show of an output file
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum InspectMode {
+    /// first n rows
+    Head(usize),
+    /// last n rows
+    Tail(usize),
+    /// column names and types
+    Schema,
+    /// number of rows
+    Count,
+}
+
+/// describe output file according to mode, with binary columns formatted for display
+pub fn inspect_file(path: &Path, mode: InspectMode) -> Result<String, CollectError> {
+    let datatype = file_datatype(path);
+    let output = match mode {
+        InspectMode::Count => count_file_rows(path)?.to_string(),
+        InspectMode::Schema => {
+            describe_schema(&read_output_file(path, Some(0))?, datatype.as_ref()).join("\n")
+        }
+        InspectMode::Head(n) => {
+            let df = read_output_file(path, Some(n))?;
+            format_binary_columns(df, datatype.as_ref())?.to_string()
+        }
+        InspectMode::Tail(n) => {
+            let df = read_output_file(path, None)?.tail(Some(n));
+            format_binary_columns(df, datatype.as_ref())?.to_string()
+        }
+    };
+    Ok(output)
+}
+
+/// output files at path, which is either a file or a directory searched recursively
+pub fn list_output_files(path: &Path) -> Result<Vec<PathBuf>, CollectError> {
+    if !path.is_dir() {
+        return Ok(vec![path.to_path_buf()])
+    }
+    let mut files = Vec::new();
+    for entry in std::fs::read_dir(path).map_err(io_error(path))?.flatten() {
+        let name = entry.file_name().to_string_lossy().to_string();
+        if name.starts_with('.') {
+            continue
+        }
+        let entry_path = entry.path();
+        if entry_path.is_dir() {
+            files.extend(list_output_files(&entry_path)?);
+        } else if [".parquet", ".csv", ".json"].iter().any(|ext| {
+            name.ends_with(ext) ||
+                name.ends_with(&format!("{}.gz", ext)) ||
+                name.ends_with(&format!("{}.zst", ext))
+        }) {
+            files.push(entry_path);
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// datatype of output file, identified by the `__{datatype}__` part of its name
+pub fn file_datatype(path: &Path) -> Option<Datatype> {
+    let name = path.file_name()?.to_str()?;
+    Datatype::all().into_iter().find(|datatype| name.contains(&format!("__{}__", datatype.name())))
+}
+
+/// number of rows of output file, read from metadata for parquet files
+fn count_file_rows(path: &Path) -> Result<usize, CollectError> {
+    if path.extension().and_then(|e| e.to_str()) == Some("parquet") {
+        let mut file = open(path)?;
+        return Ok(ParquetReader::new(&mut file).num_rows()?)
+    }
+    Ok(read_output_file(path, None)?.height())
+}
+
+/// read parquet, csv, or json output file, reading only the first n_rows rows if given
+///
+/// csv and json files may be gzip or zstd compressed
+pub fn read_output_file(path: &Path, n_rows: Option<usize>) -> Result<DataFrame, CollectError> {
+    let name = path.file_name().and_then(|name| name.to_str()).unwrap_or_default();
+    if name.ends_with(".parquet") {
+        return Ok(ParquetReader::new(open(path)?).with_n_rows(n_rows).finish()?)
+    }
+    let (stem, mut reader): (&str, Box<dyn Read>) = match name.rsplit_once('.') {
+        Some((stem, "gz")) => (stem, Box::new(flate2::read::GzDecoder::new(open(path)?))),
+        Some((stem, "zst")) => {
+            (stem, Box::new(zstd::Decoder::new(open(path)?).map_err(io_error(path))?))
+        }
+        _ => (name, Box::new(open(path)?)),
+    };
+    let mut contents = Vec::new();
+    reader.read_to_end(&mut contents).map_err(io_error(path))?;
+    let cursor = std::io::Cursor::new(contents);
+    let df = if stem.ends_with(".csv") {
+        CsvReader::new(cursor).with_n_rows(n_rows).finish()?
+    } else if stem.ends_with(".json") {
+        let df = JsonReader::new(cursor).with_json_format(JsonFormat::Json).finish()?;
+        match n_rows {
+            Some(n_rows) => df.head(Some(n_rows)),
+            None => df,
+        }
+    } else {
+        return Err(CollectError::CollectError(format!("unknown file format: {}", path.display())))
+    };
+    Ok(df)
+}
+
+/// convert binary columns into strings for display
+///
+/// u256 columns of datatype stored as 32 byte binary are shown as decimal integers, and other
+/// binary columns as hex
+pub fn format_binary_columns(
+    df: DataFrame,
+    datatype: Option<&Datatype>,
+) -> Result<DataFrame, CollectError> {
+    let column_types = datatype.map(|datatype| datatype.column_types()).unwrap_or_default();
+    let mut columns = Vec::new();
+    for series in df.get_columns().iter() {
+        if series.dtype() != &DataType::Binary {
+            columns.push(series.clone());
+            continue
+        }
+        let values: Vec<Option<Vec<u8>>> =
+            series.binary()?.into_iter().map(|value| value.map(|v| v.to_vec())).collect();
+        let is_u256 = series
+            .name()
+            .strip_suffix("_binary")
+            .and_then(|stem| column_types.get(stem))
+            .is_some_and(|column_type| *column_type == ColumnType::UInt256);
+        let strings: Vec<Option<String>> = match is_u256 {
+            true => values
+                .iter()
+                .map(|value| {
+                    value.as_ref().map(|v| match v.len() {
+                        32 => U256::from_big_endian(v).to_string(),
+                        _ => prefix_hex::encode(v.clone()),
+                    })
+                })
+                .collect(),
+            false => values.to_vec_hex(),
+        };
+        columns.push(Series::new(series.name(), strings));
+    }
+    Ok(DataFrame::new(columns)?)
+}
+
+/// describe columns of dataframe, with the dataset column type of each column if known
+fn describe_schema(df: &DataFrame, datatype: Option<&Datatype>) -> Vec<String> {
+    let column_types = datatype.map(|datatype| datatype.column_types()).unwrap_or_default();
+    df.get_columns()
+        .iter()
+        .map(|series| {
+            let column_type = column_types.get(series.name()).or_else(|| {
+                series.name().rsplit_once('_').and_then(|(stem, _)| column_types.get(stem))
+            });
+            match column_type {
+                Some(column_type) => {
+                    format!("{}: {} ({})", series.name(), series.dtype(), column_type.as_str())
+                }
+                None => format!("{}: {}", series.name(), series.dtype()),
+            }
+        })
+        .collect()
+}
+
+fn open(path: &Path) -> Result<std::fs::File, CollectError> {
+    std::fs::File::open(path).map_err(io_error(path))
+}
+
+fn io_error(path: &Path) -> impl Fn(std::io::Error) -> CollectError + '_ {
+    move |e| CollectError::CollectError(format!("could not read {}: {}", path.display(), e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use polars::prelude::NamedFrom;
+
+    #[test]
+    fn test_format_binary_columns() {
+        let value = {
+            let mut bytes = [0u8; 32];
+            U256::from(1000).to_big_endian(&mut bytes);
+            bytes.to_vec()
+        };
+        let df = df!(
+            "block_number" => [1u32],
+            "from_address" => [vec![0xabu8, 0xcd]],
+            "value_binary" => [value],
+        )
+        .unwrap();
+        let df = format_binary_columns(df, Some(&Datatype::Transactions)).unwrap();
+        let strings = |column: &str| -> Vec<Option<String>> {
+            df.column(column)
+                .unwrap()
+                .utf8()
+                .unwrap()
+                .into_iter()
+                .map(|s| s.map(String::from))
+                .collect()
+        };
+        assert_eq!(strings("from_address"), vec![Some("0xabcd".to_string())]);
+        assert_eq!(strings("value_binary"), vec![Some("1000".to_string())]);
+        assert_eq!(
+            file_datatype(Path::new("out/ethereum__transactions__00000000_to_00000999.parquet")),
+            Some(Datatype::Transactions)
+        );
+    }
+}
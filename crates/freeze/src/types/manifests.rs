@@ -0,0 +1,224 @@
+/// manifests recording the block hashes that output files were collected at
+use std::{
+    collections::{HashMap, HashSet},
+    path::{Path, PathBuf},
+};
+
+use futures::{stream, StreamExt, TryStreamExt};
+
+use crate::{err, ChunkData, CollectError, FileOutput, Partition, Query, Source};
+
+/// block hash of the last block of an output file, recorded when the file is written
+///
+/// the hash of the last block commits to every earlier block, so a reorg affecting any block of
+/// the file changes it
+#[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct ChunkManifest {
+    /// last block number of chunk
+    pub block_number: u64,
+    /// hash of last block of chunk
+    pub block_hash: String,
+}
+
+/// path of manifest of output file
+pub fn get_manifest_path(sink: &FileOutput, output_path: &Path) -> Option<PathBuf> {
+    let filename = output_path.file_name()?.to_str()?;
+    Some(sink.output_dir.join(".cryo/manifests").join(format!("{}.json", filename)))
+}
+
+/// read manifest of output file, if it has one
+pub fn read_manifest(sink: &FileOutput, output_path: &Path) -> Option<ChunkManifest> {
+    let contents = std::fs::read_to_string(get_manifest_path(sink, output_path)?).ok()?;
+    serde_json::from_str(&contents).ok()
+}
+
+pub(crate) fn write_manifest(
+    sink: &FileOutput,
+    output_path: &Path,
+    manifest: &ChunkManifest,
+) -> Result<(), CollectError> {
+    let path = get_manifest_path(sink, output_path).ok_or(err("invalid output path"))?;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|_| err("could not create manifest dir"))?;
+    }
+    let serialized =
+        serde_json::to_string(manifest).map_err(|_| err("could not serialize manifest"))?;
+    let tmp_path = crate::dataframes::tmp_path(&path);
+    std::fs::write(&tmp_path, serialized)
+        .and_then(|()| std::fs::rename(&tmp_path, &path))
+        .map_err(|_| err("could not write manifest"))
+}
+
+/// remove manifest of output file, if it has one
+pub(crate) fn remove_manifest(sink: &FileOutput, output_path: &Path) -> Result<(), CollectError> {
+    match get_manifest_path(sink, output_path) {
+        Some(path) if path.exists() => {
+            std::fs::remove_file(path).map_err(|_| err("could not remove manifest"))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// path of marker recording that the chunk of output file had no rows, so no file was written
+pub fn get_empty_marker_path(sink: &FileOutput, output_path: &Path) -> Option<PathBuf> {
+    let filename = output_path.file_name()?.to_str()?;
+    Some(sink.output_dir.join(".cryo/empty").join(filename))
+}
+
+/// whether output file is marked as an empty chunk
+pub fn is_marked_empty(sink: &FileOutput, output_path: &Path) -> bool {
+    get_empty_marker_path(sink, output_path).is_some_and(|path| path.exists())
+}
+
+pub(crate) fn write_empty_marker(
+    sink: &FileOutput,
+    output_path: &Path,
+) -> Result<(), CollectError> {
+    let path = get_empty_marker_path(sink, output_path).ok_or(err("invalid output path"))?;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|_| err("could not create empty marker dir"))?;
+    }
+    std::fs::write(&path, "").map_err(|_| err("could not write empty marker"))
+}
+
+pub(crate) fn remove_empty_marker(
+    sink: &FileOutput,
+    output_path: &Path,
+) -> Result<(), CollectError> {
+    match get_empty_marker_path(sink, output_path) {
+        Some(path) if path.exists() => {
+            std::fs::remove_file(path).map_err(|_| err("could not remove empty marker"))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// last block of partition, if it is partitioned by block
+pub(crate) fn last_block(partition: &Partition) -> Option<u64> {
+    partition.block_numbers.as_ref()?.iter().filter_map(|chunk| chunk.max_value()).max()
+}
+
+/// get manifest of the current chain state at the last block of partition
+pub(crate) async fn get_chunk_manifest(
+    partition: &Partition,
+    source: &Source,
+) -> Result<Option<ChunkManifest>, CollectError> {
+    let block_number = match last_block(partition) {
+        Some(block_number) => block_number,
+        None => return Ok(None),
+    };
+    let block = source.fetcher.get_block(block_number).await?.ok_or(err("block not found"))?;
+    let block_hash = block.hash.ok_or(err("block hash not found"))?;
+    Ok(Some(ChunkManifest { block_number, block_hash: prefix_hex::encode(block_hash.0) }))
+}
+
+/// restrict query to already-collected partitions within the last `n_blocks` blocks whose last
+/// block hash changed since they were written
+///
+/// files without a manifest are always re-collected
+pub async fn recheck_partitions(
+    query: Query,
+    source: &Source,
+    sink: &FileOutput,
+    n_blocks: u64,
+) -> Result<Query, CollectError> {
+    let latest_block = source.fetcher.get_block_number().await?.as_u64();
+    let min_block = latest_block.saturating_sub(n_blocks);
+    let mut partitions = Vec::new();
+    for partition in query.partitions.iter() {
+        match last_block(partition) {
+            Some(block_number) if block_number >= min_block => {}
+            _ => continue,
+        };
+        let paths: Vec<PathBuf> = sink
+            .get_paths(&query, partition, None)?
+            .into_values()
+            .filter(|path| path.exists() || is_marked_empty(sink, path))
+            .collect();
+        if paths.is_empty() {
+            continue
+        }
+        let current = get_chunk_manifest(partition, source).await?;
+        if paths.iter().any(|path| read_manifest(sink, path) != current) {
+            partitions.push(partition.clone())
+        }
+    }
+    Ok(Query { partitions, ..query })
+}
+
+/// block hashes recorded by the manifests of all output files of sink, by block number
+pub(crate) fn read_pinned_hashes(sink: &FileOutput) -> HashMap<u64, HashSet<String>> {
+    let mut pins: HashMap<u64, HashSet<String>> = HashMap::new();
+    let dir = sink.output_dir.join(".cryo/manifests");
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return pins,
+    };
+    let prefix = format!("{}__", sink.prefix);
+    for entry in entries.flatten() {
+        let name = entry.file_name().to_string_lossy().to_string();
+        if !name.starts_with(&prefix) || !name.ends_with(".json") {
+            continue
+        }
+        let manifest = std::fs::read_to_string(entry.path())
+            .ok()
+            .and_then(|contents| serde_json::from_str::<ChunkManifest>(&contents).ok());
+        if let Some(manifest) = manifest {
+            pins.entry(manifest.block_number).or_default().insert(manifest.block_hash);
+        }
+    }
+    pins
+}
+
+/// error if the chain no longer has the block hash pinned by earlier runs at the last block of
+/// any partition, returning the number of pinned blocks verified
+///
+/// this covers both partitions that are skipped because their files exist and partitions about
+/// to be collected, so that every file of a range is known to come from the same chain
+pub(crate) async fn verify_pinned_hashes(
+    partitions: &[Partition],
+    source: &Source,
+    sink: &FileOutput,
+) -> Result<usize, CollectError> {
+    let pins = read_pinned_hashes(sink);
+    let mut block_numbers: Vec<u64> = partitions
+        .iter()
+        .filter_map(last_block)
+        .filter(|block_number| pins.contains_key(block_number))
+        .collect();
+    block_numbers.sort();
+    block_numbers.dedup();
+    let concurrency = source.max_concurrent_requests.unwrap_or(10).max(1) as usize;
+    let current: Vec<(u64, String)> = stream::iter(block_numbers.iter().copied())
+        .map(|block_number| async move {
+            let block =
+                source.fetcher.get_block(block_number).await?.ok_or(err("block not found"))?;
+            let block_hash = block.hash.ok_or(err("block hash not found"))?;
+            Ok::<_, CollectError>((block_number, prefix_hex::encode(block_hash.0)))
+        })
+        .buffer_unordered(concurrency)
+        .try_collect()
+        .await?;
+    let mut mismatches: Vec<String> = Vec::new();
+    for (block_number, block_hash) in current.iter() {
+        let mut pinned: Vec<&String> =
+            pins[block_number].iter().filter(|pinned| *pinned != block_hash).collect();
+        pinned.sort();
+        for pinned in pinned.into_iter() {
+            mismatches.push(format!("block {} is {} but was {}", block_number, block_hash, pinned));
+        }
+    }
+    if !mismatches.is_empty() {
+        mismatches.sort();
+        let n_mismatches = mismatches.len();
+        mismatches.truncate(5);
+        return Err(CollectError::CollectError(format!(
+            "block hashes differ from those pinned by earlier runs in {} ({} mismatches: {}), \
+             the chain reorged or the rpc endpoint serves a different chain",
+            sink.output_dir.join(".cryo/manifests").display(),
+            n_mismatches,
+            mismatches.join(", "),
+        )))
+    }
+    Ok(block_numbers.len())
+}
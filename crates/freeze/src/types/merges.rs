@@ -0,0 +1,737 @@
+/// merging of many small output files of a dataset into fewer larger files
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+
+use polars::prelude::*;
+
+use crate::{
+    dataframes, dedup, err, manifests, provenance, CollectError, Datatype, FileBlockRange,
+    FileOutput,
+};
+
+/// name of hive partition of rows whose partition column is null
+pub(crate) const HIVE_NULL_PARTITION: &str = "__HIVE_DEFAULT_PARTITION__";
+
+/// contiguous output files to be merged into a single file
+#[derive(Clone, Debug, PartialEq)]
+pub struct MergeGroup {
+    /// files to merge, in block order
+    pub inputs: Vec<FileBlockRange>,
+    /// path of merged file
+    pub output: PathBuf,
+    /// first block of merged file
+    pub start_block: u64,
+    /// last block of merged file
+    pub end_block: u64,
+}
+
+/// group parquet files of datatype into merges of about target_size bytes each
+///
+/// only contiguous files of the same directory are merged, so that merged files neither hide
+/// gaps nor cross existing hive partitions, and groups of a single file are left as they are.
+/// files of overlapping block ranges are merged as well if overlapping, such as files of
+/// collections with different chunking whose duplicate rows are dropped by the merge
+pub fn plan_merges(
+    files: &[FileBlockRange],
+    datatype: &Datatype,
+    target_size: u64,
+    overlapping: bool,
+) -> Result<Vec<MergeGroup>, CollectError> {
+    let mut sized = Vec::new();
+    for file in files.iter() {
+        if file.path.extension().and_then(|e| e.to_str()) != Some("parquet") {
+            continue
+        }
+        let metadata = std::fs::metadata(&file.path).map_err(|e| {
+            CollectError::CollectError(format!("could not read {}: {}", file.path.display(), e))
+        })?;
+        sized.push((file.clone(), metadata.len()));
+    }
+    Ok(group_files(sized, datatype, target_size, overlapping))
+}
+
+fn group_files(
+    files: Vec<(FileBlockRange, u64)>,
+    datatype: &Datatype,
+    target_size: u64,
+    overlapping: bool,
+) -> Vec<MergeGroup> {
+    let mut by_directory: BTreeMap<(PathBuf, String), Vec<(FileBlockRange, u64)>> = BTreeMap::new();
+    for (file, size) in files.into_iter() {
+        if let Some(prefix) = file_prefix(&file.path, datatype) {
+            let directory = file.path.parent().map(|p| p.to_path_buf()).unwrap_or_default();
+            by_directory.entry((directory, prefix)).or_default().push((file, size));
+        }
+    }
+
+    let mut groups = Vec::new();
+    for ((directory, prefix), mut files) in by_directory.into_iter() {
+        files.sort_by_key(|(file, _)| (file.start_block, file.end_block));
+        let mut current: Vec<FileBlockRange> = Vec::new();
+        let mut current_size = 0;
+        for (file, size) in files.into_iter() {
+            let current_end = current.iter().map(|file| file.end_block).max();
+            let contiguous = match (current_end, overlapping) {
+                (None, _) => true,
+                (Some(end_block), false) => file.start_block == end_block + 1,
+                (Some(end_block), true) => file.start_block <= end_block + 1,
+            };
+            // files overlapping the group are merged into it whatever its size, so that merged
+            // files do not overlap
+            let overlaps = overlapping && current_end.is_some_and(|end| file.start_block <= end);
+            if !contiguous || (current_size >= target_size && !overlaps) {
+                groups.extend(to_group(
+                    std::mem::take(&mut current),
+                    &directory,
+                    &prefix,
+                    datatype,
+                ));
+                current_size = 0;
+            }
+            current_size += size;
+            current.push(file);
+        }
+        groups.extend(to_group(current, &directory, &prefix, datatype));
+    }
+    groups
+}
+
+fn to_group(
+    inputs: Vec<FileBlockRange>,
+    directory: &Path,
+    prefix: &str,
+    datatype: &Datatype,
+) -> Option<MergeGroup> {
+    if inputs.len() < 2 {
+        return None
+    }
+    let start_block = inputs.first()?.start_block;
+    let end_block = inputs.iter().map(|input| input.end_block).max()?;
+    let output = directory.join(merged_filename(prefix, datatype, start_block, end_block));
+    Some(MergeGroup { inputs, output, start_block, end_block })
+}
+
+/// network prefix of output file name, the part before `__{datatype}__`
+fn file_prefix(path: &Path, datatype: &Datatype) -> Option<String> {
+    let name = path.file_name()?.to_str()?;
+    let index = name.find(&format!("__{}__", datatype.name()))?;
+    Some(name[..index].to_string())
+}
+
+fn merged_filename(prefix: &str, datatype: &Datatype, start_block: u64, end_block: u64) -> String {
+    format!("{}__{}__{:0>8}_to_{:0>8}.parquet", prefix, datatype.name(), start_block, end_block)
+}
+
+/// merge the files of group, returning the paths of the merged files
+///
+/// rows are sorted by sort_columns when given, and only the first of the rows of each value of
+/// dedup_keys is kept when given. if hive_column is given, rows are split into
+/// `{hive_column}={value}` subdirectories of the group's directory, without the column itself.
+/// with the global sort of sink, inputs must each be sorted by sort_columns and are merged with a
+/// k-way merge that reads them a batch at a time, instead of being read whole and sorted.
+/// manifests of the inputs are replaced by a manifest of each merged file if every input had
+/// one, since the hash of the last block of the group commits to all of its blocks. inputs are
+/// only removed after all merged files are written
+pub fn merge_files(
+    group: &MergeGroup,
+    sink: &FileOutput,
+    sort_columns: Option<&[String]>,
+    dedup_keys: Option<&[String]>,
+    hive_column: Option<&str>,
+) -> Result<Vec<PathBuf>, CollectError> {
+    let manifests: Vec<_> =
+        group.inputs.iter().map(|input| manifests::read_manifest(sink, &input.path)).collect();
+    let last_input = group.inputs.iter().position(|input| input.end_block == group.end_block);
+    let manifest = match (manifests.iter().all(|manifest| manifest.is_some()), last_input) {
+        (true, Some(last_input)) => manifests[last_input].clone(),
+        _ => None,
+    };
+
+    // keep provenance of the first merged file, relabeled with the partition of the merged file
+    let mut metadata = match group.inputs.first() {
+        Some(input) => provenance::read_file_metadata(&input.path)?,
+        None => Vec::new(),
+    };
+    let partition_key = format!("{}partition", provenance::METADATA_PREFIX);
+    let label = group.output.file_stem().and_then(|stem| stem.to_str()?.rsplit("__").next());
+    for (key, value) in metadata.iter_mut() {
+        if let (true, Some(label)) = (key == &partition_key, label) {
+            *value = label.to_string();
+        }
+    }
+
+    let paths = match sort_columns {
+        Some(sort_columns) if sink.global_sort && !sort_columns.is_empty() => {
+            write_sorted_merge(group, sink, sort_columns, dedup_keys, hive_column, &metadata)?
+        }
+        _ => write_merge(group, sink, sort_columns, dedup_keys, hive_column, &metadata)?,
+    };
+
+    for input in group.inputs.iter() {
+        if !paths.contains(&input.path) {
+            std::fs::remove_file(&input.path).map_err(|e| {
+                CollectError::CollectError(format!(
+                    "could not remove {}: {}",
+                    input.path.display(),
+                    e
+                ))
+            })?;
+        }
+        if let Some(manifest_path) = manifests::get_manifest_path(sink, &input.path) {
+            let _ = std::fs::remove_file(manifest_path);
+        }
+    }
+    if let Some(manifest) = &manifest {
+        for path in paths.iter() {
+            manifests::write_manifest(sink, path, manifest)?;
+        }
+    }
+    Ok(paths)
+}
+
+/// read the files of group whole and write them as merged files, sorted by sort_columns
+fn write_merge(
+    group: &MergeGroup,
+    sink: &FileOutput,
+    sort_columns: Option<&[String]>,
+    dedup_keys: Option<&[String]>,
+    hive_column: Option<&str>,
+    metadata: &[(String, String)],
+) -> Result<Vec<PathBuf>, CollectError> {
+    let mut merged: Option<DataFrame> = None;
+    for input in group.inputs.iter() {
+        let file = std::fs::File::open(&input.path).map_err(|e| {
+            CollectError::CollectError(format!("could not open {}: {}", input.path.display(), e))
+        })?;
+        let df = ParquetReader::new(file).finish()?;
+        merged = match merged {
+            None => Some(df),
+            Some(mut merged) => {
+                if merged.schema() != df.schema() {
+                    return Err(schema_mismatch(&input.path))
+                }
+                merged.vstack_mut(&df)?;
+                Some(merged)
+            }
+        };
+    }
+    let mut merged = merged.ok_or(err("no files to merge"))?;
+    if let Some(keys) = dedup_keys {
+        merged = dedup::drop_duplicate_keys(merged, keys, None)?;
+    }
+    if let Some(sort_columns) = sort_columns {
+        if sort_columns.iter().all(|column| merged.column(column).is_ok()) {
+            merged = merged.sort(sort_columns, false, true)?;
+        }
+    }
+    merged.as_single_chunk_par();
+
+    let mut outputs = match hive_column {
+        Some(column) => split_hive_partitions(&merged, column)?
+            .into_iter()
+            .map(|(value, df)| (hive_path(&group.output, &value), df))
+            .collect(),
+        None => vec![(group.output.clone(), merged)],
+    };
+
+    let mut paths = Vec::new();
+    for (path, df) in outputs.iter_mut() {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(|_| err("could not create output dir"))?;
+        }
+        dataframes::df_to_file_with_metadata(df, path, sink, metadata)
+            .map_err(|_| CollectError::CollectError("error writing file".to_string()))?;
+        paths.push(path.clone());
+    }
+    Ok(paths)
+}
+
+/// number of rows read from each input of a sorted merge at a time
+const MERGE_BATCH_SIZE: usize = 64 * 1024;
+
+/// column of the inputs that rows of a sorted merge come from
+const INPUT_COLUMN: &str = "__merge_input";
+
+/// column of the positions of rows within their input's buffer in a sorted merge
+const ROW_COLUMN: &str = "__merge_row";
+
+/// merge files of group that are each sorted by sort_columns into files sorted as a whole
+///
+/// files are written to tmp files as rows are merged, which are renamed once all rows are
+/// written, so that a merge that fails partway leaves its inputs as the only complete files.
+///
+/// rows of equal dedup_keys share the values of the sort columns that are dedup keys up to the
+/// first sort column that is not, so they are merged within a run of rows of equal values of
+/// those columns, and each batch is deduplicated against the rows of the run it continues
+fn write_sorted_merge(
+    group: &MergeGroup,
+    sink: &FileOutput,
+    sort_columns: &[String],
+    dedup_keys: Option<&[String]>,
+    hive_column: Option<&str>,
+    metadata: &[(String, String)],
+) -> Result<Vec<PathBuf>, CollectError> {
+    let run_columns: Option<Vec<String>> = dedup_keys.map(|keys| {
+        sort_columns.iter().take_while(|column| keys.contains(column)).cloned().collect()
+    });
+    if run_columns.as_ref().is_some_and(|columns| columns.is_empty()) {
+        return Err(CollectError::CollectError(format!(
+            "dedup keys must include the first sort column {} to be merged sorted",
+            sort_columns[0]
+        )))
+    }
+
+    let mut inputs = group
+        .inputs
+        .iter()
+        .map(|input| SortedInput::open(&input.path, sort_columns))
+        .collect::<Result<Vec<_>, _>>()?;
+    let schema =
+        inputs.first().map(|input| input.schema.clone()).ok_or(err("no files to merge"))?;
+    if let Some(input) = inputs.iter().find(|input| input.schema != schema) {
+        return Err(schema_mismatch(&input.path))
+    }
+
+    let mut writers: BTreeMap<PathBuf, (PathBuf, dataframes::ParquetStreamWriter)> =
+        BTreeMap::new();
+    let mut run: Option<DataFrame> = None;
+    let result = (|| {
+        if hive_column.is_none() {
+            open_writer(&mut writers, &group.output, &schema, sink)?;
+        }
+        loop {
+            for input in inputs.iter_mut() {
+                input.fill(sort_columns)?;
+            }
+            let batch = match next_sorted_batch(&mut inputs, sort_columns)? {
+                Some(batch) => batch,
+                None => break,
+            };
+            let batch = match (dedup_keys, &run_columns) {
+                (Some(keys), Some(run_columns)) => {
+                    let batch = dedup::drop_duplicate_keys(batch, keys, run.as_ref())?;
+                    run = Some(last_run(run.take(), &batch, keys, run_columns)?);
+                    batch
+                }
+                _ => batch,
+            };
+            let outputs = match hive_column {
+                Some(column) => split_hive_partitions(&batch, column)?
+                    .into_iter()
+                    .map(|(value, df)| (hive_path(&group.output, &value), df))
+                    .collect(),
+                None => vec![(group.output.clone(), batch)],
+            };
+            for (path, df) in outputs.iter() {
+                if !writers.contains_key(path) {
+                    open_writer(&mut writers, path, &df.schema(), sink)?;
+                }
+                if let Some((_, writer)) = writers.get_mut(path) {
+                    writer.write(df).map_err(|_| err("error writing file"))?;
+                }
+            }
+        }
+        Ok(())
+    })();
+
+    let mut paths = Vec::new();
+    let mut result = result;
+    for (path, (tmp_path, writer)) in writers.into_iter() {
+        result = result.and_then(|()| {
+            writer
+                .finish(metadata)
+                .and_then(|()| dataframes::commit_tmp_file(&tmp_path, &path))
+                .map_err(|_| err("error writing file"))
+        });
+        match result {
+            Ok(()) => paths.push(path),
+            Err(_) => {
+                let _ = std::fs::remove_file(&tmp_path);
+            }
+        }
+    }
+    result.map(|()| paths)
+}
+
+/// key columns of the rows of the last run of rows of equal run_columns, of the rows of an
+/// earlier run followed by the rows of batch
+fn last_run(
+    run: Option<DataFrame>,
+    batch: &DataFrame,
+    keys: &[String],
+    run_columns: &[String],
+) -> Result<DataFrame, CollectError> {
+    let rows = match run {
+        Some(mut run) => {
+            run.vstack_mut(&batch.select(keys)?)?;
+            run
+        }
+        None => batch.select(keys)?,
+    };
+    let last = match rows.height() {
+        0 => return Ok(rows),
+        height => height - 1,
+    };
+    let mut mask = BooleanChunked::full("", true, rows.height());
+    for column in run_columns.iter() {
+        let values = rows.column(column)?;
+        mask = &mask & &values.equal_missing(&values.new_from_index(last, rows.height()))?;
+    }
+    Ok(rows.filter(&mask)?)
+}
+
+/// create the tmp file of a merged file at path
+fn open_writer(
+    writers: &mut BTreeMap<PathBuf, (PathBuf, dataframes::ParquetStreamWriter)>,
+    path: &Path,
+    schema: &Schema,
+    sink: &FileOutput,
+) -> Result<(), CollectError> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|_| err("could not create output dir"))?;
+    }
+    let tmp_path = dataframes::tmp_path(&crate::sinks::long_path(path));
+    let writer = dataframes::ParquetStreamWriter::try_new(&tmp_path, schema, sink)
+        .map_err(|_| err("error writing file"))?;
+    writers.insert(path.to_path_buf(), (tmp_path, writer));
+    Ok(())
+}
+
+/// input file of a sorted merge, read a batch at a time
+struct SortedInput {
+    path: PathBuf,
+    schema: Schema,
+    reader: BatchedParquetReader,
+    n_rows: usize,
+    rows_read: usize,
+    /// rows read but not yet merged
+    buffer: DataFrame,
+    /// sort columns of the last row read, to check that batches are in order
+    last_row: Option<DataFrame>,
+}
+
+impl SortedInput {
+    fn open(path: &Path, sort_columns: &[String]) -> Result<Self, CollectError> {
+        let file = std::fs::File::open(path).map_err(|e| {
+            CollectError::CollectError(format!("could not open {}: {}", path.display(), e))
+        })?;
+        let mut reader = ParquetReader::new(file);
+        let schema = reader.schema()?;
+        if let Some(column) = sort_columns.iter().find(|column| !schema.contains(column)) {
+            return Err(CollectError::CollectError(format!(
+                "{} has no sort column {}, cannot merge it sorted",
+                path.display(),
+                column
+            )))
+        }
+        let n_rows = reader.num_rows()?;
+        let reader = reader.batched(MERGE_BATCH_SIZE)?;
+        let buffer = DataFrame::from(&schema);
+        Ok(SortedInput {
+            path: path.to_path_buf(),
+            schema,
+            reader,
+            n_rows,
+            rows_read: 0,
+            buffer,
+            last_row: None,
+        })
+    }
+
+    /// whether all rows of input have been read
+    fn exhausted(&self) -> bool {
+        self.rows_read >= self.n_rows
+    }
+
+    /// read the next batch of rows into the buffer if it is empty
+    fn fill(&mut self, sort_columns: &[String]) -> Result<(), CollectError> {
+        while self.buffer.height() == 0 && !self.exhausted() {
+            let batch = match self.reader.next_batches(1)? {
+                Some(batches) => batches.into_iter().next(),
+                None => None,
+            };
+            let batch = match batch {
+                Some(batch) => batch,
+                None => {
+                    self.rows_read = self.n_rows;
+                    break
+                }
+            };
+            if batch.height() == 0 {
+                continue
+            }
+            self.rows_read += batch.height();
+
+            let keys = batch.select(sort_columns)?;
+            let checked = match &self.last_row {
+                Some(last_row) => last_row.vstack(&keys)?,
+                None => keys.clone(),
+            };
+            if !is_sorted(&checked, sort_columns)? {
+                return Err(CollectError::CollectError(format!(
+                    "{} is not sorted by {}, merge it without --global-sort",
+                    self.path.display(),
+                    sort_columns.join(", ")
+                )))
+            }
+            self.last_row = Some(keys.tail(Some(1)));
+            self.buffer = batch;
+        }
+        Ok(())
+    }
+}
+
+/// whether rows of df are in order of sort_columns
+fn is_sorted(df: &DataFrame, sort_columns: &[String]) -> Result<bool, CollectError> {
+    let df = df.select(sort_columns)?.with_row_count(ROW_COLUMN, None)?;
+    let sorted = df.sort(sort_columns, false, true)?;
+    Ok(sorted.column(ROW_COLUMN)?.equal(df.column(ROW_COLUMN)?)?.all())
+}
+
+/// take the next rows of the merge of the buffers of inputs, or None once all rows are merged
+///
+/// rows up to the smallest last buffered row of the inputs with unread rows are taken, since
+/// every row read later is at least as large as it. that input's buffer is used up, so each call
+/// makes room for at least one more batch
+fn next_sorted_batch(
+    inputs: &mut [SortedInput],
+    sort_columns: &[String],
+) -> Result<Option<DataFrame>, CollectError> {
+    let mut combined: Option<DataFrame> = None;
+    for (index, input) in inputs.iter().enumerate() {
+        if input.buffer.height() == 0 {
+            continue
+        }
+        let mut df = input.buffer.with_row_count(ROW_COLUMN, None)?;
+        df.with_column(Series::new(INPUT_COLUMN, vec![index as u32; df.height()]))?;
+        combined = match combined {
+            None => Some(df),
+            Some(mut combined) => {
+                combined.vstack_mut(&df)?;
+                Some(combined)
+            }
+        };
+    }
+    let combined = match combined {
+        Some(combined) => combined,
+        None => return Ok(None),
+    };
+    let mut sort_by = sort_columns.to_vec();
+    sort_by.extend([INPUT_COLUMN.to_string(), ROW_COLUMN.to_string()]);
+    let sorted = combined.sort(sort_by, false, false)?;
+
+    let input_indices = sorted.column(INPUT_COLUMN)?.u32()?;
+    let rows = sorted.column(ROW_COLUMN)?.idx()?;
+    let bound = input_indices.into_iter().zip(rows).position(|(index, row)| match (index, row) {
+        (Some(index), Some(row)) => {
+            let input = &inputs[index as usize];
+            !input.exhausted() && row as usize + 1 == input.buffer.height()
+        }
+        _ => false,
+    });
+    let n_taken = bound.map(|position| position + 1).unwrap_or(sorted.height());
+    let taken = sorted.slice(0, n_taken);
+
+    let mut counts = vec![0; inputs.len()];
+    for index in taken.column(INPUT_COLUMN)?.u32()?.into_iter().flatten() {
+        counts[index as usize] += 1;
+    }
+    for (input, count) in inputs.iter_mut().zip(counts) {
+        input.buffer = input.buffer.slice(count as i64, input.buffer.height());
+    }
+    Ok(Some(taken.drop(INPUT_COLUMN)?.drop(ROW_COLUMN)?))
+}
+
+/// path of the file of a hive partition of a merged file
+fn hive_path(output: &Path, partition: &str) -> PathBuf {
+    let directory = output.parent().unwrap_or(Path::new("")).join(partition);
+    directory.join(output.file_name().unwrap_or_default())
+}
+
+fn schema_mismatch(path: &Path) -> CollectError {
+    CollectError::CollectError(format!(
+        "schema of {} differs from earlier files, cannot merge",
+        path.display()
+    ))
+}
+
+/// split dataframe by the values of column into `{column}={value}` partitions
+fn split_hive_partitions(
+    df: &DataFrame,
+    column: &str,
+) -> Result<Vec<(String, DataFrame)>, CollectError> {
+    if column == "block_number" {
+        return Err(err("block_number cannot be used as hive partition column"))
+    }
+    let keys = df
+        .column(column)
+        .map_err(|_| CollectError::CollectError(format!("{} not in merged files", column)))?
+        .cast(&DataType::Utf8)?;
+    let keys = keys.utf8()?;
+    let data = df.drop(column)?;
+    let unique = keys.unique()?;
+    let mut values: Vec<Option<&str>> = unique.into_iter().collect();
+    values.sort();
+    let mut partitions = Vec::new();
+    for value in values.into_iter() {
+        let mask = match value {
+            Some(value) => keys.equal(value),
+            None => keys.is_null(),
+        };
+        let name = format!("{}={}", column, value.unwrap_or(HIVE_NULL_PARTITION));
+        partitions.push((name, data.filter(&mask)?));
+    }
+    Ok(partitions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn file(path: &str, start_block: u64, end_block: u64) -> FileBlockRange {
+        FileBlockRange { path: PathBuf::from(path), start_block, end_block }
+    }
+
+    #[test]
+    fn test_group_files() {
+        let files = vec![
+            (file("out/ethereum__logs__00000000_to_00000999.parquet", 0, 999), 10),
+            (file("out/ethereum__logs__00001000_to_00001999.parquet", 1000, 1999), 10),
+            (file("out/ethereum__logs__00002000_to_00002999.parquet", 2000, 2999), 10),
+            (file("out/ethereum__logs__00003000_to_00003999.parquet", 3000, 3999), 10),
+            // gap from 4000 to 4999
+            (file("out/ethereum__logs__00005000_to_00005999.parquet", 5000, 5999), 10),
+            (file("out/ethereum__logs__00006000_to_00006999.parquet", 6000, 6999), 10),
+            (file("out/chain=1/ethereum__logs__00007000_to_00007999.parquet", 7000, 7999), 10),
+        ];
+        let groups = group_files(files, &Datatype::Logs, 20, false);
+        let ranges: Vec<_> = groups.iter().map(|g| (g.start_block, g.end_block)).collect();
+        assert_eq!(ranges, vec![(0, 1999), (2000, 3999), (5000, 6999)]);
+        assert_eq!(
+            groups[0].output,
+            PathBuf::from("out/ethereum__logs__00000000_to_00001999.parquet")
+        );
+        assert_eq!(groups[2].inputs.len(), 2);
+    }
+
+    #[test]
+    fn test_group_overlapping_files() {
+        let files = vec![
+            (file("out/ethereum__logs__00000000_to_00000999.parquet", 0, 999), 10),
+            (file("out/ethereum__logs__00000000_to_00001999.parquet", 0, 1999), 10),
+            (file("out/ethereum__logs__00001500_to_00002499.parquet", 1500, 2499), 10),
+            (file("out/ethereum__logs__00002000_to_00002499.parquet", 2000, 2499), 10),
+            (file("out/ethereum__logs__00004000_to_00004999.parquet", 4000, 4999), 10),
+        ];
+        assert!(group_files(files.clone(), &Datatype::Logs, 20, false).is_empty());
+        let groups = group_files(files, &Datatype::Logs, 20, true);
+        let ranges: Vec<_> = groups.iter().map(|g| (g.start_block, g.end_block)).collect();
+        assert_eq!(ranges, vec![(0, 2499)]);
+        assert_eq!(groups[0].inputs.len(), 4);
+    }
+
+    #[test]
+    fn test_split_hive_partitions() {
+        use polars::prelude::NamedFrom;
+
+        let df = df!(
+            "block_number" => [1u32, 2, 3],
+            "block_date" => [Some("2023-01-01"), Some("2023-01-02"), None],
+        )
+        .unwrap();
+        let partitions = split_hive_partitions(&df, "block_date").unwrap();
+        let names: Vec<_> = partitions.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(
+            names,
+            vec![
+                "block_date=__HIVE_DEFAULT_PARTITION__",
+                "block_date=2023-01-01",
+                "block_date=2023-01-02"
+            ]
+        );
+        assert_eq!(partitions[1].1.get_column_names(), vec!["block_number"]);
+        assert!(split_hive_partitions(&df, "block_number").is_err());
+    }
+
+    #[test]
+    fn test_sorted_merge() {
+        use crate::{ExistingFilePolicy, FileFormat, TextCompression};
+        use std::collections::HashMap;
+
+        let dir = std::env::temp_dir().join("cryo_test_sorted_merge");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let sink = FileOutput {
+            output_dir: dir.clone(),
+            prefix: "ethereum".to_string(),
+            suffix: None,
+            existing_files: ExistingFilePolicy::Overwrite,
+            allow_schema_change: false,
+            format: FileFormat::Parquet,
+            row_group_size: Some(2),
+            global_sort: true,
+            dedup_keys: HashMap::new(),
+            parquet_statistics: true,
+            parquet_compression: ParquetCompression::Lz4Raw,
+            text_compression: TextCompression::Uncompressed,
+            hex_prefix: true,
+            clickhouse: None,
+            manifest: false,
+            pin_block_hash: false,
+            skip_empty_files: false,
+            max_concurrent_writes: None,
+            remote: None,
+        };
+
+        // files sorted by address whose addresses interleave, read two rows at a time
+        let inputs = vec![
+            df!("address" => [1u32, 4, 4, 7, 9], "block_number" => [0u32, 1, 0, 1, 0]).unwrap(),
+            df!("address" => [2u32, 3, 4, 8], "block_number" => [3u32, 2, 2, 3]).unwrap(),
+            df!("address" => [5u32], "block_number" => [4u32]).unwrap(),
+        ];
+        let mut files = Vec::new();
+        for (i, mut df) in inputs.into_iter().enumerate() {
+            let path = dir.join(format!("ethereum__logs__{:0>8}_to_{:0>8}.parquet", i, i));
+            dataframes::df_to_file(&mut df, &path, &sink).unwrap();
+            files.push(file(path.to_str().unwrap(), i as u64, i as u64));
+        }
+        let group = MergeGroup {
+            inputs: files,
+            output: dir.join("ethereum__logs__00000000_to_00000002.parquet"),
+            start_block: 0,
+            end_block: 2,
+        };
+        let sort = vec!["address".to_string()];
+        let paths = merge_files(&group, &sink, Some(&sort), None, None).unwrap();
+        assert_eq!(paths, vec![group.output.clone()]);
+        let merged = ParquetReader::new(std::fs::File::open(&paths[0]).unwrap()).finish().unwrap();
+        let addresses: Vec<_> =
+            merged.column("address").unwrap().u32().unwrap().into_iter().collect();
+        let expected = [1u32, 2, 3, 4, 4, 4, 5, 7, 8, 9];
+        assert_eq!(addresses, expected.into_iter().map(Some).collect::<Vec<_>>());
+        let blocks: Vec<_> =
+            merged.column("block_number").unwrap().u32().unwrap().into_iter().flatten().collect();
+        assert_eq!(blocks, vec![0, 3, 2, 1, 0, 2, 4, 1, 3, 0]);
+        assert!(group.inputs.iter().all(|input| !input.path.exists()));
+
+        // unsorted inputs are not merged
+        let unsorted = dir.join("ethereum__logs__00000003_to_00000003.parquet");
+        let mut df = df!("address" => [3u32, 1], "block_number" => [3u32, 3]).unwrap();
+        dataframes::df_to_file(&mut df, &unsorted, &sink).unwrap();
+        let group = MergeGroup {
+            inputs: vec![
+                FileBlockRange { path: paths[0].clone(), start_block: 0, end_block: 2 },
+                FileBlockRange { path: unsorted, start_block: 3, end_block: 3 },
+            ],
+            output: dir.join("ethereum__logs__00000000_to_00000003.parquet"),
+            start_block: 0,
+            end_block: 3,
+        };
+        let error = merge_files(&group, &sink, Some(&sort), None, None).unwrap_err();
+        assert!(error.to_string().contains("is not sorted by address"));
+        assert!(group.inputs.iter().all(|input| input.path.exists()));
+        assert!(!group.output.exists());
+    }
+}
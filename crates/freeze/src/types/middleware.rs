@@ -0,0 +1,93 @@
+/// hooks called around the rpc requests of fetchers
+use std::time::Duration;
+
+use crate::CollectError;
+
+/// hooks called around the rpc requests of a fetcher, for custom logging, metrics, or auth
+/// checks, or for failing requests in tests
+///
+/// hooks are called for each request sent to the provider, including each json-rpc batch, but not
+/// for responses shared with an identical request in flight or read from the disk cache. hooks of
+/// the parent of a fetcher are called for the requests of the fetcher as well
+///
+/// ```ignore
+/// struct Logger;
+///
+/// impl FetcherMiddleware for Logger {
+///     fn on_response(&self, method: &str, latency: Duration) {
+///         println!("{} took {:?}", method, latency);
+///     }
+/// }
+///
+/// let source = SourceBuilder::new(rpc_url).middleware(Arc::new(Logger)).build().await?;
+/// ```
+pub trait FetcherMiddleware: Send + Sync {
+    /// called before a request of method is sent, which is not sent if an error is returned
+    fn on_request(&self, _method: &str) -> Result<(), CollectError> {
+        Ok(())
+    }
+
+    /// called after a request of method succeeds
+    fn on_response(&self, _method: &str, _latency: Duration) {}
+
+    /// called after a request of method fails
+    fn on_error(&self, _method: &str, _latency: Duration, _error: &CollectError) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Fetcher;
+    use ethers::prelude::*;
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Default)]
+    struct Recorder {
+        calls: Mutex<Vec<String>>,
+        refuse: bool,
+    }
+
+    impl FetcherMiddleware for Recorder {
+        fn on_request(&self, method: &str) -> Result<(), CollectError> {
+            self.calls.lock().unwrap().push(format!("request {}", method));
+            match self.refuse {
+                true => Err(CollectError::CollectError("refused".to_string())),
+                false => Ok(()),
+            }
+        }
+
+        fn on_response(&self, method: &str, _latency: Duration) {
+            self.calls.lock().unwrap().push(format!("response {}", method));
+        }
+
+        fn on_error(&self, method: &str, _latency: Duration, _error: &CollectError) {
+            self.calls.lock().unwrap().push(format!("error {}", method));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_fetcher_middleware() {
+        let (provider, mock) = Provider::mocked();
+        let recorder = Arc::new(Recorder::default());
+        let fetcher = Fetcher::new(provider).with_middleware(recorder.clone());
+        mock.push(U64::from(7)).unwrap();
+        assert_eq!(fetcher.get_block_number().await.unwrap(), U64::from(7));
+        assert!(fetcher.get_block_number().await.is_err());
+        let calls = recorder.calls.lock().unwrap().clone();
+        let expected = [
+            "request eth_blockNumber",
+            "response eth_blockNumber",
+            "request eth_blockNumber",
+            "error eth_blockNumber",
+        ];
+        assert_eq!(calls, expected);
+
+        // refused requests are not sent
+        let (provider, mock) = Provider::mocked();
+        let refuser = Arc::new(Recorder { refuse: true, ..Default::default() });
+        let fetcher = Fetcher::new(provider).with_middleware(refuser);
+        mock.push(U64::from(7)).unwrap();
+        assert!(fetcher.get_block_number().await.is_err());
+        assert_eq!(fetcher.rpc_stats.summarize().len(), 0);
+    }
+}
@@ -1,11 +1,29 @@
 /// type specifications for cryo_freeze crate
 
+/// tuning of request concurrency from the feedback of providers
+pub mod autotune;
+/// analysis of deployed evm bytecode
+pub mod bytecode;
+/// caching of rpc responses
+pub mod cache;
+/// optional rpc apis supported by nodes
+pub mod capabilities;
+/// registry of known chains
+pub mod chains;
 /// type specifications for chunk types
 pub mod chunks;
+/// validation of block continuity within partitions
+pub mod continuity;
 /// conversion operations
 pub mod conversions;
 /// type specifications for collectable types
 pub mod datatypes;
+/// deduplication of rows of overlapping output files
+pub mod dedup;
+/// columns computed from other columns
+pub mod derived_columns;
+/// on-disk cache of rpc responses
+pub mod disk_cache;
 /// type specifications for data sources
 pub mod sources;
 
@@ -37,31 +55,130 @@ pub mod dataframes;
 /// function and event signatures
 pub mod signatures;
 
+/// clickhouse output
+pub mod clickhouse;
 /// error specifications
 pub mod errors;
 /// type specifications for output data formats
 pub mod files;
+/// filters applied to rows during collection
+pub mod filters;
+/// recorded json-rpc responses, for tests without a node
+pub mod fixtures;
+/// detection of block ranges missing from output files
+pub mod gaps;
+/// gas accounting of transactions
+pub mod gas;
+/// inspection of collected output files
+pub mod inspect;
+/// manifests of block hashes of output files
+pub mod manifests;
+/// merging of small output files into larger files
+pub mod merges;
+/// hooks around the rpc requests of fetchers
+pub mod middleware;
+/// batching of eth_calls through Multicall3
+pub mod multicall3;
+/// otterscan rpc response types
+pub mod otterscan;
+/// provenance metadata of output files
+pub mod provenance;
+/// column-level statistics of collected chunks
+pub mod quality;
 /// queries
 pub mod queries;
+/// batching of rpc requests into json-rpc batches
+pub mod rpc_batches;
+/// statistics of rpc requests
+pub mod rpc_stats;
+/// ordering of chunks across datatypes by their relative cost
+pub mod scheduling;
+/// checks of new chunks against the schemas of existing files
+pub mod schema_guard;
 /// type specifications for data schemas
 pub mod schemas;
+/// graceful shutdown on signals
+pub mod shutdown;
+/// locations of output files, in local directories or in object stores
+pub mod sinks;
 /// types related to summaries
 pub mod summaries;
+/// output files of datasets collected over time
+pub mod time_buckets;
+/// block timestamps attached to block-indexed datasets
+pub mod timestamps;
+/// user-defined transforms applied to collected chunks
+pub mod transforms;
+/// transports of json-rpc requests, over http, through fixtures, or read from a node's database
+pub mod transport;
+/// interactive terminal view of progress
+pub mod tui;
+/// enrichment of contract addresses with verified sources
+pub mod verification;
+/// sql queries across output files
+pub mod warehouse;
 
+pub use autotune::{
+    autotune_host, default_autotune_path, read_learned_concurrency, write_learned_concurrency,
+    AutoTuner, AUTOTUNE_INITIAL_CONCURRENCY,
+};
+pub use bytecode::{BytecodeAnalysis, BYTECODE_ANALYSIS_COLUMNS};
+pub use cache::{BlockCache, RequestCoalescer};
+pub use capabilities::Capabilities;
+pub use chains::{get_chain, get_chain_by_name, ChainInfo, CHAINS, DEFAULT_CHUNK_SIZE};
 pub use chunks::{
-    AddressChunk, BlockChunk, CallDataChunk, Chunk, ChunkData, ChunkStats, SlotChunk, Subchunk,
-    TopicChunk, TransactionChunk,
+    AddressChunk, BeaconSlotChunk, BlockChunk, CallDataChunk, Chunk, ChunkData, ChunkStats,
+    SlotChunk, Subchunk, TopicChunk, TransactionChunk,
 };
-pub use conversions::{bytes_to_u32, ToVecHex, ToVecU8};
+pub use clickhouse::ClickhouseOutput;
+pub use conversions::{bytes_to_u32, u256_to_f64_decimals, ToVecChecksumHex, ToVecHex, ToVecU8};
+pub(crate) use conversions::{parse_hex_string, parse_u64_string};
 pub use dataframes::*;
 pub use datatypes::*;
-pub use files::{ColumnEncoding, FileFormat, FileOutput};
-pub use queries::{Query, TimeDimension};
+pub use derived_columns::{ArithmeticOp, DeriveExpr, DerivedColumn};
+pub use disk_cache::{DiskCache, DISK_CACHE_CONFIRMATIONS};
+pub use files::{ColumnEncoding, ExistingFilePolicy, FileFormat, FileOutput, TextCompression};
+pub use filters::{Comparison, FilterValue, RowFilter, TraceFilter};
+pub use fixtures::Fixtures;
+pub use gaps::{find_block_gaps, find_empty_block_ranges, find_file_block_ranges, FileBlockRange};
+pub use gas::{GasAccounting, GasRules, EXECUTION_GAS_COLUMNS, GAS_ACCOUNTING_COLUMNS};
+pub use inspect::{
+    file_datatype, format_binary_columns, inspect_file, list_output_files, read_output_file,
+    InspectMode,
+};
+pub use manifests::{recheck_partitions, ChunkManifest};
+pub use merges::{merge_files, plan_merges, MergeGroup};
+pub use middleware::FetcherMiddleware;
+pub use multicall3::{decode_multicall, encode_multicall, MulticallBatcher};
+pub use otterscan::{OtsBlock, OtsBlockDetails, OtsTransactionsPage};
+pub use provenance::{read_file_metadata, METADATA_PREFIX, SCHEMA_VERSION};
+pub use quality::{column_stats, ColumnStats, DataQuality};
+pub use queries::{Query, QueryBuilder, TimeDimension};
+pub use rpc_batches::RpcBatcher;
+pub use rpc_stats::{RpcMethodSummary, RpcStats};
+pub(crate) use scheduling::interleave_by_cost;
+pub use schema_guard::SchemaGuard;
 pub use schemas::{ColumnType, Schemas, Table, U256Type};
-pub use sources::{Fetcher, RateLimiter, Source};
+pub use shutdown::Shutdown;
+pub use sinks::{ObjectStoreScheme, OutputLocation, RemoteOutput};
+pub use sources::{
+    Fetcher, RateLimiter, Source, SourceBuilder, LOG_SPLIT_ERRORS, PROPOSER_DUTIES_CACHE_EPOCHS,
+    SLOTS_PER_EPOCH,
+};
+pub use verification::{
+    SourceVerifier, VerificationProvider, VerifiedSource, ETHERSCAN_URL, SOURCIFY_URL,
+};
 // pub(crate) use summaries::FreezeSummaryAgg;
 // pub use summaries::{FreezeChunkSummary, FreezeSummary};
-pub use summaries::FreezeSummary;
+pub use summaries::{print_chain_summaries, ChunkRecord, FreezeSummary};
+pub use time_buckets::TimeBuckets;
+pub use timestamps::TIMESTAMP_CACHE_BLOCKS;
+pub use transforms::{apply_sql, WasmTransform};
+pub use transport::{
+    register_database_backend, DatabaseBackend, DatabaseOpenFn, RpcTransport, TransportError,
+};
+pub use tui::TuiProgress;
+pub use warehouse::{query_output_dir, write_dataframe};
 
 pub use errors::{err, ChunkError, CollectError, FileError, FreezeError, ParseError};
 
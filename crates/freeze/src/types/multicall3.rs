@@ -0,0 +1,203 @@
+/// types and functions related to batching eth_calls through Multicall3
+use std::{collections::HashMap, sync::Arc, time::Duration};
+
+use ethers::{
+    abi::{ParamType, Token},
+    prelude::*,
+};
+use tokio::sync::{oneshot, Mutex};
+
+use crate::{
+    sources::revert_data, CollectError, Fetcher, FUNCTION_MULTICALL3_AGGREGATE3, MULTICALL3_ADDRESS,
+};
+
+type Result<T> = ::core::result::Result<T, CollectError>;
+
+type PendingCall = (H160, Vec<u8>, oneshot::Sender<Result<Option<Bytes>>>);
+
+/// how long to wait for more calls to the same block before sending a partial batch
+const BATCH_DELAY: Duration = Duration::from_millis(10);
+
+/// encode calldata of Multicall3 `aggregate3()`, allowing each call to fail
+pub fn encode_multicall(calls: &[(H160, Vec<u8>)]) -> Vec<u8> {
+    let calls = calls
+        .iter()
+        .map(|(target, call_data)| {
+            Token::Tuple(vec![
+                Token::Address(*target),
+                Token::Bool(true),
+                Token::Bytes(call_data.clone()),
+            ])
+        })
+        .collect();
+    let mut data = FUNCTION_MULTICALL3_AGGREGATE3.clone();
+    data.extend(ethers::abi::encode(&[Token::Array(calls)]));
+    data
+}
+
+/// decode output of Multicall3 `aggregate3()`, using None for calls that failed
+///
+/// output is empty at blocks before Multicall3 was deployed, which gives None
+pub fn decode_multicall(output: &[u8]) -> Result<Option<Vec<Option<Bytes>>>> {
+    if output.is_empty() {
+        return Ok(None)
+    }
+    let result_type =
+        ParamType::Array(Box::new(ParamType::Tuple(vec![ParamType::Bool, ParamType::Bytes])));
+    let tokens = ethers::abi::decode(&[result_type], output)
+        .map_err(|_| CollectError::CollectError("could not decode multicall output".to_string()))?;
+    let results = match tokens.into_iter().next() {
+        Some(Token::Array(results)) => results,
+        _ => return Err(CollectError::CollectError("invalid multicall output".to_string())),
+    };
+    results
+        .into_iter()
+        .map(|result| match result {
+            Token::Tuple(values) => match values.as_slice() {
+                [Token::Bool(true), Token::Bytes(data)] => Ok(Some(data.clone().into())),
+                [Token::Bool(false), _] => Ok(None),
+                _ => Err(CollectError::CollectError("invalid multicall result".to_string())),
+            },
+            _ => Err(CollectError::CollectError("invalid multicall result".to_string())),
+        })
+        .collect::<Result<_>>()
+        .map(Some)
+}
+
+impl<P: JsonRpcClient> Fetcher<P> {
+    /// Return outputs of many contract calls executed in a single Multicall3 call, using None
+    /// for calls that reverted
+    ///
+    /// at blocks before Multicall3 was deployed, each call is sent on its own instead
+    pub async fn multicall(
+        &self,
+        calls: &[(H160, Vec<u8>)],
+        block_number: BlockNumber,
+    ) -> Result<Vec<Option<Bytes>>> {
+        let output = self.call2(*MULTICALL3_ADDRESS, encode_multicall(calls), block_number).await?;
+        let results = match decode_multicall(&output)? {
+            Some(results) => results,
+            None => return self.call_each(calls, block_number).await,
+        };
+        if results.len() != calls.len() {
+            return Err(CollectError::CollectError("multicall returned wrong length".to_string()))
+        }
+        Ok(results)
+    }
+
+    /// Return outputs of contract calls sent on their own, using None for calls that reverted
+    async fn call_each(
+        &self,
+        calls: &[(H160, Vec<u8>)],
+        block_number: BlockNumber,
+    ) -> Result<Vec<Option<Bytes>>> {
+        let outputs = calls.iter().map(|(address, call_data)| async move {
+            match self.call2(*address, call_data.clone(), block_number).await {
+                Ok(output) => Ok(Some(output)),
+                Err(e) if revert_data(&e).is_some() => Ok(None),
+                Err(e) => Err(e),
+            }
+        });
+        futures::future::try_join_all(outputs).await
+    }
+}
+
+/// collects concurrent eth_calls to the same block into Multicall3 batches
+pub struct MulticallBatcher {
+    /// maximum number of calls per batch
+    pub batch_size: usize,
+    pending: Arc<Mutex<HashMap<u64, Vec<PendingCall>>>>,
+}
+
+impl MulticallBatcher {
+    /// create new batcher
+    pub fn new(batch_size: usize) -> Self {
+        MulticallBatcher { batch_size, pending: Default::default() }
+    }
+
+    /// queue call and wait for the batch containing it to execute
+    ///
+    /// a batch is sent once it reaches `batch_size` calls or a short delay after its first call,
+    /// returning None for calls that reverted
+    pub async fn call<P: JsonRpcClient + 'static>(
+        &self,
+        fetcher: Arc<Fetcher<P>>,
+        address: H160,
+        call_data: Vec<u8>,
+        block_number: u64,
+    ) -> Result<Option<Bytes>> {
+        let (sender, receiver) = oneshot::channel();
+        let (full_batch, is_first) = {
+            let mut pending = self.pending.lock().await;
+            let calls = pending.entry(block_number).or_default();
+            calls.push((address, call_data, sender));
+            let is_first = calls.len() == 1;
+            match calls.len() >= self.batch_size {
+                true => (pending.remove(&block_number), is_first),
+                false => (None, is_first),
+            }
+        };
+
+        if let Some(batch) = full_batch {
+            execute_batch(fetcher, block_number, batch).await;
+        } else if is_first {
+            let pending = self.pending.clone();
+            tokio::spawn(async move {
+                tokio::time::sleep(BATCH_DELAY).await;
+                let batch = pending.lock().await.remove(&block_number);
+                if let Some(batch) = batch {
+                    execute_batch(fetcher, block_number, batch).await;
+                }
+            });
+        }
+
+        receiver
+            .await
+            .map_err(|_| CollectError::CollectError("multicall batch dropped".to_string()))?
+    }
+}
+
+async fn execute_batch<P: JsonRpcClient>(
+    fetcher: Arc<Fetcher<P>>,
+    block_number: u64,
+    batch: Vec<PendingCall>,
+) {
+    let (calls, senders): (Vec<_>, Vec<_>) = batch
+        .into_iter()
+        .map(|(address, call_data, sender)| ((address, call_data), sender))
+        .unzip();
+    match fetcher.multicall(&calls, block_number.into()).await {
+        Ok(results) => {
+            for (sender, result) in senders.into_iter().zip(results) {
+                let _ = sender.send(Ok(result));
+            }
+        }
+        Err(e) => {
+            for sender in senders.into_iter() {
+                let _ = sender.send(Err(CollectError::CollectError(e.to_string())));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_multicall() {
+        let output = ethers::abi::encode(&[Token::Array(vec![
+            Token::Tuple(vec![Token::Bool(true), Token::Bytes(vec![1, 2, 3])]),
+            Token::Tuple(vec![Token::Bool(false), Token::Bytes(vec![])]),
+        ])]);
+        let results = decode_multicall(&output).unwrap();
+        assert_eq!(results, Some(vec![Some(Bytes::from(vec![1, 2, 3])), None]));
+
+        // blocks before Multicall3 was deployed give empty output rather than results
+        assert_eq!(decode_multicall(&[]).unwrap(), None);
+        assert!(decode_multicall(&[1, 2, 3]).is_err());
+
+        let call_data = encode_multicall(&[(H160::zero(), vec![0xaa])]);
+        assert_eq!(call_data[..4], FUNCTION_MULTICALL3_AGGREGATE3[..]);
+    }
+}
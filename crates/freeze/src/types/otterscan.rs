@@ -0,0 +1,46 @@
+/// response types of the otterscan `ots_` rpc namespace
+use ethers::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// response of `ots_getBlockDetails`, a block header with its transaction count and total fees
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OtsBlockDetails {
+    /// block header
+    pub block: OtsBlock,
+    /// sum of fees paid by transactions of block, in wei
+    pub total_fees: U256,
+}
+
+/// block header returned by `ots_getBlockDetails`
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OtsBlock {
+    /// block number
+    pub number: U64,
+    /// block timestamp
+    pub timestamp: U256,
+    /// block miner
+    pub miner: Option<H160>,
+    /// number of transactions in block
+    pub transaction_count: u64,
+    /// base fee per gas, if block is after eip-1559
+    pub base_fee_per_gas: Option<U256>,
+    /// gas used by block
+    pub gas_used: U256,
+}
+
+/// page of transactions touching an address, returned by `ots_searchTransactionsBefore` and
+/// `ots_searchTransactionsAfter`
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OtsTransactionsPage {
+    /// transactions of page
+    pub txs: Vec<Transaction>,
+    /// receipts of transactions of page
+    pub receipts: Vec<TransactionReceipt>,
+    /// whether page contains the most recent transactions of address
+    pub first_page: bool,
+    /// whether page contains the earliest transactions of address
+    pub last_page: bool,
+}
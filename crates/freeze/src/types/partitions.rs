@@ -1,6 +1,6 @@
 use crate::{
-    err, types::chunks::Subchunk, AddressChunk, BlockChunk, CallDataChunk, ChunkData, ChunkStats,
-    CollectError, Params, SlotChunk, TopicChunk, TransactionChunk,
+    err, types::chunks::Subchunk, AddressChunk, BeaconSlotChunk, BlockChunk, CallDataChunk,
+    ChunkData, ChunkStats, CollectError, Params, SlotChunk, TopicChunk, TransactionChunk,
 };
 
 /// a dimension of chunking
@@ -28,6 +28,8 @@ pub enum Dim {
     Topic2,
     /// Topic3 dimension
     Topic3,
+    /// Beacon chain slot dimension
+    BeaconSlot,
 }
 
 impl Dim {
@@ -45,6 +47,7 @@ impl Dim {
             Dim::Topic1,
             Dim::Topic2,
             Dim::Topic3,
+            Dim::BeaconSlot,
         ]
     }
 
@@ -62,6 +65,7 @@ impl Dim {
             Dim::Topic1 => "topic1s",
             Dim::Topic2 => "topic2s",
             Dim::Topic3 => "topic3s",
+            Dim::BeaconSlot => "beacon_slots",
         }
     }
 }
@@ -83,6 +87,7 @@ impl std::str::FromStr for Dim {
             "topic1" => Dim::Topic1,
             "topic2" => Dim::Topic2,
             "topic3" => Dim::Topic3,
+            "beacon_slot" => Dim::BeaconSlot,
             _ => return Err(crate::ParseError::ParseError("invalid dim name".to_string())),
         };
         Ok(dim)
@@ -103,6 +108,7 @@ impl std::fmt::Display for Dim {
             Dim::Topic1 => "topic1",
             Dim::Topic2 => "topic2",
             Dim::Topic3 => "topic3",
+            Dim::BeaconSlot => "beacon_slot",
         };
         write!(f, "{}", as_str)
     }
@@ -135,6 +141,8 @@ pub struct Partition {
     pub topic2s: Option<Vec<TopicChunk>>,
     /// topic3s
     pub topic3s: Option<Vec<TopicChunk>>,
+    /// beacon slots
+    pub beacon_slots: Option<Vec<BeaconSlotChunk>>,
 }
 
 /// partition outputs
@@ -261,6 +269,7 @@ impl Partition {
                     Dim::Topic1 => chunks_to_name(&self.topic1s)?,
                     Dim::Topic2 => chunks_to_name(&self.topic2s)?,
                     Dim::Topic3 => chunks_to_name(&self.topic3s)?,
+                    Dim::BeaconSlot => chunks_to_name(&self.beacon_slots)?,
                 },
             };
             pieces.push(piece);
@@ -289,6 +298,7 @@ impl Partition {
                 Dim::Topic1 => partition!(outputs, topic1s)?,
                 Dim::Topic2 => partition!(outputs, topic2s)?,
                 Dim::Topic3 => partition!(outputs, topic3s)?,
+                Dim::BeaconSlot => partition!(outputs, beacon_slots)?,
             }
         }
         Ok(outputs)
@@ -316,6 +326,7 @@ impl Partition {
                 Dim::Topic1 => label_partition!(outputs, dim_labels, topic1s)?,
                 Dim::Topic2 => label_partition!(outputs, dim_labels, topic2s)?,
                 Dim::Topic3 => label_partition!(outputs, dim_labels, topic3s)?,
+                Dim::BeaconSlot => label_partition!(outputs, dim_labels, beacon_slots)?,
             }
         }
         Ok(outputs)
@@ -349,6 +360,7 @@ impl Partition {
                 Dim::Topic1 => parametrize!(outputs, new, self.topic1s, topic1),
                 Dim::Topic2 => parametrize!(outputs, new, self.topic2s, topic2),
                 Dim::Topic3 => parametrize!(outputs, new, self.topic3s, topic3),
+                Dim::BeaconSlot => parametrize!(outputs, new, self.beacon_slots, beacon_slot),
             }
             outputs = new;
         }
@@ -423,6 +435,9 @@ impl Partition {
         if self.topic3s.is_some() {
             dims.push(Dim::Topic3)
         };
+        if self.beacon_slots.is_some() {
+            dims.push(Dim::BeaconSlot)
+        };
         dims
     }
 
@@ -440,6 +455,7 @@ impl Partition {
             Dim::Topic1 => self.topic1s.as_ref().map(|x| x.len()).unwrap_or(0),
             Dim::Topic2 => self.topic2s.as_ref().map(|x| x.len()).unwrap_or(0),
             Dim::Topic3 => self.topic3s.as_ref().map(|x| x.len()).unwrap_or(0),
+            Dim::BeaconSlot => self.beacon_slots.as_ref().map(|x| x.len()).unwrap_or(0),
         }
     }
 
@@ -458,6 +474,7 @@ impl Partition {
             topic1s: chunk.topic1s.map(|c| c.stats()),
             topic2s: chunk.topic2s.map(|c| c.stats()),
             topic3s: chunk.topic3s.map(|c| c.stats()),
+            beacon_slots: chunk.beacon_slots.map(|c| c.stats()),
         }
     }
 }
@@ -495,6 +512,8 @@ pub struct PartitionStats {
     pub topic2s: Option<ChunkStats<Vec<u8>>>,
     /// topic3s stats
     pub topic3s: Option<ChunkStats<Vec<u8>>>,
+    /// beacon slots stats
+    pub beacon_slots: Option<ChunkStats<u64>>,
 }
 
 fn fold<T: std::cmp::Ord + crate::types::chunks::chunk_ops::ValueToString>(
@@ -523,6 +542,7 @@ impl PartitionStats {
             topic1s: fold(self.topic1s, other.topic1s),
             topic2s: fold(self.topic2s, other.topic2s),
             topic3s: fold(self.topic3s, other.topic3s),
+            beacon_slots: fold(self.beacon_slots, other.beacon_slots),
         }
     }
 }
@@ -551,6 +571,8 @@ pub struct PartitionLabels {
     pub topic2_labels: Option<Vec<Option<String>>>,
     /// topic3 labels
     pub topic3_labels: Option<Vec<Option<String>>>,
+    /// beacon slot labels
+    pub beacon_slot_labels: Option<Vec<Option<String>>>,
 }
 
 impl PartitionLabels {
@@ -567,6 +589,7 @@ impl PartitionLabels {
             Dim::Topic1 => self.topic1_labels.clone(),
             Dim::Topic2 => self.topic2_labels.clone(),
             Dim::Topic3 => self.topic3_labels.clone(),
+            Dim::BeaconSlot => self.beacon_slot_labels.clone(),
         }
     }
 
@@ -0,0 +1,114 @@
+/// provenance of output files, recorded in the key-value metadata of parquet files
+use std::path::Path;
+
+use polars::export::arrow::io::parquet::read::read_metadata;
+
+use crate::{CollectError, Datatype, Partition, Source, Table};
+
+/// version of the schemas of cryo datasets, incremented when columns or column types change
+pub const SCHEMA_VERSION: u64 = 2;
+
+/// prefix of the key-value metadata keys written by cryo
+pub const METADATA_PREFIX: &str = "cryo.";
+
+/// key written by arrow writers, holding the serialized arrow schema
+const ARROW_SCHEMA_KEY: &str = "ARROW:schema";
+
+/// provenance of a chunk whose collection is starting, as key-value metadata
+///
+/// the rpc endpoint is recorded as a hash, since rpc urls often contain api keys
+pub(crate) fn chunk_metadata(
+    datatype: &Datatype,
+    partition: &Partition,
+    source: &Source,
+    table: Option<&Table>,
+) -> Vec<(String, String)> {
+    let rpc_url_hash = ethers_core::utils::keccak256(source.rpc_url.as_bytes());
+    let mut metadata = vec![
+        ("version", crate::reports::get_cryo_version()),
+        ("schema_version", SCHEMA_VERSION.to_string()),
+        ("datatype", datatype.name()),
+        ("partition", partition.label(&partition.dims()).unwrap_or_default()),
+        ("chain_id", source.chain_id.to_string()),
+        ("rpc_url_hash", prefix_hex::encode(&rpc_url_hash[..8])),
+        ("collected_at", chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, true)),
+    ];
+    if let Some(table) = table {
+        let mut u256_types: Vec<_> = table.u256_types.iter().map(|t| t.suffix()).collect();
+        u256_types.sort();
+        let u256_types: Vec<_> = u256_types.iter().map(|t| t.trim_start_matches('_')).collect();
+        metadata.extend([
+            ("columns", table.columns().join(",")),
+            ("sort", table.sort_columns.clone().unwrap_or_default().join(",")),
+            ("u256_types", u256_types.join(",")),
+            ("binary_column_format", table.binary_type.as_str().to_string()),
+        ]);
+        if let Some(js_tracer) = &table.js_tracer {
+            metadata.push(("js_tracer", js_tracer.path.clone()));
+        }
+    }
+    metadata
+        .into_iter()
+        .map(|(key, value)| (format!("{}{}", METADATA_PREFIX, key), value))
+        .collect()
+}
+
+/// key-value metadata of parquet file, other than the arrow schema
+pub fn read_file_metadata(path: &Path) -> Result<Vec<(String, String)>, CollectError> {
+    let mut file = std::fs::File::open(path).map_err(|e| {
+        CollectError::CollectError(format!("could not read {}: {}", path.display(), e))
+    })?;
+    let metadata = read_metadata(&mut file).map_err(|e| {
+        CollectError::CollectError(format!("could not read metadata of {}: {}", path.display(), e))
+    })?;
+    let key_values = metadata
+        .key_value_metadata
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|key_value| key_value.key != ARROW_SCHEMA_KEY)
+        .map(|key_value| (key_value.key, key_value.value.unwrap_or_default()))
+        .collect();
+    Ok(key_values)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ExistingFilePolicy, FileFormat, FileOutput, TextCompression};
+    use polars::prelude::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_file_metadata_round_trip() {
+        let dir = std::env::temp_dir().join("cryo_test_provenance");
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("ethereum__blocks__00000000_to_00000009.parquet");
+        let sink = FileOutput {
+            output_dir: dir.clone(),
+            prefix: "ethereum".to_string(),
+            suffix: None,
+            existing_files: ExistingFilePolicy::Overwrite,
+            allow_schema_change: false,
+            format: FileFormat::Parquet,
+            row_group_size: Some(2),
+            global_sort: false,
+            dedup_keys: HashMap::new(),
+            parquet_statistics: true,
+            parquet_compression: ParquetCompression::Lz4Raw,
+            text_compression: TextCompression::Uncompressed,
+            hex_prefix: true,
+            clickhouse: None,
+            manifest: false,
+            pin_block_hash: false,
+            skip_empty_files: false,
+            max_concurrent_writes: None,
+            remote: None,
+        };
+        let mut df = df!("block_number" => [0u32, 1, 2, 3, 4]).unwrap();
+        let metadata = vec![("cryo.chain_id".to_string(), "1".to_string())];
+        crate::dataframes::df_to_file_with_metadata(&mut df, &path, &sink, &metadata).unwrap();
+        assert_eq!(read_file_metadata(&path).unwrap(), metadata);
+        let file = std::fs::File::open(&path).unwrap();
+        assert_eq!(ParquetReader::new(file).finish().unwrap(), df);
+    }
+}
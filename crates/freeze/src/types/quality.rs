@@ -0,0 +1,152 @@
+/// column-level statistics of collected chunks, and anomalies found in them
+use std::collections::BTreeMap;
+
+use polars::prelude::*;
+
+/// null count and range of a column, over one chunk or over all chunks of a dataset
+#[derive(Clone, Debug, Default, PartialEq, serde::Serialize)]
+pub struct ColumnStats {
+    /// name of column
+    pub column: String,
+    /// rows of column
+    pub n_rows: u64,
+    /// null values of column
+    pub null_count: u64,
+    /// minimum of a numeric column
+    pub min: Option<f64>,
+    /// maximum of a numeric column
+    pub max: Option<f64>,
+    /// chunks with rows
+    pub n_chunks: u64,
+    /// chunks with rows in which every value of column is null
+    pub n_null_chunks: u64,
+}
+
+impl ColumnStats {
+    /// statistics of a column of one chunk
+    pub fn new(series: &Series) -> ColumnStats {
+        let n_rows = series.len() as u64;
+        let null_count = series.null_count() as u64;
+        let (min, max) = match series.dtype().is_numeric() {
+            true => (series.min::<f64>(), series.max::<f64>()),
+            false => (None, None),
+        };
+        let has_rows = (n_rows > 0) as u64;
+        ColumnStats {
+            column: series.name().to_string(),
+            n_rows,
+            null_count,
+            min,
+            max,
+            n_chunks: has_rows,
+            n_null_chunks: has_rows * (null_count == n_rows) as u64,
+        }
+    }
+
+    /// combine with statistics of the same column of other chunks
+    fn merge(&mut self, other: &ColumnStats) {
+        self.n_rows += other.n_rows;
+        self.null_count += other.null_count;
+        self.min = merge_option(self.min, other.min, f64::min);
+        self.max = merge_option(self.max, other.max, f64::max);
+        self.n_chunks += other.n_chunks;
+        self.n_null_chunks += other.n_null_chunks;
+    }
+
+    /// description of what is anomalous about column, if anything
+    fn anomaly(&self, dataset: &str) -> Option<String> {
+        if self.n_rows > 0 && self.null_count == self.n_rows {
+            Some(format!("{}.{} is null in all {} rows", dataset, self.column, self.n_rows))
+        } else if self.n_null_chunks > 0 {
+            Some(format!(
+                "{}.{} is null in all rows of {} of {} chunks",
+                dataset, self.column, self.n_null_chunks, self.n_chunks
+            ))
+        } else {
+            None
+        }
+    }
+}
+
+fn merge_option(a: Option<f64>, b: Option<f64>, f: fn(f64, f64) -> f64) -> Option<f64> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(f(a, b)),
+        (a, b) => a.or(b),
+    }
+}
+
+/// statistics of each column of a chunk
+pub fn column_stats(df: &DataFrame) -> Vec<ColumnStats> {
+    df.get_columns().iter().map(ColumnStats::new).collect()
+}
+
+/// column statistics of each dataset, accumulated over the chunks of a freeze
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct DataQuality {
+    /// statistics of each column, by dataset, in column order
+    pub datasets: BTreeMap<String, Vec<ColumnStats>>,
+}
+
+impl DataQuality {
+    /// add statistics of a chunk of dataset
+    pub fn add(&mut self, dataset: &str, chunk: &[ColumnStats]) {
+        let columns = self.datasets.entry(dataset.to_string()).or_default();
+        for stats in chunk.iter() {
+            match columns.iter_mut().find(|c| c.column == stats.column) {
+                Some(column) => column.merge(stats),
+                None => columns.push(stats.clone()),
+            }
+        }
+    }
+
+    /// add statistics of other chunks
+    pub fn extend(&mut self, other: &DataQuality) {
+        for (dataset, columns) in other.datasets.iter() {
+            self.add(dataset, columns);
+        }
+    }
+
+    /// columns that are null in all rows, or in all rows of some chunks, which is often a sign
+    /// of a provider that does not return a field rather than of data that is absent
+    pub fn anomalies(&self) -> Vec<String> {
+        self.datasets
+            .iter()
+            .flat_map(|(dataset, columns)| columns.iter().filter_map(|c| c.anomaly(dataset)))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_data_quality() {
+        let first = df!(
+            "block_number" => [1u32, 2],
+            "base_fee" => [None::<u64>, None],
+            "miner" => [Some("a"), None],
+        )
+        .unwrap();
+        let second = df!(
+            "block_number" => [3u32],
+            "base_fee" => [Some(7u64)],
+            "miner" => [Some("b")],
+        )
+        .unwrap();
+        let mut quality = DataQuality::default();
+        quality.add("blocks", &column_stats(&first));
+        let mut other = DataQuality::default();
+        other.add("blocks", &column_stats(&second));
+        assert_eq!(quality.anomalies(), vec!["blocks.base_fee is null in all 2 rows"]);
+
+        quality.extend(&other);
+        let blocks = &quality.datasets["blocks"];
+        assert_eq!((blocks[0].min, blocks[0].max), (Some(1.0), Some(3.0)));
+        assert_eq!((blocks[2].null_count, blocks[2].min), (1, None));
+        assert_eq!(
+            quality.anomalies(),
+            vec!["blocks.base_fee is null in all rows of 1 of 2 chunks"]
+        );
+    }
+}
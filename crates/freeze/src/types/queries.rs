@@ -1,4 +1,9 @@
-use crate::{CollectError, Datatype, Dim, MetaDatatype, Partition, Table};
+use crate::{
+    chunks::{binary_chunk::BinaryChunk, Subchunk},
+    BlockChunk, CollectError, ColumnEncoding, Datatype, Dim, MetaDatatype, Partition, Table,
+    U256Type, DEFAULT_CHUNK_SIZE,
+};
+use ethers::types::{H160, H256};
 use std::collections::{HashMap, HashSet};
 
 /// Query
@@ -63,3 +68,314 @@ pub enum TimeDimension {
     /// Transactions
     Transactions,
 }
+
+/// builder of validated queries, so that library users need not construct partitions and
+/// tables by hand
+///
+/// ```ignore
+/// let query = QueryBuilder::new()
+///     .datatype(Datatype::Logs)
+///     .blocks(0..=1_000_000)
+///     .contract(address)
+///     .columns(["block_number", "transaction_hash", "data"])
+///     .build()?;
+/// ```
+#[derive(Clone, Default)]
+pub struct QueryBuilder {
+    datatypes: Vec<Datatype>,
+    blocks: Vec<BlockChunk>,
+    transactions: Vec<Vec<u8>>,
+    addresses: Vec<Vec<u8>>,
+    contracts: Vec<Vec<u8>>,
+    to_addresses: Vec<Vec<u8>>,
+    slots: Vec<Vec<u8>>,
+    topics: [Vec<Vec<u8>>; 4],
+    chunk_size: Option<u64>,
+    partition_by: Option<Vec<Dim>>,
+    columns: Option<Vec<String>>,
+    include_columns: Option<Vec<String>>,
+    exclude_columns: Option<Vec<String>>,
+    u256_types: Option<HashSet<U256Type>>,
+    hex: bool,
+    sort: Option<Option<Vec<String>>>,
+    errors: Vec<String>,
+}
+
+impl QueryBuilder {
+    /// initialize QueryBuilder
+    pub fn new() -> Self {
+        QueryBuilder::default()
+    }
+
+    /// add datatype to collect
+    pub fn datatype(mut self, datatype: Datatype) -> Self {
+        self.datatypes.push(datatype);
+        self
+    }
+
+    /// add datatypes to collect
+    pub fn datatypes(mut self, datatypes: impl IntoIterator<Item = Datatype>) -> Self {
+        self.datatypes.extend(datatypes);
+        self
+    }
+
+    /// add range of blocks, ex: `0..1000` or `0..=999`
+    pub fn blocks(mut self, range: impl std::ops::RangeBounds<u64>) -> Self {
+        use std::ops::Bound;
+        let start = match range.start_bound() {
+            Bound::Included(start) => Some(*start),
+            Bound::Excluded(start) => start.checked_add(1),
+            Bound::Unbounded => Some(0),
+        };
+        let end = match range.end_bound() {
+            Bound::Included(end) => Some(*end),
+            Bound::Excluded(end) => end.checked_sub(1),
+            Bound::Unbounded => None,
+        };
+        match (start, end) {
+            (Some(start), Some(end)) if start <= end => {
+                self.blocks.push(BlockChunk::Range(start, end))
+            }
+            (_, None) if matches!(range.end_bound(), Bound::Unbounded) => {
+                self.errors.push("block range must have an end".to_string())
+            }
+            _ => self.errors.push("block range is empty".to_string()),
+        }
+        self
+    }
+
+    /// add block numbers
+    pub fn block_numbers(mut self, block_numbers: impl IntoIterator<Item = u64>) -> Self {
+        self.blocks.push(BlockChunk::Numbers(block_numbers.into_iter().collect()));
+        self
+    }
+
+    /// add transaction hash
+    pub fn transaction(mut self, transaction_hash: H256) -> Self {
+        self.transactions.push(transaction_hash.as_bytes().to_vec());
+        self
+    }
+
+    /// add transaction hashes
+    pub fn transactions(self, transaction_hashes: impl IntoIterator<Item = H256>) -> Self {
+        transaction_hashes.into_iter().fold(self, |builder, hash| builder.transaction(hash))
+    }
+
+    /// add address
+    pub fn address(mut self, address: H160) -> Self {
+        self.addresses.push(address.as_bytes().to_vec());
+        self
+    }
+
+    /// add contract address
+    pub fn contract(mut self, contract: H160) -> Self {
+        self.contracts.push(contract.as_bytes().to_vec());
+        self
+    }
+
+    /// add to address
+    pub fn to_address(mut self, to_address: H160) -> Self {
+        self.to_addresses.push(to_address.as_bytes().to_vec());
+        self
+    }
+
+    /// add storage slot
+    pub fn slot(mut self, slot: H256) -> Self {
+        self.slots.push(slot.as_bytes().to_vec());
+        self
+    }
+
+    /// add value of log topic at index, from 0 to 3
+    pub fn topic(mut self, index: usize, topic: H256) -> Self {
+        match self.topics.get_mut(index) {
+            Some(topics) => topics.push(topic.as_bytes().to_vec()),
+            None => self.errors.push(format!("topic index must be 0 to 3, got {}", index)),
+        }
+        self
+    }
+
+    /// number of blocks per output file
+    pub fn chunk_size(mut self, chunk_size: u64) -> Self {
+        self.chunk_size = Some(chunk_size);
+        self
+    }
+
+    /// dimensions to partition by
+    pub fn partition_by(mut self, dims: impl IntoIterator<Item = Dim>) -> Self {
+        self.partition_by = Some(dims.into_iter().collect());
+        self
+    }
+
+    /// columns to collect, instead of the default columns
+    pub fn columns<S: Into<String>>(mut self, columns: impl IntoIterator<Item = S>) -> Self {
+        self.columns = Some(columns.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// columns to collect in addition to the default columns
+    pub fn include_columns<S: Into<String>>(
+        mut self,
+        columns: impl IntoIterator<Item = S>,
+    ) -> Self {
+        self.include_columns = Some(columns.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// columns to leave out
+    pub fn exclude_columns<S: Into<String>>(
+        mut self,
+        columns: impl IntoIterator<Item = S>,
+    ) -> Self {
+        self.exclude_columns = Some(columns.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// representations of u256 columns [default: binary, string, f64]
+    pub fn u256_types(mut self, u256_types: impl IntoIterator<Item = U256Type>) -> Self {
+        self.u256_types = Some(u256_types.into_iter().collect());
+        self
+    }
+
+    /// use hex instead of binary for binary columns
+    pub fn hex(mut self, hex: bool) -> Self {
+        self.hex = hex;
+        self
+    }
+
+    /// columns to sort rows by, or None to leave rows unsorted [default: sort of datatype]
+    pub fn sort<S: Into<String>>(mut self, columns: Option<impl IntoIterator<Item = S>>) -> Self {
+        self.sort = Some(columns.map(|columns| columns.into_iter().map(Into::into).collect()));
+        self
+    }
+
+    /// build validated query
+    pub fn build(self) -> Result<Query, CollectError> {
+        if let Some(error) = self.errors.first() {
+            return Err(CollectError::CollectError(error.clone()))
+        }
+        if self.datatypes.is_empty() {
+            return Err(CollectError::CollectError("must specify a datatype".to_string()))
+        }
+        if self.blocks.is_empty() && self.transactions.is_empty() {
+            return Err(CollectError::CollectError(
+                "must specify blocks or transactions".to_string(),
+            ))
+        }
+        if self.sort.as_ref().is_some_and(|sort| sort.is_some()) && self.datatypes.len() > 1 {
+            return Err(CollectError::CollectError(
+                "custom sort not supported for multiple datasets".to_string(),
+            ))
+        }
+
+        // schemas
+        let u256_types = self.u256_types.clone().unwrap_or_else(|| {
+            HashSet::from_iter([U256Type::Binary, U256Type::String, U256Type::F64])
+        });
+        let binary_type = if self.hex { ColumnEncoding::Hex } else { ColumnEncoding::Binary };
+        let mut schemas = HashMap::new();
+        for datatype in self.datatypes.iter() {
+            if datatype == &Datatype::JavascriptTraces {
+                return Err(CollectError::CollectError(
+                    "javascript_traces requires a tracer, which QueryBuilder does not support"
+                        .to_string(),
+                ))
+            }
+            let sort = self.sort.clone().unwrap_or_else(|| Some(datatype.default_sort()));
+            let table = datatype
+                .table_schema(
+                    &u256_types,
+                    &binary_type,
+                    &self.include_columns,
+                    &self.exclude_columns,
+                    &self.columns,
+                    sort,
+                    None,
+                )
+                .map_err(|e| {
+                    CollectError::CollectError(format!(
+                        "invalid columns for {}: {}",
+                        datatype.name(),
+                        e
+                    ))
+                })?;
+            schemas.insert(*datatype, table);
+        }
+
+        // partitions
+        let chunk_size = self.chunk_size.unwrap_or(DEFAULT_CHUNK_SIZE);
+        let values = |values: Vec<Vec<u8>>| match values.is_empty() {
+            true => None,
+            false => Some(vec![BinaryChunk::Values(values)]),
+        };
+        let block_numbers = match self.blocks.is_empty() {
+            true => None,
+            false => Some(
+                self.blocks.iter().flat_map(|chunk| chunk.subchunk_by_size(&chunk_size)).collect(),
+            ),
+        };
+        let [topic0s, topic1s, topic2s, topic3s] = self.topics;
+        let partition = Partition {
+            label: None,
+            block_numbers,
+            transactions: values(self.transactions),
+            addresses: values(self.addresses),
+            contracts: values(self.contracts),
+            to_addresses: values(self.to_addresses),
+            slots: values(self.slots),
+            call_datas: None,
+            topic0s: values(topic0s),
+            topic1s: values(topic1s),
+            topic2s: values(topic2s),
+            topic3s: values(topic3s),
+            beacon_slots: None,
+        };
+        let time_dimension = match partition.transactions.is_some() {
+            true => TimeDimension::Transactions,
+            false => TimeDimension::Blocks,
+        };
+        let partitioned_by = match (self.partition_by, &time_dimension) {
+            (Some(dims), _) => dims,
+            (None, TimeDimension::Transactions) => vec![Dim::TransactionHash],
+            (None, TimeDimension::Blocks) => vec![Dim::BlockNumber],
+        };
+        let partitions = partition.partition(partitioned_by.clone())?;
+
+        let datatypes = crate::cluster_partition_datatypes(self.datatypes, &partitions);
+        let query = Query { datatypes, schemas, time_dimension, partitions, partitioned_by };
+        query.is_valid()?;
+        Ok(query)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_query_builder() {
+        let contract = H160::from_low_u64_be(1);
+        let query = QueryBuilder::new()
+            .datatype(Datatype::Logs)
+            .blocks(0..2500)
+            .contract(contract)
+            .columns(["block_number", "address", "data"])
+            .build()
+            .unwrap();
+        assert_eq!(query.partitions.len(), 3);
+        assert_eq!(query.partitions[2].stats().block_numbers.unwrap().max_value, Some(2499));
+        assert_eq!(
+            query.schemas[&Datatype::Logs].columns(),
+            vec!["block_number", "address", "data"]
+        );
+
+        assert!(QueryBuilder::new().blocks(0..10).build().is_err());
+        assert!(QueryBuilder::new().datatype(Datatype::Blocks).blocks(5..).build().is_err());
+        assert!(QueryBuilder::new().datatype(Datatype::Balances).blocks(0..10).build().is_err());
+        assert!(QueryBuilder::new()
+            .datatype(Datatype::Blocks)
+            .blocks(0..10)
+            .columns(["not_a_column"])
+            .build()
+            .is_err());
+    }
+}
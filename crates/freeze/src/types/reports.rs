@@ -1,6 +1,10 @@
-use crate::{err, CollectError, ExecutionEnv, FileOutput, FreezeSummary, Query};
+use crate::{
+    err, ChunkRecord, CollectError, ColumnStats, ExecutionEnv, FileOutput, FreezeSummary, Query,
+    RpcMethodSummary,
+};
 use chrono::{DateTime, Local};
 use std::{
+    collections::BTreeMap,
     fs::File,
     io::Write,
     path::{Path, PathBuf},
@@ -19,7 +23,21 @@ struct FreezeReport {
 struct SerializedFreezeSummary {
     completed_paths: Vec<PathBuf>,
     errored_paths: Vec<PathBuf>,
+    empty_paths: Vec<PathBuf>,
+    interrupted_paths: Vec<PathBuf>,
     n_skipped: u64,
+    existing_files: String,
+    overwritten_paths: Vec<PathBuf>,
+    versioned_paths: Vec<PathBuf>,
+    rpc_methods: Vec<RpcMethodSummary>,
+    data_quality: SerializedDataQuality,
+    chunks_path: Option<PathBuf>,
+}
+
+#[derive(serde::Serialize, Debug)]
+struct SerializedDataQuality {
+    columns: BTreeMap<String, Vec<ColumnStats>>,
+    anomalies: Vec<String>,
 }
 
 pub(crate) fn get_report_path(
@@ -56,8 +74,17 @@ pub(crate) fn write_report(
 ) -> Result<PathBuf, CollectError> {
     // determine version
     let cryo_version = get_cryo_version();
+    let path = get_report_path(env, sink, freeze_summary.is_some())?;
     let serialized_summary = match freeze_summary {
-        Some(x) => Some(serialize_summary(x, query, sink)?),
+        Some(x) => {
+            let mut serialized = serialize_summary(x, query, sink)?;
+            if !x.chunks.is_empty() {
+                let chunks_path = get_chunks_path(&path);
+                write_chunk_table(&x.chunks, &chunks_path)?;
+                serialized.chunks_path = Some(chunks_path);
+            }
+            Some(serialized)
+        }
         None => None,
     };
     let report = FreezeReport {
@@ -69,9 +96,6 @@ pub(crate) fn write_report(
     let serialized = serde_json::to_string(&report)
         .map_err(|_| CollectError::CollectError("could not serialize report".to_string()))?;
 
-    // create path
-    let path = get_report_path(env, sink, freeze_summary.is_some())?;
-
     // save to file
     let mut file = File::create(&path)
         .map_err(|_| CollectError::CollectError("could not create report file".to_string()))?;
@@ -103,6 +127,7 @@ fn serialize_summary(
         .collect::<Result<Vec<_>, _>>()?
         .into_iter()
         .flatten()
+        .map(|path| versioned_path(summary, path))
         .collect();
 
     let errored_paths: Vec<PathBuf> = summary
@@ -119,14 +144,91 @@ fn serialize_summary(
         .flatten()
         .collect();
 
+    let empty_paths: Vec<PathBuf> = summary
+        .empty
+        .iter()
+        .map(|partition| {
+            sink.get_paths(query, partition, None)
+                .map(|paths| paths.values().cloned().collect::<Vec<_>>())
+        })
+        .collect::<Result<Vec<_>, _>>()?
+        .into_iter()
+        .flatten()
+        .collect();
+
+    let interrupted_paths: Vec<PathBuf> = summary
+        .interrupted
+        .iter()
+        .map(|partition| {
+            sink.get_paths(query, partition, None)
+                .map(|paths| paths.values().cloned().collect::<Vec<_>>())
+        })
+        .collect::<Result<Vec<_>, _>>()?
+        .into_iter()
+        .flatten()
+        .collect();
+
     Ok(SerializedFreezeSummary {
         completed_paths,
         errored_paths,
+        empty_paths,
+        interrupted_paths,
         n_skipped: summary.skipped.len() as u64,
+        existing_files: sink.existing_files.as_str().to_string(),
+        overwritten_paths: summary.overwritten.clone(),
+        versioned_paths: summary.versioned.iter().map(|(_, versioned)| versioned.clone()).collect(),
+        rpc_methods: summary.rpc_methods.clone(),
+        data_quality: SerializedDataQuality {
+            columns: summary.quality.datasets.clone(),
+            anomalies: summary.quality.anomalies(),
+        },
+        chunks_path: None,
     })
 }
 
-fn get_cryo_version() -> String {
+/// path that the file at path was written to, which is a new version beside it if it existed
+fn versioned_path(summary: &FreezeSummary, path: PathBuf) -> PathBuf {
+    match summary.versioned.iter().find(|(existing, _)| *existing == path) {
+        Some((_, versioned)) => versioned.clone(),
+        None => path,
+    }
+}
+
+/// path of the chunk table of report, ex: `<timestamp>_chunks.parquet` for `<timestamp>.json`
+fn get_chunks_path(report_path: &Path) -> PathBuf {
+    let stem = report_path.file_stem().and_then(|stem| stem.to_str()).unwrap_or_default();
+    report_path.with_file_name(format!("{}_chunks.parquet", stem))
+}
+
+/// write parquet table with one row per output file of the collected partitions, sorted by
+/// datatype and partition
+fn write_chunk_table(chunks: &[ChunkRecord], path: &Path) -> Result<(), CollectError> {
+    use polars::prelude::*;
+
+    let mut chunks: Vec<&ChunkRecord> = chunks.iter().collect();
+    chunks.sort_by(|a, b| {
+        (&a.datatype, a.start_block, &a.partition).cmp(&(&b.datatype, b.start_block, &b.partition))
+    });
+
+    let mut df = df!(
+        "datatype" => chunks.iter().map(|c| c.datatype.as_str()).collect::<Vec<_>>(),
+        "partition" => chunks.iter().map(|c| c.partition.as_str()).collect::<Vec<_>>(),
+        "start_block" => chunks.iter().map(|c| c.start_block).collect::<Vec<_>>(),
+        "end_block" => chunks.iter().map(|c| c.end_block).collect::<Vec<_>>(),
+        "path" => chunks.iter().map(|c| c.path.to_string_lossy()).collect::<Vec<_>>(),
+        "status" => chunks.iter().map(|c| c.status.as_str()).collect::<Vec<_>>(),
+        "n_rows" => chunks.iter().map(|c| c.n_rows).collect::<Vec<_>>(),
+        "n_bytes" => chunks.iter().map(|c| c.n_bytes).collect::<Vec<_>>(),
+        "duration_ms" => chunks.iter().map(|c| c.duration_ms).collect::<Vec<_>>(),
+        "retries" => chunks.iter().map(|c| c.retries).collect::<Vec<_>>(),
+        "error" => chunks.iter().map(|c| c.error.as_deref()).collect::<Vec<_>>(),
+    )?;
+    let file = File::create(path).map_err(|_| err("could not create chunk table of report"))?;
+    ParquetWriter::new(file).finish(&mut df).map_err(|_| err("could not write chunk table"))?;
+    Ok(())
+}
+
+pub(crate) fn get_cryo_version() -> String {
     format!(
         "{}__{}",
         env!("CARGO_PKG_VERSION"),
@@ -0,0 +1,170 @@
+/// batching of many small rpc requests into json-rpc batch requests
+use std::{collections::HashMap, sync::Arc, time::Duration};
+
+use ethers::prelude::*;
+use serde::{de::DeserializeOwned, Serialize};
+use tokio::sync::{oneshot, Mutex};
+use tracing::Instrument;
+
+use crate::{CollectError, Fetcher};
+
+type Result<T> = ::core::result::Result<T, CollectError>;
+
+type PendingRequest = (serde_json::Value, oneshot::Sender<Result<serde_json::Value>>);
+
+/// how long to wait for more requests of the same method before sending a partial batch
+const BATCH_DELAY: Duration = Duration::from_millis(10);
+
+#[derive(serde::Deserialize)]
+struct BatchResponse {
+    id: usize,
+    result: Option<serde_json::Value>,
+    error: Option<serde_json::Value>,
+}
+
+/// collects concurrent requests of the same rpc method into json-rpc batch requests
+///
+/// each batch is a single http request, counted once against concurrency and rate limits
+pub struct RpcBatcher {
+    /// maximum number of requests per batch
+    pub batch_size: usize,
+    http_client: reqwest::Client,
+    rpc_url: String,
+    pending: Arc<Mutex<HashMap<&'static str, Vec<PendingRequest>>>>,
+}
+
+impl RpcBatcher {
+    /// create new batcher sending batches to rpc_url
+    pub fn new(batch_size: usize, http_client: reqwest::Client, rpc_url: String) -> Self {
+        RpcBatcher { batch_size, http_client, rpc_url, pending: Default::default() }
+    }
+
+    /// queue request and wait for the batch containing it to execute
+    ///
+    /// a batch is sent once it reaches `batch_size` requests or a short delay after its first
+    /// request
+    pub async fn request<P, T, R>(
+        self: &Arc<Self>,
+        fetcher: Arc<Fetcher<P>>,
+        method: &'static str,
+        params: T,
+    ) -> Result<R>
+    where
+        P: JsonRpcClient + 'static,
+        T: Serialize,
+        R: DeserializeOwned,
+    {
+        let params = serde_json::to_value(params)
+            .map_err(|_| CollectError::CollectError("could not serialize params".to_string()))?;
+        let (sender, receiver) = oneshot::channel();
+        let (full_batch, is_first) = {
+            let mut pending = self.pending.lock().await;
+            let requests = pending.entry(method).or_default();
+            requests.push((params, sender));
+            let is_first = requests.len() == 1;
+            match requests.len() >= self.batch_size {
+                true => (pending.remove(&method), is_first),
+                false => (None, is_first),
+            }
+        };
+
+        if let Some(batch) = full_batch {
+            self.execute_batch(&fetcher, method, batch).await;
+        } else if is_first {
+            let batcher = self.clone();
+            tokio::spawn(
+                async move {
+                    tokio::time::sleep(BATCH_DELAY).await;
+                    let batch = batcher.pending.lock().await.remove(&method);
+                    if let Some(batch) = batch {
+                        batcher.execute_batch(&fetcher, method, batch).await;
+                    }
+                }
+                .in_current_span(),
+            );
+        }
+
+        let response = receiver
+            .await
+            .map_err(|_| CollectError::CollectError("rpc batch dropped".to_string()))??;
+        serde_json::from_value(response)
+            .map_err(|_| CollectError::CollectError(format!("could not parse {} response", method)))
+    }
+
+    async fn execute_batch<P: JsonRpcClient>(
+        &self,
+        fetcher: &Fetcher<P>,
+        method: &'static str,
+        batch: Vec<PendingRequest>,
+    ) {
+        let (params, senders): (Vec<_>, Vec<_>) = batch.into_iter().unzip();
+        if let Err(e) = fetcher.start_request(method) {
+            for sender in senders.into_iter() {
+                let _ = sender.send(Err(CollectError::CollectError(e.to_string())));
+            }
+            return
+        }
+        let _permit = fetcher.permit_request().await;
+        let t_start = std::time::Instant::now();
+        let result = self.send_batch(method, params).await;
+        let timed_out = matches!(&result, Err(e) if crate::sources::is_timeout(e));
+        fetcher.record_request(method, t_start.elapsed(), result.is_ok());
+        fetcher.tune_request(method, t_start.elapsed(), timed_out);
+        fetcher.finish_request(method, t_start.elapsed(), &result);
+        match result {
+            Ok(mut responses) => {
+                for (id, sender) in senders.into_iter().enumerate() {
+                    let response = responses.remove(&id).unwrap_or_else(|| {
+                        Err(CollectError::CollectError("missing rpc batch response".to_string()))
+                    });
+                    let _ = sender.send(response);
+                }
+            }
+            Err(e) => {
+                tracing::warn!(method, error = %e, "rpc batch request failed");
+                for sender in senders.into_iter() {
+                    let _ = sender.send(Err(CollectError::CollectError(e.to_string())));
+                }
+            }
+        }
+    }
+
+    /// send batch, returning the result or error of each request keyed by its index
+    async fn send_batch(
+        &self,
+        method: &'static str,
+        params: Vec<serde_json::Value>,
+    ) -> Result<HashMap<usize, Result<serde_json::Value>>> {
+        let requests: Vec<serde_json::Value> = params
+            .into_iter()
+            .enumerate()
+            .map(|(id, params)| {
+                serde_json::json!({"jsonrpc": "2.0", "id": id, "method": method, "params": params})
+            })
+            .collect();
+        let responses: Vec<BatchResponse> = self
+            .http_client
+            .post(&self.rpc_url)
+            .json(&requests)
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|e| CollectError::CollectError(format!("rpc batch failed: {}", e)))?
+            .json()
+            .await
+            .map_err(|e| {
+                CollectError::CollectError(format!("invalid rpc batch response: {}", e))
+            })?;
+        Ok(responses
+            .into_iter()
+            .map(|response| {
+                let result = match (response.result, response.error) {
+                    (_, Some(error)) => Err(CollectError::CollectError(error.to_string())),
+                    (Some(result), None) => Ok(result),
+                    (None, None) => Ok(serde_json::Value::Null),
+                };
+                (response.id, result)
+            })
+            .collect())
+    }
+}
@@ -28,6 +28,8 @@ pub struct Params {
     pub topic2: Option<Vec<u8>>,
     /// topic3
     pub topic3: Option<Vec<u8>>,
+    /// beacon slot
+    pub beacon_slot: Option<u64>,
 }
 
 impl Params {
@@ -56,11 +58,21 @@ impl Params {
         self.contract.clone().ok_or(err("contract not specified"))
     }
 
+    /// to address
+    pub fn to_address(&self) -> Result<Vec<u8>, CollectError> {
+        self.to_address.clone().ok_or(err("to_address not specified"))
+    }
+
     /// slot
     pub fn slot(&self) -> Result<Vec<u8>, CollectError> {
         self.slot.clone().ok_or(err("slot not specified"))
     }
 
+    /// beacon slot
+    pub fn beacon_slot(&self) -> Result<u64, CollectError> {
+        self.beacon_slot.ok_or(err("beacon_slot not specified"))
+    }
+
     /// call_data
     pub fn call_data(&self) -> Result<Vec<u8>, CollectError> {
         self.call_data.clone().ok_or(err("call_data not specified"))
@@ -97,7 +109,11 @@ impl Params {
             FilterBlockOption::Range { from_block: Some(start.into()), to_block: Some(end.into()) };
         let filter = Filter {
             block_option,
-            address: self.address.clone().map(|x| ValueOrArray::Value(H160::from_slice(&x))),
+            address: self
+                .contract
+                .clone()
+                .or_else(|| self.address.clone())
+                .map(|x| ValueOrArray::Value(H160::from_slice(&x))),
             topics: [
                 self.topic0.clone().map(|x| ValueOrArray::Value(Some(H256::from_slice(&x)))),
                 self.topic1.clone().map(|x| ValueOrArray::Value(Some(H256::from_slice(&x)))),
@@ -0,0 +1,106 @@
+use std::{collections::BTreeMap, sync::Mutex, time::Duration};
+
+/// counts and latencies of the requests sent for each rpc method
+#[derive(Debug, Default)]
+pub struct RpcStats {
+    methods: Mutex<BTreeMap<&'static str, MethodStats>>,
+}
+
+#[derive(Debug, Default)]
+struct MethodStats {
+    n_errors: u64,
+    latencies: Vec<Duration>,
+}
+
+/// summary of the requests sent for an rpc method
+#[derive(Clone, Debug, serde::Serialize)]
+pub struct RpcMethodSummary {
+    /// rpc method, e.g. eth_getLogs
+    pub method: String,
+    /// number of requests sent
+    pub n_requests: u64,
+    /// number of requests that returned an error
+    pub n_errors: u64,
+    /// mean latency in milliseconds
+    pub mean_ms: f64,
+    /// median latency in milliseconds
+    pub p50_ms: f64,
+    /// 90th percentile latency in milliseconds
+    pub p90_ms: f64,
+    /// 99th percentile latency in milliseconds
+    pub p99_ms: f64,
+    /// maximum latency in milliseconds
+    pub max_ms: f64,
+}
+
+impl RpcStats {
+    /// record the latency and outcome of a request
+    pub(crate) fn record(&self, method: &'static str, latency: Duration, success: bool) {
+        let mut methods = self.methods.lock().unwrap_or_else(|e| e.into_inner());
+        let stats = methods.entry(method).or_default();
+        stats.latencies.push(latency);
+        if !success {
+            stats.n_errors += 1;
+        }
+    }
+
+    /// summarize requests of each method, ordered by method name
+    pub fn summarize(&self) -> Vec<RpcMethodSummary> {
+        let methods = self.methods.lock().unwrap_or_else(|e| e.into_inner());
+        methods
+            .iter()
+            .map(|(method, stats)| {
+                let mut latencies: Vec<f64> =
+                    stats.latencies.iter().map(|latency| latency.as_secs_f64() * 1000.0).collect();
+                latencies.sort_by(|a, b| a.total_cmp(b));
+                let n_requests = latencies.len() as u64;
+                let mean_ms = match n_requests {
+                    0 => 0.0,
+                    _ => latencies.iter().sum::<f64>() / n_requests as f64,
+                };
+                RpcMethodSummary {
+                    method: method.to_string(),
+                    n_requests,
+                    n_errors: stats.n_errors,
+                    mean_ms,
+                    p50_ms: percentile(&latencies, 0.50),
+                    p90_ms: percentile(&latencies, 0.90),
+                    p99_ms: percentile(&latencies, 0.99),
+                    max_ms: latencies.last().copied().unwrap_or_default(),
+                }
+            })
+            .collect()
+    }
+}
+
+/// nearest-rank percentile of sorted values
+fn percentile(sorted: &[f64], quantile: f64) -> f64 {
+    if sorted.is_empty() {
+        return 0.0
+    }
+    let rank = (quantile * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rpc_stats_summary() {
+        let stats = RpcStats::default();
+        for ms in 1..=100 {
+            stats.record("eth_getLogs", Duration::from_millis(ms), ms != 100);
+        }
+        stats.record("eth_blockNumber", Duration::from_millis(5), true);
+
+        let summary = stats.summarize();
+        assert_eq!(summary.len(), 2);
+        assert_eq!(summary[0].method, "eth_blockNumber");
+        assert_eq!(summary[0].p99_ms, 5.0);
+        let logs = &summary[1];
+        assert_eq!((logs.n_requests, logs.n_errors), (100, 1));
+        assert_eq!((logs.p50_ms, logs.p90_ms, logs.p99_ms, logs.max_ms), (50.0, 90.0, 99.0, 100.0));
+        assert_eq!(logs.mean_ms, 50.5);
+    }
+}
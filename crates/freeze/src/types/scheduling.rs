@@ -0,0 +1,58 @@
+/// ordering of chunks of different datatypes by their relative cost
+use std::collections::VecDeque;
+
+/// order items so that each group receives an equal share of cost, like weighted fair queueing
+///
+/// items keep their relative order within a group. a group whose items cost c is scheduled
+/// once for every c items of a group of cost 1, so cheap groups are not stuck behind expensive
+/// ones, and groups of equal cost alternate item by item
+pub(crate) fn interleave_by_cost<T, K: PartialEq>(
+    items: Vec<T>,
+    group_and_cost: impl Fn(&T) -> (K, u64),
+) -> Vec<T> {
+    let n_items = items.len();
+    let mut queues: Vec<(K, u64, u64, VecDeque<T>)> = Vec::new();
+    for item in items.into_iter() {
+        let (key, cost) = group_and_cost(&item);
+        match queues.iter_mut().find(|(k, _, _, _)| *k == key) {
+            Some((_, _, _, queue)) => queue.push_back(item),
+            None => queues.push((key, cost.max(1), 0, VecDeque::from([item]))),
+        }
+    }
+
+    let mut ordered = Vec::with_capacity(n_items);
+    loop {
+        let next = queues
+            .iter_mut()
+            .filter(|(_, _, _, queue)| !queue.is_empty())
+            .min_by_key(|(_, cost, spent, _)| *spent + *cost);
+        match next {
+            Some((_, cost, spent, queue)) => {
+                *spent += *cost;
+                ordered.extend(queue.pop_front());
+            }
+            None => break,
+        }
+    }
+    ordered
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_interleave_by_cost() {
+        let items = vec![("traces", 0), ("traces", 1), ("logs", 0), ("logs", 1), ("logs", 2)];
+        let cost =
+            |item: &(&str, u32)| (item.0.to_string(), if item.0 == "traces" { 2 } else { 1 });
+        let ordered = interleave_by_cost(items, cost);
+        assert_eq!(
+            ordered,
+            vec![("logs", 0), ("traces", 0), ("logs", 1), ("logs", 2), ("traces", 1)]
+        );
+
+        let items = vec![("blocks", 0), ("logs", 0), ("blocks", 1), ("logs", 1)];
+        assert_eq!(interleave_by_cost(items.clone(), |item| (item.0.to_string(), 1)), items);
+    }
+}
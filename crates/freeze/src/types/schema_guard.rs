@@ -0,0 +1,318 @@
+/// guard against mixing schemas within the output files of a dataset
+use std::{
+    collections::{HashMap, HashSet},
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+use ethers::prelude::U256;
+use polars::prelude::*;
+
+use crate::{
+    dataframes, err, read_file_metadata, read_output_file, CollectError, Datatype, FileFormat,
+    FileOutput, Query, ToVecHex, U256Type,
+};
+
+/// schemas of the existing output files of each datatype, checked against the first collected
+/// chunk of the datatype before it is written
+///
+/// unless schema changes are allowed, a chunk whose columns or column types differ from an
+/// existing file is refused, as are the chunks of its datatype that have not been collected yet.
+/// if schema changes are allowed, the existing files are rewritten to the schema of the chunk.
+/// csv and json files do not record column types, so only their columns are compared
+pub struct SchemaGuard {
+    sink: FileOutput,
+    datatypes: Mutex<HashMap<Datatype, GuardState>>,
+}
+
+#[derive(Default)]
+struct GuardState {
+    /// existing files and their schemas, emptied once checked
+    files: Vec<(PathBuf, Schema)>,
+    /// reason that chunks of datatype are refused
+    refused: Option<String>,
+}
+
+impl SchemaGuard {
+    /// read the schemas of the existing output files of query, other than those about to be
+    /// rewritten
+    pub fn new(
+        query: &Query,
+        sink: &FileOutput,
+        rewritten: &HashSet<PathBuf>,
+    ) -> Result<SchemaGuard, CollectError> {
+        let extension = sink.file_extension();
+        let mut datatypes = HashMap::new();
+        for datatype in query.datatypes.iter().flat_map(|datatype| datatype.datatypes()) {
+            let pattern = format!("{}__{}__", sink.prefix, datatype.name());
+            let mut files = Vec::new();
+            for path in list_files(&sink.output_dir).into_iter() {
+                let name = path.file_name().and_then(|name| name.to_str()).unwrap_or_default();
+                if name.starts_with(&pattern) &&
+                    name.ends_with(&extension) &&
+                    !rewritten.contains(&path)
+                {
+                    let schema = read_output_file(&path, Some(0))?.schema();
+                    files.push((path, schema));
+                }
+            }
+            datatypes.insert(datatype, GuardState { files, refused: None });
+        }
+        Ok(SchemaGuard { sink: sink.clone(), datatypes: Mutex::new(datatypes) })
+    }
+
+    /// error if chunks of datatype are refused
+    pub fn check_allowed(&self, datatype: &Datatype) -> Result<(), CollectError> {
+        match self.state().get(datatype).and_then(|state| state.refused.clone()) {
+            Some(reason) => Err(CollectError::CollectError(reason)),
+            None => Ok(()),
+        }
+    }
+
+    /// check schema of collected chunk against existing files, migrating the files if schema
+    /// changes are allowed
+    pub fn check(&self, datatype: &Datatype, df: &DataFrame) -> Result<(), CollectError> {
+        let mut datatypes = self.state();
+        let state = match datatypes.get_mut(datatype) {
+            Some(state) => state,
+            None => return Ok(()),
+        };
+        if let Some(reason) = &state.refused {
+            return Err(CollectError::CollectError(reason.clone()))
+        }
+        let schema = df.schema();
+        let compare_types = self.sink.format == FileFormat::Parquet;
+        let changed: Vec<(PathBuf, Schema)> = state
+            .files
+            .drain(..)
+            .filter(|(_, file_schema)| !same_columns(file_schema, &schema, compare_types))
+            .collect();
+        let (path, file_schema) = match changed.first() {
+            Some(file) => file,
+            None => return Ok(()),
+        };
+        if !self.sink.allow_schema_change {
+            let reason = format!(
+                "schema of {} differs from {} existing files such as {} ({}), use \
+                 --allow-schema-change to migrate them",
+                datatype.name(),
+                changed.len(),
+                path.display(),
+                describe_changes(file_schema, &schema, compare_types).join(", "),
+            );
+            state.refused = Some(reason.clone());
+            return Err(CollectError::CollectError(reason))
+        }
+        tracing::info!(datatype = datatype.name(), n_files = changed.len(), "migrating schema");
+        for (path, _) in changed.iter() {
+            let mut migrated = migrate_dataframe(read_output_file(path, None)?, &schema)?;
+            let metadata = match self.sink.format {
+                FileFormat::Parquet => read_file_metadata(path)?,
+                FileFormat::Csv | FileFormat::Json => Vec::new(),
+            };
+            dataframes::df_to_file_with_metadata(&mut migrated, path, &self.sink, &metadata)
+                .map_err(|_| err("error writing migrated file"))?;
+        }
+        Ok(())
+    }
+
+    fn state(&self) -> std::sync::MutexGuard<'_, HashMap<Datatype, GuardState>> {
+        self.datatypes.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+fn list_files(dir: &Path) -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = match std::fs::read_dir(dir) {
+        Ok(entries) => entries.flatten().map(|entry| entry.path()).collect(),
+        Err(_) => Vec::new(),
+    };
+    files.sort();
+    files
+}
+
+/// whether schemas have the same columns, and optionally the same types, in any order
+fn same_columns(old: &Schema, new: &Schema, compare_types: bool) -> bool {
+    old.len() == new.len() &&
+        new.iter().all(|(name, dtype)| match old.get(name) {
+            Some(old_dtype) => !compare_types || old_dtype == dtype,
+            None => false,
+        })
+}
+
+/// describe columns added, removed, or optionally changed in type between schemas
+fn describe_changes(old: &Schema, new: &Schema, compare_types: bool) -> Vec<String> {
+    let mut changes = Vec::new();
+    for (name, dtype) in new.iter() {
+        match old.get(name) {
+            None => changes.push(format!("added {}", name)),
+            Some(old_dtype) if compare_types && old_dtype != dtype => {
+                changes.push(format!("changed {} from {} to {}", name, old_dtype, dtype))
+            }
+            Some(_) => {}
+        }
+    }
+    for (name, _) in old.iter() {
+        if new.get(name).is_none() {
+            changes.push(format!("removed {}", name))
+        }
+    }
+    changes
+}
+
+/// convert dataframe to schema
+///
+/// columns missing from dataframe are filled from another representation of the same u256
+/// value if there is one and are null otherwise, columns not in schema are dropped, and columns
+/// of a different type are cast, with binary columns converted to and from hex
+fn migrate_dataframe(df: DataFrame, schema: &Schema) -> Result<DataFrame, CollectError> {
+    let mut columns = Vec::new();
+    for (name, dtype) in schema.iter() {
+        let series = match df.column(name) {
+            Ok(series) if series.dtype() == dtype => series.clone(),
+            Ok(series) => cast_series(series, dtype)?,
+            Err(_) => match u256_values(&df, name) {
+                Some(values) => u256_series(name, &values, dtype)?,
+                None => Series::full_null(name, df.height(), dtype),
+            },
+        };
+        columns.push(series);
+    }
+    Ok(DataFrame::new(columns)?)
+}
+
+fn cast_series(series: &Series, dtype: &DataType) -> Result<Series, CollectError> {
+    match (series.dtype(), dtype) {
+        (DataType::Binary, DataType::Utf8) => {
+            let values: Vec<Option<Vec<u8>>> =
+                series.binary()?.into_iter().map(|v| v.map(|v| v.to_vec())).collect();
+            Ok(Series::new(series.name(), values.to_vec_hex()))
+        }
+        (DataType::Utf8, DataType::Binary) => {
+            let values: Vec<Option<Vec<u8>>> = series
+                .utf8()?
+                .into_iter()
+                .map(|v| v.and_then(|v| prefix_hex::decode::<Vec<u8>>(v).ok()))
+                .collect();
+            Ok(Series::new(series.name(), values))
+        }
+        _ => Ok(series.cast(dtype)?),
+    }
+}
+
+/// values of u256 column in another representation, ex: value_string for value_f64
+fn u256_values(df: &DataFrame, name: &str) -> Option<Vec<Option<U256>>> {
+    let suffixes = [U256Type::Binary.suffix(), U256Type::String.suffix()];
+    let stem = [U256Type::F32, U256Type::F64, U256Type::U32, U256Type::U64, U256Type::Decimal128]
+        .iter()
+        .map(|u256_type| u256_type.suffix())
+        .chain(suffixes.iter().cloned())
+        .find_map(|suffix| name.strip_suffix(suffix.as_str()))?;
+    for suffix in suffixes.iter() {
+        let series = match df.column(&format!("{}{}", stem, suffix)) {
+            Ok(series) => series,
+            Err(_) => continue,
+        };
+        let values = match series.dtype() {
+            DataType::Binary => series
+                .binary()
+                .ok()?
+                .into_iter()
+                .map(|v| v.filter(|v| v.len() == 32).map(U256::from_big_endian))
+                .collect(),
+            DataType::Utf8 if suffix == &U256Type::Binary.suffix() => series
+                .utf8()
+                .ok()?
+                .into_iter()
+                .map(|v| v.and_then(|v| prefix_hex::decode::<Vec<u8>>(v).ok()))
+                .map(|v| v.filter(|v| v.len() == 32).map(|v| U256::from_big_endian(&v)))
+                .collect(),
+            DataType::Utf8 => series
+                .utf8()
+                .ok()?
+                .into_iter()
+                .map(|v| v.and_then(|v| U256::from_dec_str(v).ok()))
+                .collect(),
+            _ => continue,
+        };
+        return Some(values)
+    }
+    None
+}
+
+fn u256_series(
+    name: &str,
+    values: &[Option<U256>],
+    dtype: &DataType,
+) -> Result<Series, CollectError> {
+    let series = match dtype {
+        DataType::Binary => {
+            let values: Vec<Option<Vec<u8>>> = values
+                .iter()
+                .map(|v| {
+                    v.map(|v| {
+                        let mut bytes = [0u8; 32];
+                        v.to_big_endian(&mut bytes);
+                        bytes.to_vec()
+                    })
+                })
+                .collect();
+            Series::new(name, values)
+        }
+        DataType::Utf8 if name.ends_with(U256Type::Binary.suffix().as_str()) => {
+            let binary = u256_series(name, values, &DataType::Binary)?;
+            cast_series(&binary, &DataType::Utf8)?
+        }
+        _ => {
+            let values: Vec<Option<String>> =
+                values.iter().map(|v| v.map(|v| v.to_string())).collect();
+            Series::new(name, values).cast(dtype)?
+        }
+    };
+    Ok(series)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_migrate_dataframe() {
+        let value = {
+            let mut bytes = [0u8; 32];
+            U256::from(1000).to_big_endian(&mut bytes);
+            bytes.to_vec()
+        };
+        let df = df!(
+            "block_number" => [1u32],
+            "from_address" => [vec![0xabu8, 0xcd]],
+            "value_binary" => [value],
+            "gas_used" => [21000u64],
+        )
+        .unwrap();
+        let schema = Schema::from_iter(vec![
+            Field::new("block_number", DataType::UInt64),
+            Field::new("from_address", DataType::Utf8),
+            Field::new("value_string", DataType::Utf8),
+            Field::new("value_f64", DataType::Float64),
+            Field::new("nonce", DataType::UInt64),
+        ]);
+        assert_eq!(
+            describe_changes(&df.schema(), &schema, true),
+            vec![
+                "changed block_number from u32 to u64",
+                "changed from_address from binary to str",
+                "added value_string",
+                "added value_f64",
+                "added nonce",
+                "removed value_binary",
+                "removed gas_used",
+            ]
+        );
+        let migrated = migrate_dataframe(df, &schema).unwrap();
+        assert!(same_columns(&migrated.schema(), &schema, true));
+        assert_eq!(migrated.column("from_address").unwrap().utf8().unwrap().get(0), Some("0xabcd"));
+        assert_eq!(migrated.column("value_string").unwrap().utf8().unwrap().get(0), Some("1000"));
+        assert_eq!(migrated.column("value_f64").unwrap().f64().unwrap().get(0), Some(1000.0));
+        assert_eq!(migrated.column("nonce").unwrap().null_count(), 1);
+    }
+}
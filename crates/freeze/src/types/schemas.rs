@@ -1,8 +1,12 @@
 /// types and functions related to schemas
 use std::collections::{HashMap, HashSet};
 
-use crate::LogDecoder;
+use crate::{
+    CollectError, DerivedColumn, ErrorDecoder, FunctionDecoder, FunctionSignatures, JsTracer,
+    LogDecoder, ParseError, RowFilter, TraceFilter, WasmTransform,
+};
 use indexmap::{IndexMap, IndexSet};
+use polars::prelude::{DataFrame, IntoLazy};
 use thiserror::Error;
 
 use crate::types::{ColumnEncoding, Datatype};
@@ -27,8 +31,38 @@ pub struct Table {
     /// representation to use for binary columns
     pub binary_type: ColumnEncoding,
 
+    /// representations of binary columns that differ from binary_type
+    pub column_encodings: HashMap<String, ColumnEncoding>,
+
     /// log decoder for table
     pub log_decoder: Option<LogDecoder>,
+
+    /// filter applied to trace rows of table
+    pub trace_filter: Option<TraceFilter>,
+
+    /// function signatures used to label the call data of table
+    pub function_signatures: Option<FunctionSignatures>,
+
+    /// calldata decoder for table
+    pub function_decoder: Option<FunctionDecoder>,
+
+    /// decoder of the custom errors in revert data of table
+    pub error_decoder: Option<ErrorDecoder>,
+
+    /// javascript tracer whose results are collected into table
+    pub js_tracer: Option<JsTracer>,
+
+    /// columns computed from other columns of table, in order of computation
+    pub derived_columns: Vec<DerivedColumn>,
+
+    /// filter that rows of table must pass to be written
+    pub row_filter: Option<RowFilter>,
+
+    /// user-defined transform applied to each chunk of table
+    pub transform: Option<WasmTransform>,
+
+    /// SQL query run over each chunk of table
+    pub sql: Option<String>,
 }
 
 impl Table {
@@ -46,6 +80,57 @@ impl Table {
     pub fn columns(&self) -> Vec<&str> {
         self.columns.keys().map(|x| x.as_str()).collect()
     }
+
+    /// get representation of binary column
+    pub fn column_encoding(&self, column: &str) -> &ColumnEncoding {
+        self.column_encodings.get(column).unwrap_or(&self.binary_type)
+    }
+
+    /// set representation of binary column, which must be a binary or hex column of Table
+    pub fn set_column_encoding(
+        &mut self,
+        column: &str,
+        encoding: ColumnEncoding,
+    ) -> Result<(), SchemaError> {
+        let column_type = match encoding.is_hex() {
+            true => ColumnType::Hex,
+            false => ColumnType::Binary,
+        };
+        match self.columns.get_mut(column) {
+            Some(ctype) if matches!(ctype, ColumnType::Binary | ColumnType::Hex) => {
+                *ctype = column_type;
+                self.column_encodings.insert(column.to_string(), encoding);
+                Ok(())
+            }
+            _ => Err(SchemaError::InvalidColumn),
+        }
+    }
+
+    /// append derived column to Table, inferring its type from the columns it references
+    pub fn add_derived_column(&mut self, column: DerivedColumn) -> Result<(), ParseError> {
+        if self.has_column(&column.name) {
+            return Err(ParseError::ParseError(format!(
+                "derived column {} already exists in {}",
+                column.name,
+                self.datatype.name()
+            )))
+        }
+        let column_type = column.column_type(self)?;
+        self.columns.insert(column.name.clone(), column_type);
+        self.derived_columns.push(column);
+        Ok(())
+    }
+
+    /// compute derived columns of Table from the other columns of dataframe
+    pub fn derive_columns(&self, df: DataFrame) -> Result<DataFrame, CollectError> {
+        let mut df = df.lazy();
+        for column in self.derived_columns.iter() {
+            let expr =
+                column.to_expr(self).map_err(|e| CollectError::CollectError(e.to_string()))?;
+            df = df.with_column(expr);
+        }
+        df.collect().map_err(CollectError::PolarsError)
+    }
 }
 
 /// representation of a U256 datum
@@ -120,6 +205,8 @@ pub enum ColumnType {
     Binary,
     /// Hex column type
     Hex,
+    /// Boolean column type
+    Boolean,
 }
 
 impl ColumnType {
@@ -137,6 +224,7 @@ impl ColumnType {
             ColumnType::String => "string",
             ColumnType::Binary => "binary",
             ColumnType::Hex => "hex",
+            ColumnType::Boolean => "boolean",
         }
     }
 }
@@ -175,7 +263,7 @@ impl Datatype {
         let mut columns = IndexMap::new();
         for column in used_columns {
             let mut ctype = column_types.get(column.as_str()).ok_or(SchemaError::InvalidColumn)?;
-            if (*binary_column_format == ColumnEncoding::Hex) & (ctype == &ColumnType::Binary) {
+            if binary_column_format.is_hex() & (ctype == &ColumnType::Binary) {
                 ctype = &ColumnType::Hex;
             }
             columns.insert((*column.clone()).to_string(), *ctype);
@@ -187,7 +275,17 @@ impl Datatype {
             columns,
             u256_types: u256_types.clone(),
             binary_type: binary_column_format.clone(),
+            column_encodings: HashMap::new(),
             log_decoder,
+            trace_filter: None,
+            function_signatures: None,
+            function_decoder: None,
+            error_decoder: None,
+            js_tracer: None,
+            derived_columns: Vec::new(),
+            row_filter: None,
+            transform: None,
+            sql: None,
         };
         Ok(schema)
     }
@@ -244,9 +342,10 @@ mod tests {
         let table = Datatype::Blocks
             .table_schema(&get_u256_types(), &ColumnEncoding::Hex, &None, &None, &cols, None, None)
             .unwrap();
-        assert_eq!(15, table.columns().len());
+        assert_eq!(21, table.columns().len());
         assert!(table.columns().contains(&"block_hash"));
         assert!(table.columns().contains(&"transactions_root"));
+        assert!(table.columns().contains(&"parent_beacon_block_root"));
     }
 
     #[test]
@@ -295,7 +394,7 @@ mod tests {
                 None,
             )
             .unwrap();
-        assert_eq!(15, table.columns().len());
+        assert_eq!(21, table.columns().len());
         assert!(table.columns().contains(&"block_hash"));
         assert!(table.columns().contains(&"transactions_root"));
     }
@@ -0,0 +1,157 @@
+/// graceful shutdown of collection on SIGINT or SIGTERM
+use std::sync::{
+    atomic::{AtomicBool, AtomicU64, Ordering},
+    Arc, OnceLock,
+};
+
+use tokio::sync::Notify;
+
+static SHUTDOWN: OnceLock<Arc<Shutdown>> = OnceLock::new();
+
+/// shutdown requests received during collection
+///
+/// after the first request, chunks that have not started are left uncollected while in-flight
+/// chunks are finished and written. after the second, in-flight chunks that are still collecting
+/// are dropped as well. files are only written once complete, so rerunning the same command
+/// resumes collection from the chunks that were left
+#[derive(Debug, Default)]
+pub struct Shutdown {
+    n_requests: AtomicU64,
+    collecting: AtomicBool,
+    notify: Notify,
+}
+
+impl Shutdown {
+    /// handle SIGINT and SIGTERM for the rest of the process, listened for by a task started on
+    /// first call
+    ///
+    /// signals received while no chunks are being collected exit the process immediately, as
+    /// they would without a handler
+    pub fn on_signal() -> Arc<Shutdown> {
+        SHUTDOWN
+            .get_or_init(|| {
+                let shutdown = Arc::new(Shutdown::default());
+                tokio::spawn(listen(shutdown.clone()));
+                shutdown
+            })
+            .clone()
+    }
+
+    /// shutdown of process, if signals are handled by `Shutdown::on_signal()`
+    pub fn on_signal_if_handled() -> Option<Arc<Shutdown>> {
+        SHUTDOWN.get().cloned()
+    }
+
+    /// request shutdown, escalating to an abort of in-flight chunks on the second request
+    pub fn request(&self) {
+        self.n_requests.fetch_add(1, Ordering::SeqCst);
+        self.notify.notify_waiters();
+    }
+
+    /// mark whether chunks are being collected, so that signals request shutdown rather than
+    /// exit the process
+    pub fn set_collecting(&self, collecting: bool) {
+        self.collecting.store(collecting, Ordering::SeqCst);
+    }
+
+    /// whether chunks that have not started should be left uncollected
+    pub fn is_requested(&self) -> bool {
+        self.n_requests.load(Ordering::SeqCst) >= 1
+    }
+
+    /// whether in-flight chunks should be dropped
+    pub fn is_aborted(&self) -> bool {
+        self.n_requests.load(Ordering::SeqCst) >= 2
+    }
+
+    /// wait until shutdown is requested
+    pub async fn requested(&self) {
+        loop {
+            let notified = self.notify.notified();
+            if self.is_requested() {
+                return
+            }
+            notified.await;
+        }
+    }
+
+    /// wait until in-flight chunks should be dropped
+    pub async fn aborted(&self) {
+        loop {
+            let notified = self.notify.notified();
+            if self.is_aborted() {
+                return
+            }
+            notified.await;
+        }
+    }
+}
+
+async fn listen(shutdown: Arc<Shutdown>) {
+    let mut signals = match Signals::new() {
+        Ok(signals) => signals,
+        Err(_) => return,
+    };
+    while signals.recv().await {
+        if !shutdown.collecting.load(Ordering::SeqCst) || shutdown.is_aborted() {
+            std::process::exit(130)
+        }
+        shutdown.request();
+        if shutdown.is_aborted() {
+            eprintln!("\ninterrupted again, dropping chunks that are still collecting");
+        } else {
+            eprintln!(
+                "\ninterrupted, finishing in-flight chunks (interrupt again to drop them instead)"
+            );
+        }
+    }
+}
+
+/// SIGINT and SIGTERM, or only Ctrl-C on platforms without unix signals
+struct Signals {
+    #[cfg(unix)]
+    terminate: tokio::signal::unix::Signal,
+}
+
+impl Signals {
+    fn new() -> std::io::Result<Signals> {
+        Ok(Signals {
+            #[cfg(unix)]
+            terminate: tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())?,
+        })
+    }
+
+    /// wait for next signal, returning false if signals can no longer be received
+    async fn recv(&mut self) -> bool {
+        #[cfg(unix)]
+        {
+            tokio::select! {
+                result = tokio::signal::ctrl_c() => result.is_ok(),
+                signal = self.terminate.recv() => signal.is_some(),
+            }
+        }
+        #[cfg(not(unix))]
+        {
+            tokio::signal::ctrl_c().await.is_ok()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_shutdown_escalates() {
+        let shutdown = Arc::new(Shutdown::default());
+        let waiter = tokio::spawn({
+            let shutdown = shutdown.clone();
+            async move { shutdown.aborted().await }
+        });
+        shutdown.request();
+        assert!(shutdown.is_requested() && !shutdown.is_aborted());
+        shutdown.request();
+        assert!(shutdown.is_aborted());
+        waiter.await.unwrap();
+    }
+}
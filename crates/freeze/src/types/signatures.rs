@@ -14,9 +14,129 @@ lazy_static::lazy_static! {
     /// function signature of FUNCTION_ERC20_BALANCE_OF
     pub static ref FUNCTION_ERC20_BALANCE_OF: Vec<u8> = prefix_hex::decode("0x70a08231").expect("Decoding failed");
 
+    /// function signature of FUNCTION_ERC20_ALLOWANCE
+    pub static ref FUNCTION_ERC20_ALLOWANCE: Vec<u8> = prefix_hex::decode("0xdd62ed3e").expect("Decoding failed");
+
     /// function signature of FUNCTION_ERC20_TOTAL_SUPPLY
     pub static ref FUNCTION_ERC20_TOTAL_SUPPLY: Vec<u8> = prefix_hex::decode("0x18160ddd").expect("Decoding failed");
 
+    /// function signature of FUNCTION_ERC721_OWNER_OF
+    pub static ref FUNCTION_ERC721_OWNER_OF: Vec<u8> = prefix_hex::decode("0x6352211e").expect("Decoding failed");
+
+    /// function signature of FUNCTION_MULTICALL3_AGGREGATE3
+    pub static ref FUNCTION_MULTICALL3_AGGREGATE3: Vec<u8> = prefix_hex::decode("0x82ad56cb").expect("Decoding failed");
+
+    /// address of Multicall3, deployed at the same address on most chains
+    pub static ref MULTICALL3_ADDRESS: H160 = H160::from_slice(
+        &prefix_hex::decode::<Vec<u8>>("0xca11bde05977b3631167028862be2a173976ca11")
+            .expect("Decoding failed"),
+    );
+
+    /// function signature of FUNCTION_BEACON_IMPLEMENTATION
+    pub static ref FUNCTION_BEACON_IMPLEMENTATION: Vec<u8> = prefix_hex::decode("0x5c60da1b").expect("Decoding failed");
+
+    /// storage slot of EIP-1967 proxy implementation address
+    pub static ref SLOT_EIP1967_IMPLEMENTATION: H256 = H256(
+        prefix_hex::decode("0x360894a13ba1a3210667c828492db98dca3e2076cc3735a920a3ca505d382bbc")
+            .expect("Decoding failed"),
+    );
+
+    /// storage slot of EIP-1967 proxy admin address
+    pub static ref SLOT_EIP1967_ADMIN: H256 = H256(
+        prefix_hex::decode("0xb53127684a568b3173ae13b9f8a6016e243e63b6e8ee1178d6a717850b5d6103")
+            .expect("Decoding failed"),
+    );
+
+    /// storage slot of EIP-1967 proxy beacon address
+    pub static ref SLOT_EIP1967_BEACON: H256 = H256(
+        prefix_hex::decode("0xa3f0ad74e5423aebfd80d3ef4346578335a9a72aeaee59ff6cb3582b35133d50")
+            .expect("Decoding failed"),
+    );
+
+    /// function signature of FUNCTION_UNISWAP_TOKEN0
+    pub static ref FUNCTION_UNISWAP_TOKEN0: Vec<u8> = prefix_hex::decode("0x0dfe1681").expect("Decoding failed");
+
+    /// function signature of FUNCTION_UNISWAP_TOKEN1
+    pub static ref FUNCTION_UNISWAP_TOKEN1: Vec<u8> = prefix_hex::decode("0xd21220a7").expect("Decoding failed");
+
+    /// function signature of FUNCTION_CURVE_COINS
+    pub static ref FUNCTION_CURVE_COINS: Vec<u8> = prefix_hex::decode("0xc6610657").expect("Decoding failed");
+
+    /// event hash of EVENT_UNISWAP_V2_SWAP
+    pub static ref EVENT_UNISWAP_V2_SWAP: H256 = H256(
+        prefix_hex::decode("0xd78ad95fa46c994b6551d0da85fc275fe613ce37657fb8d5e3d130840159d822")
+            .expect("Decoding failed"),
+    );
+
+    /// event hash of EVENT_UNISWAP_V3_SWAP
+    pub static ref EVENT_UNISWAP_V3_SWAP: H256 = H256(
+        prefix_hex::decode("0xc42079f94a6350d7e6235f29174924f928cc2ac818eb64fed8004e115fbcca67")
+            .expect("Decoding failed"),
+    );
+
+    /// event hash of EVENT_CURVE_TOKEN_EXCHANGE, emitted by stableswap pools
+    pub static ref EVENT_CURVE_TOKEN_EXCHANGE: H256 = H256(
+        prefix_hex::decode("0x8b3e96f2b889fa771c53c981b40daf005f63f637f1869f707052d15a3dd97140")
+            .expect("Decoding failed"),
+    );
+
+    /// event hash of EVENT_CURVE_CRYPTO_TOKEN_EXCHANGE, emitted by cryptoswap pools
+    pub static ref EVENT_CURVE_CRYPTO_TOKEN_EXCHANGE: H256 = H256(
+        prefix_hex::decode("0xb2e76ae99761dc136e598d4a629bb347eccb9532a5f8bbd72e18467c3c34cc98")
+            .expect("Decoding failed"),
+    );
+
+    /// event hash of EVENT_BALANCER_SWAP, emitted by the balancer v2 vault
+    pub static ref EVENT_BALANCER_SWAP: H256 = H256(
+        prefix_hex::decode("0x2170c741c41531aec20e7c107c24eecfdd15e69c9bb0a8dd37b1840b9e0b207b")
+            .expect("Decoding failed"),
+    );
+
+    /// event hash of EVENT_SEAPORT_ORDER_FULFILLED
+    pub static ref EVENT_SEAPORT_ORDER_FULFILLED: H256 = H256(
+        prefix_hex::decode("0x9d9af8e38d66c62e2c12f0225249fd9d721c54b83f48d9352c97c6cacdcb6f31")
+            .expect("Decoding failed"),
+    );
+
+    /// event hash of EVENT_BLUR_ORDERS_MATCHED
+    pub static ref EVENT_BLUR_ORDERS_MATCHED: H256 = H256(
+        prefix_hex::decode("0x61cbb2a3dee0b6064c2e681aadd61677fb4ef319f0b547508d495626f5a62f64")
+            .expect("Decoding failed"),
+    );
+
+    /// event hash of EVENT_X2Y2_INVENTORY
+    pub static ref EVENT_X2Y2_INVENTORY: H256 = H256(
+        prefix_hex::decode("0x3cbb63f144840e5b1b0a38a7c19211d2e89de4d7c5faf8b2d3c1776c302d1d33")
+            .expect("Decoding failed"),
+    );
+
+    /// function signature of FUNCTION_CHAINLINK_AGGREGATOR, returning aggregator of proxy
+    pub static ref FUNCTION_CHAINLINK_AGGREGATOR: Vec<u8> = prefix_hex::decode("0x245a7bfc").expect("Decoding failed");
+
+    /// event hash of EVENT_CHAINLINK_ANSWER_UPDATED
+    pub static ref EVENT_CHAINLINK_ANSWER_UPDATED: H256 = H256(
+        prefix_hex::decode("0x0559884fd3a460db3073b7fc896cc77986f16e378210ded43186175bf646fc5f")
+            .expect("Decoding failed"),
+    );
+
+    /// event hash of EVENT_CHAINLINK_FEED_CONFIRMED, emitted by the feed registry
+    pub static ref EVENT_CHAINLINK_FEED_CONFIRMED: H256 = H256(
+        prefix_hex::decode("0x27a180c70f2642f63d1694eb252b7df52e7ab2565e3f67adf7748acb7d82b9bc")
+            .expect("Decoding failed"),
+    );
+
+    /// address of chainlink feed registry on ethereum mainnet
+    pub static ref CHAINLINK_FEED_REGISTRY: H160 = H160::from_slice(
+        &prefix_hex::decode::<Vec<u8>>("0x47fb2585d2c56fe188d0e6ec628a38b74fceeedf")
+            .expect("Decoding failed"),
+    );
+
+    /// event hash of EVENT_BEACON_DEPOSIT, emitted by the beacon deposit contract
+    pub static ref EVENT_BEACON_DEPOSIT: H256 = H256(
+        prefix_hex::decode("0x649bbc62d0e31342afea4e5cd82d4049e7e1ee912fc0889aa790803be39038c5")
+            .expect("Decoding failed"),
+    );
+
     /// event hash of EVENT_ERC20_TRANSFER
     pub static ref EVENT_ERC20_TRANSFER: H256 = H256(
         prefix_hex::decode("0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef")
@@ -0,0 +1,344 @@
+/// locations of output files, in local directories or in object stores
+use std::{
+    collections::HashSet,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use futures::StreamExt;
+use object_store::{path::Path as ObjectPath, ObjectStore};
+use tokio::io::AsyncWriteExt;
+
+use crate::{CollectError, ParseError};
+
+/// maximum length in bytes of a file name on common file systems
+const MAX_FILE_NAME_BYTES: usize = 255;
+
+/// files larger than this are streamed to object stores in multipart uploads
+const MULTIPART_THRESHOLD_BYTES: u64 = 16 * 1024 * 1024;
+
+/// number of attempts of an upload before its chunk errors
+const MAX_UPLOAD_ATTEMPTS: u32 = 4;
+
+/// object store of an output uri
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ObjectStoreScheme {
+    /// Amazon S3, from `s3://` uris
+    S3,
+    /// Google Cloud Storage, from `gs://` uris
+    Gcs,
+    /// Azure Blob Storage, from `az://` uris naming a container
+    Azure,
+}
+
+impl ObjectStoreScheme {
+    /// scheme of uris of object store
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ObjectStoreScheme::S3 => "s3",
+            ObjectStoreScheme::Gcs => "gs",
+            ObjectStoreScheme::Azure => "az",
+        }
+    }
+}
+
+/// location of output files, given as a path or as a uri
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum OutputLocation {
+    /// local directory, from a path or a `file://` uri
+    Local(PathBuf),
+    /// bucket of an object store and prefix of the keys of files, from a `s3://`, `gs://`, or
+    /// `az://` uri
+    ObjectStore {
+        /// object store of bucket
+        scheme: ObjectStoreScheme,
+        /// name of bucket
+        bucket: String,
+        /// prefix of keys, without leading or trailing slashes
+        prefix: String,
+    },
+}
+
+impl OutputLocation {
+    /// parse output path or uri
+    pub fn parse(output: &str) -> Result<OutputLocation, ParseError> {
+        let (scheme, rest) = match output.split_once("://") {
+            Some((scheme, rest)) => (scheme, rest),
+            None => return Ok(OutputLocation::Local(PathBuf::from(output))),
+        };
+        let scheme = match scheme {
+            "file" => return Ok(OutputLocation::Local(PathBuf::from(rest))),
+            "s3" => ObjectStoreScheme::S3,
+            "gs" => ObjectStoreScheme::Gcs,
+            "az" => ObjectStoreScheme::Azure,
+            _ => {
+                return Err(ParseError::ParseError(format!(
+                "unsupported output uri {}, use a path or a file://, s3://, gs://, or az:// uri",
+                output
+            )))
+            }
+        };
+        let (bucket, prefix) = rest.split_once('/').unwrap_or((rest, ""));
+        if bucket.is_empty() {
+            return Err(ParseError::ParseError(format!("output uri {} has no bucket", output)))
+        }
+        let prefix = prefix.trim_matches('/').to_string();
+        Ok(OutputLocation::ObjectStore { scheme, bucket: bucket.to_string(), prefix })
+    }
+
+    /// uri of location
+    pub fn uri(&self) -> String {
+        match self {
+            OutputLocation::Local(path) => format!("file://{}", path.display()),
+            OutputLocation::ObjectStore { scheme, bucket, prefix } if prefix.is_empty() => {
+                format!("{}://{}", scheme.as_str(), bucket)
+            }
+            OutputLocation::ObjectStore { scheme, bucket, prefix } => {
+                format!("{}://{}/{}", scheme.as_str(), bucket, prefix)
+            }
+        }
+    }
+
+    /// local directory that files are written to, which for object stores is a staging
+    /// directory in `~/.cryo/staging` mirroring the bucket, from which files are removed once
+    /// they are uploaded
+    pub fn local_dir(&self) -> PathBuf {
+        match self {
+            OutputLocation::Local(path) => path.clone(),
+            OutputLocation::ObjectStore { scheme, bucket, prefix } => {
+                let staging = match std::env::var_os("HOME") {
+                    Some(home) => PathBuf::from(home).join(".cryo/staging"),
+                    None => std::env::temp_dir().join("cryo_staging"),
+                };
+                let mut dir = staging.join(scheme.as_str()).join(sanitize_label(bucket));
+                for piece in prefix.split('/').filter(|piece| !piece.is_empty()) {
+                    dir = dir.join(sanitize_label(piece));
+                }
+                dir
+            }
+        }
+    }
+}
+
+/// object store that output files are uploaded to once they are written to the output dir
+///
+/// credentials and endpoints are read from the environment, such as `AWS_ACCESS_KEY_ID` and
+/// `AWS_ENDPOINT` for S3, `GOOGLE_SERVICE_ACCOUNT` for Google Cloud Storage, or
+/// `AZURE_STORAGE_ACCOUNT_NAME` and `AZURE_STORAGE_ACCOUNT_KEY` for Azure Blob Storage
+#[derive(Clone)]
+pub struct RemoteOutput {
+    /// uri of output files, ex: `s3://bucket/prefix`
+    pub uri: String,
+    store: Arc<dyn ObjectStore>,
+    prefix: String,
+    /// paths relative to the output dir of files already in the object store, once listed
+    existing: Arc<HashSet<PathBuf>>,
+}
+
+impl std::fmt::Debug for RemoteOutput {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RemoteOutput").field("uri", &self.uri).finish_non_exhaustive()
+    }
+}
+
+impl RemoteOutput {
+    /// object store of location, None for local directories
+    pub fn new(location: &OutputLocation) -> Result<Option<RemoteOutput>, ParseError> {
+        let (scheme, bucket, prefix) = match location {
+            OutputLocation::Local(_) => return Ok(None),
+            OutputLocation::ObjectStore { scheme, bucket, prefix } => (scheme, bucket, prefix),
+        };
+        let store: Result<Arc<dyn ObjectStore>, object_store::Error> = match scheme {
+            ObjectStoreScheme::S3 => object_store::aws::AmazonS3Builder::from_env()
+                .with_bucket_name(bucket)
+                .build()
+                .map(|store| Arc::new(store) as Arc<dyn ObjectStore>),
+            ObjectStoreScheme::Gcs => object_store::gcp::GoogleCloudStorageBuilder::from_env()
+                .with_bucket_name(bucket)
+                .build()
+                .map(|store| Arc::new(store) as Arc<dyn ObjectStore>),
+            ObjectStoreScheme::Azure => object_store::azure::MicrosoftAzureBuilder::from_env()
+                .with_container_name(bucket)
+                .build()
+                .map(|store| Arc::new(store) as Arc<dyn ObjectStore>),
+        };
+        let store = store.map_err(|e| {
+            ParseError::ParseError(format!("could not connect to {}: {}", location.uri(), e))
+        })?;
+        Ok(Some(RemoteOutput {
+            uri: location.uri(),
+            store,
+            prefix: prefix.clone(),
+            existing: Default::default(),
+        }))
+    }
+
+    /// list the files already in the object store, so that their chunks are skipped like chunks
+    /// whose files are in the output dir
+    pub async fn list_existing(self) -> Result<RemoteOutput, CollectError> {
+        let prefix = (!self.prefix.is_empty()).then(|| ObjectPath::from(self.prefix.as_str()));
+        let list_error = |e: object_store::Error| {
+            CollectError::CollectError(format!("could not list {}: {}", self.uri, e))
+        };
+        let mut objects = self.store.list(prefix.as_ref()).await.map_err(list_error)?;
+        let mut existing = HashSet::new();
+        while let Some(object) = objects.next().await {
+            let location = object.map_err(list_error)?.location;
+            let mut parts: Vec<String> =
+                location.parts().map(|part| part.as_ref().to_string()).collect();
+            if let Some(prefix) = &prefix {
+                parts.drain(..prefix.parts().count().min(parts.len()));
+            }
+            existing.insert(parts.iter().collect::<PathBuf>());
+        }
+        drop(objects);
+        Ok(RemoteOutput { existing: Arc::new(existing), ..self })
+    }
+
+    /// whether file at path relative to the output dir was in the object store when listed
+    pub fn has_file(&self, relative_path: &Path) -> bool {
+        self.existing.contains(relative_path)
+    }
+
+    /// key of file at path relative to the output dir
+    fn key(&self, relative_path: &Path) -> ObjectPath {
+        let mut pieces: Vec<String> = vec![];
+        if !self.prefix.is_empty() {
+            pieces.push(self.prefix.clone());
+        }
+        pieces.extend(relative_path.iter().map(|piece| piece.to_string_lossy().to_string()));
+        ObjectPath::from(pieces.join("/"))
+    }
+
+    /// upload file of output dir to the same relative key of object store, retrying with backoff
+    pub async fn upload(&self, path: &Path, output_dir: &Path) -> Result<(), CollectError> {
+        let relative_path = path.strip_prefix(output_dir).map_err(|_| {
+            CollectError::CollectError(format!("{} is not in output dir", path.display()))
+        })?;
+        let key = self.key(relative_path);
+        let path = long_path(path);
+        let mut attempt = 1;
+        loop {
+            match self.upload_once(&path, &key).await {
+                Ok(()) => return Ok(()),
+                Err(e) if attempt < MAX_UPLOAD_ATTEMPTS => {
+                    tracing::warn!(path = %path.display(), attempt, error = %e, "retrying upload");
+                    tokio::time::sleep(std::time::Duration::from_secs(1 << (attempt - 1))).await;
+                    attempt += 1;
+                }
+                Err(e) => {
+                    return Err(CollectError::CollectError(format!(
+                        "could not upload {}: {}",
+                        path.display(),
+                        e
+                    )))
+                }
+            }
+        }
+    }
+
+    /// upload file in a single request, or stream it in a multipart upload if it is large
+    async fn upload_once(&self, path: &Path, key: &ObjectPath) -> Result<(), String> {
+        let size = std::fs::metadata(path).map_err(|e| e.to_string())?.len();
+        if size <= MULTIPART_THRESHOLD_BYTES {
+            let bytes = tokio::fs::read(path).await.map_err(|e| e.to_string())?;
+            return self.store.put(key, bytes.into()).await.map_err(|e| e.to_string())
+        }
+        let (upload_id, mut writer) =
+            self.store.put_multipart(key).await.map_err(|e| e.to_string())?;
+        let streamed = async {
+            let mut file = tokio::fs::File::open(path).await?;
+            tokio::io::copy(&mut file, &mut writer).await?;
+            writer.shutdown().await
+        };
+        if let Err(e) = streamed.await {
+            let _ = self.store.abort_multipart(key, &upload_id).await;
+            return Err(e.to_string())
+        }
+        Ok(())
+    }
+}
+
+/// replace characters of user supplied labels that some platform does not allow in file names
+pub fn sanitize_label(label: &str) -> String {
+    let sanitized: String = label
+        .chars()
+        .map(|c| match c {
+            '<' | '>' | ':' | '"' | '/' | '\\' | '|' | '?' | '*' => '_',
+            c if c.is_control() => '_',
+            c => c,
+        })
+        .collect();
+    // windows does not allow file names that end in dots or spaces
+    sanitized.trim_end_matches(['.', ' ']).to_string()
+}
+
+/// file name of stem and extension, with the end of stem replaced by a hash of stem if the name
+/// would be longer than file systems allow
+pub fn fit_file_name(stem: &str, extension: &str) -> String {
+    let name = format!("{}{}", stem, extension);
+    if name.len() <= MAX_FILE_NAME_BYTES {
+        return name
+    }
+    let hash = ethers::utils::hex::encode(&ethers::utils::keccak256(stem.as_bytes())[..8]);
+    let mut end = MAX_FILE_NAME_BYTES - extension.len() - hash.len() - 2;
+    while !stem.is_char_boundary(end) {
+        end -= 1;
+    }
+    format!("{}__{}{}", &stem[..end], hash, extension)
+}
+
+/// path that can be opened beyond the 260 character limit of windows paths, using the `\\?\`
+/// prefix for long absolute paths
+pub fn long_path(path: &Path) -> PathBuf {
+    #[cfg(windows)]
+    {
+        const MAX_PATH: usize = 260;
+        let display = path.to_string_lossy();
+        if path.is_absolute() && display.len() >= MAX_PATH && !display.starts_with(r"\\?\") {
+            // verbatim paths are not normalized, so they must use backslashes
+            let display = display.replace('/', r"\");
+            return match display.strip_prefix(r"\\") {
+                Some(unc) => PathBuf::from(format!(r"\\?\UNC\{}", unc)),
+                None => PathBuf::from(format!(r"\\?\{}", display)),
+            }
+        }
+    }
+    path.to_path_buf()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_output_locations() {
+        let local = OutputLocation::parse("file:///data/cryo").unwrap();
+        assert_eq!(local, OutputLocation::Local(PathBuf::from("/data/cryo")));
+        assert_eq!(OutputLocation::parse("./data").unwrap().local_dir(), PathBuf::from("./data"));
+        let s3 = OutputLocation::parse("s3://bucket/eth/blocks/").unwrap();
+        assert_eq!(
+            s3,
+            OutputLocation::ObjectStore {
+                scheme: ObjectStoreScheme::S3,
+                bucket: "bucket".to_string(),
+                prefix: "eth/blocks".to_string(),
+            }
+        );
+        assert_eq!(s3.uri(), "s3://bucket/eth/blocks");
+        assert!(s3.local_dir().ends_with("s3/bucket/eth/blocks"));
+        let azure = OutputLocation::parse("az://container").unwrap();
+        assert_eq!(azure.uri(), "az://container");
+        assert!(OutputLocation::parse("gs://").is_err());
+        assert!(OutputLocation::parse("ftp://host/dir").is_err());
+
+        assert_eq!(sanitize_label("usdc/weth: 0.3%?"), "usdc_weth_ 0.3%_");
+        assert_eq!(sanitize_label("label. "), "label");
+        let stem = "x".repeat(300);
+        let name = fit_file_name(&stem, ".parquet");
+        assert_eq!(name.len(), MAX_FILE_NAME_BYTES);
+        assert!(name.ends_with(".parquet"));
+        assert_ne!(name, fit_file_name(&"x".repeat(301), ".parquet"));
+        assert_eq!(fit_file_name("ethereum__blocks", ".csv"), "ethereum__blocks.csv");
+    }
+}
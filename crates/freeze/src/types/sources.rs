@@ -1,4 +1,10 @@
-use std::sync::Arc;
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+};
 
 use ethers::prelude::*;
 use governor::{
@@ -6,9 +12,35 @@ use governor::{
     middleware::NoOpMiddleware,
     state::{direct::NotKeyed, InMemoryState},
 };
-use tokio::sync::{AcquireError, Semaphore, SemaphorePermit};
+use tokio::sync::{Semaphore, SemaphorePermit};
+use tracing::Instrument;
+
+use crate::{
+    bytes_to_u32, AutoTuner, BlockCache, Capabilities, CollectError, Datatype, DiskCache,
+    FetcherMiddleware, MulticallBatcher, NftMarketplace, OtsBlockDetails, OtsTransactionsPage,
+    PoolEvent, PoolTokens, RequestCoalescer, RpcBatcher, RpcStats, RpcTransport, SwapProtocol,
+    FUNCTION_CURVE_COINS, FUNCTION_ERC20_DECIMALS, FUNCTION_UNISWAP_TOKEN0,
+    FUNCTION_UNISWAP_TOKEN1,
+};
+
+/// number of slots in a beacon chain epoch
+pub const SLOTS_PER_EPOCH: u64 = 32;
 
-use crate::CollectError;
+/// number of epochs whose proposer duties are cached, enough for the epochs of the chunks
+/// collected concurrently
+pub const PROPOSER_DUTIES_CACHE_EPOCHS: usize = 16;
+
+/// substrings of eth_getLogs errors returned by providers when a request covers too many blocks
+/// or logs, after which the request is split in half and retried
+pub const LOG_SPLIT_ERRORS: [&str; 7] = [
+    "query returned more than",
+    "range too large",
+    "range is too wide",
+    "max block range",
+    "maximum block range",
+    "response size exceeded",
+    "too many logs",
+];
 
 /// RateLimiter based on governor crate
 pub type RateLimiter = governor::RateLimiter<NotKeyed, InMemoryState, DefaultClock, NoOpMiddleware>;
@@ -17,7 +49,7 @@ pub type RateLimiter = governor::RateLimiter<NotKeyed, InMemoryState, DefaultClo
 #[derive(Clone)]
 pub struct Source {
     /// Shared provider for rpc data
-    pub fetcher: Arc<Fetcher<RetryClient<Http>>>,
+    pub fetcher: Arc<Fetcher<RpcTransport>>,
     /// chain_id of network
     pub chain_id: u64,
     /// number of blocks per log request
@@ -30,6 +62,248 @@ pub struct Source {
     pub max_requests_per_second: Option<u64>,
     /// Rpc Url
     pub rpc_url: String,
+    /// cache of erc20 decimals, keyed by contract address
+    pub erc20_decimals: Arc<Mutex<HashMap<Vec<u8>, Option<u32>>>>,
+    /// client for http data sources other than rpc
+    pub http_client: reqwest::Client,
+    /// MEV-Boost relay urls
+    pub relay_urls: Vec<String>,
+    /// beacon node url
+    pub beacon_url: Option<String>,
+    /// batcher of eth_calls through Multicall3, if enabled
+    pub multicall: Option<Arc<MulticallBatcher>>,
+    /// whether account states are fetched through eth_getProof
+    pub use_get_proof: bool,
+    /// whether block continuity of partitions is validated
+    pub validate_continuity: bool,
+    /// whether logs are only requested for blocks whose logsBloom may contain matches
+    pub bloom_prefilter: bool,
+    /// cache of trace_block responses, shared by datasets collected together
+    pub trace_cache: Option<Arc<BlockCache<Vec<Trace>>>>,
+    /// cache of block timestamps, attached to block-indexed datasets if present
+    pub timestamp_cache: Option<Arc<BlockCache<u32>>>,
+    /// cache of beacon proposer duties, keyed by epoch, shared by the slots of each epoch
+    pub proposer_duties: Arc<BlockCache<Arc<Vec<crate::BeaconProposerDuty>>>>,
+    /// optional rpc apis supported by node
+    pub capabilities: Capabilities,
+    /// lowercase substrings of eth_getLogs errors after which requests are split and retried
+    pub log_split_errors: Vec<String>,
+    /// batcher of per-address and per-transaction requests into json-rpc batches, if enabled
+    pub rpc_batcher: Option<Arc<RpcBatcher>>,
+    /// addresses whose erc20 balances are excluded from circulating supply
+    pub excluded_addresses: Vec<Vec<u8>>,
+    /// token ids whose erc721 owners are collected
+    pub token_ids: Vec<U256>,
+    /// directory of erc20_transfers files aggregated into erc20 holders, instead of fetching logs
+    pub erc20_transfers_dir: Option<std::path::PathBuf>,
+    /// verifier of the sources of the addresses of codes and contracts, if enabled
+    pub source_verifier: Option<Arc<crate::SourceVerifier>>,
+    /// factory events decoded into pools
+    pub pool_events: Vec<PoolEvent>,
+    /// protocols whose swaps are decoded
+    pub swap_protocols: Vec<SwapProtocol>,
+    /// cache of pool tokens, keyed by pool address and token index
+    pub pool_tokens: Arc<Mutex<PoolTokens>>,
+    /// marketplaces whose nft sales are decoded
+    pub nft_marketplaces: Vec<NftMarketplace>,
+    /// chainlink aggregators discovered through the feed registry
+    pub chainlink_aggregators: Arc<tokio::sync::OnceCell<Vec<H160>>>,
+    /// sources of datasets with their own request limits or request sizes
+    pub dataset_sources: HashMap<Datatype, Arc<Source>>,
+}
+
+/// builder of sources, with the defaults of the cli
+///
+/// ```ignore
+/// let source = SourceBuilder::new(rpc_url).max_concurrent_requests(50).build().await?;
+/// ```
+#[derive(Clone)]
+pub struct SourceBuilder {
+    rpc_url: String,
+    max_concurrent_requests: u64,
+    max_concurrent_chunks: Option<u64>,
+    requests_per_second: Option<u32>,
+    inner_request_size: u64,
+    max_retries: u32,
+    initial_backoff: u64,
+    timeout: Option<std::time::Duration>,
+    relay_urls: Vec<String>,
+    beacon_url: Option<String>,
+    middleware: Vec<Arc<dyn FetcherMiddleware>>,
+    record_fixtures: Option<std::path::PathBuf>,
+    replay_fixtures: Option<std::path::PathBuf>,
+}
+
+impl SourceBuilder {
+    /// initialize SourceBuilder for rpc url
+    pub fn new(rpc_url: impl Into<String>) -> Self {
+        SourceBuilder {
+            rpc_url: rpc_url.into(),
+            max_concurrent_requests: 100,
+            max_concurrent_chunks: Some(4),
+            requests_per_second: None,
+            inner_request_size: 1,
+            max_retries: 5,
+            initial_backoff: 500,
+            timeout: None,
+            relay_urls: vec!["https://boost-relay.flashbots.net".to_string()],
+            beacon_url: None,
+            middleware: Vec::new(),
+            record_fixtures: None,
+            replay_fixtures: None,
+        }
+    }
+
+    /// maximum requests collected concurrently [default: 100]
+    pub fn max_concurrent_requests(mut self, max_concurrent_requests: u64) -> Self {
+        self.max_concurrent_requests = max_concurrent_requests;
+        self
+    }
+
+    /// maximum chunks collected concurrently, or None for no limit [default: 4]
+    pub fn max_concurrent_chunks(mut self, max_concurrent_chunks: Option<u64>) -> Self {
+        self.max_concurrent_chunks = max_concurrent_chunks;
+        self
+    }
+
+    /// maximum requests per second
+    pub fn requests_per_second(mut self, requests_per_second: u32) -> Self {
+        self.requests_per_second = Some(requests_per_second);
+        self
+    }
+
+    /// number of blocks per log request [default: 1]
+    pub fn inner_request_size(mut self, inner_request_size: u64) -> Self {
+        self.inner_request_size = inner_request_size;
+        self
+    }
+
+    /// maximum retries of rate limited requests [default: 5]
+    pub fn max_retries(mut self, max_retries: u32) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+
+    /// initial backoff of retries in milliseconds [default: 500]
+    pub fn initial_backoff(mut self, initial_backoff: u64) -> Self {
+        self.initial_backoff = initial_backoff;
+        self
+    }
+
+    /// timeout of http requests
+    pub fn timeout(mut self, timeout: std::time::Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// MEV-Boost relay urls [default: flashbots relay]
+    pub fn relay_urls(mut self, relay_urls: Vec<String>) -> Self {
+        self.relay_urls = relay_urls;
+        self
+    }
+
+    /// beacon node url
+    pub fn beacon_url(mut self, beacon_url: impl Into<String>) -> Self {
+        self.beacon_url = Some(beacon_url.into());
+        self
+    }
+
+    /// add hooks called around each rpc request, after the hooks added before
+    pub fn middleware(mut self, middleware: Arc<dyn FetcherMiddleware>) -> Self {
+        self.middleware.push(middleware);
+        self
+    }
+
+    /// record the responses of the rpc to a fixtures file, appending to it if it exists
+    pub fn record_fixtures(mut self, path: impl Into<std::path::PathBuf>) -> Self {
+        self.record_fixtures = Some(path.into());
+        self
+    }
+
+    /// answer rpc requests from a fixtures file recorded earlier, instead of the rpc
+    pub fn replay_fixtures(mut self, path: impl Into<std::path::PathBuf>) -> Self {
+        self.replay_fixtures = Some(path.into());
+        self
+    }
+
+    /// connect to rpc, fetching its chain id and probing it for optional apis
+    pub async fn build(self) -> Result<Source> {
+        let mut client_builder = reqwest::Client::builder();
+        if let Some(timeout) = self.timeout {
+            client_builder = client_builder.timeout(timeout);
+        }
+        let http_client = client_builder
+            .build()
+            .map_err(|e| CollectError::CollectError(format!("could not build client: {}", e)))?;
+        let transport = match &self.replay_fixtures {
+            Some(path) => RpcTransport::replay(path)?,
+            None => {
+                let url: reqwest::Url = self.rpc_url.parse().map_err(|_| {
+                    CollectError::CollectError(format!("invalid rpc url: {}", self.rpc_url))
+                })?;
+                let client = RetryClient::new(
+                    Http::new_with_client(url, http_client.clone()),
+                    Box::new(HttpRateLimitRetryPolicy),
+                    self.max_retries,
+                    self.initial_backoff,
+                );
+                RpcTransport::http(client, self.record_fixtures.as_deref())?
+            }
+        };
+        let batch_endpoint = transport.is_http().then_some((&http_client, self.rpc_url.as_str()));
+        let provider = Provider::new(transport);
+        let chain_id = provider.get_chainid().await.map_err(CollectError::ProviderError)?.as_u64();
+        let rate_limiter = self
+            .requests_per_second
+            .and_then(std::num::NonZeroU32::new)
+            .map(|rate| RateLimiter::direct(governor::Quota::per_second(rate)));
+        let fetcher = Fetcher {
+            provider,
+            semaphore: Some(Semaphore::new(self.max_concurrent_requests as usize)),
+            rate_limiter,
+            n_requests: Default::default(),
+            rpc_stats: Default::default(),
+            disk_cache: None,
+            parent: None,
+            autotune: None,
+            coalescer: Some(Default::default()),
+            middleware: self.middleware,
+        };
+        let capabilities = Capabilities::probe(&fetcher, batch_endpoint).await;
+        Ok(Source {
+            fetcher: Arc::new(fetcher),
+            chain_id,
+            inner_request_size: self.inner_request_size,
+            max_concurrent_requests: Some(self.max_concurrent_requests),
+            max_concurrent_chunks: self.max_concurrent_chunks,
+            max_requests_per_second: self.requests_per_second.map(|x| x as u64),
+            rpc_url: self.rpc_url,
+            erc20_decimals: Default::default(),
+            http_client,
+            relay_urls: self.relay_urls,
+            beacon_url: self.beacon_url,
+            multicall: None,
+            use_get_proof: false,
+            validate_continuity: false,
+            bloom_prefilter: false,
+            trace_cache: None,
+            timestamp_cache: None,
+            proposer_duties: Arc::new(BlockCache::new(PROPOSER_DUTIES_CACHE_EPOCHS)),
+            capabilities,
+            log_split_errors: LOG_SPLIT_ERRORS.iter().map(|pattern| pattern.to_string()).collect(),
+            rpc_batcher: None,
+            excluded_addresses: Vec::new(),
+            token_ids: Vec::new(),
+            erc20_transfers_dir: None,
+            source_verifier: None,
+            pool_events: PoolEvent::defaults(),
+            swap_protocols: SwapProtocol::all(),
+            pool_tokens: Arc::new(Mutex::new(PoolTokens::new())),
+            nft_marketplaces: NftMarketplace::all(),
+            chainlink_aggregators: Default::default(),
+            dataset_sources: Default::default(),
+        })
+    }
 }
 
 /// Wrapper over `Provider<P>` that adds concurrency and rate limiting controls
@@ -40,15 +314,57 @@ pub struct Fetcher<P> {
     pub semaphore: Option<Semaphore>,
     /// rate limiter for controlling request rate
     pub rate_limiter: Option<RateLimiter>,
+    /// number of requests sent by fetcher
+    pub n_requests: AtomicU64,
+    /// counts and latencies of requests sent by fetcher, per rpc method
+    pub rpc_stats: RpcStats,
+    /// on-disk cache of responses of requests pinned to confirmed blocks, if enabled
+    pub disk_cache: Option<DiskCache>,
+    /// fetcher whose limits also apply to the requests of this fetcher, and which also counts
+    /// them, for fetchers with limits of their own for particular datasets
+    pub parent: Option<Arc<Fetcher<P>>>,
+    /// tuner of the size of semaphore, if concurrency is tuned during collection
+    pub autotune: Option<Arc<AutoTuner>>,
+    /// sharer of responses between identical requests in flight at the same time, if enabled
+    pub coalescer: Option<Arc<RequestCoalescer>>,
+    /// hooks called around each request sent to provider, in order
+    pub middleware: Vec<Arc<dyn FetcherMiddleware>>,
 }
 
 type Result<T> = ::core::result::Result<T, CollectError>;
 
 impl<P: JsonRpcClient> Fetcher<P> {
+    /// create fetcher of provider without concurrency or rate limits, caches, or middleware
+    pub fn new(provider: Provider<P>) -> Self {
+        Fetcher {
+            provider,
+            semaphore: None,
+            rate_limiter: None,
+            n_requests: Default::default(),
+            rpc_stats: Default::default(),
+            disk_cache: None,
+            parent: None,
+            autotune: None,
+            coalescer: None,
+            middleware: Vec::new(),
+        }
+    }
+
+    /// add hooks called around each request, after the hooks added before
+    pub fn with_middleware(mut self, middleware: Arc<dyn FetcherMiddleware>) -> Self {
+        self.middleware.push(middleware);
+        self
+    }
+
     /// Returns an array (possibly empty) of logs that match the filter
     pub async fn get_logs(&self, filter: &Filter) -> Result<Vec<Log>> {
-        let _permit = self.permit_request().await;
-        Self::map_err(self.provider.get_logs(filter).await)
+        self.cached_request(
+            "eth_getLogs",
+            filter.get_to_block().map(|block| block.as_u64()),
+            filter,
+            self.provider.get_logs(filter),
+        )
+        .await
     }
 
     /// Replays all transactions in a block returning the requested traces for each transaction
@@ -57,8 +373,13 @@ impl<P: JsonRpcClient> Fetcher<P> {
         block: BlockNumber,
         trace_types: Vec<TraceType>,
     ) -> Result<Vec<BlockTrace>> {
-        let _permit = self.permit_request().await;
-        Self::map_err(self.provider.trace_replay_block_transactions(block, trace_types).await)
+        self.cached_request(
+            "trace_replayBlockTransactions",
+            block_hint(&block),
+            (block, &trace_types),
+            self.provider.trace_replay_block_transactions(block, trace_types.clone()),
+        )
+        .await
     }
 
     /// Get state diff traces of block
@@ -108,8 +429,11 @@ impl<P: JsonRpcClient> Fetcher<P> {
         tx_hash: TxHash,
         trace_types: Vec<TraceType>,
     ) -> Result<BlockTrace> {
-        let _permit = self.permit_request().await;
-        Self::map_err(self.provider.trace_replay_transaction(tx_hash, trace_types).await)
+        self.request(
+            "trace_replayTransaction",
+            self.provider.trace_replay_transaction(tx_hash, trace_types),
+        )
+        .await
     }
 
     /// Get state diff traces of transaction
@@ -142,8 +466,7 @@ impl<P: JsonRpcClient> Fetcher<P> {
 
     /// Gets the transaction with transaction_hash
     pub async fn get_transaction(&self, tx_hash: TxHash) -> Result<Option<Transaction>> {
-        let _permit = self.permit_request().await;
-        Self::map_err(self.provider.get_transaction(tx_hash).await)
+        self.request("eth_getTransactionByHash", self.provider.get_transaction(tx_hash)).await
     }
 
     /// Gets the transaction receipt with transaction_hash
@@ -151,44 +474,169 @@ impl<P: JsonRpcClient> Fetcher<P> {
         &self,
         tx_hash: TxHash,
     ) -> Result<Option<TransactionReceipt>> {
-        let _permit = self.permit_request().await;
-        Self::map_err(self.provider.get_transaction_receipt(tx_hash).await)
+        self.request("eth_getTransactionReceipt", self.provider.get_transaction_receipt(tx_hash))
+            .await
     }
 
     /// Gets the block at `block_num` (transaction hashes only)
     pub async fn get_block(&self, block_num: u64) -> Result<Option<Block<TxHash>>> {
-        let _permit = self.permit_request().await;
-        Self::map_err(self.provider.get_block(block_num).await)
+        self.cached_request(
+            "eth_getBlockByNumber",
+            Some(block_num),
+            (block_num, false),
+            self.provider.get_block(block_num),
+        )
+        .await
     }
 
     /// Gets the block at `block_num` (transaction hashes only)
     pub async fn get_block_by_hash(&self, block_hash: H256) -> Result<Option<Block<TxHash>>> {
-        let _permit = self.permit_request().await;
-        Self::map_err(self.provider.get_block(BlockId::Hash(block_hash)).await)
+        self.request("eth_getBlockByHash", self.provider.get_block(BlockId::Hash(block_hash))).await
     }
 
     /// Gets the block at `block_num` (full transactions included)
     pub async fn get_block_with_txs(&self, block_num: u64) -> Result<Option<Block<Transaction>>> {
-        let _permit = self.permit_request().await;
-        Self::map_err(self.provider.get_block_with_txs(block_num).await)
+        self.cached_request(
+            "eth_getBlockByNumber",
+            Some(block_num),
+            (block_num, true),
+            self.provider.get_block_with_txs(block_num),
+        )
+        .await
     }
 
     /// Returns all receipts for a block.
     pub async fn get_block_receipts(&self, block_num: u64) -> Result<Vec<TransactionReceipt>> {
-        let _permit = self.permit_request().await;
-        Self::map_err(self.provider.get_block_receipts(block_num).await)
+        self.cached_request(
+            "eth_getBlockReceipts",
+            Some(block_num),
+            block_num,
+            self.provider.get_block_receipts(block_num),
+        )
+        .await
+    }
+
+    /// Returns all receipts for a block using erigon's `erigon_getBlockReceipts`
+    pub async fn erigon_get_block_receipts(
+        &self,
+        block_num: u64,
+    ) -> Result<Vec<TransactionReceipt>> {
+        let block = BlockNumber::from(block_num);
+        self.cached_request(
+            "erigon_getBlockReceipts",
+            Some(block_num),
+            block,
+            self.provider.request("erigon_getBlockReceipts", [block]),
+        )
+        .await
+    }
+
+    /// Returns the version of the otterscan api supported by the node
+    pub async fn ots_get_api_level(&self) -> Result<u64> {
+        self.send("ots_getApiLevel", self.provider.request("ots_getApiLevel", ())).await
+    }
+
+    /// Returns the header of a block with its transaction count and total fees
+    pub async fn ots_get_block_details(&self, block_num: u64) -> Result<OtsBlockDetails> {
+        let block = BlockNumber::from(block_num);
+        self.cached_request(
+            "ots_getBlockDetails",
+            Some(block_num),
+            block,
+            self.provider.request("ots_getBlockDetails", [block]),
+        )
+        .await
+    }
+
+    /// Returns a page of the transactions touching address before block, most recent first
+    ///
+    /// a block of 0 starts from the most recent transaction of address
+    pub async fn ots_search_transactions_before(
+        &self,
+        address: H160,
+        block_num: u64,
+        page_size: u64,
+    ) -> Result<OtsTransactionsPage> {
+        self.request(
+            "ots_searchTransactionsBefore",
+            self.provider.request("ots_searchTransactionsBefore", (address, block_num, page_size)),
+        )
+        .await
+    }
+
+    /// Returns a page of the transactions touching address after block, earliest first
+    pub async fn ots_search_transactions_after(
+        &self,
+        address: H160,
+        block_num: u64,
+        page_size: u64,
+    ) -> Result<OtsTransactionsPage> {
+        self.request(
+            "ots_searchTransactionsAfter",
+            self.provider.request("ots_searchTransactionsAfter", (address, block_num, page_size)),
+        )
+        .await
     }
 
     /// Returns traces created at given block
     pub async fn trace_block(&self, block_num: BlockNumber) -> Result<Vec<Trace>> {
-        let _permit = self.permit_request().await;
-        Self::map_err(self.provider.trace_block(block_num).await)
+        self.cached_request(
+            "trace_block",
+            block_hint(&block_num),
+            block_num,
+            self.provider.trace_block(block_num),
+        )
+        .await
+    }
+
+    /// Returns geth-style traces of all transactions in a block
+    pub async fn debug_trace_block_by_number(&self, block: BlockNumber) -> Result<Vec<GethTrace>> {
+        self.request(
+            "debug_traceBlockByNumber",
+            self.provider.debug_trace_block_by_number(Some(block), Default::default()),
+        )
+        .await
+    }
+
+    /// Returns the results of a javascript tracer for all transactions in a block
+    pub async fn debug_trace_block_javascript(
+        &self,
+        block_num: u64,
+        tracer: &str,
+    ) -> Result<Vec<serde_json::Value>> {
+        let block = BlockNumber::from(block_num);
+        let options = serde_json::json!({ "tracer": tracer });
+        self.cached_request(
+            "debug_traceBlockByNumber",
+            Some(block_num),
+            (block, &options),
+            self.provider.request("debug_traceBlockByNumber", (block, &options)),
+        )
+        .await
+    }
+
+    /// Returns the result of a javascript tracer for a transaction
+    pub async fn debug_trace_transaction_javascript(
+        &self,
+        tx_hash: TxHash,
+        tracer: &str,
+    ) -> Result<serde_json::Value> {
+        let options = serde_json::json!({ "tracer": tracer });
+        self.request(
+            "debug_traceTransaction",
+            self.provider.request("debug_traceTransaction", (tx_hash, options)),
+        )
+        .await
+    }
+
+    /// Returns traces matching filter
+    pub async fn trace_filter(&self, filter: ethers::types::TraceFilter) -> Result<Vec<Trace>> {
+        self.request("trace_filter", self.provider.trace_filter(filter)).await
     }
 
     /// Returns all traces of a given transaction
     pub async fn trace_transaction(&self, tx_hash: TxHash) -> Result<Vec<Trace>> {
-        let _permit = self.permit_request().await;
-        self.provider.trace_transaction(tx_hash).await.map_err(CollectError::ProviderError)
+        self.request("trace_transaction", self.provider.trace_transaction(tx_hash)).await
     }
 
     /// Deprecated
@@ -197,11 +645,13 @@ impl<P: JsonRpcClient> Fetcher<P> {
         transaction: TransactionRequest,
         block_number: BlockNumber,
     ) -> Result<Bytes> {
-        let _permit = self.permit_request().await;
-        self.provider
-            .call(&transaction.into(), Some(block_number.into()))
-            .await
-            .map_err(CollectError::ProviderError)
+        self.cached_request(
+            "eth_call",
+            block_hint(&block_number),
+            (&transaction, block_number),
+            self.provider.call(&transaction.clone().into(), Some(block_number.into())),
+        )
+        .await
     }
 
     /// Returns traces for given call data
@@ -211,11 +661,13 @@ impl<P: JsonRpcClient> Fetcher<P> {
         trace_type: Vec<TraceType>,
         block_number: Option<BlockNumber>,
     ) -> Result<BlockTrace> {
-        let _permit = self.permit_request().await;
-        self.provider
-            .trace_call(transaction, trace_type, block_number)
-            .await
-            .map_err(CollectError::ProviderError)
+        self.cached_request(
+            "trace_call",
+            block_number.as_ref().and_then(block_hint),
+            (&transaction, &trace_type, block_number),
+            self.provider.trace_call(transaction.clone(), trace_type.clone(), block_number),
+        )
+        .await
     }
 
     /// Get nonce of address
@@ -224,29 +676,51 @@ impl<P: JsonRpcClient> Fetcher<P> {
         address: H160,
         block_number: BlockNumber,
     ) -> Result<U256> {
-        let _permit = self.permit_request().await;
-        self.provider
-            .get_transaction_count(address, Some(block_number.into()))
-            .await
-            .map_err(CollectError::ProviderError)
+        self.cached_request(
+            "eth_getTransactionCount",
+            block_hint(&block_number),
+            (address, block_number),
+            self.provider.get_transaction_count(address, Some(block_number.into())),
+        )
+        .await
     }
 
     /// Get code at address
     pub async fn get_balance(&self, address: H160, block_number: BlockNumber) -> Result<U256> {
-        let _permit = self.permit_request().await;
-        self.provider
-            .get_balance(address, Some(block_number.into()))
-            .await
-            .map_err(CollectError::ProviderError)
+        self.cached_request(
+            "eth_getBalance",
+            block_hint(&block_number),
+            (address, block_number),
+            self.provider.get_balance(address, Some(block_number.into())),
+        )
+        .await
     }
 
     /// Get code at address
     pub async fn get_code(&self, address: H160, block_number: BlockNumber) -> Result<Bytes> {
-        let _permit = self.permit_request().await;
-        self.provider
-            .get_code(address, Some(block_number.into()))
-            .await
-            .map_err(CollectError::ProviderError)
+        self.cached_request(
+            "eth_getCode",
+            block_hint(&block_number),
+            (address, block_number),
+            self.provider.get_code(address, Some(block_number.into())),
+        )
+        .await
+    }
+
+    /// Get proof of account, including its balance, nonce, code hash, and storage at locations
+    pub async fn get_proof(
+        &self,
+        address: H160,
+        locations: Vec<H256>,
+        block_number: BlockNumber,
+    ) -> Result<EIP1186ProofResponse> {
+        self.cached_request(
+            "eth_getProof",
+            block_hint(&block_number),
+            (address, &locations, block_number),
+            self.provider.get_proof(address, locations.clone(), Some(block_number.into())),
+        )
+        .await
     }
 
     /// Get stored data at given location
@@ -256,16 +730,23 @@ impl<P: JsonRpcClient> Fetcher<P> {
         slot: H256,
         block_number: BlockNumber,
     ) -> Result<H256> {
-        let _permit = self.permit_request().await;
-        self.provider
-            .get_storage_at(address, slot, Some(block_number.into()))
-            .await
-            .map_err(CollectError::ProviderError)
+        self.cached_request(
+            "eth_getStorageAt",
+            block_hint(&block_number),
+            (address, slot, block_number),
+            self.provider.get_storage_at(address, slot, Some(block_number.into())),
+        )
+        .await
     }
 
     /// Get the block number
     pub async fn get_block_number(&self) -> Result<U64> {
-        Self::map_err(self.provider.get_block_number().await)
+        self.send("eth_blockNumber", self.provider.get_block_number()).await
+    }
+
+    /// Get the pending and queued transactions of the mempool
+    pub async fn txpool_content(&self) -> Result<TxpoolContent> {
+        self.request("txpool_content", self.provider.txpool_content()).await
     }
 
     // extra helpers below
@@ -301,11 +782,13 @@ impl<P: JsonRpcClient> Fetcher<P> {
             data: Some(call_data.into()),
             ..Default::default()
         };
-        let _permit = self.permit_request().await;
-        self.provider
-            .call(&transaction.into(), Some(block_number.into()))
-            .await
-            .map_err(CollectError::ProviderError)
+        self.cached_request(
+            "eth_call",
+            block_hint(&block_number),
+            (&transaction, block_number),
+            self.provider.call(&transaction.clone().into(), Some(block_number.into())),
+        )
+        .await
     }
 
     /// Return output data of a contract call
@@ -321,53 +804,523 @@ impl<P: JsonRpcClient> Fetcher<P> {
             data: Some(call_data.into()),
             ..Default::default()
         };
+        self.cached_request(
+            "trace_call",
+            block_number.as_ref().and_then(block_hint),
+            (&transaction, &trace_type, block_number),
+            self.provider.trace_call(transaction.clone(), trace_type.clone(), block_number),
+        )
+        .await
+    }
+
+    /// send request after acquiring a permit
+    async fn request<T>(
+        &self,
+        method: &'static str,
+        request: impl std::future::Future<Output = ::core::result::Result<T, ProviderError>>,
+    ) -> Result<T> {
         let _permit = self.permit_request().await;
-        self.provider
-            .trace_call(transaction, trace_type, block_number)
-            .await
-            .map_err(CollectError::ProviderError)
+        self.send(method, request).await
+    }
+
+    /// send request pinned to block, sharing the response of an identical request in flight, and
+    /// reusing the on-disk response of an identical earlier request if the block is confirmed
+    ///
+    /// shared and cached responses are returned without acquiring a permit
+    async fn cached_request<T, K>(
+        &self,
+        method: &'static str,
+        block: Option<u64>,
+        params: K,
+        request: impl std::future::Future<Output = ::core::result::Result<T, ProviderError>>,
+    ) -> Result<T>
+    where
+        T: serde::Serialize + serde::de::DeserializeOwned + Clone + Send + Sync + 'static,
+        K: serde::Serialize,
+    {
+        let coalescer = match &self.coalescer {
+            Some(coalescer) => coalescer,
+            None => return self.disk_cached_request(method, block, params, request).await,
+        };
+        match serde_json::to_string(&params) {
+            Ok(key) => {
+                let key = format!("{}({})", method, key);
+                let fetch = || self.disk_cached_request(method, block, params, request);
+                coalescer.get_or_fetch(key, fetch).await
+            }
+            Err(_) => self.disk_cached_request(method, block, params, request).await,
+        }
     }
 
-    async fn permit_request(
+    async fn disk_cached_request<T, K>(
         &self,
-    ) -> Option<::core::result::Result<SemaphorePermit<'_>, AcquireError>> {
-        let permit = match &self.semaphore {
-            Some(semaphore) => Some(semaphore.acquire().await),
-            _ => None,
+        method: &'static str,
+        block: Option<u64>,
+        params: K,
+        request: impl std::future::Future<Output = ::core::result::Result<T, ProviderError>>,
+    ) -> Result<T>
+    where
+        T: serde::Serialize + serde::de::DeserializeOwned,
+        K: serde::Serialize,
+    {
+        let cache = match &self.disk_cache {
+            Some(cache) if cache.is_cacheable(block) => cache,
+            _ => return self.request(method, request).await,
         };
-        if let Some(limiter) = &self.rate_limiter {
-            limiter.until_ready().await;
+        if let Some(response) = cache.get(method, &params) {
+            return Ok(response)
         }
-        permit
+        let response = self.request(method, request).await?;
+        cache.put(method, &params, &response);
+        Ok(response)
+    }
+
+    /// send request, recording its latency and a span named after its method
+    async fn send<T>(
+        &self,
+        method: &'static str,
+        request: impl std::future::Future<Output = ::core::result::Result<T, ProviderError>>,
+    ) -> Result<T> {
+        self.start_request(method)?;
+        let span = tracing::info_span!("rpc", otel.name = method, rpc.method = method);
+        let t_start = std::time::Instant::now();
+        let result =
+            async move { request.await.map_err(Self::provider_error) }.instrument(span).await;
+        let timed_out = matches!(&result, Err(e) if is_timeout(e));
+        self.record_request(method, t_start.elapsed(), result.is_ok());
+        self.tune_request(method, t_start.elapsed(), timed_out);
+        self.finish_request(method, t_start.elapsed(), &result);
+        result
+    }
+
+    /// middleware of parent of fetcher and of fetcher, in the order they are called
+    fn all_middleware(&self) -> impl Iterator<Item = &Arc<dyn FetcherMiddleware>> {
+        let parent = self.parent.as_deref().into_iter().flat_map(|parent| parent.middleware.iter());
+        parent.chain(self.middleware.iter())
+    }
+
+    /// call the on_request hooks of middleware, returning the first error
+    pub(crate) fn start_request(&self, method: &'static str) -> Result<()> {
+        self.all_middleware().try_for_each(|middleware| middleware.on_request(method))
     }
 
-    fn map_err<T>(res: ::core::result::Result<T, ProviderError>) -> Result<T> {
-        res.map_err(CollectError::ProviderError)
+    /// call the on_response or on_error hooks of middleware with the result of request
+    pub(crate) fn finish_request<T>(
+        &self,
+        method: &'static str,
+        latency: std::time::Duration,
+        result: &Result<T>,
+    ) {
+        for middleware in self.all_middleware() {
+            match result {
+                Ok(_) => middleware.on_response(method, latency),
+                Err(e) => middleware.on_error(method, latency, e),
+            }
+        }
+    }
+
+    /// acquire permits of the concurrency and rate limits of fetcher and of its parent
+    pub(crate) async fn permit_request(&self) -> Vec<SemaphorePermit<'_>> {
+        let mut permits = Vec::new();
+        for fetcher in self.parent.as_deref().into_iter().chain(std::iter::once(self)) {
+            if let Some(semaphore) = &fetcher.semaphore {
+                loop {
+                    match semaphore.acquire().await {
+                        Ok(permit)
+                            if fetcher.autotune.as_ref().is_some_and(|t| t.take_excess()) =>
+                        {
+                            permit.forget()
+                        }
+                        Ok(permit) => {
+                            permits.push(permit);
+                            break
+                        }
+                        Err(_) => break,
+                    }
+                }
+            }
+            if let Some(limiter) = &fetcher.rate_limiter {
+                limiter.until_ready().await;
+            }
+            fetcher.n_requests.fetch_add(1, Ordering::Relaxed);
+        }
+        permits
+    }
+
+    /// record request in the stats of fetcher and of its parent
+    pub(crate) fn record_request(
+        &self,
+        method: &'static str,
+        latency: std::time::Duration,
+        success: bool,
+    ) {
+        self.rpc_stats.record(method, latency, success);
+        if let Some(parent) = &self.parent {
+            parent.rpc_stats.record(method, latency, success);
+        }
+    }
+
+    /// report outcome of request to the tuners of fetcher and of its parent
+    pub(crate) fn tune_request(
+        &self,
+        method: &'static str,
+        latency: std::time::Duration,
+        timed_out: bool,
+    ) {
+        for fetcher in self.parent.as_deref().into_iter().chain(std::iter::once(self)) {
+            if let (Some(tuner), Some(semaphore)) = (&fetcher.autotune, &fetcher.semaphore) {
+                if tuner.observe(method, latency, timed_out) {
+                    semaphore.add_permits(1);
+                }
+            }
+        }
+    }
+
+    fn provider_error(e: ProviderError) -> CollectError {
+        tracing::warn!(error = %e, "rpc request failed");
+        CollectError::ProviderError(e)
+    }
+}
+
+/// whether request failed by timing out
+pub(crate) fn is_timeout(e: &CollectError) -> bool {
+    let message = e.to_string().to_lowercase();
+    message.contains("timed out") || message.contains("timeout")
+}
+
+/// revert data of a request that failed because the call reverted, which is empty if the call
+/// reverted without data
+pub(crate) fn revert_data(e: &CollectError) -> Option<Vec<u8>> {
+    match e {
+        CollectError::ProviderError(e) => {
+            let response = ethers::providers::RpcError::as_error_response(e);
+            response.and_then(|e| e.as_revert_data()).map(|data| data.to_vec())
+        }
+        _ => None,
+    }
+}
+
+/// block number that a request is pinned to, if it is pinned to a numbered block
+fn block_hint(block: &BlockNumber) -> Option<u64> {
+    match block {
+        BlockNumber::Number(number) => Some(number.as_u64()),
+        _ => None,
     }
 }
 
 use tokio::task;
 
+#[derive(serde::Deserialize)]
+struct BeaconResponse<T> {
+    data: T,
+}
+
 impl Source {
+    /// get `data` field of beacon node api response, returning None if resource is not found
+    pub async fn get_beacon_data<T: serde::de::DeserializeOwned>(
+        &self,
+        path: &str,
+    ) -> Result<Option<T>> {
+        let beacon_url = self
+            .beacon_url
+            .as_ref()
+            .ok_or(CollectError::CollectError("must specify a beacon url".to_string()))?;
+        let url = format!("{}{}", beacon_url.trim_end_matches('/'), path);
+        let response = self
+            .http_client
+            .get(url)
+            .send()
+            .await
+            .map_err(|e| CollectError::CollectError(format!("beacon request failed: {}", e)))?;
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None)
+        }
+        let response: BeaconResponse<T> = response
+            .error_for_status()
+            .map_err(|e| CollectError::CollectError(format!("beacon request failed: {}", e)))?
+            .json()
+            .await
+            .map_err(|e| {
+                CollectError::CollectError(format!("could not parse beacon response: {}", e))
+            })?;
+        Ok(Some(response.data))
+    }
+
+    /// get proposer duties of the slots of epoch, fetching them once for all slots of the epoch
+    pub async fn get_proposer_duties(
+        &self,
+        epoch: u64,
+    ) -> Result<Arc<Vec<crate::BeaconProposerDuty>>> {
+        let fetch = || async move {
+            let path = format!("/eth/v1/validator/duties/proposer/{}", epoch);
+            let duties: Option<Vec<crate::BeaconProposerDuty>> =
+                self.get_beacon_data(&path).await?;
+            Ok(Arc::new(duties.unwrap_or_default()))
+        };
+        self.proposer_duties.get_or_fetch(epoch, fetch).await
+    }
+
+    /// get traces of block, reusing the response of other datasets if traces are cached
+    pub async fn trace_block(&self, block_number: u64) -> Result<Vec<Trace>> {
+        match &self.trace_cache {
+            Some(cache) => {
+                let fetch = || self.fetcher.trace_block(block_number.into());
+                cache.get_or_fetch(block_number, fetch).await
+            }
+            None => self.fetcher.trace_block(block_number.into()).await,
+        }
+    }
+
+    /// get decimals of erc20 contract, caching the result for subsequent requests
+    pub async fn get_erc20_decimals(&self, contract: Vec<u8>) -> Result<Option<u32>> {
+        if let Some(decimals) = self
+            .erc20_decimals
+            .lock()
+            .map_err(|_| CollectError::CollectError("could not lock decimals cache".to_string()))?
+            .get(&contract)
+        {
+            return Ok(*decimals)
+        }
+
+        let call_data = FUNCTION_ERC20_DECIMALS.clone();
+        let address = H160::from_slice(&contract);
+        let output = self.fetcher.call2(address, call_data, BlockNumber::Latest).await;
+        let decimals = output.ok().and_then(|output| bytes_to_u32(output).ok());
+
+        self.erc20_decimals
+            .lock()
+            .map_err(|_| CollectError::CollectError("could not lock decimals cache".to_string()))?
+            .insert(contract, decimals);
+        Ok(decimals)
+    }
+
+    /// get token of pool at index, calling token0() and token1() of uniswap-style pools or
+    /// coins() of curve-style pools
+    pub async fn get_pool_token(&self, pool: Vec<u8>, index: u64) -> Result<Option<Vec<u8>>> {
+        let key = (pool, index);
+        if let Some(token) = self
+            .pool_tokens
+            .lock()
+            .map_err(|_| CollectError::CollectError("could not lock pool tokens".to_string()))?
+            .get(&key)
+        {
+            return Ok(token.clone())
+        }
+
+        let address = H160::from_slice(&key.0);
+        let mut calls = Vec::new();
+        match index {
+            0 => calls.push(FUNCTION_UNISWAP_TOKEN0.clone()),
+            1 => calls.push(FUNCTION_UNISWAP_TOKEN1.clone()),
+            _ => {}
+        };
+        let mut coins_call = FUNCTION_CURVE_COINS.clone();
+        coins_call.extend(H256::from_low_u64_be(index).as_bytes());
+        calls.push(coins_call);
+        let mut token = None;
+        for call_data in calls.into_iter() {
+            if let Ok(output) = self.fetcher.call2(address, call_data, BlockNumber::Latest).await {
+                if output.len() == 32 {
+                    token = Some(output[12..].to_vec());
+                    break
+                }
+            }
+        }
+
+        self.pool_tokens
+            .lock()
+            .map_err(|_| CollectError::CollectError("could not lock pool tokens".to_string()))?
+            .insert(key, token.clone());
+        Ok(token)
+    }
+
+    /// Return output data of a contract call, batching through Multicall3 if enabled
+    pub async fn call_batched(
+        &self,
+        address: H160,
+        call_data: Vec<u8>,
+        block_number: u64,
+    ) -> Result<Bytes> {
+        match &self.multicall {
+            Some(multicall) => multicall
+                .call(self.fetcher.clone(), address, call_data, block_number)
+                .await?
+                .ok_or(CollectError::CollectError("call reverted".to_string())),
+            None => self.fetcher.call2(address, call_data, block_number.into()).await,
+        }
+    }
+
+    /// Return output data of a contract call executed through Multicall3, None if it reverted
+    ///
+    /// calls are batched if batching is enabled, otherwise each call is sent on its own. at
+    /// blocks before Multicall3 was deployed, the call is sent directly to its contract
+    pub async fn call_through_multicall(
+        &self,
+        address: H160,
+        call_data: Vec<u8>,
+        block_number: u64,
+    ) -> Result<Option<Bytes>> {
+        match &self.multicall {
+            Some(multicall) => {
+                multicall.call(self.fetcher.clone(), address, call_data, block_number).await
+            }
+            None => {
+                let calls = [(address, call_data)];
+                let mut results = self.fetcher.multicall(&calls, block_number.into()).await?;
+                Ok(results.pop().flatten())
+            }
+        }
+    }
+
+    /// get logs matching filter, splitting its block range in half and retrying whenever the
+    /// provider rejects a request for covering too many blocks or logs
+    pub async fn get_logs(&self, filter: &Filter) -> Result<Vec<Log>> {
+        let (start_block, end_block) = match filter.block_option {
+            FilterBlockOption::Range {
+                from_block: Some(BlockNumber::Number(start_block)),
+                to_block: Some(BlockNumber::Number(end_block)),
+            } => (start_block.as_u64(), end_block.as_u64()),
+            _ => return self.fetcher.get_logs(filter).await,
+        };
+        let mut logs = Vec::new();
+        let mut ranges = vec![(start_block, end_block)];
+        while let Some((start_block, end_block)) = ranges.pop() {
+            let filter = filter.clone().from_block(start_block).to_block(end_block);
+            match self.fetcher.get_logs(&filter).await {
+                Ok(new_logs) => logs.extend(new_logs),
+                Err(e) if start_block < end_block && self.is_log_split_error(&e) => {
+                    tracing::info!(start_block, end_block, "splitting log request");
+                    let middle = start_block + (end_block - start_block) / 2;
+                    ranges.push((middle + 1, end_block));
+                    ranges.push((start_block, middle));
+                }
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(logs)
+    }
+
+    fn is_log_split_error(&self, error: &CollectError) -> bool {
+        let message = error.to_string().to_lowercase();
+        self.log_split_errors.iter().any(|pattern| message.contains(pattern.as_str()))
+    }
+
+    /// get balance of address, through a json-rpc batch if batching is enabled
+    pub async fn get_balance(&self, address: H160, block_number: BlockNumber) -> Result<U256> {
+        match &self.rpc_batcher {
+            Some(batcher) => {
+                let params = (address, block_number);
+                batcher.request(self.fetcher.clone(), "eth_getBalance", params).await
+            }
+            None => self.fetcher.get_balance(address, block_number).await,
+        }
+    }
+
+    /// get code of address, through a json-rpc batch if batching is enabled
+    pub async fn get_code(&self, address: H160, block_number: BlockNumber) -> Result<Bytes> {
+        match &self.rpc_batcher {
+            Some(batcher) => {
+                let params = (address, block_number);
+                batcher.request(self.fetcher.clone(), "eth_getCode", params).await
+            }
+            None => self.fetcher.get_code(address, block_number).await,
+        }
+    }
+
+    /// get nonce of address, through a json-rpc batch if batching is enabled
+    pub async fn get_transaction_count(
+        &self,
+        address: H160,
+        block_number: BlockNumber,
+    ) -> Result<U256> {
+        match &self.rpc_batcher {
+            Some(batcher) => {
+                let params = (address, block_number);
+                batcher.request(self.fetcher.clone(), "eth_getTransactionCount", params).await
+            }
+            None => self.fetcher.get_transaction_count(address, block_number).await,
+        }
+    }
+
+    /// get storage of address at slot, through a json-rpc batch if batching is enabled
+    pub async fn get_storage_at(
+        &self,
+        address: H160,
+        slot: H256,
+        block_number: BlockNumber,
+    ) -> Result<H256> {
+        match &self.rpc_batcher {
+            Some(batcher) => {
+                let params = (address, slot, block_number);
+                batcher.request(self.fetcher.clone(), "eth_getStorageAt", params).await
+            }
+            None => self.fetcher.get_storage_at(address, slot, block_number).await,
+        }
+    }
+
+    /// get transaction, through a json-rpc batch if batching is enabled
+    pub async fn get_transaction(&self, tx_hash: TxHash) -> Result<Option<Transaction>> {
+        match &self.rpc_batcher {
+            Some(batcher) => {
+                batcher.request(self.fetcher.clone(), "eth_getTransactionByHash", [tx_hash]).await
+            }
+            None => self.fetcher.get_transaction(tx_hash).await,
+        }
+    }
+
+    /// get transaction receipt, through a json-rpc batch if batching is enabled
+    pub async fn get_transaction_receipt(
+        &self,
+        tx_hash: TxHash,
+    ) -> Result<Option<TransactionReceipt>> {
+        match &self.rpc_batcher {
+            Some(batcher) => {
+                batcher.request(self.fetcher.clone(), "eth_getTransactionReceipt", [tx_hash]).await
+            }
+            None => self.fetcher.get_transaction_receipt(tx_hash).await,
+        }
+    }
+
+    /// get receipts of block, through `erigon_getBlockReceipts` if node supports it, and one
+    /// receipt per transaction if node supports no block receipt method
+    pub async fn get_block_receipts(&self, block_number: u64) -> Result<Vec<TransactionReceipt>> {
+        if self.capabilities.erigon_block_receipts {
+            return self.fetcher.erigon_get_block_receipts(block_number).await
+        }
+        if self.capabilities.eth_block_receipts {
+            return self.fetcher.get_block_receipts(block_number).await
+        }
+        let block = self
+            .fetcher
+            .get_block(block_number)
+            .await?
+            .ok_or(CollectError::CollectError("could not find block".to_string()))?;
+        let requests = block.transactions.into_iter().map(|tx_hash| async move {
+            self.get_transaction_receipt(tx_hash)
+                .await?
+                .ok_or(CollectError::CollectError("could not find tx receipt".to_string()))
+        });
+        futures::future::try_join_all(requests).await
+    }
+
     /// get gas used by transactions in block
     pub async fn get_txs_gas_used(&self, block: &Block<Transaction>) -> Result<Vec<u32>> {
-        match get_txs_gas_used_per_block(block, self.fetcher.clone()).await {
+        match get_txs_gas_used_per_block(block, self).await {
             Ok(value) => Ok(value),
             Err(_) => get_txs_gas_used_per_tx(block, self.fetcher.clone()).await,
         }
     }
 }
 
-async fn get_txs_gas_used_per_block<P: JsonRpcClient>(
+async fn get_txs_gas_used_per_block(
     block: &Block<Transaction>,
-    fetcher: Arc<Fetcher<P>>,
+    source: &Source,
 ) -> Result<Vec<u32>> {
-    // let fetcher = Arc::new(fetcher);
     let block_number = match block.number {
         Some(number) => number,
         None => return Err(CollectError::CollectError("no block number".to_string())),
     };
-    let receipts = fetcher.get_block_receipts(block_number.as_u64()).await?;
+    let receipts = source.get_block_receipts(block_number.as_u64()).await?;
     let mut gas_used: Vec<u32> = Vec::new();
     for receipt in receipts {
         match receipt.gas_used {
@@ -387,12 +1340,17 @@ async fn get_txs_gas_used_per_tx<P: JsonRpcClient + 'static>(
     for tx in &block.transactions {
         let tx_clone = tx.hash;
         let fetcher = fetcher.clone();
-        let task = task::spawn(async move {
-            match fetcher.get_transaction_receipt(tx_clone).await? {
-                Some(receipt) => Ok(receipt.gas_used),
-                None => Err(CollectError::CollectError("could not find tx receipt".to_string())),
+        let task = task::spawn(
+            async move {
+                match fetcher.get_transaction_receipt(tx_clone).await? {
+                    Some(receipt) => Ok(receipt.gas_used),
+                    None => {
+                        Err(CollectError::CollectError("could not find tx receipt".to_string()))
+                    }
+                }
             }
-        });
+            .in_current_span(),
+        );
         tasks.push(task);
     }
 
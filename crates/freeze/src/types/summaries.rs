@@ -5,8 +5,8 @@ use colored::Colorize;
 use thousands::Separable;
 
 use crate::{
-    chunks::chunk_ops::ValueToString, ChunkData, ChunkStats, CollectError, ColumnType, Datatype,
-    Dim, ExecutionEnv, FileOutput, Partition, Query, Source, Table,
+    chunks::chunk_ops::ValueToString, ChunkData, ChunkStats, CollectError, ColumnType, DataQuality,
+    Datatype, Dim, ExecutionEnv, FileOutput, Partition, Query, RpcMethodSummary, Source, Table,
 };
 use std::path::PathBuf;
 
@@ -26,6 +26,47 @@ pub struct FreezeSummary {
     pub skipped: Vec<Partition>,
     /// partitions errored
     pub errored: Vec<(Option<Partition>, CollectError)>,
+    /// partitions collected without rows, whose files were not written
+    pub empty: Vec<Partition>,
+    /// partitions left uncollected because of a shutdown request
+    pub interrupted: Vec<Partition>,
+    /// requests sent for each rpc method
+    pub rpc_methods: Vec<RpcMethodSummary>,
+    /// outcome of each output file of the partitions collected
+    pub chunks: Vec<ChunkRecord>,
+    /// existing files that partitions were collected into again
+    pub overwritten: Vec<PathBuf>,
+    /// existing files that partitions were collected beside, with the paths of the new versions
+    pub versioned: Vec<(PathBuf, PathBuf)>,
+    /// statistics of each column of the chunks collected
+    pub quality: DataQuality,
+}
+
+/// outcome of an output file of a collected partition, one row of the chunk table of a report
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ChunkRecord {
+    /// datatype of file
+    pub datatype: String,
+    /// label of partition
+    pub partition: String,
+    /// first block of partition, if it is partitioned by block
+    pub start_block: Option<u64>,
+    /// last block of partition, if it is partitioned by block
+    pub end_block: Option<u64>,
+    /// path of file
+    pub path: PathBuf,
+    /// one of written, empty, interrupted, or errored
+    pub status: String,
+    /// rows written to file
+    pub n_rows: Option<u64>,
+    /// size of file
+    pub n_bytes: Option<u64>,
+    /// duration of the last attempt at collecting and writing the partition
+    pub duration_ms: u64,
+    /// number of times the partition was retried after erroring
+    pub retries: u64,
+    /// error of the last attempt, if it errored
+    pub error: Option<String>,
 }
 
 pub(crate) fn print_header<A: AsRef<str>>(header: A) {
@@ -104,6 +145,18 @@ pub(crate) fn print_cryo_intro(
     if query.schemas.contains_key(&Datatype::Logs) {
         print_bullet_indent("inner request size", source.inner_request_size.to_string(), 4);
     };
+    let mut dataset_sources: Vec<_> = source.dataset_sources.iter().collect();
+    dataset_sources.sort_by_key(|(datatype, _)| datatype.name());
+    for (datatype, dataset_source) in dataset_sources.into_iter() {
+        let limit = |value: Option<u64>| value.map_or("unlimited".to_string(), |v| v.to_string());
+        let limits = format!(
+            "{} concurrent, {} per second, inner request size {}",
+            limit(dataset_source.max_concurrent_requests),
+            limit(dataset_source.max_requests_per_second),
+            dataset_source.inner_request_size
+        );
+        print_bullet_indent(format!("{} requests", datatype.name()), limits, 4);
+    }
 
     print_bullet("output", "");
     if let Some(partition) = query.partitions.first() {
@@ -121,6 +174,10 @@ pub(crate) fn print_cryo_intro(
     print_bullet_indent("chunks remaining", n_chunks_remaining.to_string(), 4);
     print_bullet_indent("output format", sink.format.as_str(), 4);
     print_bullet_indent("output dir", sink.output_dir.clone().to_string_lossy(), 4);
+    if let Some(remote) = &sink.remote {
+        print_bullet_indent("output uri", &remote.uri, 4);
+    }
+    print_bullet_indent("existing files", sink.existing_files.as_str(), 4);
 
     // print report path
     let report_path = if env.report && n_chunks_remaining > 0 {
@@ -159,7 +216,9 @@ pub(crate) fn print_cryo_intro(
 
 fn print_chunks(chunks: &[Partition]) {
     let stats = crate::types::partitions::meta_chunks_stats(chunks);
-    for (dim, dim_stats) in [(Dim::BlockNumber, stats.block_numbers)].iter() {
+    for (dim, dim_stats) in
+        [(Dim::BlockNumber, stats.block_numbers), (Dim::BeaconSlot, stats.beacon_slots)].iter()
+    {
         if let Some(dim_stats) = dim_stats {
             print_chunk(dim, dim_stats)
         }
@@ -275,6 +334,12 @@ pub(crate) fn print_cryo_conclusion(
     } else {
         println!("...done (errors in {} chunks)", freeze_summary.errored.len())
     };
+    if !freeze_summary.interrupted.is_empty() {
+        println!(
+            "...interrupted, rerun the same command to collect the remaining {} chunks",
+            freeze_summary.interrupted.len()
+        )
+    }
     println!();
     println!();
 
@@ -346,8 +411,111 @@ pub(crate) fn print_cryo_conclusion(
         ),
         4,
     );
+    if !freeze_summary.empty.is_empty() {
+        print_bullet_indent(
+            "chunks empty",
+            format!(
+                "    {:>width$} / {} ({}%)",
+                freeze_summary.empty.len().separate_with_commas(),
+                n_chunks_str,
+                format_float((100 * freeze_summary.empty.len() / n_chunks) as f64),
+                width = width
+            ),
+            4,
+        );
+    }
+    if !freeze_summary.interrupted.is_empty() {
+        print_bullet_indent(
+            "chunks interrupted",
+            format!(
+                "{:>width$} / {} ({}%)",
+                freeze_summary.interrupted.len().separate_with_commas(),
+                n_chunks_str,
+                format_float((100 * freeze_summary.interrupted.len() / n_chunks) as f64),
+                width = width
+            ),
+            4,
+        );
+    }
+    if !freeze_summary.overwritten.is_empty() {
+        print_bullet("files overwritten", freeze_summary.overwritten.len().separate_with_commas());
+    }
+    if !freeze_summary.versioned.is_empty() {
+        print_bullet("files versioned", freeze_summary.versioned.len().separate_with_commas());
+    }
 
     print_chunks_speeds(freeze_summary.completed.clone(), &query.partitioned_by, total_time);
+    print_rpc_methods(&freeze_summary.rpc_methods);
+    print_data_quality(&freeze_summary.quality);
+}
+
+fn print_data_quality(quality: &DataQuality) {
+    let anomalies = quality.anomalies();
+    if anomalies.is_empty() {
+        return
+    }
+    println!();
+    println!();
+    print_header_error("data quality");
+    for anomaly in anomalies.iter().take(10) {
+        println!("- {}", anomaly);
+    }
+    if anomalies.len() > 10 {
+        println!("...")
+    }
+}
+
+/// print outcome of each chain of a multi-chain collection
+pub fn print_chain_summaries(chains: &[(String, Result<FreezeSummary, CollectError>)]) {
+    println!();
+    println!();
+    print_header("chain summary");
+    for (chain, result) in chains.iter() {
+        match result {
+            Ok(summary) => print_bullet(
+                chain,
+                format!(
+                    "{} chunks collected, {} empty, {} skipped, {} errored, {} interrupted",
+                    summary.completed.len().separate_with_commas(),
+                    summary.empty.len().separate_with_commas(),
+                    summary.skipped.len().separate_with_commas(),
+                    summary.errored.len().separate_with_commas(),
+                    summary.interrupted.len().separate_with_commas()
+                ),
+            ),
+            Err(e) => print_bullet(chain, format!("failed: {}", e)),
+        }
+    }
+}
+
+fn print_rpc_methods(rpc_methods: &[RpcMethodSummary]) {
+    if rpc_methods.is_empty() {
+        return
+    }
+    println!();
+    println!();
+    print_header("rpc summary");
+    for method in rpc_methods.iter() {
+        print_bullet(
+            &method.method,
+            format!(
+                "{} requests, {} errors",
+                method.n_requests.separate_with_commas(),
+                method.n_errors.separate_with_commas()
+            ),
+        );
+        print_bullet_indent(
+            "latency p50 / p90 / p99 / max",
+            format!(
+                "{} / {} / {} / {} ms",
+                format_float(method.p50_ms),
+                format_float(method.p90_ms),
+                format_float(method.p99_ms),
+                format_float(method.max_ms)
+            ),
+            4,
+        );
+    }
 }
 
 macro_rules! print_dim_speed {
@@ -373,6 +541,7 @@ fn print_chunks_speeds(chunks: Vec<Partition>, partition_by: &[Dim], total_time:
     print_dim_speed!(chunks, partition_by, total_time, topic1s, Dim::Topic1);
     print_dim_speed!(chunks, partition_by, total_time, topic2s, Dim::Topic2);
     print_dim_speed!(chunks, partition_by, total_time, topic3s, Dim::Topic3);
+    print_dim_speed!(chunks, partition_by, total_time, beacon_slots, Dim::BeaconSlot);
 }
 
 fn print_chunk_speed<T: ChunkData>(name: &str, total_time: f64, chunks: Vec<Option<Vec<T>>>) {
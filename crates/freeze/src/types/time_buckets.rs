@@ -0,0 +1,165 @@
+/// output files of datasets collected over time, such as snapshots of the mempool
+use std::path::PathBuf;
+
+use polars::prelude::*;
+
+use crate::{dataframes, CollectError, Datatype, FileFormat, FileOutput};
+
+/// rows of a datatype collected over time, buffered until the time bucket of their timestamps
+/// ends and then written to the file of the bucket
+///
+/// buckets start at multiples of their length in seconds since the unix epoch, so that files of
+/// separate runs line up, and rows of a bucket whose file already exists are appended to it
+pub struct TimeBuckets {
+    datatype: Datatype,
+    seconds: u64,
+    sink: FileOutput,
+    sort: Vec<String>,
+    current: Option<(u64, DataFrame)>,
+}
+
+impl TimeBuckets {
+    /// create time buckets of seconds each for rows of datatype, sorting rows of files by sort
+    pub fn new(
+        datatype: Datatype,
+        seconds: u64,
+        sink: FileOutput,
+        sort: Vec<String>,
+    ) -> Result<Self, CollectError> {
+        if seconds == 0 {
+            return Err(CollectError::CollectError("time buckets must be at least 1s".to_string()))
+        }
+        Ok(TimeBuckets { datatype, seconds, sink, sort, current: None })
+    }
+
+    /// start of the bucket of timestamp
+    pub fn bucket_start(&self, timestamp: u64) -> u64 {
+        timestamp - timestamp % self.seconds
+    }
+
+    /// output file path of the bucket starting at start
+    pub fn path(&self, start: u64) -> PathBuf {
+        self.sink.get_time_bucket_path(self.datatype, start, self.seconds)
+    }
+
+    /// add rows collected at timestamp, writing the rows of the previous bucket if timestamp is
+    /// in a later bucket and returning the path of the written file
+    ///
+    /// the first rows of a bucket whose file exists are appended to the rows of the file
+    pub fn push(&mut self, timestamp: u64, df: DataFrame) -> Result<Option<PathBuf>, CollectError> {
+        let start = self.bucket_start(timestamp);
+        let written = match &self.current {
+            Some((current, _)) if *current != start => self.flush()?,
+            _ => None,
+        };
+        match &mut self.current {
+            Some((_, rows)) => {
+                rows.vstack_mut(&df)?;
+            }
+            None => {
+                let rows = match self.read_bucket(start)? {
+                    Some(mut rows) => {
+                        rows.vstack_mut(&df)?;
+                        rows
+                    }
+                    None => df,
+                };
+                self.current = Some((start, rows));
+            }
+        }
+        Ok(written)
+    }
+
+    /// rows of the file of the bucket of timestamp written by an earlier run, if it exists
+    pub fn read_existing(&self, timestamp: u64) -> Result<Option<DataFrame>, CollectError> {
+        self.read_bucket(self.bucket_start(timestamp))
+    }
+
+    /// write the rows of the current bucket to its file, returning its path if there were rows
+    pub fn flush(&mut self) -> Result<Option<PathBuf>, CollectError> {
+        let Some((start, rows)) = self.current.take() else { return Ok(None) };
+        if rows.height() == 0 {
+            return Ok(None)
+        }
+        let path = self.path(start);
+        let mut rows = dataframes::sort_by_columns(rows, &self.sort)?;
+        dataframes::df_to_file(&mut rows, &path, &self.sink).map_err(|_| {
+            CollectError::CollectError(format!("could not write {}", path.display()))
+        })?;
+        Ok(Some(path))
+    }
+
+    fn read_bucket(&self, start: u64) -> Result<Option<DataFrame>, CollectError> {
+        let path = self.path(start);
+        if !path.exists() {
+            return Ok(None)
+        }
+        if self.sink.format != FileFormat::Parquet {
+            return Err(CollectError::CollectError(format!(
+                "can only append to parquet files, remove {} to collect its time bucket again",
+                path.display()
+            )))
+        }
+        let file = std::fs::File::open(&path).map_err(|e| {
+            CollectError::CollectError(format!("could not open {}: {}", path.display(), e))
+        })?;
+        Ok(Some(ParquetReader::new(file).finish()?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ExistingFilePolicy, TextCompression};
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_time_buckets() {
+        let dir = std::env::temp_dir().join("cryo_test_time_buckets");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let sink = FileOutput {
+            output_dir: dir.clone(),
+            prefix: "ethereum".to_string(),
+            suffix: None,
+            existing_files: ExistingFilePolicy::Overwrite,
+            allow_schema_change: false,
+            format: FileFormat::Parquet,
+            row_group_size: None,
+            global_sort: false,
+            dedup_keys: HashMap::new(),
+            parquet_statistics: true,
+            parquet_compression: ParquetCompression::Lz4Raw,
+            text_compression: TextCompression::Uncompressed,
+            hex_prefix: true,
+            clickhouse: None,
+            manifest: false,
+            pin_block_hash: false,
+            skip_empty_files: false,
+            max_concurrent_writes: None,
+            remote: None,
+        };
+        let sort = vec!["timestamp".to_string()];
+        let mut buckets = TimeBuckets::new(Datatype::Mempool, 60, sink.clone(), sort).unwrap();
+        let path = dir.join("ethereum__mempool__0000000120_to_0000000179.parquet");
+        assert_eq!(buckets.path(buckets.bucket_start(150)), path);
+
+        // rows are written once a later bucket starts
+        let rows = |timestamps: &[u32]| df!("timestamp" => timestamps).unwrap();
+        assert_eq!(buckets.push(150, rows(&[150])).unwrap(), None);
+        assert_eq!(buckets.push(179, rows(&[179])).unwrap(), None);
+        assert_eq!(buckets.push(180, rows(&[180])).unwrap(), Some(path.clone()));
+        assert_eq!(buckets.read_existing(170).unwrap().unwrap().height(), 2);
+
+        // rows of a bucket whose file exists are appended to it
+        let sort = vec!["timestamp".to_string()];
+        let mut buckets = TimeBuckets::new(Datatype::Mempool, 60, sink, sort).unwrap();
+        buckets.push(160, rows(&[160])).unwrap();
+        assert_eq!(buckets.flush().unwrap(), Some(path.clone()));
+        let written = buckets.read_existing(120).unwrap().unwrap();
+        let timestamps: Vec<_> =
+            written.column("timestamp").unwrap().u32().unwrap().into_iter().collect();
+        assert_eq!(timestamps, vec![Some(150), Some(160), Some(179)]);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,83 @@
+/// attachment of block timestamps to datasets indexed by block
+use std::collections::{BTreeSet, HashMap};
+
+use polars::prelude::*;
+
+use crate::{err, CollectError, Datatype, Source};
+
+/// number of block timestamps cached by default, enough to span several chunks
+pub const TIMESTAMP_CACHE_BLOCKS: usize = 10_000;
+
+/// add timestamp and block_date columns after the block_number column of each dataframe
+///
+/// timestamps of dataframes that already have a timestamp column are reused, other timestamps
+/// are fetched once per block through the timestamp cache of source
+pub(crate) async fn attach_timestamps(
+    source: &Source,
+    dfs: HashMap<Datatype, DataFrame>,
+) -> Result<HashMap<Datatype, DataFrame>, CollectError> {
+    let cache = match &source.timestamp_cache {
+        Some(cache) => cache,
+        None => return Ok(dfs),
+    };
+    let mut output = HashMap::new();
+    for (datatype, mut df) in dfs.into_iter() {
+        let block_numbers: Vec<Option<u64>> = match df.column("block_number") {
+            Ok(column) => column.cast(&DataType::UInt64)?.u64()?.into_iter().collect(),
+            Err(_) => {
+                output.insert(datatype, df);
+                continue
+            }
+        };
+        let timestamps: Vec<Option<u32>> = match df.column("timestamp") {
+            Ok(column) => column.cast(&DataType::UInt32)?.u32()?.into_iter().collect(),
+            Err(_) => {
+                let unique: BTreeSet<u64> = block_numbers.iter().flatten().copied().collect();
+                let requests = unique.into_iter().map(|block_number| async move {
+                    let timestamp = cache
+                        .get_or_fetch(block_number, || fetch_timestamp(source, block_number))
+                        .await?;
+                    Ok::<_, CollectError>((block_number, timestamp))
+                });
+                let timestamps: HashMap<u64, u32> =
+                    futures::future::try_join_all(requests).await?.into_iter().collect();
+                block_numbers.iter().map(|n| n.and_then(|n| timestamps.get(&n).copied())).collect()
+            }
+        };
+        let dates: Vec<Option<String>> =
+            timestamps.iter().map(|t| t.and_then(block_date)).collect();
+
+        let mut index = df.get_column_names().iter().position(|c| *c == "block_number");
+        for series in [Series::new("timestamp", timestamps), Series::new("block_date", dates)] {
+            if df.column(series.name()).is_ok() {
+                continue
+            }
+            index = index.map(|index| index + 1);
+            df.insert_at_idx(index.unwrap_or(df.width()), series)?;
+        }
+        output.insert(datatype, df);
+    }
+    Ok(output)
+}
+
+async fn fetch_timestamp(source: &Source, block_number: u64) -> Result<u32, CollectError> {
+    let block = source.fetcher.get_block(block_number).await?.ok_or(err("block not found"))?;
+    Ok(block.timestamp.as_u32())
+}
+
+/// utc date of timestamp, formatted as YYYY-MM-DD
+fn block_date(timestamp: u32) -> Option<String> {
+    chrono::DateTime::from_timestamp(timestamp as i64, 0)
+        .map(|datetime| datetime.format("%Y-%m-%d").to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_block_date() {
+        assert_eq!(block_date(1438269973), Some("2015-07-30".to_string()));
+        assert_eq!(block_date(0), Some("1970-01-01".to_string()));
+    }
+}
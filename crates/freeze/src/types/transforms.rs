@@ -0,0 +1,161 @@
+/// types and functions related to user-defined transforms of collected data
+use std::{io::Cursor, path::PathBuf, sync::Arc};
+
+use polars::prelude::*;
+
+use crate::{CollectError, ParseError};
+
+/// fuel of each call of a WASM transform, roughly the number of instructions it can execute,
+/// so that a module that does not return fails its chunk instead of stalling collection
+const WASM_TRANSFORM_FUEL: u64 = 10_000_000_000;
+
+/// user-defined WASM module applied to each chunk before it is written
+///
+/// the module must export:
+/// - `memory`: the module's linear memory
+/// - `alloc(len: u32) -> u32`: allocate `len` bytes and return a pointer to them
+/// - `transform(ptr: u32, len: u32) -> u64`: read a chunk encoded as an Arrow IPC file from
+///   `ptr..ptr+len` and return the modified chunk, also encoded as an Arrow IPC file, packed as
+///   `(out_ptr << 32) | out_len`
+#[derive(Clone, Debug)]
+pub struct WasmTransform {
+    /// path of WASM module
+    pub path: PathBuf,
+    engine: wasmi::Engine,
+    module: Arc<wasmi::Module>,
+    /// fuel of each call of transform
+    fuel: u64,
+}
+
+impl PartialEq for WasmTransform {
+    fn eq(&self, other: &Self) -> bool {
+        self.path == other.path
+    }
+}
+
+impl WasmTransform {
+    /// load WASM transform from file
+    pub fn from_file(path: PathBuf) -> Result<Self, ParseError> {
+        let wasm = std::fs::read(&path).map_err(|e| {
+            ParseError::ParseError(format!("could not read transform {}: {}", path.display(), e))
+        })?;
+        Self::new(path, &wasm)
+    }
+
+    /// compile WASM transform from the contents of module
+    fn new(path: PathBuf, wasm: &[u8]) -> Result<Self, ParseError> {
+        let mut config = wasmi::Config::default();
+        config.consume_fuel(true);
+        let engine = wasmi::Engine::new(&config);
+        let module = wasmi::Module::new(&engine, wasm).map_err(|e| {
+            ParseError::ParseError(format!("invalid wasm module {}: {}", path.display(), e))
+        })?;
+        Ok(WasmTransform { path, engine, module: Arc::new(module), fuel: WASM_TRANSFORM_FUEL })
+    }
+
+    /// apply transform to dataframe
+    pub fn apply(&self, mut df: DataFrame) -> Result<DataFrame, CollectError> {
+        let mut input = Vec::new();
+        IpcWriter::new(&mut input)
+            .finish(&mut df)
+            .map_err(|e| transform_error(format!("could not encode chunk: {}", e)))?;
+        let output = self.call(&input).map_err(transform_error)?;
+        IpcReader::new(Cursor::new(output))
+            .finish()
+            .map_err(|e| transform_error(format!("could not decode transform output: {}", e)))
+    }
+
+    fn call(&self, input: &[u8]) -> Result<Vec<u8>, String> {
+        let mut store = wasmi::Store::new(&self.engine, ());
+        store.add_fuel(self.fuel).map_err(|e| e.to_string())?;
+        let linker = <wasmi::Linker<()>>::new(&self.engine);
+        let instance = linker
+            .instantiate(&mut store, &self.module)
+            .and_then(|pre| pre.start(&mut store))
+            .map_err(|e| e.to_string())?;
+        let memory = instance.get_memory(&store, "memory").ok_or("missing memory export")?;
+        let alloc = instance
+            .get_typed_func::<u32, u32>(&store, "alloc")
+            .map_err(|e| format!("invalid alloc export: {}", e))?;
+        let transform = instance
+            .get_typed_func::<(u32, u32), u64>(&store, "transform")
+            .map_err(|e| format!("invalid transform export: {}", e))?;
+
+        let input_len = u32::try_from(input.len()).map_err(|_| "chunk too large")?;
+        let call_error = |e: wasmi::core::Trap| match e.trap_code() {
+            Some(wasmi::core::TrapCode::OutOfFuel) => {
+                format!("transform did not return within {} instructions", self.fuel)
+            }
+            _ => e.to_string(),
+        };
+        let input_ptr = alloc.call(&mut store, input_len).map_err(call_error)?;
+        memory.write(&mut store, input_ptr as usize, input).map_err(|e| e.to_string())?;
+        let packed = transform.call(&mut store, (input_ptr, input_len)).map_err(call_error)?;
+
+        let (output_ptr, output_len) = ((packed >> 32) as usize, (packed & 0xffffffff) as usize);
+        let mut output = vec![0u8; output_len];
+        memory.read(&store, output_ptr, &mut output).map_err(|e| e.to_string())?;
+        Ok(output)
+    }
+}
+
+/// run SQL query over a chunk, where the chunk is registered as a table named after its dataset
+pub fn apply_sql(df: DataFrame, table_name: &str, query: &str) -> Result<DataFrame, CollectError> {
+    let mut context = polars::sql::SQLContext::new();
+    context.register(table_name, df.lazy());
+    context
+        .execute(query)
+        .and_then(|lf| lf.collect())
+        .map_err(|e| CollectError::CollectError(format!("sql query failed: {}", e)))
+}
+
+fn transform_error(message: impl std::fmt::Display) -> CollectError {
+    CollectError::CollectError(format!("wasm transform failed: {}", message))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// transform that returns its input, allocating every input at the start of memory
+    const IDENTITY_TRANSFORM: &str = r#"
+        (module
+            (memory (export "memory") 64)
+            (func (export "alloc") (param i32) (result i32) (i32.const 0))
+            (func (export "transform") (param i32 i32) (result i64)
+                (i64.or
+                    (i64.shl (i64.extend_i32_u (local.get 0)) (i64.const 32))
+                    (i64.extend_i32_u (local.get 1)))))
+    "#;
+
+    fn wasm_transform(wat: &str) -> WasmTransform {
+        WasmTransform::new(PathBuf::from("test.wasm"), &wat::parse_str(wat).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_wasm_transform() {
+        let df = df!("block_number" => [1u32, 2, 3], "n_logs" => [10u64, 20, 30]).unwrap();
+        let transform = wasm_transform(IDENTITY_TRANSFORM);
+        assert_eq!(transform.apply(df.clone()).unwrap(), df);
+
+        // modules without the exports of transforms fail their chunks
+        let transform = wasm_transform(&IDENTITY_TRANSFORM.replace("\"transform\"", "\"other\""));
+        let e = transform.apply(df.clone()).unwrap_err();
+        assert!(e.to_string().contains("invalid transform export"));
+
+        // modules that do not return run out of fuel
+        let looping = IDENTITY_TRANSFORM.replace("(i64.or", "(loop (br 0)) (i64.or");
+        let transform = WasmTransform { fuel: 1_000_000, ..wasm_transform(&looping) };
+        let e = transform.apply(df).unwrap_err();
+        assert!(e.to_string().contains("did not return"));
+    }
+
+    #[test]
+    fn test_apply_sql() {
+        let df = df!("block_number" => [1u32, 2, 3], "n_logs" => [10u64, 20, 30]).unwrap();
+        let query = "select block_number from logs where n_logs > 15";
+        let output = apply_sql(df, "logs", query).unwrap();
+        assert_eq!(output, df!("block_number" => [2u32, 3]).unwrap());
+        assert!(apply_sql(output, "logs", "select * from blocks").is_err());
+    }
+}
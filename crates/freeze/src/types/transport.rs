@@ -0,0 +1,238 @@
+/// transports of the json-rpc requests of sources, over http, through fixtures, or read from a
+/// node's database
+use std::{
+    collections::HashMap,
+    path::Path,
+    sync::{Arc, RwLock},
+};
+
+use ethers::prelude::*;
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
+
+use crate::{CollectError, Fixtures, ParseError};
+
+/// transport of the json-rpc requests of sources
+#[derive(Debug)]
+pub enum RpcTransport {
+    /// requests sent to node over http, retrying rate limited requests
+    Http(RetryClient<Http>),
+    /// requests sent to node over http, recording their responses to fixtures
+    Record(RetryClient<Http>, Fixtures),
+    /// requests answered from recorded fixtures, without a node
+    Replay(Fixtures),
+    /// requests answered from the database of a node, without a running node
+    Database(Arc<dyn DatabaseBackend>),
+}
+
+impl RpcTransport {
+    /// transport of requests to node over http, recording their responses to the fixtures file
+    /// at record_fixtures if given
+    pub fn http(
+        client: RetryClient<Http>,
+        record_fixtures: Option<&Path>,
+    ) -> Result<RpcTransport, CollectError> {
+        match record_fixtures {
+            Some(path) => Ok(RpcTransport::Record(client, Fixtures::load(path)?)),
+            None => Ok(RpcTransport::Http(client)),
+        }
+    }
+
+    /// transport answering requests from the fixtures file at path
+    pub fn replay(path: &Path) -> Result<RpcTransport, CollectError> {
+        if !path.exists() {
+            return Err(CollectError::CollectError(format!(
+                "fixtures file {} does not exist",
+                path.display()
+            )))
+        }
+        Ok(RpcTransport::Replay(Fixtures::load(path)?))
+    }
+
+    /// transport answering requests from the database at path, opened by the database backend
+    /// registered under name
+    pub fn database(name: &str, path: &Path) -> Result<RpcTransport, CollectError> {
+        let open = DATABASE_BACKENDS.read().ok().and_then(|backends| backends.get(name).copied());
+        let open = open.ok_or_else(|| {
+            CollectError::CollectError(format!(
+                "reading a {} database requires a cryo binary with a {} database backend, such \
+                 as the one of crates/{}",
+                name, name, name
+            ))
+        })?;
+        Ok(RpcTransport::Database(open(path)?))
+    }
+
+    /// whether requests are sent to a node over http without fixtures, so that requests sent
+    /// around the transport, such as json-rpc batches, are neither recorded, replayed, nor sent
+    /// to databases
+    pub fn is_http(&self) -> bool {
+        matches!(self, RpcTransport::Http(_))
+    }
+}
+
+/// database of a node that answers json-rpc requests by reading from it directly
+///
+/// backends are implemented out of tree, such as the reth backend of `crates/reth`, so that
+/// their dependencies are only built by the binaries that register them
+#[async_trait::async_trait]
+pub trait DatabaseBackend: std::fmt::Debug + Send + Sync {
+    /// response of json-rpc request, which is a json-rpc error for methods that the database
+    /// cannot answer
+    async fn request(&self, method: &str, params: Value) -> Result<Value, TransportError>;
+}
+
+/// function that opens the database of a backend at path
+pub type DatabaseOpenFn = fn(&Path) -> Result<Arc<dyn DatabaseBackend>, CollectError>;
+
+lazy_static::lazy_static! {
+    static ref DATABASE_BACKENDS: RwLock<HashMap<&'static str, DatabaseOpenFn>> =
+        RwLock::new(HashMap::new());
+}
+
+/// register a database backend under name, so that a custom binary can open databases of name
+/// with `RpcTransport::database()` and then call `cryo_cli::run()`
+pub fn register_database_backend(
+    name: &'static str,
+    open: DatabaseOpenFn,
+) -> Result<(), ParseError> {
+    let mut backends = DATABASE_BACKENDS
+        .write()
+        .map_err(|_| ParseError::ParseError("could not lock database backends".to_string()))?;
+    if backends.contains_key(name) {
+        return Err(ParseError::ParseError(format!("database backend already registered: {}", name)))
+    }
+    backends.insert(name, open);
+    Ok(())
+}
+
+/// error of a request sent through an `RpcTransport`
+#[derive(Debug, thiserror::Error)]
+pub enum TransportError {
+    /// error of a request sent over http
+    #[error(transparent)]
+    Http(#[from] RetryClientError),
+    /// json-rpc error response, of a database or recorded in fixtures
+    #[error(transparent)]
+    JsonRpc(JsonRpcError),
+    /// request without a recorded response
+    #[error("no fixture of request {0}, record it again with --record-fixtures")]
+    Missing(String),
+    /// error reading from the database of a node
+    #[error("database error: {0}")]
+    Database(String),
+    /// (de)serialization error
+    #[error(transparent)]
+    SerdeJson(#[from] serde_json::Error),
+}
+
+impl TransportError {
+    /// json-rpc error response of a method that is not answered by the transport
+    pub fn method_not_found(method: &str) -> TransportError {
+        TransportError::JsonRpc(JsonRpcError {
+            code: -32601,
+            message: format!("the method {} does not exist/is not available", method),
+            data: None,
+        })
+    }
+}
+
+impl RpcError for TransportError {
+    fn as_error_response(&self) -> Option<&JsonRpcError> {
+        match self {
+            TransportError::Http(e) => e.as_error_response(),
+            TransportError::JsonRpc(e) => Some(e),
+            _ => None,
+        }
+    }
+
+    fn as_serde_error(&self) -> Option<&serde_json::Error> {
+        match self {
+            TransportError::Http(e) => e.as_serde_error(),
+            TransportError::SerdeJson(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<TransportError> for ProviderError {
+    fn from(e: TransportError) -> Self {
+        match e {
+            TransportError::Http(e) => e.into(),
+            TransportError::SerdeJson(e) => ProviderError::SerdeJson(e),
+            e => ProviderError::JsonRpcClientError(Box::new(e)),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl JsonRpcClient for RpcTransport {
+    type Error = TransportError;
+
+    async fn request<T, R>(&self, method: &str, params: T) -> Result<R, Self::Error>
+    where
+        T: std::fmt::Debug + Serialize + Send + Sync,
+        R: DeserializeOwned + Send,
+    {
+        match self {
+            RpcTransport::Http(client) => Ok(JsonRpcClient::request(client, method, params).await?),
+            RpcTransport::Record(client, fixtures) => {
+                let params = serde_json::to_value(params)?;
+                let result = JsonRpcClient::request::<_, Value>(client, method, &params).await;
+                // errors of transport rather than of node are not recorded
+                match &result {
+                    Ok(response) => fixtures.record(method, &params, Ok(response)),
+                    Err(e) => {
+                        if let Some(error) = e.as_error_response() {
+                            fixtures.record(method, &params, Err(error))
+                        }
+                    }
+                }
+                Ok(serde_json::from_value(result?)?)
+            }
+            RpcTransport::Replay(fixtures) => {
+                let params = serde_json::to_value(params)?;
+                match fixtures.get(method, &params)? {
+                    Ok(response) => Ok(serde_json::from_value(response)?),
+                    Err(error) => Err(TransportError::JsonRpc(error)),
+                }
+            }
+            RpcTransport::Database(db) => {
+                let params = serde_json::to_value(params)?;
+                Ok(serde_json::from_value(db.request(method, params).await?)?)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug)]
+    struct MockDatabase;
+
+    #[async_trait::async_trait]
+    impl DatabaseBackend for MockDatabase {
+        async fn request(&self, method: &str, _params: Value) -> Result<Value, TransportError> {
+            match method {
+                "eth_blockNumber" => Ok(Value::String("0x10".to_string())),
+                method => Err(TransportError::method_not_found(method)),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_database_transport() {
+        register_database_backend("mock", |_| Ok(Arc::new(MockDatabase))).unwrap();
+        assert!(register_database_backend("mock", |_| Ok(Arc::new(MockDatabase))).is_err());
+        assert!(RpcTransport::database("unregistered", Path::new("db")).is_err());
+
+        let transport = RpcTransport::database("mock", Path::new("db")).unwrap();
+        assert!(!transport.is_http());
+        let provider = Provider::new(transport);
+        assert_eq!(provider.get_block_number().await.unwrap(), U64::from(16));
+        let error = provider.get_chainid().await.unwrap_err();
+        assert_eq!(RpcError::as_error_response(&error).unwrap().code, -32601);
+    }
+}
@@ -0,0 +1,295 @@
+/// interactive terminal view of freeze progress
+use std::{
+    collections::{HashMap, VecDeque},
+    io::Stdout,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+use crossterm::{
+    cursor,
+    terminal::{EnterAlternateScreen, LeaveAlternateScreen},
+};
+use indexmap::IndexMap;
+use ratatui::{
+    backend::CrosstermBackend,
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Gauge, List, ListItem, Paragraph},
+    Frame, Terminal,
+};
+use thousands::Separable;
+
+use crate::CollectError;
+
+const REFRESH_INTERVAL: Duration = Duration::from_millis(250);
+const THROUGHPUT_WINDOW: Duration = Duration::from_secs(10);
+const MAX_RECENT_ERRORS: usize = 8;
+const GREEN: Color = Color::Rgb(0, 225, 0);
+const GREY: Color = Color::Rgb(170, 170, 170);
+const RED: Color = Color::Rgb(225, 0, 0);
+
+type TuiTerminal = Terminal<CrosstermBackend<Stdout>>;
+
+/// live progress of a freeze, rendered as a terminal view while chunks are collected
+#[derive(Default)]
+pub struct TuiProgress {
+    state: Mutex<TuiState>,
+    finished: AtomicBool,
+}
+
+#[derive(Default)]
+struct TuiState {
+    title: String,
+    datatypes: IndexMap<String, DatatypeProgress>,
+    running: IndexMap<String, Instant>,
+    failed: HashMap<String, String>,
+    recent_errors: VecDeque<String>,
+    n_retried: u64,
+    t_start: Option<Instant>,
+    request_samples: VecDeque<(Instant, u64)>,
+}
+
+#[derive(Default)]
+struct DatatypeProgress {
+    total: u64,
+    completed: u64,
+}
+
+impl TuiProgress {
+    /// create new TuiProgress
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn state(&self) -> std::sync::MutexGuard<'_, TuiState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// register the chunks of a freeze, given as (chunk label, datatype name) pairs
+    pub(crate) fn set_chunks(&self, title: String, chunks: &[(String, String)]) {
+        let mut state = self.state();
+        state.title = title;
+        state.t_start = Some(Instant::now());
+        for (_label, datatype) in chunks.iter() {
+            state.datatypes.entry(datatype.clone()).or_default().total += 1;
+        }
+    }
+
+    /// mark chunk as being collected
+    pub(crate) fn start_chunk(&self, label: &str) {
+        let mut state = self.state();
+        if state.failed.remove(label).is_some() {
+            state.n_retried += 1;
+        }
+        state.running.insert(label.to_string(), Instant::now());
+    }
+
+    /// mark chunk as finished, either completed or errored
+    pub(crate) fn finish_chunk(&self, label: &str, datatype: &str, error: Option<&CollectError>) {
+        let mut state = self.state();
+        state.running.shift_remove(label);
+        match error {
+            None => {
+                if let Some(progress) = state.datatypes.get_mut(datatype) {
+                    progress.completed += 1;
+                }
+            }
+            Some(e) => {
+                state.failed.insert(label.to_string(), e.to_string());
+                state.recent_errors.push_back(format!("{}: {}", label, e));
+                if state.recent_errors.len() > MAX_RECENT_ERRORS {
+                    state.recent_errors.pop_front();
+                }
+            }
+        }
+    }
+
+    /// mark chunk as dropped by a shutdown request
+    pub(crate) fn interrupt_chunk(&self, label: &str) {
+        self.state().running.shift_remove(label);
+    }
+
+    /// stop rendering
+    pub(crate) fn finish(&self) {
+        self.finished.store(true, Ordering::Relaxed);
+    }
+
+    /// render progress until finished, restoring the terminal afterwards
+    pub(crate) async fn run(
+        self: Arc<Self>,
+        n_requests: impl Fn() -> u64,
+    ) -> Result<(), CollectError> {
+        let mut terminal = enter_terminal()?;
+        let mut result = Ok(());
+        while !self.finished.load(Ordering::Relaxed) {
+            self.sample_requests(n_requests());
+            if let Err(e) = terminal.draw(|frame| self.draw(frame)) {
+                result = Err(tui_error(e));
+                break
+            }
+            tokio::time::sleep(REFRESH_INTERVAL).await;
+        }
+        leave_terminal(&mut terminal)?;
+        result
+    }
+
+    fn sample_requests(&self, n_requests: u64) {
+        let mut state = self.state();
+        let now = Instant::now();
+        state.request_samples.push_back((now, n_requests));
+        while let Some((t, _)) = state.request_samples.front() {
+            match now.duration_since(*t) > THROUGHPUT_WINDOW {
+                true => state.request_samples.pop_front(),
+                false => break,
+            };
+        }
+    }
+
+    fn draw(&self, frame: &mut Frame<'_, CrosstermBackend<Stdout>>) {
+        let state = self.state();
+        let n_datatypes = state.datatypes.len() as u16;
+        let areas = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(3),
+                Constraint::Length(4),
+                Constraint::Length(n_datatypes + 2),
+                Constraint::Min(3),
+                Constraint::Length(MAX_RECENT_ERRORS as u16 + 2),
+            ])
+            .split(frame.size());
+
+        // overall progress
+        let (total, completed) = state
+            .datatypes
+            .values()
+            .fold((0, 0), |(total, completed), p| (total + p.total, completed + p.completed));
+        let gauge = Gauge::default()
+            .block(titled_block(format!("cryo  {}", state.title)))
+            .gauge_style(Style::default().fg(GREEN))
+            .ratio(ratio(completed, total))
+            .label(format!(
+                "{} / {} chunks",
+                completed.separate_with_commas(),
+                total.separate_with_commas()
+            ));
+        frame.render_widget(gauge, areas[0]);
+
+        // stats
+        let elapsed = state.t_start.map(|t| t.elapsed()).unwrap_or_default();
+        let eta = match completed {
+            0 => "-".to_string(),
+            _ => {
+                let remaining = total.saturating_sub(completed) as f64;
+                format_duration(elapsed.mul_f64(remaining / completed as f64))
+            }
+        };
+        let (n_requests, requests_per_second) =
+            match (state.request_samples.front(), state.request_samples.back()) {
+                (Some((t0, n0)), Some((t1, n1))) if t1 > t0 => {
+                    (*n1, (n1 - n0) as f64 / t1.duration_since(*t0).as_secs_f64())
+                }
+                (_, Some((_, n1))) => (*n1, 0.0),
+                _ => (0, 0.0),
+            };
+        let stats = vec![
+            Line::from(vec![
+                stat("elapsed", format_duration(elapsed)),
+                stat("eta", eta),
+                stat("running", state.running.len().to_string()),
+            ]),
+            Line::from(vec![
+                stat("requests", n_requests.separate_with_commas()),
+                stat("requests/s", format!("{:.1}", requests_per_second)),
+                stat("errored", state.failed.len().to_string()),
+                stat("retried", state.n_retried.to_string()),
+            ]),
+        ];
+        frame.render_widget(Paragraph::new(stats).block(titled_block("stats")), areas[1]);
+
+        // per-datatype progress
+        let datatypes_area = titled_block("datatypes").inner(areas[2]);
+        frame.render_widget(titled_block("datatypes"), areas[2]);
+        for (i, (name, progress)) in state.datatypes.iter().enumerate() {
+            let area = Rect { y: datatypes_area.y + i as u16, height: 1, ..datatypes_area };
+            let gauge = Gauge::default()
+                .gauge_style(Style::default().fg(GREEN))
+                .ratio(ratio(progress.completed, progress.total))
+                .label(format!("{}  {} / {}", name, progress.completed, progress.total));
+            frame.render_widget(gauge, area);
+        }
+
+        // running chunks
+        let running: Vec<ListItem<'_>> = state
+            .running
+            .iter()
+            .map(|(label, t)| {
+                ListItem::new(Line::from(vec![
+                    Span::styled(format!("{:>8}  ", format_duration(t.elapsed())), grey()),
+                    Span::raw(label.clone()),
+                ]))
+            })
+            .collect();
+        frame.render_widget(List::new(running).block(titled_block("running chunks")), areas[3]);
+
+        // recent errors
+        let errors: Vec<ListItem<'_>> = state
+            .recent_errors
+            .iter()
+            .rev()
+            .map(|e| ListItem::new(Span::styled(e.clone(), Style::default().fg(RED))))
+            .collect();
+        frame.render_widget(List::new(errors).block(titled_block("recent errors")), areas[4]);
+    }
+}
+
+fn enter_terminal() -> Result<TuiTerminal, CollectError> {
+    let mut stdout = std::io::stdout();
+    crossterm::execute!(stdout, EnterAlternateScreen, cursor::Hide).map_err(tui_error)?;
+    Terminal::new(CrosstermBackend::new(stdout)).map_err(tui_error)
+}
+
+fn leave_terminal(terminal: &mut TuiTerminal) -> Result<(), CollectError> {
+    crossterm::execute!(terminal.backend_mut(), LeaveAlternateScreen, cursor::Show)
+        .map_err(tui_error)
+}
+
+fn titled_block<'a, T: Into<String>>(title: T) -> Block<'a> {
+    Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(GREEN))
+        .title(Span::styled(title.into(), Style::default().add_modifier(Modifier::BOLD)))
+}
+
+fn stat(key: &str, value: String) -> Span<'static> {
+    Span::styled(format!("{}: {}    ", key, value), grey())
+}
+
+fn grey() -> Style {
+    Style::default().fg(GREY)
+}
+
+fn ratio(completed: u64, total: u64) -> f64 {
+    match total {
+        0 => 0.0,
+        _ => (completed as f64 / total as f64).min(1.0),
+    }
+}
+
+fn format_duration(duration: Duration) -> String {
+    let seconds = duration.as_secs();
+    match seconds {
+        0..=59 => format!("{}s", seconds),
+        60..=3599 => format!("{}m{:02}s", seconds / 60, seconds % 60),
+        _ => format!("{}h{:02}m", seconds / 3600, (seconds % 3600) / 60),
+    }
+}
+
+fn tui_error(e: std::io::Error) -> CollectError {
+    CollectError::CollectError(format!("error rendering tui: {}", e))
+}
@@ -0,0 +1,309 @@
+/// enrichment of contract addresses with verified sources from Sourcify or Etherscan
+use std::{
+    collections::{BTreeSet, HashMap},
+    str::FromStr,
+    sync::Mutex,
+    time::Duration,
+};
+
+use polars::prelude::*;
+
+use crate::{CollectError, Datatype, RateLimiter, Source};
+
+/// default url of the Sourcify server
+pub const SOURCIFY_URL: &str = "https://sourcify.dev/server";
+
+/// default url of the Etherscan api, which serves every chain of Etherscan through `chainid`
+pub const ETHERSCAN_URL: &str = "https://api.etherscan.io/v2/api";
+
+/// number of attempts of a request that is rate limited
+const MAX_ATTEMPTS: u32 = 4;
+
+/// api queried for verified sources
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum VerificationProvider {
+    /// Sourcify, which needs no api key
+    Sourcify,
+    /// Etherscan, which needs an api key
+    Etherscan,
+}
+
+impl FromStr for VerificationProvider {
+    type Err = CollectError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "sourcify" => Ok(VerificationProvider::Sourcify),
+            "etherscan" => Ok(VerificationProvider::Etherscan),
+            _ => Err(CollectError::CollectError(format!(
+                "unknown source verification provider: {}, use sourcify or etherscan",
+                s
+            ))),
+        }
+    }
+}
+
+/// verified source of a contract
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct VerifiedSource {
+    /// name of contract
+    pub contract_name: Option<String>,
+    /// compiler version the source was verified with
+    pub compiler: Option<String>,
+    /// abi of contract, as json
+    pub abi: Option<String>,
+}
+
+/// client of a source verification api, caching the verified source of each address
+pub struct SourceVerifier {
+    /// api queried
+    pub provider: VerificationProvider,
+    /// base url of api
+    pub url: String,
+    /// api key, required by Etherscan
+    pub api_key: Option<String>,
+    rate_limiter: Option<RateLimiter>,
+    verified: Mutex<HashMap<Vec<u8>, Option<VerifiedSource>>>,
+}
+
+impl SourceVerifier {
+    /// create verifier of provider, using the default url of provider if url is None
+    pub fn new(
+        provider: VerificationProvider,
+        url: Option<String>,
+        api_key: Option<String>,
+        requests_per_second: Option<u32>,
+    ) -> Result<SourceVerifier, CollectError> {
+        if provider == VerificationProvider::Etherscan && api_key.is_none() {
+            return Err(CollectError::CollectError(
+                "etherscan source verification requires an api key".to_string(),
+            ))
+        }
+        let url = url.unwrap_or_else(|| match provider {
+            VerificationProvider::Sourcify => SOURCIFY_URL.to_string(),
+            VerificationProvider::Etherscan => ETHERSCAN_URL.to_string(),
+        });
+        let rate_limiter = requests_per_second
+            .and_then(std::num::NonZeroU32::new)
+            .map(|rate| RateLimiter::direct(governor::Quota::per_second(rate)));
+        Ok(SourceVerifier { provider, url, api_key, rate_limiter, verified: Default::default() })
+    }
+
+    /// verified source of contract, None if it is not verified
+    pub async fn verify(
+        &self,
+        http_client: &reqwest::Client,
+        chain_id: u64,
+        address: &[u8],
+    ) -> Result<Option<VerifiedSource>, CollectError> {
+        if let Some(verified) = self.cached().get(address) {
+            return Ok(verified.clone())
+        }
+        let address_hex = prefix_hex::encode(address.to_vec());
+        let request = match self.provider {
+            VerificationProvider::Sourcify => http_client.get(format!(
+                "{}/v2/contract/{}/{}?fields=abi,compilation",
+                self.url.trim_end_matches('/'),
+                chain_id,
+                address_hex
+            )),
+            VerificationProvider::Etherscan => http_client.get(&self.url).query(&[
+                ("chainid", chain_id.to_string()),
+                ("module", "contract".to_string()),
+                ("action", "getsourcecode".to_string()),
+                ("address", address_hex),
+                ("apikey", self.api_key.clone().unwrap_or_default()),
+            ]),
+        };
+        let verified = self.send(request).await?;
+        self.cached().insert(address.to_vec(), verified.clone());
+        Ok(verified)
+    }
+
+    /// send request, retrying with backoff while it is rate limited
+    async fn send(
+        &self,
+        request: reqwest::RequestBuilder,
+    ) -> Result<Option<VerifiedSource>, CollectError> {
+        let request_error =
+            |e: String| CollectError::CollectError(format!("source verification failed: {}", e));
+        for attempt in 1..=MAX_ATTEMPTS {
+            if let Some(limiter) = &self.rate_limiter {
+                limiter.until_ready().await;
+            }
+            let request = request.try_clone().ok_or(request_error("invalid request".into()))?;
+            let response = request.send().await.map_err(|e| request_error(e.to_string()))?;
+            let throttled = response.status() == reqwest::StatusCode::TOO_MANY_REQUESTS;
+            let verified = match (self.provider, throttled) {
+                (_, true) => None,
+                (VerificationProvider::Sourcify, false) => {
+                    if response.status() == reqwest::StatusCode::NOT_FOUND {
+                        return Ok(None)
+                    }
+                    let response = response.error_for_status().map_err(|e| e.to_string());
+                    let body = response.map_err(request_error)?.json().await;
+                    Some(parse_sourcify(body.map_err(|e| request_error(e.to_string()))?))
+                }
+                (VerificationProvider::Etherscan, false) => {
+                    let response = response.error_for_status().map_err(|e| e.to_string());
+                    let body = response.map_err(request_error)?.json().await;
+                    parse_etherscan(body.map_err(|e| request_error(e.to_string()))?)
+                        .map_err(request_error)?
+                }
+            };
+            match verified {
+                Some(verified) => return Ok(verified),
+                None if attempt < MAX_ATTEMPTS => {
+                    tokio::time::sleep(Duration::from_secs(1 << (attempt - 1))).await
+                }
+                None => {}
+            }
+        }
+        Err(request_error("rate limited".to_string()))
+    }
+
+    fn cached(&self) -> std::sync::MutexGuard<'_, HashMap<Vec<u8>, Option<VerifiedSource>>> {
+        self.verified.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// verified source of a Sourcify v2 contract response
+fn parse_sourcify(body: serde_json::Value) -> Option<VerifiedSource> {
+    if body.get("match").is_none_or(|m| m.is_null()) {
+        return None
+    }
+    let compilation = body.get("compilation");
+    let field = |name: &str| {
+        compilation.and_then(|c| c.get(name)).and_then(|v| v.as_str()).map(str::to_string)
+    };
+    Some(VerifiedSource {
+        contract_name: field("name"),
+        compiler: field("compilerVersion"),
+        abi: body.get("abi").filter(|abi| !abi.is_null()).map(|abi| abi.to_string()),
+    })
+}
+
+/// verified source of an Etherscan getsourcecode response, or None if rate limited
+fn parse_etherscan(body: serde_json::Value) -> Result<Option<Option<VerifiedSource>>, String> {
+    let result = body.get("result");
+    if body.get("status").and_then(|s| s.as_str()) != Some("1") {
+        let message = result.and_then(|r| r.as_str()).unwrap_or_default();
+        return match message.to_lowercase().contains("rate limit") {
+            true => Ok(None),
+            false => Err(message.to_string()),
+        }
+    }
+    let contract = result.and_then(|r| r.as_array()).and_then(|r| r.first());
+    let field = |name: &str| {
+        contract
+            .and_then(|c| c.get(name))
+            .and_then(|v| v.as_str())
+            .filter(|v| !v.is_empty())
+            .map(str::to_string)
+    };
+    let abi = field("ABI").filter(|abi| abi.starts_with('['));
+    match (field("ContractName"), abi) {
+        (Some(contract_name), abi) => Ok(Some(Some(VerifiedSource {
+            contract_name: Some(contract_name),
+            compiler: field("CompilerVersion"),
+            abi,
+        }))),
+        (None, _) => Ok(Some(None)),
+    }
+}
+
+/// add contract_name, is_verified, verified_compiler, and abi columns to the dataframes of
+/// codes and contracts, from the verified sources of their addresses
+pub(crate) async fn attach_verified_sources(
+    source: &Source,
+    dfs: HashMap<Datatype, DataFrame>,
+) -> Result<HashMap<Datatype, DataFrame>, CollectError> {
+    let verifier = match &source.source_verifier {
+        Some(verifier) => verifier,
+        None => return Ok(dfs),
+    };
+    let mut output = HashMap::new();
+    for (datatype, mut df) in dfs.into_iter() {
+        let address_column = match datatype {
+            Datatype::Codes => "address",
+            Datatype::Contracts => "contract_address",
+            _ => {
+                output.insert(datatype, df);
+                continue
+            }
+        };
+        let addresses: Vec<Option<Vec<u8>>> = match df.column(address_column) {
+            Ok(column) => column.binary()?.into_iter().map(|a| a.map(|a| a.to_vec())).collect(),
+            Err(_) => {
+                output.insert(datatype, df);
+                continue
+            }
+        };
+        let unique: BTreeSet<&Vec<u8>> = addresses.iter().flatten().collect();
+        let requests = unique.into_iter().map(|address| async move {
+            let verified = verifier.verify(&source.http_client, source.chain_id, address).await;
+            Ok::<_, CollectError>((address.clone(), verified?))
+        });
+        let verified: HashMap<Vec<u8>, Option<VerifiedSource>> =
+            futures::future::try_join_all(requests).await?.into_iter().collect();
+        let sources: Vec<Option<&VerifiedSource>> = addresses
+            .iter()
+            .map(|address| address.as_ref().and_then(|a| verified.get(a)).and_then(|v| v.as_ref()))
+            .collect();
+        let string_column = |name: &str, f: fn(&VerifiedSource) -> Option<String>| {
+            Series::new(name, sources.iter().map(|s| s.and_then(f)).collect::<Vec<_>>())
+        };
+        let is_verified: Vec<Option<bool>> = addresses
+            .iter()
+            .map(|address| {
+                address.as_ref().and_then(|a| verified.get(a)).map(|verified| verified.is_some())
+            })
+            .collect();
+        let columns = [
+            string_column("contract_name", |s| s.contract_name.clone()),
+            Series::new("is_verified", is_verified),
+            string_column("verified_compiler", |s| s.compiler.clone()),
+            string_column("abi", |s| s.abi.clone()),
+        ];
+        let mut index = df.get_column_names().iter().position(|c| *c == "chain_id");
+        for series in columns.into_iter() {
+            if df.column(series.name()).is_ok() {
+                continue
+            }
+            df.insert_at_idx(index.unwrap_or(df.width()), series)?;
+            index = index.map(|index| index + 1);
+        }
+        output.insert(datatype, df);
+    }
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_verification_responses() {
+        let sourcify = serde_json::json!({
+            "match": "exact_match",
+            "compilation": {"name": "WETH9", "compilerVersion": "0.4.19+commit.c4cbbb05"},
+            "abi": [{"type": "fallback"}],
+        });
+        let verified = parse_sourcify(sourcify).unwrap();
+        assert_eq!(verified.contract_name, Some("WETH9".to_string()));
+        assert_eq!(verified.abi, Some(r#"[{"type":"fallback"}]"#.to_string()));
+        assert_eq!(parse_sourcify(serde_json::json!({"match": null})), None);
+
+        let etherscan = |name: &str, abi: &str| {
+            serde_json::json!({"status": "1", "message": "OK", "result": [
+                {"ContractName": name, "ABI": abi, "CompilerVersion": "v0.8.19+commit.7dd6d404"}
+            ]})
+        };
+        let verified = parse_etherscan(etherscan("Token", "[]")).unwrap().unwrap().unwrap();
+        assert_eq!(verified.compiler, Some("v0.8.19+commit.7dd6d404".to_string()));
+        let unverified = etherscan("", "Contract source code not verified");
+        assert_eq!(parse_etherscan(unverified), Ok(Some(None)));
+        let throttled = serde_json::json!({"status": "0", "message": "NOTOK", "result": "Max rate limit reached"});
+        assert_eq!(parse_etherscan(throttled), Ok(None));
+    }
+}
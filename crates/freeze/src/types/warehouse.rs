@@ -0,0 +1,153 @@
+/// sql queries across the output files of a directory
+use std::{collections::HashMap, path::Path};
+
+use polars::prelude::*;
+
+use crate::{
+    dataframes, file_datatype, list_output_files, read_output_file, CollectError,
+    ExistingFilePolicy, FileFormat, FileOutput, TextCompression,
+};
+
+/// run sql query over the datasets of output directory
+///
+/// each dataset whose name appears in the query is registered as a table of all of its files,
+/// including files in subdirectories. the values of `key=value` directories between dir and a
+/// file, such as those written by `cryo merge --hive-partition`, are restored as columns
+pub fn query_output_dir(dir: &Path, query: &str) -> Result<DataFrame, CollectError> {
+    let mut context = polars::sql::SQLContext::new();
+    let tables = scan_output_tables(dir, query)?;
+    if tables.is_empty() {
+        return Err(CollectError::CollectError(format!(
+            "query does not reference any dataset with files in {}",
+            dir.display()
+        )))
+    }
+    for (name, table) in tables.into_iter() {
+        context.register(&name, table);
+    }
+    context
+        .execute(query)
+        .and_then(|lf| lf.collect())
+        .map_err(|e| CollectError::CollectError(format!("sql query failed: {}", e)))
+}
+
+/// lazy tables of the datasets of output directory that are referenced by query
+fn scan_output_tables(dir: &Path, query: &str) -> Result<HashMap<String, LazyFrame>, CollectError> {
+    let words: Vec<&str> =
+        query.split(|c: char| !c.is_alphanumeric() && c != '_').filter(|w| !w.is_empty()).collect();
+    let mut files: HashMap<String, Vec<(LazyFrame, HivePartitions)>> = HashMap::new();
+    for path in list_output_files(dir)?.into_iter() {
+        let name = match file_datatype(&path) {
+            Some(datatype) if words.contains(&datatype.name().as_str()) => datatype.name(),
+            _ => continue,
+        };
+        let table = match path.extension().and_then(|e| e.to_str()) {
+            Some("parquet") => LazyFrame::scan_parquet(&path, Default::default())?,
+            _ => read_output_file(&path, None)?.lazy(),
+        };
+        files.entry(name).or_default().push((table, hive_partitions(dir, &path)));
+    }
+    let mut tables = HashMap::new();
+    for (name, files) in files.into_iter() {
+        // files outside of a partition have null values for its column
+        let mut keys: Vec<String> = Vec::new();
+        for (key, _) in files.iter().flat_map(|(_, partitions)| partitions.iter()) {
+            if !keys.contains(key) {
+                keys.push(key.clone())
+            }
+        }
+        let files: Vec<LazyFrame> = files
+            .into_iter()
+            .map(|(table, partitions)| {
+                let columns: Vec<Expr> =
+                    keys.iter().map(|key| partition_column(&partitions, key)).collect();
+                table.with_columns(columns)
+            })
+            .collect();
+        let table =
+            concat(files, UnionArgs { rechunk: false, ..Default::default() }).map_err(|e| {
+                CollectError::CollectError(format!("files of {} cannot be combined: {}", name, e))
+            })?;
+        tables.insert(name, table);
+    }
+    Ok(tables)
+}
+
+type HivePartitions = Vec<(String, Option<String>)>;
+
+/// literal column of the value of partition key, null if file is not in such a partition
+fn partition_column(partitions: &HivePartitions, key: &str) -> Expr {
+    match partitions.iter().find(|(k, _)| k == key).and_then(|(_, value)| value.clone()) {
+        Some(value) => lit(value).alias(key),
+        None => lit(NULL).cast(DataType::Utf8).alias(key),
+    }
+}
+
+/// `key=value` directories between dir and path
+fn hive_partitions(dir: &Path, path: &Path) -> HivePartitions {
+    let relative = path.strip_prefix(dir).unwrap_or(path);
+    let directories = relative.parent().map(|p| p.components().collect()).unwrap_or(vec![]);
+    directories
+        .into_iter()
+        .filter_map(|component| {
+            let (key, value) = component.as_os_str().to_str()?.split_once('=')?;
+            let value = (value != crate::merges::HIVE_NULL_PARTITION).then(|| value.to_string());
+            Some((key.to_string(), value))
+        })
+        .collect()
+}
+
+/// write dataframe to parquet, csv, or json file, chosen by the extension of path
+pub fn write_dataframe(df: &mut DataFrame, path: &Path) -> Result<(), CollectError> {
+    let format = match path.extension().and_then(|e| e.to_str()) {
+        Some("parquet") => FileFormat::Parquet,
+        Some("csv") => FileFormat::Csv,
+        Some("json") => FileFormat::Json,
+        _ => {
+            return Err(CollectError::CollectError(
+                "output file must end in .parquet, .csv, or .json".to_string(),
+            ))
+        }
+    };
+    let sink = FileOutput {
+        output_dir: path.parent().unwrap_or(Path::new(".")).to_path_buf(),
+        prefix: String::new(),
+        suffix: None,
+        existing_files: ExistingFilePolicy::Overwrite,
+        allow_schema_change: false,
+        format,
+        row_group_size: None,
+        global_sort: false,
+        dedup_keys: HashMap::new(),
+        parquet_statistics: true,
+        parquet_compression: ParquetCompression::Lz4Raw,
+        text_compression: TextCompression::Uncompressed,
+        hex_prefix: true,
+        clickhouse: None,
+        manifest: false,
+        pin_block_hash: false,
+        skip_empty_files: false,
+        max_concurrent_writes: None,
+        remote: None,
+    };
+    dataframes::df_to_file(df, path, &sink)
+        .map_err(|_| CollectError::CollectError("error writing file".to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hive_partitions() {
+        let dir = Path::new("/data");
+        let path = Path::new(
+            "/data/chain=1/block_date=__HIVE_DEFAULT_PARTITION__/ethereum__logs__0_to_9.parquet",
+        );
+        assert_eq!(
+            hive_partitions(dir, path),
+            vec![("chain".to_string(), Some("1".to_string())), ("block_date".to_string(), None)]
+        );
+        assert!(hive_partitions(dir, Path::new("/data/ethereum__logs__0_to_9.parquet")).is_empty());
+    }
+}
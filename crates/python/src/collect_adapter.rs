@@ -12,36 +12,84 @@ use cryo_freeze::collect;
         *,
         command = None,
         txs = None,
+        beacon_slots = None,
         align = false,
         reorg_buffer = 0,
+        poll_interval = 2.0,
+        time_bucket = 3600,
+        recheck_blocks = 128,
+        append = false,
         include_columns = None,
         exclude_columns = None,
         columns = None,
         u256_types = None,
         hex = false,
+        no_hex_prefix = false,
+        checksum_addresses = None,
         sort = None,
+        derive = None,
+        filter = None,
+        transform = None,
+        sql = None,
         rpc = None,
+        rpc_header = None,
+        rpc_timeout = None,
+        rpc_proxy = None,
+        rpc_ca_cert = None,
+        rpc_insecure = false,
+        network = None,
         network_name = None,
+        reth_db = None,
+        beacon_url = None,
         requests_per_second = None,
         max_concurrent_requests = None,
+        autotune = false,
         max_concurrent_chunks = None,
+        dataset_option = None,
+        max_concurrent_writes = None,
+        multicall = None,
+        rpc_batch_size = None,
+        get_proof = false,
+        validate_continuity = false,
+        bloom_prefilter = false,
+        include_timestamps = false,
+        trace_cache_blocks = None,
+        no_request_dedup = false,
+        config = None,
+        cache_dir = None,
+        record_fixtures = None,
+        replay_fixtures = None,
         max_retries = 10,
         initial_backoff = 500,
+        chunk_retries = 0,
         dry = false,
-        chunk_size = 1000,
+        chunk_size = None,
         n_chunks = None,
         partition_by = None,
+        partition = None,
         output_dir = ".".to_string(),
         file_suffix = None,
         overwrite = false,
+        no_clobber = None,
+        allow_schema_change = false,
+        manifest = false,
+        pin_block_hash = false,
+        skip_empty_files = false,
         csv = false,
         json = false,
         row_group_size = None,
         n_row_groups = None,
+        global_sort = false,
+        dedup_keys = None,
         no_stats = false,
+        merge_size = "512MB".to_string(),
+        hive_partition = None,
+        query_output = None,
         compression = vec!["lz4".to_string()],
         report_dir = None,
         no_report = false,
+        clickhouse = None,
+        flight = None,
         address = None,
         to_address = None,
         from_address = None,
@@ -49,14 +97,42 @@ use cryo_freeze::collect;
         function = None,
         inputs = None,
         slot = None,
+        excluded_address = None,
+        token_id = None,
+        transfers_dir = None,
+        pool_event = None,
+        dex = None,
+        pools_file = None,
+        marketplace = None,
         contract = None,
         topic0 = None,
         topic1 = None,
         topic2 = None,
         topic3 = None,
         inner_request_size = 1,
+        log_split_error = None,
         no_verbose = false,
+        no_color = false,
+        log_file = None,
+        otlp_endpoint = None,
+        tui = false,
         event_signature = None,
+        adjust_decimals = false,
+        trace_filter = None,
+        vm_trace_memory = false,
+        vm_trace_stack = false,
+        vm_trace_storage = false,
+        function_signatures = false,
+        signature_file = None,
+        function_abi = None,
+        error_abi = None,
+        js_tracer = None,
+        js_tracer_column = None,
+        relay_url = None,
+        verify_sources = None,
+        verify_api_key = None,
+        verify_url = None,
+        verify_requests_per_second = None,
     )
 )]
 #[allow(clippy::too_many_arguments)]
@@ -66,36 +142,84 @@ pub fn _collect(
     blocks: Option<Vec<String>>,
     command: Option<String>,
     txs: Option<Vec<String>>,
+    beacon_slots: Option<Vec<String>>,
     align: bool,
     reorg_buffer: u64,
+    poll_interval: f64,
+    time_bucket: u64,
+    recheck_blocks: u64,
+    append: bool,
     include_columns: Option<Vec<String>>,
     exclude_columns: Option<Vec<String>>,
     columns: Option<Vec<String>>,
     u256_types: Option<Vec<String>>,
     hex: bool,
+    no_hex_prefix: bool,
+    checksum_addresses: Option<Vec<String>>,
     sort: Option<Vec<String>>,
+    derive: Option<Vec<String>>,
+    filter: Option<Vec<String>>,
+    transform: Option<Vec<String>>,
+    sql: Option<String>,
     rpc: Option<String>,
+    rpc_header: Option<Vec<String>>,
+    rpc_timeout: Option<u64>,
+    rpc_proxy: Option<String>,
+    rpc_ca_cert: Option<String>,
+    rpc_insecure: bool,
+    network: Option<String>,
     network_name: Option<String>,
+    reth_db: Option<String>,
+    beacon_url: Option<String>,
     requests_per_second: Option<u32>,
     max_concurrent_requests: Option<u64>,
+    autotune: bool,
     max_concurrent_chunks: Option<u64>,
+    dataset_option: Option<Vec<String>>,
+    max_concurrent_writes: Option<u64>,
+    multicall: Option<usize>,
+    rpc_batch_size: Option<usize>,
+    get_proof: bool,
+    validate_continuity: bool,
+    bloom_prefilter: bool,
+    include_timestamps: bool,
+    trace_cache_blocks: Option<usize>,
+    no_request_dedup: bool,
+    config: Option<String>,
+    cache_dir: Option<String>,
+    record_fixtures: Option<String>,
+    replay_fixtures: Option<String>,
     max_retries: u32,
     initial_backoff: u64,
+    chunk_retries: u64,
     dry: bool,
-    chunk_size: u64,
+    chunk_size: Option<u64>,
     n_chunks: Option<u64>,
     partition_by: Option<Vec<String>>,
+    partition: Option<Vec<String>>,
     output_dir: String,
     file_suffix: Option<String>,
     overwrite: bool,
+    no_clobber: Option<String>,
+    allow_schema_change: bool,
+    manifest: bool,
+    pin_block_hash: bool,
+    skip_empty_files: bool,
     csv: bool,
     json: bool,
     row_group_size: Option<usize>,
     n_row_groups: Option<usize>,
+    global_sort: bool,
+    dedup_keys: Option<Vec<String>>,
     no_stats: bool,
+    merge_size: String,
+    hive_partition: Option<String>,
+    query_output: Option<String>,
     compression: Vec<String>,
     report_dir: Option<String>,
     no_report: bool,
+    clickhouse: Option<String>,
+    flight: Option<String>,
     address: Option<Vec<String>>,
     to_address: Option<Vec<String>>,
     from_address: Option<Vec<String>>,
@@ -103,14 +227,42 @@ pub fn _collect(
     function: Option<Vec<String>>,
     inputs: Option<Vec<String>>,
     slot: Option<Vec<String>>,
+    excluded_address: Option<Vec<String>>,
+    token_id: Option<Vec<String>>,
+    transfers_dir: Option<String>,
+    pool_event: Option<Vec<String>>,
+    dex: Option<Vec<String>>,
+    pools_file: Option<Vec<String>>,
+    marketplace: Option<Vec<String>>,
     contract: Option<Vec<String>>,
     topic0: Option<Vec<String>>,
     topic1: Option<Vec<String>>,
     topic2: Option<Vec<String>>,
     topic3: Option<Vec<String>>,
     inner_request_size: u64,
+    log_split_error: Option<Vec<String>>,
     no_verbose: bool,
+    no_color: bool,
+    log_file: Option<String>,
+    otlp_endpoint: Option<String>,
+    tui: bool,
     event_signature: Option<String>,
+    adjust_decimals: bool,
+    trace_filter: Option<String>,
+    vm_trace_memory: bool,
+    vm_trace_stack: bool,
+    vm_trace_storage: bool,
+    function_signatures: bool,
+    signature_file: Option<String>,
+    function_abi: Option<String>,
+    error_abi: Option<String>,
+    js_tracer: Option<String>,
+    js_tracer_column: Option<Vec<String>>,
+    relay_url: Option<Vec<String>>,
+    verify_sources: Option<String>,
+    verify_api_key: Option<String>,
+    verify_url: Option<String>,
+    verify_requests_per_second: Option<u32>,
 ) -> PyResult<&PyAny> {
     if let Some(command) = command {
         pyo3_asyncio::tokio::future_into_py(py, async move {
@@ -124,36 +276,84 @@ pub fn _collect(
             datatype: vec![datatype],
             blocks,
             txs,
+            beacon_slots,
             align,
             reorg_buffer,
+            poll_interval,
+            time_bucket,
+            recheck_blocks,
+            append,
             include_columns,
             exclude_columns,
             columns,
             u256_types,
             hex,
+            no_hex_prefix,
+            checksum_addresses,
             sort,
-            rpc,
+            derive,
+            filter,
+            transform,
+            sql,
+            rpc: rpc.map(|rpc| vec![rpc]),
+            rpc_header,
+            rpc_timeout,
+            rpc_proxy,
+            rpc_ca_cert,
+            rpc_insecure,
+            network,
             network_name,
+            reth_db,
+            beacon_url,
             requests_per_second,
             max_concurrent_requests,
+            autotune,
             max_concurrent_chunks,
+            dataset_option,
+            max_concurrent_writes,
+            multicall,
+            rpc_batch_size,
+            get_proof,
+            validate_continuity,
+            bloom_prefilter,
+            include_timestamps,
+            trace_cache_blocks,
+            no_request_dedup,
+            config,
+            cache_dir,
+            record_fixtures,
+            replay_fixtures,
             max_retries,
             initial_backoff,
+            chunk_retries,
             dry,
             chunk_size,
             n_chunks,
             partition_by,
+            partition,
             output_dir,
             file_suffix,
             overwrite,
+            no_clobber,
+            allow_schema_change,
+            manifest,
+            pin_block_hash,
+            skip_empty_files,
             csv,
             json,
             row_group_size,
             n_row_groups,
+            global_sort,
+            dedup_keys,
             no_stats,
+            merge_size,
+            hive_partition,
+            query_output,
             compression,
             report_dir: report_dir.map(std::path::PathBuf::from),
             no_report,
+            clickhouse,
+            flight,
             address,
             to_address,
             from_address,
@@ -161,14 +361,42 @@ pub fn _collect(
             function,
             inputs,
             slot,
+            excluded_address,
+            token_id,
+            transfers_dir,
+            pool_event,
+            dex,
+            pools_file,
+            marketplace,
             contract,
             topic0,
             topic1,
             topic2,
             topic3,
             inner_request_size,
+            log_split_error,
             no_verbose,
+            no_color,
+            log_file,
+            otlp_endpoint,
+            tui,
             event_signature,
+            adjust_decimals,
+            trace_filter,
+            vm_trace_memory,
+            vm_trace_stack,
+            vm_trace_storage,
+            function_signatures,
+            signature_file,
+            function_abi,
+            error_abi,
+            js_tracer,
+            js_tracer_column,
+            relay_url,
+            verify_sources,
+            verify_api_key,
+            verify_url,
+            verify_requests_per_second,
         };
         pyo3_asyncio::tokio::future_into_py(py, async move {
             match run_collect(args).await {
@@ -0,0 +1,444 @@
+//! cryo_reth reads blocks, transactions, receipts, and state directly from the database of a
+//! local reth node, answering the json-rpc requests of cryo's datasets without a running node
+//!
+//! it is excluded from the cryo workspace, so that reth is only fetched and built when installing
+//! this crate with `cargo install --path ./crates/reth`
+
+#![warn(missing_docs, unreachable_pub)]
+#![deny(unused_must_use, rust_2018_idioms)]
+
+use std::{fmt, path::Path, sync::Arc};
+
+use cryo_freeze::{CollectError, DatabaseBackend, ParseError, TransportError};
+
+use ethers::prelude::{Filter, FilterBlockOption, ValueOrArray};
+use reth_db::{database::Database, open_db_read_only, tables, transaction::DbTx, DatabaseEnv};
+use reth_primitives::{
+    Address, BlockHashOrNumber, ChainSpec, Log, Receipt, TransactionSigned, B256, GOERLI, HOLESKY,
+    MAINNET, SEPOLIA, U256,
+};
+use reth_provider::{
+    AccountReader, BlockHashReader, BlockNumReader, BlockReader, HeaderProvider, ProviderFactory,
+    ReceiptProvider, StateProvider, StateProviderBox, StateProviderFactory, TransactionVariant,
+    TransactionsProvider,
+};
+use reth_rpc_types::BlockTransactionsKind;
+use serde::de::DeserializeOwned;
+use serde_json::{json, Value};
+
+/// register the reth database backend, so that `--reth-db` reads from reth databases
+pub fn register() -> Result<(), ParseError> {
+    cryo_freeze::register_database_backend("reth", |path| Ok(Arc::new(RethDb::open(path)?)))
+}
+
+/// read-only database of a reth node, answering json-rpc requests of blocks, transactions,
+/// receipts, and state without a running node
+///
+/// responses are built from the database in the format of reth's json-rpc api, so that datasets
+/// are collected from the database the same way as from a node
+#[derive(Clone)]
+pub struct RethDb {
+    path: std::path::PathBuf,
+    chain_id: u64,
+    factory: Arc<ProviderFactory<DatabaseEnv>>,
+}
+
+impl fmt::Debug for RethDb {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RethDb").field("path", &self.path).finish()
+    }
+}
+
+#[async_trait::async_trait]
+impl DatabaseBackend for RethDb {
+    /// response of json-rpc request, reading from the database on a blocking thread
+    async fn request(&self, method: &str, params: Value) -> Result<Value, TransportError> {
+        let db = self.clone();
+        let method = method.to_string();
+        tokio::task::spawn_blocking(move || db.respond(&method, &params))
+            .await
+            .map_err(|e| TransportError::Database(e.to_string()))?
+    }
+}
+
+impl RethDb {
+    /// open the mdbx database of a reth node read-only, at path of its datadir or of its `db`
+    /// directory, detecting its chain from the hash of its genesis block
+    pub fn open(path: &Path) -> Result<RethDb, CollectError> {
+        let db_path = match path.join("db").join("mdbx.dat").exists() {
+            true => path.join("db"),
+            false => path.to_path_buf(),
+        };
+        if !db_path.join("mdbx.dat").exists() {
+            return Err(CollectError::CollectError(format!(
+                "no reth database at {}",
+                path.display()
+            )))
+        }
+        let db = open_db_read_only(&db_path, None).map_err(|e| {
+            CollectError::CollectError(format!(
+                "could not open reth database at {}: {}",
+                db_path.display(),
+                e
+            ))
+        })?;
+        let genesis_hash = db
+            .view(|tx| tx.get::<tables::CanonicalHeaders>(0))
+            .map_err(|e| CollectError::CollectError(e.to_string()))?
+            .map_err(|e| CollectError::CollectError(e.to_string()))?
+            .ok_or_else(|| {
+                CollectError::CollectError(format!(
+                    "reth database at {} has no genesis block",
+                    db_path.display()
+                ))
+            })?;
+        let chain_spec = chain_spec(genesis_hash).ok_or_else(|| {
+            CollectError::CollectError(format!(
+                "reth database at {} has genesis block {}, which is not of a supported chain \
+                 (mainnet, goerli, sepolia, or holesky)",
+                db_path.display(),
+                genesis_hash
+            ))
+        })?;
+        let chain_id = chain_spec.chain.id();
+        let factory = Arc::new(ProviderFactory::new(db, chain_spec));
+        Ok(RethDb { path: db_path, chain_id, factory })
+    }
+
+    fn respond(&self, method: &str, params: &Value) -> Result<Value, TransportError> {
+        let factory = &self.factory;
+        match method {
+            "eth_chainId" => Ok(quantity(self.chain_id)),
+            "eth_blockNumber" => Ok(quantity(factory.best_block_number().map_err(db_error)?)),
+            "eth_getBlockByNumber" => {
+                let number = self.block_number(&param(params, 0)?)?;
+                self.block(number, param(params, 1)?)
+            }
+            "eth_getBlockByHash" => {
+                let hash: B256 = param(params, 0)?;
+                match factory.block_number(hash).map_err(db_error)? {
+                    Some(number) => self.block(Some(number), param(params, 1)?),
+                    None => Ok(Value::Null),
+                }
+            }
+            "eth_getTransactionByHash" => self.transaction(param(params, 0)?),
+            "eth_getTransactionReceipt" => self.transaction_receipt(param(params, 0)?),
+            "eth_getBlockReceipts" => match self.block_number(&param(params, 0)?)? {
+                Some(number) => self.block_receipts(number),
+                None => Ok(Value::Null),
+            },
+            "eth_getLogs" => self.logs(&param(params, 0)?),
+            "eth_getBalance" => {
+                let address: Address = param(params, 0)?;
+                let account = self.state(&param(params, 1)?)?.basic_account(address);
+                let balance = account.map_err(db_error)?.map(|a| a.balance).unwrap_or_default();
+                Ok(json!(balance))
+            }
+            "eth_getTransactionCount" => {
+                let address: Address = param(params, 0)?;
+                let account = self.state(&param(params, 1)?)?.basic_account(address);
+                Ok(quantity(account.map_err(db_error)?.map(|a| a.nonce).unwrap_or_default()))
+            }
+            "eth_getCode" => {
+                let address: Address = param(params, 0)?;
+                let code = self.state(&param(params, 1)?)?.account_code(address);
+                let code = code.map_err(db_error)?.map(|code| code.original_bytes());
+                Ok(json!(code.unwrap_or_default()))
+            }
+            "eth_getStorageAt" => {
+                let address: Address = param(params, 0)?;
+                let slot: B256 = param(params, 1)?;
+                let value = self.state(&param(params, 2)?)?.storage(address, slot);
+                Ok(json!(B256::from(value.map_err(db_error)?.unwrap_or_default())))
+            }
+            _ => Err(TransportError::method_not_found(method)),
+        }
+    }
+
+    /// number of block of tag, or None if the block is not in the database
+    fn block_number(&self, tag: &Value) -> Result<Option<u64>, TransportError> {
+        let latest = self.factory.best_block_number().map_err(db_error)?;
+        match parse_block_tag(tag, latest) {
+            Some(number) if number <= latest => Ok(Some(number)),
+            Some(_) => Ok(None),
+            None => Err(TransportError::Database(format!("invalid block tag: {}", tag))),
+        }
+    }
+
+    /// state at the end of block of tag
+    fn state(&self, tag: &Value) -> Result<StateProviderBox, TransportError> {
+        let number = self
+            .block_number(tag)?
+            .ok_or_else(|| TransportError::Database(format!("unknown block: {}", tag)))?;
+        self.factory.history_by_block_number(number).map_err(db_error)
+    }
+
+    fn block(&self, number: Option<u64>, full: bool) -> Result<Value, TransportError> {
+        let Some(number) = number else { return Ok(Value::Null) };
+        let block = self
+            .factory
+            .block_with_senders(BlockHashOrNumber::Number(number), TransactionVariant::WithHash)
+            .map_err(db_error)?;
+        let Some(block) = block else { return Ok(Value::Null) };
+        let total_difficulty =
+            self.factory.header_td_by_number(number).map_err(db_error)?.unwrap_or_default();
+        let kind = match full {
+            true => BlockTransactionsKind::Full,
+            false => BlockTransactionsKind::Hashes,
+        };
+        let block = reth_rpc_types_compat::block::from_block(block, total_difficulty, kind, None)
+            .map_err(|e| TransportError::Database(e.to_string()))?;
+        Ok(serde_json::to_value(block)?)
+    }
+
+    fn transaction(&self, hash: B256) -> Result<Value, TransportError> {
+        let transaction = self.factory.transaction_by_hash_with_meta(hash).map_err(db_error)?;
+        let Some((transaction, meta)) = transaction else { return Ok(Value::Null) };
+        let transaction = transaction.into_ecrecovered().ok_or_else(|| {
+            TransportError::Database(format!("could not recover sender of {}", hash))
+        })?;
+        let transaction = reth_rpc_types_compat::transaction::from_recovered_with_block_context(
+            transaction,
+            meta.block_hash,
+            meta.block_number,
+            meta.base_fee,
+            U256::from(meta.index),
+        );
+        Ok(serde_json::to_value(transaction)?)
+    }
+
+    fn transaction_receipt(&self, hash: B256) -> Result<Value, TransportError> {
+        let transaction = self.factory.transaction_by_hash_with_meta(hash).map_err(db_error)?;
+        let Some((_, meta)) = transaction else { return Ok(Value::Null) };
+        let receipts = self.block_receipts(meta.block_number)?;
+        Ok(receipts.get(meta.index as usize).cloned().unwrap_or(Value::Null))
+    }
+
+    /// receipts of the transactions of block, with the fields of `eth_getBlockReceipts`
+    fn block_receipts(&self, number: u64) -> Result<Value, TransportError> {
+        let Some(block) = self.block_transactions(number)? else { return Ok(Value::Null) };
+        let mut receipts = Vec::new();
+        let mut gas_before = 0;
+        let mut log_index = 0;
+        for (index, (transaction, receipt)) in block.transactions.iter().enumerate() {
+            let sender = transaction.recover_signer().ok_or_else(|| {
+                TransportError::Database(format!(
+                    "could not recover sender of {}",
+                    transaction.hash
+                ))
+            })?;
+            let contract_address = match transaction.to() {
+                Some(_) => None,
+                None => Some(sender.create(transaction.nonce())),
+            };
+            let mut logs = Vec::new();
+            for log in receipt.logs.iter() {
+                logs.push(log_json(log, &block, number, transaction, index, log_index));
+                log_index += 1;
+            }
+            receipts.push(json!({
+                "transactionHash": transaction.hash,
+                "transactionIndex": quantity(index),
+                "blockHash": block.hash,
+                "blockNumber": quantity(number),
+                "from": sender,
+                "to": transaction.to(),
+                "cumulativeGasUsed": quantity(receipt.cumulative_gas_used),
+                "gasUsed": quantity(receipt.cumulative_gas_used - gas_before),
+                "effectiveGasPrice": quantity(transaction.effective_gas_price(block.base_fee)),
+                "contractAddress": contract_address,
+                "logs": logs,
+                "logsBloom": receipt.bloom_slow(),
+                "type": quantity(transaction.tx_type() as u8),
+                "status": quantity(receipt.success as u8),
+            }));
+            gas_before = receipt.cumulative_gas_used;
+        }
+        Ok(Value::Array(receipts))
+    }
+
+    /// logs of the blocks of filter whose address and topics match it
+    fn logs(&self, filter: &Filter) -> Result<Value, TransportError> {
+        let latest = self.factory.best_block_number().map_err(db_error)?;
+        let (from_block, to_block) = match filter.block_option {
+            FilterBlockOption::AtBlockHash(hash) => {
+                let number = self.factory.block_number(B256::from(hash.0)).map_err(db_error)?;
+                match number {
+                    Some(number) => (number, number),
+                    None => return Ok(json!([])),
+                }
+            }
+            FilterBlockOption::Range { from_block, to_block } => {
+                let tag =
+                    |block: Option<ethers::prelude::BlockNumber>| -> Result<u64, TransportError> {
+                        let tag = serde_json::to_value(block.unwrap_or_default())?;
+                        parse_block_tag(&tag, latest).ok_or_else(|| {
+                            TransportError::Database(format!("invalid block: {}", tag))
+                        })
+                    };
+                (tag(from_block)?, tag(to_block)?.min(latest))
+            }
+        };
+        let mut logs = Vec::new();
+        for number in from_block..=to_block {
+            let Some(block) = self.block_transactions(number)? else { continue };
+            let mut log_index = 0;
+            for (index, (transaction, receipt)) in block.transactions.iter().enumerate() {
+                for log in receipt.logs.iter() {
+                    let topics: Vec<&[u8]> = log.topics.iter().map(|t| t.as_slice()).collect();
+                    if filter_matches(filter, log.address.as_slice(), &topics) {
+                        logs.push(log_json(log, &block, number, transaction, index, log_index));
+                    }
+                    log_index += 1;
+                }
+            }
+        }
+        Ok(Value::Array(logs))
+    }
+
+    /// transactions of block paired with their receipts
+    fn block_transactions(&self, number: u64) -> Result<Option<BlockTransactions>, TransportError> {
+        let factory = &self.factory;
+        let block_id = BlockHashOrNumber::Number(number);
+        let (Some(hash), Some(header)) = (
+            factory.block_hash(number).map_err(db_error)?,
+            factory.header_by_number(number).map_err(db_error)?,
+        ) else {
+            return Ok(None)
+        };
+        let transactions = factory.transactions_by_block(block_id).map_err(db_error)?;
+        let receipts = factory.receipts_by_block(block_id).map_err(db_error)?;
+        let (Some(transactions), Some(receipts)) = (transactions, receipts) else {
+            return Ok(None)
+        };
+        if transactions.len() != receipts.len() {
+            return Err(TransportError::Database(format!(
+                "block {} has {} transactions but {} receipts",
+                number,
+                transactions.len(),
+                receipts.len()
+            )))
+        }
+        Ok(Some(BlockTransactions {
+            hash,
+            base_fee: header.base_fee_per_gas,
+            transactions: transactions.into_iter().zip(receipts).collect(),
+        }))
+    }
+}
+
+struct BlockTransactions {
+    hash: B256,
+    base_fee: Option<u64>,
+    transactions: Vec<(TransactionSigned, Receipt)>,
+}
+
+/// log of receipt, with the fields of `eth_getLogs`
+fn log_json(
+    log: &Log,
+    block: &BlockTransactions,
+    number: u64,
+    transaction: &TransactionSigned,
+    index: usize,
+    log_index: usize,
+) -> Value {
+    json!({
+        "address": log.address,
+        "topics": log.topics,
+        "data": log.data,
+        "blockHash": block.hash,
+        "blockNumber": quantity(number),
+        "transactionHash": transaction.hash,
+        "transactionIndex": quantity(index),
+        "logIndex": quantity(log_index),
+        "removed": false,
+    })
+}
+
+/// spec of the chain whose genesis block has genesis_hash
+fn chain_spec(genesis_hash: B256) -> Option<Arc<ChainSpec>> {
+    [MAINNET.clone(), GOERLI.clone(), SEPOLIA.clone(), HOLESKY.clone()]
+        .into_iter()
+        .find(|chain_spec| chain_spec.genesis_hash() == genesis_hash)
+}
+
+/// number of block tag, resolving named tags to the latest block of the database
+fn parse_block_tag(tag: &Value, latest: u64) -> Option<u64> {
+    match tag.as_str()? {
+        "latest" | "pending" | "safe" | "finalized" => Some(latest),
+        "earliest" => Some(0),
+        number => u64::from_str_radix(number.strip_prefix("0x")?, 16).ok(),
+    }
+}
+
+/// whether log of address and topics matches the addresses and topics of filter
+fn filter_matches(filter: &Filter, address: &[u8], topics: &[&[u8]]) -> bool {
+    let address_matches = match &filter.address {
+        None => true,
+        Some(ValueOrArray::Value(filter_address)) => filter_address.as_bytes() == address,
+        Some(ValueOrArray::Array(addresses)) => {
+            addresses.is_empty() || addresses.iter().any(|a| a.as_bytes() == address)
+        }
+    };
+    address_matches &&
+        filter.topics.iter().enumerate().all(|(i, filter_topic)| {
+            let topic = topics.get(i);
+            match filter_topic {
+                None | Some(ValueOrArray::Value(None)) => true,
+                Some(ValueOrArray::Value(Some(value))) => topic == Some(&value.as_bytes()),
+                Some(ValueOrArray::Array(values)) => {
+                    values.iter().any(|value| match value {
+                        None => true,
+                        Some(value) => topic == Some(&value.as_bytes()),
+                    }) || values.is_empty()
+                }
+            }
+        })
+}
+
+fn param<T: DeserializeOwned>(params: &Value, index: usize) -> Result<T, TransportError> {
+    Ok(serde_json::from_value(params.get(index).cloned().unwrap_or(Value::Null))?)
+}
+
+fn quantity(value: impl fmt::LowerHex) -> Value {
+    Value::String(format!("{:#x}", value))
+}
+
+fn db_error(e: impl fmt::Display) -> TransportError {
+    TransportError::Database(e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::prelude::{H160, H256};
+
+    #[test]
+    fn test_parse_block_tag() {
+        assert_eq!(parse_block_tag(&json!("latest"), 100), Some(100));
+        assert_eq!(parse_block_tag(&json!("earliest"), 100), Some(0));
+        assert_eq!(parse_block_tag(&json!("0x1f"), 100), Some(31));
+        assert_eq!(parse_block_tag(&json!("31"), 100), None);
+        assert_eq!(parse_block_tag(&Value::Null, 100), None);
+    }
+
+    #[test]
+    fn test_filter_matches() {
+        let address = H160::from_low_u64_be(1);
+        let topic0 = H256::from_low_u64_be(2);
+        let topic1 = H256::from_low_u64_be(3);
+        let topics = [topic0.as_bytes(), topic1.as_bytes()];
+
+        assert!(filter_matches(&Filter::new(), address.as_bytes(), &topics));
+        let filter = Filter::new().address(address).topic0(topic0);
+        assert!(filter_matches(&filter, address.as_bytes(), &topics));
+        let filter = Filter::new().address(vec![H160::from_low_u64_be(4), address]).topic1(topic1);
+        assert!(filter_matches(&filter, address.as_bytes(), &topics));
+        let filter = Filter::new().address(H160::from_low_u64_be(4));
+        assert!(!filter_matches(&filter, address.as_bytes(), &topics));
+        let filter = Filter::new().topic0(topic1);
+        assert!(!filter_matches(&filter, address.as_bytes(), &topics));
+        let filter = Filter::new().topic2(topic0);
+        assert!(!filter_matches(&filter, address.as_bytes(), &topics));
+    }
+}
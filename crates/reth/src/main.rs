@@ -0,0 +1,18 @@
+use clap_cryo::Parser;
+use cryo_cli::Args;
+use eyre::Result;
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    cryo_reth::register()?;
+    let args = Args::parse();
+    match cryo_cli::run(args).await {
+        Ok(Some(freeze_summary)) if freeze_summary.errored.is_empty() => Ok(()),
+        Ok(Some(_freeze_summary)) => std::process::exit(1),
+        Ok(None) => Ok(()),
+        Err(e) => {
+            println!("{}", e);
+            std::process::exit(1);
+        }
+    }
+}
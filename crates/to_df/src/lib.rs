@@ -13,12 +13,20 @@ pub fn to_df(attrs: TokenStream, input: TokenStream) -> TokenStream {
     let attrs = parse_macro_input!(attrs as syn::AttributeArgs);
     let datatypes: Vec<_> = attrs
         .into_iter()
-        .map(|arg| {
-            if let syn::NestedMeta::Meta(syn::Meta::Path(path)) = arg {
-                path
-            } else {
-                panic!("Expected Meta::Path");
+        .map(|arg| match arg {
+            syn::NestedMeta::Meta(syn::Meta::Path(path)) => {
+                let label = path.segments.iter().last().unwrap().ident.to_string();
+                (quote! { #path }, label)
             }
+            // plugin datatypes, e.g. Datatype::Plugin("my_dataset")
+            syn::NestedMeta::Meta(syn::Meta::List(list)) => match list.nested.first() {
+                Some(syn::NestedMeta::Lit(syn::Lit::Str(lit))) if list.nested.len() == 1 => {
+                    let path = &list.path;
+                    (quote! { #path(#lit) }, lit.value())
+                }
+                _ => panic!("Expected Datatype::Plugin(\"name\")"),
+            },
+            _ => panic!("Expected Meta::Path"),
         })
         .collect();
     if datatypes.is_empty() {
@@ -62,6 +70,7 @@ pub fn to_df(attrs: TokenStream, input: TokenStream) -> TokenStream {
             "Vec < f64 >" => Some(quote! { ColumnType::Float64 }),
             "Vec < String >" => Some(quote! { ColumnType::String }),
             "Vec < Vec < u8 > >" => Some(quote! { ColumnType::Binary }),
+            "Vec < bool >" => Some(quote! { ColumnType::Boolean }),
 
             "Vec < Option < u32 > >" => Some(quote! { ColumnType::UInt32 }),
             "Vec < Option < u64 > >" => Some(quote! { ColumnType::UInt64 }),
@@ -72,21 +81,69 @@ pub fn to_df(attrs: TokenStream, input: TokenStream) -> TokenStream {
             "Vec < Option < f64 > >" => Some(quote! { ColumnType::Float64 }),
             "Vec < Option < String > >" => Some(quote! { ColumnType::String }),
             "Vec < Option < Vec < u8 > > >" => Some(quote! { ColumnType::Binary }),
+            "Vec < Option < bool > >" => Some(quote! { ColumnType::Boolean }),
             _ => None,
             // _ => quote! {ColumnType::Binary},
         }
     }
 
-    let datatype_str =
-        datatypes[0].segments.iter().map(|seg| seg.ident.to_string()).collect::<Vec<_>>();
-    let datatype_str = datatype_str.iter().last().unwrap();
+    // decoded calldata columns are generated from the schema's function decoder
+    let function_cols_processing =
+        match field_names_and_types.iter().any(|(name, _)| name == "function_cols") {
+            true => quote! {
+                if let Some(decoder) = &schema.function_decoder {
+                    cols.extend(decoder.make_series(self.function_cols, &schema.binary_type)?);
+                }
+            },
+            false => quote! {},
+        };
+
+    // decoded event columns are generated from the schema's log decoder, in event input order
+    let event_cols_processing =
+        match field_names_and_types.iter().any(|(name, _)| name == "event_cols") {
+            true => quote! {
+                if let Some(decoder) = &schema.log_decoder {
+                    let mut event_cols = self.event_cols;
+                    for input in decoder.event.inputs.iter() {
+                        let data = event_cols.remove(&input.name).unwrap_or_default();
+                        let mut series = decoder
+                            .make_series(input.name.clone(), data, self.n_rows as usize)
+                            .map_err(CollectError::CollectError)?;
+                        if series.len() != self.n_rows as usize {
+                            return Err(CollectError::CollectError(format!(
+                                "could not decode all logs as {}",
+                                decoder.raw
+                            )))
+                        }
+                        series.rename(&format!("event__{}", input.name));
+                        cols.push(series);
+                    }
+                }
+            },
+            false => quote! {},
+        };
+
+    // javascript tracer columns are extracted from results by the schema's tracer
+    let js_cols_processing = match field_names_and_types.iter().any(|(name, _)| name == "js_cols") {
+        true => quote! {
+            if let Some(tracer) = &schema.js_tracer {
+                cols.extend(tracer.make_series(self.js_cols)?);
+            }
+        },
+        false => quote! {},
+    };
+
+    let datatype_str = &datatypes[0].1;
+    let datatypes: Vec<_> = datatypes.iter().map(|(datatype, _)| datatype).collect();
 
     let mut column_types = Vec::new();
     for (name, ty) in field_names_and_types.iter() {
         if let Some(column_type) = map_type_to_column_type(ty) {
             let field_name_str = format!("{}", quote!(#name));
             column_types.push(quote! { (#field_name_str, #column_type) });
-        } else if name != "n_rows" && name != "event_cols" {
+        } else if !["n_rows", "event_cols", "function_cols", "js_cols"]
+            .contains(&name.to_string().as_str())
+        {
             println!("invalid column type for {name} in table {}", datatype_str);
         }
     }
@@ -118,7 +175,16 @@ pub fn to_df(attrs: TokenStream, input: TokenStream) -> TokenStream {
                     with_series!(cols, "chain_id", self.chain_id, schema);
                 }
 
-                let df = DataFrame::new(cols).map_err(CollectError::PolarsError).sort_by_schema(schema)?;
+                #function_cols_processing
+
+                #event_cols_processing
+
+                #js_cols_processing
+
+                let df = DataFrame::new(cols)
+                    .map_err(CollectError::PolarsError)
+                    .and_then(|df| schema.derive_columns(df))
+                    .sort_by_schema(schema)?;
                 let mut output = HashMap::new();
                 output.insert(datatype, df);
                 Ok(output)